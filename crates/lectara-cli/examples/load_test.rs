@@ -0,0 +1,242 @@
+//! Drives concurrent add/list/get traffic against a running lectara service
+//! and reports latency percentiles per operation.
+//!
+//! ```sh
+//! cargo run --release -p lectara-cli --example load_test -- \
+//!     --service-url http://localhost:3000 --concurrency 32 --requests 10000
+//! ```
+
+use clap::Parser;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Parser)]
+#[command(name = "load_test")]
+#[command(about = "Load-test a running Lectara service")]
+struct Args {
+    /// Base URL for the Lectara service
+    #[arg(long, default_value = "http://localhost:3000")]
+    service_url: String,
+    /// Number of concurrent workers
+    #[arg(short, long, default_value_t = 16)]
+    concurrency: usize,
+    /// Total number of requests across all workers
+    #[arg(short, long, default_value_t = 5000)]
+    requests: usize,
+    /// Relative weight of add requests
+    #[arg(long, default_value_t = 1)]
+    add_weight: usize,
+    /// Relative weight of list requests
+    #[arg(long, default_value_t = 2)]
+    list_weight: usize,
+    /// Relative weight of get-by-id requests
+    #[arg(long, default_value_t = 7)]
+    get_weight: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operation {
+    Add,
+    List,
+    Get,
+}
+
+impl Operation {
+    const ALL: [Operation; 3] = [Operation::Add, Operation::List, Operation::Get];
+
+    fn name(self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::List => "list",
+            Operation::Get => "get",
+        }
+    }
+}
+
+struct Sample {
+    operation: Operation,
+    latency: Duration,
+    success: bool,
+}
+
+#[derive(Deserialize)]
+struct ContentResponse {
+    id: u32,
+}
+
+/// Spreads operations evenly according to their weights
+struct Mix {
+    schedule: Vec<Operation>,
+}
+
+impl Mix {
+    fn new(add: usize, list: usize, get: usize) -> Result<Self, Box<dyn Error>> {
+        let mut schedule = Vec::new();
+        schedule.extend(std::iter::repeat_n(Operation::Add, add));
+        schedule.extend(std::iter::repeat_n(Operation::List, list));
+        schedule.extend(std::iter::repeat_n(Operation::Get, get));
+        if schedule.is_empty() {
+            return Err("at least one operation weight must be non-zero".into());
+        }
+        Ok(Self { schedule })
+    }
+
+    fn pick(&self, n: usize) -> Operation {
+        self.schedule[n % self.schedule.len()]
+    }
+}
+
+async fn run_operation(
+    client: &Client,
+    service_url: &str,
+    operation: Operation,
+    n: usize,
+    run_id: u128,
+    ids: &Mutex<Vec<u32>>,
+) -> Result<bool, reqwest::Error> {
+    let endpoint = format!("{service_url}/api/v1/content");
+
+    match operation {
+        Operation::Add => {
+            let payload = json!({
+                "url": format!("https://example.com/load-test/{run_id}/{n}"),
+                "title": format!("Load test item {n}"),
+            });
+            let response = client.post(&endpoint).json(&payload).send().await?;
+            if !response.status().is_success() {
+                return Ok(false);
+            }
+            let content: ContentResponse = response.json().await?;
+            ids.lock().unwrap().push(content.id);
+            Ok(true)
+        }
+        Operation::List => {
+            let response = client
+                .get(format!("{endpoint}?limit=50&offset={}", (n % 10) * 50))
+                .send()
+                .await?;
+            Ok(response.status().is_success())
+        }
+        Operation::Get => {
+            let id = {
+                let ids = ids.lock().unwrap();
+                ids[n % ids.len()]
+            };
+            let response = client.get(format!("{endpoint}/{id}")).send().await?;
+            Ok(response.status().is_success())
+        }
+    }
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+fn report(samples: &[Sample], elapsed: Duration) {
+    println!(
+        "{} requests in {:.2?} ({:.1} req/s)",
+        samples.len(),
+        elapsed,
+        samples.len() as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "{:<6} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10}",
+        "op", "count", "errors", "p50", "p90", "p99", "max"
+    );
+
+    for operation in Operation::ALL {
+        let mut latencies: Vec<Duration> = samples
+            .iter()
+            .filter(|sample| sample.operation == operation)
+            .map(|sample| sample.latency)
+            .collect();
+        if latencies.is_empty() {
+            continue;
+        }
+        latencies.sort();
+
+        let errors = samples
+            .iter()
+            .filter(|sample| sample.operation == operation && !sample.success)
+            .count();
+
+        println!(
+            "{:<6} {:>8} {:>8} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+            operation.name(),
+            latencies.len(),
+            errors,
+            percentile(&latencies, 50.0),
+            percentile(&latencies, 90.0),
+            percentile(&latencies, 99.0),
+            latencies[latencies.len() - 1],
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let mix = Arc::new(Mix::new(
+        args.add_weight,
+        args.list_weight,
+        args.get_weight,
+    )?);
+    let client = Client::new();
+    let run_id = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+
+    // Get requests need at least one id to target
+    let ids = Arc::new(Mutex::new(Vec::new()));
+    if !run_operation(&client, &args.service_url, Operation::Add, 0, run_id, &ids).await? {
+        return Err("failed to create the warm-up item".into());
+    }
+
+    let counter = Arc::new(AtomicUsize::new(1));
+    let start = Instant::now();
+
+    let workers: Vec<_> = (0..args.concurrency)
+        .map(|_| {
+            let client = client.clone();
+            let service_url = args.service_url.clone();
+            let mix = mix.clone();
+            let counter = counter.clone();
+            let ids = ids.clone();
+            let total = args.requests;
+
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let n = counter.fetch_add(1, Ordering::Relaxed);
+                    if n > total {
+                        break;
+                    }
+                    let operation = mix.pick(n);
+                    let started = Instant::now();
+                    let success = run_operation(&client, &service_url, operation, n, run_id, &ids)
+                        .await
+                        .unwrap_or(false);
+                    samples.push(Sample {
+                        operation,
+                        latency: started.elapsed(),
+                        success,
+                    });
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::with_capacity(args.requests);
+    for worker in workers {
+        samples.extend(worker.await?);
+    }
+
+    report(&samples, start.elapsed());
+
+    Ok(())
+}
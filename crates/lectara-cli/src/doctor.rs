@@ -0,0 +1,279 @@
+//! `lectara doctor`: checks that the service can be reached and used from
+//! here, printing a fix for each problem found.
+
+use reqwest::{Client, Response, StatusCode, header::DATE};
+use serde::Deserialize;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::self_update::SUPPORTED_API_VERSION;
+
+/// How long each check waits for the service
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Clock difference from the service worth a warning; relative times such
+/// as `--since 1h` and signed requests drift with it
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct Health {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct ServerVersion {
+    api_version: u32,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct Readiness {
+    migrations: Migrations,
+}
+
+#[derive(Deserialize)]
+struct Migrations {
+    pending: Vec<String>,
+}
+
+/// The outcomes printed so far
+#[derive(Default)]
+struct Report {
+    failures: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn pass(&mut self, message: impl AsRef<str>) {
+        println!("  ok    {}", message.as_ref());
+    }
+
+    fn warn(&mut self, message: impl AsRef<str>, fix: impl AsRef<str>) {
+        self.warnings += 1;
+        println!("  warn  {}", message.as_ref());
+        println!("        fix: {}", fix.as_ref());
+    }
+
+    fn fail(&mut self, message: impl AsRef<str>, fix: impl AsRef<str>) {
+        self.failures += 1;
+        println!("  FAIL  {}", message.as_ref());
+        println!("        fix: {}", fix.as_ref());
+    }
+}
+
+/// Runs every check, returning whether none of them failed. `anonymous`
+/// sends no key, since the service turns away unknown keys even where it
+/// needs none.
+pub async fn doctor(
+    client: &Client,
+    anonymous: &Client,
+    service_url: &str,
+    has_api_key: bool,
+) -> bool {
+    println!(
+        "Checking {service_url} with lectara {}",
+        env!("CARGO_PKG_VERSION")
+    );
+    let mut report = Report::default();
+
+    // Nothing else can be checked without a connection
+    if check_health(anonymous, service_url, &mut report).await {
+        check_api_version(anonymous, service_url, &mut report).await;
+        check_readiness(anonymous, service_url, &mut report).await;
+        check_auth(client, service_url, has_api_key, &mut report).await;
+    }
+
+    match (report.failures, report.warnings) {
+        (0, 0) => println!("Everything looks fine"),
+        (0, warnings) => println!("{warnings} warning(s), nothing failed"),
+        (failures, warnings) => println!("{failures} check(s) failed, {warnings} warning(s)"),
+    }
+    report.failures == 0
+}
+
+async fn get(client: &Client, url: &str) -> reqwest::Result<Response> {
+    client.get(url).timeout(REQUEST_TIMEOUT).send().await
+}
+
+/// Connectivity, liveness and the clock, all from `/health`; returns whether
+/// the service answered
+async fn check_health(client: &Client, service_url: &str, report: &mut Report) -> bool {
+    let started = Instant::now();
+    let response = match get(client, &format!("{service_url}/health")).await {
+        Ok(response) => response,
+        Err(err) if err.is_timeout() => {
+            report.fail(
+                format!("No answer from {service_url} within {REQUEST_TIMEOUT:?}"),
+                "Check that the service isn't overloaded and that no firewall drops the connection",
+            );
+            return false;
+        }
+        Err(err) if err.is_connect() => {
+            report.fail(
+                format!("Couldn't connect to {service_url}: {err}"),
+                "Start the service (`lectara-service serve`) or pass its address with --service-url",
+            );
+            return false;
+        }
+        Err(err) => {
+            report.fail(
+                format!("Request to {service_url} failed: {err}"),
+                "Check --service-url, including its scheme (http:// or https://)",
+            );
+            return false;
+        }
+    };
+    let elapsed = started.elapsed();
+    report.pass(format!(
+        "Connected to {service_url} in {} ms",
+        elapsed.as_millis()
+    ));
+
+    check_clock(&response, report);
+
+    let status = response.status();
+    match response.json::<Health>().await {
+        Ok(health) if status.is_success() && health.status == "ok" => {
+            report.pass("Service is up")
+        }
+        _ if status == StatusCode::SERVICE_UNAVAILABLE => report.fail(
+            "The service is unavailable, e.g. during a maintenance window",
+            "Wait for the window to end, or check its schedule at /api/v1/admin/maintenance",
+        ),
+        _ => report.fail(
+            format!("{service_url}/health answered {status} without a Lectara health report"),
+            "Make sure --service-url points at Lectara itself and not at another service or a proxy's error page",
+        ),
+    }
+    true
+}
+
+/// Compares the response's `Date` with the local clock
+fn check_clock(response: &Response, report: &mut Report) {
+    let Some(server_time) = response
+        .headers()
+        .get(DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| httpdate::parse_http_date(date).ok())
+    else {
+        report.warn(
+            "The service sent no Date header to compare clocks with",
+            "Check that the proxy in front of the service passes the Date header on",
+        );
+        return;
+    };
+
+    let now = SystemTime::now();
+    let (skew, direction) = match now.duration_since(server_time) {
+        Ok(ahead) => (ahead, "ahead of"),
+        Err(err) => (err.duration(), "behind"),
+    };
+    if skew > MAX_CLOCK_SKEW {
+        report.warn(
+            format!(
+                "This clock is {}s {direction} the service's",
+                skew.as_secs()
+            ),
+            "Sync both clocks with NTP, e.g. `timedatectl set-ntp true`",
+        );
+    } else {
+        report.pass("Clock agrees with the service's");
+    }
+}
+
+async fn check_api_version(client: &Client, service_url: &str, report: &mut Report) {
+    let endpoint = format!("{service_url}/api/v1/version");
+    let server = match get(client, &endpoint).await {
+        Ok(response) if response.status().is_success() => {
+            response.json::<ServerVersion>().await.ok()
+        }
+        _ => None,
+    };
+
+    match server {
+        None => report.fail(
+            format!("Couldn't read the API version from {endpoint}"),
+            "Update the service; versions without /api/v1/version predate this CLI",
+        ),
+        Some(server) if server.api_version > SUPPORTED_API_VERSION => report.fail(
+            format!(
+                "Service {} speaks API v{}, but this CLI only speaks v{SUPPORTED_API_VERSION}",
+                server.version, server.api_version
+            ),
+            "Update the CLI with `lectara self-update`",
+        ),
+        Some(server) if server.api_version < SUPPORTED_API_VERSION => report.fail(
+            format!(
+                "Service {} only speaks API v{}, but this CLI speaks v{SUPPORTED_API_VERSION}",
+                server.version, server.api_version
+            ),
+            "Update the service",
+        ),
+        Some(server) => report.pass(format!(
+            "Service {} speaks API v{}, compatible with this CLI",
+            server.version, server.api_version
+        )),
+    }
+}
+
+async fn check_readiness(client: &Client, service_url: &str, report: &mut Report) {
+    let response = match get(client, &format!("{service_url}/ready")).await {
+        Ok(response) => response,
+        Err(err) => {
+            report.fail(
+                format!("Readiness check failed: {err}"),
+                "Run `lectara doctor` again; if it keeps failing, check the service's logs",
+            );
+            return;
+        }
+    };
+
+    let status = response.status();
+    match response.json::<Readiness>().await {
+        Ok(_) if status.is_success() => report.pass("Database is migrated and ready"),
+        Ok(readiness) if !readiness.migrations.pending.is_empty() => report.fail(
+            format!(
+                "Migrations are pending: {}",
+                readiness.migrations.pending.join(", ")
+            ),
+            "Restart the service to apply them, and check its logs if one keeps failing",
+        ),
+        _ => report.fail(
+            format!("Readiness check answered {status}"),
+            "Check the service's logs for database errors",
+        ),
+    }
+}
+
+/// Whether the key, or the lack of one, gets through to the content API
+async fn check_auth(client: &Client, service_url: &str, has_api_key: bool, report: &mut Report) {
+    let response = match get(client, &format!("{service_url}/api/v1/content/count")).await {
+        Ok(response) => response,
+        Err(err) => {
+            report.fail(
+                format!("Auth check failed: {err}"),
+                "Run `lectara doctor` again; if it keeps failing, check the service's logs",
+            );
+            return;
+        }
+    };
+
+    match (response.status(), has_api_key) {
+        (status, true) if status.is_success() => report.pass("API key accepted"),
+        (status, false) if status.is_success() => report.pass("The service needs no API key"),
+        (StatusCode::UNAUTHORIZED, true) => report.fail(
+            "The service rejected the API key",
+            "Check --api-key or LECTARA_API_KEY against the service's LECTARA_API_KEY_HASHES",
+        ),
+        (StatusCode::UNAUTHORIZED, false) => report.fail(
+            "The service needs an API key",
+            "Pass --api-key or set LECTARA_API_KEY",
+        ),
+        (StatusCode::FORBIDDEN, _) => report.fail(
+            "The API key belongs to another tenant than this host's",
+            "Use the key of the tenant that --service-url's host serves, or that tenant's host",
+        ),
+        (status, _) => report.fail(
+            format!("Listing content answered {status}"),
+            "Check the service's logs",
+        ),
+    }
+}
@@ -0,0 +1,165 @@
+//! `lectara edit`: fixes an item's metadata in `$EDITOR`. The item is
+//! written to a TOML file, and the fields changed there are shown as a diff
+//! and sent back with `PATCH /api/v1/content/{id}`.
+
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+/// Explains the file to whoever opens it
+const HEADER: &str = "\
+# Edit the fields below, then save and close the editor.
+# An empty string clears a field; an unchanged file changes nothing.
+";
+
+#[derive(Deserialize)]
+struct Item {
+    title: Option<String>,
+    author: Option<String>,
+    body: Option<String>,
+}
+
+/// The fields as they are in the file, empty when unset
+#[derive(Serialize, Deserialize, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Fields {
+    title: String,
+    author: String,
+    body: String,
+}
+
+impl From<Item> for Fields {
+    fn from(item: Item) -> Self {
+        Self {
+            title: item.title.unwrap_or_default(),
+            author: item.author.unwrap_or_default(),
+            body: item.body.unwrap_or_default(),
+        }
+    }
+}
+
+/// The changed fields, as the PATCH endpoint takes them
+#[derive(Serialize, Default)]
+struct Changes<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+}
+
+pub async fn edit(client: &Client, service_url: &str, id: u32) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/content/{id}");
+
+    let response = client.get(&endpoint).send().await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        eprintln!("No item #{id}");
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        eprintln!("Failed to fetch item: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+        return Ok(());
+    }
+    let before = Fields::from(response.json::<Item>().await?);
+
+    let path = std::env::temp_dir().join(format!("lectara-edit-{id}-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        format!("{HEADER}{}", toml::to_string_pretty(&before)?),
+    )?;
+    open_editor(&path)?;
+
+    let edited = std::fs::read_to_string(&path)?;
+    let after: Fields = match toml::from_str(&edited) {
+        Ok(fields) => fields,
+        Err(err) => {
+            // Kept so the edits aren't lost
+            eprintln!("Failed to read {}: {err}", path.display());
+            return Ok(());
+        }
+    };
+    std::fs::remove_file(&path)?;
+
+    if after == before {
+        println!("No changes");
+        return Ok(());
+    }
+
+    let mut changes = Changes::default();
+    let fields = [
+        ("title", &before.title, &after.title, &mut changes.title),
+        ("author", &before.author, &after.author, &mut changes.author),
+        ("body", &before.body, &after.body, &mut changes.body),
+    ];
+    for (name, old, new, change) in fields {
+        if old != new {
+            print_diff(name, old, new);
+            *change = Some(new.as_str());
+        }
+    }
+
+    let response = client
+        .patch(&endpoint)
+        .header("x-lectara-source", "cli")
+        .header("x-lectara-client-version", env!("CARGO_PKG_VERSION"))
+        .json(&changes)
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        println!("Updated item #{id}");
+    } else {
+        eprintln!("Failed to update item: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+    }
+
+    Ok(())
+}
+
+/// Runs `$VISUAL`, `$EDITOR` or `vi` on `path` and waits for it to exit.
+/// The variable may hold arguments, like `code --wait`.
+fn open_editor(path: &Path) -> Result<(), Box<dyn Error>> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let status = Command::new(program).args(words).arg(path).status()?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}").into());
+    }
+    Ok(())
+}
+
+/// Prints the lines of a field that changed, the lines they have in common
+/// at the start and end left out
+fn print_diff(name: &str, old: &str, new: &str) {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    println!("{name}:");
+    for line in &old[prefix..old.len() - suffix] {
+        println!("- {line}");
+    }
+    for line in &new[prefix..new.len() - suffix] {
+        println!("+ {line}");
+    }
+}
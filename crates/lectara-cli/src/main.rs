@@ -1,7 +1,18 @@
 use clap::{Parser, Subcommand};
 use reqwest::Client;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod doctor;
+mod edit;
+mod search;
+mod self_update;
+mod watch;
 
 #[derive(Parser)]
 #[command(name = "lectara")]
@@ -11,6 +22,10 @@ struct Cli {
     #[arg(long, default_value = "http://localhost:3000")]
     service_url: String,
 
+    /// API key for services that require one, sent as a bearer token
+    #[arg(long, env = "LECTARA_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -30,6 +45,90 @@ enum Commands {
         /// Optional body text for the content
         #[arg(short, long)]
         body: Option<String>,
+        /// Print tags suggested from the saved content
+        #[arg(long)]
+        suggest_tags: bool,
+    },
+    /// Fix an item's title, author or body in $VISUAL or $EDITOR, sending
+    /// back only the fields that changed
+    Edit {
+        /// ID of the item to edit
+        id: u32,
+    },
+    /// Export saved items to another format
+    Export {
+        #[command(subcommand)]
+        format: ExportFormat,
+    },
+    /// Import items from another format
+    Import {
+        #[command(subcommand)]
+        format: ImportFormat,
+    },
+    /// List saved items, newest first
+    List {
+        /// Saved view whose filters to apply, e.g. `work-reading`
+        #[arg(long)]
+        view: Option<String>,
+        /// Number of items to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: u32,
+    },
+    /// Full-text search over saved items, best match first, with the
+    /// matching words highlighted
+    Search {
+        /// Words to search for; `"quoted phrases"`, `OR`, `NOT` and prefixes
+        /// like `rust*` work as in the API
+        query: String,
+        /// Number of results to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: u32,
+        /// Open the Nth result in the browser after listing the results
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        open: Option<u32>,
+    },
+    /// Check the connection to the service and the CLI's setup, printing
+    /// how to fix what fails
+    Doctor,
+    /// Update the CLI to the latest release
+    SelfUpdate {
+        /// Only report available updates and API compatibility
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print items as they are saved and changed, until interrupted
+    Watch {
+        /// Print each event as a line of JSON with the whole item
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// BibTeX entries for items with citation metadata
+    Bibtex {
+        /// Only include items saved at or after this time: RFC3339, unix
+        /// seconds, YYYY-MM-DD, or relative like `7d` or `yesterday`
+        #[arg(long)]
+        since: Option<String>,
+        /// Only include items saved at or before this time, in the same forms
+        /// as --since
+        #[arg(long)]
+        until: Option<String>,
+        /// File to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportFormat {
+    /// Items of an RSS feed, imported in the background with a progress bar;
+    /// Ctrl+C cancels the import
+    Rss {
+        /// Feed file to import
+        file: PathBuf,
     },
 }
 
@@ -46,10 +145,62 @@ struct ContentResponse {
     id: u32,
 }
 
+#[derive(Deserialize)]
+struct SuggestedTagsResponse {
+    suggestions: Vec<SuggestedTag>,
+}
+
+#[derive(Deserialize)]
+struct SuggestedTag {
+    tag: String,
+}
+
+#[derive(Deserialize)]
+struct ListContentResponse {
+    items: Vec<ContentSummary>,
+    total: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ContentSummary {
+    id: u32,
+    url: String,
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImportJobResponse {
+    status_url: String,
+}
+
+#[derive(Deserialize)]
+struct JobProgress {
+    status: String,
+    processed: u64,
+    total: u64,
+    outcomes: BTreeMap<String, u64>,
+    error_count: u64,
+    errors: Vec<JobError>,
+    failure: Option<String>,
+    /// Set once the job has stopped; a cancelled job may still be finishing
+    /// an item before
+    finished_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JobError {
+    item: Option<String>,
+    error: String,
+}
+
+/// How often a running job's progress is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const PROGRESS_BAR_WIDTH: u64 = 30;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
-    let client = Client::new();
+    let client = build_client(cli.api_key.as_deref())?;
 
     match cli.command {
         Commands::Add {
@@ -57,32 +208,91 @@ async fn main() -> Result<(), Box<dyn Error>> {
             title,
             author,
             body,
+            suggest_tags,
         } => {
-            add_content(&client, &cli.service_url, url, title, author, body).await?;
+            let payload = NewContentItem {
+                url,
+                title,
+                author,
+                body,
+            };
+            add_content(&client, &cli.service_url, payload, suggest_tags).await?;
+        }
+        Commands::Doctor => {
+            let anonymous = build_client(None)?;
+            let healthy =
+                doctor::doctor(&client, &anonymous, &cli.service_url, cli.api_key.is_some()).await;
+            if !healthy {
+                std::process::exit(1);
+            }
+        }
+        Commands::Edit { id } => {
+            edit::edit(&client, &cli.service_url, id).await?;
+        }
+        Commands::Export {
+            format:
+                ExportFormat::Bibtex {
+                    since,
+                    until,
+                    output,
+                },
+        } => {
+            export_bibtex(&client, &cli.service_url, since, until, output).await?;
+        }
+        Commands::Import {
+            format: ImportFormat::Rss { file },
+        } => {
+            import_rss(&client, &cli.service_url, file).await?;
+        }
+        Commands::List { view, limit } => {
+            list_content(&client, &cli.service_url, view, limit).await?;
+        }
+        Commands::Search { query, limit, open } => {
+            search::search(&client, &cli.service_url, &query, limit, open).await?;
+        }
+        Commands::SelfUpdate { check } => {
+            // GitHub mustn't see the service's key
+            let github = build_client(None)?;
+            self_update::self_update(&client, &github, &cli.service_url, check).await?;
+        }
+        Commands::Watch { json } => {
+            watch::watch(&client, &cli.service_url, json).await?;
         }
     }
 
     Ok(())
 }
 
+/// An HTTP client naming the CLI, sending `api_key` with every request
+fn build_client(api_key: Option<&str>) -> Result<Client, Box<dyn Error>> {
+    let mut headers = HeaderMap::new();
+    if let Some(key) = api_key {
+        let mut value = HeaderValue::from_str(&format!("Bearer {key}"))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    let client = Client::builder()
+        .user_agent(concat!("lectara-cli/", env!("CARGO_PKG_VERSION")))
+        .default_headers(headers)
+        .build()?;
+    Ok(client)
+}
+
 async fn add_content(
     client: &Client,
     service_url: &str,
-    url: String,
-    title: Option<String>,
-    author: Option<String>,
-    body: Option<String>,
+    payload: NewContentItem,
+    suggest_tags: bool,
 ) -> Result<(), Box<dyn Error>> {
     let endpoint = format!("{service_url}/api/v1/content");
 
-    let payload = NewContentItem {
-        url,
-        title,
-        author,
-        body,
-    };
-
-    let response = client.post(&endpoint).json(&payload).send().await?;
+    let response = client
+        .post(&endpoint)
+        .header("x-lectara-source", "cli")
+        .header("x-lectara-client-version", env!("CARGO_PKG_VERSION"))
+        .json(&payload)
+        .send()
+        .await?;
 
     if response.status().is_success() {
         let content_response: ContentResponse = response.json().await?;
@@ -90,6 +300,9 @@ async fn add_content(
             "Content added successfully with ID: {}",
             content_response.id
         );
+        if suggest_tags {
+            print_suggested_tags(client, service_url, content_response.id).await?;
+        }
     } else {
         eprintln!("Failed to add content: {}", response.status());
         eprintln!("Response: {}", response.text().await?);
@@ -97,3 +310,200 @@ async fn add_content(
 
     Ok(())
 }
+
+async fn print_suggested_tags(
+    client: &Client,
+    service_url: &str,
+    id: u32,
+) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/content/{id}/suggested-tags");
+
+    let response = client.get(&endpoint).send().await?;
+
+    if !response.status().is_success() {
+        eprintln!("Failed to fetch suggested tags: {}", response.status());
+        return Ok(());
+    }
+
+    let suggested: SuggestedTagsResponse = response.json().await?;
+    if suggested.suggestions.is_empty() {
+        println!("No tags to suggest");
+    } else {
+        let tags: Vec<_> = suggested
+            .suggestions
+            .into_iter()
+            .map(|suggestion| suggestion.tag)
+            .collect();
+        println!("Suggested tags: {}", tags.join(", "));
+    }
+
+    Ok(())
+}
+
+async fn export_bibtex(
+    client: &Client,
+    service_url: &str,
+    since: Option<String>,
+    until: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/export/bibtex");
+
+    let mut query = Vec::new();
+    if let Some(since) = since {
+        query.push(("since", since));
+    }
+    if let Some(until) = until {
+        query.push(("until", until));
+    }
+
+    let response = client.get(&endpoint).query(&query).send().await?;
+
+    if !response.status().is_success() {
+        eprintln!("Failed to export BibTeX: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+        return Ok(());
+    }
+
+    let bibtex = response.text().await?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, bibtex)?;
+            eprintln!("BibTeX written to {}", path.display());
+        }
+        None => print!("{bibtex}"),
+    }
+
+    Ok(())
+}
+
+async fn list_content(
+    client: &Client,
+    service_url: &str,
+    view: Option<String>,
+    limit: u32,
+) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/content");
+
+    let mut query = vec![("limit", limit.to_string())];
+    if let Some(view) = view {
+        query.push(("view", view));
+    }
+
+    let response = client.get(&endpoint).query(&query).send().await?;
+
+    if !response.status().is_success() {
+        eprintln!("Failed to list content: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+        return Ok(());
+    }
+
+    let list: ListContentResponse = response.json().await?;
+    for item in &list.items {
+        match &item.title {
+            Some(title) => println!("{:>6}  {title} <{}>", item.id, item.url),
+            None => println!("{:>6}  {}", item.id, item.url),
+        }
+    }
+    if let Some(total) = list.total
+        && total > list.items.len() as u64
+    {
+        eprintln!("Showing {} of {total} items", list.items.len());
+    }
+
+    Ok(())
+}
+
+async fn import_rss(
+    client: &Client,
+    service_url: &str,
+    file: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/content/import/rss");
+    let feed = std::fs::read_to_string(&file)?;
+
+    let response = client
+        .post(&endpoint)
+        .query(&[("background", "true")])
+        .header("x-lectara-client-version", env!("CARGO_PKG_VERSION"))
+        .header("content-type", "application/rss+xml")
+        .body(feed)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        eprintln!("Failed to start import: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+        return Ok(());
+    }
+
+    let started: ImportJobResponse = response.json().await?;
+    let status_url = format!("{service_url}{}", started.status_url);
+    let mut cancelled = false;
+
+    let job = loop {
+        let response = client.get(&status_url).send().await?;
+        if !response.status().is_success() {
+            eprintln!("\nFailed to check import progress: {}", response.status());
+            return Ok(());
+        }
+        let job: JobProgress = response.json().await?;
+        eprint!(
+            "\r{} {}/{} ({} errors)",
+            progress_bar(job.processed, job.total),
+            job.processed,
+            job.total,
+            job.error_count
+        );
+        std::io::stderr().flush()?;
+        if job.finished_at.is_some() {
+            eprintln!();
+            break job;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = tokio::signal::ctrl_c(), if !cancelled => {
+                cancelled = true;
+                eprintln!("\nCancelling import...");
+                client.post(format!("{status_url}/cancel")).send().await?;
+            }
+        }
+    };
+
+    let outcomes: Vec<String> = job
+        .outcomes
+        .iter()
+        .map(|(outcome, count)| format!("{count} {outcome}"))
+        .collect();
+    println!("Import {}: {}", job.status, outcomes.join(", "));
+    if let Some(failure) = job.failure {
+        eprintln!("Import failed: {failure}");
+    }
+    for error in &job.errors {
+        eprintln!(
+            "  {}: {}",
+            error.item.as_deref().unwrap_or("(no URL)"),
+            error.error
+        );
+    }
+    let unlisted = job.error_count - job.errors.len() as u64;
+    if unlisted > 0 {
+        eprintln!("  ...and {unlisted} more errors");
+    }
+
+    Ok(())
+}
+
+/// A bar of `#` filled in proportion to the items processed
+fn progress_bar(processed: u64, total: u64) -> String {
+    let filled = (processed * PROGRESS_BAR_WIDTH)
+        .checked_div(total)
+        .unwrap_or(PROGRESS_BAR_WIDTH)
+        .min(PROGRESS_BAR_WIDTH);
+    format!(
+        "[{}{}]",
+        "#".repeat(filled as usize),
+        "-".repeat((PROGRESS_BAR_WIDTH - filled) as usize)
+    )
+}
@@ -0,0 +1,192 @@
+//! `lectara search`: full-text search with a snippet of each result, the
+//! words matching the query highlighted when printing to a terminal.
+
+use reqwest::Client;
+use serde::Deserialize;
+use std::error::Error;
+use std::io::IsTerminal;
+use std::process::Command;
+
+/// Characters of a result's summary shown
+const SNIPPET_CHARS: usize = 160;
+/// Characters of a snippet shown before its first match
+const SNIPPET_CONTEXT: usize = 40;
+
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    items: Vec<SearchResult>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    id: u32,
+    url: String,
+    title: Option<String>,
+    author: Option<String>,
+    summary: Option<String>,
+}
+
+pub async fn search(
+    client: &Client,
+    service_url: &str,
+    query: &str,
+    limit: u32,
+    open: Option<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/search");
+    // Enough results to have the one to open
+    let limit = open.map_or(limit, |n| limit.max(n));
+
+    let response = client
+        .get(&endpoint)
+        .query(&[("q", query.to_string()), ("limit", limit.to_string())])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        eprintln!("Failed to search: {}", response.status());
+        eprintln!("Response: {}", response.text().await?);
+        return Ok(());
+    }
+
+    let results: SearchResponse = response.json().await?;
+    if results.items.is_empty() {
+        eprintln!("No items match '{query}'");
+        return Ok(());
+    }
+
+    let terms = query_terms(query);
+    // NO_COLOR: https://no-color.org
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    for (rank, item) in results.items.iter().enumerate() {
+        let title = item.title.as_deref().unwrap_or(&item.url);
+        println!(
+            "{:>3}. {}  #{}",
+            rank + 1,
+            highlight(title, &terms, color),
+            item.id
+        );
+        println!("     {}", item.url);
+        if let Some(author) = &item.author {
+            println!("     by {}", highlight(author, &terms, color));
+        }
+        if let Some(summary) = &item.summary {
+            println!(
+                "     {}",
+                highlight(&snippet(summary, &terms), &terms, color)
+            );
+        }
+    }
+
+    if let Some(n) = open {
+        match results.items.get(n as usize - 1) {
+            Some(item) => {
+                eprintln!("Opening {}", item.url);
+                open_in_browser(&item.url)?;
+            }
+            None => eprintln!("No result {n} to open, found {}", results.items.len()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Lowercased words of a query, without its operators, quotes and prefix
+/// stars
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !matches!(*word, "AND" | "OR" | "NOT" | "NEAR"))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Byte ranges of the words in `text` starting with one of `terms`, which
+/// also catches prefix searches and most stemmed forms
+fn matches(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                let word = text[start..i].to_lowercase();
+                if terms.iter().any(|term| word.starts_with(term.as_str())) {
+                    ranges.push((start, i));
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// Up to [`SNIPPET_CHARS`] characters of `text` around its first match,
+/// with `…` where it was cut
+fn snippet(text: &str, terms: &[String]) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let first = matches(&text, terms).first().map_or(0, |&(start, _)| start);
+
+    let mut start = first.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    // Begin at a word rather than in one
+    if start > 0 {
+        start = text[start..first]
+            .find(' ')
+            .map_or(first, |space| start + space + 1);
+    }
+    let end = text[start..]
+        .char_indices()
+        .nth(SNIPPET_CHARS)
+        .map_or(text.len(), |(i, _)| start + i);
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(text[start..end].trim());
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// `text` with the words matching `terms` in bold yellow when `color` is on
+fn highlight(text: &str, terms: &[String], color: bool) -> String {
+    if !color {
+        return text.to_string();
+    }
+    let mut highlighted = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in matches(text, terms) {
+        highlighted.push_str(&text[last..start]);
+        highlighted.push_str(HIGHLIGHT);
+        highlighted.push_str(&text[start..end]);
+        highlighted.push_str(RESET);
+        last = end;
+    }
+    highlighted.push_str(&text[last..]);
+    highlighted
+}
+
+/// Opens `url` with `$BROWSER`, or else the platform's opener
+fn open_in_browser(url: &str) -> Result<(), Box<dyn Error>> {
+    let opener = match std::env::var("BROWSER") {
+        Ok(browser) if !browser.trim().is_empty() => browser,
+        _ if cfg!(target_os = "macos") => "open".to_string(),
+        _ if cfg!(target_os = "windows") => "explorer".to_string(),
+        _ => "xdg-open".to_string(),
+    };
+    let status = Command::new(&opener).arg(url).status()?;
+    // explorer exits with 1 even when it opened the URL
+    if !status.success() && opener != "explorer" {
+        return Err(format!("{opener} exited with {status}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,287 @@
+//! `lectara self-update`: checks this CLI against the server it talks to and
+//! the latest GitHub release, replacing the binary with the release's.
+//!
+//! Each binary asset is published with a `<asset>.sha256` file in
+//! `sha256sum` format (the flake's `lectara-cli-release` package builds
+//! both), and the download must match it before anything is replaced.
+
+use reqwest::Client;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::path::Path;
+
+/// Repository whose releases carry the CLI binaries
+const GITHUB_REPO: &str = "seridescent/lectara";
+/// API version this CLI speaks, as reported by the server's `/api/v1/version`
+pub const SUPPORTED_API_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct ServerVersion {
+    api_version: u32,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Name of the release asset built for this platform, e.g.
+/// `lectara-x86_64-linux`
+fn asset_name() -> String {
+    asset_name_for(std::env::consts::ARCH, std::env::consts::OS)
+}
+
+fn asset_name_for(arch: &str, os: &str) -> String {
+    format!("lectara-{arch}-{os}")
+}
+
+/// Name of the asset holding the SHA-256 checksum of `asset`
+fn checksum_name(asset: &str) -> String {
+    format!("{asset}.sha256")
+}
+
+/// The version a release tag such as `v1.2.0` names, if it is newer than
+/// `current`
+fn newer_release(tag: &str, current: &Version) -> Result<Option<Version>, semver::Error> {
+    let latest = Version::parse(tag.trim_start_matches('v'))?;
+    Ok((latest > *current).then_some(latest))
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// `client` talks to the service and `github` to GitHub
+pub async fn self_update(
+    client: &Client,
+    github: &Client,
+    service_url: &str,
+    check_only: bool,
+) -> Result<(), Box<dyn Error>> {
+    let current = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    println!("lectara {current}");
+
+    check_server(client, service_url).await;
+
+    let release = latest_release(github).await?;
+    let Some(latest) = newer_release(&release.tag_name, &current)? else {
+        println!("Already up to date");
+        return Ok(());
+    };
+    println!("lectara {latest} is available: {}", release.html_url);
+    if check_only {
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let Some(asset) = release.asset(&name) else {
+        eprintln!("Release {latest} has no {name} binary to download");
+        return Ok(());
+    };
+    let Some(checksum_asset) = release.asset(&checksum_name(&name)) else {
+        return Err(format!(
+            "Release {latest} has no {} checksum for {name}; not updating",
+            checksum_name(&name)
+        )
+        .into());
+    };
+
+    let binary = download(github, &asset.browser_download_url).await?;
+    let checksum = download(github, &checksum_asset.browser_download_url).await?;
+    let checksum = String::from_utf8_lossy(&checksum);
+    install(&std::env::current_exe()?, &binary, &checksum)?;
+    println!("Updated lectara to {latest}");
+
+    Ok(())
+}
+
+/// Warns when the server speaks another API version than this CLI; an
+/// unreachable server only gets a note, since updating doesn't need it
+async fn check_server(client: &Client, service_url: &str) {
+    let endpoint = format!("{service_url}/api/v1/version");
+    let server = match client.get(&endpoint).send().await {
+        Ok(response) if response.status().is_success() => {
+            response.json::<ServerVersion>().await.ok()
+        }
+        _ => None,
+    };
+
+    match server {
+        None => eprintln!("Could not read the server version from {endpoint}"),
+        Some(server) if server.api_version > SUPPORTED_API_VERSION => eprintln!(
+            "Warning: server {} speaks API v{}, but this CLI only speaks v{SUPPORTED_API_VERSION}; update the CLI",
+            server.version, server.api_version
+        ),
+        Some(server) if server.api_version < SUPPORTED_API_VERSION => eprintln!(
+            "Warning: server {} only speaks API v{}, but this CLI speaks v{SUPPORTED_API_VERSION}; update the server",
+            server.version, server.api_version
+        ),
+        Some(server) => println!(
+            "Server {} speaks API v{}, compatible with this CLI",
+            server.version, server.api_version
+        ),
+    }
+}
+
+async fn latest_release(client: &Client) -> Result<Release, Box<dyn Error>> {
+    let endpoint = format!("https://api.github.com/repos/{GITHUB_REPO}/releases/latest");
+    let release = client
+        .get(&endpoint)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(release)
+}
+
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+/// Checks `binary` against `checksum`, the contents of its `.sha256` asset
+fn verify_checksum(binary: &[u8], checksum: &str) -> Result<(), String> {
+    let expected = checksum
+        .split_whitespace()
+        .next()
+        .filter(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or("Release checksum is not a SHA-256 digest")?
+        .to_ascii_lowercase();
+    let actual = format!("{:x}", Sha256::digest(binary));
+    if actual != expected {
+        return Err(format!(
+            "Downloaded binary has SHA-256 {actual}, but the release lists {expected}; not updating"
+        ));
+    }
+    Ok(())
+}
+
+/// Replaces `exe` with `binary` once it matches `checksum`. The binary is
+/// written next to `exe` and renamed into place, so an interrupted update
+/// never leaves a broken `lectara` behind; on a mismatch nothing is written.
+fn install(exe: &Path, binary: &[u8], checksum: &str) -> Result<(), Box<dyn Error>> {
+    verify_checksum(binary, checksum)?;
+
+    let staged = exe.with_extension("new");
+    std::fs::write(&staged, binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    std::fs::rename(&staged, exe)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-256 of `new binary`
+    const NEW_BINARY_SHA256: &str =
+        "2f17c9ffb972a6c5da72c2b3df01f7e2ccf52dad2c0059dac631232a15126d2e";
+
+    fn release(names: &[&str]) -> Release {
+        Release {
+            tag_name: "v1.2.0".to_string(),
+            html_url: "https://github.com/seridescent/lectara/releases/tag/v1.2.0".to_string(),
+            assets: names
+                .iter()
+                .map(|name| ReleaseAsset {
+                    name: name.to_string(),
+                    browser_download_url: format!("https://example.com/{name}"),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_newer_release() {
+        let current = Version::parse("1.1.0").unwrap();
+        assert_eq!(
+            newer_release("v1.2.0", &current).unwrap(),
+            Some(Version::parse("1.2.0").unwrap())
+        );
+        assert_eq!(
+            newer_release("1.2.0", &current).unwrap(),
+            Some(Version::parse("1.2.0").unwrap())
+        );
+        assert_eq!(newer_release("v1.1.0", &current).unwrap(), None);
+        assert_eq!(newer_release("v1.0.9", &current).unwrap(), None);
+        // Pre-releases sort before the release they lead up to
+        assert_eq!(newer_release("v1.1.0-rc.1", &current).unwrap(), None);
+        assert!(newer_release("nightly", &current).is_err());
+    }
+
+    #[test]
+    fn test_platform_asset() {
+        assert_eq!(asset_name_for("x86_64", "linux"), "lectara-x86_64-linux");
+        assert_eq!(
+            checksum_name("lectara-aarch64-macos"),
+            "lectara-aarch64-macos.sha256"
+        );
+
+        let release = release(&[
+            "lectara-x86_64-linux",
+            "lectara-x86_64-linux.sha256",
+            "lectara-aarch64-macos",
+        ]);
+        let asset = release.asset("lectara-x86_64-linux").unwrap();
+        assert_eq!(
+            asset.browser_download_url,
+            "https://example.com/lectara-x86_64-linux"
+        );
+        assert!(release.asset("lectara-aarch64-macos.sha256").is_none());
+        assert!(release.asset("lectara-x86_64-windows").is_none());
+    }
+
+    #[test]
+    fn test_checksum_formats() {
+        let listed = format!("{NEW_BINARY_SHA256}  lectara-x86_64-linux\n");
+        assert!(verify_checksum(b"new binary", &listed).is_ok());
+        assert!(verify_checksum(b"new binary", &NEW_BINARY_SHA256.to_uppercase()).is_ok());
+        assert!(verify_checksum(b"new binary", "").is_err());
+        assert!(verify_checksum(b"new binary", "not-a-digest  lectara").is_err());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_leaves_the_binary_alone() {
+        let dir = std::env::temp_dir().join(format!("lectara-update-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("lectara");
+        std::fs::write(&exe, b"old binary").unwrap();
+
+        let result = install(&exe, b"tampered binary", NEW_BINARY_SHA256);
+        assert!(result.unwrap_err().to_string().contains("not updating"));
+        assert_eq!(std::fs::read(&exe).unwrap(), b"old binary");
+        assert!(!exe.with_extension("new").exists());
+
+        install(&exe, b"new binary", NEW_BINARY_SHA256).unwrap();
+        assert_eq!(std::fs::read(&exe).unwrap(), b"new binary");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
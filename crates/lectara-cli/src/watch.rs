@@ -0,0 +1,103 @@
+//! `lectara watch`: follows the service's content event stream, printing
+//! items as they are saved and changed. The stream is reopened when the
+//! connection drops, e.g. while the service restarts; events in between are
+//! missed.
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::error::Error;
+use std::time::Duration;
+
+/// How long to wait before reopening a stream that ended
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// One server-sent event
+#[derive(Debug, Default, PartialEq)]
+struct Event {
+    name: String,
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: u32,
+    url: String,
+    title: Option<String>,
+}
+
+pub async fn watch(client: &Client, service_url: &str, json: bool) -> Result<(), Box<dyn Error>> {
+    let endpoint = format!("{service_url}/api/v1/content/events");
+
+    loop {
+        match follow(client, &endpoint, json).await {
+            Ok(()) => eprintln!("The event stream ended, reconnecting"),
+            Err(err) => eprintln!("Lost the event stream ({err}), reconnecting"),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Prints the events of one stream until it ends
+async fn follow(client: &Client, endpoint: &str, json: bool) -> Result<(), Box<dyn Error>> {
+    let mut response = client
+        .get(endpoint)
+        .header("accept", "text/event-stream")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("{}: {}", response.status(), response.text().await?).into());
+    }
+    eprintln!("Watching for saved and changed items");
+
+    let mut buffer = String::new();
+    while let Some(chunk) = response.chunk().await? {
+        buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+        while let Some(end) = buffer.find("\n\n") {
+            let block: String = buffer.drain(..end + 2).collect();
+            if let Some(event) = parse_event(&block) {
+                print_event(&event, json)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The event in a block of `field: value` lines, `None` for blocks without
+/// data such as keep-alive comments
+fn parse_event(block: &str) -> Option<Event> {
+    let mut event = Event {
+        name: "message".to_string(),
+        ..Event::default()
+    };
+    let mut data = Vec::new();
+    for line in block.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event.name = value.to_string(),
+            "data" => data.push(value),
+            _ => {}
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    event.data = data.join("\n");
+    Some(event)
+}
+
+fn print_event(event: &Event, json: bool) -> Result<(), Box<dyn Error>> {
+    let item: Value = serde_json::from_str(&event.data)?;
+    if json {
+        println!("{}", json!({ "event": event.name, "item": item }));
+        return Ok(());
+    }
+
+    let item: Item = serde_json::from_value(item)?;
+    match &item.title {
+        Some(title) => println!("{:<8}{:>6}  {title} <{}>", event.name, item.id, item.url),
+        None => println!("{:<8}{:>6}  {}", event.name, item.id, item.url),
+    }
+    Ok(())
+}
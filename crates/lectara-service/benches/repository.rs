@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use diesel::{Connection, sqlite::SqliteConnection};
+use diesel_migrations::MigrationHarness;
+use lectara_service::{
+    DefaultAppState,
+    migrations::MIGRATIONS,
+    repositories::{ContentFilter, ContentRepository, ListContentParams, SqliteContentRepository},
+    routes, seed,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde_json::json;
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+const TABLE_SIZES: &[usize] = &[1_000, 10_000, 100_000];
+
+fn seeded_database(items: usize) -> Arc<Mutex<SqliteConnection>> {
+    let mut conn =
+        SqliteConnection::establish(":memory:").expect("Failed to create in-memory database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    seed::seed_content(&mut conn, &mut StdRng::seed_from_u64(0), items)
+        .expect("Failed to seed database");
+    Arc::new(Mutex::new(conn))
+}
+
+fn list_params(offset: u32) -> ListContentParams {
+    ListContentParams {
+        limit: Some(50),
+        offset: Some(offset),
+        filter: ContentFilter::default(),
+        include_total: true,
+    }
+}
+
+fn bench_list(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("repository_list");
+
+    for &size in TABLE_SIZES {
+        let repo = SqliteContentRepository::new(seeded_database(size));
+
+        group.bench_with_input(BenchmarkId::new("first_page", size), &size, |b, _| {
+            b.iter(|| rt.block_on(repo.list(black_box(&list_params(0)))).unwrap())
+        });
+
+        let deep_offset = (size / 2) as u32;
+        group.bench_with_input(BenchmarkId::new("deep_page", size), &size, |b, _| {
+            b.iter(|| {
+                rt.block_on(repo.list(black_box(&list_params(deep_offset))))
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+async fn post_content(app: &Router, payload: &serde_json::Value) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/content")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+/// Measures the add-content handler end to end, including the existing-URL
+/// lookup and metadata comparison that decide between insert, idempotent
+/// return, and conflict.
+fn bench_create(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("create_content");
+
+    let app: Router =
+        routes::create_router().with_state(DefaultAppState::new(seeded_database(10_000)));
+
+    let existing = json!({
+        "url": "https://example.com/existing",
+        "title": "Existing Article",
+        "author": "Existing Author",
+        "body": "Existing body content",
+    });
+    assert_eq!(rt.block_on(post_content(&app, &existing)), StatusCode::OK);
+
+    let counter = AtomicUsize::new(0);
+    group.bench_function("new_item", |b| {
+        b.iter(|| {
+            let n = counter.fetch_add(1, Ordering::Relaxed);
+            let payload = json!({
+                "url": format!("https://example.com/new/{n}"),
+                "title": "New Article",
+            });
+            let status = rt.block_on(post_content(&app, &payload));
+            assert_eq!(status, StatusCode::OK);
+        })
+    });
+
+    group.bench_function("idempotent_duplicate", |b| {
+        b.iter(|| {
+            let status = rt.block_on(post_content(&app, black_box(&existing)));
+            assert_eq!(status, StatusCode::OK);
+        })
+    });
+
+    let conflicting = json!({
+        "url": "https://example.com/existing",
+        "title": "Different Title",
+    });
+    group.bench_function("conflicting_duplicate", |b| {
+        b.iter(|| {
+            let status = rt.block_on(post_content(&app, black_box(&conflicting)));
+            assert_eq!(status, StatusCode::CONFLICT);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_list, bench_create);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use lectara_service::validation::normalize_url;
+use std::hint::black_box;
+
+const URLS: &[(&str, &str)] = &[
+    ("root", "https://example.com/"),
+    (
+        "path",
+        "https://example.com/blog/2024/07/some-long-article-slug/",
+    ),
+    (
+        "query",
+        "https://example.com/search?q=rust&sort=date&page=2&utm_source=feed",
+    ),
+    (
+        "complex",
+        "HTTPS://EXAMPLE.COM:443/Path/To/Resource/?c=3&a=1&b=2#fragment",
+    ),
+    ("rejected", "http://localhost:8080/local/article"),
+];
+
+fn bench_normalize_url(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalize_url");
+    group.throughput(Throughput::Elements(1));
+
+    for (name, url) in URLS {
+        group.bench_with_input(BenchmarkId::from_parameter(name), url, |b, url| {
+            b.iter(|| normalize_url(black_box(url)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_normalize_url);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+//! Bakes the git commit and build time into the binary for the health
+//! endpoints, see `src/build_info.rs`, and compiles in the custom web
+//! themes, see `src/routes/web/theme.rs`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory of custom theme stylesheets, relative to the manifest
+const THEMES_DIR: &str = "src/routes/web/assets/themes";
+/// Names of the themes the base stylesheet provides
+const BUILT_IN_THEMES: &[&str] = &["system", "light", "dark"];
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=LECTARA_GIT_SHA");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    // Builds without a checkout, like Nix's, pass the commit in instead
+    let git_sha = std::env::var("LECTARA_GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.trim().is_empty())
+        .or_else(|| {
+            watch_git_head();
+            git(&["rev-parse", "HEAD"])
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LECTARA_GIT_SHA={}", git_sha.trim());
+
+    // Reproducible builds pin the timestamp
+    let built_at = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+    println!("cargo:rustc-env=LECTARA_BUILD_EPOCH={built_at}");
+
+    write_custom_themes();
+}
+
+/// Writes `themes.rs` to `OUT_DIR`, listing each `<name>.css` in
+/// [`THEMES_DIR`] with its contents
+fn write_custom_themes() {
+    println!("cargo:rerun-if-changed={THEMES_DIR}");
+
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let mut themes = std::fs::read_dir(manifest_dir.join(THEMES_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "css"))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    themes.sort();
+
+    let mut source = String::from("pub(crate) const CUSTOM_THEMES: &[(&str, &[u8])] = &[\n");
+    for path in &themes {
+        let name = theme_name(path);
+        source.push_str(&format!(
+            "    ({name:?}, include_bytes!({:?})),\n",
+            path.display().to_string()
+        ));
+    }
+    source.push_str("];\n");
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("themes.rs"), source).unwrap();
+}
+
+/// The theme a stylesheet defines, named after its file
+fn theme_name(path: &Path) -> String {
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid || BUILT_IN_THEMES.contains(&name) {
+        panic!(
+            "{} must be named with lowercase letters, digits and '-', other than {}",
+            path.display(),
+            BUILT_IN_THEMES.join(", ")
+        );
+    }
+    name.to_string()
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Reruns the build script when a commit moves `HEAD` or the branch it is on
+fn watch_git_head() {
+    for path in [
+        git(&["rev-parse", "--git-path", "HEAD"]),
+        current_ref_path(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        println!("cargo:rerun-if-changed={path}");
+    }
+}
+
+fn current_ref_path() -> Option<String> {
+    let head_ref = git(&["symbolic-ref", "-q", "HEAD"])?;
+    git(&["rev-parse", "--git-path", &head_ref])
+}
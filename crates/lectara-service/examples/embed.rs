@@ -0,0 +1,221 @@
+//! Mounts lectara under `/reading` of a host Axum app that keeps its own
+//! routes and state, backed by an in-memory database.
+//!
+//! ```sh
+//! cargo run -p lectara-service --example embed
+//! curl -X POST localhost:3001/reading/api/v1/content \
+//!     -H 'content-type: application/json' -d '{"url": "https://example.com"}'
+//! curl localhost:3001/
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::State;
+use axum::routing::get;
+use diesel::prelude::*;
+use diesel_migrations::MigrationHarness;
+use lectara_service::activity::ActivityFeed;
+#[cfg(feature = "activitypub")]
+use lectara_service::activitypub::ActivityPub;
+use lectara_service::delivery::DeviceMailer;
+use lectara_service::enrichment::citations::CitationResolver;
+use lectara_service::enrichment::fetch::Fetcher;
+use lectara_service::enrichment::github::GithubResolver;
+use lectara_service::enrichment::pdf::PdfExtractor;
+use lectara_service::enrichment::pipeline::EnricherRegistry;
+use lectara_service::enrichment::site_rules::SiteRules;
+use lectara_service::enrichment::summary::Summarizer;
+use lectara_service::enrichment::threads::ThreadResolver;
+use lectara_service::federation::SyncTarget;
+use lectara_service::jobs::JobRegistry;
+use lectara_service::maintenance::MaintenanceSchedule;
+use lectara_service::metrics::RequestMetrics;
+use lectara_service::migrations::MIGRATIONS;
+use lectara_service::notifications::Notifier;
+use lectara_service::read_only::ReadOnlyMode;
+use lectara_service::reload::Reloader;
+use lectara_service::repositories::{ContentFilter, ContentRepository};
+use lectara_service::validation::ValidationContext;
+use lectara_service::{AppState, DefaultAppState, routes};
+
+/// The host app's state: its own settings next to lectara's repositories
+#[derive(Clone)]
+struct HostState {
+    site_name: Arc<str>,
+    lectara: DefaultAppState,
+    /// Enrichers run for items saved through the host; none here
+    enrichers: Arc<EnricherRegistry<HostState>>,
+}
+
+impl AppState for HostState {
+    type ContentRepo = <DefaultAppState as AppState>::ContentRepo;
+    type ShareLinkRepo = <DefaultAppState as AppState>::ShareLinkRepo;
+    type SchemaRepo = <DefaultAppState as AppState>::SchemaRepo;
+    type CitationRepo = <DefaultAppState as AppState>::CitationRepo;
+    type FetchAttemptRepo = <DefaultAppState as AppState>::FetchAttemptRepo;
+    type PageSnapshotRepo = <DefaultAppState as AppState>::PageSnapshotRepo;
+    type ArchiveSnapshotRepo = <DefaultAppState as AppState>::ArchiveSnapshotRepo;
+    type NotificationRepo = <DefaultAppState as AppState>::NotificationRepo;
+    type RuleRepo = <DefaultAppState as AppState>::RuleRepo;
+    type ViewRepo = <DefaultAppState as AppState>::ViewRepo;
+    type CommentRepo = <DefaultAppState as AppState>::CommentRepo;
+    type FollowerRepo = <DefaultAppState as AppState>::FollowerRepo;
+
+    fn content_repo(&self) -> Self::ContentRepo {
+        self.lectara.content_repo()
+    }
+
+    fn share_link_repo(&self) -> Self::ShareLinkRepo {
+        self.lectara.share_link_repo()
+    }
+
+    fn schema_repo(&self) -> Self::SchemaRepo {
+        self.lectara.schema_repo()
+    }
+
+    fn citation_repo(&self) -> Self::CitationRepo {
+        self.lectara.citation_repo()
+    }
+
+    fn fetch_attempt_repo(&self) -> Self::FetchAttemptRepo {
+        self.lectara.fetch_attempt_repo()
+    }
+
+    fn page_snapshot_repo(&self) -> Self::PageSnapshotRepo {
+        self.lectara.page_snapshot_repo()
+    }
+
+    fn archive_snapshot_repo(&self) -> Self::ArchiveSnapshotRepo {
+        self.lectara.archive_snapshot_repo()
+    }
+
+    fn notification_repo(&self) -> Self::NotificationRepo {
+        self.lectara.notification_repo()
+    }
+
+    fn rule_repo(&self) -> Self::RuleRepo {
+        self.lectara.rule_repo()
+    }
+
+    fn view_repo(&self) -> Self::ViewRepo {
+        self.lectara.view_repo()
+    }
+
+    fn comment_repo(&self) -> Self::CommentRepo {
+        self.lectara.comment_repo()
+    }
+
+    fn follower_repo(&self) -> Self::FollowerRepo {
+        self.lectara.follower_repo()
+    }
+
+    fn validation(&self) -> &ValidationContext {
+        self.lectara.validation()
+    }
+
+    fn fetcher(&self) -> &Fetcher {
+        self.lectara.fetcher()
+    }
+
+    fn citation_resolver(&self) -> Option<&CitationResolver> {
+        None
+    }
+
+    fn thread_resolver(&self) -> Option<&ThreadResolver> {
+        None
+    }
+
+    fn github_resolver(&self) -> Option<&GithubResolver> {
+        None
+    }
+
+    fn pdf_extractor(&self) -> Option<&PdfExtractor> {
+        None
+    }
+
+    fn summarizer(&self) -> Option<&Summarizer> {
+        None
+    }
+
+    fn site_rules(&self) -> Option<&SiteRules> {
+        None
+    }
+
+    fn device_mailer(&self) -> Option<&DeviceMailer> {
+        None
+    }
+
+    fn sync_target(&self) -> Option<&SyncTarget> {
+        None
+    }
+
+    #[cfg(feature = "activitypub")]
+    fn activitypub(&self) -> Option<&ActivityPub> {
+        None
+    }
+
+    fn enrichers(&self) -> &EnricherRegistry<Self> {
+        &self.enrichers
+    }
+
+    fn read_only(&self) -> &ReadOnlyMode {
+        self.lectara.read_only()
+    }
+
+    fn maintenance(&self) -> &MaintenanceSchedule {
+        self.lectara.maintenance()
+    }
+
+    fn reloader(&self) -> &Reloader {
+        self.lectara.reloader()
+    }
+
+    fn metrics(&self) -> &RequestMetrics {
+        self.lectara.metrics()
+    }
+
+    fn notifier(&self) -> &Notifier {
+        self.lectara.notifier()
+    }
+
+    fn jobs(&self) -> &JobRegistry {
+        self.lectara.jobs()
+    }
+
+    fn activity(&self) -> Option<&ActivityFeed<Self::ContentRepo>> {
+        None
+    }
+}
+
+/// A host route reading lectara's repositories through the shared state
+async fn home(State(state): State<HostState>) -> String {
+    let saved = state
+        .content_repo()
+        .count(&ContentFilter::default())
+        .await
+        .unwrap_or_default();
+    format!("{}: {saved} items saved under /reading\n", state.site_name)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut connection = SqliteConnection::establish(":memory:")?;
+    connection.run_pending_migrations(MIGRATIONS)?;
+
+    let state = HostState {
+        site_name: "My site".into(),
+        lectara: DefaultAppState::new(Arc::new(Mutex::new(connection))),
+        enrichers: Arc::new(EnricherRegistry::new()),
+    };
+
+    let app = Router::new()
+        .route("/", get(home))
+        .nest("/reading", routes::create_router())
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3001").await?;
+    println!("Listening on http://127.0.0.1:3001");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+CREATE TABLE share_links (
+    id INTEGER PRIMARY KEY NOT NULL,
+    token TEXT NOT NULL,
+    content_id INTEGER NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX idx_share_links_token ON share_links(token);
+CREATE INDEX idx_share_links_content_id ON share_links(content_id);
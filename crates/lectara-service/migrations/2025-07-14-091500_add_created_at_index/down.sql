@@ -0,0 +1 @@
+DROP INDEX idx_content_items_created_at_id;
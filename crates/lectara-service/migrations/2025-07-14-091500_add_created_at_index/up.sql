@@ -0,0 +1,2 @@
+-- Covers the list ordering and created_at range filters
+CREATE INDEX idx_content_items_created_at_id ON content_items(created_at, id);
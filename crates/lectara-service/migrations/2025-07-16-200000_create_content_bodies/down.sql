@@ -0,0 +1,6 @@
+ALTER TABLE content_items ADD COLUMN body TEXT;
+
+UPDATE content_items
+SET body = (SELECT body FROM content_bodies WHERE content_bodies.content_id = content_items.id);
+
+DROP TABLE content_bodies;
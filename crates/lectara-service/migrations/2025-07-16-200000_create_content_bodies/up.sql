@@ -0,0 +1,10 @@
+-- Keep large bodies out of the content_items rows scanned by list queries
+CREATE TABLE content_bodies (
+    content_id INTEGER PRIMARY KEY NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    body TEXT NOT NULL
+);
+
+INSERT INTO content_bodies (content_id, body)
+SELECT id, body FROM content_items WHERE body IS NOT NULL;
+
+ALTER TABLE content_items DROP COLUMN body;
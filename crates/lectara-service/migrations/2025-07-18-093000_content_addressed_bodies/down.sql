@@ -0,0 +1,8 @@
+INSERT INTO content_bodies (content_id, body)
+SELECT content_items.id, body_blobs.body
+FROM content_items
+JOIN body_blobs ON body_blobs.hash = content_items.body_hash;
+
+ALTER TABLE content_items DROP COLUMN body_hash;
+
+DROP TABLE body_blobs;
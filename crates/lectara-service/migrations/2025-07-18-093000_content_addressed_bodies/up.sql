@@ -0,0 +1,12 @@
+-- Identical bodies are stored once, keyed by SHA-256, and shared by every
+-- content item pointing at them
+CREATE TABLE body_blobs (
+    hash TEXT PRIMARY KEY NOT NULL,
+    body TEXT NOT NULL,
+    ref_count INTEGER NOT NULL DEFAULT 0
+);
+
+ALTER TABLE content_items ADD COLUMN body_hash TEXT REFERENCES body_blobs(hash);
+
+-- SQLite has no built-in SHA-256, so rows still in content_bodies are hashed
+-- and moved into body_blobs by the service at startup
@@ -0,0 +1,12 @@
+-- Citation metadata resolved from Crossref (DOIs) or arXiv for academic items
+CREATE TABLE citations (
+    content_id INTEGER PRIMARY KEY NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    identifier TEXT NOT NULL,
+    title TEXT,
+    authors TEXT NOT NULL DEFAULT '[]',
+    published_on DATE,
+    container_title TEXT,
+    abstract_text TEXT,
+    fetched_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
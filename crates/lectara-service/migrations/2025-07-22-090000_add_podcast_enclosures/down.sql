@@ -0,0 +1,4 @@
+DROP INDEX idx_content_items_enclosures;
+
+ALTER TABLE content_items DROP COLUMN duration_seconds;
+ALTER TABLE content_items DROP COLUMN enclosure_url;
@@ -0,0 +1,7 @@
+-- Audio enclosure for podcast episodes, as found in RSS <enclosure> elements
+ALTER TABLE content_items ADD COLUMN enclosure_url TEXT;
+ALTER TABLE content_items ADD COLUMN duration_seconds INTEGER;
+
+-- Covers the podcast feed, which only lists items with an enclosure
+CREATE INDEX idx_content_items_enclosures ON content_items(created_at, id)
+WHERE enclosure_url IS NOT NULL;
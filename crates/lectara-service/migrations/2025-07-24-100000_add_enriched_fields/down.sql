@@ -0,0 +1 @@
+ALTER TABLE content_items DROP COLUMN enriched_fields;
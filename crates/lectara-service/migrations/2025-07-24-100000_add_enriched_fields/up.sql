@@ -0,0 +1,3 @@
+-- Metadata fields filled in by background enrichment rather than by the
+-- client, as a JSON array of field names
+ALTER TABLE content_items ADD COLUMN enriched_fields TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,4 @@
+DROP INDEX idx_content_items_content_type;
+
+ALTER TABLE content_items DROP COLUMN metadata;
+ALTER TABLE content_items DROP COLUMN content_type;
@@ -0,0 +1,7 @@
+-- Kind of item (e.g. `repository`), set by enrichment
+ALTER TABLE content_items ADD COLUMN content_type TEXT;
+-- Extra metadata found by enrichment, as a JSON object
+ALTER TABLE content_items ADD COLUMN metadata TEXT NOT NULL DEFAULT '{}';
+
+-- Covers listing items of one type
+CREATE INDEX idx_content_items_content_type ON content_items(content_type, created_at);
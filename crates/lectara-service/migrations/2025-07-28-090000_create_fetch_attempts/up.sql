@@ -0,0 +1,15 @@
+-- Outcome of the latest background fetch of each kind for an item, with the
+-- schedule for retrying failures
+CREATE TABLE fetch_attempts (
+    content_id INTEGER NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    last_error TEXT,
+    last_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    next_attempt_at TIMESTAMP,
+    PRIMARY KEY (content_id, kind)
+);
+
+CREATE INDEX idx_fetch_attempts_next_attempt_at ON fetch_attempts(next_attempt_at)
+    WHERE next_attempt_at IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Normalized hash of each item's page as last fetched, for change detection
+CREATE TABLE page_snapshots (
+    content_id INTEGER PRIMARY KEY NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    checked_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
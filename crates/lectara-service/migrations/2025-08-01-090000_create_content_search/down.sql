@@ -0,0 +1,5 @@
+DROP TRIGGER content_search_delete;
+DROP TRIGGER content_search_update;
+DROP TRIGGER content_search_insert;
+DROP TABLE content_search_exact;
+DROP TABLE content_search;
@@ -0,0 +1,51 @@
+-- Full-text index over titles, authors and bodies. `content_search` stems
+-- English words with the Porter stemmer; `content_search_exact` only folds
+-- case and diacritics, for text in other languages. Rows share the id of
+-- their content item and are kept current by the triggers below.
+CREATE VIRTUAL TABLE content_search USING fts5(
+    title,
+    author,
+    body,
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE content_search_exact USING fts5(
+    title,
+    author,
+    body,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Matches in titles and authors outrank matches in bodies
+INSERT INTO content_search(content_search, rank) VALUES ('rank', 'bm25(10.0, 5.0, 1.0)');
+INSERT INTO content_search_exact(content_search_exact, rank) VALUES ('rank', 'bm25(10.0, 5.0, 1.0)');
+
+INSERT INTO content_search(rowid, title, author, body)
+SELECT content_items.id, content_items.title, content_items.author, body_blobs.body
+FROM content_items
+LEFT JOIN body_blobs ON body_blobs.hash = content_items.body_hash;
+
+INSERT INTO content_search_exact(rowid, title, author, body)
+SELECT rowid, title, author, body FROM content_search;
+
+-- Bodies are stored in body_blobs before the item referencing them
+CREATE TRIGGER content_search_insert AFTER INSERT ON content_items BEGIN
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+CREATE TRIGGER content_search_update AFTER UPDATE OF title, author, body_hash ON content_items BEGIN
+    DELETE FROM content_search WHERE rowid = OLD.id;
+    DELETE FROM content_search_exact WHERE rowid = OLD.id;
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+CREATE TRIGGER content_search_delete AFTER DELETE ON content_items BEGIN
+    DELETE FROM content_search WHERE rowid = OLD.id;
+    DELETE FROM content_search_exact WHERE rowid = OLD.id;
+END;
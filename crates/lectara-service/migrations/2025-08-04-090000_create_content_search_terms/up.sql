@@ -0,0 +1,3 @@
+-- Per-term document counts of the unstemmed search index, used to weigh
+-- keywords when suggesting tags
+CREATE VIRTUAL TABLE content_search_terms USING fts5vocab(content_search_exact, row);
@@ -0,0 +1,2 @@
+-- Short summary written by the optional summarizer
+ALTER TABLE content_items ADD COLUMN summary TEXT;
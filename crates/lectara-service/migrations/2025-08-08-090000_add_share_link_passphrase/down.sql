@@ -0,0 +1,3 @@
+ALTER TABLE share_links DROP COLUMN last_failed_at;
+ALTER TABLE share_links DROP COLUMN failed_attempts;
+ALTER TABLE share_links DROP COLUMN passphrase_hash;
@@ -0,0 +1,5 @@
+-- Argon2 hash in PHC string format; links without one are public
+ALTER TABLE share_links ADD COLUMN passphrase_hash TEXT;
+-- Wrong passphrases entered since the last lockout window began
+ALTER TABLE share_links ADD COLUMN failed_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE share_links ADD COLUMN last_failed_at TIMESTAMP;
@@ -0,0 +1,2 @@
+DROP INDEX idx_content_items_pinned_position;
+ALTER TABLE content_items DROP COLUMN pinned_position;
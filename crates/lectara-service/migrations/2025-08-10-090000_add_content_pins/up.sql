@@ -0,0 +1,4 @@
+-- Place of a pinned item at the top of listings, from 0; NULL when unpinned
+ALTER TABLE content_items ADD COLUMN pinned_position INTEGER;
+CREATE UNIQUE INDEX idx_content_items_pinned_position ON content_items (pinned_position)
+    WHERE pinned_position IS NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE content_items DROP COLUMN archived_at;
+ALTER TABLE content_items DROP COLUMN read_at;
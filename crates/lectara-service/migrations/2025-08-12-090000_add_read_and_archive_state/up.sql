@@ -0,0 +1,3 @@
+-- When the item was marked read or archived; NULL while it isn't
+ALTER TABLE content_items ADD COLUMN read_at TIMESTAMP;
+ALTER TABLE content_items ADD COLUMN archived_at TIMESTAMP;
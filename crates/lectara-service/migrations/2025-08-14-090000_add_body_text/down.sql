@@ -0,0 +1,21 @@
+DROP TRIGGER content_search_body_text;
+DROP TRIGGER content_search_update;
+DROP TRIGGER content_search_insert;
+
+CREATE TRIGGER content_search_insert AFTER INSERT ON content_items BEGIN
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+CREATE TRIGGER content_search_update AFTER UPDATE OF title, author, body_hash ON content_items BEGIN
+    DELETE FROM content_search WHERE rowid = OLD.id;
+    DELETE FROM content_search_exact WHERE rowid = OLD.id;
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT body FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+ALTER TABLE body_blobs DROP COLUMN text;
@@ -0,0 +1,33 @@
+-- Plain text of HTML bodies, filled in by the service since SQLite can't
+-- parse HTML; NULL for bodies that are plain text already. Search indexes
+-- the text in place of the markup.
+ALTER TABLE body_blobs ADD COLUMN text TEXT;
+
+DROP TRIGGER content_search_insert;
+DROP TRIGGER content_search_update;
+
+CREATE TRIGGER content_search_insert AFTER INSERT ON content_items BEGIN
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT COALESCE(text, body) FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT COALESCE(text, body) FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+CREATE TRIGGER content_search_update AFTER UPDATE OF title, author, body_hash ON content_items BEGIN
+    DELETE FROM content_search WHERE rowid = OLD.id;
+    DELETE FROM content_search_exact WHERE rowid = OLD.id;
+    INSERT INTO content_search(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT COALESCE(text, body) FROM body_blobs WHERE hash = NEW.body_hash));
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    VALUES (NEW.id, NEW.title, NEW.author, (SELECT COALESCE(text, body) FROM body_blobs WHERE hash = NEW.body_hash));
+END;
+
+-- Reindexes the items sharing a body once its text is filled in
+CREATE TRIGGER content_search_body_text AFTER UPDATE OF text ON body_blobs BEGIN
+    DELETE FROM content_search WHERE rowid IN (SELECT id FROM content_items WHERE body_hash = NEW.hash);
+    DELETE FROM content_search_exact WHERE rowid IN (SELECT id FROM content_items WHERE body_hash = NEW.hash);
+    INSERT INTO content_search(rowid, title, author, body)
+    SELECT id, title, author, COALESCE(NEW.text, NEW.body) FROM content_items WHERE body_hash = NEW.hash;
+    INSERT INTO content_search_exact(rowid, title, author, body)
+    SELECT id, title, author, COALESCE(NEW.text, NEW.body) FROM content_items WHERE body_hash = NEW.hash;
+END;
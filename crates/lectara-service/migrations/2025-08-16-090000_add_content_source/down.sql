@@ -0,0 +1,2 @@
+DROP INDEX idx_content_items_source;
+ALTER TABLE content_items DROP COLUMN source;
@@ -0,0 +1,4 @@
+-- How the item was saved, e.g. `cli` or `import:rss`; NULL for items saved
+-- before sources were recorded
+ALTER TABLE content_items ADD COLUMN source TEXT;
+CREATE INDEX idx_content_items_source ON content_items(source);
@@ -0,0 +1,2 @@
+ALTER TABLE content_items DROP COLUMN client_version;
+ALTER TABLE content_items DROP COLUMN user_agent;
@@ -0,0 +1,4 @@
+-- The client that saved the item: its User-Agent and the version it reports
+-- in X-Lectara-Client-Version
+ALTER TABLE content_items ADD COLUMN user_agent TEXT;
+ALTER TABLE content_items ADD COLUMN client_version TEXT;
@@ -0,0 +1,13 @@
+-- Outcomes of background work worth telling the user about, such as a
+-- finished import or a fetch given up on
+CREATE TABLE notifications (
+    id INTEGER PRIMARY KEY NOT NULL,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL,
+    content_id INTEGER REFERENCES content_items(id) ON DELETE CASCADE,
+    read_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_notifications_created_at ON notifications(created_at, id);
+CREATE INDEX idx_notifications_unread ON notifications(id) WHERE read_at IS NULL;
@@ -0,0 +1,2 @@
+DROP TABLE archive_snapshots;
+DROP TABLE archive_payloads;
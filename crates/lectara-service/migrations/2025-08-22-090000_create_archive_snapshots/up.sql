@@ -0,0 +1,22 @@
+-- Full-page captures of saved items, kept as the parts of a WARC response
+-- record. Payloads are stored once per SHA-256, so capturing an unchanged
+-- page again only adds its headers.
+CREATE TABLE archive_payloads (
+    digest TEXT PRIMARY KEY NOT NULL,
+    payload BLOB NOT NULL,
+    ref_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE archive_snapshots (
+    id INTEGER PRIMARY KEY NOT NULL,
+    content_id INTEGER NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    record_id TEXT NOT NULL,
+    target_uri TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    http_headers BLOB NOT NULL,
+    payload_digest TEXT NOT NULL REFERENCES archive_payloads(digest),
+    payload_length INTEGER NOT NULL,
+    captured_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_archive_snapshots_content_id ON archive_snapshots(content_id, captured_at);
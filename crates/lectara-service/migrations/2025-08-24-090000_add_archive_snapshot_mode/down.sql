@@ -0,0 +1 @@
+ALTER TABLE archive_snapshots DROP COLUMN mode;
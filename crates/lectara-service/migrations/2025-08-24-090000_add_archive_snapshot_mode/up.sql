@@ -0,0 +1,3 @@
+-- How a snapshot was archived: `warc` keeps the page as fetched, while
+-- `single-file` inlines its resources into one self-contained HTML file
+ALTER TABLE archive_snapshots ADD COLUMN mode TEXT NOT NULL DEFAULT 'warc';
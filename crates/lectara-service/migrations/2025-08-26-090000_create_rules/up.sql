@@ -0,0 +1,15 @@
+-- Rules applied in order to each newly saved item. A rule matches items
+-- whose host is `domain` or one of its subdomains and whose URL matches
+-- `url_pattern`, whichever of the two are set.
+CREATE TABLE rules (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    domain TEXT,
+    url_pattern TEXT,
+    set_content_type TEXT,
+    skip_enrichment BOOLEAN NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_rules_position ON rules(position, id);
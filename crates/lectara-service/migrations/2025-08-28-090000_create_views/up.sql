@@ -0,0 +1,11 @@
+-- Named combinations of list filters. `since` and `until` are kept as
+-- given, so relative ones like `7d` are resolved each time the view is used.
+CREATE TABLE views (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    content_type TEXT,
+    source TEXT,
+    since TEXT,
+    until TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
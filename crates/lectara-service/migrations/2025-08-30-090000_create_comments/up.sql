@@ -0,0 +1,12 @@
+-- Comments on items, left through the API or by visitors of a share link.
+-- A reply names the comment it answers in `parent_id`.
+CREATE TABLE comments (
+    id INTEGER PRIMARY KEY NOT NULL,
+    content_id INTEGER NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_comments_content_id ON comments(content_id);
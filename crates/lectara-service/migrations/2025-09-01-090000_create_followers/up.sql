@@ -0,0 +1,9 @@
+-- Fediverse accounts following the instance's ActivityPub actor, with the
+-- inbox new saves are delivered to (their server's shared inbox if it has
+-- one).
+CREATE TABLE followers (
+    id INTEGER PRIMARY KEY NOT NULL,
+    actor_id TEXT NOT NULL UNIQUE,
+    inbox_url TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,85 @@
+//! The activity feed of a multi-tenant instance.
+//!
+//! Tenants opt in with `share_activity` in the tenant registry. Each of them
+//! then sees the newest items saved by the other tenants that opted in,
+//! read straight from their databases, and tenants that didn't opt in have
+//! no feed. Nothing beyond an item's URL, title, type and save time is shown.
+
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::repositories::{ContentFilter, ContentRepository};
+
+/// A save shown in the activity feed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActivityEntry {
+    /// Tenant that saved the item
+    pub tenant: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub content_type: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Recent saves of the tenants sharing their activity, as seen by one of
+/// them; clones share the tenants' repositories
+#[derive(Clone)]
+pub struct ActivityFeed<R> {
+    tenants: Arc<Vec<(String, R)>>,
+    /// Tenant the feed is shown to, whose own saves are left out
+    viewer: Option<String>,
+}
+
+impl<R: ContentRepository> ActivityFeed<R> {
+    /// A feed over the items of `tenants`, each with its content repository
+    pub fn new(tenants: Vec<(String, R)>) -> Self {
+        Self {
+            tenants: Arc::new(tenants),
+            viewer: None,
+        }
+    }
+
+    /// The feed as shown to `tenant`, without its own saves
+    pub fn viewed_by(&self, tenant: &str) -> Self {
+        Self {
+            tenants: self.tenants.clone(),
+            viewer: Some(tenant.to_string()),
+        }
+    }
+
+    /// The `limit` newest saves of the other tenants, newest first. A tenant
+    /// whose items can't be read is left out rather than failing the feed.
+    pub async fn recent(&self, limit: u32) -> Vec<ActivityEntry> {
+        let mut entries = Vec::new();
+        for (tenant, repo) in self.tenants.iter() {
+            if self.viewer.as_ref() == Some(tenant) {
+                continue;
+            }
+            let items = match repo.list_recent(&ContentFilter::default(), limit).await {
+                Ok(items) => items,
+                Err(err) => {
+                    warn!(tenant, error = %err, "Failed to read tenant activity");
+                    continue;
+                }
+            };
+            entries.extend(items.into_iter().map(|item| ActivityEntry {
+                tenant: tenant.clone(),
+                url: item.url,
+                title: item.title,
+                content_type: item.content_type,
+                created_at: item.created_at,
+            }));
+        }
+
+        entries.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| a.tenant.cmp(&b.tenant))
+        });
+        entries.truncate(limit as usize);
+        entries
+    }
+}
@@ -0,0 +1,766 @@
+//! Publishing saves to the fediverse over ActivityPub.
+//!
+//! With [`ACTIVITYPUB_URL_ENV`] set to the instance's public URL, the
+//! instance gets an actor, `reading@host` by default, that Mastodon and
+//! other fediverse accounts can follow. The items selected by the saved view
+//! named in [`ACTIVITYPUB_VIEW_ENV`], or every item without one, are
+//! published as Notes: delivered to followers once saved and enriched, and
+//! listed in the actor's outbox.
+//!
+//! Activities posted to the inbox must carry an HTTP Signature made with
+//! the key of the actor sending them. Follows are accepted straight away,
+//! and anything but follows and their undoing is ignored.
+
+pub mod signatures;
+
+use std::path::Path;
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use serde_json::{Value, json};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::federation;
+use crate::models::{ContentItem, NewFollower};
+use crate::repositories::{ContentFilter, FollowerRepository};
+use crate::routes::web::escape_html;
+use crate::validation::{ValidatedUrl, ValidationContext, ValidationError};
+
+use signatures::SignatureHeader;
+
+/// Public base URL of the instance, e.g. `https://reading.example.com`;
+/// setting it enables ActivityPub
+pub const ACTIVITYPUB_URL_ENV: &str = "LECTARA_ACTIVITYPUB_URL";
+/// Name of the actor, the `reading` of `reading@reading.example.com`
+pub const ACTIVITYPUB_USERNAME_ENV: &str = "LECTARA_ACTIVITYPUB_USERNAME";
+/// File holding the actor's private key in PEM, created if missing
+pub const ACTIVITYPUB_KEY_ENV: &str = "LECTARA_ACTIVITYPUB_KEY";
+/// Name of the saved view selecting the items to publish; all of them when
+/// unset
+pub const ACTIVITYPUB_VIEW_ENV: &str = "LECTARA_ACTIVITYPUB_VIEW";
+
+pub const DEFAULT_USERNAME: &str = "reading";
+
+/// Media type of ActivityPub documents
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+/// Audience of public activities
+const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Items listed in the outbox
+pub const OUTBOX_SIZE: u32 = 20;
+/// Size of a generated actor key
+const KEY_BITS: u32 = 2048;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// How far the `Date` of a signed request may be from the current time
+const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::hours(12);
+
+#[derive(Error, Debug)]
+pub enum ActivityPubConfigError {
+    #[error("Invalid URL in {ACTIVITYPUB_URL_ENV}: {0}")]
+    InvalidUrl(String),
+
+    #[error("Invalid username '{0}' in {ACTIVITYPUB_USERNAME_ENV}")]
+    InvalidUsername(String),
+
+    #[error("{ACTIVITYPUB_KEY_ENV} must name the file holding the actor's key")]
+    MissingKey,
+
+    #[error("Failed to load the actor's key from {path}: {reason}")]
+    Key { path: String, reason: String },
+
+    #[error("Invalid actor key: {0}")]
+    InvalidKey(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ActivityPubError {
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("{url} answered {status}")]
+    Rejected { url: String, status: u16 },
+
+    #[error("Not an address to send requests to: {0}")]
+    Address(#[from] ValidationError),
+
+    #[error("Invalid actor: {0}")]
+    InvalidActor(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Failed to sign request: {0}")]
+    Signing(#[from] openssl::error::ErrorStack),
+}
+
+/// Another server's actor, as far as delivering to it and checking its
+/// signatures goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteActor {
+    pub id: String,
+    /// Its server's shared inbox if it has one, its own otherwise
+    pub inbox: String,
+    pub public_key_id: String,
+    pub public_key_pem: String,
+}
+
+impl RemoteActor {
+    /// Reads an actor document
+    pub fn from_document(document: &Value) -> Result<Self, ActivityPubError> {
+        let text = |value: &Value, field: &str| {
+            value[field]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| ActivityPubError::InvalidActor(format!("'{field}' is missing")))
+        };
+        let id = text(document, "id")?;
+        let inbox = document["endpoints"]["sharedInbox"]
+            .as_str()
+            .map(str::to_string)
+            .map_or_else(|| text(document, "inbox"), Ok)?;
+        let key = &document["publicKey"];
+        if key["owner"].as_str() != Some(id.as_str()) {
+            return Err(ActivityPubError::InvalidActor(
+                "its key belongs to another actor".to_string(),
+            ));
+        }
+        Ok(Self {
+            id,
+            inbox,
+            public_key_id: text(key, "id")?,
+            public_key_pem: text(key, "publicKeyPem")?,
+        })
+    }
+}
+
+/// The instance's actor, its key and what it publishes
+#[derive(Clone)]
+pub struct ActivityPub {
+    client: reqwest::Client,
+    base_url: String,
+    /// Host, and port if any, of the base URL: the domain of the handle
+    domain: String,
+    username: String,
+    key: PKey<Private>,
+    public_key_pem: String,
+    view: Option<String>,
+}
+
+impl ActivityPub {
+    /// An actor named `username` at `base_url`, signing with the RSA key in
+    /// `key_pem` and publishing the items selected by `view`
+    pub fn new(
+        base_url: &str,
+        username: &str,
+        key_pem: &[u8],
+        view: Option<&str>,
+    ) -> Result<Self, ActivityPubConfigError> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        let parsed = Url::parse(base_url)
+            .map_err(|err| ActivityPubConfigError::InvalidUrl(err.to_string()))?;
+        let domain = match (parsed.scheme(), parsed.host_str()) {
+            ("http" | "https", Some(host)) => match parsed.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            },
+            _ => {
+                return Err(ActivityPubConfigError::InvalidUrl(format!(
+                    "'{base_url}' is not an http(s) URL"
+                )));
+            }
+        };
+        let username = username.trim();
+        let valid_username = !username.is_empty()
+            && username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_username {
+            return Err(ActivityPubConfigError::InvalidUsername(
+                username.to_string(),
+            ));
+        }
+        let key_error =
+            |err: openssl::error::ErrorStack| ActivityPubConfigError::InvalidKey(err.to_string());
+        let key = PKey::private_key_from_pem(key_pem).map_err(key_error)?;
+        let public_key_pem =
+            String::from_utf8(key.public_key_to_pem().map_err(key_error)?).expect("PEM is ASCII");
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            // Every address requested is validated, which redirects would skip
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+            domain,
+            username: username.to_string(),
+            key,
+            public_key_pem,
+            view: view
+                .map(str::trim)
+                .filter(|view| !view.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// The actor configured in [`ACTIVITYPUB_URL_ENV`], if any. The key file
+    /// is required once it is set, and a new key is written to it if the
+    /// file doesn't exist yet.
+    pub fn from_env() -> Result<Option<Self>, ActivityPubConfigError> {
+        let setting = |name| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+        let Some(base_url) = setting(ACTIVITYPUB_URL_ENV) else {
+            return Ok(None);
+        };
+        let key_path = setting(ACTIVITYPUB_KEY_ENV).ok_or(ActivityPubConfigError::MissingKey)?;
+        let key_pem = load_or_create_key(Path::new(key_path.trim()))?;
+        let username = setting(ACTIVITYPUB_USERNAME_ENV);
+        let view = setting(ACTIVITYPUB_VIEW_ENV);
+        Self::new(
+            &base_url,
+            username.as_deref().unwrap_or(DEFAULT_USERNAME),
+            &key_pem,
+            view.as_deref(),
+        )
+        .map(Some)
+    }
+
+    /// Name of the view selecting the items to publish, if not all of them
+    pub fn view(&self) -> Option<&str> {
+        self.view.as_deref()
+    }
+
+    /// The actor's handle, without the leading `@`
+    pub fn handle(&self) -> String {
+        format!("{}@{}", self.username, self.domain)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/web/ap/{path}", self.base_url)
+    }
+
+    pub fn actor_id(&self) -> String {
+        self.url("actor")
+    }
+
+    fn key_id(&self) -> String {
+        format!("{}#main-key", self.actor_id())
+    }
+
+    pub fn note_id(&self, content_id: i32) -> String {
+        self.url(&format!("notes/{content_id}"))
+    }
+
+    /// The actor document
+    pub fn actor(&self) -> Value {
+        let actor_id = self.actor_id();
+        json!({
+            "@context": [
+                "https://www.w3.org/ns/activitystreams",
+                "https://w3id.org/security/v1",
+            ],
+            "id": actor_id,
+            "type": "Service",
+            "preferredUsername": self.username,
+            "name": self.username,
+            "summary": "Links saved with lectara",
+            "url": format!("{}/web/", self.base_url),
+            "inbox": self.url("inbox"),
+            "outbox": self.url("outbox"),
+            "followers": self.url("followers"),
+            "endpoints": { "sharedInbox": self.url("inbox") },
+            "manuallyApprovesFollowers": false,
+            "discoverable": true,
+            "publicKey": {
+                "id": self.key_id(),
+                "owner": actor_id,
+                "publicKeyPem": self.public_key_pem,
+            },
+        })
+    }
+
+    /// The WebFinger document for `resource`, `None` if it isn't the actor
+    /// by handle or URL
+    pub fn webfinger(&self, resource: &str) -> Option<Value> {
+        let subject = format!("acct:{}", self.handle());
+        let actor_id = self.actor_id();
+        let known = resource.eq_ignore_ascii_case(&subject) || resource == actor_id;
+        known.then(|| {
+            json!({
+                "subject": subject,
+                "aliases": [actor_id],
+                "links": [{
+                    "rel": "self",
+                    "type": ACTIVITY_JSON,
+                    "href": actor_id,
+                }],
+            })
+        })
+    }
+
+    /// An item as a Note linking to it, to be embedded in an activity
+    pub fn note(&self, item: &ContentItem) -> Value {
+        let title = item.title.as_deref().unwrap_or(&item.url);
+        let mut content = format!(
+            "<p><a href=\"{}\">{}</a></p>",
+            escape_html(&item.url),
+            escape_html(title)
+        );
+        if let Some(summary) = &item.summary {
+            content.push_str(&format!("<p>{}</p>", escape_html(summary)));
+        }
+        json!({
+            "id": self.note_id(item.id),
+            "type": "Note",
+            "attributedTo": self.actor_id(),
+            "content": content,
+            "url": item.url,
+            "published": published(item.created_at),
+            "to": [PUBLIC],
+            "cc": [self.url("followers")],
+        })
+    }
+
+    /// The activity publishing an item
+    pub fn create(&self, item: &ContentItem) -> Value {
+        json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": format!("{}#create", self.note_id(item.id)),
+            "type": "Create",
+            "actor": self.actor_id(),
+            "published": published(item.created_at),
+            "to": [PUBLIC],
+            "cc": [self.url("followers")],
+            "object": self.note(item),
+        })
+    }
+
+    /// The outbox, listing the activities publishing `items` out of `total`
+    pub fn outbox(&self, items: &[ContentItem], total: u64) -> Value {
+        json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": self.url("outbox"),
+            "type": "OrderedCollection",
+            "totalItems": total,
+            "orderedItems": items.iter().map(|item| self.create(item)).collect::<Vec<_>>(),
+        })
+    }
+
+    /// The followers collection, which only tells how many there are
+    pub fn followers(&self, total: u64) -> Value {
+        json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": self.url("followers"),
+            "type": "OrderedCollection",
+            "totalItems": total,
+        })
+    }
+
+    /// The activity accepting `follow`
+    pub fn accept(&self, follow: &Value) -> Value {
+        json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "id": format!(
+                "{}#accepts/{}",
+                self.actor_id(),
+                hex::encode(rand::random::<[u8; 8]>())
+            ),
+            "type": "Accept",
+            "actor": self.actor_id(),
+            "object": follow,
+        })
+    }
+
+    /// Posts a signed activity to an inbox
+    pub async fn deliver(
+        &self,
+        inbox: &str,
+        activity: &Value,
+        validation: &ValidationContext,
+    ) -> Result<(), ActivityPubError> {
+        let url = checked_url(inbox, validation)?;
+        let body = serde_json::to_vec(activity).expect("activities serialize");
+        let digest = signatures::digest(&body);
+        let headers = self.signed_headers("POST", &url, Some(&digest))?;
+
+        let response = self
+            .client
+            .post(url.as_str())
+            .headers(headers)
+            .header("content-type", ACTIVITY_JSON)
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ActivityPubError::Rejected {
+                url: inbox.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches an actor with a signed request, for the servers that only
+    /// answer those; the document must be the one at the actor's own id
+    pub async fn fetch_actor(
+        &self,
+        actor_url: &str,
+        validation: &ValidationContext,
+    ) -> Result<RemoteActor, ActivityPubError> {
+        let url = checked_url(actor_url, validation)?;
+        let headers = self.signed_headers("GET", &url, None)?;
+        let response = self
+            .client
+            .get(url.as_str())
+            .headers(headers)
+            .header("accept", ACTIVITY_JSON)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ActivityPubError::Rejected {
+                url: actor_url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+        let actor = RemoteActor::from_document(&response.json::<Value>().await?)?;
+        // Otherwise any server could serve a document naming someone else's
+        // actor along with its own key
+        if actor.id != actor_url {
+            return Err(ActivityPubError::InvalidActor(format!(
+                "the document at {actor_url} is for {}",
+                actor.id
+            )));
+        }
+        Ok(actor)
+    }
+
+    fn signed_headers(
+        &self,
+        method: &str,
+        url: &Url,
+        digest: Option<&str>,
+    ) -> Result<reqwest::header::HeaderMap, ActivityPubError> {
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let date = signatures::http_date(Utc::now());
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+
+        let mut names = vec!["(request-target)", "host", "date"];
+        if digest.is_some() {
+            names.push("digest");
+        }
+        let owned = names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        let signing_string = signatures::signing_string(method, &path, &owned, |name| match name {
+            "host" => Some(host.as_str()),
+            "date" => Some(date.as_str()),
+            "digest" => digest,
+            _ => None,
+        })
+        .expect("every signed header has a value");
+        let signature = signatures::sign(&self.key, &self.key_id(), &names, &signing_string)?;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        let value = |value: &str| value.parse().expect("header values are ASCII");
+        headers.insert("host", value(&host));
+        headers.insert("date", value(&date));
+        if let Some(digest) = digest {
+            headers.insert("digest", value(digest));
+        }
+        headers.insert("signature", value(&signature));
+        Ok(headers)
+    }
+
+    /// Checks the signature of a request posted to the inbox, returning the
+    /// actor that signed it
+    pub async fn verify_request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+        validation: &ValidationContext,
+    ) -> Result<RemoteActor, ActivityPubError> {
+        let invalid = |reason: &str| ActivityPubError::InvalidSignature(reason.to_string());
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let signature = header("signature")
+            .and_then(SignatureHeader::parse)
+            .ok_or_else(|| invalid("the request isn't signed"))?;
+        if !signature.covers(&["(request-target)", "host", "date", "digest"]) {
+            return Err(invalid("the target, host, date and digest must be signed"));
+        }
+        if header("digest") != Some(signatures::digest(body).as_str()) {
+            return Err(invalid("the digest doesn't match the body"));
+        }
+        let date = header("date")
+            .and_then(signatures::parse_http_date)
+            .ok_or_else(|| invalid("the date is missing or malformed"))?;
+        if (Utc::now() - date).abs() > MAX_CLOCK_SKEW {
+            return Err(invalid("the date is too far from the current time"));
+        }
+
+        let signing_string = signatures::signing_string(method, path, &signature.headers, header)
+            .ok_or_else(|| invalid("a signed header is missing"))?;
+        let actor_url = signature
+            .key_id
+            .split('#')
+            .next()
+            .unwrap_or(&signature.key_id);
+        let actor = self.fetch_actor(actor_url, validation).await?;
+        if actor.public_key_id != signature.key_id {
+            return Err(invalid("the key isn't the actor's"));
+        }
+        if !signatures::verify(&actor.public_key_pem, &signing_string, &signature.signature) {
+            return Err(invalid("the signature doesn't match"));
+        }
+        Ok(actor)
+    }
+}
+
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>, ActivityPubConfigError> {
+    let key_error = |reason: String| ActivityPubConfigError::Key {
+        path: path.display().to_string(),
+        reason,
+    };
+    match std::fs::read(path) {
+        Ok(pem) => return Ok(pem),
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            return Err(key_error(err.to_string()));
+        }
+        Err(_) => {}
+    }
+
+    let pem = Rsa::generate(KEY_BITS)
+        .and_then(|rsa| rsa.private_key_to_pem())
+        .map_err(|err| key_error(err.to_string()))?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|err| key_error(err.to_string()))?;
+    std::io::Write::write_all(&mut file, &pem).map_err(|err| key_error(err.to_string()))?;
+    info!(path = %path.display(), "Created ActivityPub actor key");
+    Ok(pem)
+}
+
+/// Parses an address to request, rejecting the ones the instance wouldn't
+/// save either
+fn checked_url(url: &str, validation: &ValidationContext) -> Result<Url, ActivityPubError> {
+    let url = Url::parse(url).map_err(|err| ActivityPubError::InvalidActor(err.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ActivityPubError::InvalidActor(format!(
+            "'{url}' is not an http(s) URL"
+        )));
+    }
+    ValidatedUrl::from_url(url.clone(), validation)?;
+    Ok(url)
+}
+
+fn published(created_at: NaiveDateTime) -> String {
+    created_at
+        .and_utc()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// The items published, or `None` if the configured view isn't in this
+/// database
+pub async fn selection<S: AppState>(
+    state: &S,
+    activitypub: &ActivityPub,
+) -> Result<Option<ContentFilter>, ApiError> {
+    match activitypub.view() {
+        Some(view) => federation::selection(state, view).await,
+        None => Ok(Some(ContentFilter::default())),
+    }
+}
+
+/// Whether an item is published
+pub async fn is_published<S: AppState>(
+    state: &S,
+    activitypub: &ActivityPub,
+    item: &ContentItem,
+) -> Result<bool, ApiError> {
+    Ok(selection(state, activitypub).await?.is_some_and(|filter| {
+        federation::selects(
+            &filter,
+            item.content_type.as_deref(),
+            item.source.as_deref(),
+            item.created_at,
+        )
+    }))
+}
+
+/// Delivers a newly saved item to the followers if it is published. Inboxes
+/// that can't be reached only miss the item.
+pub async fn deliver_if_published<S: AppState>(state: &S, item: &ContentItem) {
+    let Some(activitypub) = state.activitypub() else {
+        return;
+    };
+    match is_published(state, activitypub, item).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(err) => {
+            warn!(error = %err, content_id = item.id, "Failed to read the published view");
+            return;
+        }
+    }
+    let inboxes = match state.follower_repo().inboxes().await {
+        Ok(inboxes) => inboxes,
+        Err(err) => {
+            warn!(error = %err, content_id = item.id, "Failed to read followers");
+            return;
+        }
+    };
+
+    let activity = activitypub.create(item);
+    for inbox in &inboxes {
+        if let Err(err) = activitypub
+            .deliver(inbox, &activity, state.validation())
+            .await
+        {
+            warn!(error = %err, inbox, content_id = item.id, "Failed to deliver item");
+        }
+    }
+    debug!(
+        content_id = item.id,
+        inboxes = inboxes.len(),
+        "Published item"
+    );
+}
+
+/// Records a follow by `actor` and accepts it
+pub async fn accept_follow<S: AppState>(
+    state: &S,
+    actor: &RemoteActor,
+    follow: &Value,
+) -> Result<(), ApiError> {
+    let Some(activitypub) = state.activitypub() else {
+        return Err(ApiError::NotFound);
+    };
+    state
+        .follower_repo()
+        .add(&NewFollower {
+            actor_id: actor.id.clone(),
+            inbox_url: actor.inbox.clone(),
+        })
+        .await?;
+    info!(actor = %actor.id, "Gained a follower");
+
+    let accept = activitypub.accept(follow);
+    let (state, inbox) = (state.clone(), actor.inbox.clone());
+    tokio::spawn(async move {
+        if let Some(activitypub) = state.activitypub()
+            && let Err(err) = activitypub
+                .deliver(&inbox, &accept, state.validation())
+                .await
+        {
+            warn!(error = %err, inbox, "Failed to accept follow");
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activitypub() -> ActivityPub {
+        let key = Rsa::generate(KEY_BITS)
+            .unwrap()
+            .private_key_to_pem()
+            .unwrap();
+        ActivityPub::new("https://reading.example.com/", "reading", &key, None).unwrap()
+    }
+
+    #[test]
+    fn test_webfinger_knows_the_actor() {
+        let activitypub = activitypub();
+        assert_eq!(activitypub.handle(), "reading@reading.example.com");
+
+        let document = activitypub
+            .webfinger("acct:reading@reading.example.com")
+            .unwrap();
+        assert_eq!(
+            document["links"][0]["href"],
+            "https://reading.example.com/web/ap/actor"
+        );
+        assert!(
+            activitypub
+                .webfinger("https://reading.example.com/web/ap/actor")
+                .is_some()
+        );
+        assert!(
+            activitypub
+                .webfinger("acct:other@reading.example.com")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_new_validates_settings() {
+        let key = Rsa::generate(KEY_BITS)
+            .unwrap()
+            .private_key_to_pem()
+            .unwrap();
+        assert!(matches!(
+            ActivityPub::new("reading.example.com", "reading", &key, None),
+            Err(ActivityPubConfigError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            ActivityPub::new("https://reading.example.com", "a b", &key, None),
+            Err(ActivityPubConfigError::InvalidUsername(_))
+        ));
+        assert!(matches!(
+            ActivityPub::new("https://reading.example.com", "reading", b"nope", None),
+            Err(ActivityPubConfigError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_remote_actor_prefers_shared_inbox() {
+        let document = json!({
+            "id": "https://social.example/users/ada",
+            "inbox": "https://social.example/users/ada/inbox",
+            "endpoints": { "sharedInbox": "https://social.example/inbox" },
+            "publicKey": {
+                "id": "https://social.example/users/ada#main-key",
+                "owner": "https://social.example/users/ada",
+                "publicKeyPem": "PEM",
+            },
+        });
+        let actor = RemoteActor::from_document(&document).unwrap();
+        assert_eq!(actor.inbox, "https://social.example/inbox");
+
+        let mut impostor = document.clone();
+        impostor["publicKey"]["owner"] = json!("https://social.example/users/eve");
+        assert!(RemoteActor::from_document(&impostor).is_err());
+    }
+}
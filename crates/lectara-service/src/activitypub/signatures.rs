@@ -0,0 +1,185 @@
+//! HTTP Signatures as fediverse servers use them: `rsa-sha256` over the
+//! request target, `host`, `date` and, for requests with a body, `digest`.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private, Public};
+use openssl::sign::{Signer, Verifier};
+use sha2::{Digest, Sha256};
+
+/// A parsed `Signature` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeader {
+    /// URL of the signing key, usually the actor's with a fragment
+    pub key_id: String,
+    /// Names of the signed headers, in signing order
+    pub headers: Vec<String>,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    /// Parses `keyId="...",headers="...",signature="..."`, rejecting
+    /// algorithms other than RSA with SHA-256
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut key_id = None;
+        let mut headers = None;
+        let mut signature = None;
+        for param in value.split(',') {
+            let (name, value) = param.trim().split_once('=')?;
+            let value = value.trim_matches('"');
+            match name {
+                "keyId" => key_id = Some(value.to_string()),
+                "headers" => {
+                    headers = Some(value.split_whitespace().map(str::to_lowercase).collect())
+                }
+                "signature" => signature = Some(STANDARD.decode(value).ok()?),
+                "algorithm" if !matches!(value, "rsa-sha256" | "hs2019") => return None,
+                _ => {}
+            }
+        }
+        Some(Self {
+            key_id: key_id?,
+            // Only the date is signed when the header is left out
+            headers: headers.unwrap_or_else(|| vec!["date".to_string()]),
+            signature: signature?,
+        })
+    }
+
+    /// Whether every header in `required` is signed
+    pub fn covers(&self, required: &[&str]) -> bool {
+        required
+            .iter()
+            .all(|name| self.headers.iter().any(|header| header == name))
+    }
+}
+
+/// The string signed for a request: one `name: value` line per signed
+/// header, `None` if one of them is missing
+pub fn signing_string<'a>(
+    method: &str,
+    path: &str,
+    headers: &[String],
+    value_of: impl Fn(&str) -> Option<&'a str>,
+) -> Option<String> {
+    let lines = headers
+        .iter()
+        .map(|name| match name.as_str() {
+            "(request-target)" => Some(format!(
+                "(request-target): {} {path}",
+                method.to_lowercase()
+            )),
+            name => value_of(name).map(|value| format!("{name}: {}", value.trim())),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(lines.join("\n"))
+}
+
+/// The `Signature` header value signing `signing_string` with `key`
+pub fn sign(
+    key: &PKey<Private>,
+    key_id: &str,
+    headers: &[&str],
+    signing_string: &str,
+) -> Result<String, openssl::error::ErrorStack> {
+    let mut signer = Signer::new(MessageDigest::sha256(), key)?;
+    signer.update(signing_string.as_bytes())?;
+    let signature = STANDARD.encode(signer.sign_to_vec()?);
+    Ok(format!(
+        "keyId=\"{key_id}\",algorithm=\"rsa-sha256\",headers=\"{}\",signature=\"{signature}\"",
+        headers.join(" ")
+    ))
+}
+
+/// Whether `signature` signs `signing_string` with the key in `public_key_pem`
+pub fn verify(public_key_pem: &str, signing_string: &str, signature: &[u8]) -> bool {
+    let verified = || -> Result<bool, openssl::error::ErrorStack> {
+        let key: PKey<Public> = PKey::public_key_from_pem(public_key_pem.as_bytes())?;
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key)?;
+        verifier.update(signing_string.as_bytes())?;
+        verifier.verify(signature)
+    };
+    verified().unwrap_or(false)
+}
+
+/// The `Digest` header value of a request body
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// A time as an HTTP date, e.g. `Tue, 02 Sep 2025 09:00:00 GMT`
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an HTTP date
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.to_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::rsa::Rsa;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let public_key_pem = String::from_utf8(key.public_key_to_pem().unwrap()).unwrap();
+        let headers = ["(request-target)", "host", "date"];
+        let value_of = |name: &str| match name {
+            "host" => Some("reading.example.com"),
+            "date" => Some("Tue, 02 Sep 2025 09:00:00 GMT"),
+            _ => None,
+        };
+        let names = headers.map(str::to_string);
+        let signed = signing_string("POST", "/web/ap/inbox", &names, value_of).unwrap();
+        assert_eq!(
+            signed,
+            "(request-target): post /web/ap/inbox\nhost: reading.example.com\ndate: Tue, 02 Sep 2025 09:00:00 GMT"
+        );
+
+        let header = sign(
+            &key,
+            "https://example.com/actor#main-key",
+            &headers,
+            &signed,
+        )
+        .unwrap();
+        let parsed = SignatureHeader::parse(&header).unwrap();
+        assert_eq!(parsed.key_id, "https://example.com/actor#main-key");
+        assert!(parsed.covers(&["(request-target)", "date"]));
+        assert!(!parsed.covers(&["digest"]));
+        assert!(verify(&public_key_pem, &signed, &parsed.signature));
+        assert!(!verify(
+            &public_key_pem,
+            &signed.replace("inbox", "outbox"),
+            &parsed.signature
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_other_algorithms() {
+        assert!(
+            SignatureHeader::parse(r#"keyId="k",algorithm="hmac-sha256",signature="AAAA""#)
+                .is_none()
+        );
+        assert!(SignatureHeader::parse(r#"keyId="k""#).is_none());
+        assert_eq!(
+            SignatureHeader::parse(r#"keyId="k",signature="AAAA""#)
+                .unwrap()
+                .headers,
+            ["date"]
+        );
+    }
+
+    #[test]
+    fn test_http_date_round_trip() {
+        let time = DateTime::from_timestamp(1_756_803_600, 0).unwrap();
+        assert_eq!(http_date(time), "Tue, 02 Sep 2025 09:00:00 GMT");
+        assert_eq!(parse_http_date(&http_date(time)), Some(time));
+    }
+}
@@ -0,0 +1,110 @@
+//! Full-page archives of saved items.
+//!
+//! Capturing fetches an item's page and keeps the response as the parts of
+//! a WARC record (see [`warc`]). In [`ArchiveMode::SingleFile`] the page's
+//! resources are inlined first (see [`single_file`]), so the record holds a
+//! self-contained HTML file. Payloads are stored by SHA-256, so capturing an
+//! unchanged page again only adds its headers.
+
+use chrono::Utc;
+use reqwest::header::{self, HeaderValue};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{ArchiveSnapshot, ContentItem, NewArchiveSnapshot};
+use crate::repositories::ArchiveSnapshotRepository;
+
+pub mod single_file;
+pub mod warc;
+
+/// How a page is archived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchiveMode {
+    /// The response as fetched
+    #[default]
+    Warc,
+    /// The page with its images, stylesheets and scripts inlined
+    SingleFile,
+}
+
+impl ArchiveMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warc => "warc",
+            Self::SingleFile => "single-file",
+        }
+    }
+}
+
+/// Whether `content_type` is an HTML document whose resources can be inlined
+fn is_html(content_type: &str) -> bool {
+    matches!(content_type, "text/html" | "application/xhtml+xml")
+}
+
+/// Fetches `item`'s page and stores it as a new snapshot
+#[instrument(skip_all, fields(content_id = item.id, mode = mode.as_str()))]
+pub async fn capture<S: AppState>(
+    state: &S,
+    item: &ContentItem,
+    mode: ArchiveMode,
+) -> Result<ArchiveSnapshot, ApiError> {
+    let document = match state.fetcher().fetch(&item.url, |_| true).await {
+        Ok(Some(document)) => document,
+        Ok(None) => unreachable!("every content type is accepted"),
+        Err(err) => {
+            debug!(error = %err, "Page could not be fetched");
+            return Err(ApiError::BadGateway(format!(
+                "The page could not be fetched: {err}"
+            )));
+        }
+    };
+
+    let mut headers = document.headers;
+    let payload = match mode {
+        ArchiveMode::Warc => document.bytes,
+        ArchiveMode::SingleFile => {
+            if !is_html(&document.content_type) {
+                return Err(ApiError::BadRequest(format!(
+                    "Only HTML pages can be archived as a single file, not {}",
+                    document.content_type
+                )));
+            }
+            // The copy is written as UTF-8 whatever the page was served in
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            let html = String::from_utf8_lossy(&document.bytes);
+            single_file::inline(state.fetcher(), state.validation(), &document.url, &html)
+                .await
+                .into_bytes()
+        }
+    };
+
+    let snapshot = NewArchiveSnapshot {
+        content_id: item.id,
+        record_id: warc::record_id(),
+        http_headers: warc::http_header_block(document.status, &headers, payload.len()),
+        target_uri: document.url,
+        content_type: document.content_type,
+        payload_digest: hex::encode(Sha256::digest(&payload)),
+        payload_length: payload.len() as i32,
+        captured_at: Utc::now().naive_utc(),
+        mode: mode.as_str().to_string(),
+    };
+    let snapshot = state
+        .archive_snapshot_repo()
+        .create(&snapshot, &payload)
+        .await?;
+
+    info!(
+        snapshot_id = snapshot.id,
+        bytes = snapshot.payload_length,
+        "Captured page"
+    );
+    Ok(snapshot)
+}
@@ -0,0 +1,450 @@
+//! Self-contained HTML copies of archived pages, in the manner of monolith.
+//!
+//! The images, stylesheets, scripts and icons a page references are
+//! downloaded and inlined, as `data:` URLs or as the contents of `<style>`
+//! and `<script>` elements, so the copy renders the same offline. URLs in
+//! stylesheets are inlined too. Resources that can't be fetched, or that
+//! URL validation rejects, keep pointing at their original URLs.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures_util::{StreamExt, stream};
+use lol_html::html_content::ContentType;
+use lol_html::{RewriteStrSettings, element, rewrite_str, text};
+use tracing::debug;
+use url::Url;
+
+use crate::enrichment::fetch::Fetcher;
+use crate::validation::{ValidatedUrl, ValidationContext};
+
+/// Most resources fetched for one page
+const MAX_RESOURCES: usize = 200;
+/// Resources fetched at the same time
+const CONCURRENT_FETCHES: usize = 8;
+
+/// A downloaded resource
+struct Resource {
+    content_type: String,
+    bytes: Vec<u8>,
+}
+
+impl Resource {
+    fn data_url(&self) -> String {
+        let content_type = if self.content_type.is_empty() {
+            "application/octet-stream"
+        } else {
+            &self.content_type
+        };
+        format!("data:{content_type};base64,{}", BASE64.encode(&self.bytes))
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+}
+
+type Resources = HashMap<Url, Resource>;
+
+/// `html`, served from `page_url`, with its resources inlined
+pub async fn inline(
+    fetcher: &Fetcher,
+    validation: &ValidationContext,
+    page_url: &str,
+    html: &str,
+) -> String {
+    let Ok(base) = Url::parse(page_url) else {
+        return html.to_string();
+    };
+
+    let mut resources = Resources::new();
+    let references = page_references(html, &base);
+    fetch_all(fetcher, validation, references, &mut resources).await;
+
+    // Stylesheets reference fonts and images of their own
+    let nested: Vec<Url> = resources
+        .iter()
+        .filter(|(_, resource)| resource.content_type == "text/css")
+        .flat_map(|(url, resource)| css_urls(&resource.text(), url))
+        .collect();
+    fetch_all(fetcher, validation, nested, &mut resources).await;
+
+    rewrite_page(html, &base, &resources)
+}
+
+async fn fetch_all(
+    fetcher: &Fetcher,
+    validation: &ValidationContext,
+    urls: Vec<Url>,
+    resources: &mut Resources,
+) {
+    let mut wanted: Vec<Url> = Vec::new();
+    for url in urls {
+        if resources.len() + wanted.len() >= MAX_RESOURCES {
+            debug!("Resource limit reached, leaving the rest linked");
+            break;
+        }
+        if resources.contains_key(&url)
+            || wanted.contains(&url)
+            || ValidatedUrl::from_url(url.clone(), validation).is_err()
+        {
+            continue;
+        }
+        wanted.push(url);
+    }
+
+    let fetched: Vec<(Url, Option<Resource>)> = stream::iter(wanted)
+        .map(|url| async move {
+            let resource = match fetcher.fetch(url.as_str(), |_| true).await {
+                Ok(document) => document.map(|document| Resource {
+                    content_type: document.content_type,
+                    bytes: document.bytes,
+                }),
+                Err(err) => {
+                    debug!(error = %err, %url, "Resource could not be fetched");
+                    None
+                }
+            };
+            (url, resource)
+        })
+        .buffer_unordered(CONCURRENT_FETCHES)
+        .collect()
+        .await;
+    for (url, resource) in fetched {
+        if let Some(resource) = resource {
+            resources.insert(url, resource);
+        }
+    }
+}
+
+/// Whether a `<link>` with this `rel` is a stylesheet or an icon
+fn is_inlined_link(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("stylesheet") || rel.eq_ignore_ascii_case("icon"))
+}
+
+fn is_stylesheet(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+}
+
+/// The resources `html` references, in document order
+fn page_references(html: &str, base: &Url) -> Vec<Url> {
+    let urls = RefCell::new(Vec::new());
+    let style = RefCell::new(String::new());
+    let push = |value: Option<String>| {
+        if let Some(url) = value.and_then(|value| base.join(value.trim()).ok()) {
+            urls.borrow_mut().push(url);
+        }
+    };
+
+    let result = rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: vec![
+                element!("img[src], script[src]", |el| {
+                    push(el.get_attribute("src"));
+                    Ok(())
+                }),
+                element!("link[href]", |el| {
+                    if el
+                        .get_attribute("rel")
+                        .is_some_and(|rel| is_inlined_link(&rel))
+                    {
+                        push(el.get_attribute("href"));
+                    }
+                    Ok(())
+                }),
+                element!("[style]", |el| {
+                    let css = el.get_attribute("style").unwrap_or_default();
+                    urls.borrow_mut().extend(css_urls(&css, base));
+                    Ok(())
+                }),
+                text!("style", |chunk| {
+                    style.borrow_mut().push_str(chunk.as_str());
+                    if chunk.last_in_text_node() {
+                        let css = std::mem::take(&mut *style.borrow_mut());
+                        urls.borrow_mut().extend(css_urls(&css, base));
+                    }
+                    Ok(())
+                }),
+            ],
+            ..RewriteStrSettings::new()
+        },
+    );
+    if let Err(err) = result {
+        debug!(error = %err, "Page could not be parsed for resources");
+    }
+    urls.into_inner()
+}
+
+fn rewrite_page(html: &str, base: &Url, resources: &Resources) -> String {
+    let find = |value: Option<String>| {
+        value
+            .and_then(|value| base.join(value.trim()).ok())
+            .and_then(|url| resources.get(&url))
+    };
+    let style = RefCell::new(String::new());
+
+    let result = rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: vec![
+                element!("img[src]", |el| {
+                    if let Some(resource) = find(el.get_attribute("src")) {
+                        el.set_attribute("src", &resource.data_url())?;
+                        // Other sizes would still be loaded from the site
+                        el.remove_attribute("srcset");
+                    }
+                    Ok(())
+                }),
+                element!("script[src]", |el| {
+                    if let Some(resource) = find(el.get_attribute("src")) {
+                        el.remove_attribute("src");
+                        el.remove_attribute("integrity");
+                        el.remove_attribute("crossorigin");
+                        let script = escape_closing_tag(&resource.text(), "script");
+                        el.set_inner_content(&script, ContentType::Html);
+                    }
+                    Ok(())
+                }),
+                element!("link[href]", |el| {
+                    let Some(rel) = el.get_attribute("rel").filter(|rel| is_inlined_link(rel))
+                    else {
+                        return Ok(());
+                    };
+                    let Some(href) = el.get_attribute("href") else {
+                        return Ok(());
+                    };
+                    let Some(url) = base.join(href.trim()).ok() else {
+                        return Ok(());
+                    };
+                    let Some(resource) = resources.get(&url) else {
+                        return Ok(());
+                    };
+                    if is_stylesheet(&rel) {
+                        let css = rewrite_css(&resource.text(), &url, resources);
+                        let media = el
+                            .get_attribute("media")
+                            .map(|media| format!(" media=\"{}\"", escape_attribute(&media)))
+                            .unwrap_or_default();
+                        el.replace(
+                            &format!(
+                                "<style{media}>{}</style>",
+                                escape_closing_tag(&css, "style")
+                            ),
+                            ContentType::Html,
+                        );
+                    } else {
+                        el.set_attribute("href", &resource.data_url())?;
+                    }
+                    Ok(())
+                }),
+                element!("[style]", |el| {
+                    let css = el.get_attribute("style").unwrap_or_default();
+                    el.set_attribute("style", &rewrite_css(&css, base, resources))?;
+                    Ok(())
+                }),
+                text!("style", |chunk| {
+                    style.borrow_mut().push_str(chunk.as_str());
+                    if chunk.last_in_text_node() {
+                        let css = std::mem::take(&mut *style.borrow_mut());
+                        let css = rewrite_css(&css, base, resources);
+                        chunk.replace(&escape_closing_tag(&css, "style"), ContentType::Html);
+                    } else {
+                        chunk.remove();
+                    }
+                    Ok(())
+                }),
+            ],
+            ..RewriteStrSettings::new()
+        },
+    );
+    result.unwrap_or_else(|err| {
+        debug!(error = %err, "Page could not be rewritten, keeping it as fetched");
+        html.to_string()
+    })
+}
+
+/// The `url(...)` references in `css`: the range each covers and the URL
+fn css_url_spans(css: &str) -> Vec<(Range<usize>, &str)> {
+    let mut spans = Vec::new();
+    let lower = css.to_ascii_lowercase();
+    let mut position = 0;
+    while let Some(offset) = lower[position..].find("url(") {
+        let start = position + offset;
+        let mut cursor = start + "url(".len();
+        cursor += css[cursor..].len() - css[cursor..].trim_start().len();
+
+        let (value, end) = match css[cursor..].chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let value_start = cursor + 1;
+                let Some(length) = css[value_start..].find(quote) else {
+                    break;
+                };
+                let value_end = value_start + length;
+                let Some(close) = css[value_end + 1..].find(')') else {
+                    break;
+                };
+                (&css[value_start..value_end], value_end + 1 + close + 1)
+            }
+            _ => {
+                let Some(length) = css[cursor..].find(')') else {
+                    break;
+                };
+                (css[cursor..cursor + length].trim(), cursor + length + 1)
+            }
+        };
+        spans.push((start..end, value));
+        position = end;
+    }
+    spans
+}
+
+fn css_urls(css: &str, base: &Url) -> Vec<Url> {
+    css_url_spans(css)
+        .into_iter()
+        .filter_map(|(_, value)| base.join(value).ok())
+        .collect()
+}
+
+/// `css` with the URLs it references that were fetched inlined
+fn rewrite_css(css: &str, base: &Url, resources: &Resources) -> String {
+    let mut rewritten = String::with_capacity(css.len());
+    let mut position = 0;
+    for (range, value) in css_url_spans(css) {
+        let Some(resource) = base.join(value).ok().and_then(|url| resources.get(&url)) else {
+            continue;
+        };
+        rewritten.push_str(&css[position..range.start]);
+        rewritten.push_str(&format!("url(\"{}\")", resource.data_url()));
+        position = range.end;
+    }
+    rewritten.push_str(&css[position..]);
+    rewritten
+}
+
+/// `text` made safe inside a `<tag>` element, whose contents end at the
+/// first `</tag`
+fn escape_closing_tag(text: &str, tag: &str) -> String {
+    let closing = format!("</{tag}");
+    let lower = text.to_ascii_lowercase();
+    let mut escaped = String::with_capacity(text.len());
+    let mut position = 0;
+    while let Some(offset) = lower[position..].find(&closing) {
+        let start = position + offset;
+        escaped.push_str(&text[position..start]);
+        escaped.push_str("<\\/");
+        position = start + 2;
+    }
+    escaped.push_str(&text[position..]);
+    escaped
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/posts/1").unwrap()
+    }
+
+    fn resource(content_type: &str, body: &str) -> Resource {
+        Resource {
+            content_type: content_type.to_string(),
+            bytes: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_css_url_spans() {
+        let css =
+            r#"a { background: URL( "a.png" ) } b { src: url('b.woff') } c { x: url(c.svg) }"#;
+        let values: Vec<&str> = css_url_spans(css).into_iter().map(|(_, v)| v).collect();
+        assert_eq!(values, ["a.png", "b.woff", "c.svg"]);
+
+        // Unterminated references are ignored
+        assert!(css_url_spans("a { background: url(\"a.png) }").is_empty());
+    }
+
+    #[test]
+    fn test_page_references_resolve_against_page() {
+        let html = r#"<link rel="icon" href="/favicon.ico"><link rel="canonical" href="/x">
+            <link rel="stylesheet" href="site.css"><style>p { background: url(bg.png) }</style>
+            <img src="https://cdn.example.com/a.png"><script src="app.js"></script>
+            <div style="background: url('/hero.jpg')"></div>"#;
+        let urls: Vec<String> = page_references(html, &base())
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/favicon.ico",
+                "https://example.com/posts/site.css",
+                "https://example.com/posts/bg.png",
+                "https://cdn.example.com/a.png",
+                "https://example.com/posts/app.js",
+                "https://example.com/hero.jpg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_page_inlines_fetched_resources() {
+        let mut resources = Resources::new();
+        resources.insert(
+            Url::parse("https://example.com/posts/site.css").unwrap(),
+            resource("text/css", "body { background: url(img/bg.gif) }"),
+        );
+        resources.insert(
+            Url::parse("https://example.com/posts/img/bg.gif").unwrap(),
+            resource("image/gif", "GIF"),
+        );
+        resources.insert(
+            Url::parse("https://example.com/posts/app.js").unwrap(),
+            resource("text/javascript", "document.write('</script>')"),
+        );
+
+        let html = r#"<link rel="stylesheet" media="screen" href="site.css"><script src="app.js" integrity="sha384-x"></script><img src="img/bg.gif" srcset="big.gif 2x"><img src="missing.png">"#;
+        assert_eq!(
+            rewrite_page(html, &base(), &resources),
+            "<style media=\"screen\">body { background: url(\"data:image/gif;base64,R0lG\") }</style>\
+             <script>document.write('<\\/script>')</script>\
+             <img src=\"data:image/gif;base64,R0lG\">\
+             <img src=\"missing.png\">"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_page_inlines_style_elements() {
+        let mut resources = Resources::new();
+        resources.insert(
+            Url::parse("https://example.com/bg.png").unwrap(),
+            resource("image/png", "PNG"),
+        );
+
+        let html =
+            "<style>p { background: url(/bg.png) }</style><p style=\"background: url('/bg.png')\">";
+        assert_eq!(
+            rewrite_page(html, &base(), &resources),
+            "<style>p { background: url(\"data:image/png;base64,UE5H\") }</style>\
+             <p style=\"background: url(&quot;data:image/png;base64,UE5H&quot;)\">"
+        );
+    }
+
+    #[test]
+    fn test_escape_closing_tag() {
+        assert_eq!(
+            escape_closing_tag("a</SCRIPT>b</script", "script"),
+            "a<\\/SCRIPT>b<\\/script"
+        );
+    }
+}
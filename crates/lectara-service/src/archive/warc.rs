@@ -0,0 +1,180 @@
+//! WARC 1.1 files of archived pages.
+//!
+//! A capture is written as a `response` record holding the HTTP status
+//! line, headers and payload, after a `warcinfo` record naming the
+//! software, so replay tools such as pywb can read the file directly.
+//! Payloads are kept decoded, so the headers stored with them drop the
+//! transfer encoding and state the payload's real length.
+
+use reqwest::StatusCode;
+use reqwest::header::{self, HeaderMap};
+
+use crate::models::ArchiveSnapshot;
+
+/// Media type of WARC files
+pub const WARC_MEDIA_TYPE: &str = "application/warc";
+
+const WARC_VERSION: &str = "WARC/1.1";
+
+/// Status line and headers of a response, as kept with its capture
+pub fn http_header_block(
+    status: StatusCode,
+    headers: &HeaderMap,
+    payload_length: usize,
+) -> Vec<u8> {
+    let mut block = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    )
+    .into_bytes();
+    for (name, value) in headers {
+        if name == header::TRANSFER_ENCODING || name == header::CONTENT_LENGTH {
+            continue;
+        }
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b": ");
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+    block.extend_from_slice(format!("Content-Length: {payload_length}\r\n").as_bytes());
+    block
+}
+
+/// A new random `urn:uuid:` record identifier
+pub fn record_id() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    // Version 4, RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The capture of `snapshot` with its `payload` as a WARC file
+pub fn render(snapshot: &ArchiveSnapshot, payload: &[u8]) -> Vec<u8> {
+    let date = snapshot
+        .captured_at
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let warcinfo_id = record_id();
+    let info = format!(
+        "software: lectara/{}\r\nformat: WARC File Format 1.1\r\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    let payload_digest = format!("sha256:{}", snapshot.payload_digest);
+
+    let mut file = Vec::with_capacity(payload.len() + 1024);
+    write_record(
+        &mut file,
+        &[
+            ("WARC-Type", "warcinfo"),
+            ("WARC-Record-ID", &format!("<{warcinfo_id}>")),
+            ("WARC-Date", &date),
+            ("Content-Type", "application/warc-fields"),
+        ],
+        &[info.as_bytes()],
+    );
+    write_record(
+        &mut file,
+        &[
+            ("WARC-Type", "response"),
+            ("WARC-Record-ID", &format!("<{}>", snapshot.record_id)),
+            ("WARC-Warcinfo-ID", &format!("<{warcinfo_id}>")),
+            ("WARC-Date", &date),
+            ("WARC-Target-URI", &snapshot.target_uri),
+            ("WARC-Payload-Digest", &payload_digest),
+            ("WARC-Identified-Payload-Type", &snapshot.content_type),
+            ("Content-Type", "application/http;msgtype=response"),
+        ],
+        &[&snapshot.http_headers, b"\r\n", payload],
+    );
+    file
+}
+
+fn write_record(file: &mut Vec<u8>, fields: &[(&str, &str)], block: &[&[u8]]) {
+    file.extend_from_slice(WARC_VERSION.as_bytes());
+    file.extend_from_slice(b"\r\n");
+    for (name, value) in fields {
+        file.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+    }
+    let length: usize = block.iter().map(|part| part.len()).sum();
+    file.extend_from_slice(format!("Content-Length: {length}\r\n\r\n").as_bytes());
+    for part in block {
+        file.extend_from_slice(part);
+    }
+    // Records are separated by two newlines
+    file.extend_from_slice(b"\r\n\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_header_block_states_decoded_length() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        headers.insert(
+            header::TRANSFER_ENCODING,
+            HeaderValue::from_static("chunked"),
+        );
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("3"));
+
+        let block = http_header_block(StatusCode::OK, &headers, 12);
+        assert_eq!(
+            String::from_utf8(block).unwrap(),
+            "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\nContent-Length: 12\r\n"
+        );
+    }
+
+    #[test]
+    fn test_record_id_is_uuid_v4() {
+        let id = record_id();
+        let uuid = id.strip_prefix("urn:uuid:").unwrap();
+        let groups: Vec<usize> = uuid.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(record_id(), id);
+    }
+
+    #[test]
+    fn test_render_writes_warcinfo_and_response() {
+        let snapshot = ArchiveSnapshot {
+            id: 1,
+            content_id: 1,
+            record_id: "urn:uuid:00000000-0000-4000-8000-000000000000".to_string(),
+            target_uri: "https://example.com/".to_string(),
+            content_type: "text/html".to_string(),
+            http_headers: b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n".to_vec(),
+            payload_digest: "abc".to_string(),
+            payload_length: 5,
+            captured_at: DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
+            mode: "warc".to_string(),
+        };
+        let file = String::from_utf8(render(&snapshot, b"hello")).unwrap();
+
+        let records: Vec<&str> = file.split_terminator("\r\n\r\nWARC/1.1\r\n").collect();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].starts_with("WARC/1.1\r\nWARC-Type: warcinfo\r\n"));
+        assert!(records[1].starts_with(
+            "WARC-Type: response\r\n\
+             WARC-Record-ID: <urn:uuid:00000000-0000-4000-8000-000000000000>\r\n"
+        ));
+        assert!(records[1].contains("WARC-Date: 1970-01-01T00:00:00Z\r\n"));
+        assert!(records[1].contains("WARC-Payload-Digest: sha256:abc\r\n"));
+        assert!(file.ends_with(
+            "Content-Length: 43\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello\r\n\r\n"
+        ));
+    }
+}
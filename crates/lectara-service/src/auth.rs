@@ -0,0 +1,307 @@
+//! API keys and public access.
+//!
+//! Once keys are configured, requests must send one as
+//! `Authorization: Bearer <key>`, or as the password of HTTP Basic auth for
+//! clients such as e-readers that only support that. A public collection additionally lets
+//! anyone list, get and search its items, so it can double as a "what I'm
+//! reading" site while writes still need a key. Health checks, share pages
+//! and the web assets they load never need a key, and neither does the
+//! version endpoint clients check compatibility with. Without keys
+//! everything stays open, as before.
+//!
+//! Keys are only ever stored as SHA-256 hex digests.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use http::{HeaderMap, Method, header};
+use sha2::{Digest, Sha256};
+
+use crate::errors::ApiError;
+
+/// Comma-separated SHA-256 hex digests of the keys accepted by a
+/// single-tenant instance
+pub const API_KEY_HASHES_ENV: &str = "LECTARA_API_KEY_HASHES";
+/// Set to `true` to let anyone read the collection without a key
+pub const PUBLIC_READS_ENV: &str = "LECTARA_PUBLIC_READS";
+
+/// Routes anyone may `GET` from a public collection
+const PUBLIC_READ_ROUTES: &[&str] = &[
+    "/api/v1/content",
+    "/api/v1/content/count",
+    "/api/v1/content/pins",
+    "/api/v1/content/{id}",
+    "/api/v1/search",
+    "/web",
+    "/web/manifest.webmanifest",
+    "/web/sw.js",
+    "/web/read/{id}",
+    "/web/search",
+    "/web/opds",
+];
+
+/// Route prefixes that never need a key
+const OPEN_PREFIXES: &[&str] = &[
+    "/health",
+    "/ready",
+    "/api/v1/version",
+    "/web/share/",
+    "/web/assets/",
+    "/web/theme",
+    "/web/.well-known/",
+    "/web/ap/",
+];
+
+/// SHA-256 hex digest of `api_key`, the form keys are configured in
+pub fn hash_api_key(api_key: &str) -> String {
+    hex::encode(Sha256::digest(api_key.as_bytes()))
+}
+
+/// Normalizes a configured key digest, or `None` if it isn't a SHA-256 hex
+/// digest
+pub fn parse_key_hash(key_hash: &str) -> Option<String> {
+    let key_hash = key_hash.trim().to_lowercase();
+    (key_hash.len() == 64 && key_hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(key_hash)
+}
+
+/// The key sent as `Authorization: Bearer <key>`, if any
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// The key sent as a bearer token or as the password of
+/// `Authorization: Basic`, whose user name is ignored
+pub fn api_key(headers: &HeaderMap) -> Option<Cow<'_, str>> {
+    if let Some(token) = bearer_token(headers) {
+        return Some(Cow::Borrowed(token));
+    }
+    let credentials = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let credentials = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (_, password) = credentials.split_once(':')?;
+    Some(Cow::Owned(password.to_string()))
+}
+
+/// Who may use one collection's routes
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    key_hashes: Arc<HashSet<String>>,
+    public_reads: bool,
+}
+
+impl AccessPolicy {
+    pub fn new(key_hashes: impl IntoIterator<Item = String>, public_reads: bool) -> Self {
+        Self {
+            key_hashes: Arc::new(key_hashes.into_iter().collect()),
+            public_reads,
+        }
+    }
+
+    /// Reads [`API_KEY_HASHES_ENV`] and [`PUBLIC_READS_ENV`], failing on a
+    /// malformed digest
+    pub fn from_env() -> Result<Self, String> {
+        let key_hashes = std::env::var(API_KEY_HASHES_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .filter(|key_hash| !key_hash.trim().is_empty())
+            .map(|key_hash| {
+                parse_key_hash(key_hash)
+                    .ok_or_else(|| format!("{API_KEY_HASHES_ENV} must list SHA-256 hex digests"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let public_reads =
+            std::env::var(PUBLIC_READS_ENV).is_ok_and(|value| matches!(value.trim(), "1" | "true"));
+        Ok(Self::new(key_hashes, public_reads))
+    }
+
+    /// Whether any key is required at all
+    pub fn requires_keys(&self) -> bool {
+        !self.key_hashes.is_empty()
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.public_reads
+    }
+
+    /// Rejects requests to `router` that this policy doesn't allow. The
+    /// policy sees route patterns, so it must be applied to the complete
+    /// router.
+    pub fn protect<S: Clone + Send + Sync + 'static>(self, router: Router<S>) -> Router<S> {
+        router.layer(middleware::from_fn_with_state(self, enforce))
+    }
+
+    fn check(
+        &self,
+        method: &Method,
+        route: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<(), ApiError> {
+        if !self.requires_keys() {
+            return Ok(());
+        }
+        if let Some(api_key) = api_key {
+            return if self.key_hashes.contains(&hash_api_key(api_key)) {
+                Ok(())
+            } else {
+                Err(ApiError::Unauthorized("Unknown API key".to_string()))
+            };
+        }
+
+        let route = route.unwrap_or_default();
+        let open = OPEN_PREFIXES.iter().any(|prefix| route.starts_with(prefix));
+        let public_read = self.public_reads
+            && (method == Method::GET || method == Method::HEAD)
+            && PUBLIC_READ_ROUTES.contains(&route);
+        if open || public_read {
+            Ok(())
+        } else {
+            Err(ApiError::Unauthorized("An API key is required".to_string()))
+        }
+    }
+}
+
+async fn enforce(State(policy): State<AccessPolicy>, request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    let api_key = api_key(request.headers());
+    match policy.check(request.method(), route, api_key.as_deref()) {
+        Ok(()) => next.run(request).await,
+        Err(err) => err.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(public_reads: bool) -> AccessPolicy {
+        AccessPolicy::new([hash_api_key("secret")], public_reads)
+    }
+
+    #[test]
+    fn test_keys_required_once_configured() {
+        let open = AccessPolicy::default();
+        assert!(
+            open.check(&Method::POST, Some("/api/v1/content"), None)
+                .is_ok()
+        );
+
+        let private = policy(false);
+        assert!(
+            private
+                .check(&Method::GET, Some("/api/v1/content"), None)
+                .is_err()
+        );
+        assert!(
+            private
+                .check(&Method::GET, Some("/api/v1/content"), Some("guess"))
+                .is_err()
+        );
+        assert!(
+            private
+                .check(&Method::POST, Some("/api/v1/content"), Some("secret"))
+                .is_ok()
+        );
+        assert!(private.check(&Method::GET, Some("/health"), None).is_ok());
+        assert!(
+            private
+                .check(&Method::GET, Some("/api/v1/version"), None)
+                .is_ok()
+        );
+        assert!(
+            private
+                .check(&Method::POST, Some("/web/share/{token}"), None)
+                .is_ok()
+        );
+        assert!(
+            private
+                .check(&Method::GET, Some("/web/assets/{file}"), None)
+                .is_ok()
+        );
+        assert!(private.check(&Method::GET, None, None).is_err());
+    }
+
+    #[test]
+    fn test_public_reads() {
+        let public = policy(true);
+        assert!(
+            public
+                .check(&Method::GET, Some("/api/v1/content/{id}"), None)
+                .is_ok()
+        );
+        assert!(
+            public
+                .check(&Method::GET, Some("/api/v1/search"), None)
+                .is_ok()
+        );
+        assert!(
+            public
+                .check(&Method::POST, Some("/api/v1/content"), None)
+                .is_err()
+        );
+        // Share tokens grant access to items, so listing them stays private
+        assert!(
+            public
+                .check(&Method::GET, Some("/api/v1/content/{id}/share"), None)
+                .is_err()
+        );
+        assert!(
+            public
+                .check(&Method::GET, Some("/api/v1/admin/read-only"), None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_api_key_from_bearer_or_basic() {
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+            headers
+        };
+        assert_eq!(
+            api_key(&headers("Bearer secret")).as_deref(),
+            Some("secret")
+        );
+        // printf %s reader:secret | base64
+        assert_eq!(
+            api_key(&headers("Basic cmVhZGVyOnNlY3JldA==")).as_deref(),
+            Some("secret")
+        );
+        assert_eq!(api_key(&headers("Basic not-base64")), None);
+        assert_eq!(api_key(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_hash_api_key() {
+        // printf %s bob-key | sha256sum
+        assert_eq!(
+            hash_api_key("bob-key"),
+            "9b94dc1a51a38769f135edf04033ad7f2f487b6c25929be7a861cfc1ab10cf98"
+        );
+    }
+
+    #[test]
+    fn test_parse_key_hash() {
+        let digest = hash_api_key("secret");
+        assert_eq!(parse_key_hash(&digest.to_uppercase()), Some(digest));
+        assert_eq!(parse_key_hash("secret"), None);
+    }
+}
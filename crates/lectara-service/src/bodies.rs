@@ -0,0 +1,240 @@
+//! Content-addressed body storage.
+//!
+//! Bodies live in `body_blobs` keyed by the hex SHA-256 of their text, so an
+//! article saved from several URLs is stored once. Each blob counts the
+//! content items referencing it and is deleted when the last one lets go.
+//!
+//! HTML bodies also keep their plain text, which search indexes and which
+//! is used wherever words are counted or shown without markup. Since a
+//! changed body is a new blob, the text never goes stale.
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use scraper::{ElementRef, Html, Node};
+use sha2::{Digest, Sha256};
+
+use crate::schema::{body_blobs, content_bodies, content_items};
+
+/// Elements whose contents are never visible text
+const HIDDEN_ELEMENTS: &[&str] = &["head", "noscript", "script", "style", "template"];
+
+/// Elements that start a new paragraph of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Hex-encoded SHA-256 of `body`, used as its storage key
+pub fn body_hash(body: &str) -> String {
+    hex::encode(Sha256::digest(body.as_bytes()))
+}
+
+/// Whether `body` has markup, judged by it closing at least one tag
+fn is_html(body: &str) -> bool {
+    body.match_indices("</")
+        .any(|(start, _)| body[start + 2..].starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// Plain text of an HTML body, with a blank line between paragraphs, or
+/// `None` if `body` isn't HTML
+pub fn plain_text(body: &str) -> Option<String> {
+    if !is_html(body) {
+        return None;
+    }
+
+    let fragment = Html::parse_fragment(body);
+    let mut text = String::with_capacity(body.len() / 2);
+    push_text(fragment.root_element(), &mut text);
+
+    // Blank lines from nested blocks collapse into one
+    let mut paragraphs = Vec::new();
+    for paragraph in text.split("\n\n") {
+        let lines: Vec<&str> = paragraph
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if !lines.is_empty() {
+            paragraphs.push(lines.join("\n"));
+        }
+    }
+    Some(paragraphs.join("\n\n"))
+}
+
+fn push_text(element: ElementRef<'_>, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(fragment) => {
+                // Source formatting isn't visible, so whitespace collapses
+                let mut words = fragment.split_whitespace().peekable();
+                if fragment.starts_with(char::is_whitespace) && words.peek().is_some() {
+                    text.push(' ');
+                }
+                while let Some(word) = words.next() {
+                    text.push_str(word);
+                    if words.peek().is_some() || fragment.ends_with(char::is_whitespace) {
+                        text.push(' ');
+                    }
+                }
+            }
+            Node::Element(element) => {
+                let name = element.name();
+                if HIDDEN_ELEMENTS.contains(&name) {
+                    continue;
+                }
+                if name == "br" {
+                    text.push('\n');
+                    continue;
+                }
+                let block = BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    text.push_str("\n\n");
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    push_text(child, text);
+                }
+                if block {
+                    text.push_str("\n\n");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Stores `body` with its plain text, or takes another reference to an
+/// identical stored body, returning its hash
+pub fn store_body(conn: &mut SqliteConnection, body: &str) -> QueryResult<String> {
+    let hash = body_hash(body);
+
+    diesel::insert_into(body_blobs::table)
+        .values((
+            body_blobs::hash.eq(&hash),
+            body_blobs::body.eq(body),
+            body_blobs::ref_count.eq(1),
+            body_blobs::text.eq(plain_text(body)),
+        ))
+        .on_conflict(body_blobs::hash)
+        .do_update()
+        .set(body_blobs::ref_count.eq(body_blobs::ref_count + 1))
+        .execute(conn)?;
+
+    Ok(hash)
+}
+
+/// Drops one reference to the body stored under `hash`, deleting the blob
+/// once nothing points at it
+pub fn release_body(conn: &mut SqliteConnection, hash: &str) -> QueryResult<()> {
+    diesel::update(body_blobs::table.find(hash))
+        .set(body_blobs::ref_count.eq(body_blobs::ref_count - 1))
+        .execute(conn)?;
+
+    diesel::delete(
+        body_blobs::table
+            .filter(body_blobs::hash.eq(hash))
+            .filter(body_blobs::ref_count.le(0)),
+    )
+    .execute(conn)?;
+
+    Ok(())
+}
+
+/// Moves bodies left in the per-item `content_bodies` table into blob
+/// storage, returning how many were moved.
+///
+/// SQLite can't compute SHA-256 itself, so this runs after migrations rather
+/// than as part of one.
+pub fn migrate_legacy_bodies(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let legacy = content_bodies::table.load::<(i32, String)>(conn)?;
+
+        for (content_id, body) in &legacy {
+            let hash = store_body(conn, body)?;
+            diesel::update(content_items::table.find(content_id))
+                .set(content_items::body_hash.eq(hash))
+                .execute(conn)?;
+        }
+
+        diesel::delete(content_bodies::table).execute(conn)?;
+        Ok(legacy.len())
+    })
+}
+
+/// Fills in the plain text of HTML bodies stored before it was kept,
+/// returning how many were filled in. Like [`migrate_legacy_bodies`], this
+/// runs after migrations.
+pub fn fill_plain_text(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let candidates = body_blobs::table
+            .filter(body_blobs::text.is_null())
+            .filter(body_blobs::body.like("%</%"))
+            .select((body_blobs::hash, body_blobs::body))
+            .load::<(String, String)>(conn)?;
+
+        let mut filled = 0;
+        for (hash, body) in &candidates {
+            if let Some(text) = plain_text(body) {
+                diesel::update(body_blobs::table.find(hash))
+                    .set(body_blobs::text.eq(text))
+                    .execute(conn)?;
+                filled += 1;
+            }
+        }
+        Ok(filled)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_hash_is_hex_sha256() {
+        assert_eq!(
+            body_hash("hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn test_plain_text_of_html() {
+        let html = "<article>\n  <h1>Title</h1>\n  <p class=\"lede\">First\n    paragraph with <em>emphasis</em>.</p>\n  <script>track()</script>\n  <p>Line<br>break &amp; more</p>\n</article>";
+        assert_eq!(
+            plain_text(html).as_deref(),
+            Some("Title\n\nFirst paragraph with emphasis.\n\nLine\nbreak & more")
+        );
+    }
+
+    #[test]
+    fn test_plain_text_skips_non_html() {
+        assert_eq!(plain_text("Plain text with 1 < 2 and a > b"), None);
+        assert_eq!(plain_text("Just a paragraph"), None);
+    }
+}
@@ -0,0 +1,26 @@
+//! Version, commit and build time of the running binary, baked in by
+//! `build.rs` so deploy tooling can check which artifact is serving.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Commit the binary was built from, or `unknown` outside a checkout
+    pub git_sha: &'static str,
+    pub built_at: Option<DateTime<Utc>>,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: env!("LECTARA_GIT_SHA"),
+            built_at: env!("LECTARA_BUILD_EPOCH")
+                .parse()
+                .ok()
+                .and_then(|epoch| DateTime::from_timestamp(epoch, 0)),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Threaded comments on items.
+//!
+//! Anyone who can reach an item can comment on it: clients holding an API
+//! key through the API, and visitors of a share link from the shared page.
+//! A comment can reply to another comment on the same item, and every new
+//! comment leaves a notification so the people following the collection
+//! hear about the discussion.
+
+use tracing::info;
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{Comment, ContentItem, NewComment};
+use crate::notifications;
+use crate::repositories::CommentRepository;
+
+/// Longest commenter name accepted
+pub const MAX_AUTHOR_LENGTH: usize = 80;
+
+/// Longest comment accepted, in characters
+pub const MAX_BODY_LENGTH: usize = 10_000;
+
+/// Normalizes a comment before it is stored, rejecting empty or overlong
+/// ones
+pub fn validate(mut comment: NewComment) -> Result<NewComment, ApiError> {
+    comment.author = comment.author.trim().to_string();
+    if comment.author.is_empty() {
+        return Err(ApiError::BadRequest(
+            "'author' must not be empty".to_string(),
+        ));
+    }
+    if comment.author.chars().count() > MAX_AUTHOR_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "'author' must be at most {MAX_AUTHOR_LENGTH} characters"
+        )));
+    }
+
+    comment.body = comment.body.trim().to_string();
+    if comment.body.is_empty() {
+        return Err(ApiError::BadRequest("'body' must not be empty".to_string()));
+    }
+    if comment.body.chars().count() > MAX_BODY_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "'body' must be at most {MAX_BODY_LENGTH} characters"
+        )));
+    }
+
+    Ok(comment)
+}
+
+/// Stores a comment on `item` and notifies about it. Replies must answer a
+/// comment on the same item.
+pub async fn post<S: AppState>(
+    state: &S,
+    item: &ContentItem,
+    comment: NewComment,
+) -> Result<Comment, ApiError> {
+    let comment = validate(comment)?;
+    let repo = state.comment_repo();
+    if let Some(parent_id) = comment.parent_id {
+        let parent = repo.find_by_id(parent_id).await?;
+        if parent.is_none_or(|parent| parent.content_id != item.id) {
+            return Err(ApiError::BadRequest(format!(
+                "No comment {parent_id} on this item to reply to"
+            )));
+        }
+    }
+
+    let comment = repo.create(&comment).await?;
+    info!(
+        content_id = item.id,
+        comment_id = comment.id,
+        reply = comment.parent_id.is_some(),
+        "Stored comment"
+    );
+
+    let title = item.title.as_deref().unwrap_or(&item.url);
+    let message = match comment.parent_id {
+        Some(_) => format!("{} replied to a comment on {title}", comment.author),
+        None => format!("{} commented on {title}", comment.author),
+    };
+    notifications::notify(
+        state,
+        notifications::KIND_COMMENT_ADDED,
+        message,
+        Some(item.id),
+    )
+    .await;
+
+    Ok(comment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_comment(author: &str, body: &str) -> NewComment {
+        NewComment {
+            content_id: 1,
+            parent_id: None,
+            author: author.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_trims() {
+        let comment = validate(new_comment(" Ada ", "\nGood read\n")).unwrap();
+        assert_eq!(comment.author, "Ada");
+        assert_eq!(comment.body, "Good read");
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_and_overlong() {
+        let invalid = [
+            new_comment(" ", "Good read"),
+            new_comment("Ada", " "),
+            new_comment(&"a".repeat(MAX_AUTHOR_LENGTH + 1), "Good read"),
+            new_comment("Ada", &"a".repeat(MAX_BODY_LENGTH + 1)),
+        ];
+
+        for comment in invalid {
+            assert!(matches!(validate(comment), Err(ApiError::BadRequest(_))));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Settings files for `lectara-service --config`, holding the environment
+//! variables the service reads so a deployment can keep them in one place:
+//!
+//! ```toml
+//! DATABASE_URL = "/var/lib/lectara/lectara.db"
+//! LECTARA_BIND = "127.0.0.1:3000"
+//! LECTARA_SYNC_URL = "https://backup.lectara.example"
+//! LECTARA_PUBLIC_READS = true
+//! ```
+//!
+//! Variables set in the environment win over the file, and flags win over
+//! both.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use toml::Value;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid settings file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid setting {name}: {reason}")]
+    InvalidSetting { name: String, reason: String },
+}
+
+/// The variables of a settings file, by name
+#[derive(Debug, Default, PartialEq)]
+pub struct Settings {
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&contents)
+    }
+
+    /// Parses a table of variables. Numbers and booleans are written as the
+    /// environment would hold them, and arrays of them joined by commas, as
+    /// in `LECTARA_API_KEY_HASHES`.
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let table: toml::Table = toml::from_str(contents)?;
+
+        let mut variables = BTreeMap::new();
+        for (name, value) in table {
+            let invalid = |reason: &str| ConfigError::InvalidSetting {
+                name: name.clone(),
+                reason: reason.to_string(),
+            };
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Err(invalid("not an environment variable name"));
+            }
+            let value = match &value {
+                Value::Array(values) => values
+                    .iter()
+                    .map(scalar)
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| values.join(","))
+                    .ok_or_else(|| invalid("arrays may only hold strings, numbers and booleans"))?,
+                value => {
+                    scalar(value).ok_or_else(|| invalid("tables and dates aren't settings"))?
+                }
+            };
+            variables.insert(name, value);
+        }
+        Ok(Self { variables })
+    }
+
+    /// The variables not already set in the environment
+    pub fn unset(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables
+            .iter()
+            .filter(|(name, _)| std::env::var_os(name).is_none())
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Integer(value) => Some(value.to_string()),
+        Value::Float(value) => Some(value.to_string()),
+        Value::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_writes_values_as_the_environment_holds_them() {
+        let settings = Settings::parse(
+            r#"
+            DATABASE_URL = "/var/lib/lectara/lectara.db"
+            LECTARA_PUBLIC_READS = true
+            LECTARA_METRICS_WINDOW_SECONDS = 600
+            LECTARA_API_KEY_HASHES = ["aa", "bb"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.variables,
+            BTreeMap::from([
+                (
+                    "DATABASE_URL".to_string(),
+                    "/var/lib/lectara/lectara.db".to_string()
+                ),
+                ("LECTARA_API_KEY_HASHES".to_string(), "aa,bb".to_string()),
+                (
+                    "LECTARA_METRICS_WINDOW_SECONDS".to_string(),
+                    "600".to_string()
+                ),
+                ("LECTARA_PUBLIC_READS".to_string(), "true".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_nested_tables() {
+        assert!(matches!(
+            Settings::parse("[sync]\nurl = \"https://example.com\""),
+            Err(ConfigError::InvalidSetting { name, .. }) if name == "sync"
+        ));
+        assert!(matches!(
+            Settings::parse("HOSTS = [[\"a\"]]"),
+            Err(ConfigError::InvalidSetting { .. })
+        ));
+        assert!(matches!(
+            Settings::parse("NOT TOML"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+//! Health checks for the shared database connection.
+//!
+//! An open SQLite connection keeps reading the file it opened even after
+//! that file is replaced, e.g. when a backup is restored over it, so the
+//! service would keep serving the old data (or errors, once the old file is
+//! gone) until restarted. [`ConnectionMonitor`] notices a replaced file or a
+//! connection that stopped answering and swaps a fresh connection into the
+//! shared handle, bringing its schema up to date first since a restored
+//! backup may predate recent migrations.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::MigrationHarness;
+use tracing::{debug, info, warn};
+
+use crate::bodies;
+use crate::database;
+use crate::migrations::MIGRATIONS;
+
+/// How often the serve command checks the connection
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Outcome of one [`ConnectionMonitor::check`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConnectionHealth {
+    Healthy,
+    /// A new connection replaced the shared one
+    Reconnected,
+    /// The connection is unusable and couldn't be replaced; the old one is
+    /// kept until a later check succeeds
+    Unavailable(String),
+}
+
+/// Identifies a database file across renames over its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity {
+    device: u64,
+    inode: u64,
+}
+
+pub struct ConnectionMonitor {
+    db: Arc<Mutex<SqliteConnection>>,
+    database_url: String,
+    /// The file the shared connection has open, if the URL names one
+    file: Option<FileIdentity>,
+}
+
+impl ConnectionMonitor {
+    /// Watches `db`, which must have been opened from `database_url`
+    pub fn new(db: Arc<Mutex<SqliteConnection>>, database_url: impl Into<String>) -> Self {
+        let database_url = database_url.into();
+        let file = file_identity(&database_url);
+        Self {
+            db,
+            database_url,
+            file,
+        }
+    }
+
+    /// Checks that the database file is still the one the connection has
+    /// open and that the connection answers, reconnecting if either fails
+    pub fn check(&mut self) -> ConnectionHealth {
+        let current = file_identity(&self.database_url);
+        if self.file.is_some() && current.is_none() {
+            // Connecting now would create an empty database in its place
+            return ConnectionHealth::Unavailable("Database file is missing".to_string());
+        }
+
+        if current == self.file {
+            let ping = diesel::sql_query("SELECT 1").execute(&mut *database::lock(&self.db));
+            match ping {
+                Ok(_) => return ConnectionHealth::Healthy,
+                Err(err) => warn!(error = %err, "Database connection stopped answering"),
+            }
+        } else {
+            info!("Database file was replaced");
+        }
+
+        match self.reconnect() {
+            Ok(()) => ConnectionHealth::Reconnected,
+            Err(err) => ConnectionHealth::Unavailable(err),
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<(), String> {
+        let mut connection =
+            SqliteConnection::establish(&self.database_url).map_err(|err| err.to_string())?;
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|err| format!("Failed to run migrations: {err}"))?;
+        bodies::migrate_legacy_bodies(&mut connection)
+            .map_err(|err| format!("Failed to move legacy content bodies: {err}"))?;
+        bodies::fill_plain_text(&mut connection)
+            .map_err(|err| format!("Failed to extract plain text of bodies: {err}"))?;
+
+        *database::lock(&self.db) = connection;
+        self.file = file_identity(&self.database_url);
+        Ok(())
+    }
+}
+
+/// Periodically checks the connection for as long as the process runs,
+/// calling `on_reconnect` after each reconnect so caches of the old
+/// database's contents can be cleared
+pub fn spawn_health_checks(
+    mut monitor: ConnectionMonitor,
+    interval: Duration,
+    on_reconnect: impl Fn() + Send + 'static,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately, right after startup connected
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match monitor.check() {
+                ConnectionHealth::Healthy => debug!("Database connection healthy"),
+                ConnectionHealth::Reconnected => {
+                    info!("Reconnected to database");
+                    on_reconnect();
+                }
+                ConnectionHealth::Unavailable(err) => {
+                    warn!(error = %err, "Failed to reconnect to database");
+                }
+            }
+        }
+    });
+}
+
+/// Path of the file named by a SQLite URL: a plain path or a `file:` URI,
+/// which diesel also accepts spelled `sqlite://`
+fn database_path(database_url: &str) -> Option<&str> {
+    let uri = database_url
+        .strip_prefix("file:")
+        .or_else(|| database_url.strip_prefix("sqlite://"));
+    let path = match uri {
+        Some(uri) => uri.split('?').next().unwrap_or(uri),
+        None => database_url,
+    };
+    (!path.is_empty() && path != ":memory:").then_some(path)
+}
+
+#[cfg(unix)]
+fn file_identity(database_url: &str) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(database_path(database_url)?).ok()?;
+    Some(FileIdentity {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+    })
+}
+
+/// Without inodes a replaced file can't be told apart, leaving the
+/// liveness check alone
+#[cfg(not(unix))]
+fn file_identity(_database_url: &str) -> Option<FileIdentity> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_path() {
+        assert_eq!(
+            database_path("/var/lib/lectara.db"),
+            Some("/var/lib/lectara.db")
+        );
+        assert_eq!(
+            database_path("file:lectara.db?mode=rwc"),
+            Some("lectara.db")
+        );
+        assert_eq!(
+            database_path("sqlite:///var/lib/x.db"),
+            Some("/var/lib/x.db")
+        );
+        assert_eq!(database_path(":memory:"), None);
+        assert_eq!(database_path("file::memory:?cache=shared"), None);
+    }
+}
@@ -0,0 +1,428 @@
+//! The database handle shared by the repositories.
+//!
+//! Writes are queued to a single writer thread, which runs them one at a
+//! time on the write connection, so requests never contend for it. Reads go
+//! to a [`ReadPool`] of read-only connections when there is one, and
+//! otherwise share the write connection, as they must for an in-memory
+//! database.
+//!
+//! Every write moves the [`CollectionVersion`] on, so list responses can be
+//! revalidated without querying anything.
+//!
+//! A write that panics is rolled back and reported as an error rather than
+//! taking the writer down, and the connections' locks are taken even when a
+//! panic poisoned them, so one bad request can't turn every later one into
+//! a panic.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, mpsc};
+
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use tokio::sync::oneshot;
+use tracing::{error, info};
+
+use crate::errors::ApiError;
+use crate::repositories::retry::write_with_retry;
+
+/// Number of read-only connections `serve` opens (default 4; 0 reads on the
+/// write connection)
+pub const READ_CONNECTIONS_ENV: &str = "LECTARA_READ_CONNECTIONS";
+
+const DEFAULT_READ_CONNECTIONS: usize = 4;
+/// How long a read waits for the write lock SQLite briefly takes when a
+/// write commits
+const READ_BUSY_TIMEOUT_MS: u32 = 5000;
+
+type WriteJob = Box<dyn FnOnce() + Send>;
+
+#[derive(Clone)]
+pub struct Database {
+    connection: Arc<Mutex<SqliteConnection>>,
+    writer: mpsc::Sender<WriteJob>,
+    readers: Option<Arc<ReadPool>>,
+    version: Arc<Version>,
+}
+
+struct Version {
+    /// Chosen at startup, so versions from before a restart never match
+    epoch: u64,
+    writes: AtomicU64,
+}
+
+/// The state of the whole database as far as caching goes: it changes after
+/// every write, whichever table it touched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionVersion {
+    epoch: u64,
+    writes: u64,
+}
+
+impl CollectionVersion {
+    /// A weak entity tag for a response built on this version from
+    /// `inputs`; weak, since such responses may still differ in details
+    /// such as the time they were made
+    pub fn etag(&self, inputs: &impl Hash) -> String {
+        let mut hasher = DefaultHasher::new();
+        inputs.hash(&mut hasher);
+        format!(
+            "W/\"{:x}-{}-{:x}\"",
+            self.epoch,
+            self.writes,
+            hasher.finish()
+        )
+    }
+}
+
+impl From<Arc<Mutex<SqliteConnection>>> for Database {
+    fn from(connection: Arc<Mutex<SqliteConnection>>) -> Self {
+        Self::new(connection)
+    }
+}
+
+impl Database {
+    /// Starts a writer thread for `connection`, which ends once every clone
+    /// of the handle is dropped
+    pub fn new(connection: Arc<Mutex<SqliteConnection>>) -> Self {
+        let (writer, jobs) = mpsc::channel::<WriteJob>();
+        std::thread::Builder::new()
+            .name("lectara-writer".to_string())
+            .spawn(move || {
+                for job in jobs {
+                    job();
+                }
+            })
+            .expect("Failed to start the database writer thread");
+
+        Self {
+            connection,
+            writer,
+            readers: None,
+            version: Arc::new(Version {
+                epoch: rand::random(),
+                writes: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn with_readers(mut self, readers: ReadPool) -> Self {
+        self.readers = Some(Arc::new(readers));
+        self
+    }
+
+    /// The write connection, e.g. for swapping in a new one
+    pub fn connection(&self) -> &Arc<Mutex<SqliteConnection>> {
+        &self.connection
+    }
+
+    /// The read-only connections, if reads don't share the write connection
+    pub fn readers(&self) -> Option<&ReadPool> {
+        self.readers.as_deref()
+    }
+
+    pub fn version(&self) -> CollectionVersion {
+        CollectionVersion {
+            epoch: self.version.epoch,
+            writes: self.version.writes.load(Ordering::Acquire),
+        }
+    }
+
+    /// Moves the version on for a change made other than through
+    /// [`Database::write`], e.g. swapping in a new database file
+    pub fn mark_changed(&self) {
+        self.version.writes.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// A connection to read from, held until the guard is dropped
+    pub fn read(&self) -> MutexGuard<'_, SqliteConnection> {
+        match &self.readers {
+            Some(readers) => readers.get(),
+            None => lock(&self.connection),
+        }
+    }
+
+    /// Queues `write` for the writer thread and waits for its result. It is
+    /// retried there while another process holds the database locked, and
+    /// moves the version on before its caller hears back, even if it failed
+    /// part way.
+    pub async fn write<T, F>(&self, write: F) -> Result<T, ApiError>
+    where
+        T: Send + 'static,
+        F: FnMut(&mut SqliteConnection) -> Result<T, ApiError> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let connection = self.connection.clone();
+        let version = self.version.clone();
+        let job: WriteJob = Box::new(move || {
+            let result = catch_unwind(AssertUnwindSafe(|| write_with_retry(&connection, write)))
+                .unwrap_or_else(|_| {
+                    error!("Database write panicked, rolling it back");
+                    roll_back(&mut lock(&connection));
+                    Err(ApiError::InternalError)
+                });
+            version.writes.fetch_add(1, Ordering::AcqRel);
+            // The caller may have given up waiting, which changes nothing
+            let _ = reply.send(result);
+        });
+        if self.writer.send(job).is_err() {
+            error!("Database writer thread is gone");
+            return Err(ApiError::InternalError);
+        }
+        result.await.map_err(|_| ApiError::InternalError)?
+    }
+
+    /// Checks that the writer thread takes jobs and the write connection
+    /// answers, leaving the version alone since nothing changes
+    pub async fn ping(&self) -> Result<(), ApiError> {
+        let (reply, result) = oneshot::channel();
+        let connection = self.connection.clone();
+        let job: WriteJob = Box::new(move || {
+            let ping = diesel::sql_query("SELECT 1").execute(&mut *lock(&connection));
+            let _ = reply.send(ping.map(drop).map_err(ApiError::from));
+        });
+        if self.writer.send(job).is_err() {
+            error!("Database writer thread is gone");
+            return Err(ApiError::InternalError);
+        }
+        result.await.map_err(|_| ApiError::InternalError)?
+    }
+}
+
+/// Locks `connection`, even if a panic poisoned it; the writer rolls back
+/// whatever a panicking write left open, so the connection stays usable
+pub(crate) fn lock(connection: &Mutex<SqliteConnection>) -> MutexGuard<'_, SqliteConnection> {
+    connection.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Rolls back the transactions a write that unwound out of them left open
+fn roll_back(connection: &mut SqliteConnection) {
+    while let Ok(Some(_)) =
+        AnsiTransactionManager::transaction_manager_status_mut(connection).transaction_depth()
+    {
+        if let Err(err) = AnsiTransactionManager::rollback_transaction(connection) {
+            error!(error = %err, "Failed to roll back a panicked write");
+            return;
+        }
+    }
+}
+
+/// Read-only connections to a database file, handed out in turn
+pub struct ReadPool {
+    database_url: String,
+    connections: Vec<Mutex<SqliteConnection>>,
+    next: AtomicUsize,
+}
+
+impl ReadPool {
+    /// Opens `size` connections, at least one, to `database_url`, switching
+    /// the database to write-ahead logging so they can read while a write is
+    /// under way
+    pub fn open(database_url: &str, size: usize) -> Result<Self, String> {
+        let connections = open_readers(database_url, size)?;
+        Ok(Self {
+            database_url: database_url.to_string(),
+            connections: connections.into_iter().map(Mutex::new).collect(),
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Reads [`READ_CONNECTIONS_ENV`], falling back to the default for
+    /// unset or invalid values
+    pub fn size_from_env() -> usize {
+        std::env::var(READ_CONNECTIONS_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_READ_CONNECTIONS)
+    }
+
+    pub fn size(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Replaces every connection, e.g. after the database file was
+    /// replaced; if any fails to open, the old ones are all kept
+    pub fn reconnect(&self) -> Result<(), String> {
+        let fresh = open_readers(&self.database_url, self.size())?;
+        for (connection, fresh) in self.connections.iter().zip(fresh) {
+            *lock(connection) = fresh;
+        }
+        info!(connections = self.size(), "Reopened read connections");
+        Ok(())
+    }
+
+    /// An idle connection if there is one, or else the next one in turn
+    fn get(&self) -> MutexGuard<'_, SqliteConnection> {
+        for connection in &self.connections {
+            if let Ok(guard) = connection.try_lock() {
+                return guard;
+            }
+        }
+        let next = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        lock(&self.connections[next])
+    }
+}
+
+fn open_readers(database_url: &str, size: usize) -> Result<Vec<SqliteConnection>, String> {
+    (0..size.max(1))
+        .map(|index| {
+            let mut connection =
+                SqliteConnection::establish(database_url).map_err(|err| err.to_string())?;
+            // The mode is kept in the file, so one connection sets it; a
+            // restored backup may not have it yet
+            if index == 0 {
+                diesel::sql_query("PRAGMA journal_mode = WAL")
+                    .execute(&mut connection)
+                    .map_err(|err| format!("Failed to enable write-ahead logging: {err}"))?;
+            }
+            diesel::sql_query(format!("PRAGMA busy_timeout = {READ_BUSY_TIMEOUT_MS}"))
+                .execute(&mut connection)
+                .map_err(|err| err.to_string())?;
+            diesel::sql_query("PRAGMA query_only = ON")
+                .execute(&mut connection)
+                .map_err(|err| err.to_string())?;
+            Ok(connection)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database() -> Database {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query("CREATE TABLE items (id INTEGER PRIMARY KEY)")
+            .execute(&mut connection)
+            .unwrap();
+        Database::new(Arc::new(Mutex::new(connection)))
+    }
+
+    fn count(database: &Database) -> i64 {
+        diesel::select(diesel::dsl::sql::<diesel::sql_types::BigInt>(
+            "(SELECT COUNT(*) FROM items)",
+        ))
+        .get_result(&mut *database.read())
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_writes_run_in_order_on_the_writer() {
+        let database = database();
+        let writes: Vec<_> = (0..20)
+            .map(|id| {
+                let database = database.clone();
+                tokio::spawn(async move {
+                    database
+                        .write(move |conn| {
+                            diesel::sql_query(format!("INSERT INTO items VALUES ({id})"))
+                                .execute(conn)?;
+                            Ok(std::thread::current().name().map(str::to_string))
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for write in writes {
+            let thread = write.await.unwrap().unwrap();
+            assert_eq!(thread.as_deref(), Some("lectara-writer"));
+        }
+        assert_eq!(count(&database), 20);
+    }
+
+    #[tokio::test]
+    async fn test_every_write_moves_the_version_on() {
+        let database = database();
+        let start = database.version();
+        assert_eq!(start.etag(&()), database.version().etag(&()));
+        assert_ne!(start.etag(&()), start.etag(&"filter"));
+
+        database
+            .write(|conn| {
+                diesel::sql_query("INSERT INTO items VALUES (1)").execute(conn)?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        let written = database.version();
+        assert_ne!(written, start);
+
+        // A failed write may still have changed something
+        let failed = database
+            .write(|conn| {
+                diesel::sql_query("INSERT INTO items VALUES (1)").execute(conn)?;
+                Ok(())
+            })
+            .await;
+        assert!(failed.is_err());
+        assert_ne!(database.version(), written);
+
+        // Versions of another database, or from before a restart, never match
+        let restarted = Database::new(database.connection().clone());
+        assert_ne!(restarted.version(), start);
+    }
+
+    #[tokio::test]
+    async fn test_a_panicking_write_leaves_the_database_usable() {
+        let database = database();
+        let result = database
+            .write(|conn| -> Result<(), ApiError> {
+                conn.transaction(|conn| {
+                    diesel::sql_query("INSERT INTO items VALUES (2)").execute(conn)?;
+                    panic!("bug in a write")
+                })
+            })
+            .await;
+        assert!(matches!(result, Err(ApiError::InternalError)));
+
+        database
+            .write(|conn| {
+                diesel::sql_query("INSERT INTO items VALUES (1)").execute(conn)?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(count(&database), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ping_waits_for_the_writer() {
+        let database = database();
+        let start = database.version();
+        database.ping().await.unwrap();
+        assert_eq!(database.version(), start);
+
+        // A write that hangs until released
+        let (started, running) = oneshot::channel();
+        let (release, stuck) = mpsc::channel::<()>();
+        let mut started = Some(started);
+        let write = tokio::spawn({
+            let database = database.clone();
+            async move {
+                database
+                    .write(move |_| {
+                        if let Some(started) = started.take() {
+                            let _ = started.send(());
+                        }
+                        let _ = stuck.recv();
+                        Ok(())
+                    })
+                    .await
+            }
+        });
+        running.await.unwrap();
+        let timeout = std::time::Duration::from_millis(100);
+        assert!(
+            tokio::time::timeout(timeout, database.ping())
+                .await
+                .is_err()
+        );
+
+        release.send(()).unwrap();
+        write.await.unwrap().unwrap();
+        database.ping().await.unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+//! Single-chapter EPUB 3 books of a saved item's text.
+//!
+//! An EPUB is a zip archive whose first entry is an uncompressed
+//! `mimetype` file. Articles are small, so every entry is stored
+//! uncompressed, which keeps the writer a few dozen lines.
+
+use chrono::{DateTime, Utc};
+use quick_xml::escape::escape;
+
+/// Media type of EPUB files
+pub const EPUB_MEDIA_TYPE: &str = "application/epub+zip";
+
+/// What goes into a book
+pub struct Book<'a> {
+    /// Unique and stable, e.g. the item's URL
+    pub identifier: &'a str,
+    pub title: &'a str,
+    pub author: Option<&'a str>,
+    /// Paragraphs are separated by blank lines
+    pub text: &'a str,
+    pub modified: DateTime<Utc>,
+}
+
+/// The book as an EPUB file
+pub fn render(book: &Book) -> Vec<u8> {
+    let mut archive = StoredZip::default();
+    archive.add("mimetype", EPUB_MEDIA_TYPE.as_bytes());
+    archive.add(
+        "META-INF/container.xml",
+        b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+          <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+          <rootfiles><rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/></rootfiles>\n\
+          </container>\n",
+    );
+    archive.add("OEBPS/content.opf", package(book).as_bytes());
+    archive.add("OEBPS/nav.xhtml", navigation(book).as_bytes());
+    archive.add("OEBPS/text.xhtml", chapter(book).as_bytes());
+    archive.finish()
+}
+
+fn package(book: &Book) -> String {
+    let creator = book
+        .author
+        .map(|author| format!("<dc:creator>{}</dc:creator>\n", escape(author)))
+        .unwrap_or_default();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+         <dc:identifier id=\"id\">{identifier}</dc:identifier>\n\
+         <dc:title>{title}</dc:title>\n\
+         {creator}\
+         <dc:language>und</dc:language>\n\
+         <meta property=\"dcterms:modified\">{modified}</meta>\n\
+         </metadata>\n\
+         <manifest>\n\
+         <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+         <item id=\"text\" href=\"text.xhtml\" media-type=\"application/xhtml+xml\"/>\n\
+         </manifest>\n\
+         <spine><itemref idref=\"text\"/></spine>\n\
+         </package>\n",
+        identifier = escape(book.identifier),
+        title = escape(book.title),
+        modified = book.modified.format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
+
+fn navigation(book: &Book) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+         <head><title>{title}</title></head>\n\
+         <body><nav epub:type=\"toc\"><ol><li><a href=\"text.xhtml\">{title}</a></li></ol></nav></body>\n\
+         </html>\n",
+        title = escape(book.title)
+    )
+}
+
+fn chapter(book: &Book) -> String {
+    let paragraphs: String = book
+        .text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>\n", escape(paragraph)))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\">\n\
+         <head><title>{title}</title></head>\n\
+         <body>\n<h1>{title}</h1>\n{paragraphs}</body>\n\
+         </html>\n",
+        title = escape(book.title)
+    )
+}
+
+/// Zip archive writer storing entries without compression
+#[derive(Default)]
+struct StoredZip {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+/// 1980-01-01, the earliest time zip archives can record
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl StoredZip {
+    fn add(&mut self, name: &str, contents: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32fast::hash(contents);
+        let size = contents.len() as u32;
+
+        // Local file header
+        self.data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&10u16.to_le_bytes()); // version needed
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // flags
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.data.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.data.extend_from_slice(&crc.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data.extend_from_slice(&size.to_le_bytes());
+        self.data
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // extra field
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        let directory = &mut self.central_directory;
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        directory.extend_from_slice(&10u16.to_le_bytes()); // version needed
+        directory.extend_from_slice(&0u16.to_le_bytes()); // flags
+        directory.extend_from_slice(&0u16.to_le_bytes()); // stored
+        directory.extend_from_slice(&0u16.to_le_bytes()); // time
+        directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+        directory.extend_from_slice(&crc.to_le_bytes());
+        directory.extend_from_slice(&size.to_le_bytes());
+        directory.extend_from_slice(&size.to_le_bytes());
+        directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // Extra field, comment, disk, internal and external attributes
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+
+        // End of central directory record
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]); // disk numbers
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&directory_size.to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes()); // comment
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> Book<'static> {
+        Book {
+            identifier: "https://example.com/engines?a=1&b=2",
+            title: "Engines & looms",
+            author: Some("Ada Lovelace"),
+            text: "First <paragraph>.\n\n\n\nSecond one.",
+            modified: DateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_mimetype_comes_first_uncompressed() {
+        let epub = render(&book());
+        assert_eq!(&epub[..4], b"PK\x03\x04");
+        assert_eq!(&epub[30..38], b"mimetype");
+        assert_eq!(&epub[38..58], EPUB_MEDIA_TYPE.as_bytes());
+    }
+
+    #[test]
+    fn test_central_directory_lists_every_entry() {
+        let epub = render(&book());
+        let end = &epub[epub.len() - 22..];
+        assert_eq!(&end[..4], &0x0605_4b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 5);
+    }
+
+    #[test]
+    fn test_chapter_escapes_text() {
+        let chapter = chapter(&book());
+        assert!(chapter.contains("<h1>Engines &amp; looms</h1>"));
+        assert!(chapter.contains("<p>First &lt;paragraph&gt;.</p>\n<p>Second one.</p>"));
+
+        let package = package(&book());
+        assert!(package.contains(
+            "<dc:identifier id=\"id\">https://example.com/engines?a=1&amp;b=2</dc:identifier>"
+        ));
+        assert!(package.contains("<dc:creator>Ada Lovelace</dc:creator>"));
+        assert!(
+            package.contains("<meta property=\"dcterms:modified\">1970-01-01T00:00:00Z</meta>")
+        );
+    }
+}
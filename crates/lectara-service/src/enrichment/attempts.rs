@@ -0,0 +1,182 @@
+//! Bookkeeping for background fetches. Every enricher run is recorded per
+//! item, and failures are retried with exponential backoff until
+//! [`MAX_ATTEMPTS`] consecutive runs have failed.
+
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use tracing::{debug, info, instrument, warn};
+
+use super::pipeline::{self, Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{ContentItem, NewFetchAttempt};
+use crate::notifications;
+use crate::repositories::{ContentRepository, FetchAttemptRepository};
+
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+/// Failed, with a retry scheduled
+pub const STATUS_FAILED: &str = "failed";
+/// Failed too often to be retried automatically
+pub const STATUS_ABANDONED: &str = "abandoned";
+
+/// Consecutive failures after which a fetch is no longer retried
+pub const MAX_ATTEMPTS: i32 = 6;
+
+/// How often the retry worker looks for due retries
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Delay before the first retry; each later one waits four times longer
+const FIRST_RETRY_DELAY: TimeDelta = TimeDelta::minutes(1);
+const RETRY_BATCH_SIZE: u32 = 50;
+
+/// Delay before retrying a fetch that has now failed `attempts` times in a
+/// row, or `None` once it should be given up
+pub fn retry_delay(attempts: i32) -> Option<TimeDelta> {
+    (1..MAX_ATTEMPTS)
+        .contains(&attempts)
+        .then(|| FIRST_RETRY_DELAY * 4_i32.pow(attempts as u32 - 1))
+}
+
+/// Runs one enricher and records its outcome, scheduling a retry on
+/// failure. Returns the item as updated by the enricher, if it changed.
+pub async fn run_and_record<S: AppState>(
+    state: &S,
+    enricher: &dyn Enricher<S>,
+    input: &EnrichmentInput,
+) -> Option<ContentItem> {
+    let result = pipeline::run_step(state, enricher, input).await;
+    let content_id = input.item().id;
+    let kind = enricher.name();
+
+    let repo = state.fetch_attempt_repo();
+    let previous_failures = match repo.find(content_id, kind).await {
+        Ok(Some(previous)) if previous.status != STATUS_SUCCEEDED => previous.attempts,
+        Ok(_) => 0,
+        Err(err) => {
+            warn!(error = %err, "Failed to read previous fetch attempt");
+            0
+        }
+    };
+    let attempts = previous_failures + 1;
+    let now = Utc::now().naive_utc();
+
+    let (attempt, item) = match result {
+        Ok(item) => (
+            NewFetchAttempt {
+                content_id,
+                kind: kind.to_string(),
+                status: STATUS_SUCCEEDED.to_string(),
+                attempts,
+                last_error: None,
+                last_attempt_at: now,
+                next_attempt_at: None,
+            },
+            item,
+        ),
+        Err(err) => {
+            let next_attempt_at = retry_delay(attempts).map(|delay| now + delay);
+            warn!(error = %err, content_id, kind, attempts, ?next_attempt_at, "Background fetch failed");
+            let attempt = NewFetchAttempt {
+                content_id,
+                kind: kind.to_string(),
+                status: if next_attempt_at.is_some() {
+                    STATUS_FAILED
+                } else {
+                    STATUS_ABANDONED
+                }
+                .to_string(),
+                attempts,
+                last_error: Some(err.to_string()),
+                last_attempt_at: now,
+                next_attempt_at,
+            };
+            (attempt, None)
+        }
+    };
+
+    if let Err(err) = repo.record(&attempt).await {
+        warn!(error = %err, content_id, kind, "Failed to record fetch attempt");
+    }
+    if attempt.status == STATUS_ABANDONED {
+        let message = format!(
+            "Gave up fetching {kind} for {} after {attempts} failed attempts",
+            input.item().url
+        );
+        notifications::notify(
+            state,
+            notifications::KIND_FETCH_ABANDONED,
+            message,
+            Some(content_id),
+        )
+        .await;
+    }
+    item
+}
+
+/// Re-runs failed fetches whose retry is due at `now`, returning how many
+/// were attempted
+#[instrument(skip_all)]
+pub async fn retry_due<S: AppState>(state: &S, now: NaiveDateTime) -> Result<usize, ApiError> {
+    let due = state
+        .fetch_attempt_repo()
+        .list_due(now, RETRY_BATCH_SIZE)
+        .await?;
+
+    let mut retried = 0;
+    for attempt in due {
+        let Some(enricher) = state.enrichers().get(&attempt.kind) else {
+            warn!(
+                kind = attempt.kind,
+                "Skipping retry of unconfigured enricher"
+            );
+            continue;
+        };
+        let Some(item) = state.content_repo().find_by_id(attempt.content_id).await? else {
+            continue;
+        };
+
+        run_and_record(state, enricher.as_ref(), &EnrichmentInput::new(item)).await;
+        retried += 1;
+    }
+
+    if retried > 0 {
+        info!(retried, "Retried failed fetches");
+    }
+    Ok(retried)
+}
+
+/// Periodically retries due fetches for as long as the process runs
+pub fn spawn_retry_worker<S: AppState>(state: S, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if state.read_only().is_enabled() {
+                debug!("Read-only mode, skipping fetch retries");
+                continue;
+            }
+            if state.maintenance().active(Utc::now().naive_utc()).is_some() {
+                debug!("Maintenance window in progress, skipping fetch retries");
+                continue;
+            }
+            debug!("Checking for due fetch retries");
+            if let Err(err) = retry_due(&state, Utc::now().naive_utc()).await {
+                warn!(error = %err, "Failed to retry fetches");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_then_gives_up() {
+        assert_eq!(retry_delay(1), Some(TimeDelta::minutes(1)));
+        assert_eq!(retry_delay(2), Some(TimeDelta::minutes(4)));
+        assert_eq!(retry_delay(5), Some(TimeDelta::minutes(256)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+}
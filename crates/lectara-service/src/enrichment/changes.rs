@@ -0,0 +1,202 @@
+//! Change detection for saved pages.
+//!
+//! A check re-fetches an item's URL and hashes a normalized form of the
+//! page, so markup churn, scripts and whitespace don't count as changes.
+//! The hash is kept in `page_snapshots` for the next check to compare with.
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use tracing::{debug, info, instrument};
+
+use super::EnrichmentError;
+use crate::AppState;
+use crate::bodies::body_hash;
+use crate::errors::ApiError;
+use crate::models::{ContentItem, NewPageSnapshot};
+use crate::repositories::PageSnapshotRepository;
+
+/// Elements whose content never counts as page text
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "noscript", "template"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageStatus {
+    /// No earlier snapshot existed; this check stored the first one
+    FirstCheck,
+    Unchanged,
+    Changed,
+    /// The page couldn't be fetched; the stored snapshot is kept
+    Unreachable,
+}
+
+/// Outcome of re-fetching an item's URL
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheck {
+    pub id: i32,
+    pub status: PageStatus,
+    pub content_hash: Option<String>,
+    pub previous_hash: Option<String>,
+    /// When the current content was first seen
+    pub changed_at: Option<NaiveDateTime>,
+    pub checked_at: NaiveDateTime,
+    /// Status code of an error response, e.g. 404 for a dead link
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Text used for hashing: HTML is reduced to its visible text, and runs of
+/// whitespace collapse to single spaces
+pub fn normalize_page(content_type: &str, bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = if content_type.contains("html") {
+        visible_text(&text)
+    } else {
+        text.into_owned()
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn visible_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[1..end].trim().to_ascii_lowercase();
+        rest = &rest[end + 1..];
+
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next();
+        if let Some(hidden) = name.filter(|name| HIDDEN_ELEMENTS.contains(name)) {
+            let close = format!("</{hidden}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .and_then(|position| {
+                    rest[position..]
+                        .find('>')
+                        .map(|end| &rest[position + end + 1..])
+                })
+                .unwrap_or("");
+        }
+    }
+    text.push_str(rest);
+
+    quick_xml::escape::unescape(&text)
+        .map(|unescaped| unescaped.into_owned())
+        .unwrap_or(text)
+}
+
+/// Re-fetches `item`'s URL and compares it with the stored snapshot,
+/// replacing the snapshot when the page could be fetched
+#[instrument(skip_all, fields(content_id = item.id))]
+pub async fn check_for_update<S: AppState>(
+    state: &S,
+    item: &ContentItem,
+) -> Result<UpdateCheck, ApiError> {
+    let repo = state.page_snapshot_repo();
+    let previous = repo.find_by_content_id(item.id).await?;
+    let now = Utc::now().naive_utc();
+
+    let document = match state.fetcher().fetch(&item.url, |_| true).await {
+        Ok(Some(document)) => document,
+        Ok(None) => unreachable!("every content type is accepted"),
+        Err(err) => {
+            debug!(error = %err, "Page could not be fetched");
+            let http_status = match &err {
+                EnrichmentError::Request(err) => err.status().map(|status| status.as_u16()),
+                _ => None,
+            };
+            return Ok(UpdateCheck {
+                id: item.id,
+                status: PageStatus::Unreachable,
+                content_hash: None,
+                previous_hash: previous.as_ref().map(|s| s.content_hash.clone()),
+                changed_at: previous.map(|snapshot| snapshot.changed_at),
+                checked_at: now,
+                http_status,
+                error: Some(err.to_string()),
+            });
+        }
+    };
+
+    let content_hash = body_hash(&normalize_page(&document.content_type, &document.bytes));
+    let (status, changed_at) = match &previous {
+        None => (PageStatus::FirstCheck, now),
+        Some(snapshot) if snapshot.content_hash == content_hash => {
+            (PageStatus::Unchanged, snapshot.changed_at)
+        }
+        Some(_) => (PageStatus::Changed, now),
+    };
+
+    repo.upsert(&NewPageSnapshot {
+        content_id: item.id,
+        content_hash: content_hash.clone(),
+        checked_at: now,
+        changed_at,
+    })
+    .await?;
+
+    info!(?status, "Checked page for updates");
+    Ok(UpdateCheck {
+        id: item.id,
+        status,
+        content_hash: Some(content_hash),
+        previous_hash: previous.map(|snapshot| snapshot.content_hash),
+        changed_at: Some(changed_at),
+        checked_at: now,
+        http_status: None,
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_html_keeps_visible_text() {
+        let html = r#"<html><head><title>Post</title>
+            <style>body { color: red }</style>
+            <script type="text/javascript">let x = "<p>";</script></head>
+            <body><!-- build 1234 --><p>Hello&amp;
+            <b>world</b></p></body></html>"#;
+
+        assert_eq!(
+            normalize_page("text/html", html.as_bytes()),
+            "Post Hello& world"
+        );
+    }
+
+    #[test]
+    fn test_normalize_ignores_markup_churn() {
+        let before = r#"<div class="a"><p>Same text</p><script>nonce=1</script></div>"#;
+        let after = r#"<section id="b"><p>Same   text</p><script>nonce=2</script></section>"#;
+
+        assert_eq!(
+            normalize_page("text/html", before.as_bytes()),
+            normalize_page("text/html", after.as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_normalize_plain_text_collapses_whitespace() {
+        assert_eq!(
+            normalize_page("text/plain", b"  line one\n\n line <two>\t"),
+            "line one line <two>"
+        );
+    }
+}
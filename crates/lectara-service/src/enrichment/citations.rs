@@ -0,0 +1,482 @@
+//! Citation metadata for DOI and arXiv items, from Crossref and the arXiv API.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use super::EnrichmentError;
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch, NewCitation};
+use crate::repositories::CitationRepository;
+
+pub const CROSSREF_API_URL: &str = "https://api.crossref.org";
+pub const ARXIV_API_URL: &str = "https://export.arxiv.org/api";
+
+/// Environment variable that disables citation lookups when `false` or `0`
+pub const CITATION_LOOKUP_ENV: &str = "LECTARA_CITATION_LOOKUP";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A scholarly identifier recognized in a saved (normalized) URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CitationId {
+    Doi(String),
+    Arxiv(String),
+}
+
+impl CitationId {
+    /// Recognizes `https://doi.org/<doi>` and arXiv abstract or PDF pages
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url::Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let path = url.path().trim_start_matches('/');
+
+        match host {
+            "doi.org" | "dx.doi.org" if path.starts_with("10.") => {
+                Some(CitationId::Doi(path.to_string()))
+            }
+            "arxiv.org" | "www.arxiv.org" | "export.arxiv.org" => {
+                let id = path
+                    .strip_prefix("abs/")
+                    .or_else(|| path.strip_prefix("pdf/"))?;
+                let id = id.strip_suffix(".pdf").unwrap_or(id);
+                (!id.is_empty()).then(|| CitationId::Arxiv(id.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn source(&self) -> &'static str {
+        match self {
+            CitationId::Doi(_) => "crossref",
+            CitationId::Arxiv(_) => "arxiv",
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        match self {
+            CitationId::Doi(doi) => doi,
+            CitationId::Arxiv(id) => id,
+        }
+    }
+}
+
+/// Citation fields common to both upstream services
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CitationMetadata {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub published_on: Option<NaiveDate>,
+    pub container_title: Option<String>,
+    pub abstract_text: Option<String>,
+}
+
+impl CitationMetadata {
+    fn into_new_citation(self, content_id: i32, id: &CitationId) -> NewCitation {
+        NewCitation {
+            content_id,
+            source: id.source().to_string(),
+            identifier: id.identifier().to_string(),
+            title: self.title,
+            authors: serde_json::to_string(&self.authors).expect("names serialize as JSON"),
+            published_on: self.published_on,
+            container_title: self.container_title,
+            abstract_text: self.abstract_text,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CitationResolver {
+    client: reqwest::Client,
+    crossref_url: String,
+    arxiv_url: String,
+}
+
+impl Default for CitationResolver {
+    fn default() -> Self {
+        Self::with_endpoints(CROSSREF_API_URL, ARXIV_API_URL)
+    }
+}
+
+impl CitationResolver {
+    /// A resolver using the given API base URLs, e.g. a local stub in tests
+    pub fn with_endpoints(crossref_url: &str, arxiv_url: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            client,
+            crossref_url: crossref_url.trim_end_matches('/').to_string(),
+            arxiv_url: arxiv_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The default resolver, unless disabled via [`CITATION_LOOKUP_ENV`]
+    pub fn from_env() -> Option<Self> {
+        let disabled = std::env::var(CITATION_LOOKUP_ENV).is_ok_and(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            )
+        });
+        (!disabled).then(Self::default)
+    }
+
+    /// Fetches metadata for `id`, returning `None` if the service doesn't know it
+    pub async fn resolve(
+        &self,
+        id: &CitationId,
+    ) -> Result<Option<CitationMetadata>, EnrichmentError> {
+        match id {
+            CitationId::Doi(doi) => {
+                let response = self
+                    .client
+                    .get(format!("{}/works/{doi}", self.crossref_url))
+                    .send()
+                    .await?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                let body = response.error_for_status()?.text().await?;
+                parse_crossref(&body).map(Some)
+            }
+            CitationId::Arxiv(arxiv_id) => {
+                let body = self
+                    .client
+                    .get(format!("{}/query", self.arxiv_url))
+                    .query(&[("id_list", arxiv_id.as_str())])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                parse_arxiv(&body)
+            }
+        }
+    }
+}
+
+/// Looks up Crossref/arXiv metadata for DOI and arXiv items, storing it in
+/// `citations` rather than on the item
+pub struct CitationEnricher;
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for CitationEnricher {
+    fn name(&self) -> &'static str {
+        "citation"
+    }
+
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state.citation_resolver().is_some() && CitationId::from_url(&item.url).is_some()
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let item = input.item();
+        let (Some(resolver), Some(id)) =
+            (state.citation_resolver(), CitationId::from_url(&item.url))
+        else {
+            return Ok(None);
+        };
+
+        debug!(
+            source = id.source(),
+            identifier = id.identifier(),
+            "Resolving citation metadata"
+        );
+
+        let Some(metadata) = resolver.resolve(&id).await? else {
+            debug!("Identifier not known upstream");
+            return Ok(None);
+        };
+
+        state
+            .citation_repo()
+            .upsert(&metadata.into_new_citation(item.id, &id))
+            .await?;
+
+        info!("Stored citation metadata");
+        Ok(None)
+    }
+}
+
+#[derive(Deserialize)]
+struct CrossrefResponse {
+    message: CrossrefWork,
+}
+
+#[derive(Deserialize)]
+struct CrossrefWork {
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<CrossrefAuthor>,
+    #[serde(default, rename = "container-title")]
+    container_title: Vec<String>,
+    issued: Option<CrossrefDate>,
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrossrefAuthor {
+    given: Option<String>,
+    family: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrossrefDate {
+    #[serde(rename = "date-parts")]
+    date_parts: Vec<Vec<Option<i32>>>,
+}
+
+fn parse_crossref(body: &str) -> Result<CitationMetadata, EnrichmentError> {
+    let work = serde_json::from_str::<CrossrefResponse>(body)
+        .map_err(|err| EnrichmentError::InvalidResponse(err.to_string()))?
+        .message;
+
+    let authors = work
+        .author
+        .into_iter()
+        .filter_map(|author| match (author.given, author.family, author.name) {
+            (Some(given), Some(family), _) => Some(format!("{given} {family}")),
+            (None, Some(family), _) => Some(family),
+            (_, None, name) => name,
+        })
+        .collect();
+
+    // Crossref dates may be partial; missing parts fall back to the first
+    let published_on = work
+        .issued
+        .and_then(|issued| issued.date_parts.into_iter().next())
+        .and_then(|parts| {
+            let part = |index: usize| parts.get(index).copied().flatten();
+            NaiveDate::from_ymd_opt(
+                part(0)?,
+                part(1).unwrap_or(1) as u32,
+                part(2).unwrap_or(1) as u32,
+            )
+        });
+
+    Ok(CitationMetadata {
+        title: work
+            .title
+            .into_iter()
+            .next()
+            .map(|title| collapse_whitespace(&title)),
+        authors,
+        published_on,
+        container_title: work.container_title.into_iter().next(),
+        abstract_text: work
+            .abstract_text
+            .map(|text| collapse_whitespace(&strip_markup(&text))),
+    })
+}
+
+/// Parses the first entry of an arXiv Atom feed; an empty feed means the id
+/// is unknown
+fn parse_arxiv(body: &str) -> Result<Option<CitationMetadata>, EnrichmentError> {
+    let mut reader = Reader::from_str(body);
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut metadata: Option<CitationMetadata> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|err| EnrichmentError::InvalidResponse(err.to_string()))?;
+
+        match event {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                if name == "entry" && path.len() == 1 {
+                    if metadata.is_some() {
+                        break;
+                    }
+                    metadata = Some(CitationMetadata {
+                        container_title: Some("arXiv".to_string()),
+                        ..CitationMetadata::default()
+                    });
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(content) => {
+                let content = content
+                    .decode()
+                    .map_err(|err| EnrichmentError::InvalidResponse(err.to_string()))?;
+                text.push_str(&content);
+            }
+            Event::GeneralRef(reference) => {
+                if let Ok(Some(ch)) = reference.resolve_char_ref() {
+                    text.push(ch);
+                } else if let Ok(name) = reference.decode()
+                    && let Some(resolved) = quick_xml::escape::resolve_predefined_entity(&name)
+                {
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(_) => {
+                let element: Vec<&str> = path.iter().map(String::as_str).collect();
+                if let Some(entry) = metadata.as_mut() {
+                    let value = collapse_whitespace(&text);
+                    match element.as_slice() {
+                        [_, "entry", "title"] => entry.title = Some(value),
+                        [_, "entry", "summary"] => entry.abstract_text = Some(value),
+                        [_, "entry", "published"] => {
+                            entry.published_on = value
+                                .get(..10)
+                                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+                        }
+                        [_, "entry", "author", "name"] => entry.authors.push(value),
+                        [_, "entry", "journal_ref"] => entry.container_title = Some(value),
+                        _ => {}
+                    }
+                }
+                path.pop();
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // arXiv answers unknown ids with an entry titled "Error"
+    Ok(metadata.filter(|entry| entry.title.as_deref() != Some("Error")))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Drops the JATS tags Crossref wraps abstracts in
+fn strip_markup(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for ch in text.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                stripped.push(' ');
+            }
+            _ if !in_tag => stripped.push(ch),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CROSSREF_FIXTURE: &str = r#"{
+        "status": "ok",
+        "message": {
+            "title": ["Attention Is  All You Need"],
+            "author": [
+                {"given": "Ashish", "family": "Vaswani"},
+                {"family": "Shazeer"},
+                {"name": "Google Brain"}
+            ],
+            "container-title": ["Advances in Neural Information Processing Systems"],
+            "issued": {"date-parts": [[2017, 12]]},
+            "abstract": "<jats:p>The dominant sequence\n transduction models.</jats:p>"
+        }
+    }"#;
+
+    const ARXIV_FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <title>ArXiv Query</title>
+  <entry>
+    <id>http://arxiv.org/abs/1706.03762v7</id>
+    <published>2017-06-12T17:57:34Z</published>
+    <title>Attention Is All
+      You Need</title>
+    <summary>  The dominant sequence transduction models &amp; more.
+    </summary>
+    <author><name>Ashish Vaswani</name></author>
+    <author><name>Noam Shazeer</name></author>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_citation_id_from_url() {
+        assert_eq!(
+            CitationId::from_url("https://doi.org/10.1000/182"),
+            Some(CitationId::Doi("10.1000/182".to_string()))
+        );
+        assert_eq!(
+            CitationId::from_url("https://arxiv.org/abs/1706.03762v7"),
+            Some(CitationId::Arxiv("1706.03762v7".to_string()))
+        );
+        assert_eq!(
+            CitationId::from_url("https://arxiv.org/pdf/hep-th/9901001.pdf"),
+            Some(CitationId::Arxiv("hep-th/9901001".to_string()))
+        );
+        assert_eq!(CitationId::from_url("https://arxiv.org/list/cs.AI"), None);
+        assert_eq!(
+            CitationId::from_url("https://example.com/10.1000/182"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_crossref() {
+        let metadata = parse_crossref(CROSSREF_FIXTURE).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Attention Is All You Need"));
+        assert_eq!(
+            metadata.authors,
+            vec!["Ashish Vaswani", "Shazeer", "Google Brain"]
+        );
+        assert_eq!(metadata.published_on, NaiveDate::from_ymd_opt(2017, 12, 1));
+        assert_eq!(
+            metadata.abstract_text.as_deref(),
+            Some("The dominant sequence transduction models.")
+        );
+    }
+
+    #[test]
+    fn test_parse_crossref_rejects_unexpected_json() {
+        assert!(matches!(
+            parse_crossref("{\"status\": \"ok\"}"),
+            Err(EnrichmentError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_arxiv() {
+        let metadata = parse_arxiv(ARXIV_FIXTURE).unwrap().unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Attention Is All You Need"));
+        assert_eq!(metadata.authors, vec!["Ashish Vaswani", "Noam Shazeer"]);
+        assert_eq!(metadata.published_on, NaiveDate::from_ymd_opt(2017, 6, 12));
+        assert_eq!(metadata.container_title.as_deref(), Some("arXiv"));
+        assert_eq!(
+            metadata.abstract_text.as_deref(),
+            Some("The dominant sequence transduction models & more.")
+        );
+    }
+
+    #[test]
+    fn test_parse_arxiv_empty_feed() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>ArXiv Query</title></feed>"#;
+        assert_eq!(parse_arxiv(feed).unwrap(), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Per-site cookies and headers sent by the server-side fetcher, so pages
+//! from sites the owner subscribes to are archived in full rather than as
+//! the teaser shown to anonymous visitors.
+//!
+//! Credentials live in a TOML file keyed by host:
+//!
+//! ```toml
+//! [sites."lwn.net"]
+//! cookies = { session = "..." }
+//!
+//! [sites."example.com"]
+//! headers = { Authorization = "Bearer ..." }
+//! ```
+//!
+//! Like the validation host lists, an entry matches the host and any
+//! subdomain of it; the most specific entry wins.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{COOKIE, HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::validation::host_matches;
+
+/// Environment variable naming the credentials file; nothing is sent when unset
+pub const FETCH_CREDENTIALS_ENV: &str = "LECTARA_FETCH_CREDENTIALS";
+
+#[derive(Error, Debug)]
+pub enum CredentialsError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid credentials file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid {field} for {host}")]
+    InvalidEntry { host: String, field: String },
+}
+
+/// Headers to add to requests, by host
+#[derive(Debug, Clone, Default)]
+pub struct SiteCredentials {
+    /// Sorted most specific first, so the first match is the best one
+    sites: Vec<(String, HeaderMap)>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CredentialsFile {
+    #[serde(default)]
+    sites: BTreeMap<String, SiteEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SiteEntry {
+    #[serde(default)]
+    cookies: BTreeMap<String, String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl SiteCredentials {
+    /// Loads the file named by [`FETCH_CREDENTIALS_ENV`], or no credentials
+    /// when it is unset
+    pub fn from_env() -> Result<Self, CredentialsError> {
+        match std::env::var(FETCH_CREDENTIALS_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CredentialsError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| CredentialsError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, CredentialsError> {
+        let file: CredentialsFile = toml::from_str(contents)?;
+
+        let mut sites = file
+            .sites
+            .into_iter()
+            .map(|(host, entry)| {
+                let host = host.trim().trim_start_matches('.').to_lowercase();
+                let headers = entry.into_headers(&host)?;
+                Ok((host, headers))
+            })
+            .collect::<Result<Vec<_>, CredentialsError>>()?;
+        sites.sort_by_key(|(host, _)| std::cmp::Reverse(host.len()));
+
+        Ok(Self { sites })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    /// Number of sites with credentials
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Headers configured for `host`, if any entry matches it
+    pub fn for_host(&self, host: &str) -> Option<&HeaderMap> {
+        let host = host.to_lowercase();
+        self.sites
+            .iter()
+            .find(|(entry, _)| host_matches(&host, entry))
+            .map(|(_, headers)| headers)
+    }
+}
+
+impl SiteEntry {
+    fn into_headers(self, host: &str) -> Result<HeaderMap, CredentialsError> {
+        let invalid = |field: String| CredentialsError::InvalidEntry {
+            host: host.to_string(),
+            field,
+        };
+        // Values are secrets: marking them sensitive keeps them out of
+        // Debug output and logs
+        let sensitive = |value: &str, field: &str| {
+            let mut value = HeaderValue::from_str(value).map_err(|_| invalid(field.to_string()))?;
+            value.set_sensitive(true);
+            Ok::<_, CredentialsError>(value)
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let field = format!("header {name}");
+            let header_name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(field.clone()))?;
+            headers.insert(header_name, sensitive(value, &field)?);
+        }
+
+        if !self.cookies.is_empty() {
+            let cookie = self
+                .cookies
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            headers.insert(COOKIE, sensitive(&cookie, "cookies")?);
+        }
+
+        Ok(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        [sites."lwn.net"]
+        cookies = { session = "abc", lang = "en" }
+
+        [sites."example.com"]
+        headers = { Authorization = "Bearer token" }
+
+        [sites."news.example.com"]
+        headers = { "X-Subscriber" = "1" }
+    "#;
+
+    #[test]
+    fn test_cookies_become_one_header() {
+        let credentials = SiteCredentials::parse(FILE).unwrap();
+
+        let headers = credentials.for_host("lwn.net").unwrap();
+        assert_eq!(headers[COOKIE], "lang=en; session=abc");
+    }
+
+    #[test]
+    fn test_most_specific_host_wins() {
+        let credentials = SiteCredentials::parse(FILE).unwrap();
+
+        let headers = credentials.for_host("News.Example.com").unwrap();
+        assert_eq!(headers["x-subscriber"], "1");
+        assert!(!headers.contains_key("authorization"));
+
+        let headers = credentials.for_host("www.example.com").unwrap();
+        assert_eq!(headers["authorization"], "Bearer token");
+
+        assert!(credentials.for_host("notexample.com").is_none());
+    }
+
+    #[test]
+    fn test_values_are_redacted_from_debug() {
+        let credentials = SiteCredentials::parse(FILE).unwrap();
+
+        assert!(!format!("{credentials:?}").contains("abc"));
+    }
+
+    #[test]
+    fn test_invalid_entries_are_rejected() {
+        let error = SiteCredentials::parse(
+            r#"
+            [sites."example.com"]
+            headers = { "Bad Header" = "x" }
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid header Bad Header for example.com"
+        );
+
+        assert!(SiteCredentials::parse("[sites.\"example.com\"]\npassword = \"x\"").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Server-side fetching of saved URLs.
+//!
+//! Redirects are followed only to URLs that would themselves pass
+//! validation, so a saved page can't bounce the fetcher onto a local address.
+//! Requests carry the [`SiteCredentials`] configured for their host, which
+//! may be replaced while the service runs.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{StatusCode, header};
+use tokio::sync::watch;
+use url::Url;
+
+use super::EnrichmentError;
+use super::credentials::SiteCredentials;
+use crate::validation::{ValidatedUrl, ValidationContext};
+
+/// Responses larger than this are abandoned rather than buffered
+pub const MAX_DOCUMENT_BYTES: usize = 25 * 1024 * 1024;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_REDIRECTS: usize = 5;
+
+/// A fetched response body with the URL it was finally served from
+#[derive(Debug, Clone)]
+pub struct FetchedDocument {
+    pub url: String,
+    pub status: StatusCode,
+    pub headers: header::HeaderMap,
+    /// Media type without parameters, lowercased
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct Fetcher {
+    client: reqwest::Client,
+    validation: Arc<ValidationContext>,
+    credentials: watch::Receiver<Arc<SiteCredentials>>,
+    max_bytes: usize,
+}
+
+impl Fetcher {
+    pub fn new(validation: ValidationContext) -> Self {
+        Self::with_max_bytes(validation, MAX_DOCUMENT_BYTES)
+    }
+
+    pub fn with_max_bytes(validation: ValidationContext, max_bytes: usize) -> Self {
+        // Redirects are followed in `fetch`, so each hop is validated and
+        // only gets the credentials configured for its own host
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            client,
+            validation: Arc::new(validation),
+            credentials: watch::channel(Arc::default()).1,
+            max_bytes,
+        }
+    }
+
+    pub fn with_credentials(mut self, credentials: SiteCredentials) -> Self {
+        self.credentials = watch::channel(Arc::new(credentials)).1;
+        self
+    }
+
+    /// Sends whichever credentials `credentials` holds when a fetch starts,
+    /// e.g. those of a [`Reloader`](crate::reload::Reloader)
+    pub fn with_credential_updates(
+        mut self,
+        credentials: watch::Receiver<Arc<SiteCredentials>>,
+    ) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Downloads `url` if its content type satisfies `wanted`; other
+    /// responses return `None` without reading their body
+    pub async fn fetch(
+        &self,
+        url: &str,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<Option<FetchedDocument>, EnrichmentError> {
+        let mut url = Url::parse(url)
+            .map_err(|err| EnrichmentError::InvalidResponse(format!("Invalid URL: {err}")))?;
+        let mut redirects = 0;
+        // One set for every hop, even if a reload lands mid-fetch
+        let credentials = self.credentials.borrow().clone();
+
+        let mut response = loop {
+            let mut request = self.client.get(url.clone());
+            if let Some(headers) = url.host_str().and_then(|host| credentials.for_host(host)) {
+                request = request.headers(headers.clone());
+            }
+            let response = request.send().await?;
+
+            if !response.status().is_redirection() || response.status() == StatusCode::NOT_MODIFIED
+            {
+                break response.error_for_status()?;
+            }
+
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(EnrichmentError::InvalidResponse(
+                    "too many redirects".to_string(),
+                ));
+            }
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|location| url.join(location).ok())
+                .ok_or_else(|| {
+                    EnrichmentError::InvalidResponse("redirect without a location".to_string())
+                })?;
+            if ValidatedUrl::from_url(location.clone(), &self.validation).is_err() {
+                return Err(EnrichmentError::InvalidResponse(format!(
+                    "redirect to a disallowed URL: {location}"
+                )));
+            }
+            url = location;
+        };
+
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        if !wanted(&content_type) {
+            return Ok(None);
+        }
+
+        let too_large = || {
+            EnrichmentError::InvalidResponse(format!("response exceeds {} bytes", self.max_bytes))
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_bytes as u64)
+        {
+            return Err(too_large());
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if bytes.len() + chunk.len() > self.max_bytes {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(Some(FetchedDocument {
+            url: url.to_string(),
+            status: response.status(),
+            headers: response.headers().clone(),
+            content_type,
+            bytes,
+        }))
+    }
+}
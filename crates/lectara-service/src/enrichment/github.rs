@@ -0,0 +1,330 @@
+//! Repository metadata for github.com URLs, from the GitHub REST API.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info};
+
+use super::EnrichmentError;
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Environment variable that disables GitHub lookups when `false` or `0`
+pub const GITHUB_LOOKUP_ENV: &str = "LECTARA_GITHUB_LOOKUP";
+
+/// Environment variable with an optional API token, raising the rate limit
+/// from 60 to 5000 requests an hour
+pub const GITHUB_TOKEN_ENV: &str = "LECTARA_GITHUB_TOKEN";
+
+/// `content_type` given to enriched repository items
+pub const REPOSITORY_CONTENT_TYPE: &str = "repository";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Top-level github.com paths that are site pages rather than owners
+const RESERVED_OWNERS: &[&str] = &[
+    "about",
+    "apps",
+    "collections",
+    "enterprise",
+    "explore",
+    "features",
+    "issues",
+    "login",
+    "marketplace",
+    "notifications",
+    "orgs",
+    "pricing",
+    "pulls",
+    "search",
+    "settings",
+    "sponsors",
+    "topics",
+    "trending",
+];
+
+/// A repository recognized in a saved (normalized) URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryId {
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepositoryId {
+    /// Recognizes `https://github.com/<owner>/<name>` and any page below it
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url::Url::parse(url).ok()?;
+        if !matches!(url.host_str()?, "github.com" | "www.github.com") {
+            return None;
+        }
+
+        let mut segments = url.path_segments()?;
+        let owner = segments.next().filter(|owner| !owner.is_empty())?;
+        let name = segments.next()?;
+        let name = name.strip_suffix(".git").unwrap_or(name);
+        if name.is_empty() || RESERVED_OWNERS.contains(&owner) {
+            return None;
+        }
+
+        Some(RepositoryId {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.name)
+    }
+}
+
+/// The parts of a repository kept on the saved item
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepositoryMetadata {
+    pub full_name: String,
+    pub owner: String,
+    pub description: Option<String>,
+    pub stars: u64,
+    pub language: Option<String>,
+    pub latest_release: Option<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub published_at: Option<String>,
+}
+
+impl RepositoryMetadata {
+    fn into_patch(self) -> MetadataPatch {
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("description".to_string(), json!(self.description));
+        metadata.insert("stars".to_string(), json!(self.stars));
+        metadata.insert("language".to_string(), json!(self.language));
+        metadata.insert(
+            "latest_release".to_string(),
+            json!(self.latest_release.map(|release| json!({
+                "tag": release.tag_name,
+                "published_at": release.published_at,
+            }))),
+        );
+
+        MetadataPatch {
+            title: Some(self.full_name),
+            author: Some(self.owner),
+            content_type: Some(REPOSITORY_CONTENT_TYPE.to_string()),
+            metadata,
+            ..MetadataPatch::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GithubResolver {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl Default for GithubResolver {
+    fn default() -> Self {
+        Self::with_endpoint(GITHUB_API_URL, None)
+    }
+}
+
+impl GithubResolver {
+    /// A resolver using the given API base URL, e.g. a local stub in tests
+    pub fn with_endpoint(api_url: &str, token: Option<&str>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token: token.map(str::to_string),
+        }
+    }
+
+    /// The default resolver, unless disabled via [`GITHUB_LOOKUP_ENV`], using
+    /// the token in [`GITHUB_TOKEN_ENV`] if set
+    pub fn from_env() -> Option<Self> {
+        let disabled = std::env::var(GITHUB_LOOKUP_ENV).is_ok_and(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            )
+        });
+        let token = std::env::var(GITHUB_TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.trim().is_empty());
+        (!disabled).then(|| Self::with_endpoint(GITHUB_API_URL, token.as_deref()))
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(format!("{}{path}", self.api_url))
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Fetches metadata for `id`, returning `None` if the repository doesn't
+    /// exist or is private
+    pub async fn resolve(
+        &self,
+        id: &RepositoryId,
+    ) -> Result<Option<RepositoryMetadata>, EnrichmentError> {
+        let path = format!("/repos/{}/{}", id.owner, id.name);
+
+        let response = self.get(&path).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let repository: GithubRepository = response.error_for_status()?.json().await?;
+
+        // Repositories without releases answer 404 here
+        let response = self.get(&format!("{path}/releases/latest")).send().await?;
+        let latest_release = if response.status() == reqwest::StatusCode::NOT_FOUND {
+            None
+        } else {
+            Some(response.error_for_status()?.json::<Release>().await?)
+        };
+
+        Ok(Some(RepositoryMetadata {
+            full_name: repository.full_name,
+            owner: repository.owner.login,
+            description: repository
+                .description
+                .filter(|description| !description.trim().is_empty()),
+            stars: repository.stargazers_count,
+            language: repository.language,
+            latest_release,
+        }))
+    }
+}
+
+/// Fills github.com items with repository metadata from the GitHub API
+pub struct RepositoryEnricher;
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for RepositoryEnricher {
+    fn name(&self) -> &'static str {
+        "repository"
+    }
+
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state.github_resolver().is_some() && RepositoryId::from_url(&item.url).is_some()
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let (Some(resolver), Some(id)) = (
+            state.github_resolver(),
+            RepositoryId::from_url(&input.item().url),
+        ) else {
+            return Ok(None);
+        };
+
+        debug!(repository = %id.full_name(), "Resolving repository metadata");
+
+        let Some(metadata) = resolver.resolve(&id).await? else {
+            debug!("Repository not found");
+            return Ok(None);
+        };
+
+        info!("Found repository metadata");
+        Ok(Some(metadata.into_patch()))
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubRepository {
+    full_name: String,
+    description: Option<String>,
+    #[serde(default)]
+    stargazers_count: u64,
+    language: Option<String>,
+    owner: GithubOwner,
+}
+
+#[derive(Deserialize)]
+struct GithubOwner {
+    login: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_id_from_url() {
+        let expected = Some(RepositoryId {
+            owner: "rust-lang".to_string(),
+            name: "rust".to_string(),
+        });
+        assert_eq!(
+            RepositoryId::from_url("https://github.com/rust-lang/rust"),
+            expected
+        );
+        assert_eq!(
+            RepositoryId::from_url("https://github.com/rust-lang/rust.git"),
+            expected
+        );
+        assert_eq!(
+            RepositoryId::from_url("https://github.com/rust-lang/rust/issues/1"),
+            expected
+        );
+        assert_eq!(RepositoryId::from_url("https://github.com/rust-lang"), None);
+        assert_eq!(
+            RepositoryId::from_url("https://github.com/topics/rust"),
+            None
+        );
+        assert_eq!(
+            RepositoryId::from_url("https://gitlab.com/rust-lang/rust"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_into_patch() {
+        let patch = RepositoryMetadata {
+            full_name: "rust-lang/rust".to_string(),
+            owner: "rust-lang".to_string(),
+            description: Some("Empowering everyone".to_string()),
+            stars: 100_000,
+            language: Some("Rust".to_string()),
+            latest_release: Some(Release {
+                tag_name: "1.88.0".to_string(),
+                published_at: Some("2025-06-26T13:00:00Z".to_string()),
+            }),
+        }
+        .into_patch();
+
+        assert_eq!(patch.title.as_deref(), Some("rust-lang/rust"));
+        assert_eq!(patch.author.as_deref(), Some("rust-lang"));
+        assert_eq!(patch.content_type.as_deref(), Some("repository"));
+        assert_eq!(
+            serde_json::Value::Object(patch.metadata),
+            json!({
+                "description": "Empowering everyone",
+                "stars": 100_000,
+                "language": "Rust",
+                "latest_release": {"tag": "1.88.0", "published_at": "2025-06-26T13:00:00Z"},
+            })
+        );
+    }
+}
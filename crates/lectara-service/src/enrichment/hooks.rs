@@ -0,0 +1,479 @@
+//! External programs run as enrichment steps or after an item is saved, so
+//! lectara can be extended without recompiling it.
+//!
+//! Hooks are configured in a TOML file:
+//!
+//! ```toml
+//! [[hooks]]
+//! name = "tagger"
+//! command = "/usr/local/bin/lectara-tagger"
+//! args = ["--lang", "en"]
+//! stage = "enrich"        # or "post_save"
+//! timeout_seconds = 10
+//! env = { TAGGER_MODEL = "small" }
+//! ```
+//!
+//! A hook reads `{"stage": ..., "item": {...}}` as JSON on stdin. Enrich
+//! hooks run as pipeline steps after the built-in enrichers and may print
+//! a patch such as `{"title": "...", "metadata": {"tags": [...]}}` to
+//! stdout; empty output means nothing was found. Post-save hooks run once
+//! the pipeline has finished and their output is ignored.
+//!
+//! Hooks don't inherit the server's environment, which holds database and
+//! API credentials: they see only `PATH`, `HOME` pointing at their working
+//! directory, and the variables set in `env`. They are killed when they
+//! run past their timeout.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tracing::debug;
+
+use super::EnrichmentError;
+use super::pipeline::{BUILTIN_ENRICHERS, Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+
+/// Environment variable naming the hooks file; no hooks run when it is unset
+pub const HOOKS_ENV: &str = "LECTARA_HOOKS";
+
+/// `PATH` given to hooks that don't set their own
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+const MAX_TIMEOUT_SECONDS: u64 = 600;
+/// Output beyond this is an error rather than a patch
+const MAX_OUTPUT_BYTES: u64 = 1024 * 1024;
+/// Characters of stderr included in the error of a failed hook
+const MAX_ERROR_CHARS: usize = 500;
+
+#[derive(Error, Debug)]
+pub enum HookConfigError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid hooks file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid hook {name:?}: {reason}")]
+    InvalidHook { name: String, reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    /// A pipeline step whose output is applied to the item
+    #[default]
+    Enrich,
+    /// Notified once the pipeline has finished; output is ignored
+    PostSave,
+}
+
+impl HookStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Enrich => "enrich",
+            Self::PostSave => "post_save",
+        }
+    }
+}
+
+/// An external program and how to run it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandHook {
+    name: String,
+    command: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    stage: HookStage,
+    #[serde(default = "default_timeout_seconds")]
+    timeout_seconds: u64,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    /// Defaults to the system temporary directory
+    #[serde(default)]
+    working_dir: Option<PathBuf>,
+}
+
+fn default_timeout_seconds() -> u64 {
+    DEFAULT_TIMEOUT_SECONDS
+}
+
+/// What an enrich hook may print; every field is optional
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HookOutput {
+    title: Option<String>,
+    author: Option<String>,
+    body: Option<String>,
+    content_type: Option<String>,
+    summary: Option<String>,
+    #[serde(default)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<HookOutput> for MetadataPatch {
+    fn from(output: HookOutput) -> Self {
+        MetadataPatch {
+            title: output.title,
+            author: output.author,
+            body: output.body,
+            content_type: output.content_type,
+            summary: output.summary,
+            metadata: output.metadata,
+        }
+    }
+}
+
+impl CommandHook {
+    pub fn new(name: &str, command: impl Into<PathBuf>, stage: HookStage) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.into(),
+            args: Vec::new(),
+            stage,
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+            env: BTreeMap::new(),
+            working_dir: None,
+        }
+    }
+
+    pub fn with_args<I: IntoIterator<Item = S>, S: Into<String>>(mut self, args: I) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.env.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_seconds = timeout.as_secs().max(1);
+        self
+    }
+
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stage(&self) -> HookStage {
+        self.stage
+    }
+
+    fn validate(&self) -> Result<(), HookConfigError> {
+        let invalid = |reason: &str| HookConfigError::InvalidHook {
+            name: self.name.clone(),
+            reason: reason.to_string(),
+        };
+
+        let valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(invalid(
+                "names may only contain lowercase letters, digits, '-' and '_'",
+            ));
+        }
+        if BUILTIN_ENRICHERS.contains(&self.name.as_str()) {
+            return Err(invalid("the name is taken by a built-in enricher"));
+        }
+        // The environment is cleared, so there is no PATH to search
+        if !self.command.is_absolute() {
+            return Err(invalid("command must be an absolute path"));
+        }
+        if !(1..=MAX_TIMEOUT_SECONDS).contains(&self.timeout_seconds) {
+            return Err(invalid(&format!(
+                "timeout_seconds must be between 1 and {MAX_TIMEOUT_SECONDS}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Runs the program with `item` on stdin, returning what it printed
+    /// as a patch
+    pub async fn run(&self, item: &ContentItem) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let failed = |reason: String| EnrichmentError::Hook {
+            name: self.name.clone(),
+            reason,
+        };
+
+        let input = hook_input(self.stage.as_str(), item);
+
+        let working_dir = self.working_dir.clone().unwrap_or_else(std::env::temp_dir);
+        let mut command = Command::new(&self.command);
+        command
+            .args(&self.args)
+            .env_clear()
+            .env("PATH", DEFAULT_PATH)
+            .env("HOME", &working_dir)
+            .envs(&self.env)
+            .current_dir(&working_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command
+            .spawn()
+            .map_err(|err| failed(format!("could not start {}: {err}", self.command.display())))?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let mut stdout = child
+            .stdout
+            .take()
+            .expect("stdout is piped")
+            .take(MAX_OUTPUT_BYTES + 1);
+        let mut stderr = child
+            .stderr
+            .take()
+            .expect("stderr is piped")
+            .take(MAX_OUTPUT_BYTES);
+
+        let run = async {
+            // A hook may exit without reading its input, closing the pipe
+            let write = async {
+                let _ = stdin.write_all(&input).await;
+                drop(stdin);
+            };
+            let mut output = Vec::new();
+            let mut errors = Vec::new();
+            let read_output = stdout.read_to_end(&mut output);
+            let read_errors = stderr.read_to_end(&mut errors);
+            let ((), output_read, _) = tokio::join!(write, read_output, read_errors);
+            output_read?;
+            let status = child.wait().await?;
+            Ok::<_, std::io::Error>((status, output, errors))
+        };
+
+        let timeout = Duration::from_secs(self.timeout_seconds);
+        let (status, output, errors) = match tokio::time::timeout(timeout, run).await {
+            Ok(result) => result.map_err(|err| failed(err.to_string()))?,
+            // Dropping the child kills it
+            Err(_) => {
+                return Err(failed(format!("timed out after {}s", self.timeout_seconds)));
+            }
+        };
+
+        if !status.success() {
+            let stderr: String = String::from_utf8_lossy(&errors)
+                .trim()
+                .chars()
+                .take(MAX_ERROR_CHARS)
+                .collect();
+            return Err(failed(format!("{status}: {stderr}")));
+        }
+        if output.len() as u64 > MAX_OUTPUT_BYTES {
+            return Err(failed(format!(
+                "printed more than {MAX_OUTPUT_BYTES} bytes"
+            )));
+        }
+        if self.stage == HookStage::PostSave || output.iter().all(u8::is_ascii_whitespace) {
+            debug!(hook = self.name, "Hook printed no patch");
+            return Ok(None);
+        }
+
+        let patch = parse_patch(&output).map_err(|err| failed(format!("invalid output: {err}")))?;
+        Ok(Some(patch))
+    }
+}
+
+/// The JSON document extensions read: the stage they run in and the item,
+/// with its metadata as an object
+pub(crate) fn hook_input(stage: &str, item: &ContentItem) -> Vec<u8> {
+    let mut item_json = serde_json::to_value(item).expect("items serialize to JSON");
+    item_json["metadata"] = item.metadata().into();
+    serde_json::to_vec(&json!({
+        "stage": stage,
+        "item": item_json,
+    }))
+    .expect("JSON values serialize")
+}
+
+/// A patch printed by an extension; unknown fields are rejected so typos
+/// don't go unnoticed
+pub(crate) fn parse_patch(output: &[u8]) -> Result<MetadataPatch, serde_json::Error> {
+    serde_json::from_slice::<HookOutput>(output).map(Into::into)
+}
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for CommandHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn applies(&self, _state: &S, _item: &ContentItem) -> bool {
+        self.stage == HookStage::Enrich
+    }
+
+    async fn enrich(
+        &self,
+        _state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        self.run(input.item()).await
+    }
+}
+
+/// The hooks configured in the hooks file, in file order
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    hooks: Vec<CommandHook>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HooksFile {
+    #[serde(default)]
+    hooks: Vec<CommandHook>,
+}
+
+impl Hooks {
+    /// Loads the file named by [`HOOKS_ENV`], or no hooks when it is unset
+    pub fn from_env() -> Result<Self, HookConfigError> {
+        match std::env::var(HOOKS_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, HookConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| HookConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, HookConfigError> {
+        let file: HooksFile = toml::from_str(contents)?;
+        for (index, hook) in file.hooks.iter().enumerate() {
+            hook.validate()?;
+            if file.hooks[..index]
+                .iter()
+                .any(|earlier| earlier.name == hook.name)
+            {
+                return Err(HookConfigError::InvalidHook {
+                    name: hook.name.clone(),
+                    reason: "another hook has the same name".to_string(),
+                });
+            }
+        }
+        Ok(Self { hooks: file.hooks })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn into_hooks(self) -> Vec<CommandHook> {
+        self.hooks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks_file() {
+        let hooks = Hooks::parse(
+            r#"
+            [[hooks]]
+            name = "tagger"
+            command = "/usr/local/bin/tagger"
+            args = ["--fast"]
+            env = { MODEL = "small" }
+
+            [[hooks]]
+            name = "notify"
+            command = "/usr/bin/notify-send"
+            stage = "post_save"
+            timeout_seconds = 5
+            "#,
+        )
+        .unwrap()
+        .into_hooks();
+
+        assert_eq!(hooks.len(), 2);
+        assert_eq!(hooks[0].stage(), HookStage::Enrich);
+        assert_eq!(hooks[0].timeout_seconds, DEFAULT_TIMEOUT_SECONDS);
+        assert_eq!(hooks[0].env["MODEL"], "small");
+        assert_eq!(hooks[1].stage(), HookStage::PostSave);
+        assert_eq!(hooks[1].timeout_seconds, 5);
+    }
+
+    #[test]
+    fn test_invalid_hooks_are_rejected() {
+        let hook = |fields: &str| Hooks::parse(&format!("[[hooks]]\n{fields}"));
+
+        assert!(
+            hook(
+                r#"name = "Tagger"
+            command = "/bin/tagger""#
+            )
+            .is_err()
+        );
+        assert!(
+            hook(
+                r#"name = "summary"
+            command = "/bin/tagger""#
+            )
+            .is_err()
+        );
+        assert!(
+            hook(
+                r#"name = "tagger"
+            command = "tagger""#
+            )
+            .is_err()
+        );
+        assert!(
+            hook(
+                r#"name = "tagger"
+            command = "/bin/tagger"
+            timeout_seconds = 0"#
+            )
+            .is_err()
+        );
+        assert!(
+            hook(
+                r#"name = "tagger"
+            command = "/bin/tagger"
+            shell = true"#
+            )
+            .is_err()
+        );
+        assert!(
+            Hooks::parse(
+                r#"
+                [[hooks]]
+                name = "tagger"
+                command = "/bin/a"
+                [[hooks]]
+                name = "tagger"
+                command = "/bin/b"
+                "#
+            )
+            .is_err()
+        );
+    }
+}
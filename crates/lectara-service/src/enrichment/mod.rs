@@ -0,0 +1,44 @@
+//! Enrichment of saved items with metadata from external services.
+//!
+//! Lookups run in the background after an item is created, so a slow or
+//! unavailable upstream never delays or fails the save itself.
+
+pub mod attempts;
+pub mod changes;
+pub mod citations;
+pub mod credentials;
+pub mod fetch;
+pub mod github;
+pub mod hooks;
+pub mod pdf;
+pub mod pipeline;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugins;
+pub mod site_rules;
+pub mod summary;
+pub mod threads;
+
+use thiserror::Error;
+
+use crate::errors::ApiError;
+
+#[derive(Error, Debug)]
+pub enum EnrichmentError {
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Unexpected response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Failed to store enrichment result: {0}")]
+    Storage(#[from] ApiError),
+
+    #[error("Enricher panicked")]
+    Panicked,
+
+    #[error("Hook {name} failed: {reason}")]
+    Hook { name: String, reason: String },
+
+    #[error("Plugin {name} failed: {reason}")]
+    Plugin { name: String, reason: String },
+}
@@ -0,0 +1,182 @@
+//! Text extraction for saved URLs that turn out to be PDFs.
+
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::{debug, info};
+
+use super::EnrichmentError;
+use super::fetch::{FetchedDocument, Fetcher};
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+
+/// Environment variable that disables PDF extraction when `false` or `0`
+pub const PDF_EXTRACTION_ENV: &str = "LECTARA_PDF_EXTRACTION";
+
+/// `content_type` given to items whose URL served a PDF
+pub const PDF_CONTENT_TYPE: &str = "pdf";
+
+const PDF_MEDIA_TYPE: &str = "application/pdf";
+
+/// Text of a PDF, one entry per page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfText {
+    pub pages: Vec<String>,
+}
+
+impl PdfText {
+    /// Non-empty pages separated by blank lines
+    pub fn text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|page| page.trim())
+            .filter(|page| !page.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn into_patch(self) -> MetadataPatch {
+        let text = self.text();
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("page_count".to_string(), json!(self.pages.len()));
+
+        MetadataPatch {
+            // Scanned PDFs have pages but no text layer
+            body: (!text.is_empty()).then_some(text),
+            content_type: Some(PDF_CONTENT_TYPE.to_string()),
+            metadata,
+            ..MetadataPatch::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PdfExtractor {
+    fetcher: Fetcher,
+}
+
+impl PdfExtractor {
+    pub fn new(fetcher: Fetcher) -> Self {
+        Self { fetcher }
+    }
+
+    /// An extractor downloading through `fetcher`, unless disabled via
+    /// [`PDF_EXTRACTION_ENV`]
+    pub fn from_env(fetcher: Fetcher) -> Option<Self> {
+        let disabled = std::env::var(PDF_EXTRACTION_ENV).is_ok_and(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            )
+        });
+        (!disabled).then(|| Self::new(fetcher))
+    }
+
+    /// Downloads `url` and extracts its text, returning `None` if it isn't
+    /// served as a PDF
+    pub async fn extract(&self, url: &str) -> Result<Option<PdfText>, EnrichmentError> {
+        let Some(document) = self
+            .fetcher
+            .fetch(url, |content_type| content_type == PDF_MEDIA_TYPE)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        parse(document.bytes).await.map(Some)
+    }
+
+    /// Extracts the text of an already downloaded document, returning `None`
+    /// if it isn't a PDF
+    pub async fn extract_document(
+        &self,
+        document: &FetchedDocument,
+    ) -> Result<Option<PdfText>, EnrichmentError> {
+        if document.content_type != PDF_MEDIA_TYPE {
+            return Ok(None);
+        }
+        parse(document.bytes.clone()).await.map(Some)
+    }
+}
+
+async fn parse(bytes: Vec<u8>) -> Result<PdfText, EnrichmentError> {
+    // Parsing is CPU-bound, and malformed files can panic inside the
+    // parser, which the blocking task turns into an error
+    let pages =
+        tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem_by_pages(&bytes))
+            .await
+            .map_err(|err| EnrichmentError::InvalidResponse(format!("PDF parser failed: {err}")))?
+            .map_err(|err| EnrichmentError::InvalidResponse(format!("Unreadable PDF: {err}")))?;
+
+    Ok(PdfText { pages })
+}
+
+/// Extracts the text of bodiless items whose URL serves a PDF
+pub struct PdfEnricher;
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for PdfEnricher {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    // Every URL has to be downloaded to learn its content type, and a
+    // submitted body is what the client wants stored, so only bodiless
+    // items are worth checking
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state.pdf_extractor().is_some() && item.url.starts_with("http") && item.body.is_none()
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let Some(extractor) = state.pdf_extractor() else {
+            return Ok(None);
+        };
+
+        debug!("Checking for a PDF");
+
+        let document = input.document(state.fetcher()).await?;
+        let Some(pdf) = extractor.extract_document(document).await? else {
+            debug!("Not a PDF");
+            return Ok(None);
+        };
+
+        info!(page_count = pdf.pages.len(), "Extracted PDF text");
+        Ok(Some(pdf.into_patch()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_skips_blank_pages() {
+        let pdf = PdfText {
+            pages: vec![
+                " First page\n".to_string(),
+                "\n\n".to_string(),
+                "Third page".to_string(),
+            ],
+        };
+        assert_eq!(pdf.text(), "First page\n\nThird page");
+
+        let patch = pdf.into_patch();
+        assert_eq!(patch.metadata["page_count"], 3);
+        assert_eq!(patch.content_type.as_deref(), Some("pdf"));
+    }
+
+    #[test]
+    fn test_scanned_pdf_has_no_body() {
+        let patch = PdfText {
+            pages: vec![String::new(), String::new()],
+        }
+        .into_patch();
+
+        assert_eq!(patch.body, None);
+        assert_eq!(patch.metadata["page_count"], 2);
+    }
+}
@@ -0,0 +1,284 @@
+//! The enrichment pipeline: a registry of [`Enricher`] steps run in order
+//! for each newly saved item.
+//!
+//! Each step sees the item as left by the steps before it, so e.g. the
+//! summarizer can summarize a body the PDF step just extracted. A failing
+//! or panicking step is recorded and retried on its own without affecting
+//! the others. Configured [`hooks`](super::hooks) run after the built-in
+//! steps.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use futures_util::FutureExt;
+use thiserror::Error;
+use tokio::sync::OnceCell;
+use tracing::{Instrument, debug, info, info_span, warn};
+
+use super::EnrichmentError;
+use super::attempts;
+use super::citations::CitationEnricher;
+use super::fetch::{FetchedDocument, Fetcher};
+use super::github::RepositoryEnricher;
+use super::hooks::{CommandHook, HookStage, Hooks};
+use super::pdf::PdfEnricher;
+use super::site_rules::SiteRulesEnricher;
+use super::summary::SummaryEnricher;
+use super::threads::ThreadEnricher;
+use crate::AppState;
+use crate::federation;
+use crate::models::{ContentItem, MetadataPatch};
+use crate::notifications::ContentEventKind;
+use crate::repositories::ContentRepository;
+
+/// Environment variable listing the enrichers to run, comma-separated and
+/// in order; all built-in ones run when it is unset
+pub const ENRICHERS_ENV: &str = "LECTARA_ENRICHERS";
+
+/// Built-in enrichers in their default order. Steps that may fill in the
+/// body come before the summarizer, which needs it.
+pub const BUILTIN_ENRICHERS: [&str; 6] =
+    ["citation", "thread", "repository", "site", "pdf", "summary"];
+
+/// One step of the pipeline
+#[async_trait]
+pub trait Enricher<S: AppState>: Send + Sync + 'static {
+    /// Name used in configuration and recorded with fetch attempts
+    fn name(&self) -> &str;
+
+    /// Whether this step is enabled and relevant for `item`
+    fn applies(&self, state: &S, item: &ContentItem) -> bool;
+
+    /// Metadata found for the item, or `None` if there is nothing to add.
+    /// Fields the item already has are left alone when the patch is applied.
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError>;
+}
+
+/// What a step works from: the item and, on request, the page at its URL
+pub struct EnrichmentInput {
+    item: ContentItem,
+    document: OnceCell<FetchedDocument>,
+}
+
+impl EnrichmentInput {
+    pub fn new(item: ContentItem) -> Self {
+        Self {
+            item,
+            document: OnceCell::new(),
+        }
+    }
+
+    pub fn item(&self) -> &ContentItem {
+        &self.item
+    }
+
+    /// The page at the item's URL. It is downloaded by the first step that
+    /// asks for it and shared with the later ones; a failed download is
+    /// tried again by the next step that asks.
+    pub async fn document(&self, fetcher: &Fetcher) -> Result<&FetchedDocument, EnrichmentError> {
+        self.document
+            .get_or_try_init(|| async {
+                let document = fetcher.fetch(&self.item.url, |_| true).await?;
+                Ok(document.expect("every content type is accepted"))
+            })
+            .await
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown enricher {0:?}; expected one of {BUILTIN_ENRICHERS:?}")]
+pub struct UnknownEnricher(pub String);
+
+/// The enrichers to run, in order, and the hooks to notify afterwards
+pub struct EnricherRegistry<S> {
+    enrichers: Vec<Arc<dyn Enricher<S>>>,
+    post_save: Vec<CommandHook>,
+}
+
+impl<S: AppState> Default for EnricherRegistry<S> {
+    fn default() -> Self {
+        Self::from_names(&BUILTIN_ENRICHERS).expect("built-in enrichers exist")
+    }
+}
+
+impl<S: AppState> EnricherRegistry<S> {
+    pub fn new() -> Self {
+        Self {
+            enrichers: Vec::new(),
+            post_save: Vec::new(),
+        }
+    }
+
+    /// Appends `enricher` as the last step
+    pub fn with(mut self, enricher: impl Enricher<S>) -> Self {
+        self.enrichers.push(Arc::new(enricher));
+        self
+    }
+
+    /// Appends the enrich hooks as steps after the current ones, and
+    /// registers the post-save hooks
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        for hook in hooks.into_hooks() {
+            match hook.stage() {
+                HookStage::Enrich => self = self.with(hook),
+                HookStage::PostSave => self.post_save.push(hook),
+            }
+        }
+        self
+    }
+
+    /// The named built-in enrichers, in the given order
+    pub fn from_names<N: AsRef<str>>(names: &[N]) -> Result<Self, UnknownEnricher> {
+        names
+            .iter()
+            .try_fold(Self::new(), |registry, name| match name.as_ref() {
+                "citation" => Ok(registry.with(CitationEnricher)),
+                "thread" => Ok(registry.with(ThreadEnricher)),
+                "repository" => Ok(registry.with(RepositoryEnricher)),
+                "site" => Ok(registry.with(SiteRulesEnricher)),
+                "pdf" => Ok(registry.with(PdfEnricher)),
+                "summary" => Ok(registry.with(SummaryEnricher)),
+                other => Err(UnknownEnricher(other.to_string())),
+            })
+    }
+
+    /// The enrichers listed in [`ENRICHERS_ENV`], or all built-in ones
+    pub fn from_env() -> Result<Self, UnknownEnricher> {
+        match std::env::var(ENRICHERS_ENV) {
+            Ok(names) => {
+                let names: Vec<&str> = names
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                Self::from_names(&names)
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Enricher<S>>> {
+        self.enrichers
+            .iter()
+            .find(|enricher| enricher.name() == name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.enrichers
+            .iter()
+            .map(|enricher| enricher.name())
+            .collect()
+    }
+}
+
+/// Runs the pipeline for a newly saved item in the background
+pub fn spawn_enrichment<S: AppState>(state: &S, item: &ContentItem) {
+    let state = state.clone();
+    let item = item.clone();
+    tokio::spawn(async move {
+        run_pipeline(&state, item).await;
+    });
+}
+
+/// Runs every applicable step in order, recording each outcome, then
+/// hands the enriched item to the post-save hooks and publishes it
+pub async fn run_pipeline<S: AppState>(state: &S, item: ContentItem) {
+    let content_id = item.id;
+    let mut input = EnrichmentInput::new(item);
+
+    for enricher in &state.enrichers().enrichers {
+        if !enricher.applies(state, input.item()) {
+            continue;
+        }
+        if let Some(updated) = attempts::run_and_record(state, enricher.as_ref(), &input).await {
+            input.item = updated;
+        }
+    }
+
+    for hook in &state.enrichers().post_save {
+        if let Err(err) = hook.run(input.item()).await {
+            warn!(error = %err, content_id, "Post-save hook failed");
+        }
+    }
+    publish(state, input.item()).await;
+
+    debug!(content_id, "Enrichment pipeline finished");
+}
+
+/// Passes a newly saved item on to the sync target and the fediverse
+/// followers that should get it
+pub async fn publish<S: AppState>(state: &S, item: &ContentItem) {
+    federation::push_if_selected(state, item).await;
+    #[cfg(feature = "activitypub")]
+    crate::activitypub::deliver_if_published(state, item).await;
+}
+
+/// Runs one step and applies its patch, returning the updated item if the
+/// step changed it. Panics are caught and reported as errors.
+pub async fn run_step<S: AppState>(
+    state: &S,
+    enricher: &dyn Enricher<S>,
+    input: &EnrichmentInput,
+) -> Result<Option<ContentItem>, EnrichmentError> {
+    let span = info_span!(
+        "enricher",
+        name = enricher.name(),
+        content_id = input.item().id
+    );
+    async {
+        let started = Instant::now();
+        let patch = AssertUnwindSafe(enricher.enrich(state, input))
+            .catch_unwind()
+            .await
+            .map_err(|_| EnrichmentError::Panicked)??;
+
+        let Some(patch) = patch else {
+            debug!(elapsed_ms = started.elapsed().as_millis(), "Nothing found");
+            return Ok(None);
+        };
+
+        let item = state
+            .content_repo()
+            .apply_enrichment(input.item().id, &patch)
+            .await?;
+        info!(
+            elapsed_ms = started.elapsed().as_millis(),
+            "Applied enrichment"
+        );
+        if let Some(item) = &item {
+            state
+                .notifier()
+                .publish_content(ContentEventKind::Updated, item);
+        }
+        Ok(item)
+    }
+    .instrument(span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultAppState;
+
+    #[test]
+    fn test_registry_from_names_keeps_order() {
+        let registry =
+            EnricherRegistry::<DefaultAppState>::from_names(&["summary", "pdf"]).unwrap();
+        assert_eq!(registry.names(), ["summary", "pdf"]);
+        assert!(registry.get("pdf").is_some());
+        assert!(registry.get("citation").is_none());
+
+        assert_eq!(
+            EnricherRegistry::<DefaultAppState>::default().names(),
+            BUILTIN_ENRICHERS
+        );
+        assert!(EnricherRegistry::<DefaultAppState>::from_names(&["opengraph"]).is_err());
+    }
+}
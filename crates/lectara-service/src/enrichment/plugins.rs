@@ -0,0 +1,295 @@
+//! Processors compiled to WebAssembly, run in an embedded runtime as
+//! enrichment steps. Unlike [`hooks`](super::hooks) they need no process
+//! per run and behave the same on every platform.
+//!
+//! Every `.wasm` (or `.wat`) file in the directory named by
+//! [`PLUGINS_ENV`] is loaded at startup, in file name order, and named
+//! after its file stem. A plugin is a core module exporting:
+//!
+//! - `memory`
+//! - `lectara_api_version() -> i32`, returning [`PLUGIN_API_VERSION`]
+//! - `lectara_alloc(len: i32) -> i32`, returning where the host may write
+//!   `len` bytes of input
+//! - `lectara_process(ptr: i32, len: i32) -> i64`, given the same JSON
+//!   document hooks read on stdin and returning the location of a JSON
+//!   patch as `ptr << 32 | len`, or 0 when it has nothing to add
+//!
+//! Modules may not import anything, so a plugin can't reach the file
+//! system, the network or the clock. Each run gets a fresh instance with
+//! capped memory and a fuel budget, so a runaway plugin fails instead of
+//! stalling the pipeline.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::EnrichmentError;
+use super::hooks::{hook_input, parse_patch};
+use super::pipeline::{BUILTIN_ENRICHERS, Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+
+/// Environment variable naming the plugin directory; no plugins are loaded
+/// when it is unset
+pub const PLUGINS_ENV: &str = "LECTARA_PLUGINS";
+
+/// Version of the guest interface described above
+pub const PLUGIN_API_VERSION: i32 = 1;
+
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// Instructions, roughly, a plugin may run per item
+const FUEL_PER_RUN: u64 = 2_000_000_000;
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid plugin {name:?}: {reason}")]
+    Invalid { name: String, reason: String },
+}
+
+/// A loaded plugin module
+#[derive(Clone)]
+pub struct WasmProcessor {
+    name: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmProcessor {
+    /// Compiles `bytes`, a binary module or its text format, and checks
+    /// that it implements the guest interface
+    pub fn new(engine: &Engine, name: &str, bytes: &[u8]) -> Result<Self, PluginError> {
+        let invalid = |reason: String| PluginError::Invalid {
+            name: name.to_string(),
+            reason,
+        };
+
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(invalid(
+                "names may only contain lowercase letters, digits, '-' and '_'".to_string(),
+            ));
+        }
+        if BUILTIN_ENRICHERS.contains(&name) {
+            return Err(invalid(
+                "the name is taken by a built-in enricher".to_string(),
+            ));
+        }
+
+        let module = Module::new(engine, bytes).map_err(|err| invalid(format!("{err:#}")))?;
+        if let Some(import) = module.imports().next() {
+            return Err(invalid(format!(
+                "plugins may not import anything, but it imports {}::{}",
+                import.module(),
+                import.name()
+            )));
+        }
+
+        let processor = Self {
+            name: name.to_string(),
+            engine: engine.clone(),
+            module,
+        };
+        let (mut store, instance) = processor.instantiate().map_err(invalid)?;
+        let version = instance
+            .get_typed_func::<(), i32>(&mut store, "lectara_api_version")
+            .and_then(|version| version.call(&mut store, ()))
+            .map_err(|err| invalid(format!("{err:#}")))?;
+        if version != PLUGIN_API_VERSION {
+            return Err(invalid(format!(
+                "it targets plugin API version {version}, not {PLUGIN_API_VERSION}"
+            )));
+        }
+        Ok(processor)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance), String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(FUEL_PER_RUN)
+            .expect("fuel is enabled on the engine");
+        let instance =
+            Instance::new(&mut store, &self.module, &[]).map_err(|err| format!("{err:#}"))?;
+        Ok((store, instance))
+    }
+
+    /// Runs the plugin on `item` in a fresh instance, returning its patch
+    pub fn process(&self, item: &ContentItem) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let failed = |reason: String| EnrichmentError::Plugin {
+            name: self.name.clone(),
+            reason,
+        };
+        let trapped = |err: wasmtime::Error| failed(format!("{err:#}"));
+
+        let input = hook_input("enrich", item);
+        let input_len = i32::try_from(input.len()).map_err(|_| failed("item too large".into()))?;
+
+        let (mut store, instance) = self.instantiate().map_err(failed)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| failed("no exported memory".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "lectara_alloc")
+            .map_err(trapped)?;
+        let process = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "lectara_process")
+            .map_err(trapped)?;
+
+        let input_ptr = alloc.call(&mut store, input_len).map_err(trapped)?;
+        memory
+            .write(&mut store, input_ptr as u32 as usize, &input)
+            .map_err(|err| failed(format!("input out of bounds: {err}")))?;
+
+        let location = process
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(trapped)? as u64;
+        if location == 0 {
+            return Ok(None);
+        }
+
+        let (output_ptr, output_len) = ((location >> 32) as usize, (location as u32) as usize);
+        let mut output = vec![0; output_len];
+        memory
+            .read(&store, output_ptr, &mut output)
+            .map_err(|err| failed(format!("output out of bounds: {err}")))?;
+        let patch = parse_patch(&output).map_err(|err| failed(format!("invalid output: {err}")))?;
+        Ok(Some(patch))
+    }
+}
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for WasmProcessor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn applies(&self, _state: &S, _item: &ContentItem) -> bool {
+        true
+    }
+
+    async fn enrich(
+        &self,
+        _state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        // Plugins run to completion or until their fuel runs out, so they
+        // are kept off the async worker threads
+        let processor = self.clone();
+        let item = input.item().clone();
+        tokio::task::spawn_blocking(move || processor.process(&item))
+            .await
+            .map_err(|_| EnrichmentError::Panicked)?
+    }
+}
+
+/// An engine configured for running plugins
+pub fn plugin_engine() -> Engine {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("plugin engine configuration is valid")
+}
+
+/// Loads the plugins in the directory named by [`PLUGINS_ENV`], or none
+/// when it is unset
+pub fn load_from_env() -> Result<Vec<WasmProcessor>, PluginError> {
+    match std::env::var(PLUGINS_ENV) {
+        Ok(dir) if !dir.trim().is_empty() => load_dir(Path::new(dir.trim())),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Loads every `.wasm` and `.wat` file in `dir`, in file name order
+pub fn load_dir(dir: &Path) -> Result<Vec<WasmProcessor>, PluginError> {
+    let read_error = |source| PluginError::Read {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "wasm" || extension == "wat")
+    });
+    paths.sort();
+
+    let engine = plugin_engine();
+    let mut processors: Vec<WasmProcessor> = Vec::new();
+    for path in &paths {
+        let bytes = std::fs::read(path).map_err(|source| PluginError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if processors.iter().any(|processor| processor.name == name) {
+            return Err(PluginError::Invalid {
+                name,
+                reason: "another plugin has the same name".to_string(),
+            });
+        }
+        processors.push(WasmProcessor::new(&engine, &name, &bytes)?);
+    }
+    Ok(processors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION_ONLY: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "lectara_api_version") (result i32) i32.const 1))"#;
+
+    fn load(name: &str, wat: &str) -> Result<WasmProcessor, PluginError> {
+        WasmProcessor::new(&plugin_engine(), name, wat.as_bytes())
+    }
+
+    #[test]
+    fn test_plugins_are_checked_when_loaded() {
+        assert!(load("version-only", VERSION_ONLY).is_ok());
+
+        let error = |name: &str, wat: &str| load(name, wat).err().unwrap().to_string();
+        assert_eq!(
+            error(
+                "networked",
+                r#"(module (import "env" "connect" (func)) (memory (export "memory") 1))"#
+            ),
+            "Invalid plugin \"networked\": plugins may not import anything, \
+             but it imports env::connect"
+        );
+        assert_eq!(
+            error(
+                "future",
+                &VERSION_ONLY.replace("i32.const 1", "i32.const 2")
+            ),
+            "Invalid plugin \"future\": it targets plugin API version 2, not 1"
+        );
+        assert!(error("unversioned", "(module)").contains("lectara_api_version"));
+        assert!(error("summary", VERSION_ONLY).contains("built-in"));
+        assert!(error("Upper", VERSION_ONLY).contains("names may only"));
+    }
+}
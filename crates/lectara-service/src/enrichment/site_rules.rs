@@ -0,0 +1,698 @@
+//! Per-site extraction rules, for pages whose title, author or article
+//! text can't be found without knowing the site's markup.
+//!
+//! Rules live in a directory of TOML files, one per site:
+//!
+//! ```toml
+//! # lwn.net.toml
+//! hosts = ["lwn.net"]            # defaults to the file name
+//! title = "div.PageHeadline h1"
+//! author = ["//div[@class='Byline']/a", ".FeatureByline b"]
+//! body = "div.ArticleText"
+//! strip = ["div.ad", "table.Form"]
+//! ```
+//!
+//! Each field takes one selector or a list tried in order. Selectors
+//! starting with `/` are XPath, as in the site configs of graby and
+//! Wallabag; the commonly used subset (element steps, `@attr = '...'`,
+//! `contains(@attr, '...')`, positions and `and`) is translated to CSS.
+//!
+//! Like the fetch credentials, a rule matches its hosts and their
+//! subdomains, and the most specific one wins. The directory is checked
+//! for changes at most every [`RELOAD_CHECK_INTERVAL`] and reloaded when a
+//! file was added, removed or modified; a broken edit keeps the previous
+//! rules in place.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use super::EnrichmentError;
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+use crate::validation::host_matches;
+
+/// Environment variable naming the rules directory; no rules apply when
+/// it is unset
+pub const SITE_RULES_ENV: &str = "LECTARA_SITE_RULES";
+
+/// How long a loaded set of rules is used before checking for changes
+pub const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Elements whose text is never part of an extracted field
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "noscript", "template"];
+
+/// Elements whose text is kept apart from the text around them
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+#[derive(Error, Debug)]
+pub enum SiteRulesError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid site rules in {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid selector {selector:?} in {}: {reason}", path.display())]
+    InvalidSelector {
+        path: PathBuf,
+        selector: String,
+        reason: String,
+    },
+}
+
+/// An XPath expression outside the supported subset
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unsupported XPath: {0}")]
+pub struct UnsupportedXpath(pub String);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(selector) => vec![selector],
+            Self::Many(selectors) => selectors,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    hosts: Option<Vec<String>>,
+    title: Option<OneOrMany>,
+    author: Option<OneOrMany>,
+    body: Option<OneOrMany>,
+    strip: Option<OneOrMany>,
+}
+
+/// The rules for one site, with selectors compiled
+#[derive(Debug, Clone)]
+pub struct SiteRule {
+    hosts: Vec<String>,
+    title: Vec<Selector>,
+    author: Vec<Selector>,
+    body: Vec<Selector>,
+    strip: Vec<Selector>,
+}
+
+/// Fields found on a page by a [`SiteRule`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extracted {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub body: Option<String>,
+}
+
+impl SiteRule {
+    /// Parses one rules file; `default_host` is used when it lists no hosts
+    pub fn parse(contents: &str, default_host: &str, path: &Path) -> Result<Self, SiteRulesError> {
+        let file: RuleFile = toml::from_str(contents).map_err(|source| SiteRulesError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let compile = |selectors: Option<OneOrMany>| {
+            selectors
+                .map(OneOrMany::into_vec)
+                .unwrap_or_default()
+                .iter()
+                .map(|selector| {
+                    compile_selector(selector).map_err(|reason| SiteRulesError::InvalidSelector {
+                        path: path.to_path_buf(),
+                        selector: selector.clone(),
+                        reason,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let hosts = file
+            .hosts
+            .unwrap_or_else(|| vec![default_host.to_string()])
+            .iter()
+            .map(|host| host.trim().trim_start_matches('.').to_lowercase())
+            .collect();
+        Ok(Self {
+            hosts,
+            title: compile(file.title)?,
+            author: compile(file.author)?,
+            body: compile(file.body)?,
+            strip: compile(file.strip)?,
+        })
+    }
+
+    /// Whether the rule can fill in a field `item` is missing
+    fn fills_gap(&self, item: &ContentItem) -> bool {
+        (item.title.is_none() && !self.title.is_empty())
+            || (item.author.is_none() && !self.author.is_empty())
+            || (item.body.is_none() && !self.body.is_empty())
+    }
+
+    pub fn extract(&self, html: &str) -> Extracted {
+        let document = Html::parse_document(html);
+        let first_match = |selectors: &[Selector], separator: &str| {
+            selectors.iter().find_map(|selector| {
+                let text = document
+                    .select(selector)
+                    .map(|element| self.text_of(element))
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(separator);
+                (!text.is_empty()).then_some(text)
+            })
+        };
+
+        Extracted {
+            title: first_match(&self.title, " ").map(|text| one_line(&text)),
+            author: first_match(&self.author, ", ").map(|text| one_line(&text)),
+            body: first_match(&self.body, "\n\n"),
+        }
+    }
+
+    /// Text of `element` without stripped elements, with blocks separated
+    /// by blank lines and other whitespace collapsed
+    fn text_of(&self, element: ElementRef) -> String {
+        let mut raw = String::new();
+        self.push_text(element, &mut raw);
+        raw.split("\n\n")
+            .map(one_line)
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn push_text(&self, element: ElementRef, out: &mut String) {
+        for child in element.children() {
+            if let Some(text) = child.value().as_text() {
+                // Blank lines in the markup are not paragraph breaks
+                out.push_str(&text.replace('\n', " "));
+            } else if let Some(child) = ElementRef::wrap(child) {
+                let name = child.value().name();
+                if HIDDEN_ELEMENTS.contains(&name)
+                    || self.strip.iter().any(|selector| selector.matches(&child))
+                {
+                    continue;
+                }
+                let block = BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    out.push_str("\n\n");
+                }
+                self.push_text(child, out);
+                if block {
+                    out.push_str("\n\n");
+                }
+            }
+        }
+    }
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn compile_selector(selector: &str) -> Result<Selector, String> {
+    let css = if selector.trim_start().starts_with('/') {
+        xpath_to_css(selector.trim()).map_err(|err| err.to_string())?
+    } else {
+        selector.to_string()
+    };
+    Selector::parse(&css).map_err(|err| err.to_string())
+}
+
+/// Translates an XPath location path to an equivalent CSS selector
+pub fn xpath_to_css(xpath: &str) -> Result<String, UnsupportedXpath> {
+    let unsupported = || UnsupportedXpath(xpath.to_string());
+
+    // Selecting the text of the matched elements is what extraction does
+    // anyway
+    let path = xpath.strip_suffix("/text()").unwrap_or(xpath);
+
+    let mut css = String::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let combinator = if let Some(after) = rest.strip_prefix("//") {
+            rest = after;
+            " "
+        } else if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            " > "
+        } else {
+            return Err(unsupported());
+        };
+        if !css.is_empty() {
+            css.push_str(combinator);
+        }
+
+        let end = step_end(rest).ok_or_else(unsupported)?;
+        css.push_str(&step_to_css(&rest[..end]).ok_or_else(unsupported)?);
+        rest = &rest[end..];
+    }
+
+    if css.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(css)
+}
+
+/// Length of the step at the start of `path`: up to the next `/` outside
+/// brackets and quotes
+fn step_end(path: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (index, ch) in path.char_indices() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, '/') if depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+    (depth == 0 && quote.is_none()).then_some(path.len())
+}
+
+fn step_to_css(step: &str) -> Option<String> {
+    let (name, mut predicates) = match step.find('[') {
+        Some(start) => (&step[..start], &step[start..]),
+        None => (step, ""),
+    };
+    if name != "*" && !is_name(name) {
+        return None;
+    }
+
+    let mut css = name.to_string();
+    while let Some(after) = predicates.strip_prefix('[') {
+        let end = step_end_bracket(after)?;
+        for condition in split_and(&after[..end]) {
+            css.push_str(&predicate_to_css(condition.trim())?);
+        }
+        predicates = &after[end + 1..];
+    }
+    predicates.is_empty().then_some(css)
+}
+
+/// Index of the `]` closing a predicate, skipping quoted text
+fn step_end_bracket(predicate: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, ch) in predicate.char_indices() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, ']') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a predicate at ` and ` outside quotes
+fn split_and(predicate: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (index, ch) in predicate.char_indices() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, ' ') if predicate[index..].starts_with(" and ") => {
+                parts.push(&predicate[start..index]);
+                start = index + " and ".len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&predicate[start..]);
+    parts
+}
+
+fn predicate_to_css(condition: &str) -> Option<String> {
+    if let Ok(position) = condition.parse::<u32>() {
+        return Some(format!(":nth-of-type({position})"));
+    }
+    if let Some(arguments) = condition
+        .strip_prefix("contains(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let (attribute, value) = arguments.split_once(',')?;
+        let attribute = attribute.trim().strip_prefix('@').filter(|a| is_name(a))?;
+        return Some(format!("[{attribute}*={}]", css_string(value.trim())?));
+    }
+
+    let attribute = condition.strip_prefix('@')?;
+    match attribute.split_once('=') {
+        Some((name, value)) => {
+            let name = name.trim();
+            is_name(name).then_some(())?;
+            Some(format!("[{name}={}]", css_string(value.trim())?))
+        }
+        None => is_name(attribute).then(|| format!("[{attribute}]")),
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ':')
+}
+
+/// An XPath string literal as a CSS string
+fn css_string(literal: &str) -> Option<String> {
+    let quote = literal.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+    let value = literal.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+/// The rules found in the rules directory, reloaded when it changes
+pub struct SiteRules {
+    dir: PathBuf,
+    reload_check_interval: Duration,
+    loaded: RwLock<Loaded>,
+}
+
+struct Loaded {
+    /// Sorted most specific host first, so the first match is the best one
+    rules: Arc<Vec<(String, Arc<SiteRule>)>>,
+    fingerprint: Vec<(PathBuf, Option<SystemTime>, u64)>,
+    checked_at: Instant,
+}
+
+impl SiteRules {
+    /// Loads the directory named by [`SITE_RULES_ENV`], if it is set
+    pub fn from_env() -> Result<Option<Self>, SiteRulesError> {
+        match std::env::var(SITE_RULES_ENV) {
+            Ok(dir) if !dir.trim().is_empty() => Self::load(Path::new(dir.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn load(dir: &Path) -> Result<Self, SiteRulesError> {
+        let fingerprint = fingerprint(dir)?;
+        let rules = load_rules(&fingerprint)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            reload_check_interval: RELOAD_CHECK_INTERVAL,
+            loaded: RwLock::new(Loaded {
+                rules: Arc::new(rules),
+                fingerprint,
+                checked_at: Instant::now(),
+            }),
+        })
+    }
+
+    pub fn with_reload_check_interval(mut self, interval: Duration) -> Self {
+        self.reload_check_interval = interval;
+        self
+    }
+
+    /// The rule for `host`, if any entry matches it
+    pub fn for_host(&self, host: &str) -> Option<Arc<SiteRule>> {
+        self.reload_if_changed();
+        let host = host.to_lowercase();
+        let rules = self
+            .loaded
+            .read()
+            .expect("site rules lock poisoned")
+            .rules
+            .clone();
+        rules
+            .iter()
+            .find(|(entry, _)| host_matches(&host, entry))
+            .map(|(_, rule)| rule.clone())
+    }
+
+    fn reload_if_changed(&self) {
+        {
+            let loaded = self.loaded.read().expect("site rules lock poisoned");
+            if loaded.checked_at.elapsed() < self.reload_check_interval {
+                return;
+            }
+        }
+
+        let mut loaded = self.loaded.write().expect("site rules lock poisoned");
+        loaded.checked_at = Instant::now();
+        let current = match fingerprint(&self.dir) {
+            Ok(current) if current != loaded.fingerprint => current,
+            Ok(_) => return,
+            Err(err) => {
+                warn!(error = %err, "Failed to check site rules for changes");
+                return;
+            }
+        };
+        match load_rules(&current) {
+            Ok(rules) => {
+                info!(sites = rules.len(), "Reloaded site rules");
+                loaded.rules = Arc::new(rules);
+                loaded.fingerprint = current;
+            }
+            Err(err) => {
+                warn!(error = %err, "Keeping previous site rules");
+                // Not retried until the files change again
+                loaded.fingerprint = current;
+            }
+        }
+    }
+}
+
+/// The `.toml` files in `dir` with their modification times and sizes,
+/// sorted by path
+fn fingerprint(dir: &Path) -> Result<Vec<(PathBuf, Option<SystemTime>, u64)>, SiteRulesError> {
+    let read_error = |source| SiteRulesError::Read {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.extension().is_none_or(|extension| extension != "toml") {
+            continue;
+        }
+        let metadata = std::fs::metadata(&path).map_err(|source| SiteRulesError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        files.push((path, metadata.modified().ok(), metadata.len()));
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn load_rules(
+    files: &[(PathBuf, Option<SystemTime>, u64)],
+) -> Result<Vec<(String, Arc<SiteRule>)>, SiteRulesError> {
+    let mut rules = Vec::new();
+    for (path, _, _) in files {
+        let contents = std::fs::read_to_string(path).map_err(|source| SiteRulesError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        let default_host = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let rule = Arc::new(SiteRule::parse(&contents, &default_host, path)?);
+        rules.extend(rule.hosts.iter().map(|host| (host.clone(), rule.clone())));
+    }
+    rules.sort_by_key(|(host, _)| std::cmp::Reverse(host.len()));
+    Ok(rules)
+}
+
+/// Fills in the title, author and body of pages from sites with rules
+pub struct SiteRulesEnricher;
+
+fn host_of(item: &ContentItem) -> Option<String> {
+    url::Url::parse(&item.url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+}
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for SiteRulesEnricher {
+    fn name(&self) -> &'static str {
+        "site"
+    }
+
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state
+            .site_rules()
+            .zip(host_of(item))
+            .and_then(|(rules, host)| rules.for_host(&host))
+            .is_some_and(|rule| rule.fills_gap(item))
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let item = input.item();
+        let Some(rule) = state
+            .site_rules()
+            .zip(host_of(item))
+            .and_then(|(rules, host)| rules.for_host(&host))
+        else {
+            return Ok(None);
+        };
+
+        let document = input.document(state.fetcher()).await?;
+        if !document.content_type.contains("html") {
+            debug!(content_type = document.content_type, "Not an HTML page");
+            return Ok(None);
+        }
+
+        let extracted = rule.extract(&String::from_utf8_lossy(&document.bytes));
+        if extracted == Extracted::default() {
+            debug!("No site rule selector matched");
+            return Ok(None);
+        }
+
+        info!("Extracted page with site rules");
+        Ok(Some(MetadataPatch {
+            title: extracted.title,
+            author: extracted.author,
+            body: extracted.body,
+            ..MetadataPatch::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xpath_to_css() {
+        let css = |xpath| xpath_to_css(xpath).unwrap();
+
+        assert_eq!(css("//h1"), "h1");
+        assert_eq!(
+            css("//div[@class='byline']/a"),
+            r#"div[class="byline"] > a"#
+        );
+        assert_eq!(
+            css("//div[contains(@class, 'post-body')]//p/text()"),
+            r#"div[class*="post-body"] p"#
+        );
+        assert_eq!(
+            css("/html/body/div[2]/span[@itemprop=\"author\" and @lang]"),
+            r#"html > body > div:nth-of-type(2) > span[itemprop="author"][lang]"#
+        );
+        assert_eq!(css("//*[@id='a/b']"), r#"*[id="a/b"]"#);
+
+        for unsupported in [
+            "h1",
+            "//a/@href",
+            "//div[starts-with(@class, 'x')]",
+            "//p[",
+            "//a | //b",
+            "//",
+        ] {
+            assert!(xpath_to_css(unsupported).is_err(), "{unsupported}");
+        }
+    }
+
+    #[test]
+    fn test_extract_with_rule() {
+        let rule = SiteRule::parse(
+            r#"
+            title = ["h1.missing", "//header/h1"]
+            author = ".byline a"
+            body = "article"
+            strip = [".ad"]
+            "#,
+            "example.com",
+            Path::new("example.com.toml"),
+        )
+        .unwrap();
+        let html = r#"<html><body>
+            <header><h1>  Stubborn
+              Site</h1></header>
+            <p class="byline">By <a>Ada</a> and <a>Grace</a></p>
+            <article>
+              <p>First paragraph
+                 continues here.</p>
+              <div class="ad"><p>Buy now</p></div>
+              <script>track()</script>
+              <ul><li>One</li><li>Two</li></ul>
+            </article>
+        </body></html>"#;
+
+        assert_eq!(
+            rule.extract(html),
+            Extracted {
+                title: Some("Stubborn Site".to_string()),
+                author: Some("Ada, Grace".to_string()),
+                body: Some("First paragraph continues here.\n\nOne\n\nTwo".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_selectors_are_reported() {
+        let error = SiteRule::parse("title = \"//a/@href\"", "x", Path::new("x.toml"))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Invalid selector \"//a/@href\" in x.toml: unsupported XPath: //a/@href"
+        );
+        assert!(SiteRule::parse("title = \"div[\"", "x", Path::new("x.toml")).is_err());
+        assert!(SiteRule::parse("titel = \"h1\"", "x", Path::new("x.toml")).is_err());
+    }
+}
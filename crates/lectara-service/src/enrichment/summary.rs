@@ -0,0 +1,347 @@
+//! Short summaries of saved articles.
+//!
+//! Two backends are available: an extractive one that picks the sentences
+//! sharing the most vocabulary with the rest of the article, which needs no
+//! network access, and one that asks an OpenAI-compatible chat completions
+//! endpoint (a hosted LLM or a local server such as Ollama or llama.cpp).
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use tracing::{debug, info};
+
+use super::EnrichmentError;
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::keywords;
+use crate::models::{ContentItem, MetadataPatch};
+
+/// Environment variable selecting the backend: `extractive` or `llm`.
+/// Summaries are off when it is unset.
+pub const SUMMARIZER_ENV: &str = "LECTARA_SUMMARIZER";
+
+/// Base URL of the chat completions API, e.g. `http://localhost:11434/v1`
+pub const SUMMARIZER_URL_ENV: &str = "LECTARA_SUMMARIZER_URL";
+pub const SUMMARIZER_MODEL_ENV: &str = "LECTARA_SUMMARIZER_MODEL";
+/// Optional bearer token for the chat completions API
+pub const SUMMARIZER_API_KEY_ENV: &str = "LECTARA_SUMMARIZER_API_KEY";
+
+/// Sentences kept by the extractive summarizer for longer articles
+const SUMMARY_SENTENCES: usize = 3;
+/// Articles with fewer candidate sentences than this get two
+const SHORT_ARTICLE_SENTENCES: usize = 6;
+/// Sentences shorter than this are usually headings or captions
+const MIN_SENTENCE_WORDS: usize = 5;
+const MAX_SENTENCE_WORDS: usize = 60;
+
+/// Characters of the body sent to the LLM; longer articles are cut off
+const MAX_PROMPT_CHARS: usize = 12_000;
+const LLM_TIMEOUT: Duration = Duration::from_secs(60);
+const LLM_INSTRUCTIONS: &str = "Summarize the article in two or three sentences. \
+    Reply with the summary only, in the language of the article.";
+
+#[derive(Error, Debug)]
+pub enum SummarizerConfigError {
+    #[error("Unknown summarizer {0:?}; expected 'extractive' or 'llm'")]
+    UnknownBackend(String),
+
+    #[error("{0} must be set for the llm summarizer")]
+    MissingSetting(&'static str),
+}
+
+#[derive(Clone)]
+pub enum Summarizer {
+    Extractive,
+    Llm(LlmSummarizer),
+}
+
+impl Summarizer {
+    /// The summarizer configured in [`SUMMARIZER_ENV`], if any
+    pub fn from_env() -> Result<Option<Self>, SummarizerConfigError> {
+        let backend = std::env::var(SUMMARIZER_ENV).unwrap_or_default();
+        match backend.trim().to_lowercase().as_str() {
+            "" | "off" | "none" => Ok(None),
+            "extractive" => Ok(Some(Self::Extractive)),
+            "llm" => {
+                let setting = |name| {
+                    std::env::var(name)
+                        .ok()
+                        .filter(|value| !value.trim().is_empty())
+                };
+                let url = setting(SUMMARIZER_URL_ENV)
+                    .ok_or(SummarizerConfigError::MissingSetting(SUMMARIZER_URL_ENV))?;
+                let model = setting(SUMMARIZER_MODEL_ENV)
+                    .ok_or(SummarizerConfigError::MissingSetting(SUMMARIZER_MODEL_ENV))?;
+                let api_key = setting(SUMMARIZER_API_KEY_ENV);
+                Ok(Some(Self::Llm(LlmSummarizer::new(
+                    &url,
+                    &model,
+                    api_key.as_deref(),
+                ))))
+            }
+            _ => Err(SummarizerConfigError::UnknownBackend(backend)),
+        }
+    }
+
+    /// A two or three sentence summary of `body`, or `None` if it has no
+    /// usable sentences
+    pub async fn summarize(
+        &self,
+        title: Option<&str>,
+        body: &str,
+    ) -> Result<Option<String>, EnrichmentError> {
+        match self {
+            Self::Extractive => Ok(extractive_summary(body)),
+            Self::Llm(llm) => llm.summarize(title, body).await,
+        }
+    }
+}
+
+/// Picks the sentences whose words occur most often across `body`, in
+/// their original order
+pub fn extractive_summary(body: &str) -> Option<String> {
+    let sentences: Vec<&str> = split_sentences(body)
+        .filter(|sentence| {
+            let words = sentence.split_whitespace().count();
+            (MIN_SENTENCE_WORDS..=MAX_SENTENCE_WORDS).contains(&words)
+        })
+        .collect();
+    if sentences.is_empty() {
+        return None;
+    }
+
+    let frequencies = keywords::term_counts(body);
+    let score = |sentence: &str| {
+        let terms = keywords::term_counts(sentence);
+        let total: u32 = terms.values().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let weight: u32 = terms
+            .iter()
+            .map(|(term, count)| count * frequencies.get(term).copied().unwrap_or(0))
+            .sum();
+        f64::from(weight) / f64::from(total)
+    };
+
+    let wanted = if sentences.len() < SHORT_ARTICLE_SENTENCES {
+        SUMMARY_SENTENCES - 1
+    } else {
+        SUMMARY_SENTENCES
+    };
+    let mut ranked: Vec<(usize, f64)> = sentences
+        .iter()
+        .enumerate()
+        .map(|(index, sentence)| (index, score(sentence)))
+        .collect();
+    // Earlier sentences win ties; openings tend to state the topic
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut chosen: Vec<usize> = ranked
+        .into_iter()
+        .take(wanted)
+        .map(|(index, _)| index)
+        .collect();
+    chosen.sort_unstable();
+
+    Some(
+        chosen
+            .into_iter()
+            .map(|index| sentences[index])
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Sentences of `text`, split after `.`, `!` or `?` followed by whitespace
+/// and at line breaks, with inner whitespace left as is
+fn split_sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().flat_map(|line| {
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut chars = line.char_indices().peekable();
+        while let Some((index, ch)) = chars.next() {
+            let at_break = matches!(ch, '.' | '!' | '?')
+                && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+            if at_break {
+                sentences.push(&line[start..index + ch.len_utf8()]);
+                start = index + ch.len_utf8();
+            }
+        }
+        sentences.push(&line[start..]);
+        sentences
+            .into_iter()
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+    })
+}
+
+/// Client for an OpenAI-compatible `/chat/completions` endpoint
+#[derive(Clone)]
+pub struct LlmSummarizer {
+    client: reqwest::Client,
+    api_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+impl LlmSummarizer {
+    pub fn new(api_url: &str, model: &str, api_key: Option<&str>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(LLM_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: api_key.map(str::to_string),
+        }
+    }
+
+    async fn summarize(
+        &self,
+        title: Option<&str>,
+        body: &str,
+    ) -> Result<Option<String>, EnrichmentError> {
+        let body: String = body.chars().take(MAX_PROMPT_CHARS).collect();
+        let article = match title {
+            Some(title) => format!("{title}\n\n{body}"),
+            None => body,
+        };
+
+        let request = self
+            .client
+            .post(format!("{}/chat/completions", self.api_url))
+            .json(&json!({
+                "model": self.model,
+                "messages": [
+                    { "role": "system", "content": LLM_INSTRUCTIONS },
+                    { "role": "user", "content": article },
+                ],
+                "temperature": 0.2,
+            }));
+        let request = match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        };
+
+        let completion: ChatCompletion = request.send().await?.error_for_status()?.json().await?;
+        let summary = completion
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| EnrichmentError::InvalidResponse("no completion choices".to_string()))?
+            .message
+            .content
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty());
+        Ok(summary)
+    }
+}
+
+/// Writes a short summary of items with a body
+pub struct SummaryEnricher;
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for SummaryEnricher {
+    fn name(&self) -> &'static str {
+        "summary"
+    }
+
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state.summarizer().is_some() && item.body.is_some() && item.summary.is_none()
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let item = input.item();
+        let (Some(summarizer), Some(body)) = (state.summarizer(), item.plain_text()) else {
+            return Ok(None);
+        };
+
+        let Some(summary) = summarizer.summarize(item.title.as_deref(), body).await? else {
+            debug!("No summary could be made");
+            return Ok(None);
+        };
+
+        info!("Summarized item");
+        Ok(Some(MetadataPatch {
+            summary: Some(summary),
+            ..MetadataPatch::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sentences() {
+        let sentences: Vec<_> =
+            split_sentences("One two. Version 1.5 is out!\nHeading\n\nWhy? Because e.g.x")
+                .collect();
+
+        assert_eq!(
+            sentences,
+            [
+                "One two.",
+                "Version 1.5 is out!",
+                "Heading",
+                "Why?",
+                "Because e.g.x"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extractive_summary_keeps_central_sentences_in_order() {
+        let body = "\
+            Sourdough bread needs a healthy starter before anything else.\n\
+            My neighbour's cat watched the whole time from the windowsill.\n\
+            Feed the starter flour and water until the starter doubles reliably.\n\
+            The weather was unusually warm for October that week.\n\
+            Bread made with a strong starter rises well and tastes of sourdough.\n\
+            Anyway, that is enough about my kitchen for one day.";
+
+        assert_eq!(
+            extractive_summary(body).unwrap(),
+            "Sourdough bread needs a healthy starter before anything else. \
+             Feed the starter flour and water until the starter doubles reliably. \
+             Bread made with a strong starter rises well and tastes of sourdough."
+        );
+    }
+
+    #[test]
+    fn test_extractive_summary_of_short_texts() {
+        assert_eq!(
+            extractive_summary("Just a few words here. And a second sentence follows it."),
+            Some("Just a few words here. And a second sentence follows it.".to_string())
+        );
+        assert_eq!(extractive_summary("Too short. Tiny."), None);
+        assert_eq!(extractive_summary(""), None);
+    }
+}
@@ -0,0 +1,470 @@
+//! Unrolled text of Mastodon and X threads, stored as the body of saved
+//! status URLs before the posts disappear.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use super::EnrichmentError;
+use super::pipeline::{Enricher, EnrichmentInput};
+use crate::AppState;
+use crate::models::{ContentItem, MetadataPatch};
+
+/// Environment variable that disables thread unrolling when `false` or `0`
+pub const THREAD_LOOKUP_ENV: &str = "LECTARA_THREAD_LOOKUP";
+
+/// Environment variable with the base URL of a syndication-compatible
+/// endpoint (`/tweet-result?id=`) for X posts; X threads are skipped without it
+pub const X_SYNDICATION_URL_ENV: &str = "LECTARA_X_SYNDICATION_URL";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound on posts fetched one by one when walking an X thread
+const MAX_X_POSTS: usize = 25;
+
+const X_HOSTS: &[&str] = &[
+    "twitter.com",
+    "www.twitter.com",
+    "mobile.twitter.com",
+    "x.com",
+    "www.x.com",
+];
+
+/// A status recognized in a saved (normalized) URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadId {
+    /// `origin` is the instance's scheme, host and port
+    Mastodon {
+        origin: String,
+        status_id: String,
+    },
+    X {
+        status_id: String,
+    },
+}
+
+impl ThreadId {
+    /// Recognizes `/@user/<id>` and `/users/<user>/statuses/<id>` on any host
+    /// as Mastodon, and `/<user>/status/<id>` on twitter.com or x.com
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url::Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let segments: Vec<&str> = url.path_segments()?.collect();
+        let is_status_id = |id: &str| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit());
+
+        if X_HOSTS.contains(&host) {
+            return match segments.as_slice() {
+                [_, "status", id] if is_status_id(id) => Some(ThreadId::X {
+                    status_id: id.to_string(),
+                }),
+                _ => None,
+            };
+        }
+
+        let status_id = match segments.as_slice() {
+            [user, id] if user.starts_with('@') && is_status_id(id) => id,
+            ["users", _, "statuses", id] if is_status_id(id) => id,
+            _ => return None,
+        };
+        Some(ThreadId::Mastodon {
+            origin: url.origin().ascii_serialization(),
+            status_id: status_id.to_string(),
+        })
+    }
+
+    pub fn platform(&self) -> &'static str {
+        match self {
+            ThreadId::Mastodon { .. } => "mastodon",
+            ThreadId::X { .. } => "x",
+        }
+    }
+
+    pub fn status_id(&self) -> &str {
+        match self {
+            ThreadId::Mastodon { status_id, .. } | ThreadId::X { status_id } => status_id,
+        }
+    }
+}
+
+/// The author's own posts in a thread, in reading order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thread {
+    pub author: String,
+    pub posts: Vec<String>,
+}
+
+impl Thread {
+    pub fn text(&self) -> String {
+        self.posts.join("\n\n")
+    }
+
+    fn into_patch(self) -> MetadataPatch {
+        MetadataPatch {
+            body: Some(self.text()),
+            author: Some(self.author),
+            ..MetadataPatch::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ThreadResolver {
+    client: reqwest::Client,
+    x_syndication_url: Option<String>,
+}
+
+impl Default for ThreadResolver {
+    fn default() -> Self {
+        Self::with_x_syndication_url(None)
+    }
+}
+
+impl ThreadResolver {
+    pub fn with_x_syndication_url(x_syndication_url: Option<&str>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Self {
+            client,
+            x_syndication_url: x_syndication_url.map(|url| url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    /// The default resolver, unless disabled via [`THREAD_LOOKUP_ENV`], with
+    /// X support when [`X_SYNDICATION_URL_ENV`] is set
+    pub fn from_env() -> Option<Self> {
+        let disabled = std::env::var(THREAD_LOOKUP_ENV).is_ok_and(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            )
+        });
+        let x_syndication_url = std::env::var(X_SYNDICATION_URL_ENV)
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+        (!disabled).then(|| Self::with_x_syndication_url(x_syndication_url.as_deref()))
+    }
+
+    /// Fetches the thread around `id`, returning `None` if it is gone or X
+    /// lookups aren't configured
+    pub async fn resolve(&self, id: &ThreadId) -> Result<Option<Thread>, EnrichmentError> {
+        match id {
+            ThreadId::Mastodon { origin, status_id } => {
+                self.resolve_mastodon(origin, status_id).await
+            }
+            ThreadId::X { status_id } => match &self.x_syndication_url {
+                Some(base_url) => self.resolve_x(base_url, status_id).await,
+                None => Ok(None),
+            },
+        }
+    }
+
+    async fn resolve_mastodon(
+        &self,
+        origin: &str,
+        status_id: &str,
+    ) -> Result<Option<Thread>, EnrichmentError> {
+        let status_url = format!("{origin}/api/v1/statuses/{status_id}");
+        let response = self.client.get(&status_url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let status: MastodonStatus = response.error_for_status()?.json().await?;
+
+        let context: MastodonContext = self
+            .client
+            .get(format!("{status_url}/context"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Some(unroll_mastodon(status, context)))
+    }
+
+    /// Walks reply parents while they are by the same author; the syndication
+    /// API has no way to list replies, so the thread ends at the saved post
+    async fn resolve_x(
+        &self,
+        base_url: &str,
+        status_id: &str,
+    ) -> Result<Option<Thread>, EnrichmentError> {
+        let mut posts = Vec::new();
+        let mut author: Option<(String, String)> = None;
+        let mut next_id = Some(status_id.to_string());
+
+        while let Some(id) = next_id.take()
+            && posts.len() < MAX_X_POSTS
+        {
+            let response = self
+                .client
+                .get(format!("{base_url}/tweet-result"))
+                .query(&[("id", id.as_str()), ("token", "0")])
+                .send()
+                .await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                break;
+            }
+            let tweet: XTweet = response.error_for_status()?.json().await?;
+
+            match &author {
+                Some((screen_name, _)) if *screen_name != tweet.user.screen_name => break,
+                Some(_) => {}
+                None => author = Some((tweet.user.screen_name.clone(), tweet.user.name.clone())),
+            }
+            posts.push(unescape_entities(&tweet.text));
+            next_id = tweet.in_reply_to_status_id_str;
+        }
+
+        let Some((screen_name, name)) = author else {
+            return Ok(None);
+        };
+        posts.reverse();
+        Ok(Some(Thread {
+            author: if name.trim().is_empty() {
+                format!("@{screen_name}")
+            } else {
+                name
+            },
+            posts,
+        }))
+    }
+}
+
+/// Unrolls Mastodon and X threads into the body of saved status URLs
+pub struct ThreadEnricher;
+
+#[async_trait]
+impl<S: AppState> Enricher<S> for ThreadEnricher {
+    fn name(&self) -> &'static str {
+        "thread"
+    }
+
+    fn applies(&self, state: &S, item: &ContentItem) -> bool {
+        state.thread_resolver().is_some() && ThreadId::from_url(&item.url).is_some()
+    }
+
+    async fn enrich(
+        &self,
+        state: &S,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let (Some(resolver), Some(id)) = (
+            state.thread_resolver(),
+            ThreadId::from_url(&input.item().url),
+        ) else {
+            return Ok(None);
+        };
+
+        debug!(
+            platform = id.platform(),
+            status_id = id.status_id(),
+            "Unrolling thread"
+        );
+
+        let Some(thread) = resolver.resolve(&id).await? else {
+            debug!("Thread not available");
+            return Ok(None);
+        };
+
+        info!(post_count = thread.posts.len(), "Unrolled thread");
+        Ok(Some(thread.into_patch()))
+    }
+}
+
+#[derive(Deserialize)]
+struct MastodonStatus {
+    id: String,
+    in_reply_to_id: Option<String>,
+    content: String,
+    account: MastodonAccount,
+}
+
+#[derive(Deserialize)]
+struct MastodonAccount {
+    id: String,
+    acct: String,
+    #[serde(default)]
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct MastodonContext {
+    #[serde(default)]
+    ancestors: Vec<MastodonStatus>,
+    #[serde(default)]
+    descendants: Vec<MastodonStatus>,
+}
+
+#[derive(Deserialize)]
+struct XTweet {
+    text: String,
+    user: XUser,
+    in_reply_to_status_id_str: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct XUser {
+    name: String,
+    screen_name: String,
+}
+
+/// Keeps the chain of self-replies through `status`: the author's ancestors
+/// directly above it and their replies to themselves below it
+fn unroll_mastodon(status: MastodonStatus, context: MastodonContext) -> Thread {
+    let author_id = status.account.id.clone();
+
+    let mut posts: Vec<String> = context
+        .ancestors
+        .iter()
+        .rev()
+        .take_while(|ancestor| ancestor.account.id == author_id)
+        .map(|ancestor| html_to_text(&ancestor.content))
+        .collect();
+    posts.reverse();
+    posts.push(html_to_text(&status.content));
+
+    let mut last_id = status.id;
+    for descendant in context.descendants {
+        if descendant.account.id == author_id
+            && descendant.in_reply_to_id.as_deref() == Some(&last_id)
+        {
+            posts.push(html_to_text(&descendant.content));
+            last_id = descendant.id;
+        }
+    }
+
+    let account = status.account;
+    Thread {
+        author: if account.display_name.trim().is_empty() {
+            format!("@{}", account.acct)
+        } else {
+            account.display_name
+        },
+        posts,
+    }
+}
+
+/// Plain text of a Mastodon post: paragraphs become blank lines, line
+/// breaks newlines, and all other markup is dropped
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag == "/p" {
+            text.push_str("\n\n");
+        } else if tag.starts_with("br") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    unescape_entities(text.trim())
+}
+
+/// Resolves the character references posts are served with, leaving anything
+/// unrecognized as written
+fn unescape_entities(text: &str) -> String {
+    quick_xml::escape::unescape(text)
+        .map(|unescaped| unescaped.into_owned())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(
+        id: &str,
+        account: &str,
+        in_reply_to_id: Option<&str>,
+        content: &str,
+    ) -> MastodonStatus {
+        MastodonStatus {
+            id: id.to_string(),
+            in_reply_to_id: in_reply_to_id.map(str::to_string),
+            content: content.to_string(),
+            account: MastodonAccount {
+                id: account.to_string(),
+                acct: format!("user{account}@example.social"),
+                display_name: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_thread_id_from_url() {
+        assert_eq!(
+            ThreadId::from_url("https://mastodon.social/@Gargron/109305323547380210"),
+            Some(ThreadId::Mastodon {
+                origin: "https://mastodon.social".to_string(),
+                status_id: "109305323547380210".to_string(),
+            })
+        );
+        assert_eq!(
+            ThreadId::from_url("https://example.social/users/ada/statuses/42"),
+            Some(ThreadId::Mastodon {
+                origin: "https://example.social".to_string(),
+                status_id: "42".to_string(),
+            })
+        );
+        assert_eq!(
+            ThreadId::from_url("https://x.com/jack/status/20"),
+            Some(ThreadId::X {
+                status_id: "20".to_string(),
+            })
+        );
+        assert_eq!(ThreadId::from_url("https://twitter.com/jack"), None);
+        assert_eq!(
+            ThreadId::from_url("https://medium.com/@ada/a-post-1f2e"),
+            None
+        );
+        assert_eq!(ThreadId::from_url("https://example.com/articles/42"), None);
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text(
+                r#"<p>First &amp; foremost<br />second line</p><p>See <a href="https://example.com"><span>example.com</span></a></p>"#
+            ),
+            "First & foremost\nsecond line\n\nSee example.com"
+        );
+        assert_eq!(html_to_text("<p>a &nbsp; b</p>"), "a &nbsp; b");
+    }
+
+    #[test]
+    fn test_unroll_mastodon_keeps_self_replies() {
+        let context = MastodonContext {
+            ancestors: vec![
+                status("1", "other", None, "<p>Someone else</p>"),
+                status("2", "a", Some("1"), "<p>1/3</p>"),
+            ],
+            descendants: vec![
+                status("4", "other", Some("3"), "<p>Nice thread</p>"),
+                status("5", "a", Some("3"), "<p>3/3</p>"),
+                status("6", "a", Some("4"), "<p>Replying to a reply</p>"),
+            ],
+        };
+
+        let thread = unroll_mastodon(status("3", "a", Some("2"), "<p>2/3</p>"), context);
+
+        assert_eq!(thread.author, "@usera@example.social");
+        assert_eq!(thread.posts, vec!["1/3", "2/3", "3/3"]);
+        assert_eq!(thread.text(), "1/3\n\n2/3\n\n3/3");
+    }
+}
@@ -1,22 +1,24 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde_json::json;
 use thiserror::Error;
 use tracing::error;
 
+use crate::validation::ValidationError;
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("URL validation failed: {0}")]
-    ValidationError(#[from] crate::validation::ValidationError),
+    ValidationError(#[from] ValidationError),
 
     #[error("Database error: {0}")]
     DatabaseError(#[from] diesel::result::Error),
 
     #[error("URL already exists with different metadata")]
-    DuplicateUrlDifferentMetadata,
+    DuplicateUrlDifferentMetadata(Box<crate::models::DuplicateUrlConflict>),
 
     #[error("Bad request: {0}")]
     BadRequest(String),
@@ -24,17 +26,64 @@ pub enum ApiError {
     #[error("Resource not found")]
     NotFound,
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Not acceptable: {0}")]
+    NotAcceptable(String),
+
     #[error("Internal server error")]
     InternalError,
+
+    #[error("Upstream request failed: {0}")]
+    BadGateway(String),
+
+    #[error("Service is read-only: {0}")]
+    ReadOnly(String),
+
+    #[error("Database is busy, try again shortly")]
+    Busy,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
+            ApiError::ValidationError(ValidationError::InvalidMetadata(ref violations)) => {
+                let body = Json(json!({
+                    "error": self.to_string(),
+                    "fields": violations,
+                }));
+                return (StatusCode::BAD_REQUEST, body).into_response();
+            }
             ApiError::ValidationError(ref err) => (StatusCode::BAD_REQUEST, err.to_string()),
-            ApiError::DuplicateUrlDifferentMetadata => (StatusCode::CONFLICT, self.to_string()),
+            ApiError::DuplicateUrlDifferentMetadata(ref conflict) => {
+                let body = Json(json!({
+                    "error": self.to_string(),
+                    "existing": conflict.existing,
+                    "differences": conflict.differences,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
             ApiError::BadRequest(ref message) => (StatusCode::BAD_REQUEST, message.clone()),
             ApiError::NotFound => (StatusCode::NOT_FOUND, self.to_string()),
+            ApiError::Unauthorized(ref message) => {
+                let body = Json(json!({ "error": message }));
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    body,
+                )
+                    .into_response();
+            }
+            ApiError::Forbidden(ref message) => (StatusCode::FORBIDDEN, message.clone()),
+            ApiError::Conflict(ref message) => (StatusCode::CONFLICT, message.clone()),
+            ApiError::NotAcceptable(ref message) => (StatusCode::NOT_ACCEPTABLE, message.clone()),
             ApiError::DatabaseError(ref err) => {
                 // Log the detailed error but don't expose it to the client
                 error!(error = %err, "Database error occurred");
@@ -44,6 +93,17 @@ impl IntoResponse for ApiError {
                 )
             }
             ApiError::InternalError => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            ApiError::BadGateway(ref message) => (StatusCode::BAD_GATEWAY, message.clone()),
+            ApiError::ReadOnly(_) => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
+            ApiError::Busy => {
+                let body = Json(json!({ "error": self.to_string() }));
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(header::RETRY_AFTER, "1")],
+                    body,
+                )
+                    .into_response();
+            }
         };
 
         let body = Json(json!({
@@ -0,0 +1,317 @@
+//! BibTeX entries for items with resolved citation metadata.
+//!
+//! Entries are keyed `<surname><year><titleword>` (e.g. `vaswani2017attention`),
+//! with a letter suffix when two items would otherwise share a key.
+
+use chrono::Datelike;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::models::{Citation, ContentItemSummary};
+
+/// Leading title words skipped when building cite keys
+const STOP_WORDS: &[&str] = &["a", "an", "the", "on", "of", "in", "to", "for"];
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Renders one entry per item, in the given order
+pub fn render(entries: &[(ContentItemSummary, Citation)]) -> String {
+    let mut used_keys = HashSet::new();
+    let mut output = String::new();
+
+    for (item, citation) in entries {
+        let key = unique_key(&mut used_keys, base_key(item, citation));
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&render_entry(&key, item, citation));
+    }
+
+    output
+}
+
+fn render_entry(key: &str, item: &ContentItemSummary, citation: &Citation) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+
+    let entry_type = match (citation.source.as_str(), &citation.container_title) {
+        ("crossref", Some(_)) => "article",
+        _ => "misc",
+    };
+
+    if let Some(title) = citation.title.as_ref().or(item.title.as_ref()) {
+        // Double braces keep BibTeX styles from lowercasing acronyms
+        fields.push(("title", format!("{{{}}}", escape(title))));
+    }
+
+    let authors = citation.author_names();
+    if !authors.is_empty() {
+        let authors: Vec<String> = authors.iter().map(|name| escape(name)).collect();
+        fields.push(("author", authors.join(" and ")));
+    } else if let Some(author) = &item.author {
+        fields.push(("author", escape(author)));
+    }
+
+    if let Some(container) = &citation.container_title {
+        fields.push(("journal", escape(container)));
+    }
+
+    if let Some(published_on) = citation.published_on {
+        fields.push(("year", published_on.year().to_string()));
+    }
+
+    match citation.source.as_str() {
+        "crossref" => fields.push(("doi", escape_url(&citation.identifier))),
+        "arxiv" => {
+            fields.push(("eprint", escape_url(&citation.identifier)));
+            fields.push(("archiveprefix", "arXiv".to_string()));
+        }
+        _ => {}
+    }
+
+    fields.push(("url", escape_url(&item.url)));
+
+    if let Some(abstract_text) = &citation.abstract_text {
+        fields.push(("abstract", escape(abstract_text)));
+    }
+
+    let mut entry = format!("@{entry_type}{{{key},\n");
+    for (name, value) in fields {
+        let _ = writeln!(entry, "  {name} = {{{value}}},");
+    }
+    // Month macros are written bare so styles can localise them
+    if let Some(published_on) = citation.published_on {
+        let _ = writeln!(
+            entry,
+            "  month = {},",
+            MONTHS[published_on.month0() as usize]
+        );
+    }
+    entry.push_str("}\n");
+    entry
+}
+
+fn base_key(item: &ContentItemSummary, citation: &Citation) -> String {
+    let surname = citation
+        .author_names()
+        .first()
+        .cloned()
+        .or_else(|| item.author.clone())
+        .and_then(|name| name.split_whitespace().last().map(key_part))
+        .unwrap_or_default();
+
+    let year = citation
+        .published_on
+        .map(|date| date.year().to_string())
+        .unwrap_or_default();
+
+    let title_word = citation
+        .title
+        .as_ref()
+        .or(item.title.as_ref())
+        .and_then(|title| {
+            title
+                .split_whitespace()
+                .map(key_part)
+                .find(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+        })
+        .unwrap_or_default();
+
+    let key = format!("{surname}{year}{title_word}");
+    if key.is_empty() {
+        format!("item{}", item.id)
+    } else {
+        key
+    }
+}
+
+/// Lowercased ASCII alphanumerics of `word`, as allowed in cite keys
+fn key_part(word: &str) -> String {
+    word.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn unique_key(used_keys: &mut HashSet<String>, base: String) -> String {
+    if used_keys.insert(base.clone()) {
+        return base;
+    }
+
+    let mut n = 0;
+    loop {
+        let candidate = format!("{base}{}", suffix(n));
+        if used_keys.insert(candidate.clone()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// `a`..`z`, then `aa`, `ab`, ...
+fn suffix(mut n: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'a' + (n % 26) as u8) as char);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Escapes characters with special meaning in (La)TeX field values
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '&' | '%' | '$' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// URLs and identifiers are written verbatim, apart from braces which would
+/// unbalance the field
+fn escape_url(value: &str) -> String {
+    value.replace('{', "%7B").replace('}', "%7D")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn item(id: i32, url: &str) -> ContentItemSummary {
+        ContentItemSummary {
+            id,
+            url: url.to_string(),
+            title: None,
+            author: None,
+            created_at: NaiveDate::from_ymd_opt(2025, 7, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            body_hash: None,
+            enclosure_url: None,
+            duration_seconds: None,
+            enriched_fields: "[]".to_string(),
+            content_type: None,
+            metadata: "{}".to_string(),
+            summary: None,
+            pinned_position: None,
+            read_at: None,
+            archived_at: None,
+            source: None,
+            user_agent: None,
+            client_version: None,
+        }
+    }
+
+    fn citation(content_id: i32, source: &str, identifier: &str) -> Citation {
+        Citation {
+            content_id,
+            source: source.to_string(),
+            identifier: identifier.to_string(),
+            title: Some("Attention Is All You Need".to_string()),
+            authors: r#"["Ashish Vaswani", "Noam Shazeer"]"#.to_string(),
+            published_on: NaiveDate::from_ymd_opt(2017, 6, 12),
+            container_title: None,
+            abstract_text: None,
+            fetched_at: NaiveDate::from_ymd_opt(2025, 7, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_arxiv_entry() {
+        let output = render(&[(
+            item(1, "https://arxiv.org/abs/1706.03762"),
+            citation(1, "arxiv", "1706.03762"),
+        )]);
+
+        assert_eq!(
+            output,
+            "@misc{vaswani2017attention,\n  \
+             title = {{Attention Is All You Need}},\n  \
+             author = {Ashish Vaswani and Noam Shazeer},\n  \
+             year = {2017},\n  \
+             eprint = {1706.03762},\n  \
+             archiveprefix = {arXiv},\n  \
+             url = {https://arxiv.org/abs/1706.03762},\n  \
+             month = jun,\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_crossref_article() {
+        let mut crossref = citation(2, "crossref", "10.1000/182");
+        crossref.container_title = Some("Journal of Stubs & Co".to_string());
+
+        let output = render(&[(item(2, "https://doi.org/10.1000/182"), crossref)]);
+
+        assert!(output.starts_with("@article{vaswani2017attention,\n"));
+        assert!(output.contains("  journal = {Journal of Stubs \\& Co},\n"));
+        assert!(output.contains("  doi = {10.1000/182},\n"));
+    }
+
+    #[test]
+    fn test_render_deduplicates_keys() {
+        let output = render(&[
+            (
+                item(1, "https://arxiv.org/abs/1"),
+                citation(1, "arxiv", "1"),
+            ),
+            (
+                item(2, "https://arxiv.org/abs/2"),
+                citation(2, "arxiv", "2"),
+            ),
+            (
+                item(3, "https://arxiv.org/abs/3"),
+                citation(3, "arxiv", "3"),
+            ),
+        ]);
+
+        assert!(output.contains("@misc{vaswani2017attention,"));
+        assert!(output.contains("@misc{vaswani2017attentiona,"));
+        assert!(output.contains("@misc{vaswani2017attentionb,"));
+    }
+
+    #[test]
+    fn test_base_key_falls_back_to_item_id() {
+        let mut bare = citation(7, "arxiv", "x");
+        bare.title = None;
+        bare.authors = "[]".to_string();
+        bare.published_on = None;
+
+        assert_eq!(
+            base_key(&item(7, "https://arxiv.org/abs/x"), &bare),
+            "item7"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("50% of R&D_{x}"), "50\\% of R\\&D\\_\\{x\\}");
+        assert_eq!(escape("a\\b~c"), "a\\textbackslash{}b\\textasciitilde{}c");
+    }
+
+    #[test]
+    fn test_suffix() {
+        assert_eq!(suffix(0), "a");
+        assert_eq!(suffix(25), "z");
+        assert_eq!(suffix(26), "aa");
+        assert_eq!(suffix(27), "ab");
+    }
+}
@@ -0,0 +1,60 @@
+//! Bookmarks in the JSON format Karakeep (formerly Hoarder) exports and
+//! imports, so a library can move between the two without loss.
+//!
+//! Lectara has no tags or notes, so exported bookmarks carry none.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ContentItemSummary;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Export {
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    /// Seconds since the Unix epoch
+    pub created_at: i64,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub content: Option<BookmarkContent>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BookmarkContent {
+    Link {
+        url: String,
+    },
+    Text {
+        text: String,
+    },
+    /// Bookmark types lectara has no equivalent for, e.g. uploaded assets
+    #[serde(other)]
+    Other,
+}
+
+/// One link bookmark per item, in the given order
+pub fn render(items: &[ContentItemSummary]) -> Export {
+    let bookmarks = items
+        .iter()
+        .map(|item| Bookmark {
+            created_at: item.created_at.and_utc().timestamp(),
+            title: item.title.clone(),
+            tags: Vec::new(),
+            content: Some(BookmarkContent::Link {
+                url: item.url.clone(),
+            }),
+            note: None,
+            archived: item.archived_at.is_some(),
+        })
+        .collect();
+    Export { bookmarks }
+}
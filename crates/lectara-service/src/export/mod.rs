@@ -0,0 +1,6 @@
+//! Rendering of saved items into formats consumed by other tools.
+
+pub mod bibtex;
+pub mod karakeep;
+pub mod opds;
+pub mod podcast;
@@ -0,0 +1,159 @@
+//! An OPDS 1.2 acquisition feed of saved PDFs and EPUBs, for browsing and
+//! downloading them from e-reader apps such as KOReader or Calibre.
+
+use chrono::{DateTime, Utc};
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+use crate::models::ContentItemSummary;
+
+pub const FEED_TITLE: &str = "Lectara documents";
+
+/// Media type of acquisition feeds
+pub const ACQUISITION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+
+/// Renders one page of `items`, linking to the pages either side of it.
+/// `path` is where the feed is served, e.g. `/web/opds`.
+pub fn render(
+    items: &[ContentItemSummary],
+    path: &str,
+    page: u32,
+    has_next: bool,
+    updated: DateTime<Utc>,
+) -> String {
+    let page_link = |rel: &str, page: u32| {
+        let href = if page == 1 {
+            path.to_string()
+        } else {
+            format!("{path}?page={page}")
+        };
+        format!(
+            "<link rel=\"{rel}\" href=\"{}\" type=\"{ACQUISITION_TYPE}\"/>\n",
+            escape(&href)
+        )
+    };
+
+    let mut feed = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/terms/\" \
+         xmlns:opds=\"http://opds-spec.org/2010/catalog\">\n\
+         <id>urn:lectara:opds</id>\n",
+    );
+    let _ = writeln!(feed, "<title>{FEED_TITLE}</title>");
+    let _ = writeln!(feed, "<updated>{}</updated>", updated.to_rfc3339());
+    feed.push_str(&page_link("start", 1));
+    feed.push_str(&page_link("self", page));
+    if page > 1 {
+        feed.push_str(&page_link("previous", page - 1));
+    }
+    if has_next {
+        feed.push_str(&page_link("next", page + 1));
+    }
+
+    for item in items {
+        feed.push_str("<entry>\n");
+        let title = item.title.as_deref().unwrap_or(&item.url);
+        let _ = writeln!(feed, "<title>{}</title>", escape(title));
+        let _ = writeln!(feed, "<id>urn:lectara:item:{}</id>", item.id);
+        let saved = DateTime::<Utc>::from_naive_utc_and_offset(item.created_at, Utc);
+        let _ = writeln!(feed, "<updated>{}</updated>", saved.to_rfc3339());
+        if let Some(author) = &item.author {
+            let _ = writeln!(feed, "<author><name>{}</name></author>", escape(author));
+        }
+        if let Some(summary) = &item.summary {
+            let _ = writeln!(feed, "<summary>{}</summary>", escape(summary));
+        }
+        let _ = writeln!(
+            feed,
+            "<link rel=\"http://opds-spec.org/acquisition\" href=\"{}\" type=\"{}\"/>",
+            escape(&item.url),
+            document_type(&item.url)
+        );
+        if item.body_hash.is_some() {
+            let _ = writeln!(
+                feed,
+                "<link rel=\"alternate\" href=\"/web/read/{}\" type=\"text/html\"/>",
+                item.id
+            );
+        }
+        feed.push_str("</entry>\n");
+    }
+
+    feed.push_str("</feed>\n");
+    feed
+}
+
+/// MIME type guessed from the file extension; items found to serve a PDF
+/// may have any URL
+fn document_type(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    if path.to_ascii_lowercase().ends_with(".epub") {
+        "application/epub+zip"
+    } else {
+        "application/pdf"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn document(id: i32, url: &str) -> ContentItemSummary {
+        ContentItemSummary {
+            id,
+            url: url.to_string(),
+            title: Some(format!("Paper {id} & notes")),
+            author: Some("Ada Lovelace".to_string()),
+            created_at: NaiveDate::from_ymd_opt(2025, 7, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            body_hash: None,
+            enclosure_url: None,
+            duration_seconds: None,
+            enriched_fields: "[]".to_string(),
+            content_type: Some("pdf".to_string()),
+            metadata: "{}".to_string(),
+            summary: None,
+            pinned_position: None,
+            read_at: None,
+            archived_at: None,
+            source: None,
+            user_agent: None,
+            client_version: None,
+        }
+    }
+
+    #[test]
+    fn test_render_entries() {
+        let mut with_text = document(1, "https://example.com/paper?id=1&v=2");
+        with_text.body_hash = Some("abc".to_string());
+        let feed = render(
+            &[with_text, document(2, "https://example.com/book.EPUB")],
+            "/web/opds",
+            1,
+            false,
+            DateTime::default(),
+        );
+
+        assert!(feed.contains("<title>Paper 1 &amp; notes</title>"));
+        assert!(feed.contains("<updated>2025-07-01T12:00:00+00:00</updated>"));
+        assert!(feed.contains(
+            "<link rel=\"http://opds-spec.org/acquisition\" href=\"https://example.com/paper?id=1&amp;v=2\" type=\"application/pdf\"/>"
+        ));
+        assert!(feed.contains("type=\"application/epub+zip\""));
+        assert_eq!(feed.matches("href=\"/web/read/").count(), 1);
+        assert!(!feed.contains("rel=\"next\""));
+        assert!(!feed.contains("rel=\"previous\""));
+    }
+
+    #[test]
+    fn test_render_page_links() {
+        let feed = render(&[], "/web/opds", 2, true, DateTime::default());
+        assert!(feed.contains("<link rel=\"start\" href=\"/web/opds\""));
+        assert!(feed.contains("<link rel=\"self\" href=\"/web/opds?page=2\""));
+        assert!(feed.contains("<link rel=\"previous\" href=\"/web/opds\""));
+        assert!(feed.contains("<link rel=\"next\" href=\"/web/opds?page=3\""));
+    }
+}
@@ -0,0 +1,157 @@
+//! An RSS 2.0 podcast feed of saved episodes, for subscribing from a
+//! podcast app.
+
+use chrono::{DateTime, Utc};
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+use crate::models::ContentItemSummary;
+
+pub const FEED_TITLE: &str = "Lectara saved episodes";
+
+/// Renders `items` (those with an enclosure) newest first as given
+pub fn render(items: &[ContentItemSummary]) -> String {
+    let mut feed = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n\
+         <channel>\n",
+    );
+    let _ = writeln!(feed, "<title>{FEED_TITLE}</title>");
+    feed.push_str("<link>/web</link>\n");
+    feed.push_str("<description>Episodes saved to Lectara</description>\n");
+
+    for item in items {
+        let Some(enclosure_url) = &item.enclosure_url else {
+            continue;
+        };
+
+        feed.push_str("<item>\n");
+        let title = item.title.as_deref().unwrap_or(&item.url);
+        let _ = writeln!(feed, "<title>{}</title>", escape(title));
+        let _ = writeln!(feed, "<link>{}</link>", escape(&item.url));
+        let _ = writeln!(
+            feed,
+            "<guid isPermaLink=\"false\">lectara-{}</guid>",
+            item.id
+        );
+        if let Some(summary) = &item.summary {
+            let _ = writeln!(feed, "<description>{}</description>", escape(summary));
+        }
+        let published = DateTime::<Utc>::from_naive_utc_and_offset(item.created_at, Utc);
+        let _ = writeln!(feed, "<pubDate>{}</pubDate>", published.to_rfc2822());
+        if let Some(author) = &item.author {
+            let _ = writeln!(feed, "<itunes:author>{}</itunes:author>", escape(author));
+        }
+        let _ = writeln!(
+            feed,
+            "<enclosure url=\"{}\" length=\"0\" type=\"{}\"/>",
+            escape(enclosure_url),
+            audio_type(enclosure_url)
+        );
+        if let Some(duration) = item.duration_seconds {
+            let _ = writeln!(feed, "<itunes:duration>{duration}</itunes:duration>");
+        }
+        feed.push_str("</item>\n");
+    }
+
+    feed.push_str("</channel>\n</rss>\n");
+    feed
+}
+
+/// MIME type guessed from the file extension; podcast apps need one to
+/// decide how to play the enclosure
+fn audio_type(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("m4a" | "mp4" | "aac") => "audio/mp4",
+        Some("ogg" | "oga") => "audio/ogg",
+        Some("opus") => "audio/opus",
+        Some("wav") => "audio/wav",
+        _ => "audio/mpeg",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::rss;
+    use chrono::NaiveDate;
+
+    fn episode(id: i32, enclosure_url: Option<&str>) -> ContentItemSummary {
+        ContentItemSummary {
+            id,
+            url: format!("https://podcast.example.com/episodes/{id}"),
+            title: Some(format!("Episode {id} & friends")),
+            author: Some("Ada Lovelace".to_string()),
+            created_at: NaiveDate::from_ymd_opt(2025, 7, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            body_hash: None,
+            enclosure_url: enclosure_url.map(str::to_string),
+            duration_seconds: Some(3723),
+            enriched_fields: "[]".to_string(),
+            content_type: None,
+            metadata: "{}".to_string(),
+            summary: None,
+            pinned_position: None,
+            read_at: None,
+            archived_at: None,
+            source: None,
+            user_agent: None,
+            client_version: None,
+        }
+    }
+
+    #[test]
+    fn test_render_round_trips_through_feed_parser() {
+        let feed = render(&[
+            episode(1, Some("https://cdn.example.com/ep1.mp3?a=1&b=2")),
+            episode(2, None),
+        ]);
+
+        assert!(feed.contains("<pubDate>Tue, 1 Jul 2025 12:00:00 +0000</pubDate>"));
+        assert!(feed.contains("type=\"audio/mpeg\""));
+
+        let items = rss::parse_items(&feed).unwrap();
+        assert_eq!(
+            items,
+            vec![rss::FeedItem {
+                url: Some("https://podcast.example.com/episodes/1".to_string()),
+                title: Some("Episode 1 & friends".to_string()),
+                author: Some("Ada Lovelace".to_string()),
+                enclosure_url: Some("https://cdn.example.com/ep1.mp3?a=1&b=2".to_string()),
+                duration_seconds: Some(3723),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_summary_is_the_episode_description() {
+        let mut summarized = episode(1, Some("https://cdn.example.com/ep1.mp3"));
+        summarized.summary = Some("Ada & Charles talk engines.".to_string());
+
+        let feed = render(&[
+            summarized,
+            episode(2, Some("https://cdn.example.com/ep2.mp3")),
+        ]);
+        assert!(feed.contains("<description>Ada &amp; Charles talk engines.</description>"));
+        // Only the channel description is left for the unsummarized episode
+        assert_eq!(feed.matches("<description>").count(), 2);
+    }
+
+    #[test]
+    fn test_audio_type() {
+        assert_eq!(audio_type("https://cdn.example.com/a.MP3"), "audio/mpeg");
+        assert_eq!(
+            audio_type("https://cdn.example.com/a.m4a?x=y.ogg"),
+            "audio/mp4"
+        );
+        assert_eq!(audio_type("https://cdn.example.com/a.opus"), "audio/opus");
+        assert_eq!(audio_type("https://cdn.example.com/stream"), "audio/mpeg");
+    }
+}
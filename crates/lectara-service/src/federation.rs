@@ -0,0 +1,312 @@
+//! Pushing saves to another lectara instance.
+//!
+//! With [`SYNC_URL_ENV`] set, the items selected by the saved view named in
+//! [`SYNC_VIEW_ENV`] are posted to the other instance's API once they are
+//! saved and enriched, e.g. to keep a public instance fed from a private
+//! one. Only an item's URL, title and author are sent, and the other
+//! instance fetches the page itself.
+//!
+//! Posting is idempotent: an item the other instance already has with the
+//! same metadata is left alone there, and one it has with different
+//! metadata is reported as conflicting rather than overwritten. Pushing the
+//! whole selection again, e.g. after the other instance was unreachable, is
+//! therefore always safe.
+
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use reqwest::StatusCode;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::ContentItem;
+use crate::notifications;
+use crate::repositories::{ContentFilter, ViewRepository};
+use crate::routes::extract::parse_datetime;
+
+/// Base URL of the instance to push to, e.g. `https://reading.example.com`
+pub const SYNC_URL_ENV: &str = "LECTARA_SYNC_URL";
+/// API key of the other instance, if it requires one
+pub const SYNC_API_KEY_ENV: &str = "LECTARA_SYNC_API_KEY";
+/// Name of the saved view selecting the items to push
+pub const SYNC_VIEW_ENV: &str = "LECTARA_SYNC_VIEW";
+
+/// Source pushed items are recorded with on the other instance
+pub const SYNC_SOURCE: &str = "sync";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Characters of an error response kept in the error
+const MAX_ERROR_CHARS: usize = 200;
+
+#[derive(Error, Debug)]
+pub enum SyncConfigError {
+    #[error("{SYNC_VIEW_ENV} must name the view selecting the items to push")]
+    MissingView,
+
+    #[error("Invalid URL in {SYNC_URL_ENV}: {0}")]
+    InvalidUrl(String),
+}
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("The other instance answered {status}: {message}")]
+    Rejected { status: u16, message: String },
+}
+
+/// How the other instance took a pushed item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// Saved there, now or earlier with the same metadata
+    Saved,
+    /// Already saved there with different metadata, which was kept
+    Conflicting,
+}
+
+impl PushOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Saved => "saved",
+            Self::Conflicting => "conflicting",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PushRequest<'a> {
+    url: &'a str,
+    title: Option<&'a str>,
+    author: Option<&'a str>,
+    source: &'static str,
+}
+
+/// The instance saves are pushed to, and which of them
+#[derive(Clone)]
+pub struct SyncTarget {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    view: String,
+}
+
+impl SyncTarget {
+    /// Pushes the items selected by `view` to the instance at `base_url`
+    pub fn new(base_url: &str, api_key: Option<&str>, view: &str) -> Result<Self, SyncConfigError> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        let parsed = url::Url::parse(base_url)
+            .map_err(|err| SyncConfigError::InvalidUrl(err.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(SyncConfigError::InvalidUrl(format!(
+                "'{base_url}' is not an http(s) URL"
+            )));
+        }
+        if view.trim().is_empty() {
+            return Err(SyncConfigError::MissingView);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("lectara/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client configuration is valid");
+
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+            api_key: api_key.map(str::to_string),
+            view: view.trim().to_string(),
+        })
+    }
+
+    /// The target configured in [`SYNC_URL_ENV`], if any. The view is
+    /// required once it is set.
+    pub fn from_env() -> Result<Option<Self>, SyncConfigError> {
+        let setting = |name| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+        let Some(base_url) = setting(SYNC_URL_ENV) else {
+            return Ok(None);
+        };
+        let view = setting(SYNC_VIEW_ENV).ok_or(SyncConfigError::MissingView)?;
+        let api_key = setting(SYNC_API_KEY_ENV);
+        Self::new(&base_url, api_key.as_deref().map(str::trim), &view).map(Some)
+    }
+
+    /// Name of the view selecting the items to push
+    pub fn view(&self) -> &str {
+        &self.view
+    }
+
+    /// Posts an item to the other instance
+    pub async fn push(
+        &self,
+        url: &str,
+        title: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<PushOutcome, SyncError> {
+        let mut request = self
+            .client
+            .post(format!("{}/api/v1/content", self.base_url))
+            .json(&PushRequest {
+                url,
+                title,
+                author,
+                source: SYNC_SOURCE,
+            });
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(PushOutcome::Saved),
+            StatusCode::CONFLICT => Ok(PushOutcome::Conflicting),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(SyncError::Rejected {
+                    status: status.as_u16(),
+                    message: body.chars().take(MAX_ERROR_CHARS).collect(),
+                })
+            }
+        }
+    }
+}
+
+/// The items the view named `view` selects, or `None` if this database has
+/// no view of that name
+pub async fn selection<S: AppState>(
+    state: &S,
+    view: &str,
+) -> Result<Option<ContentFilter>, ApiError> {
+    let Some(view) = state.view_repo().find_by_name(view).await? else {
+        return Ok(None);
+    };
+    let bound = |value: Option<String>| {
+        value
+            .as_deref()
+            .and_then(parse_datetime)
+            .map(|datetime| datetime.naive_utc())
+    };
+    Ok(Some(ContentFilter {
+        since: bound(view.since),
+        until: bound(view.until),
+        content_type: view.content_type,
+        source: view.source,
+    }))
+}
+
+/// Whether `filter` selects an item with these fields
+pub(crate) fn selects(
+    filter: &ContentFilter,
+    content_type: Option<&str>,
+    source: Option<&str>,
+    created_at: NaiveDateTime,
+) -> bool {
+    filter
+        .content_type
+        .as_deref()
+        .is_none_or(|wanted| content_type == Some(wanted))
+        && filter
+            .source
+            .as_deref()
+            .is_none_or(|wanted| source == Some(wanted))
+        && filter.since.is_none_or(|since| created_at >= since)
+        && filter.until.is_none_or(|until| created_at <= until)
+}
+
+/// Pushes a newly saved item if the view selects it. An item that can't be
+/// pushed leaves a notification rather than failing anything.
+pub async fn push_if_selected<S: AppState>(state: &S, item: &ContentItem) {
+    let Some(target) = state.sync_target() else {
+        return;
+    };
+    let filter = match selection(state, target.view()).await {
+        Ok(Some(filter)) => filter,
+        Ok(None) => {
+            debug!(view = target.view(), "No sync view in this database");
+            return;
+        }
+        Err(err) => {
+            warn!(error = %err, content_id = item.id, "Failed to read the sync view");
+            return;
+        }
+    };
+    if !selects(
+        &filter,
+        item.content_type.as_deref(),
+        item.source.as_deref(),
+        item.created_at,
+    ) {
+        return;
+    }
+
+    match target
+        .push(&item.url, item.title.as_deref(), item.author.as_deref())
+        .await
+    {
+        Ok(PushOutcome::Saved) => info!(content_id = item.id, "Pushed item"),
+        Ok(PushOutcome::Conflicting) => warn!(
+            content_id = item.id,
+            "Pushed item is saved with different metadata on the other instance"
+        ),
+        Err(err) => {
+            warn!(error = %err, content_id = item.id, "Failed to push item");
+            let title = item.title.as_deref().unwrap_or(&item.url);
+            notifications::notify(
+                state,
+                notifications::KIND_SYNC_FAILED,
+                format!("Failed to push {title} to {}: {err}", target.base_url),
+                Some(item.id),
+            )
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn at(seconds: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(seconds, 0).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_selects_by_every_filter() {
+        let filter = ContentFilter {
+            since: Some(at(100)),
+            until: None,
+            content_type: Some("paper".to_string()),
+            source: None,
+        };
+
+        assert!(selects(&filter, Some("paper"), Some("cli"), at(100)));
+        assert!(!selects(&filter, Some("article"), Some("cli"), at(100)));
+        assert!(!selects(&filter, None, Some("cli"), at(100)));
+        assert!(!selects(&filter, Some("paper"), Some("cli"), at(99)));
+        assert!(selects(&ContentFilter::default(), None, None, at(0)));
+    }
+
+    #[test]
+    fn test_new_validates_settings() {
+        let target = SyncTarget::new(" https://reading.example.com/ ", None, "public").unwrap();
+        assert_eq!(target.base_url, "https://reading.example.com");
+
+        assert!(matches!(
+            SyncTarget::new("ftp://reading.example.com", None, "public"),
+            Err(SyncConfigError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            SyncTarget::new("https://reading.example.com", None, " "),
+            Err(SyncConfigError::MissingView)
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+//! Bookmarks from a Karakeep (formerly Hoarder) JSON export.
+
+use crate::export::karakeep::Export;
+pub use crate::export::karakeep::{Bookmark, BookmarkContent};
+
+use super::ImportError;
+
+pub fn parse_bookmarks(json: &str) -> Result<Vec<Bookmark>, ImportError> {
+    let export: Export =
+        serde_json::from_str(json).map_err(|err| ImportError::InvalidExport(err.to_string()))?;
+    Ok(export.bookmarks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bookmarks() {
+        let bookmarks = parse_bookmarks(
+            r#"{"bookmarks": [
+                {"createdAt": 1704153600, "title": "Post", "tags": ["rust"],
+                 "content": {"type": "link", "url": "https://blog.example.com/post"},
+                 "note": "Read later", "archived": true},
+                {"createdAt": 1704153601, "title": null, "tags": [],
+                 "content": {"type": "text", "text": "A thought"}, "note": null},
+                {"createdAt": 1704153602, "title": null, "tags": [],
+                 "content": {"type": "asset", "assetType": "image", "assetId": "x"}, "note": null}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            bookmarks[0],
+            Bookmark {
+                created_at: 1704153600,
+                title: Some("Post".to_string()),
+                tags: vec!["rust".to_string()],
+                content: Some(BookmarkContent::Link {
+                    url: "https://blog.example.com/post".to_string()
+                }),
+                note: Some("Read later".to_string()),
+                archived: true,
+            }
+        );
+        assert!(!bookmarks[1].archived);
+        assert_eq!(bookmarks[2].content, Some(BookmarkContent::Other));
+    }
+
+    #[test]
+    fn test_parse_rejects_other_json() {
+        assert!(parse_bookmarks(r#"{"items": []}"#).is_err());
+        assert!(parse_bookmarks("<rss/>").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Parsing of items from external formats into content to be saved.
+
+pub mod karakeep;
+pub mod rss;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Invalid feed: {0}")]
+    InvalidFeed(String),
+    #[error("Invalid export: {0}")]
+    InvalidExport(String),
+}
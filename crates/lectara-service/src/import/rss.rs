@@ -0,0 +1,237 @@
+//! RSS 2.0 feed items, including podcast enclosures and `itunes:duration`.
+
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use super::ImportError;
+
+/// One `<item>` of a feed; `url` is its link, or the enclosure for
+/// audio-only items that have none
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeedItem {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub duration_seconds: Option<i32>,
+}
+
+pub fn parse_items(xml: &str) -> Result<Vec<FeedItem>, ImportError> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut items = Vec::new();
+    let mut current: Option<FeedItem> = None;
+    let mut guid: Option<String> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|err| ImportError::InvalidFeed(err.to_string()))?;
+
+        match event {
+            Event::Start(start) => {
+                let name = local_name(&start);
+                if name == "item" {
+                    current = Some(FeedItem::default());
+                    guid = None;
+                } else if name == "enclosure"
+                    && let Some(item) = current.as_mut()
+                {
+                    item.enclosure_url = attribute(&start, "url")?;
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Empty(element) => {
+                if local_name(&element) == "enclosure"
+                    && let Some(item) = current.as_mut()
+                {
+                    item.enclosure_url = attribute(&element, "url")?;
+                }
+            }
+            Event::Text(content) => {
+                let content = content
+                    .decode()
+                    .map_err(|err| ImportError::InvalidFeed(err.to_string()))?;
+                text.push_str(&content);
+            }
+            Event::CData(content) => {
+                text.push_str(&String::from_utf8_lossy(&content));
+            }
+            Event::GeneralRef(reference) => {
+                if let Ok(Some(ch)) = reference.resolve_char_ref() {
+                    text.push(ch);
+                } else if let Ok(name) = reference.decode()
+                    && let Some(resolved) = quick_xml::escape::resolve_predefined_entity(&name)
+                {
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(_) => {
+                let element = path.pop().unwrap_or_default();
+                let value = text.trim().to_string();
+                text.clear();
+
+                if element == "item" {
+                    if let Some(mut item) = current.take() {
+                        item.url = item
+                            .url
+                            .or_else(|| guid.take())
+                            .or_else(|| item.enclosure_url.clone());
+                        items.push(item);
+                    }
+                    continue;
+                }
+
+                // Only direct children of <item>
+                let Some(item) = current
+                    .as_mut()
+                    .filter(|_| path.last().is_some_and(|p| p == "item"))
+                else {
+                    continue;
+                };
+                if value.is_empty() {
+                    continue;
+                }
+                match element.as_str() {
+                    "title" => item.title = Some(value),
+                    "link" => item.url = Some(value),
+                    "guid" if value.starts_with("http://") || value.starts_with("https://") => {
+                        guid = Some(value)
+                    }
+                    // <author>, <itunes:author> and <dc:creator>; the first one wins
+                    "author" | "creator" => {
+                        item.author.get_or_insert(value);
+                    }
+                    "duration" => item.duration_seconds = parse_duration(&value),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(items)
+}
+
+fn local_name(element: &BytesStart<'_>) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).into_owned()
+}
+
+fn attribute(element: &BytesStart<'_>, name: &str) -> Result<Option<String>, ImportError> {
+    let attribute = element
+        .try_get_attribute(name)
+        .map_err(|err| ImportError::InvalidFeed(err.to_string()))?;
+    attribute
+        .map(|attribute| {
+            attribute
+                .unescape_value()
+                .map(|value| value.trim().to_string())
+                .map_err(|err| ImportError::InvalidFeed(err.to_string()))
+        })
+        .transpose()
+}
+
+/// `itunes:duration` is either plain seconds or `[[H:]M:]S`
+pub fn parse_duration(value: &str) -> Option<i32> {
+    let mut seconds: i32 = 0;
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for part in parts {
+        let part: i32 = part.trim().parse().ok()?;
+        if part < 0 {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(part)?;
+    }
+    Some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PODCAST_FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Stub Cast</title>
+    <link>https://podcast.example.com</link>
+    <item>
+      <title>Episode 1: Tom &amp; Jerry</title>
+      <link>https://podcast.example.com/episodes/1</link>
+      <itunes:author>Ada Lovelace</itunes:author>
+      <enclosure url="https://cdn.example.com/ep1.mp3?token=a&amp;b=c" length="1234" type="audio/mpeg"/>
+      <itunes:duration>1:02:03</itunes:duration>
+    </item>
+    <item>
+      <title><![CDATA[Episode <2>]]></title>
+      <guid isPermaLink="false">ep-2</guid>
+      <enclosure url="https://cdn.example.com/ep2.mp3" type="audio/mpeg"/>
+      <itunes:duration>95</itunes:duration>
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn test_parse_podcast_items() {
+        let items = parse_items(PODCAST_FIXTURE).unwrap();
+
+        assert_eq!(
+            items,
+            vec![
+                FeedItem {
+                    url: Some("https://podcast.example.com/episodes/1".to_string()),
+                    title: Some("Episode 1: Tom & Jerry".to_string()),
+                    author: Some("Ada Lovelace".to_string()),
+                    enclosure_url: Some("https://cdn.example.com/ep1.mp3?token=a&b=c".to_string()),
+                    duration_seconds: Some(3723),
+                },
+                FeedItem {
+                    url: Some("https://cdn.example.com/ep2.mp3".to_string()),
+                    title: Some("Episode <2>".to_string()),
+                    author: None,
+                    enclosure_url: Some("https://cdn.example.com/ep2.mp3".to_string()),
+                    duration_seconds: Some(95),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_article_feed() {
+        let items = parse_items(
+            r#"<rss><channel><title>Blog</title><item>
+                <title>Post</title>
+                <guid>https://blog.example.com/post</guid>
+                <dc:creator xmlns:dc="http://purl.org/dc/elements/1.1/">Grace Hopper</dc:creator>
+            </item></channel></rss>"#,
+        )
+        .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].url.as_deref(),
+            Some("https://blog.example.com/post")
+        );
+        assert_eq!(items[0].author.as_deref(), Some("Grace Hopper"));
+        assert_eq!(items[0].enclosure_url, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_feed() {
+        assert!(parse_items("<rss><channel><item></channel></rss>").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("95"), Some(95));
+        assert_eq!(parse_duration("12:05"), Some(725));
+        assert_eq!(parse_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_duration("1:2:3:4"), None);
+        assert_eq!(parse_duration("about an hour"), None);
+    }
+}
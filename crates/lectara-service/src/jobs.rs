@@ -0,0 +1,267 @@
+//! Long-running work, such as a large import, run in the background.
+//!
+//! The request starting a job answers with its id right away, and the job
+//! reports its progress through the jobs API until it finishes, fails or is
+//! cancelled. Jobs are kept in memory, so they end with the process, and
+//! only the most recent finished ones are remembered.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+
+/// Finished jobs remembered for clients to look up
+const MAX_FINISHED_JOBS: usize = 100;
+/// Item errors listed per job; later ones are only counted
+const MAX_LISTED_ERRORS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Finished,
+    /// Stopped by an error other than a bad item
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobError {
+    /// The item that failed, as given to the job
+    pub item: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobProgress {
+    pub id: u64,
+    /// What the job does, e.g. `import:rss`
+    pub kind: &'static str,
+    pub status: JobStatus,
+    pub processed: u64,
+    pub total: u64,
+    /// Items processed so far by outcome, e.g. `created`
+    pub outcomes: BTreeMap<&'static str, u64>,
+    /// Items that failed so far
+    pub error_count: u64,
+    /// The first items that failed
+    pub errors: Vec<JobError>,
+    /// Why the job failed, when it did
+    pub failure: Option<String>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+#[derive(Default)]
+struct Jobs {
+    last_id: u64,
+    jobs: BTreeMap<u64, JobProgress>,
+}
+
+impl Jobs {
+    /// Forgets the oldest finished jobs beyond [`MAX_FINISHED_JOBS`]
+    fn prune(&mut self) {
+        let finished: Vec<u64> = self
+            .jobs
+            .values()
+            .filter(|job| job.status != JobStatus::Running)
+            .map(|job| job.id)
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for id in &finished[..excess] {
+            self.jobs.remove(id);
+        }
+    }
+}
+
+/// The jobs of one database; clones share them
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<Jobs>>,
+}
+
+impl JobRegistry {
+    /// Registers a running job over `total` items, returning the handle the
+    /// job reports its progress through
+    pub fn start(&self, kind: &'static str, total: u64) -> JobHandle {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.last_id += 1;
+        let id = jobs.last_id;
+        jobs.jobs.insert(
+            id,
+            JobProgress {
+                id,
+                kind,
+                status: JobStatus::Running,
+                processed: 0,
+                total,
+                outcomes: BTreeMap::new(),
+                error_count: 0,
+                errors: Vec::new(),
+                failure: None,
+                started_at: Utc::now().naive_utc(),
+                finished_at: None,
+            },
+        );
+        JobHandle {
+            id,
+            registry: self.clone(),
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<JobProgress> {
+        self.jobs.lock().unwrap().jobs.get(&id).cloned()
+    }
+
+    /// Asks a running job to stop after the item it is processing. Jobs that
+    /// already ended are left as they are.
+    pub fn cancel(&self, id: u64) -> Option<JobProgress> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.jobs.get_mut(&id)?;
+        if job.status == JobStatus::Running {
+            job.status = JobStatus::Cancelled;
+        }
+        Some(job.clone())
+    }
+
+    fn update(&self, id: u64, update: impl FnOnce(&mut JobProgress)) {
+        if let Some(job) = self.jobs.lock().unwrap().jobs.get_mut(&id) {
+            update(job);
+        }
+    }
+}
+
+/// A running job's side of its progress. A job dropped before it finished,
+/// e.g. because its task panicked, is marked failed.
+pub struct JobHandle {
+    id: u64,
+    registry: JobRegistry,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Whether the job should stop
+    pub fn is_cancelled(&self) -> bool {
+        self.registry
+            .get(self.id)
+            .is_none_or(|job| job.status == JobStatus::Cancelled)
+    }
+
+    /// Counts an item processed with `outcome`
+    pub fn record(&self, outcome: &'static str) {
+        self.registry.update(self.id, |job| {
+            job.processed += 1;
+            *job.outcomes.entry(outcome).or_default() += 1;
+        });
+    }
+
+    /// Counts an item that failed with `outcome`, listing its error
+    pub fn record_error(&self, outcome: &'static str, item: Option<String>, error: String) {
+        self.registry.update(self.id, |job| {
+            job.processed += 1;
+            *job.outcomes.entry(outcome).or_default() += 1;
+            job.error_count += 1;
+            if job.errors.len() < MAX_LISTED_ERRORS {
+                job.errors.push(JobError { item, error });
+            }
+        });
+    }
+
+    /// Ends the job, as cancelled if that was asked for, and returns its
+    /// final progress
+    pub fn finish(self, failure: Option<String>) -> Option<JobProgress> {
+        self.end(failure);
+        self.registry.get(self.id)
+    }
+
+    fn end(&self, failure: Option<String>) {
+        let mut jobs = self.registry.jobs.lock().unwrap();
+        if let Some(job) = jobs.jobs.get_mut(&self.id)
+            && job.finished_at.is_none()
+        {
+            job.status = match (&failure, job.status) {
+                (Some(_), _) => JobStatus::Failed,
+                (None, JobStatus::Cancelled) => JobStatus::Cancelled,
+                (None, _) => JobStatus::Finished,
+            };
+            job.failure = failure;
+            job.finished_at = Some(Utc::now().naive_utc());
+        }
+        jobs.prune();
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        // A no-op for jobs that finished
+        self.end(Some("The job stopped unexpectedly".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_progress() {
+        let registry = JobRegistry::default();
+        let job = registry.start("import:rss", 3);
+        job.record("created");
+        job.record("created");
+        job.record_error(
+            "invalid",
+            Some("ftp://x".to_string()),
+            "Bad URL".to_string(),
+        );
+
+        let progress = job.finish(None).unwrap();
+        assert_eq!(progress.status, JobStatus::Finished);
+        assert_eq!(progress.processed, 3);
+        assert_eq!(progress.outcomes["created"], 2);
+        assert_eq!(progress.error_count, 1);
+        assert_eq!(progress.errors[0].item.as_deref(), Some("ftp://x"));
+        assert!(progress.finished_at.is_some());
+    }
+
+    #[test]
+    fn test_cancel_only_running_jobs() {
+        let registry = JobRegistry::default();
+        let job = registry.start("import:rss", 2);
+        assert!(!job.is_cancelled());
+        assert_eq!(
+            registry.cancel(job.id()).unwrap().status,
+            JobStatus::Cancelled
+        );
+        assert!(job.is_cancelled());
+        assert_eq!(job.finish(None).unwrap().status, JobStatus::Cancelled);
+
+        let finished = registry.start("import:rss", 0);
+        let id = finished.id();
+        finished.finish(None);
+        assert_eq!(registry.cancel(id).unwrap().status, JobStatus::Finished);
+        assert!(registry.cancel(id + 1).is_none());
+    }
+
+    #[test]
+    fn test_dropped_job_fails() {
+        let registry = JobRegistry::default();
+        let id = registry.start("import:rss", 1).id();
+        assert_eq!(registry.get(id).unwrap().status, JobStatus::Failed);
+    }
+
+    #[test]
+    fn test_only_recent_finished_jobs_are_kept() {
+        let registry = JobRegistry::default();
+        let running = registry.start("import:rss", 1);
+        for _ in 0..=MAX_FINISHED_JOBS {
+            registry.start("import:rss", 0).finish(None);
+        }
+        assert!(registry.get(running.id()).is_some());
+        assert!(registry.get(running.id() + 1).is_none());
+        assert!(registry.get(running.id() + 2).is_some());
+    }
+}
@@ -0,0 +1,238 @@
+//! Keyword extraction for tag suggestions.
+//!
+//! Words are weighed by TF-IDF: how often they occur in the item, scaled
+//! down by how many saved items contain them, so words common to the whole
+//! collection rank below the ones that set an item apart. Words are folded
+//! the same way the unstemmed search index folds them (lowercase, no
+//! diacritics) so their document counts can be read from the index.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+use crate::repositories::DocumentFrequencies;
+
+/// Words shorter than this are rarely useful as tags
+const MIN_WORD_LENGTH: usize = 3;
+const MAX_WORD_LENGTH: usize = 32;
+
+const STOP_WORDS: &[&str] = &[
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "also",
+    "and",
+    "any",
+    "are",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "can",
+    "could",
+    "did",
+    "does",
+    "doing",
+    "down",
+    "during",
+    "each",
+    "even",
+    "few",
+    "for",
+    "from",
+    "further",
+    "get",
+    "got",
+    "had",
+    "has",
+    "have",
+    "having",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "however",
+    "into",
+    "its",
+    "itself",
+    "just",
+    "like",
+    "many",
+    "may",
+    "more",
+    "most",
+    "much",
+    "must",
+    "not",
+    "now",
+    "off",
+    "once",
+    "one",
+    "only",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "she",
+    "should",
+    "since",
+    "some",
+    "still",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "too",
+    "under",
+    "until",
+    "use",
+    "used",
+    "using",
+    "very",
+    "was",
+    "way",
+    "well",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "would",
+    "yet",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+/// A suggested tag and its TF-IDF weight
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keyword {
+    pub tag: String,
+    pub score: f64,
+}
+
+/// Words of `text` in index form, with how often each occurs. Stop words,
+/// numbers and very short or long words are left out.
+pub fn term_counts(text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let word = fold(word);
+        let length = word.chars().count();
+        if (MIN_WORD_LENGTH..=MAX_WORD_LENGTH).contains(&length)
+            && word.chars().any(char::is_alphabetic)
+            && !STOP_WORDS.contains(&word.as_str())
+        {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Lowercases `word` and strips its diacritics, like the search tokenizer
+fn fold(word: &str) -> String {
+    word.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The `limit` highest-weighted terms, best first
+pub fn rank(
+    counts: &HashMap<String, u32>,
+    frequencies: &DocumentFrequencies,
+    limit: usize,
+) -> Vec<Keyword> {
+    let total: u32 = counts.values().sum();
+    let documents = frequencies.documents as f64;
+
+    let mut keywords: Vec<Keyword> = counts
+        .iter()
+        .map(|(term, &count)| {
+            let containing = frequencies.terms.get(term).copied().unwrap_or(0) as f64;
+            let tf = f64::from(count) / f64::from(total);
+            // Smoothed so terms found in every item still count a little
+            let idf = ((1.0 + documents) / (1.0 + containing)).ln() + 1.0;
+            Keyword {
+                tag: term.clone(),
+                score: tf * idf,
+            }
+        })
+        .collect();
+
+    keywords.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+    keywords.truncate(limit);
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_term_counts_fold_and_filter_words() {
+        let counts = term_counts("The Café served coffee; the cafe's coffee was 42 degrees. Go!");
+
+        assert_eq!(counts.get("cafe"), Some(&2));
+        assert_eq!(counts.get("coffee"), Some(&2));
+        assert_eq!(counts.get("degrees"), Some(&1));
+        // Stop words, numbers and short words
+        for dropped in ["the", "was", "42", "go", "s"] {
+            assert!(!counts.contains_key(dropped), "{dropped}");
+        }
+    }
+
+    #[test]
+    fn test_rank_prefers_distinctive_terms() {
+        let counts = term_counts("rust rust borrow checker article article");
+        let frequencies = DocumentFrequencies {
+            documents: 100,
+            terms: HashMap::from([
+                ("rust".to_string(), 5),
+                ("borrow".to_string(), 2),
+                ("checker".to_string(), 2),
+                ("article".to_string(), 100),
+            ]),
+        };
+
+        let tags: Vec<_> = rank(&counts, &frequencies, 3)
+            .into_iter()
+            .map(|keyword| keyword.tag)
+            .collect();
+        assert_eq!(tags, ["rust", "borrow", "checker"]);
+    }
+}
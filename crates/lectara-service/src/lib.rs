@@ -1,44 +1,503 @@
-use diesel::sqlite::SqliteConnection;
-use std::sync::{Arc, Mutex};
+use axum::Router;
+use std::sync::{Arc, OnceLock};
 
-use crate::repositories::{ContentRepository, SqliteContentRepository};
+use crate::activity::ActivityFeed;
+#[cfg(feature = "activitypub")]
+use crate::activitypub::ActivityPub;
+use crate::database::Database;
+use crate::delivery::DeviceMailer;
+use crate::enrichment::citations::CitationResolver;
+use crate::enrichment::fetch::Fetcher;
+use crate::enrichment::github::GithubResolver;
+use crate::enrichment::pdf::PdfExtractor;
+use crate::enrichment::pipeline::EnricherRegistry;
+use crate::enrichment::site_rules::SiteRules;
+use crate::enrichment::summary::Summarizer;
+use crate::enrichment::threads::ThreadResolver;
+use crate::federation::SyncTarget;
+use crate::jobs::JobRegistry;
+use crate::maintenance::MaintenanceSchedule;
+use crate::metrics::RequestMetrics;
+use crate::notifications::Notifier;
+use crate::read_only::ReadOnlyMode;
+use crate::reload::Reloader;
+use crate::repositories::{
+    ArchiveSnapshotRepository, CitationRepository, CommentRepository, ContentRepository,
+    FetchAttemptRepository, FollowerRepository, NotificationRepository, PageSnapshotRepository,
+    QueryCache, RuleRepository, SchemaRepository, ShareLinkRepository,
+    SqliteArchiveSnapshotRepository, SqliteCitationRepository, SqliteCommentRepository,
+    SqliteContentRepository, SqliteFetchAttemptRepository, SqliteFollowerRepository,
+    SqliteNotificationRepository, SqlitePageSnapshotRepository, SqliteRuleRepository,
+    SqliteSchemaRepository, SqliteShareLinkRepository, SqliteViewRepository, ViewRepository,
+};
+use crate::validation::ValidationContext;
 
+pub mod activity;
+#[cfg(feature = "activitypub")]
+pub mod activitypub;
+pub mod archive;
+pub mod auth;
+pub mod bodies;
+pub mod build_info;
+pub mod comments;
+pub mod config;
+pub mod connection;
+pub mod database;
+pub mod delivery;
+pub mod enrichment;
 pub mod errors;
+pub mod export;
+pub mod federation;
+pub mod import;
+pub mod jobs;
+pub mod keywords;
+pub mod limits;
+pub mod maintenance;
+pub mod metrics;
+pub mod migrations;
 pub mod models;
+pub mod notifications;
+pub mod passphrases;
+pub mod read_only;
+pub mod reload;
 pub mod repositories;
 pub mod routes;
+pub mod rules;
 pub mod schema;
+pub mod search;
+pub mod seed;
 pub mod shutdown;
+pub mod systemd;
+pub mod tenants;
 pub mod validation;
 
-#[derive(Clone)]
-pub struct PocAppState {
-    pub db: Arc<Mutex<SqliteConnection>>,
+/// The full service, health checks, API and web pages, without
+/// authentication or middleware, for embedding in another Axum app.
+///
+/// `state` can be [`DefaultAppState`] or any other [`AppState`]. To mount
+/// lectara inside an app with routes of its own, nest
+/// [`routes::create_router`] into a router sharing the app's state instead;
+/// see `examples/embed.rs`.
+pub fn create_app<S: AppState>(state: S) -> Router {
+    routes::create_router().with_state(state)
 }
 
+/// Everything the routes need: repositories, deployment settings and the
+/// clients used by enrichment. Implement it to back the routes with other
+/// repositories or to share state with an app lectara is embedded in.
 pub trait AppState: Clone + Send + Sync + 'static {
     type ContentRepo: ContentRepository;
+    type ShareLinkRepo: ShareLinkRepository;
+    type SchemaRepo: SchemaRepository;
+    type CitationRepo: CitationRepository;
+    type FetchAttemptRepo: FetchAttemptRepository;
+    type PageSnapshotRepo: PageSnapshotRepository;
+    type ArchiveSnapshotRepo: ArchiveSnapshotRepository;
+    type NotificationRepo: NotificationRepository;
+    type RuleRepo: RuleRepository;
+    type ViewRepo: ViewRepository;
+    type CommentRepo: CommentRepository;
+    type FollowerRepo: FollowerRepository;
 
     fn content_repo(&self) -> Self::ContentRepo;
+    fn share_link_repo(&self) -> Self::ShareLinkRepo;
+    fn schema_repo(&self) -> Self::SchemaRepo;
+    fn citation_repo(&self) -> Self::CitationRepo;
+    fn fetch_attempt_repo(&self) -> Self::FetchAttemptRepo;
+    fn page_snapshot_repo(&self) -> Self::PageSnapshotRepo;
+    fn archive_snapshot_repo(&self) -> Self::ArchiveSnapshotRepo;
+    fn notification_repo(&self) -> Self::NotificationRepo;
+    fn rule_repo(&self) -> Self::RuleRepo;
+    fn view_repo(&self) -> Self::ViewRepo;
+    fn comment_repo(&self) -> Self::CommentRepo;
+    fn follower_repo(&self) -> Self::FollowerRepo;
+    /// Deployment rules applied when validating submitted URLs
+    fn validation(&self) -> &ValidationContext;
+    /// Client for fetching saved pages themselves
+    fn fetcher(&self) -> &Fetcher;
+    /// Client for Crossref/arXiv lookups; `None` disables citation enrichment
+    fn citation_resolver(&self) -> Option<&CitationResolver>;
+    /// Client for Mastodon/X thread lookups; `None` disables thread unrolling
+    fn thread_resolver(&self) -> Option<&ThreadResolver>;
+    /// Client for GitHub repository lookups; `None` disables them
+    fn github_resolver(&self) -> Option<&GithubResolver>;
+    /// Downloader for PDF text extraction; `None` disables it
+    fn pdf_extractor(&self) -> Option<&PdfExtractor>;
+    /// Backend writing item summaries; `None` disables them
+    fn summarizer(&self) -> Option<&Summarizer>;
+    /// Per-site extraction rules; `None` disables rule-based extraction
+    fn site_rules(&self) -> Option<&SiteRules>;
+    /// Mailer sending items to e-readers; `None` disables sending
+    fn device_mailer(&self) -> Option<&DeviceMailer>;
+    /// Instance saves are pushed to; `None` disables pushing
+    fn sync_target(&self) -> Option<&SyncTarget>;
+    /// The actor publishing saves to the fediverse; `None` disables it
+    #[cfg(feature = "activitypub")]
+    fn activitypub(&self) -> Option<&ActivityPub>;
+    /// Steps run in order for each newly saved item
+    fn enrichers(&self) -> &EnricherRegistry<Self>;
+    /// Switch that makes every writing endpoint answer 503
+    fn read_only(&self) -> &ReadOnlyMode;
+    /// Upcoming or current maintenance window, announced to clients
+    fn maintenance(&self) -> &MaintenanceSchedule;
+    /// Settings re-read without a restart, such as the log filter
+    fn reloader(&self) -> &Reloader;
+    /// Recent request latencies and statuses, per endpoint
+    fn metrics(&self) -> &RequestMetrics;
+    /// Publishes notifications to the clients following them
+    fn notifier(&self) -> &Notifier;
+    /// Background jobs, such as large imports, and their progress
+    fn jobs(&self) -> &JobRegistry;
+    /// Recent saves of the other tenants sharing their activity; `None`
+    /// outside multi-tenant mode or for tenants not sharing theirs
+    fn activity(&self) -> Option<&ActivityFeed<Self::ContentRepo>>;
 }
 
 #[derive(Clone)]
 pub struct DefaultAppState {
     content_repository: SqliteContentRepository,
+    share_link_repository: SqliteShareLinkRepository,
+    schema_repository: SqliteSchemaRepository,
+    citation_repository: SqliteCitationRepository,
+    fetch_attempt_repository: SqliteFetchAttemptRepository,
+    page_snapshot_repository: SqlitePageSnapshotRepository,
+    archive_snapshot_repository: SqliteArchiveSnapshotRepository,
+    notification_repository: SqliteNotificationRepository,
+    rule_repository: SqliteRuleRepository,
+    view_repository: SqliteViewRepository,
+    comment_repository: SqliteCommentRepository,
+    follower_repository: SqliteFollowerRepository,
+    validation: Arc<ValidationContext>,
+    /// Built on first use, following the configured validation rules
+    fetcher: OnceLock<Fetcher>,
+    citation_resolver: Option<CitationResolver>,
+    thread_resolver: Option<ThreadResolver>,
+    github_resolver: Option<GithubResolver>,
+    pdf_extractor: Option<PdfExtractor>,
+    summarizer: Option<Summarizer>,
+    site_rules: Option<Arc<SiteRules>>,
+    device_mailer: Option<DeviceMailer>,
+    sync_target: Option<SyncTarget>,
+    #[cfg(feature = "activitypub")]
+    activitypub: Option<ActivityPub>,
+    enrichers: Arc<EnricherRegistry<DefaultAppState>>,
+    read_only: ReadOnlyMode,
+    maintenance: MaintenanceSchedule,
+    reloader: Reloader,
+    metrics: RequestMetrics,
+    notifier: Notifier,
+    jobs: JobRegistry,
+    activity: Option<ActivityFeed<SqliteContentRepository>>,
 }
 
 impl DefaultAppState {
-    pub fn new(db: Arc<Mutex<SqliteConnection>>) -> Self {
+    /// Builds the state on `db`, a connection or a [`Database`] with read
+    /// connections
+    pub fn new(db: impl Into<Database>) -> Self {
+        let db = db.into();
         Self {
-            content_repository: SqliteContentRepository::new(db),
+            content_repository: SqliteContentRepository::new(db.clone()),
+            share_link_repository: SqliteShareLinkRepository::new(db.clone()),
+            schema_repository: SqliteSchemaRepository::new(db.clone()),
+            citation_repository: SqliteCitationRepository::new(db.clone()),
+            fetch_attempt_repository: SqliteFetchAttemptRepository::new(db.clone()),
+            page_snapshot_repository: SqlitePageSnapshotRepository::new(db.clone()),
+            archive_snapshot_repository: SqliteArchiveSnapshotRepository::new(db.clone()),
+            notification_repository: SqliteNotificationRepository::new(db.clone()),
+            rule_repository: SqliteRuleRepository::new(db.clone()),
+            view_repository: SqliteViewRepository::new(db.clone()),
+            comment_repository: SqliteCommentRepository::new(db.clone()),
+            follower_repository: SqliteFollowerRepository::new(db),
+            validation: Arc::new(ValidationContext::default()),
+            fetcher: OnceLock::new(),
+            citation_resolver: None,
+            thread_resolver: None,
+            github_resolver: None,
+            pdf_extractor: None,
+            summarizer: None,
+            site_rules: None,
+            device_mailer: None,
+            sync_target: None,
+            #[cfg(feature = "activitypub")]
+            activitypub: None,
+            enrichers: Arc::new(EnricherRegistry::default()),
+            read_only: ReadOnlyMode::default(),
+            maintenance: MaintenanceSchedule::default(),
+            reloader: Reloader::default(),
+            metrics: RequestMetrics::default(),
+            notifier: Notifier::default(),
+            jobs: JobRegistry::default(),
+            activity: None,
+        }
+    }
+
+    /// Points every repository at `db`, keeping the rest of the state.
+    /// Tenants of a multi-tenant instance share everything but this.
+    pub fn with_database(mut self, db: impl Into<Database>) -> Self {
+        let db = db.into();
+        self.content_repository = SqliteContentRepository::new(db.clone());
+        self.share_link_repository = SqliteShareLinkRepository::new(db.clone());
+        self.schema_repository = SqliteSchemaRepository::new(db.clone());
+        self.citation_repository = SqliteCitationRepository::new(db.clone());
+        self.fetch_attempt_repository = SqliteFetchAttemptRepository::new(db.clone());
+        self.page_snapshot_repository = SqlitePageSnapshotRepository::new(db.clone());
+        self.archive_snapshot_repository = SqliteArchiveSnapshotRepository::new(db.clone());
+        self.notification_repository = SqliteNotificationRepository::new(db.clone());
+        self.rule_repository = SqliteRuleRepository::new(db.clone());
+        self.view_repository = SqliteViewRepository::new(db.clone());
+        self.comment_repository = SqliteCommentRepository::new(db.clone());
+        self.follower_repository = SqliteFollowerRepository::new(db);
+        // Notifications and jobs are the database's own, so are their
+        // streams and job ids, and the activity feed leaves out its items.
+        // Tenants don't push or publish saves, which would mix them on the
+        // target or under the one actor.
+        self.notifier = Notifier::default();
+        self.jobs = JobRegistry::default();
+        self.activity = None;
+        self.sync_target = None;
+        #[cfg(feature = "activitypub")]
+        {
+            self.activitypub = None;
+        }
+        self
+    }
+
+    /// Caches single-item lookups of the content repository; each database
+    /// needs a cache of its own
+    pub fn with_query_cache(mut self, cache: Option<QueryCache>) -> Self {
+        if let Some(cache) = cache {
+            self.content_repository = self.content_repository.with_cache(cache);
         }
+        self
+    }
+
+    pub fn with_citation_resolver(mut self, resolver: Option<CitationResolver>) -> Self {
+        self.citation_resolver = resolver;
+        self
+    }
+
+    pub fn with_thread_resolver(mut self, resolver: Option<ThreadResolver>) -> Self {
+        self.thread_resolver = resolver;
+        self
+    }
+
+    pub fn with_github_resolver(mut self, resolver: Option<GithubResolver>) -> Self {
+        self.github_resolver = resolver;
+        self
+    }
+
+    pub fn with_fetcher(mut self, fetcher: Fetcher) -> Self {
+        self.fetcher = OnceLock::from(fetcher);
+        self
+    }
+
+    pub fn with_pdf_extractor(mut self, extractor: Option<PdfExtractor>) -> Self {
+        self.pdf_extractor = extractor;
+        self
+    }
+
+    pub fn with_summarizer(mut self, summarizer: Option<Summarizer>) -> Self {
+        self.summarizer = summarizer;
+        self
+    }
+
+    pub fn with_site_rules(mut self, rules: Option<SiteRules>) -> Self {
+        self.site_rules = rules.map(Arc::new);
+        self
+    }
+
+    pub fn with_device_mailer(mut self, mailer: Option<DeviceMailer>) -> Self {
+        self.device_mailer = mailer;
+        self
+    }
+
+    pub fn with_sync_target(mut self, target: Option<SyncTarget>) -> Self {
+        self.sync_target = target;
+        self
+    }
+
+    #[cfg(feature = "activitypub")]
+    pub fn with_activitypub(mut self, activitypub: Option<ActivityPub>) -> Self {
+        self.activitypub = activitypub;
+        self
+    }
+
+    pub fn with_enrichers(mut self, enrichers: EnricherRegistry<Self>) -> Self {
+        self.enrichers = Arc::new(enrichers);
+        self
+    }
+
+    pub fn with_validation(mut self, validation: ValidationContext) -> Self {
+        self.validation = Arc::new(validation);
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: ReadOnlyMode) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn with_maintenance(mut self, maintenance: MaintenanceSchedule) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    pub fn with_reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = reloader;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: RequestMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_activity(
+        mut self,
+        activity: Option<ActivityFeed<SqliteContentRepository>>,
+    ) -> Self {
+        self.activity = activity;
+        self
     }
 }
 
 impl AppState for DefaultAppState {
     type ContentRepo = SqliteContentRepository;
+    type ShareLinkRepo = SqliteShareLinkRepository;
+    type SchemaRepo = SqliteSchemaRepository;
+    type CitationRepo = SqliteCitationRepository;
+    type FetchAttemptRepo = SqliteFetchAttemptRepository;
+    type PageSnapshotRepo = SqlitePageSnapshotRepository;
+    type ArchiveSnapshotRepo = SqliteArchiveSnapshotRepository;
+    type NotificationRepo = SqliteNotificationRepository;
+    type RuleRepo = SqliteRuleRepository;
+    type ViewRepo = SqliteViewRepository;
+    type CommentRepo = SqliteCommentRepository;
+    type FollowerRepo = SqliteFollowerRepository;
 
     fn content_repo(&self) -> Self::ContentRepo {
         self.content_repository.clone()
     }
+
+    fn share_link_repo(&self) -> Self::ShareLinkRepo {
+        self.share_link_repository.clone()
+    }
+
+    fn schema_repo(&self) -> Self::SchemaRepo {
+        self.schema_repository.clone()
+    }
+
+    fn citation_repo(&self) -> Self::CitationRepo {
+        self.citation_repository.clone()
+    }
+
+    fn fetch_attempt_repo(&self) -> Self::FetchAttemptRepo {
+        self.fetch_attempt_repository.clone()
+    }
+
+    fn page_snapshot_repo(&self) -> Self::PageSnapshotRepo {
+        self.page_snapshot_repository.clone()
+    }
+
+    fn archive_snapshot_repo(&self) -> Self::ArchiveSnapshotRepo {
+        self.archive_snapshot_repository.clone()
+    }
+
+    fn notification_repo(&self) -> Self::NotificationRepo {
+        self.notification_repository.clone()
+    }
+
+    fn rule_repo(&self) -> Self::RuleRepo {
+        self.rule_repository.clone()
+    }
+
+    fn view_repo(&self) -> Self::ViewRepo {
+        self.view_repository.clone()
+    }
+
+    fn comment_repo(&self) -> Self::CommentRepo {
+        self.comment_repository.clone()
+    }
+
+    fn follower_repo(&self) -> Self::FollowerRepo {
+        self.follower_repository.clone()
+    }
+
+    fn validation(&self) -> &ValidationContext {
+        &self.validation
+    }
+
+    fn fetcher(&self) -> &Fetcher {
+        self.fetcher
+            .get_or_init(|| Fetcher::new(self.validation.as_ref().clone()))
+    }
+
+    fn citation_resolver(&self) -> Option<&CitationResolver> {
+        self.citation_resolver.as_ref()
+    }
+
+    fn thread_resolver(&self) -> Option<&ThreadResolver> {
+        self.thread_resolver.as_ref()
+    }
+
+    fn github_resolver(&self) -> Option<&GithubResolver> {
+        self.github_resolver.as_ref()
+    }
+
+    fn pdf_extractor(&self) -> Option<&PdfExtractor> {
+        self.pdf_extractor.as_ref()
+    }
+
+    fn summarizer(&self) -> Option<&Summarizer> {
+        self.summarizer.as_ref()
+    }
+
+    fn site_rules(&self) -> Option<&SiteRules> {
+        self.site_rules.as_deref()
+    }
+
+    fn device_mailer(&self) -> Option<&DeviceMailer> {
+        self.device_mailer.as_ref()
+    }
+
+    fn sync_target(&self) -> Option<&SyncTarget> {
+        self.sync_target.as_ref()
+    }
+
+    #[cfg(feature = "activitypub")]
+    fn activitypub(&self) -> Option<&ActivityPub> {
+        self.activitypub.as_ref()
+    }
+
+    fn enrichers(&self) -> &EnricherRegistry<Self> {
+        &self.enrichers
+    }
+
+    fn read_only(&self) -> &ReadOnlyMode {
+        &self.read_only
+    }
+
+    fn maintenance(&self) -> &MaintenanceSchedule {
+        &self.maintenance
+    }
+
+    fn reloader(&self) -> &Reloader {
+        &self.reloader
+    }
+
+    fn metrics(&self) -> &RequestMetrics {
+        &self.metrics
+    }
+
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    fn jobs(&self) -> &JobRegistry {
+        &self.jobs
+    }
+
+    fn activity(&self) -> Option<&ActivityFeed<Self::ContentRepo>> {
+        self.activity.as_ref()
+    }
 }
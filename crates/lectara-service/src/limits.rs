@@ -0,0 +1,232 @@
+//! Concurrency limits for expensive endpoints.
+//!
+//! Searches, exports and archive captures can each keep the database or the
+//! CPU busy for seconds, so a few of them at once would starve quick
+//! requests such as saves. Every route of a [`RouteGroup`] goes through the
+//! group's `tower` [`GlobalConcurrencyLimitLayer`]: requests past the limit wait
+//! for a slot instead of competing, and the health report counts how many
+//! had to.
+//!
+//! The limits are process-wide and read from the environment on first use,
+//! since every tenant shares the same machine.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::task::{Context, Poll};
+
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tower::limit::{ConcurrencyLimit, GlobalConcurrencyLimitLayer};
+use tower::{Layer, Service};
+
+/// Searches run at once, API and web (default 8)
+pub const SEARCH_CONCURRENCY_ENV: &str = "LECTARA_SEARCH_CONCURRENCY";
+/// BibTeX, podcast, Karakeep and WARC exports run at once (default 2)
+pub const EXPORT_CONCURRENCY_ENV: &str = "LECTARA_EXPORT_CONCURRENCY";
+/// Archive snapshots captured at once (default 2)
+pub const ARCHIVE_CONCURRENCY_ENV: &str = "LECTARA_ARCHIVE_CONCURRENCY";
+
+/// Expensive routes sharing one limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    Search,
+    Export,
+    Archive,
+}
+
+impl RouteGroup {
+    const ALL: [Self; 3] = [Self::Search, Self::Export, Self::Archive];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Export => "export",
+            Self::Archive => "archive",
+        }
+    }
+
+    fn env(self) -> &'static str {
+        match self {
+            Self::Search => SEARCH_CONCURRENCY_ENV,
+            Self::Export => EXPORT_CONCURRENCY_ENV,
+            Self::Archive => ARCHIVE_CONCURRENCY_ENV,
+        }
+    }
+
+    fn default_limit(self) -> usize {
+        match self {
+            Self::Search => 8,
+            Self::Export | Self::Archive => 2,
+        }
+    }
+}
+
+static LIMITS: LazyLock<[Arc<RouteLimit>; 3]> =
+    LazyLock::new(|| RouteGroup::ALL.map(|group| Arc::new(RouteLimit::from_env(group))));
+
+/// The layer limiting `group`'s routes, e.g. `get(search).layer(limit(RouteGroup::Search))`
+pub fn limit(group: RouteGroup) -> RouteLimitLayer {
+    RouteLimitLayer::new(LIMITS[group as usize].clone())
+}
+
+/// Current use of every group's limit
+pub fn report() -> Vec<ConcurrencyReport> {
+    LIMITS.iter().map(|limit| limit.report()).collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConcurrencyReport {
+    pub group: &'static str,
+    pub limit: usize,
+    pub in_flight: usize,
+    /// Requests that found every slot taken and waited, since startup
+    pub saturated_requests: u64,
+}
+
+/// Slots shared by the routes of one group
+pub struct RouteLimit {
+    group: RouteGroup,
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    saturated: AtomicU64,
+}
+
+impl RouteLimit {
+    pub fn new(group: RouteGroup, limit: usize) -> Self {
+        Self {
+            group,
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+            saturated: AtomicU64::new(0),
+        }
+    }
+
+    /// Reads the group's limit, falling back to its default for unset,
+    /// invalid or zero values
+    fn from_env(group: RouteGroup) -> Self {
+        let limit = std::env::var(group.env())
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|limit| *limit > 0)
+            .unwrap_or_else(|| group.default_limit());
+        Self::new(group, limit)
+    }
+
+    pub fn report(&self) -> ConcurrencyReport {
+        ConcurrencyReport {
+            group: self.group.as_str(),
+            limit: self.limit,
+            in_flight: self.limit - self.semaphore.available_permits(),
+            saturated_requests: self.saturated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// [`GlobalConcurrencyLimitLayer`] over a [`RouteLimit`]'s slots, counting the
+/// requests that wait for one
+#[derive(Clone)]
+pub struct RouteLimitLayer {
+    limit: Arc<RouteLimit>,
+}
+
+impl RouteLimitLayer {
+    pub fn new(limit: Arc<RouteLimit>) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Layer<S> for RouteLimitLayer {
+    type Service = RouteLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RouteLimitService {
+            inner: GlobalConcurrencyLimitLayer::with_semaphore(self.limit.semaphore.clone())
+                .layer(inner),
+            limit: self.limit.clone(),
+            waiting: false,
+        }
+    }
+}
+
+pub struct RouteLimitService<S> {
+    inner: ConcurrencyLimit<S>,
+    limit: Arc<RouteLimit>,
+    /// Whether this request already counted as saturated
+    waiting: bool,
+}
+
+// Each request runs on its own clone, which starts out not waiting
+impl<S: Clone> Clone for RouteLimitService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            limit: self.limit.clone(),
+            waiting: false,
+        }
+    }
+}
+
+impl<S, Request> Service<Request> for RouteLimitService<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = <ConcurrencyLimit<S> as Service<Request>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let poll = self.inner.poll_ready(cx);
+        if poll.is_pending() && !self.waiting {
+            self.waiting = true;
+            self.limit.saturated.fetch_add(1, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        self.waiting = false;
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_requests_past_the_limit_wait_and_are_counted() {
+        let limit = Arc::new(RouteLimit::new(RouteGroup::Export, 1));
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let released = Arc::new(tokio::sync::Mutex::new(Some(released)));
+        let service =
+            RouteLimitLayer::new(limit.clone()).layer(tower::service_fn(move |slow: bool| {
+                let released = released.clone();
+                async move {
+                    if slow && let Some(released) = released.lock().await.take() {
+                        released.await.ok();
+                    }
+                    Ok::<_, std::convert::Infallible>(slow)
+                }
+            }));
+
+        let slow = tokio::spawn(service.clone().oneshot(true));
+        while limit.report().in_flight == 0 {
+            tokio::task::yield_now().await;
+        }
+        let fast = tokio::spawn(service.clone().oneshot(false));
+        while limit.report().saturated_requests == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!fast.is_finished());
+
+        release.send(()).unwrap();
+        assert!(slow.await.unwrap().unwrap());
+        assert!(!fast.await.unwrap().unwrap());
+
+        let report = limit.report();
+        assert_eq!(report.group, "export");
+        assert_eq!(report.in_flight, 0);
+        assert_eq!(report.saturated_requests, 1);
+    }
+}
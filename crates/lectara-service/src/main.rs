@@ -1,34 +1,192 @@
+use axum::Router;
+use clap::builder::BoolishValueParser;
+use clap::{Parser, Subcommand, ValueEnum};
 use diesel::Connection;
 use diesel::sqlite::SqliteConnection;
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use diesel_migrations::MigrationHarness;
 use lectara_service::{
-    DefaultAppState,
-    routes::create_router,
+    AppState, DefaultAppState,
+    activity::ActivityFeed,
+    auth::AccessPolicy,
+    bodies,
+    config::Settings,
+    connection::{self, ConnectionMonitor},
+    database::{Database, ReadPool},
+    delivery::DeviceMailer,
+    enrichment::{
+        attempts, citations::CitationResolver, credentials::SiteCredentials, fetch::Fetcher,
+        github::GithubResolver, hooks::Hooks, pdf::PdfExtractor, pipeline::EnricherRegistry,
+        site_rules::SiteRules, summary::Summarizer, threads::ThreadResolver,
+    },
+    federation::SyncTarget,
+    maintenance::MaintenanceSchedule,
+    metrics::{MetricsLayer, RequestMetrics},
+    migrations::{self, MIGRATIONS},
+    notifications::Notifier,
+    read_only::ReadOnlyMode,
+    reload::{self, Reloader},
+    repositories::QueryCache,
+    routes::{create_instance_router, create_router, create_tenant_router},
+    seed,
     shutdown::{GracefulShutdownLayer, ShutdownState},
+    systemd,
+    tenants::{TenantRegistry, create_multi_tenant_router},
+    validation::ValidationContext,
 };
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
 use tokio::signal;
 use tower::ServiceBuilder;
 use tower_http::{timeout::TimeoutLayer, trace::TraceLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+#[derive(Parser)]
+#[command(name = "lectara-service")]
+#[command(about = "Lectara web service for collecting internet content")]
+struct Cli {
+    /// TOML file of environment variables, e.g. `LECTARA_SYNC_URL = "..."`,
+    /// applied where the environment doesn't set them
+    #[arg(long, global = true, env = "LECTARA_CONFIG", value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// SQLite database file
+    #[arg(long, global = true, env = "DATABASE_URL")]
+    database_url: Option<String>,
+    /// Address the server listens on
+    #[arg(
+        long,
+        global = true,
+        env = "LECTARA_BIND",
+        default_value = "0.0.0.0:3000"
+    )]
+    bind: String,
+    /// Log lines as readable text or as JSON objects; RUST_LOG filters them
+    #[arg(
+        long,
+        global = true,
+        env = "LECTARA_LOG_FORMAT",
+        value_enum,
+        default_value_t = LogFormat::Text
+    )]
+    log_format: LogFormat,
+    /// Apply pending migrations before serving; with `false`, `/ready`
+    /// answers 503 until `migrate up` has applied them
+    #[arg(
+        long,
+        global = true,
+        env = "LECTARA_MIGRATE",
+        value_name = "BOOL",
+        value_parser = BoolishValueParser::new(),
+        num_args = 0..=1,
+        default_value_t = true,
+        default_missing_value = "true"
+    )]
+    migrate: bool,
 
-#[tokio::main]
-async fn main() {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("lectara_service=debug".parse().unwrap()),
-        )
-        .init();
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the HTTP server (the default)
+    Serve {
+        /// Serve generated content from an in-memory database instead of
+        /// DATABASE_URL; nothing is kept after exit
+        #[arg(long)]
+        demo: bool,
+    },
+    /// Fill the database with generated content for development
+    Seed {
+        /// Number of content items to generate
+        #[arg(long, default_value_t = 1000)]
+        items: usize,
+        /// Random seed, for reproducible data sets
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Inspect or change the database schema version
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateAction {
+    /// List applied and pending migrations
+    Status,
+    /// Apply all pending migrations
+    Up,
+    /// Revert the most recently applied migrations
+    Down {
+        /// Number of migrations to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+/// SQLite URL of the throwaway database `serve --demo` runs on
+const DEMO_DATABASE_URL: &str = ":memory:";
+/// Number of generated items the demo database starts with
+const DEMO_ITEMS: usize = 200;
+
+fn main() {
+    let mut cli = Cli::parse();
+    let environment = Reloader::capture_environment();
+    let settings = cli.config.take();
+    if let Some(path) = &settings {
+        apply_settings(path);
+        // Again, for flags falling back to variables from the file
+        cli = Cli::parse();
+    }
+    let reloader = Reloader::new(settings, environment);
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start the Tokio runtime")
+        .block_on(run(cli, reloader));
+}
+
+/// Sets the variables of a settings file that the environment doesn't. Runs
+/// before the runtime starts, since changing the environment isn't safe
+/// while other threads may read it.
+fn apply_settings(path: &Path) {
+    let settings = Settings::load(path).unwrap_or_else(|err| {
+        eprintln!("Failed to load settings: {err}");
+        std::process::exit(1);
+    });
+    let unset: Vec<_> = settings.unset().collect();
+    for (name, value) in unset {
+        // SAFETY: no other thread has been started yet
+        unsafe { std::env::set_var(name, value) };
+    }
+}
 
-    let database_url =
-        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable must be set");
+async fn run(cli: Cli, reloader: Reloader) {
+    init_logging(cli.log_format, &reloader);
+
+    let command = cli.command.unwrap_or(Command::Serve { demo: false });
+    let database_url = match (&command, cli.database_url) {
+        (Command::Serve { demo: true }, _) => DEMO_DATABASE_URL.to_string(),
+        (_, Some(database_url)) => database_url,
+        (_, None) => {
+            error!("No database given; pass --database-url or set DATABASE_URL");
+            std::process::exit(1);
+        }
+    };
 
     let mut connection = SqliteConnection::establish(&database_url).unwrap_or_else(|err| {
         error!(database_url = %database_url, error = %err, "Failed to connect to database");
@@ -37,7 +195,69 @@ async fn main() {
 
     info!(database_url = %database_url, "Connected to database");
 
-    // Check for and run pending migrations
+    match command {
+        Command::Serve { demo } => {
+            // The demo database starts out empty
+            let migrate = cli.migrate || demo;
+            if migrate {
+                run_migrations(&mut connection);
+            } else {
+                warn!(
+                    "Not applying pending migrations; /ready reports any until `migrate up` does"
+                );
+            }
+            if demo {
+                warn!("Running in demo mode, changes are lost on exit");
+                seed_database(&mut connection, DEMO_ITEMS, Some(0));
+            }
+            serve(connection, database_url, &cli.bind, migrate, reloader).await
+        }
+        Command::Seed { items, seed } => {
+            run_migrations(&mut connection);
+            seed_database(&mut connection, items, seed)
+        }
+        Command::Migrate { action } => migrate(connection, action),
+    }
+}
+
+/// Installs the log subscriber, swapping in each filter the reloader
+/// publishes
+fn init_logging(format: LogFormat, reloader: &Reloader) {
+    use tracing_subscriber::{EnvFilter, fmt, prelude::*, reload::Layer};
+
+    // Invalid directives at startup are skipped, as they always were
+    let filter =
+        EnvFilter::from_default_env().add_directive("lectara_service=debug".parse().unwrap());
+    let (filter, handle) = Layer::new(filter);
+    let (text, json) = match format {
+        LogFormat::Text => (Some(fmt::layer()), None),
+        LogFormat::Json => (None, Some(fmt::layer().json())),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(text)
+        .with(json)
+        .init();
+
+    let mut log_filter = reloader.log_filter();
+    tokio::spawn(async move {
+        while log_filter.changed().await.is_ok() {
+            let directives = log_filter.borrow_and_update().clone();
+            match reload::parse_log_filter(&directives) {
+                Ok(filter) => {
+                    if let Err(err) = handle.reload(filter) {
+                        error!(error = %err, "Failed to apply the log filter");
+                    }
+                }
+                Err(err) => error!(error = %err, "Failed to apply the log filter"),
+            }
+        }
+    });
+}
+
+/// Applies pending migrations, then moves any bodies left in the legacy
+/// table into blob storage
+fn run_migrations(connection: &mut SqliteConnection) {
     match connection.has_pending_migration(MIGRATIONS) {
         Ok(has_pending) => {
             if has_pending {
@@ -59,28 +279,286 @@ async fn main() {
         }
     }
 
-    let app_state = DefaultAppState::new(Arc::new(Mutex::new(connection)));
+    match bodies::migrate_legacy_bodies(connection) {
+        Ok(0) => {}
+        Ok(moved) => info!(moved, "Moved legacy content bodies into blob storage"),
+        Err(err) => {
+            error!(error = %err, "Failed to move legacy content bodies");
+            std::process::exit(1);
+        }
+    }
+
+    match bodies::fill_plain_text(connection) {
+        Ok(0) => {}
+        Ok(filled) => info!(filled, "Extracted plain text of HTML bodies"),
+        Err(err) => {
+            error!(error = %err, "Failed to extract plain text of bodies");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn migrate(mut connection: SqliteConnection, action: MigrateAction) {
+    match action {
+        MigrateAction::Status => match migrations::migration_status(&mut connection) {
+            Ok(status) => {
+                for name in &status.applied {
+                    println!("applied  {name}");
+                }
+                for name in &status.pending {
+                    println!("pending  {name}");
+                }
+            }
+            Err(err) => {
+                error!(error = %err, "Failed to read migration status");
+                std::process::exit(1);
+            }
+        },
+        MigrateAction::Up => run_migrations(&mut connection),
+        MigrateAction::Down { steps } => {
+            for _ in 0..steps {
+                match connection.revert_last_migration(MIGRATIONS) {
+                    Ok(version) => info!(%version, "Reverted migration"),
+                    Err(err) => {
+                        error!(error = %err, "Failed to revert migration");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn seed_database(connection: &mut SqliteConnection, items: usize, seed: Option<u64>) {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    info!(items, seed, "Seeding database with generated content");
+
+    match seed::seed_content(connection, &mut rng, items) {
+        Ok(inserted) => info!(inserted, "Seeding completed successfully"),
+        Err(err) => {
+            error!(error = %err, "Failed to seed database");
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn serve(
+    connection: SqliteConnection,
+    database_url: String,
+    bind: &str,
+    migrate: bool,
+    reloader: Reloader,
+) {
+    let validation = ValidationContext::from_env();
+    let credentials = SiteCredentials::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Failed to load fetch credentials");
+        std::process::exit(1);
+    });
+    if !credentials.is_empty() {
+        info!("Loaded per-site fetch credentials");
+    }
+    let reloader = reloader.with_credentials(credentials);
+    let fetcher = Fetcher::new(validation.clone()).with_credential_updates(reloader.credentials());
+    let hooks = Hooks::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Failed to load hooks");
+        std::process::exit(1);
+    });
+    if !hooks.is_empty() {
+        info!("Loaded external hooks");
+    }
+    let enrichers = EnricherRegistry::from_env()
+        .unwrap_or_else(|err| {
+            error!(error = %err, "Invalid enricher configuration");
+            std::process::exit(1);
+        })
+        .with_hooks(hooks);
+    #[cfg(feature = "wasm-plugins")]
+    let enrichers = {
+        use lectara_service::enrichment::plugins;
+
+        let plugins = plugins::load_from_env().unwrap_or_else(|err| {
+            error!(error = %err, "Failed to load plugins");
+            std::process::exit(1);
+        });
+        if !plugins.is_empty() {
+            info!(count = plugins.len(), "Loaded WebAssembly plugins");
+        }
+        plugins.into_iter().fold(enrichers, EnricherRegistry::with)
+    };
+    let summarizer = Summarizer::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Invalid summarizer configuration");
+        std::process::exit(1);
+    });
+    let site_rules = SiteRules::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Failed to load site rules");
+        std::process::exit(1);
+    });
+    let device_mailer = DeviceMailer::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Invalid device delivery configuration");
+        std::process::exit(1);
+    });
+    let sync_target = SyncTarget::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Invalid sync configuration");
+        std::process::exit(1);
+    });
+    #[cfg(feature = "activitypub")]
+    let activitypub = lectara_service::activitypub::ActivityPub::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Invalid ActivityPub configuration");
+        std::process::exit(1);
+    });
+
+    let read_only = ReadOnlyMode::from_env();
+    if let Some(status) = read_only.status() {
+        warn!(reason = %status.reason, "Starting in read-only mode");
+    }
+
+    let tenants = TenantRegistry::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Failed to load tenant registry");
+        std::process::exit(1);
+    });
+    let access = AccessPolicy::from_env().unwrap_or_else(|err| {
+        error!(error = %err, "Invalid API key configuration");
+        std::process::exit(1);
+    });
+    if access.is_public() && !access.requires_keys() {
+        warn!("Public reads are on but no API keys are configured, so writes are open too");
+    }
+
+    let maintenance = MaintenanceSchedule::default();
+    let metrics = RequestMetrics::from_env();
+
+    let db = open_database(connection, &database_url);
+    let app_state = DefaultAppState::new(db.clone())
+        .with_query_cache(QueryCache::from_env())
+        .with_pdf_extractor(PdfExtractor::from_env(fetcher.clone()))
+        .with_fetcher(fetcher)
+        .with_validation(validation)
+        .with_citation_resolver(CitationResolver::from_env())
+        .with_thread_resolver(ThreadResolver::from_env())
+        .with_github_resolver(GithubResolver::from_env())
+        .with_summarizer(summarizer)
+        .with_site_rules(site_rules)
+        .with_device_mailer(device_mailer)
+        .with_sync_target(sync_target)
+        .with_enrichers(enrichers)
+        .with_read_only(read_only)
+        .with_maintenance(maintenance.clone())
+        .with_reloader(reloader.clone())
+        .with_metrics(metrics.clone());
+    #[cfg(feature = "activitypub")]
+    let app_state = {
+        if let Some(activitypub) = &activitypub {
+            info!(handle = %activitypub.handle(), "Publishing saves over ActivityPub");
+        }
+        app_state.with_activitypub(activitypub)
+    };
+    // The watchdog is only pinged while all of these answer
+    let mut databases = vec![db.clone()];
+    spawn_background_tasks(&app_state, db, database_url);
     let shutdown_state = ShutdownState::new();
 
-    let app = create_router()
-        .layer(
+    let with_middleware = |router: Router| {
+        router.layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
-                .layer(GracefulShutdownLayer::new(shutdown_state.clone()))
+                .layer(MetricsLayer::new(metrics.clone()))
+                .layer(
+                    GracefulShutdownLayer::new(shutdown_state.clone())
+                        .with_maintenance(maintenance.clone()),
+                )
                 .layer(TimeoutLayer::new(Duration::from_secs(15))),
         )
-        .with_state(app_state);
+    };
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
+    // Streams are ended at shutdown, or their clients would hold it up
+    let mut notifiers = vec![app_state.notifier().clone()];
+    let app = match tenants {
+        None => with_middleware(access.protect(create_router()).with_state(app_state)),
+        Some(registry) => {
+            let mut tenant_states = Vec::new();
+            for tenant in registry.tenants() {
+                let database_url = tenant.database.display().to_string();
+                let mut connection = SqliteConnection::establish(&database_url)
+                    .unwrap_or_else(|err| {
+                        error!(tenant = %tenant.name, database_url = %database_url, error = %err, "Failed to connect to tenant database");
+                        std::process::exit(1);
+                    });
+                if migrate {
+                    run_migrations(&mut connection);
+                }
+
+                let db = open_database(connection, &database_url);
+                let state = app_state
+                    .clone()
+                    .with_database(db.clone())
+                    .with_query_cache(QueryCache::from_env());
+                tenant_states.push((tenant, state, db, database_url));
+            }
+
+            let activity = ActivityFeed::new(
+                tenant_states
+                    .iter()
+                    .filter(|(tenant, ..)| tenant.share_activity)
+                    .map(|(tenant, state, ..)| (tenant.name.clone(), state.content_repo()))
+                    .collect(),
+            );
+
+            let mut routers = HashMap::new();
+            for (tenant, state, db, database_url) in tenant_states {
+                let state = state.with_activity(
+                    tenant
+                        .share_activity
+                        .then(|| activity.viewed_by(&tenant.name)),
+                );
+                notifiers.push(state.notifier().clone());
+                databases.push(db.clone());
+                spawn_background_tasks(&state, db, database_url);
+                routers.insert(
+                    tenant.name.clone(),
+                    with_middleware(
+                        tenant
+                            .access_policy()
+                            .protect(create_tenant_router())
+                            .with_state(state),
+                    ),
+                );
+            }
+            info!(tenants = routers.len(), "Serving in multi-tenant mode");
+
+            if !access.requires_keys() {
+                error!("No API keys are configured for the instance, so its admin API is off");
+            }
+            let instance = with_middleware(create_instance_router(access).with_state(app_state));
+            create_multi_tenant_router(registry, routers, instance)
+        }
+    };
+
+    let listener = tokio::net::TcpListener::bind(bind)
         .await
         .unwrap_or_else(|err| {
-            error!(bind_address = "0.0.0.0:3000", error = %err, "Failed to bind to address");
+            error!(bind_address = bind, error = %err, "Failed to bind to address");
             std::process::exit(1);
         });
 
-    info!("Server running on http://localhost:3000");
+    match listener.local_addr() {
+        Ok(address) => {
+            info!(%address, "Server running on http://{address}");
+            systemd::notify_ready(&format!("Serving on {address}"));
+        }
+        Err(_) => {
+            info!(bind_address = bind, "Server running");
+            systemd::notify_ready("Serving");
+        }
+    }
+    systemd::spawn_watchdog(databases);
+    spawn_reload_on_hangup(reloader);
 
-    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown_signal(shutdown_state));
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(shutdown_state, notifiers));
 
     if let Err(err) = server.await {
         error!(error = %err, "Server error");
@@ -88,7 +566,47 @@ async fn main() {
     }
 }
 
-async fn shutdown_signal(shutdown_state: ShutdownState) {
+/// The handle the repositories share, reading through a pool of read-only
+/// connections unless the database lives in memory
+fn open_database(connection: SqliteConnection, database_url: &str) -> Database {
+    let database = Database::new(Arc::new(Mutex::new(connection)));
+    let size = ReadPool::size_from_env();
+    if size == 0 || database_url == DEMO_DATABASE_URL {
+        return database;
+    }
+    match ReadPool::open(database_url, size) {
+        Ok(readers) => {
+            info!(connections = readers.size(), "Opened read connections");
+            database.with_readers(readers)
+        }
+        Err(err) => {
+            error!(database_url = %database_url, error = %err, "Failed to open read connections");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Starts the fetch retry worker and connection health checks for one
+/// database
+fn spawn_background_tasks(state: &DefaultAppState, db: Database, database_url: String) {
+    attempts::spawn_retry_worker(state.clone(), attempts::RETRY_INTERVAL);
+    let content_repo = state.content_repo();
+    connection::spawn_health_checks(
+        ConnectionMonitor::new(db.connection().clone(), database_url),
+        connection::HEALTH_CHECK_INTERVAL,
+        move || {
+            content_repo.invalidate_caches();
+            db.mark_changed();
+            if let Some(readers) = db.readers()
+                && let Err(err) = readers.reconnect()
+            {
+                warn!(error = %err, "Failed to reopen read connections, reads may see the old database");
+            }
+        },
+    );
+}
+
+async fn shutdown_signal(shutdown_state: ShutdownState, notifiers: Vec<Notifier>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -112,9 +630,41 @@ async fn shutdown_signal(shutdown_state: ShutdownState) {
     }
 
     info!("Shutdown signal received, starting graceful shutdown");
+    systemd::notify_stopping();
     let shutdown_completed = shutdown_state.completed();
     shutdown_state.start_shutdown();
+    for notifier in &notifiers {
+        notifier.close();
+    }
 
     shutdown_completed.await;
     info!("Graceful shutdown completed - all requests finished");
 }
+
+/// Reloads settings on each SIGHUP, as `POST /api/v1/admin/reload` does
+#[cfg(unix)]
+fn spawn_reload_on_hangup(reloader: Reloader) {
+    let mut hangups = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(err) => {
+            warn!(error = %err, "Failed to install SIGHUP handler, settings reload only through the admin API");
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("SIGHUP received, reloading settings");
+            match reloader.reload() {
+                Ok(reloaded) => info!(
+                    log_filter = %reloaded.log_filter,
+                    credential_sites = reloaded.credential_sites,
+                    "Settings reloaded"
+                ),
+                Err(err) => error!(error = %err, "Reload failed, keeping the current settings"),
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_reload_on_hangup(_reloader: Reloader) {}
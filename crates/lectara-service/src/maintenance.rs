@@ -0,0 +1,143 @@
+//! Scheduled maintenance windows.
+//!
+//! Once a window is scheduled, every response carries a `Warning` header
+//! announcing it and web pages show a banner, so clients can plan around
+//! it. When the window starts the graceful shutdown layer drains the
+//! service: requests already running finish, new ones get 503 with
+//! `Retry-After` until the window ends. Admin endpoints and `/health` stay
+//! reachable throughout so the window can be moved or cancelled.
+
+use std::sync::{Arc, RwLock};
+
+use chrono::NaiveDateTime;
+use http::HeaderValue;
+use serde::Serialize;
+
+/// Path prefixes still served while a window drains the service
+const DRAIN_EXEMPT_PREFIXES: &[&str] = &["/api/v1/admin/", "/health"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaintenanceWindow {
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub message: Option<String>,
+}
+
+impl MaintenanceWindow {
+    /// Whether the window has started and not yet ended at `now`
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// Human readable description used by the header and the banner
+    pub fn notice(&self) -> String {
+        let mut notice = format!(
+            "Scheduled maintenance from {} to {} UTC",
+            self.starts_at.format("%Y-%m-%d %H:%M"),
+            self.ends_at.format("%Y-%m-%d %H:%M"),
+        );
+        if let Some(message) = &self.message {
+            notice.push_str(": ");
+            notice.push_str(message);
+        }
+        notice
+    }
+
+    /// `Warning` header value (RFC 7234 code 199) announcing the window
+    pub fn warning_header(&self) -> HeaderValue {
+        let text: String = self
+            .notice()
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect::<String>()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        HeaderValue::from_bytes(format!("199 lectara \"{text}\"").as_bytes())
+            .expect("control characters were replaced")
+    }
+}
+
+/// Shared maintenance schedule; clones see the same window
+#[derive(Clone, Default)]
+pub struct MaintenanceSchedule {
+    window: Arc<RwLock<Option<MaintenanceWindow>>>,
+}
+
+impl MaintenanceSchedule {
+    /// Replaces any scheduled window
+    pub fn schedule(&self, window: MaintenanceWindow) {
+        *self.window.write().unwrap() = Some(window);
+    }
+
+    pub fn cancel(&self) {
+        *self.window.write().unwrap() = None;
+    }
+
+    /// The scheduled window, unless it has already ended at `now`
+    pub fn current(&self, now: NaiveDateTime) -> Option<MaintenanceWindow> {
+        self.window
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|window| now < window.ends_at)
+            .cloned()
+    }
+
+    /// The window, if the service should be drained at `now`
+    pub fn active(&self, now: NaiveDateTime) -> Option<MaintenanceWindow> {
+        self.current(now).filter(|window| window.is_active(now))
+    }
+}
+
+/// Whether requests to `path` are still served during an active window
+pub fn is_drain_exempt(path: &str) -> bool {
+    DRAIN_EXEMPT_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeDelta};
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 8, 9)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_window_lifecycle() {
+        let schedule = MaintenanceSchedule::default();
+        schedule.schedule(MaintenanceWindow {
+            starts_at: at(2),
+            ends_at: at(3),
+            message: None,
+        });
+
+        assert!(schedule.current(at(1)).is_some());
+        assert!(schedule.active(at(1)).is_none());
+        assert!(schedule.active(at(2)).is_some());
+        assert!(schedule.active(at(3) - TimeDelta::seconds(1)).is_some());
+        assert!(schedule.current(at(3)).is_none());
+
+        schedule.cancel();
+        assert!(schedule.current(at(1)).is_none());
+    }
+
+    #[test]
+    fn test_warning_header_quotes_message() {
+        let window = MaintenanceWindow {
+            starts_at: at(2),
+            ends_at: at(3),
+            message: Some("Moving to a \"bigger\" disk\n".to_string()),
+        };
+        assert_eq!(
+            window.warning_header(),
+            "199 lectara \"Scheduled maintenance from 2025-08-09 02:00 to \
+             2025-08-09 03:00 UTC: Moving to a \\\"bigger\\\" disk \""
+        );
+    }
+}
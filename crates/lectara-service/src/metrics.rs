@@ -0,0 +1,375 @@
+//! In-process request metrics for deployments without a metrics stack.
+//!
+//! [`MetricsLayer`] records the latency and status of every request under
+//! its route pattern, keeping only the samples inside a sliding window.
+//! [`RequestMetrics::report`] summarizes them per endpoint as p50/p95
+//! latencies and error rates, flagging endpoints over the configured alert
+//! thresholds, alongside the use of the [`limits`](crate::limits) on
+//! expensive routes.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use axum::extract::MatchedPath;
+use http::{Request, Response};
+use pin_project::pin_project;
+use serde::Serialize;
+use tower::{Layer, Service};
+
+use crate::limits::{self, ConcurrencyReport};
+
+/// Length of the sliding window, in seconds (default 300)
+pub const METRICS_WINDOW_ENV: &str = "LECTARA_METRICS_WINDOW_SECONDS";
+/// p95 latency, in milliseconds, above which an endpoint alerts (default 1000)
+pub const ALERT_P95_ENV: &str = "LECTARA_ALERT_P95_MS";
+/// Share of 5xx responses above which an endpoint alerts (default 0.05)
+pub const ALERT_ERROR_RATE_ENV: &str = "LECTARA_ALERT_ERROR_RATE";
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(300);
+/// Oldest samples are dropped beyond this, bounding memory per endpoint
+const MAX_SAMPLES_PER_ENDPOINT: usize = 10_000;
+/// Endpoints with fewer requests in the window never alert
+const MIN_SAMPLES_FOR_ALERTS: usize = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AlertThresholds {
+    pub p95_latency: Duration,
+    pub error_rate: f64,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self {
+            p95_latency: Duration::from_millis(1000),
+            error_rate: 0.05,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    latency: Duration,
+    status: u16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointReport {
+    pub endpoint: String,
+    pub requests: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    /// Share of 5xx responses
+    pub error_rate: f64,
+    /// Share of 4xx responses
+    pub client_error_rate: f64,
+    /// Thresholds exceeded: `p95_latency` and/or `error_rate`
+    pub alerts: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// `ok`, or `alerting` when any endpoint exceeds a threshold
+    pub status: &'static str,
+    pub window_seconds: u64,
+    pub endpoints: Vec<EndpointReport>,
+    /// Slots in use on each group of expensive routes
+    pub concurrency: Vec<ConcurrencyReport>,
+}
+
+/// Shared store of recent request samples; clones see the same samples
+#[derive(Clone)]
+pub struct RequestMetrics {
+    samples: Arc<Mutex<HashMap<String, VecDeque<Sample>>>>,
+    window: Duration,
+    thresholds: AlertThresholds,
+}
+
+impl Default for RequestMetrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW, AlertThresholds::default())
+    }
+}
+
+impl RequestMetrics {
+    pub fn new(window: Duration, thresholds: AlertThresholds) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(HashMap::new())),
+            window,
+            thresholds,
+        }
+    }
+
+    /// Reads the window and thresholds from the environment, falling back
+    /// to the defaults for unset or invalid values
+    pub fn from_env() -> Self {
+        fn parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+
+        let defaults = AlertThresholds::default();
+        let window = parse::<u64>(METRICS_WINDOW_ENV)
+            .filter(|seconds| *seconds > 0)
+            .map_or(DEFAULT_WINDOW, Duration::from_secs);
+        let thresholds = AlertThresholds {
+            p95_latency: parse::<u64>(ALERT_P95_ENV)
+                .map_or(defaults.p95_latency, Duration::from_millis),
+            error_rate: parse::<f64>(ALERT_ERROR_RATE_ENV)
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .unwrap_or(defaults.error_rate),
+        };
+        Self::new(window, thresholds)
+    }
+
+    pub fn record(&self, endpoint: &str, latency: Duration, status: u16) {
+        self.record_at(endpoint, Instant::now(), latency, status);
+    }
+
+    fn record_at(&self, endpoint: &str, at: Instant, latency: Duration, status: u16) {
+        let mut samples = self.samples.lock().unwrap();
+        let endpoint_samples = match samples.get_mut(endpoint) {
+            Some(endpoint_samples) => endpoint_samples,
+            None => samples.entry(endpoint.to_string()).or_default(),
+        };
+        if endpoint_samples.len() == MAX_SAMPLES_PER_ENDPOINT {
+            endpoint_samples.pop_front();
+        }
+        endpoint_samples.push_back(Sample {
+            at,
+            latency,
+            status,
+        });
+    }
+
+    /// Summarizes the samples inside the window ending now
+    pub fn report(&self) -> HealthReport {
+        self.report_at(Instant::now())
+    }
+
+    fn report_at(&self, now: Instant) -> HealthReport {
+        let mut samples = self.samples.lock().unwrap();
+
+        // Expired samples are dropped here rather than on every request
+        samples.retain(|_, endpoint_samples| {
+            while endpoint_samples
+                .front()
+                .is_some_and(|sample| now.saturating_duration_since(sample.at) > self.window)
+            {
+                endpoint_samples.pop_front();
+            }
+            !endpoint_samples.is_empty()
+        });
+
+        let mut endpoints: Vec<EndpointReport> = samples
+            .iter()
+            .map(|(endpoint, endpoint_samples)| self.summarize(endpoint, endpoint_samples))
+            .collect();
+        endpoints.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+
+        let alerting = endpoints.iter().any(|endpoint| !endpoint.alerts.is_empty());
+        HealthReport {
+            status: if alerting { "alerting" } else { "ok" },
+            window_seconds: self.window.as_secs(),
+            endpoints,
+            concurrency: limits::report(),
+        }
+    }
+
+    fn summarize(&self, endpoint: &str, samples: &VecDeque<Sample>) -> EndpointReport {
+        let mut latencies: Vec<Duration> = samples.iter().map(|sample| sample.latency).collect();
+        latencies.sort();
+        let requests = samples.len();
+        let share = |matches: fn(u16) -> bool| {
+            samples
+                .iter()
+                .filter(|sample| matches(sample.status))
+                .count() as f64
+                / requests as f64
+        };
+
+        let p95 = percentile(&latencies, 95);
+        let error_rate = share(|status| status >= 500);
+        let mut alerts = Vec::new();
+        if requests >= MIN_SAMPLES_FOR_ALERTS {
+            if p95 > self.thresholds.p95_latency {
+                alerts.push("p95_latency");
+            }
+            if error_rate > self.thresholds.error_rate {
+                alerts.push("error_rate");
+            }
+        }
+
+        EndpointReport {
+            endpoint: endpoint.to_string(),
+            requests,
+            p50_ms: as_millis(percentile(&latencies, 50)),
+            p95_ms: as_millis(p95),
+            error_rate,
+            client_error_rate: share(|status| (400..500).contains(&status)),
+            alerts,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`
+fn percentile(values: &[Duration], percent: usize) -> Duration {
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    values[rank - 1]
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0 * 100.0).round() / 100.0
+}
+
+/// Tower layer recording every request into [`RequestMetrics`]. It must
+/// wrap the router's routes (`Router::layer`) to see their patterns.
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: RequestMetrics,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: RequestMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: RequestMetrics,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = MetricsFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // Route patterns rather than paths, so `/content/1` and
+        // `/content/2` are one endpoint
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or("unmatched", |path| path.as_str());
+        let endpoint = format!("{} {route}", req.method());
+
+        MetricsFuture {
+            inner: self.inner.call(req),
+            metrics: self.metrics.clone(),
+            endpoint,
+            started: Instant::now(),
+        }
+    }
+}
+
+#[pin_project]
+pub struct MetricsFuture<F> {
+    #[pin]
+    inner: F,
+    metrics: RequestMetrics,
+    endpoint: String,
+    started: Instant,
+}
+
+impl<F, B, E> Future for MetricsFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = std::task::ready!(this.inner.poll(cx));
+        // Errors never reach clients as responses here; count them as 500s
+        let status = result
+            .as_ref()
+            .map_or(500, |response| response.status().as_u16());
+        this.metrics
+            .record(this.endpoint, this.started.elapsed(), status);
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_percentiles_and_error_rates() {
+        let metrics = RequestMetrics::new(Duration::from_secs(60), AlertThresholds::default());
+        let start = Instant::now();
+        for ms in 1..=100 {
+            let status = match ms {
+                1..=3 => 500,
+                4..=13 => 404,
+                _ => 200,
+            };
+            metrics.record_at("GET /items", start, millis(ms), status);
+        }
+
+        let report = metrics.report_at(start);
+        assert_eq!(report.status, "ok");
+        let endpoint = &report.endpoints[0];
+        assert_eq!(endpoint.requests, 100);
+        assert_eq!(endpoint.p50_ms, 50.0);
+        assert_eq!(endpoint.p95_ms, 95.0);
+        assert_eq!(endpoint.error_rate, 0.03);
+        assert_eq!(endpoint.client_error_rate, 0.1);
+        assert!(endpoint.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_alerts_and_sliding_window() {
+        let thresholds = AlertThresholds {
+            p95_latency: millis(100),
+            error_rate: 0.1,
+        };
+        let metrics = RequestMetrics::new(Duration::from_secs(60), thresholds);
+        let start = Instant::now();
+        for _ in 0..MIN_SAMPLES_FOR_ALERTS {
+            metrics.record_at("POST /slow", start, millis(500), 503);
+        }
+        // Too few requests to alert on
+        metrics.record_at("GET /rare", start, millis(500), 500);
+
+        let report = metrics.report_at(start);
+        assert_eq!(report.status, "alerting");
+        assert_eq!(report.endpoints[0].endpoint, "GET /rare");
+        assert!(report.endpoints[0].alerts.is_empty());
+        assert_eq!(report.endpoints[1].alerts, ["p95_latency", "error_rate"]);
+
+        let later = start + Duration::from_secs(61);
+        metrics.record_at("GET /rare", later, millis(5), 200);
+        let report = metrics.report_at(later);
+        assert_eq!(report.status, "ok");
+        assert_eq!(report.endpoints.len(), 1);
+        assert_eq!(report.endpoints[0].requests, 1);
+    }
+}
@@ -0,0 +1,49 @@
+//! Embedded schema migrations and a summary of which have been applied.
+
+use diesel::migration::MigrationSource;
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use serde::Serialize;
+use std::collections::HashSet;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+/// Error type returned by diesel's migration harness
+pub type MigrationError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Embedded migrations split by whether the database has applied them,
+/// each listed by directory name in order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MigrationStatus {
+    pub applied: Vec<String>,
+    pub pending: Vec<String>,
+}
+
+/// Version of the newest embedded migration, the schema this binary expects
+pub fn schema_version() -> Option<String> {
+    MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .ok()?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .max()
+}
+
+pub fn migration_status(conn: &mut SqliteConnection) -> Result<MigrationStatus, MigrationError> {
+    let applied: HashSet<String> = conn
+        .applied_migrations()?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect();
+
+    let mut status = MigrationStatus::default();
+    for migration in MigrationSource::<Sqlite>::migrations(&MIGRATIONS)? {
+        let name = migration.name();
+        if applied.contains(&name.version().to_string()) {
+            status.applied.push(name.to_string());
+        } else {
+            status.pending.push(name.to_string());
+        }
+    }
+
+    Ok(status)
+}
@@ -1,26 +1,219 @@
-use crate::validation::normalize_url;
+use crate::validation::{
+    MetadataComparison, NormalizedUrl, ValidationContext, check_metadata, clean_client_info,
+    normalize_url_with, parse_source,
+};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
-#[diesel(table_name = crate::schema::content_items)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+/// A content item with its body, as returned for single-item lookups
+#[derive(Debug, Clone, Serialize)]
 pub struct ContentItem {
     pub id: i32,
     pub url: String,
     pub title: Option<String>,
     pub author: Option<String>,
     pub created_at: chrono::NaiveDateTime,
+    pub body_hash: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub duration_seconds: Option<i32>,
+    /// JSON array of the [`MetadataField`]s filled in by enrichment
+    #[serde(skip)]
+    pub enriched_fields: String,
+    pub content_type: Option<String>,
+    /// JSON object of extra metadata found by enrichment
+    #[serde(skip)]
+    pub metadata: String,
+    pub summary: Option<String>,
+    /// Place among the pinned items, or `None` if the item isn't pinned
+    pub pinned_position: Option<i32>,
+    pub read_at: Option<chrono::NaiveDateTime>,
+    pub archived_at: Option<chrono::NaiveDateTime>,
+    /// How the item was saved, see [`crate::validation::parse_source`]
+    pub source: Option<String>,
+    /// `User-Agent` of the client that saved the item
+    pub user_agent: Option<String>,
+    /// Version the saving client reported about itself
+    pub client_version: Option<String>,
     pub body: Option<String>,
+    /// Plain text of an HTML body; `None` when the body is plain already
+    #[serde(skip)]
+    pub text: Option<String>,
+}
+
+impl ContentItem {
+    pub fn from_parts(summary: ContentItemSummary, body: Option<String>) -> Self {
+        ContentItem {
+            id: summary.id,
+            url: summary.url,
+            title: summary.title,
+            author: summary.author,
+            created_at: summary.created_at,
+            body_hash: summary.body_hash,
+            enclosure_url: summary.enclosure_url,
+            duration_seconds: summary.duration_seconds,
+            enriched_fields: summary.enriched_fields,
+            content_type: summary.content_type,
+            metadata: summary.metadata,
+            summary: summary.summary,
+            pinned_position: summary.pinned_position,
+            read_at: summary.read_at,
+            archived_at: summary.archived_at,
+            source: summary.source,
+            user_agent: summary.user_agent,
+            client_version: summary.client_version,
+            body,
+            text: None,
+        }
+    }
+
+    /// Sets the stored plain text of the item's body
+    pub fn with_text(mut self, text: Option<String>) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// The body without markup, for indexing, counting and display
+    pub fn plain_text(&self) -> Option<&str> {
+        self.text.as_deref().or(self.body.as_deref())
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.plain_text()
+            .map_or(0, |text| text.split_whitespace().count())
+    }
+
+    /// Drops the body, keeping only the `content_items` columns
+    pub fn into_summary(self) -> ContentItemSummary {
+        ContentItemSummary {
+            id: self.id,
+            url: self.url,
+            title: self.title,
+            author: self.author,
+            created_at: self.created_at,
+            body_hash: self.body_hash,
+            enclosure_url: self.enclosure_url,
+            duration_seconds: self.duration_seconds,
+            enriched_fields: self.enriched_fields,
+            content_type: self.content_type,
+            metadata: self.metadata,
+            summary: self.summary,
+            pinned_position: self.pinned_position,
+            read_at: self.read_at,
+            archived_at: self.archived_at,
+            source: self.source,
+            user_agent: self.user_agent,
+            client_version: self.client_version,
+        }
+    }
+
+    /// Fields whose stored value came from enrichment rather than the client
+    pub fn enriched_fields(&self) -> Vec<MetadataField> {
+        serde_json::from_str(&self.enriched_fields).unwrap_or_default()
+    }
+
+    pub fn metadata(&self) -> serde_json::Map<String, serde_json::Value> {
+        serde_json::from_str(&self.metadata).unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Insertable, Deserialize)]
+/// A `content_items` row on its own; bodies live in `body_blobs` and are
+/// only loaded when a full [`ContentItem`] is needed
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
 #[diesel(table_name = crate::schema::content_items)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ContentItemSummary {
+    pub id: i32,
+    pub url: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub body_hash: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub duration_seconds: Option<i32>,
+    #[serde(skip)]
+    pub enriched_fields: String,
+    pub content_type: Option<String>,
+    #[serde(skip)]
+    pub metadata: String,
+    pub summary: Option<String>,
+    pub pinned_position: Option<i32>,
+    pub read_at: Option<chrono::NaiveDateTime>,
+    pub archived_at: Option<chrono::NaiveDateTime>,
+    pub source: Option<String>,
+    pub user_agent: Option<String>,
+    pub client_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct NewContentItem {
     pub url: String,
     pub title: Option<String>,
     pub author: Option<String>,
     pub body: Option<String>,
+    /// Audio file of a podcast episode
+    #[serde(default)]
+    pub enclosure_url: Option<String>,
+    #[serde(default)]
+    pub duration_seconds: Option<i32>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub client_version: Option<String>,
+    /// When the item was saved, if earlier than now, e.g. in the tool it is
+    /// imported from
+    #[serde(skip)]
+    pub created_at: Option<chrono::NaiveDateTime>,
+    /// Rules for matching an already stored item, taken from the validation
+    /// context the item was built with
+    #[serde(skip)]
+    pub comparison: MetadataComparison,
+}
+
+/// Client-supplied metadata fields, as compared when the same URL is saved
+/// again and named in validation errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    Title,
+    Author,
+    Body,
+    Enclosure,
+}
+
+impl MetadataField {
+    /// This field's value among an item's metadata
+    fn value_of(
+        self,
+        title: &Option<String>,
+        author: &Option<String>,
+        body: &Option<String>,
+        enclosure_url: &Option<String>,
+        duration_seconds: Option<i32>,
+    ) -> serde_json::Value {
+        match self {
+            MetadataField::Title => serde_json::json!(title),
+            MetadataField::Author => serde_json::json!(author),
+            MetadataField::Body => serde_json::json!(body),
+            MetadataField::Enclosure => serde_json::json!({
+                "enclosure_url": enclosure_url,
+                "duration_seconds": duration_seconds,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for MetadataField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataField::Title => write!(f, "title"),
+            MetadataField::Author => write!(f, "author"),
+            MetadataField::Body => write!(f, "body"),
+            MetadataField::Enclosure => write!(f, "enclosure"),
+        }
+    }
 }
 
 impl NewContentItem {
@@ -30,13 +223,503 @@ impl NewContentItem {
         author: Option<String>,
         body: Option<String>,
     ) -> Result<Self, crate::validation::ValidationError> {
-        let normalized_url = normalize_url(&url)?;
+        Self::new_with_context(url, title, author, body, &ValidationContext::default())
+    }
+
+    /// Like [`NewContentItem::new`], validating the URL and metadata against
+    /// deployment rules
+    pub fn new_with_context(
+        url: String,
+        title: Option<String>,
+        author: Option<String>,
+        body: Option<String>,
+        context: &ValidationContext,
+    ) -> Result<Self, crate::validation::ValidationError> {
+        let url = NormalizedUrl::parse(&url, context)?;
+        Self::from_normalized(url, title, author, body, context)
+    }
+
+    /// Like [`NewContentItem::new_with_context`] for a URL validated already,
+    /// e.g. by [`crate::routes::extract::ValidatedUrlJson`]
+    pub fn from_normalized(
+        url: NormalizedUrl,
+        mut title: Option<String>,
+        mut author: Option<String>,
+        mut body: Option<String>,
+        context: &ValidationContext,
+    ) -> Result<Self, crate::validation::ValidationError> {
+        check_metadata(&mut title, &mut author, &mut body, &context.limits)?;
 
         Ok(NewContentItem {
-            url: normalized_url,
+            url: url.into_string(),
             title,
             author,
             body,
+            enclosure_url: None,
+            duration_seconds: None,
+            source: None,
+            user_agent: None,
+            client_version: None,
+            created_at: None,
+            comparison: context.comparison,
+        })
+    }
+
+    /// Attaches a podcast episode's audio file, validated like the item URL
+    pub fn with_enclosure(
+        mut self,
+        enclosure_url: &str,
+        duration_seconds: Option<i32>,
+        context: &ValidationContext,
+    ) -> Result<Self, crate::validation::ValidationError> {
+        self.enclosure_url = Some(normalize_url_with(enclosure_url, context)?);
+        self.duration_seconds = duration_seconds;
+        Ok(self)
+    }
+
+    /// Records how the item is being saved; the source of an item saved
+    /// again is left as first recorded
+    pub fn with_source(mut self, source: &str) -> Result<Self, crate::validation::ValidationError> {
+        self.source = Some(parse_source(source)?);
+        Ok(self)
+    }
+
+    /// Records the client saving the item, for debugging what a client
+    /// sent. Values are only cleaned and shortened, never rejected.
+    pub fn with_client(mut self, user_agent: Option<&str>, client_version: Option<&str>) -> Self {
+        self.user_agent = user_agent.and_then(clean_client_info);
+        self.client_version = client_version.and_then(clean_client_info);
+        self
+    }
+
+    /// Keeps the time the item was first saved elsewhere; an item saved
+    /// again keeps the time it was first saved here
+    pub fn with_created_at(mut self, created_at: chrono::NaiveDateTime) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Lists the metadata fields that differ from an already stored item with
+    /// the same URL. Saving again is idempotent only when this is empty.
+    ///
+    /// Text fields are compared under the item's [`MetadataComparison`]. A
+    /// field filled in by enrichment still matches when it is omitted again,
+    /// since the client never sent a value for it.
+    pub fn metadata_differences(&self, existing: &ContentItem) -> Vec<MetadataField> {
+        let enriched = existing.enriched_fields();
+        let differs = |field, new: &Option<String>, stored: &Option<String>| {
+            let case_insensitive = matches!(field, MetadataField::Title | MetadataField::Author);
+            !self
+                .comparison
+                .matches(new.as_deref(), stored.as_deref(), case_insensitive)
+                && !(new.is_none() && enriched.contains(&field))
+        };
+
+        let mut differences = Vec::new();
+        if differs(MetadataField::Title, &self.title, &existing.title) {
+            differences.push(MetadataField::Title);
+        }
+        if differs(MetadataField::Author, &self.author, &existing.author) {
+            differences.push(MetadataField::Author);
+        }
+        if differs(MetadataField::Body, &self.body, &existing.body) {
+            differences.push(MetadataField::Body);
+        }
+        if existing.enclosure_url != self.enclosure_url
+            || existing.duration_seconds != self.duration_seconds
+        {
+            differences.push(MetadataField::Enclosure);
+        }
+        differences
+    }
+
+    /// The conflict saving this again would cause, or `None` if its metadata
+    /// matches the stored item
+    pub fn conflict_with(&self, existing: ContentItem) -> Option<DuplicateUrlConflict> {
+        let differences = self
+            .metadata_differences(&existing)
+            .into_iter()
+            .map(|field| FieldDifference {
+                field,
+                stored: field.value_of(
+                    &existing.title,
+                    &existing.author,
+                    &existing.body,
+                    &existing.enclosure_url,
+                    existing.duration_seconds,
+                ),
+                submitted: field.value_of(
+                    &self.title,
+                    &self.author,
+                    &self.body,
+                    &self.enclosure_url,
+                    self.duration_seconds,
+                ),
+            })
+            .collect::<Vec<_>>();
+
+        (!differences.is_empty()).then_some(DuplicateUrlConflict {
+            existing,
+            differences,
         })
     }
 }
+
+/// A metadata field whose submitted value differs from the stored one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDifference {
+    pub field: MetadataField,
+    pub stored: serde_json::Value,
+    pub submitted: serde_json::Value,
+}
+
+/// A URL saved again with different metadata, with what is needed to offer
+/// a merge or overwrite
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateUrlConflict {
+    pub existing: ContentItem,
+    pub differences: Vec<FieldDifference>,
+}
+
+/// Metadata found by enrichment; only fields the item doesn't have yet are
+/// applied
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataPatch {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub body: Option<String>,
+    pub content_type: Option<String>,
+    pub summary: Option<String>,
+    /// Merged into the item's metadata, replacing earlier values of the
+    /// same keys
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Metadata corrected by hand: each field given replaces the item's, an
+/// empty one clearing it, and no longer counts as enriched
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct MetadataEdit {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub body: Option<String>,
+}
+
+impl MetadataEdit {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.author.is_none() && self.body.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::share_links)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ShareLink {
+    pub id: i32,
+    pub token: String,
+    pub content_id: i32,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+    /// Argon2 hash of the passphrase needed to view the item, if any
+    #[serde(skip)]
+    pub passphrase_hash: Option<String>,
+    pub failed_attempts: i32,
+    pub last_failed_at: Option<chrono::NaiveDateTime>,
+}
+
+impl ShareLink {
+    /// Whether the link can still be used to view its item at `now`
+    pub fn is_active(&self, now: chrono::NaiveDateTime) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = crate::schema::share_links)]
+pub struct NewShareLink {
+    pub token: String,
+    pub content_id: i32,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub passphrase_hash: Option<String>,
+}
+
+impl NewShareLink {
+    /// Length of generated share tokens, in alphanumeric characters
+    pub const TOKEN_LENGTH: usize = 32;
+
+    pub fn new(content_id: i32, expires_at: Option<chrono::NaiveDateTime>) -> Self {
+        use rand::Rng;
+        use rand::distr::Alphanumeric;
+
+        let token = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(Self::TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+
+        NewShareLink {
+            token,
+            content_id,
+            expires_at,
+            passphrase_hash: None,
+        }
+    }
+
+    /// Requires a passphrase, hashed with [`crate::passphrases::hash`], to
+    /// view the item
+    pub fn with_passphrase_hash(mut self, passphrase_hash: String) -> Self {
+        self.passphrase_hash = Some(passphrase_hash);
+        self
+    }
+}
+
+/// Citation metadata for an academic item, resolved from Crossref or arXiv
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::citations)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Citation {
+    pub content_id: i32,
+    pub source: String,
+    pub identifier: String,
+    pub title: Option<String>,
+    /// JSON array of author names, in publication order
+    pub authors: String,
+    pub published_on: Option<chrono::NaiveDate>,
+    pub container_title: Option<String>,
+    pub abstract_text: Option<String>,
+    pub fetched_at: chrono::NaiveDateTime,
+}
+
+impl Citation {
+    pub fn author_names(&self) -> Vec<String> {
+        serde_json::from_str(&self.authors).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::citations)]
+pub struct NewCitation {
+    pub content_id: i32,
+    pub source: String,
+    pub identifier: String,
+    pub title: Option<String>,
+    pub authors: String,
+    pub published_on: Option<chrono::NaiveDate>,
+    pub container_title: Option<String>,
+    pub abstract_text: Option<String>,
+}
+
+/// Outcome of the latest background fetch of one kind for an item
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::fetch_attempts)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FetchAttempt {
+    pub content_id: i32,
+    pub kind: String,
+    /// `succeeded`, `failed` (a retry is scheduled) or `abandoned`
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub last_attempt_at: chrono::NaiveDateTime,
+    pub next_attempt_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::fetch_attempts)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewFetchAttempt {
+    pub content_id: i32,
+    pub kind: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub last_attempt_at: chrono::NaiveDateTime,
+    pub next_attempt_at: Option<chrono::NaiveDateTime>,
+}
+
+/// The outcome of some background work, e.g. a finished import
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::notifications)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Notification {
+    pub id: i32,
+    /// What happened, e.g. `import-finished`
+    pub kind: String,
+    pub message: String,
+    /// The item the notification is about, if any
+    pub content_id: Option<i32>,
+    pub read_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = crate::schema::notifications)]
+pub struct NewNotification {
+    pub kind: String,
+    pub message: String,
+    pub content_id: Option<i32>,
+}
+
+/// A rule applied to newly saved items, see [`crate::rules`]
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::rules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Rule {
+    pub id: i32,
+    pub name: String,
+    /// Rules run in ascending position, then id
+    pub position: i32,
+    /// Host the item's URL must be on, subdomains included
+    pub domain: Option<String>,
+    /// Regular expression the item's URL must match
+    pub url_pattern: Option<String>,
+    /// `content_type` given to matching items
+    pub set_content_type: Option<String>,
+    /// Whether matching items are saved without fetching their page
+    pub skip_enrichment: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::rules)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewRule {
+    pub name: String,
+    pub position: i32,
+    pub domain: Option<String>,
+    pub url_pattern: Option<String>,
+    pub set_content_type: Option<String>,
+    pub skip_enrichment: bool,
+}
+
+/// A named combination of list filters
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::views)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct View {
+    #[serde(skip)]
+    pub id: i32,
+    pub name: String,
+    pub content_type: Option<String>,
+    pub source: Option<String>,
+    /// Lower bound on the save time as given, possibly relative like `7d`
+    pub since: Option<String>,
+    /// Upper bound on the save time, in the same forms as `since`
+    pub until: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::views)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewView {
+    pub name: String,
+    pub content_type: Option<String>,
+    pub source: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+/// A comment on an item, possibly answering another one
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = crate::schema::comments)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Comment {
+    pub id: i32,
+    pub content_id: i32,
+    /// The comment this one replies to, if any
+    pub parent_id: Option<i32>,
+    /// Name the commenter gave
+    pub author: String,
+    pub body: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = crate::schema::comments)]
+pub struct NewComment {
+    pub content_id: i32,
+    pub parent_id: Option<i32>,
+    pub author: String,
+    pub body: String,
+}
+
+/// A fediverse account following the instance's ActivityPub actor
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::followers)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Follower {
+    pub id: i32,
+    /// The follower's actor URL
+    pub actor_id: String,
+    /// Where activities for the follower are delivered
+    pub inbox_url: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::followers)]
+pub struct NewFollower {
+    pub actor_id: String,
+    pub inbox_url: String,
+}
+
+/// Hash of an item's page as last fetched by an update check
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::page_snapshots)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct PageSnapshot {
+    pub content_id: i32,
+    pub content_hash: String,
+    pub checked_at: chrono::NaiveDateTime,
+    /// When `content_hash` was first seen
+    pub changed_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::page_snapshots)]
+pub struct NewPageSnapshot {
+    pub content_id: i32,
+    pub content_hash: String,
+    pub checked_at: chrono::NaiveDateTime,
+    pub changed_at: chrono::NaiveDateTime,
+}
+
+/// A full-page capture of an item, kept as the parts of a WARC response
+/// record whose payload is stored by digest in `archive_payloads`
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::archive_snapshots)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ArchiveSnapshot {
+    pub id: i32,
+    pub content_id: i32,
+    /// `urn:uuid:` identifier of the WARC record
+    pub record_id: String,
+    /// URL the page was finally served from
+    pub target_uri: String,
+    /// Media type of the payload without parameters
+    pub content_type: String,
+    /// Status line and headers of the HTTP response, CRLF-terminated
+    pub http_headers: Vec<u8>,
+    /// Hex SHA-256 of the payload
+    pub payload_digest: String,
+    pub payload_length: i32,
+    pub captured_at: chrono::NaiveDateTime,
+    /// How the page was archived, see [`crate::archive::ArchiveMode`]
+    pub mode: String,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = crate::schema::archive_snapshots)]
+pub struct NewArchiveSnapshot {
+    pub content_id: i32,
+    pub record_id: String,
+    pub target_uri: String,
+    pub content_type: String,
+    pub http_headers: Vec<u8>,
+    pub payload_digest: String,
+    pub payload_length: i32,
+    pub captured_at: chrono::NaiveDateTime,
+    /// How the page was archived, see [`crate::archive::ArchiveMode`]
+    pub mode: String,
+}
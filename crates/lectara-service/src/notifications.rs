@@ -0,0 +1,173 @@
+//! Notifications about the outcome of background work and new comments.
+//!
+//! Work that finishes out of sight, such as an import or a fetch that is
+//! given up on, leaves a notification in the database for clients to list
+//! and mark read, and so does a comment left on an item. Each one is also published to the clients following the
+//! notification stream at the time; a client that falls too far behind
+//! misses some and should list the unread ones instead.
+//!
+//! Items being saved and changed are published the same way, to the clients
+//! following the content event stream, without being stored.
+
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, warn};
+
+use crate::AppState;
+use crate::models::{ContentItem, NewNotification, Notification};
+use crate::repositories::NotificationRepository;
+
+/// An import through the API has finished
+pub const KIND_IMPORT_FINISHED: &str = "import-finished";
+/// A background fetch failed too often to be retried automatically
+pub const KIND_FETCH_ABANDONED: &str = "fetch-abandoned";
+/// An item could not be sent to a device
+pub const KIND_SEND_FAILED: &str = "send-failed";
+/// A new item could not be pushed to the sync target
+pub const KIND_SYNC_FAILED: &str = "sync-failed";
+/// Someone commented on an item, e.g. through a share link
+pub const KIND_COMMENT_ADDED: &str = "comment-added";
+
+/// Notifications kept for each stream that is slow to read them
+const CHANNEL_CAPACITY: usize = 64;
+
+/// What happened to the item of a [`ContentEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEventKind {
+    /// Newly stored, before enrichment
+    Saved,
+    /// Changed by enrichment or an edit
+    Updated,
+}
+
+impl ContentEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Saved => "saved",
+            Self::Updated => "updated",
+        }
+    }
+}
+
+/// An item as it is after being saved or changed
+#[derive(Debug, Clone)]
+pub struct ContentEvent {
+    pub kind: ContentEventKind,
+    pub item: ContentItem,
+}
+
+/// Publishes new notifications and content events to the open streams;
+/// clones share them
+#[derive(Clone)]
+pub struct Notifier {
+    sender: broadcast::Sender<Notification>,
+    content: broadcast::Sender<ContentEvent>,
+    closed: watch::Sender<bool>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            content: broadcast::channel(CHANNEL_CAPACITY).0,
+            closed: watch::Sender::new(false),
+        }
+    }
+}
+
+impl Notifier {
+    /// Notifications published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
+        self.sender.subscribe()
+    }
+
+    /// Resolves once [`close`](Self::close) has been called
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + use<> {
+        let mut closed = self.closed.subscribe();
+        async move {
+            // An error means the notifier is gone, which ends streams too
+            let _ = closed.wait_for(|closed| *closed).await;
+        }
+    }
+
+    /// Ends the open streams, e.g. so a graceful shutdown isn't held up by
+    /// clients that never disconnect
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    pub fn publish(&self, notification: Notification) {
+        // Fails only when no stream is open, which is fine
+        let receivers = self.sender.send(notification).unwrap_or(0);
+        debug!(receivers, "Published notification");
+    }
+
+    /// Content events published from now on
+    pub fn subscribe_content(&self) -> broadcast::Receiver<ContentEvent> {
+        self.content.subscribe()
+    }
+
+    pub fn publish_content(&self, kind: ContentEventKind, item: &ContentItem) {
+        // Items are only cloned for an open stream
+        if self.content.receiver_count() == 0 {
+            return;
+        }
+        let receivers = self
+            .content
+            .send(ContentEvent {
+                kind,
+                item: item.clone(),
+            })
+            .unwrap_or(0);
+        debug!(receivers, kind = kind.as_str(), "Published content event");
+    }
+}
+
+/// Stores a notification and publishes it. Failing to store one is logged
+/// rather than failing the work it reports on.
+pub async fn notify<S: AppState>(state: &S, kind: &str, message: String, content_id: Option<i32>) {
+    let notification = NewNotification {
+        kind: kind.to_string(),
+        message,
+        content_id,
+    };
+    match state.notification_repo().create(&notification).await {
+        Ok(notification) => state.notifier().publish(notification),
+        Err(err) => warn!(error = %err, kind, "Failed to store notification"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(id: i32) -> Notification {
+        Notification {
+            id,
+            kind: KIND_IMPORT_FINISHED.to_string(),
+            message: "Imported".to_string(),
+            content_id: None,
+            read_at: None,
+            created_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publishes_to_subscribers_only() {
+        let notifier = Notifier::default();
+        notifier.publish(notification(1));
+
+        let mut receiver = notifier.clone().subscribe();
+        notifier.publish(notification(2));
+        assert_eq!(receiver.recv().await.unwrap().id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_close_resolves_waiters() {
+        let notifier = Notifier::default();
+        let closed = notifier.closed();
+        notifier.close();
+        closed.await;
+        // Also once already closed
+        notifier.closed().await;
+    }
+}
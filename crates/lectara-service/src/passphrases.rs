@@ -0,0 +1,85 @@
+//! Passphrases protecting share links.
+//!
+//! Only an Argon2id hash of each passphrase is stored. Checking a guess
+//! recomputes the hash and compares it in constant time, and a link stops
+//! accepting guesses for a while after too many wrong ones.
+
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use chrono::{Duration, NaiveDateTime};
+use rand::Rng;
+
+use crate::models::ShareLink;
+
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+pub const MAX_PASSPHRASE_LENGTH: usize = 1024;
+
+/// Wrong guesses allowed within [`LOCKOUT`] before a link is locked
+pub const MAX_FAILED_ATTEMPTS: i32 = 5;
+/// How long a link stays locked after its last wrong guess
+pub const LOCKOUT: Duration = Duration::minutes(15);
+
+/// Checks that a passphrase chosen for a new link is usable
+pub fn validate(passphrase: &str) -> Result<(), String> {
+    let length = passphrase.chars().count();
+    if length < MIN_PASSPHRASE_LENGTH {
+        return Err(format!(
+            "Passphrase must be at least {MIN_PASSPHRASE_LENGTH} characters"
+        ));
+    }
+    if length > MAX_PASSPHRASE_LENGTH {
+        return Err(format!(
+            "Passphrase must be at most {MAX_PASSPHRASE_LENGTH} characters"
+        ));
+    }
+    Ok(())
+}
+
+/// Hashes `passphrase` with a random salt, in PHC string format
+pub fn hash(passphrase: &str) -> String {
+    let mut salt = [0u8; 16];
+    rand::rng().fill(&mut salt);
+    let salt = SaltString::encode_b64(&salt).expect("16 bytes is a valid salt length");
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .expect("default Argon2 parameters accept any passphrase")
+        .to_string()
+}
+
+/// Whether `passphrase` matches `hash`. A malformed hash matches nothing.
+pub fn verify(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(passphrase.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// When `link` accepts guesses again, or `None` if it does now
+pub fn locked_until(link: &ShareLink, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let unlocks_at = link.last_failed_at? + LOCKOUT;
+    (link.failed_attempts >= MAX_FAILED_ATTEMPTS && unlocks_at > now).then_some(unlocks_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hashed = hash("correct horse");
+        assert!(hashed.starts_with("$argon2id$"));
+        assert_ne!(hashed, hash("correct horse"));
+
+        assert!(verify("correct horse", &hashed));
+        assert!(!verify("correct horsE", &hashed));
+        assert!(!verify("correct horse", "not a hash"));
+    }
+
+    #[test]
+    fn test_validate_length() {
+        assert!(validate("short").is_err());
+        assert!(validate("long enough").is_ok());
+        assert!(validate(&"x".repeat(MAX_PASSPHRASE_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Emergency read-only mode.
+//!
+//! While it is on, every endpoint that would write to the database answers
+//! 503 and background fetch retries pause, so the database can be migrated,
+//! restored from a backup or left alone on a full disk while reads keep
+//! working. It is switched with [`READ_ONLY_ENV`] at startup and through
+//! the admin API at runtime.
+
+use std::sync::{Arc, RwLock};
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::AppState;
+use crate::errors::ApiError;
+
+/// Starts the service read-only when set to `true` or `1`, or to a message
+/// explaining why
+pub const READ_ONLY_ENV: &str = "LECTARA_READ_ONLY";
+
+const DEFAULT_REASON: &str = "The service is in read-only mode";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadOnlyStatus {
+    pub reason: String,
+    pub since: NaiveDateTime,
+}
+
+/// Shared switch for read-only mode; clones see the same setting
+#[derive(Clone, Default)]
+pub struct ReadOnlyMode {
+    status: Arc<RwLock<Option<ReadOnlyStatus>>>,
+}
+
+impl ReadOnlyMode {
+    /// Reads [`READ_ONLY_ENV`], starting writable when it is unset, empty,
+    /// `0` or `false`
+    pub fn from_env() -> Self {
+        let mode = Self::default();
+        if let Ok(value) = std::env::var(READ_ONLY_ENV) {
+            match value.trim() {
+                "" | "0" | "false" => {}
+                "1" | "true" => mode.enable(None),
+                reason => mode.enable(Some(reason.to_string())),
+            }
+        }
+        mode
+    }
+
+    /// Turns read-only mode on, keeping the original start time if it
+    /// already was
+    pub fn enable(&self, reason: Option<String>) {
+        let reason = reason.unwrap_or_else(|| DEFAULT_REASON.to_string());
+        let mut status = self.status.write().unwrap();
+        let since = status
+            .as_ref()
+            .map(|status| status.since)
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+        *status = Some(ReadOnlyStatus { reason, since });
+    }
+
+    pub fn disable(&self) {
+        *self.status.write().unwrap() = None;
+    }
+
+    /// Why the service is read-only, or `None` if it accepts writes
+    pub fn status(&self) -> Option<ReadOnlyStatus> {
+        self.status.read().unwrap().clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status.read().unwrap().is_some()
+    }
+}
+
+/// Extractor for handlers that write, rejecting the request with
+/// [`ApiError::ReadOnly`] while read-only mode is on
+pub struct Writable;
+
+impl<S: AppState> FromRequestParts<S> for Writable {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match state.read_only().status() {
+            Some(status) => Err(ApiError::ReadOnly(status.reason)),
+            None => Ok(Writable),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enable_keeps_start_time() {
+        let mode = ReadOnlyMode::default();
+        assert!(mode.status().is_none());
+
+        mode.enable(None);
+        let first = mode.status().unwrap();
+        assert_eq!(first.reason, DEFAULT_REASON);
+
+        mode.clone().enable(Some("Restoring backup".to_string()));
+        let second = mode.status().unwrap();
+        assert_eq!(second.reason, "Restoring backup");
+        assert_eq!(second.since, first.since);
+
+        mode.disable();
+        assert!(!mode.is_enabled());
+    }
+}
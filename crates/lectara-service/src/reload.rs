@@ -0,0 +1,252 @@
+//! Settings that change without a restart, re-read on SIGHUP or through
+//! `POST /api/v1/admin/reload`: the log filter in [`LOG_FILTER_ENV`] and the
+//! fetch credentials file named by [`FETCH_CREDENTIALS_ENV`].
+//!
+//! Each is published on a watch channel followed by the layer using it: the
+//! server's log subscriber swaps in new filters, and every [`Fetcher`] clone
+//! reads the latest credentials when it starts a fetch. A reload that fails
+//! applies nothing, so a typo in one file can't leave half a change behind.
+//!
+//! [`Fetcher`]: crate::enrichment::fetch::Fetcher
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{ConfigError, Settings};
+use crate::enrichment::credentials::{CredentialsError, FETCH_CREDENTIALS_ENV, SiteCredentials};
+
+/// Environment variable holding the log filter, e.g. `info,tower_http=debug`
+pub const LOG_FILTER_ENV: &str = "RUST_LOG";
+
+/// Added to every filter, so the service's own debug logs stay on
+const SERVICE_DIRECTIVE: &str = "lectara_service=debug";
+
+/// The variables a reload re-reads
+const RELOADABLE: [&str; 2] = [LOG_FILTER_ENV, FETCH_CREDENTIALS_ENV];
+
+#[derive(Error, Debug)]
+pub enum ReloadError {
+    #[error("Failed to load settings: {0}")]
+    Settings(#[from] ConfigError),
+
+    #[error("Invalid {LOG_FILTER_ENV}: {0}")]
+    LogFilter(String),
+
+    #[error("Failed to load fetch credentials: {0}")]
+    Credentials(#[from] CredentialsError),
+}
+
+/// The settings a reload applied
+#[derive(Debug, Clone, Serialize)]
+pub struct Reloaded {
+    pub log_filter: String,
+    /// Number of sites fetches send credentials to
+    pub credential_sites: usize,
+}
+
+/// Source and current value of each reloadable setting; clones share them
+#[derive(Clone)]
+pub struct Reloader {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// The `--config` file, re-read on each reload
+    settings: Option<PathBuf>,
+    /// Reloadable variables set in the environment at startup, which win
+    /// over the settings file as they did then
+    environment: HashMap<String, String>,
+    log_filter: watch::Sender<String>,
+    credentials: watch::Sender<Arc<SiteCredentials>>,
+    /// Held while reloading, so SIGHUP and the admin API can't interleave
+    reloading: Mutex<()>,
+}
+
+impl Default for Reloader {
+    fn default() -> Self {
+        Self::new(None, HashMap::new())
+    }
+}
+
+impl Reloader {
+    pub fn new(settings: Option<PathBuf>, environment: HashMap<String, String>) -> Self {
+        let log_filter = environment.get(LOG_FILTER_ENV).cloned().unwrap_or_default();
+        Self {
+            inner: Arc::new(Inner {
+                settings,
+                environment,
+                log_filter: watch::Sender::new(log_filter),
+                credentials: watch::Sender::new(Arc::default()),
+                reloading: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// The reloadable variables as the environment holds them now; taken
+    /// before a settings file fills in the rest
+    pub fn capture_environment() -> HashMap<String, String> {
+        RELOADABLE
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+            .collect()
+    }
+
+    /// Publishes the credentials loaded at startup
+    pub fn with_credentials(self, credentials: SiteCredentials) -> Self {
+        self.inner.credentials.send_replace(Arc::new(credentials));
+        self
+    }
+
+    /// Follows the log filter directives, without [`SERVICE_DIRECTIVE`]
+    pub fn log_filter(&self) -> watch::Receiver<String> {
+        self.inner.log_filter.subscribe()
+    }
+
+    /// Follows the per-site fetch credentials
+    pub fn credentials(&self) -> watch::Receiver<Arc<SiteCredentials>> {
+        self.inner.credentials.subscribe()
+    }
+
+    /// Re-reads the settings file and the credentials file, then publishes
+    /// both if every one of them is valid
+    pub fn reload(&self) -> Result<Reloaded, ReloadError> {
+        let _reloading = self.inner.reloading.lock().unwrap();
+
+        let settings = match &self.inner.settings {
+            Some(path) => Settings::load(path)?,
+            None => Settings::default(),
+        };
+        let value = |name: &str| {
+            self.inner
+                .environment
+                .get(name)
+                .or_else(|| settings.variables.get(name))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let log_filter = value(LOG_FILTER_ENV).unwrap_or_default();
+        parse_log_filter(&log_filter)?;
+        let credentials = match value(FETCH_CREDENTIALS_ENV) {
+            Some(path) => SiteCredentials::load(Path::new(&path))?,
+            None => SiteCredentials::default(),
+        };
+
+        let reloaded = Reloaded {
+            log_filter: log_filter.clone(),
+            credential_sites: credentials.len(),
+        };
+        self.inner.log_filter.send_if_modified(|current| {
+            let changed = *current != log_filter;
+            *current = log_filter;
+            changed
+        });
+        self.inner.credentials.send_replace(Arc::new(credentials));
+        Ok(reloaded)
+    }
+}
+
+/// Parses filter directives as [`LOG_FILTER_ENV`] holds them, adding
+/// [`SERVICE_DIRECTIVE`]; unlike at startup, invalid directives are an error
+/// rather than skipped
+pub fn parse_log_filter(directives: &str) -> Result<EnvFilter, ReloadError> {
+    let filter = EnvFilter::builder()
+        .parse(directives)
+        .map_err(|err| ReloadError::LogFilter(err.to_string()))?;
+    Ok(filter.add_directive(SERVICE_DIRECTIVE.parse().expect("directive is valid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file under the temp directory, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("lectara-reload-{}-{name}", std::process::id()));
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_reload_reads_the_settings_file_under_the_environment() {
+        let credentials = TempFile::new(
+            "credentials.toml",
+            "[sites.\"example.com\"]\ncookies = { session = \"abc\" }\n",
+        );
+        let settings = TempFile::new(
+            "settings.toml",
+            &format!(
+                "RUST_LOG = \"warn\"\nLECTARA_FETCH_CREDENTIALS = {:?}\n",
+                credentials.0.display().to_string()
+            ),
+        );
+        let reloader = Reloader::new(Some(settings.0.clone()), HashMap::new());
+        let mut log_filter = reloader.log_filter();
+        let credential_updates = reloader.credentials();
+
+        let reloaded = reloader.reload().unwrap();
+        assert_eq!(reloaded.log_filter, "warn");
+        assert_eq!(reloaded.credential_sites, 1);
+        assert!(log_filter.has_changed().unwrap());
+        assert_eq!(*log_filter.borrow_and_update(), "warn");
+        assert!(
+            credential_updates
+                .borrow()
+                .for_host("example.com")
+                .is_some()
+        );
+
+        // Set in the environment at startup, so the file doesn't change it
+        let reloader = Reloader::new(
+            Some(settings.0.clone()),
+            HashMap::from([(LOG_FILTER_ENV.to_string(), "info".to_string())]),
+        );
+        assert_eq!(reloader.reload().unwrap().log_filter, "info");
+    }
+
+    #[test]
+    fn test_failed_reload_applies_nothing() {
+        let settings = TempFile::new(
+            "partial.toml",
+            "RUST_LOG = \"warn\"\nLECTARA_FETCH_CREDENTIALS = \"/nonexistent/credentials.toml\"\n",
+        );
+        let reloader = Reloader::new(Some(settings.0.clone()), HashMap::new())
+            .with_credentials(SiteCredentials::parse("[sites.\"example.com\"]\n").unwrap());
+        let log_filter = reloader.log_filter();
+
+        assert!(matches!(
+            reloader.reload(),
+            Err(ReloadError::Credentials(_))
+        ));
+        assert!(!log_filter.has_changed().unwrap());
+        assert_eq!(*log_filter.borrow(), "");
+        assert!(
+            reloader
+                .credentials()
+                .borrow()
+                .for_host("example.com")
+                .is_some()
+        );
+
+        std::fs::write(&settings.0, "RUST_LOG = \"lectara=nope\"\n").unwrap();
+        assert!(matches!(reloader.reload(), Err(ReloadError::LogFilter(_))));
+        assert!(!log_filter.has_changed().unwrap());
+    }
+}
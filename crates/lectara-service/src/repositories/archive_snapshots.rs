@@ -0,0 +1,126 @@
+use super::traits::ArchiveSnapshotRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{ArchiveSnapshot, NewArchiveSnapshot};
+use crate::schema::{archive_payloads, archive_snapshots};
+use async_trait::async_trait;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+#[derive(Clone)]
+pub struct SqliteArchiveSnapshotRepository {
+    db: Database,
+}
+
+impl SqliteArchiveSnapshotRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+/// Stores `payload` under `digest`, or counts one more reference to it
+fn store_payload(conn: &mut SqliteConnection, digest: &str, payload: &[u8]) -> QueryResult<()> {
+    diesel::insert_into(archive_payloads::table)
+        .values((
+            archive_payloads::digest.eq(digest),
+            archive_payloads::payload.eq(payload),
+            archive_payloads::ref_count.eq(1),
+        ))
+        .on_conflict(archive_payloads::digest)
+        .do_update()
+        .set(archive_payloads::ref_count.eq(archive_payloads::ref_count + 1))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Drops one reference to the payload stored under `digest`, deleting it
+/// once no capture points at it
+pub(crate) fn release_payload(conn: &mut SqliteConnection, digest: &str) -> QueryResult<()> {
+    diesel::update(archive_payloads::table.find(digest))
+        .set(archive_payloads::ref_count.eq(archive_payloads::ref_count - 1))
+        .execute(conn)?;
+
+    diesel::delete(
+        archive_payloads::table
+            .filter(archive_payloads::digest.eq(digest))
+            .filter(archive_payloads::ref_count.le(0)),
+    )
+    .execute(conn)?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl ArchiveSnapshotRepository for SqliteArchiveSnapshotRepository {
+    async fn create(
+        &self,
+        snapshot: &NewArchiveSnapshot,
+        payload: &[u8],
+    ) -> Result<ArchiveSnapshot, ApiError> {
+        let snapshot = snapshot.clone();
+        let payload = payload.to_vec();
+        self.db
+            .write(move |conn| {
+                let result = conn.transaction(|conn| {
+                    store_payload(conn, &snapshot.payload_digest, &payload)?;
+                    diesel::insert_into(archive_snapshots::table)
+                        .values(&snapshot)
+                        .returning(ArchiveSnapshot::as_returning())
+                        .get_result::<ArchiveSnapshot>(conn)
+                })?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<ArchiveSnapshot>, ApiError> {
+        let mut conn = self.db.read();
+        let result = archive_snapshots::table
+            .find(id)
+            .select(ArchiveSnapshot::as_select())
+            .first::<ArchiveSnapshot>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<ArchiveSnapshot>, ApiError> {
+        let mut conn = self.db.read();
+        let result = archive_snapshots::table
+            .filter(archive_snapshots::content_id.eq(content_id))
+            .order((
+                archive_snapshots::captured_at.desc(),
+                archive_snapshots::id.desc(),
+            ))
+            .select(ArchiveSnapshot::as_select())
+            .load::<ArchiveSnapshot>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn find_payload(&self, digest: &str) -> Result<Option<Vec<u8>>, ApiError> {
+        let mut conn = self.db.read();
+        let result = archive_payloads::table
+            .find(digest)
+            .select(archive_payloads::payload)
+            .first::<Vec<u8>>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, ApiError> {
+        self.db
+            .write(move |conn| {
+                let deleted = conn.transaction(|conn| {
+                    let digest = diesel::delete(archive_snapshots::table.find(id))
+                        .returning(archive_snapshots::payload_digest)
+                        .get_result::<String>(conn)
+                        .optional()?;
+                    match digest {
+                        Some(digest) => release_payload(conn, &digest).map(|()| true),
+                        None => Ok(false),
+                    }
+                })?;
+                Ok(deleted)
+            })
+            .await
+    }
+}
@@ -0,0 +1,221 @@
+//! Optional in-process cache of single-item lookups.
+//!
+//! The browser extension looks up the URL of every open tab to badge the
+//! saved ones, and saving checks the URL for duplicates first, so the same
+//! few rows are read over and over. [`QueryCache`] keeps the results of
+//! `find_by_url` and `find_by_id`, misses included, in `moka` caches bounded
+//! by their size in memory.
+//!
+//! The content repository forgets the items it writes once the write has
+//! committed. A lookup that started before a write can still finish after
+//! it, so results are only kept if no write happened in between, and
+//! entries expire after [`TIME_TO_LIVE`] in case something else changes the
+//! database.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use moka::sync::Cache;
+
+use crate::errors::ApiError;
+use crate::models::ContentItem;
+
+/// Memory for cached lookups in MiB; unset or 0 turns the cache off
+pub const QUERY_CACHE_ENV: &str = "LECTARA_QUERY_CACHE_MB";
+
+/// Upper bound on how stale an entry gets if the database is changed other
+/// than through the repository
+const TIME_TO_LIVE: Duration = Duration::from_secs(60);
+
+/// Rough overhead of an entry besides its strings
+const ENTRY_OVERHEAD: usize = 256;
+
+type Lookups<K> = Cache<K, Option<ContentItem>>;
+
+/// Items by URL and by id; clones share the entries
+#[derive(Clone)]
+pub struct QueryCache {
+    by_url: Lookups<String>,
+    by_id: Lookups<i32>,
+    /// Bumped by every write, so lookups overlapping one aren't kept
+    writes: Arc<AtomicU64>,
+}
+
+impl QueryCache {
+    /// A cache holding up to `capacity_bytes` of items, half for each kind
+    /// of lookup
+    pub fn new(capacity_bytes: u64) -> Self {
+        Self {
+            by_url: lookups(capacity_bytes / 2, |url: &String| url.len()),
+            by_id: lookups(capacity_bytes / 2, |_: &i32| 0),
+            writes: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The cache sized by [`QUERY_CACHE_ENV`], if it is set to a positive
+    /// number
+    pub fn from_env() -> Option<Self> {
+        let megabytes = std::env::var(QUERY_CACHE_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|megabytes| *megabytes > 0)?;
+        Some(Self::new(megabytes.saturating_mul(1024 * 1024)))
+    }
+
+    /// The item at `url`, from the cache or else from `load`
+    pub fn find_by_url(
+        &self,
+        url: &str,
+        load: impl FnOnce() -> Result<Option<ContentItem>, ApiError>,
+    ) -> Result<Option<ContentItem>, ApiError> {
+        if let Some(item) = self.by_url.get(url) {
+            return Ok(item);
+        }
+        self.load(load, |item| self.by_url.insert(url.to_string(), item))
+    }
+
+    /// The item with `id`, from the cache or else from `load`
+    pub fn find_by_id(
+        &self,
+        id: i32,
+        load: impl FnOnce() -> Result<Option<ContentItem>, ApiError>,
+    ) -> Result<Option<ContentItem>, ApiError> {
+        if let Some(item) = self.by_id.get(&id) {
+            return Ok(item);
+        }
+        self.load(load, |item| self.by_id.insert(id, item))
+    }
+
+    /// Forgets `item` after a write changed or created it; its URL never
+    /// changes, so its two entries are all there is
+    pub fn forget(&self, item: &ContentItem) {
+        self.writes.fetch_add(1, Ordering::AcqRel);
+        self.by_url.invalidate(&item.url);
+        self.by_id.invalidate(&item.id);
+    }
+
+    /// Forgets everything, after a write that may have changed any item
+    pub fn clear(&self) {
+        self.writes.fetch_add(1, Ordering::AcqRel);
+        self.by_url.invalidate_all();
+        self.by_id.invalidate_all();
+    }
+
+    fn load(
+        &self,
+        load: impl FnOnce() -> Result<Option<ContentItem>, ApiError>,
+        keep: impl FnOnce(Option<ContentItem>),
+    ) -> Result<Option<ContentItem>, ApiError> {
+        let writes = self.writes.load(Ordering::Acquire);
+        let item = load()?;
+        if self.writes.load(Ordering::Acquire) == writes {
+            keep(item.clone());
+        }
+        Ok(item)
+    }
+}
+
+fn lookups<K>(capacity_bytes: u64, key_size: fn(&K) -> usize) -> Lookups<K>
+where
+    K: std::hash::Hash + Eq + Send + Sync + 'static,
+{
+    Cache::builder()
+        .max_capacity(capacity_bytes)
+        .weigher(move |key, item: &Option<ContentItem>| {
+            let size = ENTRY_OVERHEAD + key_size(key) + item.as_ref().map_or(0, item_size);
+            u32::try_from(size).unwrap_or(u32::MAX)
+        })
+        .time_to_live(TIME_TO_LIVE)
+        .build()
+}
+
+fn item_size(item: &ContentItem) -> usize {
+    [
+        Some(&item.url),
+        item.title.as_ref(),
+        item.summary.as_ref(),
+        item.body.as_ref(),
+        item.text.as_ref(),
+        Some(&item.metadata),
+    ]
+    .into_iter()
+    .flatten()
+    .map(String::len)
+    .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ContentItemSummary;
+
+    fn item(id: i32, url: &str) -> ContentItem {
+        ContentItem::from_parts(
+            ContentItemSummary {
+                id,
+                url: url.to_string(),
+                title: None,
+                author: None,
+                created_at: chrono::Utc::now().naive_utc(),
+                body_hash: None,
+                enclosure_url: None,
+                duration_seconds: None,
+                enriched_fields: "[]".to_string(),
+                content_type: None,
+                metadata: "{}".to_string(),
+                summary: None,
+                pinned_position: None,
+                read_at: None,
+                archived_at: None,
+                source: None,
+                user_agent: None,
+                client_version: None,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_lookups_are_cached_until_the_item_is_written() {
+        let cache = QueryCache::new(1024 * 1024);
+        let url = "https://example.com/";
+        let mut loads = 0;
+
+        for _ in 0..3 {
+            let found = cache.find_by_url(url, || {
+                loads += 1;
+                Ok(None)
+            });
+            assert!(found.unwrap().is_none());
+        }
+        assert_eq!(loads, 1);
+
+        let saved = item(1, url);
+        cache.forget(&saved);
+        let found = cache.find_by_url(url, || Ok(Some(saved.clone())));
+        assert_eq!(found.unwrap().map(|item| item.id), Some(1));
+        let found = cache.find_by_url(url, || panic!("should be cached"));
+        assert_eq!(found.unwrap().map(|item| item.id), Some(1));
+
+        cache.clear();
+        let found = cache.find_by_id(1, || Ok(None));
+        assert!(found.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_lookups_overlapping_a_write_are_not_kept() {
+        let cache = QueryCache::new(1024 * 1024);
+        let stale = item(1, "https://example.com/");
+
+        // The write commits and is forgotten while the lookup is reading
+        let found = cache.find_by_id(1, || {
+            cache.forget(&stale);
+            Ok(Some(stale.clone()))
+        });
+        assert!(found.unwrap().is_some());
+
+        let found = cache.find_by_id(1, || Ok(None));
+        assert!(found.unwrap().is_none());
+    }
+}
@@ -0,0 +1,76 @@
+use super::traits::{CitationRepository, ContentFilter};
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{Citation, ContentItemSummary, NewCitation};
+use crate::schema::{citations, content_items};
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteCitationRepository {
+    db: Database,
+}
+
+impl SqliteCitationRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl CitationRepository for SqliteCitationRepository {
+    async fn upsert(&self, citation: &NewCitation) -> Result<Citation, ApiError> {
+        let citation = citation.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(citations::table)
+                    .values(&citation)
+                    .on_conflict(citations::content_id)
+                    .do_update()
+                    .set((&citation, citations::fetched_at.eq(diesel::dsl::now)))
+                    .returning(Citation::as_returning())
+                    .get_result::<Citation>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn find_by_content_id(&self, content_id: i32) -> Result<Option<Citation>, ApiError> {
+        let mut conn = self.db.read();
+        let result = citations::table
+            .find(content_id)
+            .select(Citation::as_select())
+            .first::<Citation>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn list_with_items(
+        &self,
+        filter: &ContentFilter,
+    ) -> Result<Vec<(ContentItemSummary, Citation)>, ApiError> {
+        let mut conn = self.db.read();
+        let mut query = citations::table
+            .inner_join(content_items::table)
+            .into_boxed();
+
+        if let Some(since) = filter.since {
+            query = query.filter(content_items::created_at.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(content_items::created_at.le(until));
+        }
+        if let Some(content_type) = &filter.content_type {
+            query = query.filter(content_items::content_type.eq(content_type.clone()));
+        }
+        if let Some(source) = &filter.source {
+            query = query.filter(content_items::source.eq(source.clone()));
+        }
+
+        let result = query
+            .order(content_items::id.asc())
+            .select((ContentItemSummary::as_select(), Citation::as_select()))
+            .load::<(ContentItemSummary, Citation)>(&mut *conn)?;
+        Ok(result)
+    }
+}
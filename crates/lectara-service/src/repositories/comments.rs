@@ -0,0 +1,85 @@
+use super::traits::CommentRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{Comment, NewComment};
+use crate::schema::comments;
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteCommentRepository {
+    db: Database,
+}
+
+impl SqliteCommentRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl CommentRepository for SqliteCommentRepository {
+    async fn create(&self, comment: &NewComment) -> Result<Comment, ApiError> {
+        let comment = comment.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(comments::table)
+                    .values(&comment)
+                    .returning(Comment::as_returning())
+                    .get_result::<Comment>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<Comment>, ApiError> {
+        let mut conn = self.db.read();
+        let result = comments::table
+            .filter(comments::content_id.eq(content_id))
+            .order((comments::created_at.asc(), comments::id.asc()))
+            .select(Comment::as_select())
+            .load::<Comment>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Comment>, ApiError> {
+        let mut conn = self.db.read();
+        let result = comments::table
+            .find(id)
+            .select(Comment::as_select())
+            .first::<Comment>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn delete(&self, content_id: i32, id: i32) -> Result<bool, ApiError> {
+        self.db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    let exists = comments::table
+                        .filter(comments::id.eq(id))
+                        .filter(comments::content_id.eq(content_id))
+                        .count()
+                        .get_result::<i64>(conn)?
+                        > 0;
+                    if !exists {
+                        return Ok(false);
+                    }
+
+                    // Foreign keys aren't enforced, so replies are found level
+                    // by level rather than cascading
+                    let mut level = vec![id];
+                    while !level.is_empty() {
+                        diesel::delete(comments::table.filter(comments::id.eq_any(&level)))
+                            .execute(conn)?;
+                        level = comments::table
+                            .filter(comments::parent_id.eq_any(&level))
+                            .select(comments::id)
+                            .load::<i32>(conn)?;
+                    }
+                    Ok(true)
+                })
+            })
+            .await
+    }
+}
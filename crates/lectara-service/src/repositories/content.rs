@@ -1,83 +1,1006 @@
-use super::traits::{ContentRepository, ListContentParams, ListContentResult};
+use super::archive_snapshots::release_payload;
+use super::cache::QueryCache;
+use super::traits::{
+    AdjacentItems, BulkAction, BulkSelection, ContentFilter, ContentRepository,
+    DocumentFrequencies, ListContentParams, ListContentResult, SearchContentParams,
+};
+use crate::bodies;
+use crate::database::{CollectionVersion, Database};
+use crate::enrichment::pdf::PDF_CONTENT_TYPE;
 use crate::errors::ApiError;
-use crate::models::{ContentItem, NewContentItem};
-use crate::schema::content_items;
+use crate::models::{
+    ContentItem, ContentItemSummary, MetadataEdit, MetadataField, MetadataPatch, NewContentItem,
+};
+use crate::schema::{
+    archive_snapshots, body_blobs, citations, comments, content_bodies, content_items,
+    fetch_attempts, notifications, page_snapshots, share_links,
+};
+use crate::search::SearchLanguage;
+use crate::validation::host_matches;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::dsl::sql;
 use diesel::prelude::*;
-use diesel::sqlite::SqliteConnection;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::sql_types::{Bool, Text};
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+// The full-text indexes are FTS5 virtual tables kept in sync by triggers,
+// so they're declared here rather than in the generated schema; only the
+// columns queries need are listed.
+diesel::table! {
+    content_search (rowid) {
+        rowid -> Integer,
+        rank -> Double,
+    }
+}
+
+diesel::table! {
+    content_search_exact (rowid) {
+        rowid -> Integer,
+        rank -> Double,
+    }
+}
+
+diesel::table! {
+    /// `fts5vocab` view of the terms in `content_search_exact`
+    content_search_terms (term) {
+        term -> Text,
+        doc -> BigInt,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(content_items, content_search, content_search_exact);
+
+/// Terms looked up per query when reading document frequencies
+const TERM_LOOKUP_CHUNK: usize = 500;
+
+/// Items changed per statement by a bulk update, keeping well below
+/// SQLite's limit on bound parameters
+const BULK_CHUNK: usize = 500;
+
+/// Upper bound on cached filter totals; the cache is cleared when it is exceeded
+const TOTALS_CACHE_CAPACITY: usize = 256;
 
 #[derive(Clone)]
 pub struct SqliteContentRepository {
-    db: Arc<Mutex<SqliteConnection>>,
+    db: Database,
+    /// Totals per filter, so paging through a list doesn't recount on every page.
+    /// They are counted at one version of the database and dropped once it
+    /// moves on, so any write through it, by any repository, invalidates them.
+    totals: Arc<Mutex<Totals>>,
+    /// Items looked up by URL or id, when the cache is on
+    cache: Option<QueryCache>,
 }
 
 impl SqliteContentRepository {
-    pub fn new(db: Arc<Mutex<SqliteConnection>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self {
+            db: db.into(),
+            totals: Arc::new(Mutex::new(Totals::default())),
+            cache: None,
+        }
+    }
+
+    /// Caches lookups of single items in `cache`, which only this
+    /// repository should use
+    pub fn with_cache(mut self, cache: QueryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    fn cached_total(
+        &self,
+        conn: &mut SqliteConnection,
+        filter: &ContentFilter,
+    ) -> Result<u64, ApiError> {
+        // Read before counting, so a write landing in between leaves the
+        // total under a version that is already stale
+        let version = self.db.version();
+        {
+            let totals = self.totals.lock().unwrap();
+            if totals.version == Some(version)
+                && let Some(total) = totals.counts.get(filter)
+            {
+                return Ok(*total);
+            }
+        }
+
+        let total = filtered_content_items(filter)
+            .count()
+            .get_result::<i64>(conn)? as u64;
+
+        let mut totals = self.totals.lock().unwrap();
+        if totals.version != Some(version) || totals.counts.len() >= TOTALS_CACHE_CAPACITY {
+            totals.counts.clear();
+            totals.version = Some(version);
+        }
+        totals.counts.insert(filter.clone(), total);
+
+        Ok(total)
+    }
+
+    /// Forgets cached totals, e.g. after the database was swapped out from
+    /// under this repository
+    pub fn invalidate_totals(&self) {
+        *self.totals.lock().unwrap() = Totals::default();
+    }
+
+    /// Forgets cached totals and items, e.g. after the database was
+    /// swapped out from under this repository
+    pub fn invalidate_caches(&self) {
+        self.invalidate_totals();
+        self.forget_all();
+    }
+
+    /// Drops `item` from the cache once a write to it has committed
+    fn forget(&self, item: &ContentItem) {
+        if let Some(cache) = &self.cache {
+            cache.forget(item);
+        }
+    }
+
+    /// Empties the cache once a write to any number of items has committed
+    fn forget_all(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+}
+
+/// Filter totals counted at `version`
+#[derive(Default)]
+struct Totals {
+    version: Option<CollectionVersion>,
+    counts: HashMap<ContentFilter, u64>,
+}
+
+fn pinned_items(conn: &mut SqliteConnection) -> QueryResult<Vec<ContentItemSummary>> {
+    content_items::table
+        .filter(content_items::pinned_position.is_not_null())
+        .order(content_items::pinned_position.asc())
+        .select(ContentItemSummary::as_select())
+        .load(conn)
+}
+
+/// Pins exactly the items in `ids`, in that order
+fn set_pin_order(conn: &mut SqliteConnection, ids: &[i32]) -> QueryResult<()> {
+    // Cleared first so no two items ever share a position, which the unique
+    // index would reject
+    diesel::update(content_items::table.filter(content_items::pinned_position.is_not_null()))
+        .set(content_items::pinned_position.eq(None::<i32>))
+        .execute(conn)?;
+    for (position, id) in ids.iter().enumerate() {
+        diesel::update(content_items::table.find(id))
+            .set(content_items::pinned_position.eq(position as i32))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Whether `url`'s host is `domain` or one of its subdomains
+fn url_host_matches(url: &str, domain: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase))
+        .is_some_and(|host| host_matches(&host, domain))
+}
+
+/// Ids of the existing items in `selection`
+fn bulk_selection_ids(
+    conn: &mut SqliteConnection,
+    selection: &BulkSelection,
+) -> QueryResult<Vec<i32>> {
+    match selection {
+        BulkSelection::Ids(ids) => {
+            let mut found = Vec::new();
+            for chunk in ids.chunks(BULK_CHUNK) {
+                found.extend(
+                    content_items::table
+                        .filter(content_items::id.eq_any(chunk))
+                        .select(content_items::id)
+                        .load::<i32>(conn)?,
+                );
+            }
+            found.sort_unstable();
+            found.dedup();
+            Ok(found)
+        }
+        BulkSelection::Filter { domain, until } => {
+            let mut query = content_items::table.into_boxed();
+            if let Some(until) = until {
+                query = query.filter(content_items::created_at.le(*until));
+            }
+            let rows = query
+                .order(content_items::id.asc())
+                .select((content_items::id, content_items::url))
+                .load::<(i32, String)>(conn)?;
+
+            // Hosts aren't stored on their own, so the domain is matched here
+            let domain = domain.as_deref().map(str::to_lowercase);
+            Ok(rows
+                .into_iter()
+                .filter(|(_, url)| {
+                    domain
+                        .as_deref()
+                        .is_none_or(|domain| url_host_matches(url, domain))
+                })
+                .map(|(id, _)| id)
+                .collect())
+        }
+    }
+}
+
+/// Deletes the items and everything stored about them
+fn delete_items(conn: &mut SqliteConnection, ids: &[i32]) -> QueryResult<()> {
+    for chunk in ids.chunks(BULK_CHUNK) {
+        let payload_digests = archive_snapshots::table
+            .filter(archive_snapshots::content_id.eq_any(chunk))
+            .select(archive_snapshots::payload_digest)
+            .load::<String>(conn)?;
+        let body_hashes = content_items::table
+            .filter(content_items::id.eq_any(chunk))
+            .filter(content_items::body_hash.is_not_null())
+            .select(content_items::body_hash.assume_not_null())
+            .load::<String>(conn)?;
+
+        // Foreign keys aren't enforced, so the cascades in the schema don't run
+        diesel::delete(share_links::table.filter(share_links::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(citations::table.filter(citations::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(comments::table.filter(comments::content_id.eq_any(chunk))).execute(conn)?;
+        // Ids are reused, so these would come to point at another item
+        diesel::delete(notifications::table.filter(notifications::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(fetch_attempts::table.filter(fetch_attempts::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(page_snapshots::table.filter(page_snapshots::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(
+            archive_snapshots::table.filter(archive_snapshots::content_id.eq_any(chunk)),
+        )
+        .execute(conn)?;
+        diesel::delete(content_bodies::table.filter(content_bodies::content_id.eq_any(chunk)))
+            .execute(conn)?;
+        diesel::delete(content_items::table.filter(content_items::id.eq_any(chunk)))
+            .execute(conn)?;
+
+        for hash in &body_hashes {
+            bodies::release_body(conn, hash)?;
+        }
+        for digest in &payload_digests {
+            release_payload(conn, digest)?;
+        }
     }
+
+    // Close any gaps left among the pinned positions
+    let pinned: Vec<i32> = pinned_items(conn)?
+        .into_iter()
+        .map(|item| item.id)
+        .collect();
+    set_pin_order(conn, &pinned)
+}
+
+fn filtered_content_items(filter: &ContentFilter) -> content_items::BoxedQuery<'static, Sqlite> {
+    let mut query = content_items::table.into_boxed();
+
+    if let Some(since) = filter.since {
+        query = query.filter(content_items::created_at.ge(since));
+    }
+    if let Some(until) = filter.until {
+        query = query.filter(content_items::created_at.le(until));
+    }
+    if let Some(content_type) = &filter.content_type {
+        query = query.filter(content_items::content_type.eq(content_type.clone()));
+    }
+    if let Some(source) = &filter.source {
+        query = query.filter(content_items::source.eq(source.clone()));
+    }
+
+    query
+}
+
+type ItemsWithBodies<'a> = diesel::dsl::IntoBoxed<
+    'a,
+    diesel::dsl::LeftJoin<content_items::table, body_blobs::table>,
+    Sqlite,
+>;
+
+/// Content items joined to their (optional) bodies, ready for filtering
+fn items_with_bodies<'a>() -> ItemsWithBodies<'a> {
+    content_items::table
+        .left_join(body_blobs::table)
+        .into_boxed()
+}
+
+/// Loads the first item of `query` together with its body, if any
+fn first_with_body(
+    conn: &mut SqliteConnection,
+    query: ItemsWithBodies<'_>,
+) -> Result<Option<ContentItem>, DieselError> {
+    let row = query
+        .select((
+            ContentItemSummary::as_select(),
+            body_blobs::body.nullable(),
+            body_blobs::text.nullable(),
+        ))
+        .first::<(ContentItemSummary, Option<String>, Option<String>)>(conn)
+        .optional()?;
+
+    Ok(row.map(|(summary, body, text)| ContentItem::from_parts(summary, body).with_text(text)))
+}
+
+/// Stores the body (sharing an identical stored one) and inserts the item
+/// row pointing at it
+fn insert_item(
+    conn: &mut SqliteConnection,
+    content: &NewContentItem,
+) -> Result<ContentItem, DieselError> {
+    conn.transaction(|conn| {
+        let body_hash = content
+            .body
+            .as_deref()
+            .map(|body| bodies::store_body(conn, body))
+            .transpose()?;
+
+        let summary = diesel::insert_into(content_items::table)
+            .values((
+                content_items::url.eq(&content.url),
+                content_items::title.eq(&content.title),
+                content_items::author.eq(&content.author),
+                content_items::body_hash.eq(&body_hash),
+                content_items::enclosure_url.eq(&content.enclosure_url),
+                content_items::duration_seconds.eq(content.duration_seconds),
+                content_items::source.eq(&content.source),
+                content_items::user_agent.eq(&content.user_agent),
+                content_items::client_version.eq(&content.client_version),
+                content
+                    .created_at
+                    .map(|created_at| content_items::created_at.eq(created_at)),
+            ))
+            .returning(ContentItemSummary::as_returning())
+            .get_result::<ContentItemSummary>(conn)?;
+
+        let text = content.body.as_deref().and_then(bodies::plain_text);
+        Ok(ContentItem::from_parts(summary, content.body.clone()).with_text(text))
+    })
+}
+
+/// Inserts `content`, applying the idempotency rules if its URL is already
+/// saved: identical metadata returns the existing item, anything else conflicts
+fn create_or_match_existing(
+    conn: &mut SqliteConnection,
+    content: &NewContentItem,
+) -> Result<ContentItem, ApiError> {
+    match insert_item(conn, content) {
+        Ok(item) => Ok(item),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            let existing = first_with_body(
+                conn,
+                items_with_bodies().filter(content_items::url.eq(&content.url)),
+            )?
+            .ok_or(DieselError::NotFound)?;
+
+            let id = existing.id;
+            match content.conflict_with(existing.clone()) {
+                None => {
+                    debug!(id, "Insert matched identical existing item");
+                    Ok(existing)
+                }
+                Some(conflict) => {
+                    warn!(
+                        id,
+                        differing_fields = ?conflict.differences.iter().map(|d| d.field).collect::<Vec<_>>(),
+                        "Insert matched existing URL with different metadata"
+                    );
+                    Err(ApiError::DuplicateUrlDifferentMetadata(Box::new(conflict)))
+                }
+            }
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Fills the item's missing fields from `patch` and merges its metadata,
+/// returning the updated item or `None` if it no longer exists
+fn merge_patch(
+    conn: &mut SqliteConnection,
+    id: i32,
+    patch: &MetadataPatch,
+) -> Result<Option<ContentItem>, DieselError> {
+    conn.transaction(|conn| {
+        let Some(item) =
+            first_with_body(conn, items_with_bodies().filter(content_items::id.eq(id)))?
+        else {
+            return Ok(None);
+        };
+
+        let mut enriched = item.enriched_fields();
+        let mut fill = |field, stored: &Option<String>, found: &Option<String>| {
+            if stored.is_some() || found.is_none() {
+                return stored.clone();
+            }
+            if !enriched.contains(&field) {
+                enriched.push(field);
+            }
+            found.clone()
+        };
+        let title = fill(MetadataField::Title, &item.title, &patch.title);
+        let author = fill(MetadataField::Author, &item.author, &patch.author);
+        let body = fill(MetadataField::Body, &item.body, &patch.body);
+
+        let body_hash = match (&item.body_hash, &body) {
+            (None, Some(body)) => Some(bodies::store_body(conn, body)?),
+            (stored, _) => stored.clone(),
+        };
+
+        let mut metadata = item.metadata();
+        metadata.extend(patch.metadata.clone());
+
+        diesel::update(content_items::table.find(id))
+            .set((
+                content_items::title.eq(title),
+                content_items::author.eq(author),
+                content_items::body_hash.eq(body_hash),
+                content_items::enriched_fields
+                    .eq(serde_json::to_string(&enriched).expect("fields serialize as JSON")),
+                content_items::content_type.eq(item.content_type.or(patch.content_type.clone())),
+                content_items::summary.eq(item.summary.or(patch.summary.clone())),
+                content_items::metadata.eq(serde_json::Value::Object(metadata).to_string()),
+            ))
+            .execute(conn)?;
+
+        first_with_body(conn, items_with_bodies().filter(content_items::id.eq(id)))
+    })
+}
+
+fn apply_edit(
+    conn: &mut SqliteConnection,
+    id: i32,
+    edit: &MetadataEdit,
+) -> Result<Option<ContentItem>, DieselError> {
+    conn.transaction(|conn| {
+        let Some(item) =
+            first_with_body(conn, items_with_bodies().filter(content_items::id.eq(id)))?
+        else {
+            return Ok(None);
+        };
+
+        let mut enriched = item.enriched_fields();
+        let mut replace = |field, stored: &Option<String>, edited: &Option<String>| {
+            let Some(edited) = edited else {
+                return stored.clone();
+            };
+            enriched.retain(|enriched| *enriched != field);
+            (!edited.is_empty()).then(|| edited.clone())
+        };
+        let title = replace(MetadataField::Title, &item.title, &edit.title);
+        let author = replace(MetadataField::Author, &item.author, &edit.author);
+        let body = replace(MetadataField::Body, &item.body, &edit.body);
+
+        let body_hash = if body == item.body {
+            item.body_hash.clone()
+        } else {
+            if let Some(hash) = &item.body_hash {
+                bodies::release_body(conn, hash)?;
+            }
+            body.as_deref()
+                .map(|body| bodies::store_body(conn, body))
+                .transpose()?
+        };
+
+        diesel::update(content_items::table.find(id))
+            .set((
+                content_items::title.eq(title),
+                content_items::author.eq(author),
+                content_items::body_hash.eq(body_hash),
+                content_items::enriched_fields
+                    .eq(serde_json::to_string(&enriched).expect("fields serialize as JSON")),
+            ))
+            .execute(conn)?;
+
+        first_with_body(conn, items_with_bodies().filter(content_items::id.eq(id)))
+    })
 }
 
 #[async_trait]
 impl ContentRepository for SqliteContentRepository {
+    fn version(&self) -> CollectionVersion {
+        self.db.version()
+    }
+
     async fn find_by_url(&self, url: &str) -> Result<Option<ContentItem>, ApiError> {
-        let mut conn = self.db.lock().unwrap();
-        let result = content_items::table
-            .filter(content_items::url.eq(url))
-            .first::<ContentItem>(&mut *conn)
-            .optional()?;
-        Ok(result)
+        let load = || {
+            let mut conn = self.db.read();
+            let result = first_with_body(
+                &mut conn,
+                items_with_bodies().filter(content_items::url.eq(url)),
+            )?;
+            Ok(result)
+        };
+        match &self.cache {
+            Some(cache) => cache.find_by_url(url, load),
+            None => load(),
+        }
     }
 
     async fn create(&self, content: &NewContentItem) -> Result<ContentItem, ApiError> {
-        let mut conn = self.db.lock().unwrap();
-        let result = diesel::insert_into(content_items::table)
-            .values(content)
-            .returning(content_items::all_columns)
-            .get_result::<ContentItem>(&mut *conn)?;
-        Ok(result)
+        // Another writer may have saved this URL between the caller's lookup
+        // and this insert, in which case the existing row is checked instead
+        let content = content.clone();
+        let item = self
+            .db
+            .write(move |conn| create_or_match_existing(conn, &content))
+            .await?;
+        self.forget(&item);
+        Ok(item)
+    }
+
+    async fn create_many(&self, contents: &[NewContentItem]) -> Result<Vec<ContentItem>, ApiError> {
+        let contents = contents.to_vec();
+        let items = self
+            .db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    contents
+                        .iter()
+                        .map(|content| create_or_match_existing(conn, content))
+                        .collect::<Result<Vec<_>, ApiError>>()
+                })
+            })
+            .await?;
+        for item in &items {
+            self.forget(item);
+        }
+        Ok(items)
     }
 
     async fn find_by_id(&self, id: i32) -> Result<Option<ContentItem>, ApiError> {
-        let mut conn = self.db.lock().unwrap();
+        let load = || {
+            let mut conn = self.db.read();
+            let result = first_with_body(
+                &mut conn,
+                items_with_bodies().filter(content_items::id.eq(id)),
+            )?;
+            Ok(result)
+        };
+        match &self.cache {
+            Some(cache) => cache.find_by_id(id, load),
+            None => load(),
+        }
+    }
+
+    async fn apply_enrichment(
+        &self,
+        id: i32,
+        patch: &MetadataPatch,
+    ) -> Result<Option<ContentItem>, ApiError> {
+        let patch = patch.clone();
+        let item = self
+            .db
+            .write(move |conn| Ok(merge_patch(conn, id, &patch)?))
+            .await?;
+        if let Some(item) = &item {
+            self.forget(item);
+        }
+        Ok(item)
+    }
+
+    async fn edit(&self, id: i32, edit: &MetadataEdit) -> Result<Option<ContentItem>, ApiError> {
+        let edit = edit.clone();
+        let item = self
+            .db
+            .write(move |conn| Ok(apply_edit(conn, id, &edit)?))
+            .await?;
+        if let Some(item) = &item {
+            self.forget(item);
+        }
+        Ok(item)
+    }
+
+    async fn find_by_urls(&self, urls: &[String]) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
         let result = content_items::table
-            .find(id)
-            .first::<ContentItem>(&mut *conn)
-            .optional()?;
+            .filter(content_items::url.eq_any(urls))
+            .order(content_items::id.asc())
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn find_by_ids(&self, ids: &[i32]) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        let result = content_items::table
+            .filter(content_items::id.eq_any(ids))
+            .order(content_items::id.asc())
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
         Ok(result)
     }
 
     async fn list(&self, params: &ListContentParams) -> Result<ListContentResult, ApiError> {
-        let mut conn = self.db.lock().unwrap();
+        let mut conn = self.db.read();
 
         let limit = params.limit.unwrap_or(50).min(1000) as i64;
 
-        let mut query = content_items::table.into_boxed();
-
-        if let Some(since) = params.since {
-            query = query.filter(content_items::created_at.ge(since));
-        }
-        if let Some(until) = params.until {
-            query = query.filter(content_items::created_at.le(until));
-        }
+        let mut query = filtered_content_items(&params.filter);
 
         if let Some(offset) = params.offset {
             query = query.offset(offset as i64);
         }
 
-        query = query.order((content_items::created_at.desc(), content_items::id.desc()));
+        // Pinned items come first, in their own order
+        query = query.order((
+            content_items::pinned_position.is_null(),
+            content_items::pinned_position.asc(),
+            content_items::created_at.desc(),
+            content_items::id.desc(),
+        ));
+
+        let items = query
+            .limit(limit)
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+
+        let total = if params.include_total {
+            Some(self.cached_total(&mut conn, &params.filter)?)
+        } else {
+            None
+        };
+
+        Ok(ListContentResult { items, total })
+    }
+
+    async fn adjacent(
+        &self,
+        id: i32,
+        created_at: NaiveDateTime,
+        filter: &ContentFilter,
+    ) -> Result<AdjacentItems, ApiError> {
+        let mut conn = self.db.read();
+
+        // Ties on created_at are broken by id, as in the list
+        let newer = filtered_content_items(filter)
+            .filter(
+                content_items::created_at
+                    .gt(created_at)
+                    .or(content_items::created_at
+                        .eq(created_at)
+                        .and(content_items::id.gt(id))),
+            )
+            .order((content_items::created_at.asc(), content_items::id.asc()))
+            .select(ContentItemSummary::as_select())
+            .first(&mut *conn)
+            .optional()?;
+        let older = filtered_content_items(filter)
+            .filter(
+                content_items::created_at
+                    .lt(created_at)
+                    .or(content_items::created_at
+                        .eq(created_at)
+                        .and(content_items::id.lt(id))),
+            )
+            .order((content_items::created_at.desc(), content_items::id.desc()))
+            .select(ContentItemSummary::as_select())
+            .first(&mut *conn)
+            .optional()?;
+
+        Ok(AdjacentItems { newer, older })
+    }
 
-        let items = query.limit(limit).load::<ContentItem>(&mut *conn)?;
+    async fn list_all(&self, filter: &ContentFilter) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        let result = filtered_content_items(filter)
+            .order((content_items::created_at.asc(), content_items::id.asc()))
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn list_recent(
+        &self,
+        filter: &ContentFilter,
+        limit: u32,
+    ) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        let result = filtered_content_items(filter)
+            .order((content_items::created_at.desc(), content_items::id.desc()))
+            .limit(limit as i64)
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+        Ok(result)
+    }
 
-        let mut count_query = content_items::table.into_boxed();
-        if let Some(since) = params.since {
-            count_query = count_query.filter(content_items::created_at.ge(since));
+    async fn list_episodes(&self, limit: u32) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        let result = content_items::table
+            .filter(content_items::enclosure_url.is_not_null())
+            .order((content_items::created_at.desc(), content_items::id.desc()))
+            .limit(limit as i64)
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn list_documents(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        // LIKE ignores ASCII case in SQLite, so `.PDF` links match too
+        let result = content_items::table
+            .filter(
+                content_items::content_type
+                    .eq(PDF_CONTENT_TYPE)
+                    .or(content_items::url.like("%.pdf"))
+                    .or(content_items::url.like("%.epub")),
+            )
+            .order((content_items::created_at.desc(), content_items::id.desc()))
+            .limit(limit as i64)
+            .offset(offset as i64)
+            .select(ContentItemSummary::as_select())
+            .load::<ContentItemSummary>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn search(
+        &self,
+        params: &SearchContentParams,
+    ) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+
+        let limit = params.limit.unwrap_or(50).min(1000) as i64;
+        let offset = params.offset.unwrap_or(0) as i64;
+        let expression = params.query.to_fts();
+        let filter = &params.filter;
+
+        // Both indexes have the same shape, but each is its own Diesel type
+        macro_rules! search_index {
+            ($index:ident) => {{
+                let mut query = content_items::table
+                    .inner_join($index::table.on($index::rowid.eq(content_items::id)))
+                    .filter(
+                        sql::<Bool>(concat!(stringify!($index), " MATCH "))
+                            .bind::<Text, _>(expression),
+                    )
+                    .into_boxed();
+
+                if let Some(since) = filter.since {
+                    query = query.filter(content_items::created_at.ge(since));
+                }
+                if let Some(until) = filter.until {
+                    query = query.filter(content_items::created_at.le(until));
+                }
+                if let Some(content_type) = &filter.content_type {
+                    query = query.filter(content_items::content_type.eq(content_type.clone()));
+                }
+                if let Some(source) = &filter.source {
+                    query = query.filter(content_items::source.eq(source.clone()));
+                }
+
+                query
+                    .order(($index::rank.asc(), content_items::id.desc()))
+                    .limit(limit)
+                    .offset(offset)
+                    .select(ContentItemSummary::as_select())
+                    .load::<ContentItemSummary>(&mut *conn)?
+            }};
         }
-        if let Some(until) = params.until {
-            count_query = count_query.filter(content_items::created_at.le(until));
+
+        let items = match params.language {
+            SearchLanguage::English => search_index!(content_search),
+            SearchLanguage::None => search_index!(content_search_exact),
+        };
+        Ok(items)
+    }
+
+    async fn document_frequencies(
+        &self,
+        terms: &[String],
+    ) -> Result<DocumentFrequencies, ApiError> {
+        let mut conn = self.db.read();
+
+        let documents = content_items::table.count().get_result::<i64>(&mut *conn)? as u64;
+
+        let mut counts = HashMap::new();
+        for chunk in terms.chunks(TERM_LOOKUP_CHUNK) {
+            let rows = content_search_terms::table
+                .filter(content_search_terms::term.eq_any(chunk))
+                .select((content_search_terms::term, content_search_terms::doc))
+                .load::<(String, i64)>(&mut *conn)?;
+            counts.extend(rows.into_iter().map(|(term, doc)| (term, doc as u64)));
         }
-        let total = count_query.count().get_result::<i64>(&mut *conn)? as u64;
 
-        Ok(ListContentResult { items, total })
+        Ok(DocumentFrequencies {
+            documents,
+            terms: counts,
+        })
+    }
+
+    async fn count(&self, filter: &ContentFilter) -> Result<u64, ApiError> {
+        let mut conn = self.db.read();
+        self.cached_total(&mut conn, filter)
+    }
+
+    async fn exists(&self, filter: &ContentFilter) -> Result<bool, ApiError> {
+        let mut conn = self.db.read();
+        let result = diesel::select(diesel::dsl::exists(
+            filtered_content_items(filter).select(content_items::id),
+        ))
+        .get_result::<bool>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn list_pinned(&self) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let mut conn = self.db.read();
+        Ok(pinned_items(&mut conn)?)
+    }
+
+    async fn pin(
+        &self,
+        id: i32,
+        max_pins: usize,
+    ) -> Result<Option<Vec<ContentItemSummary>>, ApiError> {
+        let pinned = self
+            .db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    let Some(position) = content_items::table
+                        .find(id)
+                        .select(content_items::pinned_position)
+                        .first::<Option<i32>>(conn)
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let pinned = pinned_items(conn)?;
+                    if position.is_some() {
+                        return Ok(Some(pinned));
+                    }
+                    if pinned.len() >= max_pins {
+                        return Err(ApiError::BadRequest(format!(
+                            "At most {max_pins} items can be pinned"
+                        )));
+                    }
+                    // Positions are kept contiguous, so the count is the next one
+                    diesel::update(content_items::table.find(id))
+                        .set(content_items::pinned_position.eq(pinned.len() as i32))
+                        .execute(conn)?;
+                    Ok(Some(pinned_items(conn)?))
+                })
+            })
+            .await?;
+        self.forget_all();
+        Ok(pinned)
+    }
+
+    async fn unpin(&self, id: i32) -> Result<Option<Vec<ContentItemSummary>>, ApiError> {
+        let pinned = self
+            .db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    let exists = diesel::select(diesel::dsl::exists(content_items::table.find(id)))
+                        .get_result::<bool>(conn)?;
+                    if !exists {
+                        return Ok(None);
+                    }
+
+                    let remaining: Vec<i32> = pinned_items(conn)?
+                        .into_iter()
+                        .map(|item| item.id)
+                        .filter(|pinned_id| *pinned_id != id)
+                        .collect();
+                    set_pin_order(conn, &remaining)?;
+                    Ok(Some(pinned_items(conn)?))
+                })
+            })
+            .await?;
+        // Moving one pin can move every other
+        self.forget_all();
+        Ok(pinned)
+    }
+
+    async fn reorder_pins(&self, ids: &[i32]) -> Result<Vec<ContentItemSummary>, ApiError> {
+        let ids = ids.to_vec();
+        let pinned = self
+            .db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    let mut pinned: Vec<i32> = pinned_items(conn)?
+                        .into_iter()
+                        .map(|item| item.id)
+                        .collect();
+                    let mut requested = ids.to_vec();
+                    pinned.sort_unstable();
+                    requested.sort_unstable();
+                    if pinned != requested {
+                        return Err(ApiError::BadRequest(
+                            "ids must list every pinned item exactly once".to_string(),
+                        ));
+                    }
+
+                    set_pin_order(conn, &ids)?;
+                    Ok(pinned_items(conn)?)
+                })
+            })
+            .await?;
+        self.forget_all();
+        Ok(pinned)
+    }
+
+    async fn bulk_update(
+        &self,
+        action: BulkAction,
+        selection: &BulkSelection,
+        dry_run: bool,
+    ) -> Result<u64, ApiError> {
+        let selection = selection.clone();
+        let affected = self
+            .db
+            .write(move |conn| {
+                conn.transaction(|conn| {
+                    let ids = bulk_selection_ids(conn, &selection)?;
+                    if dry_run {
+                        return Ok(ids.len() as u64);
+                    }
+
+                    let now = chrono::Utc::now().naive_utc();
+                    match action {
+                        BulkAction::Delete => delete_items(conn, &ids)?,
+                        // Items already read or archived keep their first timestamp
+                        BulkAction::Archive => {
+                            for chunk in ids.chunks(BULK_CHUNK) {
+                                diesel::update(
+                                    content_items::table
+                                        .filter(content_items::id.eq_any(chunk))
+                                        .filter(content_items::archived_at.is_null()),
+                                )
+                                .set(content_items::archived_at.eq(now))
+                                .execute(conn)?;
+                            }
+                        }
+                        BulkAction::MarkRead => {
+                            for chunk in ids.chunks(BULK_CHUNK) {
+                                diesel::update(
+                                    content_items::table
+                                        .filter(content_items::id.eq_any(chunk))
+                                        .filter(content_items::read_at.is_null()),
+                                )
+                                .set(content_items::read_at.eq(now))
+                                .execute(conn)?;
+                            }
+                        }
+                    }
+                    Ok::<_, ApiError>(ids.len() as u64)
+                })
+            })
+            .await?;
+
+        if !dry_run {
+            self.forget_all();
+        }
+        Ok(affected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_host_matches_domain_and_subdomains() {
+        assert!(url_host_matches("https://example.com/a", "example.com"));
+        assert!(url_host_matches(
+            "https://Blog.Example.com/a",
+            "example.com"
+        ));
+        assert!(!url_host_matches("https://notexample.com/a", "example.com"));
+        assert!(!url_host_matches("not a url", "example.com"));
     }
 }
@@ -0,0 +1,73 @@
+use super::traits::FetchAttemptRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{FetchAttempt, NewFetchAttempt};
+use crate::schema::fetch_attempts;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteFetchAttemptRepository {
+    db: Database,
+}
+
+impl SqliteFetchAttemptRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl FetchAttemptRepository for SqliteFetchAttemptRepository {
+    async fn record(&self, attempt: &NewFetchAttempt) -> Result<FetchAttempt, ApiError> {
+        let attempt = attempt.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(fetch_attempts::table)
+                    .values(&attempt)
+                    .on_conflict((fetch_attempts::content_id, fetch_attempts::kind))
+                    .do_update()
+                    .set(&attempt)
+                    .returning(FetchAttempt::as_returning())
+                    .get_result::<FetchAttempt>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn find(&self, content_id: i32, kind: &str) -> Result<Option<FetchAttempt>, ApiError> {
+        let mut conn = self.db.read();
+        let result = fetch_attempts::table
+            .find((content_id, kind))
+            .select(FetchAttempt::as_select())
+            .first::<FetchAttempt>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<FetchAttempt>, ApiError> {
+        let mut conn = self.db.read();
+        let result = fetch_attempts::table
+            .filter(fetch_attempts::content_id.eq(content_id))
+            .order(fetch_attempts::kind.asc())
+            .select(FetchAttempt::as_select())
+            .load::<FetchAttempt>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn list_due(
+        &self,
+        now: NaiveDateTime,
+        limit: u32,
+    ) -> Result<Vec<FetchAttempt>, ApiError> {
+        let mut conn = self.db.read();
+        let result = fetch_attempts::table
+            .filter(fetch_attempts::next_attempt_at.le(now))
+            .order(fetch_attempts::next_attempt_at.asc())
+            .limit(i64::from(limit))
+            .select(FetchAttempt::as_select())
+            .load::<FetchAttempt>(&mut *conn)?;
+        Ok(result)
+    }
+}
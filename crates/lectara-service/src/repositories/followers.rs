@@ -0,0 +1,65 @@
+use super::traits::FollowerRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{Follower, NewFollower};
+use crate::schema::followers;
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteFollowerRepository {
+    db: Database,
+}
+
+impl SqliteFollowerRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl FollowerRepository for SqliteFollowerRepository {
+    async fn add(&self, follower: &NewFollower) -> Result<Follower, ApiError> {
+        let follower = follower.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(followers::table)
+                    .values(&follower)
+                    .on_conflict(followers::actor_id)
+                    .do_update()
+                    .set(followers::inbox_url.eq(&follower.inbox_url))
+                    .returning(Follower::as_returning())
+                    .get_result::<Follower>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn remove(&self, actor_id: &str) -> Result<bool, ApiError> {
+        let actor_id = actor_id.to_string();
+        self.db
+            .write(move |conn| {
+                let deleted =
+                    diesel::delete(followers::table.filter(followers::actor_id.eq(&actor_id)))
+                        .execute(conn)?;
+                Ok(deleted > 0)
+            })
+            .await
+    }
+
+    async fn inboxes(&self) -> Result<Vec<String>, ApiError> {
+        let mut conn = self.db.read();
+        let result = followers::table
+            .select(followers::inbox_url)
+            .distinct()
+            .order(followers::inbox_url.asc())
+            .load::<String>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn count(&self) -> Result<u64, ApiError> {
+        let mut conn = self.db.read();
+        let count = followers::table.count().get_result::<i64>(&mut *conn)?;
+        Ok(count as u64)
+    }
+}
@@ -1,5 +1,30 @@
+pub mod archive_snapshots;
+pub mod cache;
+pub mod citations;
+pub mod comments;
 pub mod content;
+pub mod fetch_attempts;
+pub mod followers;
+pub mod notifications;
+pub mod page_snapshots;
+pub(crate) mod retry;
+pub mod rules;
+pub mod schema;
+pub mod share_links;
 pub mod traits;
+pub mod views;
 
+pub use archive_snapshots::SqliteArchiveSnapshotRepository;
+pub use cache::QueryCache;
+pub use citations::SqliteCitationRepository;
+pub use comments::SqliteCommentRepository;
 pub use content::SqliteContentRepository;
+pub use fetch_attempts::SqliteFetchAttemptRepository;
+pub use followers::SqliteFollowerRepository;
+pub use notifications::SqliteNotificationRepository;
+pub use page_snapshots::SqlitePageSnapshotRepository;
+pub use rules::SqliteRuleRepository;
+pub use schema::SqliteSchemaRepository;
+pub use share_links::SqliteShareLinkRepository;
 pub use traits::*;
+pub use views::SqliteViewRepository;
@@ -0,0 +1,76 @@
+use super::traits::NotificationRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{NewNotification, Notification};
+use crate::schema::notifications;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteNotificationRepository {
+    db: Database,
+}
+
+impl SqliteNotificationRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SqliteNotificationRepository {
+    async fn create(&self, notification: &NewNotification) -> Result<Notification, ApiError> {
+        let notification = notification.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(notifications::table)
+                    .values(&notification)
+                    .returning(Notification::as_returning())
+                    .get_result::<Notification>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn list(&self, unread_only: bool, limit: u32) -> Result<Vec<Notification>, ApiError> {
+        let mut conn = self.db.read();
+        let mut query = notifications::table.into_boxed();
+        if unread_only {
+            query = query.filter(notifications::read_at.is_null());
+        }
+        let result = query
+            .order((notifications::created_at.desc(), notifications::id.desc()))
+            .limit(i64::from(limit))
+            .select(Notification::as_select())
+            .load::<Notification>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn count_unread(&self) -> Result<u64, ApiError> {
+        let mut conn = self.db.read();
+        let count = notifications::table
+            .filter(notifications::read_at.is_null())
+            .count()
+            .get_result::<i64>(&mut *conn)?;
+        Ok(count as u64)
+    }
+
+    async fn mark_read(&self, ids: Option<&[i32]>, now: NaiveDateTime) -> Result<u64, ApiError> {
+        let ids = ids.map(<[i32]>::to_vec);
+        self.db
+            .write(move |conn| {
+                let unread = notifications::table.filter(notifications::read_at.is_null());
+                let marked = match &ids {
+                    Some(ids) => diesel::update(unread.filter(notifications::id.eq_any(ids)))
+                        .set(notifications::read_at.eq(now))
+                        .execute(conn)?,
+                    None => diesel::update(unread)
+                        .set(notifications::read_at.eq(now))
+                        .execute(conn)?,
+                };
+                Ok(marked as u64)
+            })
+            .await
+    }
+}
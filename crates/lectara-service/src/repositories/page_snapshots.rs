@@ -0,0 +1,47 @@
+use super::traits::PageSnapshotRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{NewPageSnapshot, PageSnapshot};
+use crate::schema::page_snapshots;
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqlitePageSnapshotRepository {
+    db: Database,
+}
+
+impl SqlitePageSnapshotRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl PageSnapshotRepository for SqlitePageSnapshotRepository {
+    async fn upsert(&self, snapshot: &NewPageSnapshot) -> Result<PageSnapshot, ApiError> {
+        let snapshot = snapshot.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(page_snapshots::table)
+                    .values(&snapshot)
+                    .on_conflict(page_snapshots::content_id)
+                    .do_update()
+                    .set(&snapshot)
+                    .returning(PageSnapshot::as_returning())
+                    .get_result::<PageSnapshot>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn find_by_content_id(&self, content_id: i32) -> Result<Option<PageSnapshot>, ApiError> {
+        let mut conn = self.db.read();
+        let result = page_snapshots::table
+            .find(content_id)
+            .select(PageSnapshot::as_select())
+            .first::<PageSnapshot>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+}
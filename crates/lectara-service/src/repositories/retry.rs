@@ -0,0 +1,84 @@
+//! Retrying writes that find the database locked.
+//!
+//! SQLite allows one writer at a time per database file, so a write can
+//! fail with `SQLITE_BUSY` (or `SQLITE_LOCKED`) while another process, such
+//! as a backup or the CLI, holds the lock. Those writes are retried a few
+//! times with jittered exponential backoff before giving up with
+//! [`ApiError::Busy`]. The retries run on the database writer thread, so
+//! the writes queued behind one wait with it.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::sqlite::SqliteConnection;
+use rand::Rng;
+use tracing::{debug, warn};
+
+use crate::database::lock;
+use crate::errors::ApiError;
+
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled for each one after it
+const BASE_DELAY: Duration = Duration::from_millis(20);
+
+/// Runs `write` on the connection, retrying it while the database is
+/// locked. The connection is released while waiting so reads in this
+/// process aren't held up too.
+pub(crate) fn write_with_retry<T>(
+    db: &Mutex<SqliteConnection>,
+    mut write: impl FnMut(&mut SqliteConnection) -> Result<T, ApiError>,
+) -> Result<T, ApiError> {
+    let mut attempt = 1;
+    loop {
+        let result = write(&mut lock(db));
+        match result {
+            Err(err) if is_busy(&err) => {
+                if attempt == MAX_ATTEMPTS {
+                    warn!(attempts = attempt, "Database still locked, giving up");
+                    return Err(ApiError::Busy);
+                }
+                let delay = backoff(attempt);
+                debug!(attempt, ?delay, "Database locked, retrying write");
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether `err` is SQLite reporting the database or a table as locked
+fn is_busy(err: &ApiError) -> bool {
+    match err {
+        ApiError::DatabaseError(DieselError::DatabaseError(DatabaseErrorKind::Unknown, info)) => {
+            let message = info.message();
+            message.contains("database is locked") || message.contains("database table is locked")
+        }
+        _ => false,
+    }
+}
+
+/// Random delay between half and all of the exponential backoff, so
+/// writers that collided don't retry in lockstep
+fn backoff(attempt: u32) -> Duration {
+    let max = BASE_DELAY * 2u32.pow(attempt - 1);
+    max.mul_f64(rand::rng().random_range(0.5..=1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_within_bounds() {
+        for attempt in 1..MAX_ATTEMPTS {
+            let max = BASE_DELAY * 2u32.pow(attempt - 1);
+            let delay = backoff(attempt);
+            assert!(
+                delay >= max / 2 && delay <= max,
+                "{delay:?} for attempt {attempt}"
+            );
+        }
+    }
+}
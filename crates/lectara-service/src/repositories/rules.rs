@@ -0,0 +1,76 @@
+use super::traits::RuleRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{NewRule, Rule};
+use crate::schema::rules;
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteRuleRepository {
+    db: Database,
+}
+
+impl SqliteRuleRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl RuleRepository for SqliteRuleRepository {
+    async fn create(&self, rule: &NewRule) -> Result<Rule, ApiError> {
+        let rule = rule.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(rules::table)
+                    .values(&rule)
+                    .returning(Rule::as_returning())
+                    .get_result::<Rule>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn list(&self) -> Result<Vec<Rule>, ApiError> {
+        let mut conn = self.db.read();
+        let result = rules::table
+            .order((rules::position.asc(), rules::id.asc()))
+            .select(Rule::as_select())
+            .load::<Rule>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Rule>, ApiError> {
+        let mut conn = self.db.read();
+        let result = rules::table
+            .find(id)
+            .select(Rule::as_select())
+            .first::<Rule>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn update(&self, id: i32, rule: &NewRule) -> Result<Option<Rule>, ApiError> {
+        let rule = rule.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::update(rules::table.find(id))
+                    .set(&rule)
+                    .returning(Rule::as_returning())
+                    .get_result::<Rule>(conn)
+                    .optional()?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, ApiError> {
+        self.db
+            .write(move |conn| {
+                let deleted = diesel::delete(rules::table.find(id)).execute(conn)?;
+                Ok(deleted > 0)
+            })
+            .await
+    }
+}
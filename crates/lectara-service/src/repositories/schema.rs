@@ -0,0 +1,28 @@
+use super::traits::SchemaRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::migrations::{self, MigrationStatus};
+use async_trait::async_trait;
+use tracing::error;
+
+#[derive(Clone)]
+pub struct SqliteSchemaRepository {
+    db: Database,
+}
+
+impl SqliteSchemaRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl SchemaRepository for SqliteSchemaRepository {
+    async fn migration_status(&self) -> Result<MigrationStatus, ApiError> {
+        let mut conn = self.db.read();
+        migrations::migration_status(&mut conn).map_err(|err| {
+            error!(error = %err, "Failed to read migration status");
+            ApiError::InternalError
+        })
+    }
+}
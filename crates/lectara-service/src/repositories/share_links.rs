@@ -0,0 +1,120 @@
+use super::traits::ShareLinkRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{NewShareLink, ShareLink};
+use crate::schema::share_links;
+use async_trait::async_trait;
+use diesel::prelude::*;
+
+#[derive(Clone)]
+pub struct SqliteShareLinkRepository {
+    db: Database,
+}
+
+impl SqliteShareLinkRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl ShareLinkRepository for SqliteShareLinkRepository {
+    async fn create(&self, share_link: &NewShareLink) -> Result<ShareLink, ApiError> {
+        let share_link = share_link.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(share_links::table)
+                    .values(&share_link)
+                    .returning(share_links::all_columns)
+                    .get_result::<ShareLink>(conn)?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<ShareLink>, ApiError> {
+        let mut conn = self.db.read();
+        let result = share_links::table
+            .filter(share_links::token.eq(token))
+            .first::<ShareLink>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<ShareLink>, ApiError> {
+        let mut conn = self.db.read();
+        let result = share_links::table
+            .filter(share_links::content_id.eq(content_id))
+            .order((share_links::created_at.desc(), share_links::id.desc()))
+            .load::<ShareLink>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn revoke(&self, content_id: i32, token: &str) -> Result<Option<ShareLink>, ApiError> {
+        let token = token.to_string();
+        self.db
+            .write(move |conn| {
+                let now = chrono::Utc::now().naive_utc();
+
+                // Revoking an already revoked link keeps the original revocation time
+                diesel::update(
+                    share_links::table
+                        .filter(share_links::content_id.eq(content_id))
+                        .filter(share_links::token.eq(&token))
+                        .filter(share_links::revoked_at.is_null()),
+                )
+                .set(share_links::revoked_at.eq(now))
+                .execute(conn)?;
+
+                let result = share_links::table
+                    .filter(share_links::content_id.eq(content_id))
+                    .filter(share_links::token.eq(&token))
+                    .first::<ShareLink>(conn)
+                    .optional()?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn record_failed_attempt(
+        &self,
+        id: i32,
+        now: chrono::NaiveDateTime,
+        window_start: chrono::NaiveDateTime,
+    ) -> Result<ShareLink, ApiError> {
+        self.db
+            .write(move |conn| {
+                let result = conn.transaction(|conn| {
+                    let link = share_links::table.find(id).first::<ShareLink>(conn)?;
+                    let recent = link
+                        .last_failed_at
+                        .is_some_and(|last_failed_at| last_failed_at >= window_start);
+                    let failed_attempts = if recent { link.failed_attempts + 1 } else { 1 };
+
+                    diesel::update(share_links::table.find(id))
+                        .set((
+                            share_links::failed_attempts.eq(failed_attempts),
+                            share_links::last_failed_at.eq(now),
+                        ))
+                        .returning(share_links::all_columns)
+                        .get_result::<ShareLink>(conn)
+                })?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn clear_failed_attempts(&self, id: i32) -> Result<(), ApiError> {
+        self.db
+            .write(move |conn| {
+                diesel::update(share_links::table.find(id))
+                    .set((
+                        share_links::failed_attempts.eq(0),
+                        share_links::last_failed_at.eq(None::<chrono::NaiveDateTime>),
+                    ))
+                    .execute(conn)?;
+                Ok(())
+            })
+            .await
+    }
+}
@@ -1,26 +1,301 @@
+use crate::database::CollectionVersion;
 use crate::errors::ApiError;
-use crate::models::{ContentItem, NewContentItem};
+use crate::migrations::MigrationStatus;
+use crate::models::{
+    ArchiveSnapshot, Citation, Comment, ContentItem, ContentItemSummary, FetchAttempt, Follower,
+    MetadataEdit, MetadataPatch, NewArchiveSnapshot, NewCitation, NewComment, NewContentItem,
+    NewFetchAttempt, NewFollower, NewNotification, NewPageSnapshot, NewRule, NewShareLink, NewView,
+    Notification, PageSnapshot, Rule, ShareLink, View,
+};
+use crate::search::{SearchLanguage, SearchQuery};
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Filters shared by the list, count, and exists queries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ContentFilter {
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    pub content_type: Option<String>,
+    pub source: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct ListContentParams {
     pub limit: Option<u32>,
     pub offset: Option<u32>,
-    pub since: Option<NaiveDateTime>,
-    pub until: Option<NaiveDateTime>,
+    pub filter: ContentFilter,
+    /// Whether to also count all items matching the filter
+    pub include_total: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchContentParams {
+    pub query: SearchQuery,
+    pub language: SearchLanguage,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    pub filter: ContentFilter,
 }
 
 #[derive(Debug, Clone)]
 pub struct ListContentResult {
-    pub items: Vec<ContentItem>,
-    pub total: u64,
+    pub items: Vec<ContentItemSummary>,
+    /// `None` when the total was not requested
+    pub total: Option<u64>,
+}
+
+/// The items either side of one in the list, which is newest first
+#[derive(Debug, Clone, Default)]
+pub struct AdjacentItems {
+    pub newer: Option<ContentItemSummary>,
+    pub older: Option<ContentItemSummary>,
+}
+
+/// What a bulk update does to each selected item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BulkAction {
+    Delete,
+    Archive,
+    MarkRead,
+}
+
+/// The items a bulk update applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkSelection {
+    Ids(Vec<i32>),
+    /// Items saved at or before `until` whose host is `domain` or one of its
+    /// subdomains; a missing criterion matches everything
+    Filter {
+        domain: Option<String>,
+        until: Option<NaiveDateTime>,
+    },
+}
+
+/// How many indexed items contain each of a set of terms
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentFrequencies {
+    /// Number of indexed items
+    pub documents: u64,
+    /// Terms found in no item are left out
+    pub terms: HashMap<String, u64>,
 }
 
 #[async_trait]
 pub trait ContentRepository: Clone + Send + Sync + 'static {
+    /// Version of the database the items live in, which changes after any
+    /// write to it
+    fn version(&self) -> CollectionVersion;
     async fn find_by_url(&self, url: &str) -> Result<Option<ContentItem>, ApiError>;
     async fn create(&self, content: &NewContentItem) -> Result<ContentItem, ApiError>;
+    /// Creates every item in one transaction with the same idempotency rules
+    /// as `create`; a conflict on any item rolls back the whole batch
+    async fn create_many(&self, contents: &[NewContentItem]) -> Result<Vec<ContentItem>, ApiError>;
+    /// Items matching any of the (already normalized) URLs, ordered by id
+    async fn find_by_urls(&self, urls: &[String]) -> Result<Vec<ContentItemSummary>, ApiError>;
     async fn find_by_id(&self, id: i32) -> Result<Option<ContentItem>, ApiError>;
+    /// Fills the fields the item doesn't have yet from `patch`, recording them
+    /// as enriched; `None` if the item doesn't exist
+    async fn apply_enrichment(
+        &self,
+        id: i32,
+        patch: &MetadataPatch,
+    ) -> Result<Option<ContentItem>, ApiError>;
+    /// Replaces the fields given in `edit`; `None` if the item doesn't exist
+    async fn edit(&self, id: i32, edit: &MetadataEdit) -> Result<Option<ContentItem>, ApiError>;
+    async fn find_by_ids(&self, ids: &[i32]) -> Result<Vec<ContentItemSummary>, ApiError>;
     async fn list(&self, params: &ListContentParams) -> Result<ListContentResult, ApiError>;
+    /// The items matching `filter` saved just before and after the item
+    /// saved at `created_at` with `id`, ignoring pins
+    async fn adjacent(
+        &self,
+        id: i32,
+        created_at: NaiveDateTime,
+        filter: &ContentFilter,
+    ) -> Result<AdjacentItems, ApiError>;
+    /// Every item matching `filter`, oldest first, for exports
+    async fn list_all(&self, filter: &ContentFilter) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// The most recently saved items within `filter`, newest first, ignoring
+    /// pins
+    async fn list_recent(
+        &self,
+        filter: &ContentFilter,
+        limit: u32,
+    ) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// The most recent items with an audio enclosure, newest first
+    async fn list_episodes(&self, limit: u32) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// Items that are PDF or EPUB files, newest first
+    async fn list_documents(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// Items matching a full-text query within `filter`, best match first
+    async fn search(
+        &self,
+        params: &SearchContentParams,
+    ) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// Document counts of `terms` in the unstemmed search index; terms must
+    /// already be folded the way the index folds them
+    async fn document_frequencies(&self, terms: &[String])
+    -> Result<DocumentFrequencies, ApiError>;
+    async fn count(&self, filter: &ContentFilter) -> Result<u64, ApiError>;
+    async fn exists(&self, filter: &ContentFilter) -> Result<bool, ApiError>;
+    /// Pinned items in their pinned order
+    async fn list_pinned(&self) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// Pins the item after the already pinned ones, leaving it in place if
+    /// it already is, and returns the pinned items; `None` if the item
+    /// doesn't exist. Fails once `max_pins` items are pinned.
+    async fn pin(
+        &self,
+        id: i32,
+        max_pins: usize,
+    ) -> Result<Option<Vec<ContentItemSummary>>, ApiError>;
+    /// Unpins the item and returns the remaining pinned items; `None` if the
+    /// item doesn't exist
+    async fn unpin(&self, id: i32) -> Result<Option<Vec<ContentItemSummary>>, ApiError>;
+    /// Puts the pinned items in the order of `ids`, which must list each of
+    /// them exactly once
+    async fn reorder_pins(&self, ids: &[i32]) -> Result<Vec<ContentItemSummary>, ApiError>;
+    /// Applies `action` to every selected item in one transaction, returning
+    /// how many were selected. With `dry_run` nothing is changed.
+    async fn bulk_update(
+        &self,
+        action: BulkAction,
+        selection: &BulkSelection,
+        dry_run: bool,
+    ) -> Result<u64, ApiError>;
+}
+
+#[async_trait]
+pub trait ShareLinkRepository: Clone + Send + Sync + 'static {
+    async fn create(&self, share_link: &NewShareLink) -> Result<ShareLink, ApiError>;
+    async fn find_by_token(&self, token: &str) -> Result<Option<ShareLink>, ApiError>;
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<ShareLink>, ApiError>;
+    /// Marks the link as revoked, returning `None` if no link matches
+    async fn revoke(&self, content_id: i32, token: &str) -> Result<Option<ShareLink>, ApiError>;
+    /// Counts a wrong passphrase entered at `now`, starting a new count if
+    /// the last failure was before `window_start`
+    async fn record_failed_attempt(
+        &self,
+        id: i32,
+        now: chrono::NaiveDateTime,
+        window_start: chrono::NaiveDateTime,
+    ) -> Result<ShareLink, ApiError>;
+    /// Clears the failure count after a correct passphrase
+    async fn clear_failed_attempts(&self, id: i32) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+pub trait SchemaRepository: Clone + Send + Sync + 'static {
+    async fn migration_status(&self) -> Result<MigrationStatus, ApiError>;
+}
+
+#[async_trait]
+pub trait CitationRepository: Clone + Send + Sync + 'static {
+    /// Stores citation metadata for an item, replacing any earlier lookup
+    async fn upsert(&self, citation: &NewCitation) -> Result<Citation, ApiError>;
+    async fn find_by_content_id(&self, content_id: i32) -> Result<Option<Citation>, ApiError>;
+    /// Items saved within `filter` that have citation metadata, oldest first
+    async fn list_with_items(
+        &self,
+        filter: &ContentFilter,
+    ) -> Result<Vec<(ContentItemSummary, Citation)>, ApiError>;
+}
+
+#[async_trait]
+pub trait FetchAttemptRepository: Clone + Send + Sync + 'static {
+    /// Stores the outcome of a fetch, replacing the previous one of its kind
+    async fn record(&self, attempt: &NewFetchAttempt) -> Result<FetchAttempt, ApiError>;
+    async fn find(&self, content_id: i32, kind: &str) -> Result<Option<FetchAttempt>, ApiError>;
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<FetchAttempt>, ApiError>;
+    /// Failed fetches whose retry is due at `now`, longest waiting first
+    async fn list_due(&self, now: NaiveDateTime, limit: u32)
+    -> Result<Vec<FetchAttempt>, ApiError>;
+}
+
+#[async_trait]
+pub trait PageSnapshotRepository: Clone + Send + Sync + 'static {
+    /// Stores the latest snapshot of an item's page, replacing the previous one
+    async fn upsert(&self, snapshot: &NewPageSnapshot) -> Result<PageSnapshot, ApiError>;
+    async fn find_by_content_id(&self, content_id: i32) -> Result<Option<PageSnapshot>, ApiError>;
+}
+
+#[async_trait]
+pub trait ArchiveSnapshotRepository: Clone + Send + Sync + 'static {
+    /// Stores a capture, keeping `payload` once however many captures share it
+    async fn create(
+        &self,
+        snapshot: &NewArchiveSnapshot,
+        payload: &[u8],
+    ) -> Result<ArchiveSnapshot, ApiError>;
+    async fn find_by_id(&self, id: i32) -> Result<Option<ArchiveSnapshot>, ApiError>;
+    /// The item's captures, newest first
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<ArchiveSnapshot>, ApiError>;
+    async fn find_payload(&self, digest: &str) -> Result<Option<Vec<u8>>, ApiError>;
+    /// Deletes a capture, and its payload once no other capture uses it;
+    /// `false` if it didn't exist
+    async fn delete(&self, id: i32) -> Result<bool, ApiError>;
+}
+
+#[async_trait]
+pub trait NotificationRepository: Clone + Send + Sync + 'static {
+    async fn create(&self, notification: &NewNotification) -> Result<Notification, ApiError>;
+    /// Newest first, only unread ones with `unread_only`
+    async fn list(&self, unread_only: bool, limit: u32) -> Result<Vec<Notification>, ApiError>;
+    async fn count_unread(&self) -> Result<u64, ApiError>;
+    /// Marks the notifications in `ids`, or all of them when `None`, as read
+    /// at `now`, returning how many were unread
+    async fn mark_read(&self, ids: Option<&[i32]>, now: NaiveDateTime) -> Result<u64, ApiError>;
+}
+
+#[async_trait]
+pub trait RuleRepository: Clone + Send + Sync + 'static {
+    async fn create(&self, rule: &NewRule) -> Result<Rule, ApiError>;
+    /// Every rule, in the order they run
+    async fn list(&self) -> Result<Vec<Rule>, ApiError>;
+    async fn find_by_id(&self, id: i32) -> Result<Option<Rule>, ApiError>;
+    /// Replaces a rule, returning `None` if it doesn't exist
+    async fn update(&self, id: i32, rule: &NewRule) -> Result<Option<Rule>, ApiError>;
+    /// `false` if the rule didn't exist
+    async fn delete(&self, id: i32) -> Result<bool, ApiError>;
+}
+
+#[async_trait]
+pub trait ViewRepository: Clone + Send + Sync + 'static {
+    /// Fails with a conflict if a view of the same name exists
+    async fn create(&self, view: &NewView) -> Result<View, ApiError>;
+    /// Every view, by name
+    async fn list(&self) -> Result<Vec<View>, ApiError>;
+    async fn find_by_name(&self, name: &str) -> Result<Option<View>, ApiError>;
+    /// Replaces the filters of the view named like `view`, returning `None`
+    /// if there is none
+    async fn update(&self, view: &NewView) -> Result<Option<View>, ApiError>;
+    /// `false` if the view didn't exist
+    async fn delete(&self, name: &str) -> Result<bool, ApiError>;
+}
+
+#[async_trait]
+pub trait CommentRepository: Clone + Send + Sync + 'static {
+    async fn create(&self, comment: &NewComment) -> Result<Comment, ApiError>;
+    /// Every comment on an item, oldest first
+    async fn list_for_content(&self, content_id: i32) -> Result<Vec<Comment>, ApiError>;
+    async fn find_by_id(&self, id: i32) -> Result<Option<Comment>, ApiError>;
+    /// Deletes a comment on the item along with the replies to it, `false`
+    /// if the item has no such comment
+    async fn delete(&self, content_id: i32, id: i32) -> Result<bool, ApiError>;
+}
+
+#[async_trait]
+pub trait FollowerRepository: Clone + Send + Sync + 'static {
+    /// Records a follower, updating the inbox of one that follows already
+    async fn add(&self, follower: &NewFollower) -> Result<Follower, ApiError>;
+    /// `false` if the actor wasn't following
+    async fn remove(&self, actor_id: &str) -> Result<bool, ApiError>;
+    /// The inboxes to deliver to, each once even if shared by followers
+    async fn inboxes(&self) -> Result<Vec<String>, ApiError>;
+    async fn count(&self) -> Result<u64, ApiError>;
 }
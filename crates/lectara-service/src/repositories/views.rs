@@ -0,0 +1,85 @@
+use super::traits::ViewRepository;
+use crate::database::Database;
+use crate::errors::ApiError;
+use crate::models::{NewView, View};
+use crate::schema::views;
+use async_trait::async_trait;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+
+#[derive(Clone)]
+pub struct SqliteViewRepository {
+    db: Database,
+}
+
+impl SqliteViewRepository {
+    pub fn new(db: impl Into<Database>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl ViewRepository for SqliteViewRepository {
+    async fn create(&self, view: &NewView) -> Result<View, ApiError> {
+        let view = view.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::insert_into(views::table)
+                    .values(&view)
+                    .returning(View::as_returning())
+                    .get_result::<View>(conn);
+                match result {
+                    Ok(view) => Ok(view),
+                    Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(
+                        ApiError::Conflict(format!("A view named '{}' already exists", view.name)),
+                    ),
+                    Err(err) => Err(err.into()),
+                }
+            })
+            .await
+    }
+
+    async fn list(&self) -> Result<Vec<View>, ApiError> {
+        let mut conn = self.db.read();
+        let result = views::table
+            .order(views::name.asc())
+            .select(View::as_select())
+            .load::<View>(&mut *conn)?;
+        Ok(result)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<View>, ApiError> {
+        let mut conn = self.db.read();
+        let result = views::table
+            .filter(views::name.eq(name))
+            .select(View::as_select())
+            .first::<View>(&mut *conn)
+            .optional()?;
+        Ok(result)
+    }
+
+    async fn update(&self, view: &NewView) -> Result<Option<View>, ApiError> {
+        let view = view.clone();
+        self.db
+            .write(move |conn| {
+                let result = diesel::update(views::table.filter(views::name.eq(&view.name)))
+                    .set(&view)
+                    .returning(View::as_returning())
+                    .get_result::<View>(conn)
+                    .optional()?;
+                Ok(result)
+            })
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool, ApiError> {
+        let name = name.to_string();
+        self.db
+            .write(move |conn| {
+                let deleted =
+                    diesel::delete(views::table.filter(views::name.eq(&name))).execute(conn)?;
+                Ok(deleted > 0)
+            })
+            .await
+    }
+}
@@ -0,0 +1,48 @@
+use axum::{Router, extract::State, response::Json as ResponseJson, routing::get};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::AppState;
+use crate::activity::ActivityEntry;
+use crate::errors::ApiError;
+use crate::routes::extract::ApiQuery;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+struct ActivityQuery {
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ActivityResponse {
+    items: Vec<ActivityEntry>,
+}
+
+#[instrument(skip_all, fields(limit = query.limit))]
+async fn list_activity<S: AppState>(
+    State(state): State<S>,
+    ApiQuery(query): ApiQuery<ActivityQuery>,
+) -> Result<ResponseJson<ActivityResponse>, ApiError> {
+    debug!("Processing activity request");
+
+    let Some(activity) = state.activity() else {
+        debug!("Activity is not shared");
+        return Err(ApiError::NotFound);
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "Limit must be between 1 and {MAX_LIMIT}"
+        )));
+    }
+
+    let items = activity.recent(limit).await;
+    Ok(ResponseJson(ActivityResponse { items }))
+}
+
+pub fn create_activity_router<S: AppState>() -> Router<S> {
+    Router::new().route("/", get(list_activity::<S>))
+}
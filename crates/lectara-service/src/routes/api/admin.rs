@@ -0,0 +1,188 @@
+use axum::{
+    Router,
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use super::v1::parse_datetime_param;
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::maintenance::MaintenanceWindow;
+use crate::metrics::HealthReport;
+use crate::read_only::ReadOnlyStatus;
+use crate::reload::Reloaded;
+
+const MAX_MAINTENANCE_MESSAGE_LENGTH: usize = 200;
+
+#[derive(Debug, Deserialize)]
+struct SetReadOnlyRequest {
+    enabled: bool,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadOnlyResponse {
+    enabled: bool,
+    reason: Option<String>,
+    since: Option<NaiveDateTime>,
+}
+
+impl From<Option<ReadOnlyStatus>> for ReadOnlyResponse {
+    fn from(status: Option<ReadOnlyStatus>) -> Self {
+        match status {
+            Some(status) => Self {
+                enabled: true,
+                reason: Some(status.reason),
+                since: Some(status.since),
+            },
+            None => Self {
+                enabled: false,
+                reason: None,
+                since: None,
+            },
+        }
+    }
+}
+
+async fn get_read_only<S: AppState>(State(state): State<S>) -> ResponseJson<ReadOnlyResponse> {
+    ResponseJson(state.read_only().status().into())
+}
+
+#[instrument(skip_all, fields(enabled = payload.enabled))]
+async fn set_read_only<S: AppState>(
+    State(state): State<S>,
+    Json(payload): Json<SetReadOnlyRequest>,
+) -> Result<ResponseJson<ReadOnlyResponse>, ApiError> {
+    let reason = payload
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+
+    if payload.enabled {
+        state.read_only().enable(reason);
+        warn!("Read-only mode enabled");
+    } else {
+        if reason.is_some() {
+            return Err(ApiError::BadRequest(
+                "'reason' only applies when enabling read-only mode".to_string(),
+            ));
+        }
+        state.read_only().disable();
+        info!("Read-only mode disabled");
+    }
+
+    Ok(ResponseJson(state.read_only().status().into()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleMaintenanceRequest {
+    starts_at: String, // ISO 8601 datetime string
+    ends_at: String,   // ISO 8601 datetime string
+    message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct MaintenanceResponse {
+    window: Option<MaintenanceWindow>,
+    /// Whether the window is in progress and the service is drained
+    active: bool,
+}
+
+fn maintenance_response<S: AppState>(state: &S) -> MaintenanceResponse {
+    let now = chrono::Utc::now().naive_utc();
+    let window = state.maintenance().current(now);
+    MaintenanceResponse {
+        active: window.as_ref().is_some_and(|window| window.is_active(now)),
+        window,
+    }
+}
+
+async fn get_maintenance<S: AppState>(State(state): State<S>) -> ResponseJson<MaintenanceResponse> {
+    ResponseJson(maintenance_response(&state))
+}
+
+#[instrument(skip_all)]
+async fn schedule_maintenance<S: AppState>(
+    State(state): State<S>,
+    Json(payload): Json<ScheduleMaintenanceRequest>,
+) -> Result<ResponseJson<MaintenanceResponse>, ApiError> {
+    let starts_at = parse_datetime_param("starts_at", &payload.starts_at)?;
+    let ends_at = parse_datetime_param("ends_at", &payload.ends_at)?;
+
+    if ends_at <= starts_at {
+        return Err(ApiError::BadRequest(
+            "'ends_at' must be after 'starts_at'".to_string(),
+        ));
+    }
+    if ends_at <= chrono::Utc::now().naive_utc() {
+        return Err(ApiError::BadRequest(
+            "'ends_at' must be in the future".to_string(),
+        ));
+    }
+
+    let message = payload
+        .message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+    if message
+        .as_ref()
+        .is_some_and(|message| message.chars().count() > MAX_MAINTENANCE_MESSAGE_LENGTH)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "'message' must be at most {MAX_MAINTENANCE_MESSAGE_LENGTH} characters"
+        )));
+    }
+
+    state.maintenance().schedule(MaintenanceWindow {
+        starts_at,
+        ends_at,
+        message,
+    });
+    warn!(%starts_at, %ends_at, "Maintenance window scheduled");
+
+    Ok(ResponseJson(maintenance_response(&state)))
+}
+
+#[instrument(skip_all)]
+async fn cancel_maintenance<S: AppState>(State(state): State<S>) -> StatusCode {
+    state.maintenance().cancel();
+    info!("Maintenance window cancelled");
+    StatusCode::NO_CONTENT
+}
+
+/// Re-reads the settings that change without a restart, as SIGHUP does
+#[instrument(skip_all)]
+async fn reload<S: AppState>(State(state): State<S>) -> Result<ResponseJson<Reloaded>, ApiError> {
+    let reloaded = state.reloader().reload().map_err(|err| {
+        warn!(error = %err, "Reload failed, keeping the current settings");
+        ApiError::BadRequest(err.to_string())
+    })?;
+    info!(log_filter = %reloaded.log_filter, credential_sites = reloaded.credential_sites, "Settings reloaded");
+    Ok(ResponseJson(reloaded))
+}
+
+/// p50/p95 latencies and error rates per endpoint over the sliding window
+async fn health_report<S: AppState>(State(state): State<S>) -> ResponseJson<HealthReport> {
+    ResponseJson(state.metrics().report())
+}
+
+pub fn create_admin_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/health-report", get(health_report::<S>))
+        .route(
+            "/read-only",
+            get(get_read_only::<S>).put(set_read_only::<S>),
+        )
+        .route(
+            "/maintenance",
+            get(get_maintenance::<S>)
+                .put(schedule_maintenance::<S>)
+                .delete(cancel_maintenance::<S>),
+        )
+        .route("/reload", post(reload::<S>))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::comments;
+use crate::errors::ApiError;
+use crate::models::{Comment, NewComment};
+use crate::read_only::Writable;
+use crate::repositories::{CommentRepository, ContentRepository};
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CreateCommentRequest {
+    author: String,
+    body: String,
+    /// The comment this one replies to
+    parent_id: Option<i32>,
+}
+
+#[instrument(skip_all, fields(id = %id))]
+pub(super) async fn list_comments<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<Vec<Comment>>, ApiError> {
+    debug!("Processing list comments request");
+
+    if state.content_repo().find_by_id(id).await?.is_none() {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(ResponseJson(
+        state.comment_repo().list_for_content(id).await?,
+    ))
+}
+
+#[instrument(skip_all, fields(id = %id, reply = request.parent_id.is_some()))]
+pub(super) async fn create_comment<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<(StatusCode, ResponseJson<Comment>), ApiError> {
+    debug!("Processing create comment request");
+
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+
+    let comment = NewComment {
+        content_id: id,
+        parent_id: request.parent_id,
+        author: request.author,
+        body: request.body,
+    };
+    let comment = comments::post(&state, &item, comment).await?;
+
+    Ok((StatusCode::CREATED, ResponseJson(comment)))
+}
+
+#[instrument(skip_all, fields(id = %id, comment_id = %comment_id))]
+pub(super) async fn delete_comment<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path((id, comment_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing delete comment request");
+
+    if state.comment_repo().delete(id, comment_id).await? {
+        info!("Deleted comment and its replies");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        debug!("Comment not found");
+        Err(ApiError::NotFound)
+    }
+}
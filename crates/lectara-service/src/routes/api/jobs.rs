@@ -0,0 +1,45 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::jobs::JobProgress;
+use crate::read_only::Writable;
+
+#[instrument(skip_all, fields(id = %id))]
+async fn get_job<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<u64>,
+) -> Result<ResponseJson<JobProgress>, ApiError> {
+    debug!("Processing get job request");
+
+    state
+        .jobs()
+        .get(id)
+        .map(ResponseJson)
+        .ok_or(ApiError::NotFound)
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn cancel_job<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<u64>,
+) -> Result<ResponseJson<JobProgress>, ApiError> {
+    debug!("Processing cancel job request");
+
+    let job = state.jobs().cancel(id).ok_or(ApiError::NotFound)?;
+    info!(status = ?job.status, "Requested job cancellation");
+    Ok(ResponseJson(job))
+}
+
+pub fn create_jobs_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/{id}", get(get_job::<S>))
+        .route("/{id}/cancel", post(cancel_job::<S>))
+}
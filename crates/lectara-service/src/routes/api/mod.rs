@@ -1,8 +1,28 @@
 use crate::AppState;
 use axum::Router;
 
+pub mod activity;
+pub mod admin;
+pub mod comments;
+pub mod jobs;
+pub mod notifications;
+pub mod rules;
+pub mod snapshots;
 pub mod v1;
+pub mod versioning;
+pub mod views;
+pub mod zotero;
+
+/// Newest major version of the API, bumped for changes that break clients
+pub const API_VERSION: u32 = versioning::V1.version;
 
 pub fn create_api_router<S: AppState>() -> Router<S> {
-    Router::new().nest("/v1", v1::create_api_v1_router())
+    Router::new().nest("/v1", versioning::V1.apply(v1::create_api_v1_router()))
+}
+
+pub fn create_tenant_api_router<S: AppState>() -> Router<S> {
+    Router::new().nest(
+        "/v1",
+        versioning::V1.apply(v1::create_api_v1_tenant_router()),
+    )
 }
@@ -0,0 +1,162 @@
+use std::convert::Infallible;
+
+use axum::{
+    Router,
+    extract::{Json, State},
+    response::{
+        Json as ResponseJson,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use chrono::Utc;
+use futures_util::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::Notification;
+use crate::notifications::ContentEvent;
+use crate::read_only::Writable;
+use crate::repositories::NotificationRepository;
+use crate::routes::extract::ApiQuery;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+struct ListNotificationsQuery {
+    #[serde(default)]
+    unread: bool,
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ListNotificationsResponse {
+    items: Vec<Notification>,
+    /// Unread notifications in total, listed or not
+    unread: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkReadRequest {
+    /// Notifications to mark read; all of them when missing
+    ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MarkReadResponse {
+    /// How many of the notifications were unread
+    marked: u64,
+}
+
+#[instrument(skip_all, fields(unread = query.unread, limit = query.limit))]
+async fn list_notifications<S: AppState>(
+    State(state): State<S>,
+    ApiQuery(query): ApiQuery<ListNotificationsQuery>,
+) -> Result<ResponseJson<ListNotificationsResponse>, ApiError> {
+    debug!("Processing list notifications request");
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "Limit must be between 1 and {MAX_LIMIT}"
+        )));
+    }
+
+    let repo = state.notification_repo();
+    let items = repo.list(query.unread, limit).await?;
+    let unread = repo.count_unread().await?;
+    Ok(ResponseJson(ListNotificationsResponse { items, unread }))
+}
+
+#[instrument(skip_all, fields(count = request.ids.as_ref().map(Vec::len)))]
+async fn mark_notifications_read<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<MarkReadRequest>,
+) -> Result<ResponseJson<MarkReadResponse>, ApiError> {
+    debug!("Processing mark notifications read request");
+
+    let marked = state
+        .notification_repo()
+        .mark_read(request.ids.as_deref(), Utc::now().naive_utc())
+        .await?;
+    info!(marked, "Marked notifications read");
+    Ok(ResponseJson(MarkReadResponse { marked }))
+}
+
+/// New notifications as server-sent `notification` events, until the client
+/// disconnects or the service shuts down
+async fn stream_notifications<S: AppState>(
+    State(state): State<S>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    debug!("Opening notification stream");
+
+    let notifications = stream::unfold(state.notifier().subscribe(), |mut receiver| async {
+        loop {
+            match receiver.recv().await {
+                Ok(notification) => return Some((notification, receiver)),
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "Notification stream fell behind");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = notifications
+        .map(|notification| {
+            let event = Event::default()
+                .event("notification")
+                .id(notification.id.to_string())
+                .json_data(&notification)
+                .expect("notifications serialize to JSON");
+            Ok(event)
+        })
+        .take_until(state.notifier().closed());
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Items as they are saved and changed, as server-sent `saved` and `updated`
+/// events (the item as JSON, `id` is the item's), until the client
+/// disconnects or the service shuts down
+pub async fn stream_content_events<S: AppState>(
+    State(state): State<S>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    debug!("Opening content event stream");
+
+    let content_events =
+        stream::unfold(state.notifier().subscribe_content(), |mut receiver| async {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!(skipped, "Content event stream fell behind");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+    let events = content_events
+        .map(|ContentEvent { kind, item }| {
+            let event = Event::default()
+                .event(kind.as_str())
+                .id(item.id.to_string())
+                .json_data(&item)
+                .expect("content items serialize to JSON");
+            Ok(event)
+        })
+        .take_until(state.notifier().closed());
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+pub fn create_notifications_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/", get(list_notifications::<S>))
+        .route("/read", post(mark_notifications_read::<S>))
+        .route("/stream", get(stream_notifications::<S>))
+}
@@ -0,0 +1,131 @@
+use axum::{
+    Router,
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{NewRule, Rule};
+use crate::read_only::Writable;
+use crate::repositories::RuleRepository;
+use crate::rules;
+
+#[derive(Debug, Deserialize)]
+struct RuleRequest {
+    name: String,
+    /// Where the rule runs among the others; after all of them when missing
+    /// from a new rule, unchanged when missing from an update
+    position: Option<i32>,
+    domain: Option<String>,
+    url_pattern: Option<String>,
+    set_content_type: Option<String>,
+    #[serde(default)]
+    skip_enrichment: bool,
+}
+
+impl RuleRequest {
+    fn into_new_rule(self, default_position: i32) -> Result<NewRule, ApiError> {
+        rules::validate(NewRule {
+            name: self.name,
+            position: self.position.unwrap_or(default_position),
+            domain: self.domain,
+            url_pattern: self.url_pattern,
+            set_content_type: self.set_content_type,
+            skip_enrichment: self.skip_enrichment,
+        })
+    }
+}
+
+async fn list_rules<S: AppState>(
+    State(state): State<S>,
+) -> Result<ResponseJson<Vec<Rule>>, ApiError> {
+    debug!("Processing list rules request");
+
+    Ok(ResponseJson(state.rule_repo().list().await?))
+}
+
+#[instrument(skip_all, fields(name = %request.name))]
+async fn create_rule<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<RuleRequest>,
+) -> Result<(StatusCode, ResponseJson<Rule>), ApiError> {
+    debug!("Processing create rule request");
+
+    let repo = state.rule_repo();
+    let last_position = repo.list().await?.last().map(|rule| rule.position);
+    let rule = request.into_new_rule(last_position.map_or(0, |position| position + 1))?;
+    let rule = repo.create(&rule).await?;
+
+    info!(id = rule.id, position = rule.position, "Created rule");
+    Ok((StatusCode::CREATED, ResponseJson(rule)))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn get_rule<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<Rule>, ApiError> {
+    debug!("Processing get rule request");
+
+    state
+        .rule_repo()
+        .find_by_id(id)
+        .await?
+        .map(ResponseJson)
+        .ok_or(ApiError::NotFound)
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn update_rule<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    Json(request): Json<RuleRequest>,
+) -> Result<ResponseJson<Rule>, ApiError> {
+    debug!("Processing update rule request");
+
+    let repo = state.rule_repo();
+    let Some(existing) = repo.find_by_id(id).await? else {
+        debug!("Rule not found");
+        return Err(ApiError::NotFound);
+    };
+    let rule = request.into_new_rule(existing.position)?;
+    let rule = repo.update(id, &rule).await?.ok_or(ApiError::NotFound)?;
+
+    info!("Updated rule");
+    Ok(ResponseJson(rule))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn delete_rule<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing delete rule request");
+
+    if state.rule_repo().delete(id).await? {
+        info!("Deleted rule");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        debug!("Rule not found");
+        Err(ApiError::NotFound)
+    }
+}
+
+pub fn create_rules_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/", get(list_rules::<S>).post(create_rule::<S>))
+        .route(
+            "/{id}",
+            get(get_rule::<S>)
+                .put(update_rule::<S>)
+                .delete(delete_rule::<S>),
+        )
+}
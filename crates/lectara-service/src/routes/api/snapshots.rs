@@ -0,0 +1,172 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json as ResponseJson},
+    routing::get,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::AppState;
+use crate::archive::{self, ArchiveMode, warc};
+use crate::errors::ApiError;
+use crate::limits::{RouteGroup, limit};
+use crate::models::ArchiveSnapshot;
+use crate::read_only::Writable;
+use crate::repositories::{ArchiveSnapshotRepository, ContentRepository};
+use crate::routes::extract::ApiQuery;
+
+#[derive(Debug, Serialize)]
+pub(super) struct SnapshotResponse {
+    id: i32,
+    content_id: i32,
+    /// URL the page was finally served from
+    url: String,
+    content_type: String,
+    /// Payload size in bytes
+    size: i32,
+    /// Hex SHA-256 of the payload
+    digest: String,
+    captured_at: NaiveDateTime,
+    /// `warc` or `single-file`
+    mode: String,
+    /// Where the capture can be downloaded as a WARC file
+    warc_url: String,
+    /// Where a single-file capture can be viewed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    view_url: Option<String>,
+}
+
+impl From<ArchiveSnapshot> for SnapshotResponse {
+    fn from(snapshot: ArchiveSnapshot) -> Self {
+        Self {
+            warc_url: format!("/api/v1/snapshots/{}/warc", snapshot.id),
+            view_url: (snapshot.mode == ArchiveMode::SingleFile.as_str())
+                .then(|| format!("/web/archive/{}", snapshot.id)),
+            mode: snapshot.mode,
+            id: snapshot.id,
+            content_id: snapshot.content_id,
+            url: snapshot.target_uri,
+            content_type: snapshot.content_type,
+            size: snapshot.payload_length,
+            digest: snapshot.payload_digest,
+            captured_at: snapshot.captured_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CaptureQuery {
+    #[serde(default)]
+    mode: ArchiveMode,
+}
+
+#[instrument(skip_all, fields(id = %id, mode = query.mode.as_str()))]
+pub(super) async fn capture_snapshot<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    ApiQuery(query): ApiQuery<CaptureQuery>,
+) -> Result<(StatusCode, ResponseJson<SnapshotResponse>), ApiError> {
+    debug!("Processing capture snapshot request");
+
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+
+    let snapshot = archive::capture(&state, &item, query.mode).await?;
+
+    Ok((StatusCode::CREATED, ResponseJson(snapshot.into())))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+pub(super) async fn list_snapshots<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<Vec<SnapshotResponse>>, ApiError> {
+    debug!("Processing list snapshots request");
+
+    if state.content_repo().find_by_id(id).await?.is_none() {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    }
+
+    let snapshots = state.archive_snapshot_repo().list_for_content(id).await?;
+
+    Ok(ResponseJson(
+        snapshots.into_iter().map(Into::into).collect(),
+    ))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn get_snapshot<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<SnapshotResponse>, ApiError> {
+    debug!("Processing get snapshot request");
+
+    state
+        .archive_snapshot_repo()
+        .find_by_id(id)
+        .await?
+        .map(|snapshot| ResponseJson(snapshot.into()))
+        .ok_or(ApiError::NotFound)
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn download_warc<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ApiError> {
+    debug!("Processing WARC download request");
+
+    let repo = state.archive_snapshot_repo();
+    let Some(snapshot) = repo.find_by_id(id).await? else {
+        debug!("Snapshot not found");
+        return Err(ApiError::NotFound);
+    };
+    let Some(payload) = repo.find_payload(&snapshot.payload_digest).await? else {
+        warn!(digest = %snapshot.payload_digest, "Snapshot payload is missing");
+        return Err(ApiError::InternalError);
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, warc::WARC_MEDIA_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"lectara-snapshot-{id}.warc\""),
+            ),
+        ],
+        warc::render(&snapshot, &payload),
+    ))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn delete_snapshot<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing delete snapshot request");
+
+    if state.archive_snapshot_repo().delete(id).await? {
+        info!("Deleted snapshot");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        debug!("Snapshot not found");
+        Err(ApiError::NotFound)
+    }
+}
+
+pub fn create_snapshots_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/{id}", get(get_snapshot::<S>).delete(delete_snapshot::<S>))
+        .route(
+            "/{id}/warc",
+            get(download_warc::<S>).layer(limit(RouteGroup::Export)),
+        )
+}
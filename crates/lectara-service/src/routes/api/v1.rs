@@ -1,18 +1,42 @@
 use axum::{
     Router,
-    extract::{Json, Path, Query, State},
-    response::Json as ResponseJson,
-    routing::{get, post},
+    extract::{DefaultBodyLimit, Json, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post},
 };
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info, instrument, warn};
 
+use super::views;
+use crate::delivery::DeliveryTarget;
+use crate::enrichment::{attempts, changes, pipeline};
 use crate::errors::ApiError;
+use crate::export::{self, bibtex, podcast};
+use crate::federation;
+use crate::import::{karakeep, rss};
+use crate::jobs::{JobHandle, JobStatus};
+use crate::keywords;
+use crate::limits::{RouteGroup, limit};
 use crate::models;
+use crate::notifications;
+use crate::passphrases;
+use crate::read_only::Writable;
+use crate::routes::extract::{
+    ApiQuery, UrlPayload, ValidatedUrlJson, Versioned, deserialize_optional_datetime,
+};
+use crate::rules;
+use crate::validation;
 use crate::{
     AppState,
-    repositories::{ContentRepository, ListContentParams},
+    repositories::{
+        BulkAction, BulkSelection, CitationRepository, ContentFilter, ContentRepository,
+        FetchAttemptRepository, ListContentParams, RuleRepository, SearchContentParams,
+        ShareLinkRepository,
+    },
+    search::{SearchLanguage, SearchQuery},
 };
 
 #[derive(Debug, serde::Deserialize)]
@@ -21,6 +45,86 @@ struct AddContentRequest {
     title: Option<String>,
     author: Option<String>,
     body: Option<String>,
+    enclosure_url: Option<String>,
+    duration_seconds: Option<u32>,
+    /// How the item is being saved, overriding [`SOURCE_HEADER`]
+    source: Option<String>,
+}
+
+impl UrlPayload for AddContentRequest {
+    fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+/// Header naming how an item is being saved, e.g. `extension`; items saved
+/// without one are recorded as saved through the `api`
+const SOURCE_HEADER: &str = "x-lectara-source";
+/// Header in which clients report their own version, e.g. `1.4.2`
+const CLIENT_VERSION_HEADER: &str = "x-lectara-client-version";
+
+/// One of the formats the import endpoints accept
+#[derive(Debug, Clone, Copy)]
+struct ImportFormat {
+    /// Source recorded for created items, and the kind of import jobs
+    source: &'static str,
+    /// Names the format in notifications
+    label: &'static str,
+}
+
+const RSS_IMPORT: ImportFormat = ImportFormat {
+    source: "import:rss",
+    label: "RSS",
+};
+const KARAKEEP_IMPORT: ImportFormat = ImportFormat {
+    source: "import:karakeep",
+    label: "Karakeep",
+};
+
+/// Whether a save created a new item or matched an identical existing one
+pub(crate) enum SaveOutcome {
+    Created(i32),
+    Existing(i32),
+}
+
+#[derive(Debug, Serialize)]
+struct CitationResponse {
+    source: String,
+    identifier: String,
+    title: Option<String>,
+    authors: Vec<String>,
+    published_on: Option<NaiveDate>,
+    container_title: Option<String>,
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+    fetched_at: NaiveDateTime,
+}
+
+impl From<models::Citation> for CitationResponse {
+    fn from(citation: models::Citation) -> Self {
+        Self {
+            authors: citation.author_names(),
+            source: citation.source,
+            identifier: citation.identifier,
+            title: citation.title,
+            published_on: citation.published_on,
+            container_title: citation.container_title,
+            abstract_text: citation.abstract_text,
+            fetched_at: citation.fetched_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ContentItemResponse {
+    #[serde(flatten)]
+    item: models::ContentItem,
+    /// Words in the body's plain text
+    word_count: usize,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    metadata: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    citation: Option<CitationResponse>,
 }
 
 #[derive(Debug, Serialize)]
@@ -32,8 +136,149 @@ struct ContentResponse {
 struct ListContentQuery {
     limit: Option<u32>,
     offset: Option<u32>,
-    since: Option<String>, // ISO 8601 datetime string
-    until: Option<String>, // ISO 8601 datetime string
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    since: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    until: Option<DateTime<Utc>>,
+    content_type: Option<String>,
+    source: Option<String>,
+    /// Saved view whose filters apply where the query gives none
+    view: Option<String>,
+    include_total: Option<bool>,
+    /// Pins the list to items created no later than this, so pages fetched
+    /// while new items are saved don't shift
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    snapshot_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchContentQuery {
+    q: String,
+    language: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    since: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    until: Option<DateTime<Utc>>,
+    content_type: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    snapshot_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountContentQuery {
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    since: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    until: Option<DateTime<Utc>>,
+    content_type: Option<String>,
+    source: Option<String>,
+    exists: Option<bool>,
+}
+
+/// Maximum number of items accepted by a single import request
+const MAX_IMPORT_SIZE: usize = 500;
+/// Maximum number of items accepted by an import run in the background
+const MAX_BACKGROUND_IMPORT_SIZE: usize = 20_000;
+/// Largest feed or export accepted by the import endpoints
+const MAX_IMPORT_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct ImportQuery {
+    /// Run the import as a background job, answering with its id
+    #[serde(default)]
+    background: bool,
+    /// Only report what importing would do, without saving anything
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ImportStatus {
+    Created,
+    Existing,
+    Conflict,
+    Invalid,
+}
+
+impl ImportStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Existing => "existing",
+            Self::Conflict => "conflict",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+/// One item of an import, checked and ready to save
+struct ImportItem {
+    /// As found in the import, before normalization
+    url: Option<String>,
+    /// The item to save, or why it can't be
+    content: Result<models::NewContentItem, String>,
+    /// Whether to archive the item once it is created
+    archived: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ImportItemResult {
+    pub(crate) url: Option<String>, // as found in the import, before normalization
+    pub(crate) id: Option<i32>,
+    pub(crate) status: ImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportResponse {
+    /// Whether the statuses are only what importing would do
+    dry_run: bool,
+    items: Vec<ImportItemResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct JobResponse {
+    job_id: u64,
+    /// Where the job's progress can be followed
+    status_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestedTagsQuery {
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct SuggestedTagsResponse {
+    id: i32,
+    suggestions: Vec<keywords::Keyword>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PodcastFeedQuery {
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    since: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    until: Option<DateTime<Utc>>,
+    content_type: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CountContentResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exists: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,192 +288,1614 @@ struct ContentSummary {
     title: Option<String>,
     author: Option<String>,
     created_at: NaiveDateTime,
+    body_hash: Option<String>,
+    enclosure_url: Option<String>,
+    duration_seconds: Option<i32>,
+    content_type: Option<String>,
+    summary: Option<String>,
+    pinned_position: Option<i32>,
+    read_at: Option<NaiveDateTime>,
+    archived_at: Option<NaiveDateTime>,
+    source: Option<String>,
+}
+
+impl From<models::ContentItemSummary> for ContentSummary {
+    fn from(item: models::ContentItemSummary) -> Self {
+        Self {
+            id: item.id,
+            url: item.url,
+            title: item.title,
+            author: item.author,
+            created_at: item.created_at,
+            body_hash: item.body_hash,
+            enclosure_url: item.enclosure_url,
+            duration_seconds: item.duration_seconds,
+            content_type: item.content_type,
+            summary: item.summary,
+            pinned_position: item.pinned_position,
+            read_at: item.read_at,
+            archived_at: item.archived_at,
+            source: item.source,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct ListContentResponse {
     items: Vec<ContentSummary>,
-    total: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<u64>,
     limit: u32,
+    /// The snapshot the items were listed from, to pass back as `snapshot_at`
+    /// when fetching the following pages
+    snapshot_at: DateTime<Utc>,
 }
 
-#[instrument(skip_all, fields(url = %payload.url, has_title = payload.title.is_some(), has_author = payload.author.is_some(), has_body = payload.body.is_some()))]
-async fn add_content<S: AppState>(
-    State(state): State<S>,
-    Json(payload): Json<AddContentRequest>,
-) -> Result<ResponseJson<ContentResponse>, ApiError> {
-    debug!("Processing content request");
+/// Maximum number of ids and URLs combined accepted by a single lookup request
+const MAX_LOOKUP_SIZE: usize = 100;
 
-    // Create and validate the content item
-    // Convert empty strings to None for body field
-    let body = payload.body.filter(|s| !s.trim().is_empty());
-    let new_content =
-        models::NewContentItem::new(payload.url, payload.title, payload.author, body)?;
-    debug!(normalized_url = %new_content.url, "URL validated and normalized");
+/// Maximum number of items pinned to the top of listings at once
+pub const MAX_PINNED_ITEMS: usize = 10;
+
+#[derive(Debug, Serialize)]
+struct PinnedItemsResponse {
+    items: Vec<ContentSummary>,
+}
+
+impl From<Vec<models::ContentItemSummary>> for PinnedItemsResponse {
+    fn from(items: Vec<models::ContentItemSummary>) -> Self {
+        Self {
+            items: items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ReorderPinsRequest {
+    ids: Vec<i32>,
+}
+
+/// Maximum number of ids accepted by a single bulk request
+const MAX_BULK_IDS: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct BulkContentRequest {
+    action: BulkAction,
+    ids: Option<Vec<i32>>,
+    filter: Option<BulkFilter>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkFilter {
+    tag: Option<String>,
+    domain: Option<String>,
+    until: Option<String>, // ISO 8601 datetime string
+}
+
+#[derive(Debug, Serialize)]
+struct BulkContentResponse {
+    action: BulkAction,
+    dry_run: bool,
+    /// Items the action applied to, or would apply to on a dry run
+    affected: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LookupContentRequest {
+    #[serde(default)]
+    ids: Vec<i32>,
+    #[serde(default)]
+    urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct UrlLookupResult {
+    url: String, // as submitted, before normalization
+    id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+struct LookupContentResponse {
+    items: Vec<ContentSummary>,
+    missing_ids: Vec<i32>,
+    urls: Vec<UrlLookupResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateShareLinkRequest {
+    expires_at: Option<String>, // ISO 8601 datetime string
+    passphrase: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareLinkResponse {
+    token: String,
+    content_id: i32,
+    share_url: String,
+    expires_at: Option<NaiveDateTime>,
+    revoked_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+    /// Whether viewers must enter a passphrase
+    protected: bool,
+}
+
+impl From<models::ShareLink> for ShareLinkResponse {
+    fn from(link: models::ShareLink) -> Self {
+        Self {
+            share_url: format!("/web/share/{}", link.token),
+            token: link.token,
+            content_id: link.content_id,
+            expires_at: link.expires_at,
+            revoked_at: link.revoked_at,
+            created_at: link.created_at,
+            protected: link.passphrase_hash.is_some(),
+        }
+    }
+}
+
+pub(super) fn parse_datetime_param(name: &str, value: &str) -> Result<NaiveDateTime, ApiError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|datetime| datetime.naive_utc())
+        .map_err(|_| {
+            ApiError::BadRequest(format!(
+                "Invalid '{name}' datetime format. Use RFC3339 format."
+            ))
+        })
+}
+
+fn parse_content_filter(
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    content_type: Option<&str>,
+    source: Option<&str>,
+) -> Result<ContentFilter, ApiError> {
+    Ok(ContentFilter {
+        since: since.map(|since| since.naive_utc()),
+        until: until.map(|until| until.naive_utc()),
+        content_type: content_type.map(str::to_string),
+        source: source.map(validation::parse_source).transpose()?,
+    })
+}
+
+/// The `until` bound narrowed to a requested snapshot, and the snapshot to
+/// report, which is the current time when none was requested
+fn pin_snapshot(
+    until: Option<DateTime<Utc>>,
+    snapshot_at: Option<DateTime<Utc>>,
+) -> (Option<DateTime<Utc>>, DateTime<Utc>) {
+    let Some(snapshot_at) = snapshot_at else {
+        // Taken before listing so nothing saved in between falls outside
+        // the snapshot. Stored timestamps are whole seconds, so this is too.
+        let now =
+            DateTime::from_timestamp(Utc::now().timestamp(), 0).expect("current time is in range");
+        return (until, now);
+    };
+    let until = until.map_or(snapshot_at, |until| until.min(snapshot_at));
+    (Some(until), snapshot_at)
+}
+
+/// The `User-Agent` and [`CLIENT_VERSION_HEADER`] a request was sent with
+fn client_info(headers: &HeaderMap) -> (Option<&str>, Option<&str>) {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    (
+        header(header::USER_AGENT.as_str()),
+        header(CLIENT_VERSION_HEADER),
+    )
+}
 
+/// Saves `new_content` unless its URL is already saved with identical
+/// metadata, applying the rules to newly created items and starting their
+/// background enrichment unless a rule skips it
+pub(crate) async fn save_content<S: AppState>(
+    state: &S,
+    new_content: &models::NewContentItem,
+) -> Result<SaveOutcome, ApiError> {
     let content_repo = state.content_repo();
 
     // Check if URL already exists
     let existing_item = content_repo.find_by_url(&new_content.url).await?;
 
     if let Some(existing) = existing_item {
+        let id = existing.id;
+
         // Check if metadata matches - if not, return error
-        if existing.title != new_content.title {
+        if let Some(conflict) = new_content.conflict_with(existing) {
+            let existing = &conflict.existing;
             warn!(
+                existing_id = id,
+                differing_fields = ?conflict.differences.iter().map(|d| d.field).collect::<Vec<_>>(),
                 existing_title = ?existing.title,
                 new_title = ?new_content.title,
-                "URL already exists with different title"
-            );
-            return Err(ApiError::DuplicateUrlDifferentMetadata);
-        }
-
-        if existing.author != new_content.author {
-            warn!(
                 existing_author = ?existing.author,
                 new_author = ?new_content.author,
-                "URL already exists with different author"
-            );
-            return Err(ApiError::DuplicateUrlDifferentMetadata);
-        }
-
-        if existing.body != new_content.body {
-            warn!(
                 existing_body_length = existing.body.as_ref().map(|b| b.len()),
                 new_body_length = new_content.body.as_ref().map(|b| b.len()),
-                "URL already exists with different body content"
+                "URL already exists with different metadata"
             );
-            return Err(ApiError::DuplicateUrlDifferentMetadata);
+            return Err(ApiError::DuplicateUrlDifferentMetadata(Box::new(conflict)));
         }
 
         // Return existing item (idempotent behavior)
-        info!(id = existing.id, "Returning existing content item");
-        let response = ContentResponse {
-            id: existing.id as u32,
-        };
-        return Ok(ResponseJson(response));
+        info!(id, "Returning existing content item");
+        return Ok(SaveOutcome::Existing(id));
     }
 
+    let rules = state.rule_repo().list().await?;
+    let outcome = rules::evaluate(&rules, &new_content.url);
+
     // Insert new item
-    let inserted_content = content_repo.create(&new_content).await?;
+    let mut inserted_content = content_repo.create(new_content).await?;
 
     info!(
         id = inserted_content.id,
+        source = ?inserted_content.source,
+        user_agent = ?inserted_content.user_agent,
+        client_version = ?inserted_content.client_version,
         "Successfully created new content item"
     );
 
-    let response = ContentResponse {
-        id: inserted_content.id as u32,
-    };
+    if !outcome.matched.is_empty() {
+        info!(id = inserted_content.id, rules = ?outcome.matched, "Applying rules");
+    }
+    if let Some(content_type) = outcome.content_type {
+        let patch = models::MetadataPatch {
+            content_type: Some(content_type),
+            ..Default::default()
+        };
+        if let Some(updated) = content_repo
+            .apply_enrichment(inserted_content.id, &patch)
+            .await?
+        {
+            inserted_content = updated;
+        }
+    }
+    state
+        .notifier()
+        .publish_content(notifications::ContentEventKind::Saved, &inserted_content);
 
-    Ok(ResponseJson(response))
+    if outcome.skip_enrichment {
+        // The pipeline would publish the item once it finished
+        let state = state.clone();
+        let item = inserted_content.clone();
+        tokio::spawn(async move { pipeline::publish(&state, &item).await });
+    } else {
+        pipeline::spawn_enrichment(state, &inserted_content);
+    }
+
+    Ok(SaveOutcome::Created(inserted_content.id))
 }
 
-#[instrument(skip_all, fields(limit = query.limit, offset = query.offset, has_since = query.since.is_some(), has_until = query.until.is_some()))]
-async fn list_content<S: AppState>(
+#[instrument(skip_all, fields(url = %request.url, has_title = request.payload.title.is_some(), has_author = request.payload.author.is_some(), has_body = request.payload.body.is_some(), has_enclosure = request.payload.enclosure_url.is_some()))]
+async fn add_content<S: AppState>(
     State(state): State<S>,
-    Query(query): Query<ListContentQuery>,
-) -> Result<ResponseJson<ListContentResponse>, ApiError> {
-    debug!("Processing list content request");
-
-    // Parse datetime strings
-    let since = if let Some(since_str) = &query.since {
-        Some(
-            DateTime::parse_from_rfc3339(since_str)
-                .map_err(|_| {
-                    ApiError::BadRequest(
-                        "Invalid 'since' datetime format. Use RFC3339 format.".to_string(),
-                    )
-                })?
-                .naive_utc(),
-        )
-    } else {
-        None
-    };
+    _: Writable,
+    headers: HeaderMap,
+    request: ValidatedUrlJson<AddContentRequest>,
+) -> Result<ResponseJson<ContentResponse>, ApiError> {
+    debug!("Processing content request");
+    let ValidatedUrlJson { url, payload } = request;
 
-    let until = if let Some(until_str) = &query.until {
-        Some(
-            DateTime::parse_from_rfc3339(until_str)
-                .map_err(|_| {
-                    ApiError::BadRequest(
-                        "Invalid 'until' datetime format. Use RFC3339 format.".to_string(),
-                    )
-                })?
-                .naive_utc(),
-        )
-    } else {
-        None
-    };
+    // Create the content item, validating its metadata
+    // Convert empty strings to None for body field
+    let body = payload.body.filter(|s| !s.trim().is_empty());
+    let source = payload
+        .source
+        .as_deref()
+        .or_else(|| headers.get(SOURCE_HEADER)?.to_str().ok())
+        .unwrap_or("api");
+    let mut new_content = models::NewContentItem::from_normalized(
+        url,
+        payload.title,
+        payload.author,
+        body,
+        state.validation(),
+    )?
+    .with_source(source)?;
+    debug!(normalized_url = %new_content.url, source = ?new_content.source, "Content item validated");
 
-    // Validate limit
-    if let Some(limit) = query.limit {
-        if limit == 0 {
+    let duration_seconds = payload
+        .duration_seconds
+        .map(|duration| {
+            i32::try_from(duration)
+                .map_err(|_| ApiError::BadRequest("'duration_seconds' is too large".to_string()))
+        })
+        .transpose()?;
+    match payload.enclosure_url {
+        Some(enclosure_url) => {
+            new_content =
+                new_content.with_enclosure(&enclosure_url, duration_seconds, state.validation())?;
+        }
+        None if duration_seconds.is_some() => {
             return Err(ApiError::BadRequest(
-                "Limit must be greater than 0".to_string(),
+                "'duration_seconds' requires 'enclosure_url'".to_string(),
             ));
         }
+        None => {}
     }
 
-    let params = ListContentParams {
-        limit: query.limit,
-        offset: query.offset,
-        since,
-        until,
-    };
+    let (user_agent, client_version) = client_info(&headers);
+    new_content = new_content.with_client(user_agent, client_version);
 
-    let content_repo = state.content_repo();
-    let result = content_repo.list(&params).await?;
+    let (SaveOutcome::Created(id) | SaveOutcome::Existing(id)) =
+        save_content(&state, &new_content).await?;
 
-    let items = result
-        .items
-        .into_iter()
-        .map(|item| ContentSummary {
-            id: item.id,
-            url: item.url,
-            title: item.title,
-            author: item.author,
-            created_at: item.created_at,
+    Ok(ResponseJson(ContentResponse { id: id as u32 }))
+}
+
+impl ImportItem {
+    fn from_feed_item<S: AppState>(
+        state: &S,
+        feed_item: rss::FeedItem,
+        user_agent: Option<&str>,
+        client_version: Option<&str>,
+    ) -> Self {
+        let url = feed_item.url.clone();
+        let content = models::NewContentItem::new_with_context(
+            feed_item.url.unwrap_or_default(),
+            feed_item.title,
+            feed_item.author,
+            None,
+            state.validation(),
+        )
+        .and_then(|new_content| match &feed_item.enclosure_url {
+            Some(enclosure_url) => new_content.with_enclosure(
+                enclosure_url,
+                feed_item.duration_seconds,
+                state.validation(),
+            ),
+            None => Ok(new_content),
         })
-        .collect();
+        .and_then(|new_content| new_content.with_source(RSS_IMPORT.source))
+        .map(|new_content| new_content.with_client(user_agent, client_version))
+        .map_err(|err| err.to_string());
+        Self {
+            url,
+            content,
+            archived: false,
+        }
+    }
 
-    let response = ListContentResponse {
-        items,
-        total: result.total,
-        limit: params.limit.unwrap_or(50),
+    /// Only link bookmarks can be saved; their tags and notes are dropped
+    fn from_bookmark<S: AppState>(
+        state: &S,
+        bookmark: karakeep::Bookmark,
+        user_agent: Option<&str>,
+        client_version: Option<&str>,
+    ) -> Self {
+        let url = match bookmark.content {
+            Some(karakeep::BookmarkContent::Link { url }) => Some(url),
+            _ => None,
+        };
+        let content = match (&url, DateTime::from_timestamp(bookmark.created_at, 0)) {
+            (None, _) => Err("Only link bookmarks can be imported".to_string()),
+            (Some(_), None) => Err(format!("Invalid createdAt: {}", bookmark.created_at)),
+            (Some(url), Some(created_at)) => models::NewContentItem::new_with_context(
+                url.clone(),
+                bookmark.title,
+                None,
+                None,
+                state.validation(),
+            )
+            .and_then(|new_content| new_content.with_source(KARAKEEP_IMPORT.source))
+            .map(|new_content| {
+                new_content
+                    .with_client(user_agent, client_version)
+                    .with_created_at(created_at.naive_utc())
+            })
+            .map_err(|err| err.to_string()),
+        };
+        Self {
+            url,
+            content,
+            archived: bookmark.archived,
+        }
+    }
+}
+
+/// Saves one imported item, reporting bad or conflicting items in the
+/// result rather than as an error
+async fn import_item<S: AppState>(
+    state: &S,
+    item: ImportItem,
+) -> Result<ImportItemResult, ApiError> {
+    let (id, status, error) = match item.content {
+        Err(error) => (None, ImportStatus::Invalid, Some(error)),
+        Ok(new_content) => match save_content(state, &new_content).await {
+            Ok(SaveOutcome::Created(id)) => {
+                if item.archived {
+                    let selection = BulkSelection::Ids(vec![id]);
+                    state
+                        .content_repo()
+                        .bulk_update(BulkAction::Archive, &selection, false)
+                        .await?;
+                }
+                (Some(id), ImportStatus::Created, None)
+            }
+            Ok(SaveOutcome::Existing(id)) => (Some(id), ImportStatus::Existing, None),
+            Err(err @ ApiError::DuplicateUrlDifferentMetadata(_)) => {
+                (None, ImportStatus::Conflict, Some(err.to_string()))
+            }
+            Err(err) => return Err(err),
+        },
     };
+    Ok(ImportItemResult {
+        url: item.url,
+        id,
+        status,
+        error,
+    })
+}
 
-    info!(
-        returned_count = response.items.len(),
-        total = response.total,
-        "Successfully retrieved content list"
-    );
+/// What [`import_item`] would do with `item`, without saving it.
+/// `created` holds the items the preview has found would be created, which
+/// later items of the same import would match instead of the database.
+async fn preview_item<S: AppState>(
+    state: &S,
+    item: ImportItem,
+    created: &mut HashMap<String, models::NewContentItem>,
+) -> Result<ImportItemResult, ApiError> {
+    let (id, status, error) = match item.content {
+        Err(error) => (None, ImportStatus::Invalid, Some(error)),
+        Ok(new_content) => {
+            if let Some(earlier) = created.get(&new_content.url) {
+                if same_metadata(earlier, &new_content) {
+                    (None, ImportStatus::Existing, None)
+                } else {
+                    let error =
+                        "An earlier item of the import has this URL with different metadata";
+                    (None, ImportStatus::Conflict, Some(error.to_string()))
+                }
+            } else {
+                match state.content_repo().find_by_url(&new_content.url).await? {
+                    Some(existing) => {
+                        let id = existing.id;
+                        match new_content.conflict_with(existing) {
+                            Some(conflict) => {
+                                let err =
+                                    ApiError::DuplicateUrlDifferentMetadata(Box::new(conflict));
+                                (None, ImportStatus::Conflict, Some(err.to_string()))
+                            }
+                            None => (Some(id), ImportStatus::Existing, None),
+                        }
+                    }
+                    None => {
+                        created.insert(new_content.url.clone(), new_content);
+                        (None, ImportStatus::Created, None)
+                    }
+                }
+            }
+        }
+    };
+    Ok(ImportItemResult {
+        url: item.url,
+        id,
+        status,
+        error,
+    })
+}
 
-    Ok(ResponseJson(response))
+/// Whether two items saved under one URL carry the same metadata, compared
+/// exactly since neither is stored yet
+fn same_metadata(a: &models::NewContentItem, b: &models::NewContentItem) -> bool {
+    a.title == b.title
+        && a.author == b.author
+        && a.body == b.body
+        && a.enclosure_url == b.enclosure_url
+        && a.duration_seconds == b.duration_seconds
 }
 
-#[instrument(skip_all, fields(id = %id))]
-async fn get_content_by_id<S: AppState>(
+fn import_summary(counts: impl Fn(ImportStatus) -> u64) -> String {
+    format!(
+        "{} created, {} already saved, {} conflicting, {} invalid",
+        counts(ImportStatus::Created),
+        counts(ImportStatus::Existing),
+        counts(ImportStatus::Conflict),
+        counts(ImportStatus::Invalid)
+    )
+}
+
+#[instrument(skip_all, fields(feed_length = body.len(), background = query.background, dry_run = query.dry_run))]
+async fn import_rss<S: AppState>(
     State(state): State<S>,
-    Path(id): Path<i32>,
-) -> Result<ResponseJson<models::ContentItem>, ApiError> {
-    debug!("Processing get content by ID request");
+    _: Writable,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<ImportQuery>,
+    body: String,
+) -> Result<Response, ApiError> {
+    debug!("Processing RSS import request");
 
-    let content_repo = state.content_repo();
-    let content = content_repo.find_by_id(id).await?;
+    let (user_agent, client_version) = client_info(&headers);
+    let items = rss::parse_items(&body)
+        .map_err(|err| ApiError::BadRequest(err.to_string()))?
+        .into_iter()
+        .map(|feed_item| ImportItem::from_feed_item(&state, feed_item, user_agent, client_version))
+        .collect();
+    run_import(state, RSS_IMPORT, &query, items).await
+}
 
-    match content {
-        Some(item) => {
-            info!(id = item.id, "Successfully retrieved content item");
-            Ok(ResponseJson(item))
-        }
-        None => {
-            debug!("Content item not found");
-            Err(ApiError::NotFound)
+#[instrument(skip_all, fields(export_length = body.len(), background = query.background, dry_run = query.dry_run))]
+async fn import_karakeep<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<ImportQuery>,
+    body: String,
+) -> Result<Response, ApiError> {
+    debug!("Processing Karakeep import request");
+
+    let (user_agent, client_version) = client_info(&headers);
+    let items = karakeep::parse_bookmarks(&body)
+        .map_err(|err| ApiError::BadRequest(err.to_string()))?
+        .into_iter()
+        .map(|bookmark| ImportItem::from_bookmark(&state, bookmark, user_agent, client_version))
+        .collect();
+    run_import(state, KARAKEEP_IMPORT, &query, items).await
+}
+
+/// Imports `items` now, previews them or starts a job importing them, as
+/// `query` asks
+async fn run_import<S: AppState>(
+    state: S,
+    format: ImportFormat,
+    query: &ImportQuery,
+    items: Vec<ImportItem>,
+) -> Result<Response, ApiError> {
+    if query.background && query.dry_run {
+        return Err(ApiError::BadRequest(
+            "A dry run can't run in the background".to_string(),
+        ));
+    }
+
+    let max_size = if query.background {
+        MAX_BACKGROUND_IMPORT_SIZE
+    } else {
+        MAX_IMPORT_SIZE
+    };
+    if items.len() > max_size {
+        return Err(ApiError::BadRequest(format!(
+            "Import accepts at most {max_size} items"
+        )));
+    }
+
+    if query.background {
+        let job = state.jobs().start(format.source, items.len() as u64);
+        let response = JobResponse {
+            job_id: job.id(),
+            status_url: format!("/api/v1/jobs/{}", job.id()),
+        };
+        info!(
+            job_id = job.id(),
+            source = format.source,
+            item_count = items.len(),
+            "Started import job"
+        );
+
+        tokio::spawn(async move { run_import_job(&state, format, job, items).await });
+        return Ok((StatusCode::ACCEPTED, ResponseJson(response)).into_response());
+    }
+
+    let mut results = Vec::with_capacity(items.len());
+    if query.dry_run {
+        let mut created = HashMap::new();
+        for item in items {
+            results.push(preview_item(&state, item, &mut created).await?);
         }
+        info!(
+            source = format.source,
+            item_count = results.len(),
+            "Previewed import"
+        );
+        return Ok(ResponseJson(ImportResponse {
+            dry_run: true,
+            items: results,
+        })
+        .into_response());
     }
-}
 
-pub fn create_api_v1_router<S: AppState>() -> Router<S> {
-    Router::new()
-        .route("/content", post(add_content::<S>).get(list_content::<S>))
-        .route("/content/{id}", get(get_content_by_id::<S>))
+    for item in items {
+        // One bad or conflicting item shouldn't fail the rest of the import
+        results.push(import_item(&state, item).await?);
+    }
+
+    info!(
+        source = format.source,
+        item_count = results.len(),
+        "Successfully imported items"
+    );
+
+    let message = format!(
+        "{} import finished: {}",
+        format.label,
+        import_summary(|status| results.iter().filter(|item| item.status == status).count() as u64)
+    );
+    notifications::notify(&state, notifications::KIND_IMPORT_FINISHED, message, None).await;
+
+    Ok(ResponseJson(ImportResponse {
+        dry_run: false,
+        items: results,
+    })
+    .into_response())
+}
+
+/// Imports `items` one by one, reporting progress through `job`
+#[instrument(skip_all, fields(job_id = job.id(), source = format.source))]
+async fn run_import_job<S: AppState>(
+    state: &S,
+    format: ImportFormat,
+    job: JobHandle,
+    items: Vec<ImportItem>,
+) {
+    let mut failure = None;
+    for item in items {
+        if job.is_cancelled() {
+            break;
+        }
+        match import_item(state, item).await {
+            Ok(ImportItemResult {
+                status,
+                error: Some(error),
+                url,
+                ..
+            }) => job.record_error(status.as_str(), url, error),
+            Ok(result) => job.record(result.status.as_str()),
+            Err(err) => {
+                warn!(error = %err, "Import job failed");
+                failure = Some(err.to_string());
+                break;
+            }
+        }
+    }
+
+    let Some(progress) = job.finish(failure) else {
+        return;
+    };
+    info!(status = ?progress.status, processed = progress.processed, "Import job ended");
+
+    let counts =
+        |status: ImportStatus| progress.outcomes.get(status.as_str()).copied().unwrap_or(0);
+    let ending = match progress.status {
+        JobStatus::Cancelled => "was cancelled",
+        JobStatus::Failed => "failed",
+        _ => "finished",
+    };
+    let message = format!(
+        "{} import job {} {ending} after {} of {} items: {}",
+        format.label,
+        progress.id,
+        progress.processed,
+        progress.total,
+        import_summary(counts)
+    );
+    notifications::notify(state, notifications::KIND_IMPORT_FINISHED, message, None).await;
+}
+
+#[instrument(skip_all, fields(limit = query.limit, offset = query.offset, view = query.view, has_since = query.since.is_some(), has_until = query.until.is_some(), has_snapshot = query.snapshot_at.is_some()))]
+async fn list_content<S: AppState>(
+    State(state): State<S>,
+    versioned: Versioned,
+    ApiQuery(query): ApiQuery<ListContentQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Processing list content request");
+
+    let view = match query.view.as_deref() {
+        Some(name) => views::view_filters(&state, name).await?,
+        None => views::ViewFilters::default(),
+    };
+    let (until, snapshot_at) = pin_snapshot(query.until.or(view.until), query.snapshot_at);
+    let filter = parse_content_filter(
+        query.since.or(view.since),
+        until,
+        query
+            .content_type
+            .as_deref()
+            .or(view.content_type.as_deref()),
+        query.source.as_deref().or(view.source.as_deref()),
+    )?;
+
+    // Validate limit
+    if let Some(limit) = query.limit
+        && limit == 0
+    {
+        return Err(ApiError::BadRequest(
+            "Limit must be greater than 0".to_string(),
+        ));
+    }
+
+    let tag = versioned.tag(&filter);
+    if let Some(not_modified) = tag.not_modified() {
+        return Ok(not_modified);
+    }
+
+    let params = ListContentParams {
+        limit: query.limit,
+        offset: query.offset,
+        filter,
+        include_total: query.include_total.unwrap_or(true),
+    };
+
+    let content_repo = state.content_repo();
+    let result = content_repo.list(&params).await?;
+
+    let items = result.items.into_iter().map(Into::into).collect();
+
+    let response = ListContentResponse {
+        items,
+        total: result.total,
+        limit: params.limit.unwrap_or(50),
+        snapshot_at,
+    };
+
+    info!(
+        returned_count = response.items.len(),
+        total = response.total,
+        "Successfully retrieved content list"
+    );
+
+    Ok((tag, ResponseJson(response)).into_response())
+}
+
+#[instrument(skip_all, fields(has_since = query.since.is_some(), has_until = query.until.is_some(), exists = query.exists))]
+async fn count_content<S: AppState>(
+    State(state): State<S>,
+    versioned: Versioned,
+    ApiQuery(query): ApiQuery<CountContentQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Processing count content request");
+
+    let filter = parse_content_filter(
+        query.since,
+        query.until,
+        query.content_type.as_deref(),
+        query.source.as_deref(),
+    )?;
+    let tag = versioned.tag(&filter);
+    if let Some(not_modified) = tag.not_modified() {
+        return Ok(not_modified);
+    }
+    let content_repo = state.content_repo();
+
+    let response = if query.exists.unwrap_or(false) {
+        CountContentResponse {
+            total: None,
+            exists: Some(content_repo.exists(&filter).await?),
+        }
+    } else {
+        CountContentResponse {
+            total: Some(content_repo.count(&filter).await?),
+            exists: None,
+        }
+    };
+
+    info!(
+        total = response.total,
+        exists = response.exists,
+        "Successfully counted content"
+    );
+
+    Ok((tag, ResponseJson(response)).into_response())
+}
+
+#[instrument(skip_all, fields(q = %query.q))]
+async fn search_content<S: AppState>(
+    State(state): State<S>,
+    versioned: Versioned,
+    ApiQuery(query): ApiQuery<SearchContentQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Processing search request");
+
+    let search_query =
+        SearchQuery::parse(&query.q).map_err(|err| ApiError::BadRequest(err.to_string()))?;
+    let language = match query.language.as_deref() {
+        Some(language) => SearchLanguage::parse(language).ok_or_else(|| {
+            ApiError::BadRequest("Language must be 'english' or 'none'".to_string())
+        })?,
+        None => SearchLanguage::default(),
+    };
+    let (until, snapshot_at) = pin_snapshot(query.until, query.snapshot_at);
+    let filter = parse_content_filter(query.since, until, query.content_type.as_deref(), None)?;
+
+    if query.limit == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Limit must be greater than 0".to_string(),
+        ));
+    }
+
+    let tag = versioned.tag(&filter);
+    if let Some(not_modified) = tag.not_modified() {
+        return Ok(not_modified);
+    }
+
+    let params = SearchContentParams {
+        query: search_query,
+        language,
+        limit: query.limit,
+        offset: query.offset,
+        filter,
+    };
+    let items = state.content_repo().search(&params).await?;
+
+    let response = ListContentResponse {
+        items: items.into_iter().map(Into::into).collect(),
+        total: None,
+        limit: params.limit.unwrap_or(50),
+        snapshot_at,
+    };
+
+    info!(
+        returned_count = response.items.len(),
+        "Successfully searched content"
+    );
+
+    Ok((tag, ResponseJson(response)).into_response())
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn get_content_by_id<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<ContentItemResponse>, ApiError> {
+    debug!("Processing get content by ID request");
+
+    let content_repo = state.content_repo();
+    let content = content_repo.find_by_id(id).await?;
+
+    match content {
+        Some(item) => {
+            let citation = state.citation_repo().find_by_content_id(item.id).await?;
+            info!(id = item.id, "Successfully retrieved content item");
+            Ok(ResponseJson(ContentItemResponse {
+                metadata: item.metadata(),
+                word_count: item.word_count(),
+                item,
+                citation: citation.map(Into::into),
+            }))
+        }
+        None => {
+            debug!("Content item not found");
+            Err(ApiError::NotFound)
+        }
+    }
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn update_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    Json(mut edit): Json<models::MetadataEdit>,
+) -> Result<ResponseJson<ContentItemResponse>, ApiError> {
+    debug!("Processing update content request");
+
+    if edit.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Give at least one of 'title', 'author' or 'body'".to_string(),
+        ));
+    }
+    validation::check_metadata(
+        &mut edit.title,
+        &mut edit.author,
+        &mut edit.body,
+        &state.validation().limits,
+    )?;
+
+    let Some(item) = state.content_repo().edit(id, &edit).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+    state
+        .notifier()
+        .publish_content(notifications::ContentEventKind::Updated, &item);
+    let citation = state.citation_repo().find_by_content_id(item.id).await?;
+
+    info!("Updated content item");
+    Ok(ResponseJson(ContentItemResponse {
+        metadata: item.metadata(),
+        word_count: item.word_count(),
+        item,
+        citation: citation.map(Into::into),
+    }))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn check_for_update<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<changes::UpdateCheck>, ApiError> {
+    debug!("Processing check update request");
+
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+
+    let check = changes::check_for_update(&state, &item).await?;
+
+    Ok(ResponseJson(check))
+}
+
+#[instrument(skip_all, fields(id = %id, limit = query.limit))]
+async fn suggest_tags<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+    Query(query): Query<SuggestedTagsQuery>,
+) -> Result<ResponseJson<SuggestedTagsResponse>, ApiError> {
+    debug!("Processing suggested tags request");
+
+    if query.limit == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Limit must be greater than 0".to_string(),
+        ));
+    }
+
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+
+    // Bodiless items still get suggestions from their title
+    let text = item.plain_text().or(item.title.as_deref()).unwrap_or("");
+    let counts = keywords::term_counts(text);
+    let terms: Vec<String> = counts.keys().cloned().collect();
+    let frequencies = state.content_repo().document_frequencies(&terms).await?;
+
+    let limit = query.limit.unwrap_or(10).min(50) as usize;
+    let suggestions = keywords::rank(&counts, &frequencies, limit);
+
+    info!(
+        suggestion_count = suggestions.len(),
+        "Successfully suggested tags"
+    );
+
+    Ok(ResponseJson(SuggestedTagsResponse { id, suggestions }))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn get_fetch_status<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<Vec<models::FetchAttempt>>, ApiError> {
+    debug!("Processing fetch status request");
+
+    if state.content_repo().find_by_id(id).await?.is_none() {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    }
+
+    let attempts = state.fetch_attempt_repo().list_for_content(id).await?;
+
+    Ok(ResponseJson(attempts))
+}
+
+#[derive(Debug, Serialize)]
+struct RetryFetchResponse {
+    retrying: Vec<String>,
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn retry_fetches<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ResponseJson<RetryFetchResponse>), ApiError> {
+    debug!("Processing retry fetches request");
+
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+
+    // Retries run in the background like the original fetches; clients poll
+    // the fetch status for the outcome
+    let mut retrying = Vec::new();
+    for attempt in state.fetch_attempt_repo().list_for_content(id).await? {
+        if attempt.status == attempts::STATUS_SUCCEEDED {
+            continue;
+        }
+        if state.enrichers().get(&attempt.kind).is_none() {
+            continue;
+        }
+
+        let state = state.clone();
+        let item = item.clone();
+        let kind = attempt.kind.clone();
+        tokio::spawn(async move {
+            if let Some(enricher) = state.enrichers().get(&kind) {
+                let input = pipeline::EnrichmentInput::new(item);
+                attempts::run_and_record(&state, enricher.as_ref(), &input).await;
+            }
+        });
+        retrying.push(attempt.kind);
+    }
+
+    info!(id, retrying = retrying.len(), "Scheduled fetch retries");
+    Ok((
+        StatusCode::ACCEPTED,
+        ResponseJson(RetryFetchResponse { retrying }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct SendContentQuery {
+    target: DeliveryTarget,
+}
+
+#[instrument(skip_all, fields(id = %id, target = ?query.target))]
+async fn send_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    ApiQuery(query): ApiQuery<SendContentQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Processing send content request");
+
+    if state.device_mailer().is_none() {
+        return Err(ApiError::BadRequest(
+            "Sending to devices is not configured".to_string(),
+        ));
+    }
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    };
+    if item.plain_text().is_none() {
+        return Err(ApiError::BadRequest(
+            "The item has no stored text to send".to_string(),
+        ));
+    }
+
+    // SMTP servers can take a while to answer, so the email is sent in the
+    // background and its outcome reported through the job
+    let target = query.target;
+    let job = state.jobs().start(target.job_kind(), 1);
+    let response = JobResponse {
+        job_id: job.id(),
+        status_url: format!("/api/v1/jobs/{}", job.id()),
+    };
+    info!(job_id = job.id(), "Started send job");
+
+    tokio::spawn(async move {
+        let Some(mailer) = state.device_mailer() else {
+            return;
+        };
+        let sent = mailer.send(&item, target).await;
+        match &sent {
+            Ok(()) => job.record("sent"),
+            Err(err) => {
+                warn!(error = %err, id = item.id, "Failed to send item to device");
+                job.record_error("failed", Some(item.url.clone()), err.to_string());
+            }
+        }
+        job.finish(None);
+
+        if let Err(err) = sent {
+            let title = item.title.as_deref().unwrap_or(&item.url);
+            notifications::notify(
+                &state,
+                notifications::KIND_SEND_FAILED,
+                format!("Failed to send {title} to your device: {err}"),
+                Some(item.id),
+            )
+            .await;
+        }
+    });
+    Ok((StatusCode::ACCEPTED, ResponseJson(response)).into_response())
+}
+
+#[instrument(skip_all)]
+async fn sync_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+) -> Result<Response, ApiError> {
+    debug!("Processing sync request");
+
+    let Some(target) = state.sync_target() else {
+        return Err(ApiError::BadRequest(
+            "Pushing to another instance is not configured".to_string(),
+        ));
+    };
+    let Some(filter) = federation::selection(&state, target.view()).await? else {
+        return Err(ApiError::BadRequest(format!(
+            "No view named '{}' selects the items to push",
+            target.view()
+        )));
+    };
+    let items = state.content_repo().list_all(&filter).await?;
+
+    let job = state.jobs().start("sync", items.len() as u64);
+    let response = JobResponse {
+        job_id: job.id(),
+        status_url: format!("/api/v1/jobs/{}", job.id()),
+    };
+    info!(job_id = job.id(), items = items.len(), "Started sync job");
+
+    tokio::spawn(async move {
+        let Some(target) = state.sync_target() else {
+            return;
+        };
+        for item in items {
+            if job.is_cancelled() {
+                break;
+            }
+            match target
+                .push(&item.url, item.title.as_deref(), item.author.as_deref())
+                .await
+            {
+                Ok(outcome) => job.record(outcome.as_str()),
+                Err(err) => {
+                    warn!(error = %err, id = item.id, "Failed to push item");
+                    job.record_error("failed", Some(item.url), err.to_string());
+                }
+            }
+        }
+        job.finish(None);
+    });
+    Ok((StatusCode::ACCEPTED, ResponseJson(response)).into_response())
+}
+
+#[instrument(skip_all, fields(id_count = payload.ids.len(), url_count = payload.urls.len()))]
+async fn lookup_content<S: AppState>(
+    State(state): State<S>,
+    Json(payload): Json<LookupContentRequest>,
+) -> Result<ResponseJson<LookupContentResponse>, ApiError> {
+    debug!("Processing lookup content request");
+
+    if payload.ids.len() + payload.urls.len() > MAX_LOOKUP_SIZE {
+        return Err(ApiError::BadRequest(format!(
+            "Lookup accepts at most {MAX_LOOKUP_SIZE} ids and URLs combined"
+        )));
+    }
+
+    let content_repo = state.content_repo();
+    let mut found: BTreeMap<i32, models::ContentItemSummary> = BTreeMap::new();
+
+    for item in content_repo.find_by_ids(&payload.ids).await? {
+        found.insert(item.id, item);
+    }
+    let missing_ids = payload
+        .ids
+        .iter()
+        .copied()
+        .filter(|id| !found.contains_key(id))
+        .collect();
+
+    // Invalid URLs can't have been saved, so they are reported as unmatched
+    // rather than failing the whole batch
+    let normalized: Vec<Option<String>> = payload
+        .urls
+        .iter()
+        .map(|url| validation::normalize_url_with(url, state.validation()).ok())
+        .collect();
+    let lookup_urls: Vec<String> = normalized.iter().flatten().cloned().collect();
+
+    let mut ids_by_url = HashMap::new();
+    for item in content_repo.find_by_urls(&lookup_urls).await? {
+        ids_by_url.insert(item.url.clone(), item.id);
+        found.insert(item.id, item);
+    }
+
+    let urls = payload
+        .urls
+        .into_iter()
+        .zip(normalized)
+        .map(|(url, normalized)| UrlLookupResult {
+            id: normalized.and_then(|normalized| ids_by_url.get(&normalized).copied()),
+            url,
+        })
+        .collect();
+
+    let response = LookupContentResponse {
+        items: found.into_values().map(Into::into).collect(),
+        missing_ids,
+        urls,
+    };
+
+    info!(
+        found_count = response.items.len(),
+        "Successfully looked up content items"
+    );
+
+    Ok(ResponseJson(response))
+}
+
+fn parse_bulk_selection(
+    ids: Option<Vec<i32>>,
+    filter: Option<BulkFilter>,
+) -> Result<BulkSelection, ApiError> {
+    match (ids, filter) {
+        (Some(ids), None) => {
+            if ids.len() > MAX_BULK_IDS {
+                return Err(ApiError::BadRequest(format!(
+                    "Bulk requests accept at most {MAX_BULK_IDS} ids"
+                )));
+            }
+            Ok(BulkSelection::Ids(ids))
+        }
+        (None, Some(filter)) => {
+            if filter.tag.is_some() {
+                return Err(ApiError::BadRequest(
+                    "Filtering by tag is not supported".to_string(),
+                ));
+            }
+            let domain = filter
+                .domain
+                .map(|domain| domain.trim().trim_end_matches('.').to_lowercase())
+                .filter(|domain| !domain.is_empty());
+            let until = filter
+                .until
+                .map(|until| parse_datetime_param("until", &until))
+                .transpose()?;
+            // An empty filter would select the whole collection
+            if domain.is_none() && until.is_none() {
+                return Err(ApiError::BadRequest(
+                    "filter must set a domain or until".to_string(),
+                ));
+            }
+            Ok(BulkSelection::Filter { domain, until })
+        }
+        _ => Err(ApiError::BadRequest(
+            "Provide either ids or a filter".to_string(),
+        )),
+    }
+}
+
+#[instrument(skip_all, fields(action = ?request.action, dry_run = request.dry_run))]
+async fn bulk_update_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<BulkContentRequest>,
+) -> Result<ResponseJson<BulkContentResponse>, ApiError> {
+    debug!("Processing bulk content request");
+
+    let selection = parse_bulk_selection(request.ids, request.filter)?;
+    let affected = state
+        .content_repo()
+        .bulk_update(request.action, &selection, request.dry_run)
+        .await?;
+
+    info!(
+        action = ?request.action,
+        affected,
+        dry_run = request.dry_run,
+        "Applied bulk content action"
+    );
+
+    Ok(ResponseJson(BulkContentResponse {
+        action: request.action,
+        dry_run: request.dry_run,
+        affected,
+    }))
+}
+
+#[instrument(skip_all, fields(has_since = query.since.is_some(), has_until = query.until.is_some()))]
+async fn export_bibtex<S: AppState>(
+    State(state): State<S>,
+    ApiQuery(query): ApiQuery<ExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    debug!("Processing BibTeX export request");
+
+    // Accepted so that a tag filter fails loudly instead of silently
+    // exporting everything
+    if query.tag.is_some() {
+        return Err(ApiError::BadRequest(
+            "Filtering by tag is not supported".to_string(),
+        ));
+    }
+
+    let filter = parse_content_filter(
+        query.since,
+        query.until,
+        query.content_type.as_deref(),
+        None,
+    )?;
+    let entries = state.citation_repo().list_with_items(&filter).await?;
+
+    info!(
+        entry_count = entries.len(),
+        "Successfully exported BibTeX entries"
+    );
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-bibtex; charset=utf-8")],
+        bibtex::render(&entries),
+    ))
+}
+
+#[instrument(skip_all, fields(has_since = query.since.is_some(), has_until = query.until.is_some()))]
+async fn export_karakeep<S: AppState>(
+    State(state): State<S>,
+    ApiQuery(query): ApiQuery<ExportQuery>,
+) -> Result<ResponseJson<export::karakeep::Export>, ApiError> {
+    debug!("Processing Karakeep export request");
+
+    if query.tag.is_some() {
+        return Err(ApiError::BadRequest(
+            "Filtering by tag is not supported".to_string(),
+        ));
+    }
+
+    let filter = parse_content_filter(
+        query.since,
+        query.until,
+        query.content_type.as_deref(),
+        None,
+    )?;
+    let items = state.content_repo().list_all(&filter).await?;
+
+    info!(
+        bookmark_count = items.len(),
+        "Successfully exported Karakeep bookmarks"
+    );
+
+    Ok(ResponseJson(export::karakeep::render(&items)))
+}
+
+#[instrument(skip_all, fields(limit = query.limit))]
+async fn export_podcast_feed<S: AppState>(
+    State(state): State<S>,
+    Query(query): Query<PodcastFeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    debug!("Processing podcast feed request");
+
+    let limit = query.limit.unwrap_or(100).min(1000);
+    let episodes = state.content_repo().list_episodes(limit).await?;
+
+    info!(
+        episode_count = episodes.len(),
+        "Successfully rendered podcast feed"
+    );
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        podcast::render(&episodes),
+    ))
+}
+
+#[instrument(skip_all, fields(
+    id = %id,
+    has_expiry = payload.expires_at.is_some(),
+    protected = payload.passphrase.is_some(),
+))]
+async fn create_share_link<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+    Json(payload): Json<CreateShareLinkRequest>,
+) -> Result<(StatusCode, ResponseJson<ShareLinkResponse>), ApiError> {
+    debug!("Processing create share link request");
+
+    let expires_at = payload
+        .expires_at
+        .as_deref()
+        .map(|expires_at| parse_datetime_param("expires_at", expires_at))
+        .transpose()?;
+
+    if let Some(expires_at) = expires_at
+        && expires_at <= chrono::Utc::now().naive_utc()
+    {
+        return Err(ApiError::BadRequest(
+            "'expires_at' must be in the future".to_string(),
+        ));
+    }
+
+    if let Some(passphrase) = &payload.passphrase {
+        passphrases::validate(passphrase).map_err(ApiError::BadRequest)?;
+    }
+
+    if state.content_repo().find_by_id(id).await?.is_none() {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    }
+
+    let mut new_link = models::NewShareLink::new(id, expires_at);
+    if let Some(passphrase) = payload.passphrase {
+        // Hashing is deliberately slow, so it stays off the async workers
+        let hash = tokio::task::spawn_blocking(move || passphrases::hash(&passphrase))
+            .await
+            .map_err(|_| ApiError::InternalError)?;
+        new_link = new_link.with_passphrase_hash(hash);
+    }
+    let link = state.share_link_repo().create(&new_link).await?;
+
+    info!(content_id = id, "Successfully created share link");
+
+    Ok((StatusCode::CREATED, ResponseJson(link.into())))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn list_share_links<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<Vec<ShareLinkResponse>>, ApiError> {
+    debug!("Processing list share links request");
+
+    if state.content_repo().find_by_id(id).await?.is_none() {
+        debug!("Content item not found");
+        return Err(ApiError::NotFound);
+    }
+
+    let links = state.share_link_repo().list_for_content(id).await?;
+
+    Ok(ResponseJson(links.into_iter().map(Into::into).collect()))
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn revoke_share_link<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path((id, token)): Path<(i32, String)>,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing revoke share link request");
+
+    match state.share_link_repo().revoke(id, &token).await? {
+        Some(_) => {
+            info!(content_id = id, "Successfully revoked share link");
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => {
+            debug!("Share link not found");
+            Err(ApiError::NotFound)
+        }
+    }
+}
+
+#[instrument(skip_all)]
+async fn list_pinned<S: AppState>(
+    State(state): State<S>,
+    versioned: Versioned,
+) -> Result<Response, ApiError> {
+    debug!("Processing list pinned items request");
+
+    let tag = versioned.tag(&());
+    if let Some(not_modified) = tag.not_modified() {
+        return Ok(not_modified);
+    }
+    let pinned = state.content_repo().list_pinned().await?;
+    Ok((tag, ResponseJson::<PinnedItemsResponse>(pinned.into())).into_response())
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn pin_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<PinnedItemsResponse>, ApiError> {
+    debug!("Processing pin content request");
+
+    match state.content_repo().pin(id, MAX_PINNED_ITEMS).await? {
+        Some(pinned) => {
+            info!(id, pinned_count = pinned.len(), "Pinned content item");
+            Ok(ResponseJson(pinned.into()))
+        }
+        None => {
+            debug!("Content item not found");
+            Err(ApiError::NotFound)
+        }
+    }
+}
+
+#[instrument(skip_all, fields(id = %id))]
+async fn unpin_content<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(id): Path<i32>,
+) -> Result<ResponseJson<PinnedItemsResponse>, ApiError> {
+    debug!("Processing unpin content request");
+
+    match state.content_repo().unpin(id).await? {
+        Some(pinned) => {
+            info!(id, pinned_count = pinned.len(), "Unpinned content item");
+            Ok(ResponseJson(pinned.into()))
+        }
+        None => {
+            debug!("Content item not found");
+            Err(ApiError::NotFound)
+        }
+    }
+}
+
+#[instrument(skip_all, fields(count = request.ids.len()))]
+async fn reorder_pins<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<ReorderPinsRequest>,
+) -> Result<ResponseJson<PinnedItemsResponse>, ApiError> {
+    debug!("Processing reorder pins request");
+
+    let pinned = state.content_repo().reorder_pins(&request.ids).await?;
+    info!("Reordered pinned items");
+    Ok(ResponseJson(pinned.into()))
+}
+
+pub fn create_api_v1_router<S: AppState>() -> Router<S> {
+    create_api_v1_tenant_router().nest("/admin", super::admin::create_admin_router())
+}
+
+/// The v1 API without the instance-wide admin endpoints
+pub fn create_api_v1_tenant_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/content", post(add_content::<S>).get(list_content::<S>))
+        .route("/content/count", get(count_content::<S>))
+        .route(
+            "/content/events",
+            get(super::notifications::stream_content_events::<S>),
+        )
+        .route("/content:bulk", post(bulk_update_content::<S>))
+        .route("/content/lookup", post(lookup_content::<S>))
+        .route(
+            "/content/import/rss",
+            post(import_rss::<S>).layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_BYTES)),
+        )
+        .route(
+            "/content/import/karakeep",
+            post(import_karakeep::<S>).layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_BYTES)),
+        )
+        .route(
+            "/content/pins",
+            get(list_pinned::<S>).put(reorder_pins::<S>),
+        )
+        .route(
+            "/content/{id}",
+            get(get_content_by_id::<S>).patch(update_content::<S>),
+        )
+        .route("/content/{id}/check-update", post(check_for_update::<S>))
+        .route("/content/{id}/suggested-tags", get(suggest_tags::<S>))
+        .route(
+            "/content/{id}/pin",
+            post(pin_content::<S>).delete(unpin_content::<S>),
+        )
+        .route("/content/{id}/fetch-status", get(get_fetch_status::<S>))
+        .route("/content/{id}/fetch-status/retry", post(retry_fetches::<S>))
+        .route("/content/{id}/send", post(send_content::<S>))
+        .route(
+            "/content/{id}/snapshots",
+            post(super::snapshots::capture_snapshot::<S>)
+                .layer(limit(RouteGroup::Archive))
+                .get(super::snapshots::list_snapshots::<S>),
+        )
+        .route(
+            "/content/{id}/comments",
+            post(super::comments::create_comment::<S>).get(super::comments::list_comments::<S>),
+        )
+        .route(
+            "/content/{id}/comments/{comment_id}",
+            delete(super::comments::delete_comment::<S>),
+        )
+        .route(
+            "/content/{id}/share",
+            post(create_share_link::<S>).get(list_share_links::<S>),
+        )
+        .route(
+            "/content/{id}/share/{token}",
+            delete(revoke_share_link::<S>),
+        )
+        .route(
+            "/search",
+            get(search_content::<S>).layer(limit(RouteGroup::Search)),
+        )
+        .route("/sync", post(sync_content::<S>))
+        .route(
+            "/export/bibtex",
+            get(export_bibtex::<S>).layer(limit(RouteGroup::Export)),
+        )
+        .route(
+            "/export/podcast",
+            get(export_podcast_feed::<S>).layer(limit(RouteGroup::Export)),
+        )
+        .route(
+            "/export/karakeep",
+            get(export_karakeep::<S>).layer(limit(RouteGroup::Export)),
+        )
+        .nest("/activity", super::activity::create_activity_router())
+        .nest("/jobs", super::jobs::create_jobs_router())
+        .nest("/rules", super::rules::create_rules_router())
+        .nest("/views", super::views::create_views_router())
+        .nest("/snapshots", super::snapshots::create_snapshots_router())
+        .nest("/zotero", super::zotero::create_zotero_router())
+        .nest(
+            "/notifications",
+            super::notifications::create_notifications_router(),
+        )
 }
@@ -0,0 +1,202 @@
+//! API version negotiation and deprecation signals.
+//!
+//! Each major version is served under its own path prefix, such as
+//! `/api/v1`. Clients may additionally send `Accept-Version` with the
+//! versions they understand (`1`, `v1`, or a comma-separated list); a route
+//! of another version answers 406 instead of a response the client would
+//! misread. Responses name their version in `Api-Version`, and once a
+//! version is deprecated they carry `Deprecation` (RFC 9745), `Sunset`
+//! (RFC 8594) and a `Link` to the successor version.
+
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use chrono::DateTime;
+use http::{HeaderMap, HeaderValue, header};
+
+use crate::errors::ApiError;
+
+pub const ACCEPT_VERSION_HEADER: &str = "accept-version";
+pub const API_VERSION_HEADER: &str = "api-version";
+
+/// A major version of the API and how much longer it is served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionPolicy {
+    pub version: u32,
+    /// When the version was deprecated, as Unix seconds
+    pub deprecated_at: Option<i64>,
+    /// When the version stops being served, as Unix seconds
+    pub sunset_at: Option<i64>,
+    /// Path of the version replacing this one, e.g. `/api/v2`
+    pub successor: Option<&'static str>,
+}
+
+/// The only version so far; once v2 exists, its deprecation and sunset
+/// dates go here
+pub const V1: VersionPolicy = VersionPolicy {
+    version: 1,
+    deprecated_at: None,
+    sunset_at: None,
+    successor: None,
+};
+
+/// Every version this binary serves
+pub const SUPPORTED_VERSIONS: &[VersionPolicy] = &[V1];
+
+impl VersionPolicy {
+    /// Negotiates versions for every route of `router`, which must serve
+    /// this version
+    pub fn apply<S: Clone + Send + Sync + 'static>(self, router: Router<S>) -> Router<S> {
+        router.layer(middleware::from_fn_with_state(self, negotiate))
+    }
+
+    fn check(&self, headers: &HeaderMap) -> Result<(), ApiError> {
+        let Some(accepted) = headers.get(ACCEPT_VERSION_HEADER) else {
+            return Ok(());
+        };
+        let accepted = accepted
+            .to_str()
+            .ok()
+            .and_then(parse_accept_version)
+            .ok_or_else(|| {
+                ApiError::BadRequest(
+                    "Accept-Version must list versions like '1' or 'v1', or '*'".to_string(),
+                )
+            })?;
+
+        match accepted {
+            Some(versions) if !versions.contains(&self.version) => {
+                let supported = SUPPORTED_VERSIONS
+                    .iter()
+                    .map(|policy| format!("v{}", policy.version))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(ApiError::NotAcceptable(format!(
+                    "This route serves API v{}; this server supports {supported}",
+                    self.version
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn add_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(API_VERSION_HEADER, HeaderValue::from(self.version));
+        if let Some(deprecated_at) = self.deprecated_at
+            && let Ok(value) = HeaderValue::from_str(&format!("@{deprecated_at}"))
+        {
+            headers.insert("deprecation", value);
+        }
+        if let Some(sunset) = self
+            .sunset_at
+            .and_then(|at| DateTime::from_timestamp(at, 0))
+        {
+            let http_date = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            if let Ok(value) = HeaderValue::from_str(&http_date) {
+                headers.insert("sunset", value);
+            }
+        }
+        if let Some(successor) = self.successor
+            && let Ok(value) =
+                HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\""))
+        {
+            headers.append(header::LINK, value);
+        }
+    }
+}
+
+/// Versions listed in an `Accept-Version` header, `Some(None)` for `*` or
+/// `None` if it is malformed
+fn parse_accept_version(value: &str) -> Option<Option<Vec<u32>>> {
+    let mut versions = Vec::new();
+    for entry in value.split(',').map(str::trim) {
+        if entry == "*" {
+            return Some(None);
+        }
+        let number = entry
+            .strip_prefix('v')
+            .or_else(|| entry.strip_prefix('V'))
+            .unwrap_or(entry);
+        versions.push(number.parse().ok()?);
+    }
+    Some(Some(versions))
+}
+
+async fn negotiate(State(policy): State<VersionPolicy>, request: Request, next: Next) -> Response {
+    let mut response = match policy.check(request.headers()) {
+        Ok(()) => next.run(request).await,
+        Err(err) => err.into_response(),
+    };
+    policy.add_headers(response.headers_mut());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::get;
+    use http::StatusCode;
+    use tower::ServiceExt;
+
+    async fn call(policy: VersionPolicy, accept_version: Option<&str>) -> Response {
+        let router: Router = policy.apply(Router::new().route("/", get(|| async { "ok" })));
+        let mut request = Request::builder().uri("/");
+        if let Some(accept_version) = accept_version {
+            request = request.header(ACCEPT_VERSION_HEADER, accept_version);
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_accept_version() {
+        assert_eq!(parse_accept_version("1"), Some(Some(vec![1])));
+        assert_eq!(parse_accept_version("v2, 1"), Some(Some(vec![2, 1])));
+        assert_eq!(parse_accept_version("2, *"), Some(None));
+        assert_eq!(parse_accept_version("latest"), None);
+    }
+
+    #[tokio::test]
+    async fn test_negotiates_accepted_versions() {
+        let response = call(V1, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[API_VERSION_HEADER], "1");
+        assert!(response.headers().get("deprecation").is_none());
+
+        assert_eq!(call(V1, Some("v1")).await.status(), StatusCode::OK);
+        assert_eq!(call(V1, Some("*")).await.status(), StatusCode::OK);
+        let response = call(V1, Some("2")).await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(response.headers()[API_VERSION_HEADER], "1");
+        assert_eq!(
+            call(V1, Some("two")).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_version_headers() {
+        let deprecated = VersionPolicy {
+            deprecated_at: Some(1_767_225_600),
+            sunset_at: Some(1_782_864_000),
+            successor: Some("/api/v2"),
+            ..V1
+        };
+
+        let response = call(deprecated, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "@1767225600");
+        assert_eq!(
+            response.headers()["sunset"],
+            "Wed, 01 Jul 2026 00:00:00 GMT"
+        );
+        assert_eq!(
+            response.headers()[header::LINK],
+            "</api/v2>; rel=\"successor-version\""
+        );
+    }
+}
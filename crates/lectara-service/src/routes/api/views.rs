@@ -0,0 +1,182 @@
+use axum::{
+    Router,
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{NewView, View};
+use crate::read_only::Writable;
+use crate::repositories::ViewRepository;
+use crate::routes::extract::parse_datetime;
+use crate::validation;
+
+/// Longest view name accepted
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Deserialize)]
+struct CreateViewRequest {
+    name: String,
+    #[serde(flatten)]
+    filters: ViewFiltersRequest,
+}
+
+#[derive(Debug, Deserialize)]
+struct ViewFiltersRequest {
+    content_type: Option<String>,
+    source: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl ViewFiltersRequest {
+    fn into_new_view(self, name: String) -> Result<NewView, ApiError> {
+        let valid_name = (1..=MAX_NAME_LENGTH).contains(&name.len())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(ApiError::BadRequest(format!(
+                "'name' must be 1 to {MAX_NAME_LENGTH} lowercase letters, digits, '-' or '_'"
+            )));
+        }
+        for (field, value) in [("since", &self.since), ("until", &self.until)] {
+            if let Some(value) = value
+                && parse_datetime(value).is_none()
+            {
+                return Err(ApiError::BadRequest(format!(
+                    "Invalid '{field}' datetime '{value}', use RFC 3339, unix seconds, YYYY-MM-DD or an age like 7d"
+                )));
+            }
+        }
+
+        Ok(NewView {
+            name,
+            content_type: self.content_type.filter(|value| !value.is_empty()),
+            source: self
+                .source
+                .as_deref()
+                .map(validation::parse_source)
+                .transpose()?,
+            since: self.since,
+            until: self.until,
+        })
+    }
+}
+
+/// A view's filters, with relative bounds resolved to the current time
+#[derive(Debug, Default)]
+pub(super) struct ViewFilters {
+    pub(super) content_type: Option<String>,
+    pub(super) source: Option<String>,
+    pub(super) since: Option<DateTime<Utc>>,
+    pub(super) until: Option<DateTime<Utc>>,
+}
+
+/// The filters of the view named `name`, for listings that use it
+pub(super) async fn view_filters<S: AppState>(
+    state: &S,
+    name: &str,
+) -> Result<ViewFilters, ApiError> {
+    let Some(view) = state.view_repo().find_by_name(name).await? else {
+        return Err(ApiError::BadRequest(format!("No view named '{name}'")));
+    };
+    Ok(ViewFilters {
+        since: view.since.as_deref().and_then(parse_datetime),
+        until: view.until.as_deref().and_then(parse_datetime),
+        content_type: view.content_type,
+        source: view.source,
+    })
+}
+
+async fn list_views<S: AppState>(
+    State(state): State<S>,
+) -> Result<ResponseJson<Vec<View>>, ApiError> {
+    debug!("Processing list views request");
+
+    Ok(ResponseJson(state.view_repo().list().await?))
+}
+
+#[instrument(skip_all, fields(name = %request.name))]
+async fn create_view<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<CreateViewRequest>,
+) -> Result<(StatusCode, ResponseJson<View>), ApiError> {
+    debug!("Processing create view request");
+
+    let view = request.filters.into_new_view(request.name)?;
+    let view = state.view_repo().create(&view).await?;
+
+    info!("Created view");
+    Ok((StatusCode::CREATED, ResponseJson(view)))
+}
+
+#[instrument(skip_all, fields(name = %name))]
+async fn get_view<S: AppState>(
+    State(state): State<S>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<View>, ApiError> {
+    debug!("Processing get view request");
+
+    state
+        .view_repo()
+        .find_by_name(&name)
+        .await?
+        .map(ResponseJson)
+        .ok_or(ApiError::NotFound)
+}
+
+#[instrument(skip_all, fields(name = %name))]
+async fn update_view<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(name): Path<String>,
+    Json(request): Json<ViewFiltersRequest>,
+) -> Result<ResponseJson<View>, ApiError> {
+    debug!("Processing update view request");
+
+    let view = request.into_new_view(name)?;
+    let view = state
+        .view_repo()
+        .update(&view)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    info!("Updated view");
+    Ok(ResponseJson(view))
+}
+
+#[instrument(skip_all, fields(name = %name))]
+async fn delete_view<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing delete view request");
+
+    if state.view_repo().delete(&name).await? {
+        info!("Deleted view");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        debug!("View not found");
+        Err(ApiError::NotFound)
+    }
+}
+
+pub fn create_views_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/", get(list_views::<S>).post(create_view::<S>))
+        .route(
+            "/{name}",
+            get(get_view::<S>)
+                .put(update_view::<S>)
+                .delete(delete_view::<S>),
+        )
+}
@@ -0,0 +1,310 @@
+//! Saving references pushed by Zotero.
+//!
+//! `saveItems` takes the body the Zotero connector sends to the desktop
+//! app's `/connector/saveItems`: items in Zotero's translator JSON. Each item
+//! is saved by its URL, or its DOI when it has none, and its bibliographic
+//! fields are kept as a `zotero` citation, so it shows up in the BibTeX
+//! export. Attachments are recorded as links in the item's metadata; their
+//! files aren't downloaded.
+
+use axum::{
+    Router,
+    extract::{Json, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::post,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, info, instrument};
+
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::{MetadataPatch, NewCitation, NewContentItem};
+use crate::read_only::Writable;
+use crate::repositories::{CitationRepository, ContentRepository};
+use crate::routes::api::v1::{ImportItemResult, ImportStatus, SaveOutcome, save_content};
+
+/// Source recorded for items and citations saved from Zotero
+const ZOTERO_SOURCE: &str = "zotero";
+/// Maximum number of items accepted by a single save
+const MAX_ITEMS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct SaveItemsRequest {
+    items: Vec<ZoteroItem>,
+}
+
+/// The fields lectara keeps of an item in Zotero's translator JSON
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZoteroItem {
+    title: Option<String>,
+    #[serde(default)]
+    creators: Vec<Creator>,
+    /// Free-form, e.g. `2017-06-12` or `June 2017`
+    date: Option<String>,
+    url: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+    abstract_note: Option<String>,
+    publication_title: Option<String>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Creator {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    /// Single-field names, e.g. of organizations
+    name: Option<String>,
+    creator_type: Option<String>,
+}
+
+impl Creator {
+    fn is_author(&self) -> bool {
+        self.creator_type
+            .as_deref()
+            .is_none_or(|kind| kind == "author")
+    }
+
+    fn full_name(&self) -> Option<String> {
+        let name = match (&self.name, &self.first_name, &self.last_name) {
+            (Some(name), _, _) => name.trim().to_string(),
+            (None, Some(first), Some(last)) => format!("{} {}", first.trim(), last.trim()),
+            (None, first, last) => first.as_ref().or(last.as_ref())?.trim().to_string(),
+        };
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Attachment {
+    title: Option<String>,
+    url: Option<String>,
+    mime_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SaveItemsResponse {
+    items: Vec<ImportItemResult>,
+}
+
+impl ZoteroItem {
+    /// The page to save, falling back to the DOI's resolver link
+    fn url(&self) -> Option<String> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        non_empty(&self.url)
+            .or_else(|| non_empty(&self.doi).map(|doi| format!("https://doi.org/{doi}")))
+    }
+
+    fn authors(&self) -> Vec<String> {
+        self.creators
+            .iter()
+            .filter(|creator| creator.is_author())
+            .filter_map(Creator::full_name)
+            .collect()
+    }
+
+    fn citation(&self, content_id: i32, url: &str) -> NewCitation {
+        NewCitation {
+            content_id,
+            source: ZOTERO_SOURCE.to_string(),
+            identifier: self.doi.clone().unwrap_or_else(|| url.to_string()),
+            title: self.title.clone(),
+            authors: serde_json::to_string(&self.authors()).expect("names serialize as JSON"),
+            published_on: self.date.as_deref().and_then(parse_date),
+            container_title: self.publication_title.clone(),
+            abstract_text: self.abstract_note.clone(),
+        }
+    }
+}
+
+/// Reads the year, and month and day when given as numbers after it;
+/// missing parts fall back to the first, as for Crossref dates
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let numbers: Vec<u32> = date
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    match numbers.as_slice() {
+        [year, rest @ ..] if *year >= 1000 => {
+            let month = rest.first().copied().unwrap_or(1);
+            let day = rest.get(1).copied().unwrap_or(1);
+            NaiveDate::from_ymd_opt(*year as i32, month, day)
+                .or_else(|| NaiveDate::from_ymd_opt(*year as i32, 1, 1))
+        }
+        // e.g. `12/06/2017`, where only the year is certain
+        _ => {
+            let year = numbers
+                .iter()
+                .find(|number| (1000..=9999).contains(*number))?;
+            NaiveDate::from_ymd_opt(*year as i32, 1, 1)
+        }
+    }
+}
+
+/// Saves one Zotero item and its citation. An item whose URL is already
+/// saved keeps its title and author, but still gets the citation.
+async fn save_item<S: AppState>(state: &S, item: ZoteroItem) -> Result<ImportItemResult, ApiError> {
+    let Some(url) = item.url() else {
+        return Ok(ImportItemResult {
+            url: None,
+            id: None,
+            status: ImportStatus::Invalid,
+            error: Some("The item has no URL or DOI".to_string()),
+        });
+    };
+    let authors = item.authors();
+    let new_content = NewContentItem::new_with_context(
+        url.clone(),
+        item.title.clone(),
+        (!authors.is_empty()).then(|| authors.join(", ")),
+        None,
+        state.validation(),
+    )
+    .and_then(|new_content| new_content.with_source(ZOTERO_SOURCE));
+    let new_content = match new_content {
+        Ok(new_content) => new_content,
+        Err(err) => {
+            return Ok(ImportItemResult {
+                url: Some(url),
+                id: None,
+                status: ImportStatus::Invalid,
+                error: Some(err.to_string()),
+            });
+        }
+    };
+
+    let (id, status) = match save_content(state, &new_content).await {
+        Ok(SaveOutcome::Created(id)) => (id, ImportStatus::Created),
+        Ok(SaveOutcome::Existing(id)) => (id, ImportStatus::Existing),
+        Err(ApiError::DuplicateUrlDifferentMetadata(conflict)) => {
+            (conflict.existing.id, ImportStatus::Existing)
+        }
+        Err(err) => return Err(err),
+    };
+
+    state
+        .citation_repo()
+        .upsert(&item.citation(id, &new_content.url))
+        .await?;
+
+    let attachments: Vec<&Attachment> = item
+        .attachments
+        .iter()
+        .filter(|attachment| attachment.url.is_some())
+        .collect();
+    if !attachments.is_empty() {
+        let mut patch = MetadataPatch::default();
+        patch
+            .metadata
+            .insert("attachments".to_string(), json!(attachments));
+        state.content_repo().apply_enrichment(id, &patch).await?;
+    }
+
+    Ok(ImportItemResult {
+        url: Some(url),
+        id: Some(id),
+        status,
+        error: None,
+    })
+}
+
+/// Answers the connector's check for a running app
+async fn ping() -> &'static str {
+    "Lectara is running"
+}
+
+#[instrument(skip_all, fields(count = request.items.len()))]
+async fn save_items<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Json(request): Json<SaveItemsRequest>,
+) -> Result<(StatusCode, ResponseJson<SaveItemsResponse>), ApiError> {
+    debug!("Processing Zotero save request");
+
+    if request.items.len() > MAX_ITEMS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_ITEMS} items can be saved at once"
+        )));
+    }
+
+    let mut items = Vec::with_capacity(request.items.len());
+    for item in request.items {
+        items.push(save_item(&state, item).await?);
+    }
+    info!(item_count = items.len(), "Saved Zotero items");
+
+    Ok((
+        StatusCode::CREATED,
+        ResponseJson(SaveItemsResponse { items }),
+    ))
+}
+
+pub fn create_zotero_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/ping", post(ping).get(ping))
+        .route("/saveItems", post(save_items::<S>))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(parse_date("2017-06-12"), date(2017, 6, 12));
+        assert_eq!(parse_date("2017-06"), date(2017, 6, 1));
+        assert_eq!(parse_date("2017"), date(2017, 1, 1));
+        assert_eq!(parse_date("June 2017"), date(2017, 1, 1));
+        assert_eq!(parse_date("12/06/2017"), date(2017, 1, 1));
+        assert_eq!(parse_date("2017-13-40"), date(2017, 1, 1));
+        assert_eq!(parse_date("n.d."), None);
+    }
+
+    #[test]
+    fn test_authors() {
+        let creator = |first: &str, last: &str, kind: &str| Creator {
+            first_name: Some(first.to_string()),
+            last_name: Some(last.to_string()),
+            name: None,
+            creator_type: Some(kind.to_string()),
+        };
+        let item = ZoteroItem {
+            creators: vec![
+                creator("Ada", "Lovelace", "author"),
+                creator("Charles", "Babbage", "editor"),
+                Creator {
+                    first_name: None,
+                    last_name: None,
+                    name: Some("ACM".to_string()),
+                    creator_type: None,
+                },
+            ],
+            ..ZoteroItem::default()
+        };
+        assert_eq!(item.authors(), ["Ada Lovelace", "ACM"]);
+    }
+
+    #[test]
+    fn test_url_falls_back_to_doi() {
+        let item = ZoteroItem {
+            url: Some(" ".to_string()),
+            doi: Some("10.1000/xyz".to_string()),
+            ..ZoteroItem::default()
+        };
+        assert_eq!(item.url().as_deref(), Some("https://doi.org/10.1000/xyz"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Extractors shared by the route modules.
+
+use axum::extract::{FromRequest, FromRequestParts, Json, Query, Request};
+use axum::response::{IntoResponse, IntoResponseParts, Response, ResponseParts};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use http::request::Parts;
+use http::{HeaderValue, StatusCode, header};
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer};
+use std::hash::Hash;
+
+use crate::AppState;
+use crate::database::CollectionVersion;
+use crate::errors::ApiError;
+use crate::repositories::ContentRepository;
+use crate::validation::NormalizedUrl;
+
+/// JSON request bodies naming a URL to save
+pub trait UrlPayload {
+    fn url(&self) -> &str;
+}
+
+/// A JSON body whose URL has been validated and normalized under the
+/// deployment's rules before the handler runs. An invalid URL is rejected
+/// with the same 400 as every other validation error; malformed bodies are
+/// rejected as [`Json`] rejects them.
+pub struct ValidatedUrlJson<T> {
+    pub url: NormalizedUrl,
+    pub payload: T,
+}
+
+impl<S, T> FromRequest<S> for ValidatedUrlJson<T>
+where
+    S: AppState,
+    T: DeserializeOwned + UrlPayload,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(payload) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let url = NormalizedUrl::parse(payload.url(), state.validation())
+            .map_err(|err| ApiError::from(err).into_response())?;
+        Ok(Self { url, payload })
+    }
+}
+
+/// Query string parameters. Unlike [`Query`], a malformed query string is
+/// rejected with the JSON 400 every other bad request gets.
+pub struct ApiQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Query::<T>::from_request_parts(parts, state)
+            .await
+            .map(|Query(query)| Self(query))
+            .map_err(|rejection| ApiError::BadRequest(rejection.body_text()))
+    }
+}
+
+/// The version of the items a response is built from, read before the
+/// handler queries anything, so a write landing in between only makes the
+/// client's next request miss
+pub struct Versioned {
+    version: CollectionVersion,
+    if_none_match: Option<String>,
+}
+
+impl<S: AppState> FromRequestParts<S> for Versioned {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            version: state.content_repo().version(),
+            if_none_match: parts
+                .headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
+
+impl Versioned {
+    /// The tag of a response built from this version and `inputs`, the
+    /// parameters as resolved for this request; times relative to now
+    /// resolve differently every time, so they never match
+    pub fn tag(&self, inputs: &impl Hash) -> Tagged {
+        let etag = self.version.etag(inputs);
+        let unchanged = self
+            .if_none_match
+            .as_deref()
+            .is_some_and(|tags| etag_matches(tags, &etag));
+        Tagged { etag, unchanged }
+    }
+}
+
+/// An `ETag` to return with a response, which clients revalidate on every
+/// use
+#[derive(Clone)]
+pub struct Tagged {
+    etag: String,
+    unchanged: bool,
+}
+
+impl Tagged {
+    /// A 304 if the client's copy is current, so the handler can skip
+    /// building the response
+    pub fn not_modified(&self) -> Option<Response> {
+        self.unchanged
+            .then(|| (StatusCode::NOT_MODIFIED, self.clone(), ()).into_response())
+    }
+}
+
+impl IntoResponseParts for Tagged {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(self, mut parts: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let headers = parts.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-cache"),
+        );
+        Ok(parts)
+    }
+}
+
+/// Whether an `If-None-Match` list names `etag`, comparing weakly as the
+/// header requires
+fn etag_matches(tags: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    tags.split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// Parses an RFC 3339 datetime, unix epoch seconds, a `YYYY-MM-DD` date
+/// standing for midnight UTC, or a time relative to now: `now`, `today`,
+/// `yesterday`, or an age like `30m`, `12h`, `7d` or `2w`
+pub fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    parse_datetime_at(value, Utc::now())
+}
+
+fn parse_datetime_at(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.to_utc());
+    }
+    if let Ok(seconds) = value.parse::<i64>() {
+        return DateTime::from_timestamp(seconds, 0);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    let today = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+    match value.to_ascii_lowercase().as_str() {
+        "now" => return Some(now),
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+
+    let unit_at = value.len().checked_sub(1)?;
+    let (amount, unit) = (value.get(..unit_at)?, value.get(unit_at..)?);
+    let amount = amount.parse::<u32>().ok()?;
+    let age = match unit {
+        "m" => Duration::minutes(amount.into()),
+        "h" => Duration::hours(amount.into()),
+        "d" => Duration::days(amount.into()),
+        "w" => Duration::weeks(amount.into()),
+        _ => return None,
+    };
+    now.checked_sub_signed(age)
+}
+
+/// Deserializes an optional datetime in any form [`parse_datetime`]
+/// accepts. Fields using it also need `#[serde(default)]`.
+pub fn deserialize_optional_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| {
+            parse_datetime(&value).ok_or_else(|| {
+                de::Error::custom(format!(
+                    "invalid datetime '{value}', use RFC 3339, unix seconds, YYYY-MM-DD or an age like 7d"
+                ))
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches_weakly() {
+        let etag = r#"W/"a1-7""#;
+        assert!(etag_matches(r#"W/"a1-7""#, etag));
+        assert!(etag_matches(r#""a1-7""#, etag));
+        assert!(etag_matches(r#""other", W/"a1-7""#, etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches(r#"W/"a1-8""#, etag));
+        assert!(!etag_matches("", etag));
+    }
+
+    #[test]
+    fn test_parse_datetime_forms() {
+        let expected = DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(parse_datetime("2024-01-02T00:00:00Z"), Some(expected));
+        assert_eq!(parse_datetime("2024-01-02T01:00:00+01:00"), Some(expected));
+        assert_eq!(parse_datetime("1704153600"), Some(expected));
+        assert_eq!(parse_datetime("2024-01-02"), Some(expected));
+
+        assert_eq!(parse_datetime("not_a_date"), None);
+        assert_eq!(parse_datetime("2024-13-01"), None);
+        assert_eq!(parse_datetime(""), None);
+    }
+
+    #[test]
+    fn test_parse_relative_datetime() {
+        let now = DateTime::parse_from_rfc3339("2024-01-10T15:30:00Z")
+            .unwrap()
+            .to_utc();
+        let at = |value| parse_datetime_at(value, now).map(|datetime| datetime.to_rfc3339());
+
+        assert_eq!(at("now"), Some(now.to_rfc3339()));
+        assert_eq!(at("Today"), Some("2024-01-10T00:00:00+00:00".to_string()));
+        assert_eq!(
+            at("yesterday"),
+            Some("2024-01-09T00:00:00+00:00".to_string())
+        );
+        assert_eq!(at("30m"), Some("2024-01-10T15:00:00+00:00".to_string()));
+        assert_eq!(at("12h"), Some("2024-01-10T03:30:00+00:00".to_string()));
+        assert_eq!(at("7d"), Some("2024-01-03T15:30:00+00:00".to_string()));
+        assert_eq!(at("2w"), Some("2023-12-27T15:30:00+00:00".to_string()));
+
+        assert_eq!(at("7"), Some("1970-01-01T00:00:07+00:00".to_string()));
+        assert_eq!(at("d"), None);
+        assert_eq!(at("-7d"), None);
+        assert_eq!(at("7y"), None);
+        assert_eq!(at("7 days"), None);
+    }
+}
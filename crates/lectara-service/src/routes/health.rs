@@ -0,0 +1,97 @@
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use serde::Serialize;
+use tracing::{debug, instrument, warn};
+
+use crate::build_info::BuildInfo;
+use crate::errors::ApiError;
+use crate::migrations::{self, MigrationStatus};
+use crate::routes::api::API_VERSION;
+use crate::{AppState, repositories::SchemaRepository};
+
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub build: BuildInfo,
+    /// Newest migration this binary embeds
+    pub schema_version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub api_version: u32,
+    #[serde(flatten)]
+    pub build: BuildInfo,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub build: BuildInfo,
+    pub schema_version: Option<String>,
+    pub migrations: MigrationStatus,
+}
+
+/// Liveness: the process is up and serving requests
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        build: BuildInfo::current(),
+        schema_version: migrations::schema_version(),
+    })
+}
+
+/// Versions of the API and the running binary, for clients checking
+/// compatibility before anything else
+async fn version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        api_version: API_VERSION,
+        build: BuildInfo::current(),
+    })
+}
+
+/// Readiness: the database is reachable and every embedded migration has
+/// been applied, so deployments can verify schema state before routing
+/// traffic here
+#[instrument(skip_all)]
+async fn ready<S: AppState>(
+    State(state): State<S>,
+) -> Result<(StatusCode, Json<ReadinessResponse>), ApiError> {
+    debug!("Processing readiness request");
+
+    let build = BuildInfo::current();
+    let schema_version = migrations::schema_version();
+    let migrations = state.schema_repo().migration_status().await?;
+
+    if migrations.pending.is_empty() {
+        Ok((
+            StatusCode::OK,
+            Json(ReadinessResponse {
+                status: "ready",
+                build,
+                schema_version,
+                migrations,
+            }),
+        ))
+    } else {
+        warn!(
+            pending = ?migrations.pending,
+            "Not ready: migrations pending"
+        );
+        Ok((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "pending_migrations",
+                build,
+                schema_version,
+                migrations,
+            }),
+        ))
+    }
+}
+
+pub fn create_health_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready::<S>))
+        .route("/api/v1/version", get(version))
+}
@@ -1,15 +1,43 @@
 use crate::AppState;
+use crate::auth::AccessPolicy;
+use crate::errors::ApiError;
 use axum::Router;
 
 pub mod api;
+pub mod extract;
+pub mod health;
 pub mod web;
 
 pub fn create_router<S: AppState>() -> Router<S> {
     Router::new()
+        .merge(health::create_health_router())
         .nest("/api", api::create_api_router())
         .nest("/web", web::create_web_router())
 }
 
+/// Everything but the admin API, served to each tenant of a multi-tenant
+/// instance
+pub fn create_tenant_router<S: AppState>() -> Router<S> {
+    Router::new()
+        .merge(health::create_health_router())
+        .nest("/api", api::create_tenant_api_router())
+        .nest("/web", web::create_web_router())
+}
+
+/// Health checks and the admin API guarded by `access`, served by a
+/// multi-tenant instance to requests that match no tenant. Tenant keys don't
+/// reach these routes, so without instance keys the admin API is left out
+/// rather than open to anyone.
+pub fn create_instance_router<S: AppState>(access: AccessPolicy) -> Router<S> {
+    let router = Router::new().merge(health::create_health_router());
+    let router = if access.requires_keys() {
+        router.nest("/api/v1/admin", api::admin::create_admin_router())
+    } else {
+        router
+    };
+    access.protect(router.fallback(|| async { ApiError::NotFound }))
+}
+
 pub fn create_api_only_router<S: AppState>() -> Router<S> {
     Router::new().nest("/api", api::create_api_router())
 }
@@ -0,0 +1,157 @@
+use axum::{
+    body::Bytes,
+    extract::{OriginalUri, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::{debug, info, instrument, warn};
+
+use crate::AppState;
+use crate::activitypub::{self, ACTIVITY_JSON, ActivityPub, ActivityPubError, OUTBOX_SIZE};
+use crate::errors::ApiError;
+use crate::models::ContentItem;
+use crate::read_only::Writable;
+use crate::repositories::{ContentRepository, FollowerRepository};
+
+/// Media type of WebFinger documents
+const JRD_JSON: &str = "application/jrd+json";
+
+#[derive(Debug, Deserialize)]
+pub struct WebfingerQuery {
+    resource: String,
+}
+
+fn configured<S: AppState>(state: &S) -> Result<&ActivityPub, ApiError> {
+    state.activitypub().ok_or(ApiError::NotFound)
+}
+
+fn activity_json(document: Value) -> Response {
+    (
+        [(header::CONTENT_TYPE, ACTIVITY_JSON)],
+        document.to_string(),
+    )
+        .into_response()
+}
+
+/// Finds the actor by handle, for Mastodon's search and follow buttons.
+/// Servers look it up at the root `/.well-known/webfinger`, which a reverse
+/// proxy has to map here.
+#[instrument(skip_all, fields(resource = %query.resource))]
+pub async fn webfinger<S: AppState>(
+    State(state): State<S>,
+    Query(query): Query<WebfingerQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Processing WebFinger request");
+
+    let document = configured(&state)?
+        .webfinger(query.resource.trim())
+        .ok_or(ApiError::NotFound)?;
+    Ok(([(header::CONTENT_TYPE, JRD_JSON)], document.to_string()).into_response())
+}
+
+pub async fn actor<S: AppState>(State(state): State<S>) -> Result<Response, ApiError> {
+    debug!("Processing actor request");
+
+    Ok(activity_json(configured(&state)?.actor()))
+}
+
+/// The newest published items
+pub async fn outbox<S: AppState>(State(state): State<S>) -> Result<Response, ApiError> {
+    debug!("Processing outbox request");
+
+    let activitypub = configured(&state)?;
+    let (items, total) = match activitypub::selection(&state, activitypub).await? {
+        Some(filter) => {
+            let repo = state.content_repo();
+            let items = repo.list_recent(&filter, OUTBOX_SIZE).await?;
+            (items, repo.count(&filter).await?)
+        }
+        None => (Vec::new(), 0),
+    };
+    let items = items
+        .into_iter()
+        .map(|item| ContentItem::from_parts(item, None))
+        .collect::<Vec<_>>();
+    Ok(activity_json(activitypub.outbox(&items, total)))
+}
+
+pub async fn followers<S: AppState>(State(state): State<S>) -> Result<Response, ApiError> {
+    debug!("Processing followers request");
+
+    let activitypub = configured(&state)?;
+    let total = state.follower_repo().count().await?;
+    Ok(activity_json(activitypub.followers(total)))
+}
+
+/// A published item as a Note
+#[instrument(skip_all, fields(id = %id))]
+pub async fn note<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<Response, ApiError> {
+    debug!("Processing note request");
+
+    let activitypub = configured(&state)?;
+    let Some(item) = state.content_repo().find_by_id(id).await? else {
+        return Err(ApiError::NotFound);
+    };
+    if !activitypub::is_published(&state, activitypub, &item).await? {
+        debug!("Item isn't published");
+        return Err(ApiError::NotFound);
+    }
+
+    let mut note = activitypub.note(&item);
+    note["@context"] = json!("https://www.w3.org/ns/activitystreams");
+    Ok(activity_json(note))
+}
+
+/// Takes follows and their undoing from other servers. Other activities are
+/// acknowledged and dropped.
+#[instrument(skip_all)]
+pub async fn inbox<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    debug!("Processing inbox request");
+
+    let activitypub = configured(&state)?;
+    let activity: Value = serde_json::from_slice(&body)
+        .map_err(|err| ApiError::BadRequest(format!("Invalid activity: {err}")))?;
+    let path = uri
+        .path_and_query()
+        .map_or(uri.path(), |path| path.as_str());
+    let actor = activitypub
+        .verify_request("POST", path, &headers, &body, state.validation())
+        .await
+        .map_err(|err| match err {
+            ActivityPubError::InvalidSignature(reason) => ApiError::Unauthorized(reason),
+            err => {
+                warn!(error = %err, "Failed to check the signing actor");
+                ApiError::Unauthorized(format!("Failed to check the signing actor: {err}"))
+            }
+        })?;
+    if activity["actor"].as_str() != Some(actor.id.as_str()) {
+        return Err(ApiError::Forbidden(
+            "The activity is signed by another actor".to_string(),
+        ));
+    }
+
+    let actor_id = activitypub.actor_id();
+    match activity["type"].as_str() {
+        Some("Follow") if activity["object"].as_str() == Some(actor_id.as_str()) => {
+            activitypub::accept_follow(&state, &actor, &activity).await?;
+        }
+        Some("Undo") if activity["object"]["type"].as_str() == Some("Follow") => {
+            if state.follower_repo().remove(&actor.id).await? {
+                info!(actor = %actor.id, "Lost a follower");
+            }
+        }
+        kind => debug!(kind, "Ignoring activity"),
+    }
+    Ok(StatusCode::ACCEPTED)
+}
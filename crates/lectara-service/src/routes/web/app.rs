@@ -0,0 +1,81 @@
+//! What makes the web UI installable as a Progressive Web App: a manifest
+//! whose share target lets the system share sheet send links to
+//! [`super::save`], and a service worker caching the page shell for
+//! offline use.
+
+use std::sync::LazyLock;
+
+use axum::{
+    Json,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use super::assets;
+
+/// Colour of the browser UI around the installed app
+const THEME_COLOR: &str = "#1a4fb5";
+
+static SERVICE_WORKER: LazyLock<String> = LazyLock::new(|| {
+    let assets = assets::asset_paths().collect::<Vec<_>>();
+    // Any changed asset changes its fingerprint and so the cache name
+    let version = hex::encode(Sha256::digest(assets.join("\n")));
+    include_str!("assets/sw.js")
+        .replace("__ASSETS__", &serde_json::to_string(&assets).unwrap())
+        .replace("__VERSION__", &version[..12])
+});
+
+/// The `<head>` tags announcing the app to browsers
+pub fn head_tags() -> String {
+    format!(
+        "<link rel=\"manifest\" href=\"/web/manifest.webmanifest\">\n\
+         <link rel=\"icon\" href=\"{}\" type=\"image/svg+xml\">\n\
+         <meta name=\"theme-color\" content=\"{THEME_COLOR}\">\n",
+        assets::asset_path("icon.svg")
+    )
+}
+
+pub async fn manifest() -> Response {
+    let manifest = json!({
+        "name": "Lectara",
+        "short_name": "Lectara",
+        "description": "Your saved reading",
+        "start_url": "/web",
+        "scope": "/web/",
+        "display": "standalone",
+        "theme_color": THEME_COLOR,
+        "background_color": "#ffffff",
+        "icons": [{
+            "src": assets::asset_path("icon.svg"),
+            "sizes": "any",
+            "type": "image/svg+xml",
+            "purpose": "any",
+        }],
+        "share_target": {
+            "action": "/web/save",
+            "method": "POST",
+            "enctype": "application/x-www-form-urlencoded",
+            "params": { "title": "title", "text": "text", "url": "url" },
+        },
+    });
+    (
+        [(header::CONTENT_TYPE, "application/manifest+json")],
+        Json(manifest),
+    )
+        .into_response()
+}
+
+/// Served from `/web/` rather than with the assets so it may control every
+/// page there, and never cached so updates are picked up
+pub async fn service_worker() -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/javascript; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        SERVICE_WORKER.as_str(),
+    )
+        .into_response()
+}
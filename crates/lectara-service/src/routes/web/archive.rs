@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{Html, IntoResponse, Response},
+};
+use tracing::{debug, instrument, warn};
+
+use super::render_page;
+use crate::AppState;
+use crate::archive::ArchiveMode;
+use crate::errors::ApiError;
+use crate::repositories::ArchiveSnapshotRepository;
+
+/// Archived pages run in a sandbox without the service's origin, and may
+/// only load what was inlined into them
+const ARCHIVE_POLICY: &str = "sandbox allow-scripts allow-popups; default-src 'none'; \
+    img-src data:; media-src data:; font-src data:; style-src data: 'unsafe-inline'; \
+    script-src data: 'unsafe-inline'";
+
+/// A single-file snapshot, as the self-contained page it was saved as
+#[instrument(skip_all, fields(id = %id))]
+pub async fn archive<S: AppState>(
+    State(state): State<S>,
+    Path(id): Path<i32>,
+) -> Result<Response, ApiError> {
+    debug!("Serving archived page");
+
+    let repo = state.archive_snapshot_repo();
+    let snapshot = repo
+        .find_by_id(id)
+        .await?
+        .filter(|snapshot| snapshot.mode == ArchiveMode::SingleFile.as_str());
+    let Some(snapshot) = snapshot else {
+        debug!("Single-file snapshot not found");
+        let content =
+            "<h1>Archive not found</h1>\n<p><a href=\"/web\">Back to the reading list</a></p>";
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Html(render_page(&state, "Archive not found", content)),
+        )
+            .into_response());
+    };
+    let Some(payload) = repo.find_payload(&snapshot.payload_digest).await? else {
+        warn!(digest = %snapshot.payload_digest, "Snapshot payload is missing");
+        return Err(ApiError::InternalError);
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CONTENT_SECURITY_POLICY, ARCHIVE_POLICY),
+        ],
+        payload,
+    )
+        .into_response())
+}
@@ -0,0 +1,144 @@
+//! CSS and JavaScript for the web pages, embedded in the binary so a
+//! deployment stays a single file.
+//!
+//! Pages link each asset under a name carrying a digest of its contents,
+//! e.g. `/web/assets/lectara.3f2a9c1e.css`, which browsers may cache
+//! forever since a changed file gets a new name. The plain name is served
+//! too, but must be revalidated. Custom theme stylesheets are served as
+//! `theme-<name>.css`.
+
+use std::sync::LazyLock;
+
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use super::theme;
+
+/// Cache policy for fingerprinted names, whose contents never change
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Cache policy for plain names, whose contents change between releases
+const REVALIDATE: &str = "no-cache";
+
+struct Asset {
+    name: String,
+    content_type: &'static str,
+    contents: &'static [u8],
+    fingerprinted: String,
+    etag: String,
+}
+
+impl Asset {
+    fn new(name: impl Into<String>, content_type: &'static str, contents: &'static [u8]) -> Self {
+        let name = name.into();
+        let digest = hex::encode(Sha256::digest(contents));
+        let fingerprinted = match name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}.{}.{extension}", &digest[..8]),
+            None => format!("{name}.{}", &digest[..8]),
+        };
+        Self {
+            name,
+            content_type,
+            contents,
+            fingerprinted,
+            etag: format!("\"{digest}\""),
+        }
+    }
+}
+
+const CSS: &str = "text/css; charset=utf-8";
+const JAVASCRIPT: &str = "text/javascript; charset=utf-8";
+const SVG: &str = "image/svg+xml";
+
+static ASSETS: LazyLock<Vec<Asset>> = LazyLock::new(|| {
+    let custom_themes = theme::CUSTOM_THEMES
+        .iter()
+        .map(|(name, contents)| Asset::new(format!("theme-{name}.css"), CSS, contents));
+    [
+        Asset::new("lectara.css", CSS, include_bytes!("assets/lectara.css")),
+        Asset::new(
+            "palette.js",
+            JAVASCRIPT,
+            include_bytes!("assets/palette.js"),
+        ),
+        Asset::new("reader.js", JAVASCRIPT, include_bytes!("assets/reader.js")),
+        Asset::new("app.js", JAVASCRIPT, include_bytes!("assets/app.js")),
+        Asset::new("icon.svg", SVG, include_bytes!("assets/icon.svg")),
+    ]
+    .into_iter()
+    .chain(custom_themes)
+    .collect()
+});
+
+/// URL path of the embedded asset `name`, fingerprinted so it can be
+/// cached forever
+///
+/// # Panics
+///
+/// If no asset is called `name`
+pub fn asset_path(name: &str) -> String {
+    let asset = ASSETS
+        .iter()
+        .find(|asset| asset.name == name)
+        .unwrap_or_else(|| panic!("no embedded asset called {name}"));
+    format!("/web/assets/{}", asset.fingerprinted)
+}
+
+/// URL paths of every embedded asset, fingerprinted
+pub fn asset_paths() -> impl Iterator<Item = String> {
+    ASSETS
+        .iter()
+        .map(|asset| format!("/web/assets/{}", asset.fingerprinted))
+}
+
+/// Serves an embedded asset by its fingerprinted or plain name
+pub async fn serve_asset(Path(file): Path<String>, headers: HeaderMap) -> Response {
+    let Some(asset) = ASSETS
+        .iter()
+        .find(|asset| file == asset.fingerprinted || file == asset.name)
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let cache_control = if file == asset.fingerprinted {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    };
+    let cache_headers = [
+        (header::CACHE_CONTROL, cache_control),
+        (header::ETAG, asset.etag.as_str()),
+    ];
+
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == asset.etag));
+    if unchanged {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (
+        cache_headers,
+        [(header::CONTENT_TYPE, asset.content_type)],
+        asset.contents,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_path_is_fingerprinted() {
+        let path = asset_path("lectara.css");
+        let fingerprint = path
+            .strip_prefix("/web/assets/lectara.")
+            .and_then(|rest| rest.strip_suffix(".css"))
+            .unwrap();
+        assert_eq!(fingerprint.len(), 8);
+        assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
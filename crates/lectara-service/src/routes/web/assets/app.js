@@ -0,0 +1,4 @@
+// Installs the service worker that lets the web UI open offline.
+if ("serviceWorker" in navigator) {
+  navigator.serviceWorker.register("/web/sw.js", { scope: "/web/" }).catch(() => {});
+}
@@ -0,0 +1,129 @@
+// Command palette: Ctrl+K (Cmd+K on macOS) searches saved items as you
+// type from any page. Arrow keys move through the results, Enter opens one
+// (or the full results page when nothing matched) and Escape closes it.
+(() => {
+  const DEBOUNCE_MS = 150;
+  const LIMIT = 10;
+
+  let overlay, input, list, timer, controller;
+  let results = [];
+  let selected = 0;
+
+  function build() {
+    overlay = document.createElement("div");
+    overlay.className = "palette";
+    overlay.hidden = true;
+    overlay.innerHTML =
+      '<div class="palette-box" role="dialog" aria-label="Search saved items">' +
+      '<input type="search" placeholder="Search saved items" aria-label="Search" autocomplete="off">' +
+      '<ul role="listbox"></ul>' +
+      "</div>";
+    document.body.appendChild(overlay);
+
+    input = overlay.querySelector("input");
+    list = overlay.querySelector("ul");
+    input.addEventListener("input", () => {
+      clearTimeout(timer);
+      timer = setTimeout(search, DEBOUNCE_MS);
+    });
+    input.addEventListener("keydown", onKey);
+    overlay.addEventListener("click", (event) => {
+      if (event.target === overlay) close();
+    });
+  }
+
+  function open() {
+    if (!overlay) build();
+    overlay.hidden = false;
+    input.select();
+    input.focus();
+  }
+
+  function close() {
+    overlay.hidden = true;
+  }
+
+  async function search() {
+    const q = input.value.trim();
+    if (controller) controller.abort();
+    if (!q) {
+      show([]);
+      return;
+    }
+
+    controller = new AbortController();
+    try {
+      const response = await fetch(
+        `/api/v1/search?limit=${LIMIT}&q=${encodeURIComponent(q)}`,
+        { signal: controller.signal },
+      );
+      // Half-typed queries like an open quote are rejected; keep typing
+      show(response.ok ? (await response.json()).items : []);
+    } catch (err) {
+      if (err.name !== "AbortError") show([]);
+    }
+  }
+
+  function show(items) {
+    results = items;
+    selected = 0;
+    list.replaceChildren(
+      ...items.map((item, index) => {
+        const option = document.createElement("li");
+        option.setAttribute("role", "option");
+        option.textContent = item.title || item.url;
+        option.addEventListener("click", () => go(index));
+        return option;
+      }),
+    );
+    highlight();
+  }
+
+  function highlight() {
+    [...list.children].forEach((option, index) =>
+      option.setAttribute("aria-selected", index === selected),
+    );
+    list.children[selected]?.scrollIntoView({ block: "nearest" });
+  }
+
+  function go(index) {
+    if (results[index]) window.location.href = results[index].url;
+  }
+
+  function onKey(event) {
+    switch (event.key) {
+      case "ArrowDown":
+        selected = Math.min(selected + 1, results.length - 1);
+        highlight();
+        break;
+      case "ArrowUp":
+        selected = Math.max(selected - 1, 0);
+        highlight();
+        break;
+      case "Enter":
+        if (results.length) {
+          go(selected);
+        } else if (input.value.trim()) {
+          window.location.href = `/web/search?q=${encodeURIComponent(input.value.trim())}`;
+        }
+        break;
+      case "Escape":
+        close();
+        break;
+      default:
+        return;
+    }
+    event.preventDefault();
+  }
+
+  document.addEventListener("keydown", (event) => {
+    if ((event.ctrlKey || event.metaKey) && event.key.toLowerCase() === "k") {
+      event.preventDefault();
+      if (overlay && !overlay.hidden) {
+        close();
+      } else {
+        open();
+      }
+    }
+  });
+})();
@@ -0,0 +1,33 @@
+// Reader view: font size buttons, remembered across visits, and the left
+// and right arrow keys to move to the newer or older item.
+(() => {
+  const STORAGE_KEY = "lectara-reader-font-size";
+  const SIZES = ["0.9rem", "1rem", "1.1rem", "1.25rem", "1.4rem", "1.6rem"];
+  const DEFAULT_SIZE = 2;
+
+  let size = Number(localStorage.getItem(STORAGE_KEY) ?? DEFAULT_SIZE);
+  if (!Number.isInteger(size)) size = DEFAULT_SIZE;
+
+  function applySize() {
+    size = Math.min(Math.max(size, 0), SIZES.length - 1);
+    document.documentElement.style.setProperty("--reader-font-size", SIZES[size]);
+    localStorage.setItem(STORAGE_KEY, size);
+  }
+
+  applySize();
+  document.querySelectorAll("[data-font-step]").forEach((button) =>
+    button.addEventListener("click", () => {
+      size += Number(button.dataset.fontStep);
+      applySize();
+    }),
+  );
+
+  document.addEventListener("keydown", (event) => {
+    if (event.defaultPrevented || event.ctrlKey || event.metaKey || event.altKey) return;
+    if (event.target.closest("input, textarea, select")) return;
+
+    const rel = { ArrowLeft: "prev", ArrowRight: "next" }[event.key];
+    const link = rel && document.querySelector(`.reader-nav a[rel="${rel}"]`);
+    if (link) window.location.href = link.href;
+  });
+})();
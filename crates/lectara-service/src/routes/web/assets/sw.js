@@ -0,0 +1,49 @@
+// Service worker for the web UI. Stylesheets and scripts are served from
+// a cache filled at install, and pages from the network, falling back to
+// the last copy seen (or the reading list) when offline. The server fills
+// in ASSETS and VERSION, so each release starts a fresh cache.
+const ASSETS = __ASSETS__;
+const CACHE = "lectara-__VERSION__";
+const SHELL = "/web";
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll([...ASSETS, SHELL])));
+  self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) =>
+        Promise.all(
+          keys
+            .filter((key) => key.startsWith("lectara-") && key !== CACHE)
+            .map((key) => caches.delete(key)),
+        ),
+      )
+      .then(() => self.clients.claim()),
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  if (request.method !== "GET" || url.origin !== self.location.origin) return;
+
+  if (url.pathname.startsWith("/web/assets/")) {
+    event.respondWith(caches.match(request).then((cached) => cached || fetch(request)));
+  } else if (request.mode === "navigate") {
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          if (response.ok) {
+            const copy = response.clone();
+            caches.open(CACHE).then((cache) => cache.put(request, copy));
+          }
+          return response;
+        })
+        .catch(() => caches.match(request).then((cached) => cached || caches.match(SHELL))),
+    );
+  }
+});
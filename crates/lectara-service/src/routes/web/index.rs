@@ -0,0 +1,50 @@
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Response},
+};
+use tracing::{debug, instrument};
+
+use super::theme::{Theme, theme_picker};
+use super::{render_collection_page, render_items};
+use crate::errors::ApiError;
+use crate::{
+    AppState,
+    repositories::{ContentFilter, ContentRepository, ListContentParams},
+};
+
+/// Number of items shown on the index
+const INDEX_SIZE: u32 = 50;
+
+/// The most recently saved items, with pinned items first
+#[instrument(skip_all)]
+pub async fn index<S: AppState>(
+    State(state): State<S>,
+    theme: Theme,
+) -> Result<Response, ApiError> {
+    debug!("Serving web index");
+
+    let params = ListContentParams {
+        limit: Some(INDEX_SIZE),
+        offset: None,
+        filter: ContentFilter::default(),
+        include_total: false,
+    };
+    let items = state.content_repo().list(&params).await?.items;
+
+    let mut content = "<h1>Reading list</h1>\n".to_string();
+    if items.is_empty() {
+        content.push_str("<p>Nothing saved yet.</p>");
+    } else {
+        content.push_str(&render_items(&items));
+    }
+    content.push('\n');
+    content.push_str(&theme_picker(theme));
+
+    Ok(Html(render_collection_page(
+        &state,
+        theme,
+        "Reading list",
+        &content,
+    ))
+    .into_response())
+}
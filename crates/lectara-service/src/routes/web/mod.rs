@@ -1,12 +1,197 @@
-use axum::Router;
-
-pub fn create_web_router<S>() -> Router<S>
-where
-    S: Clone + Send + Sync + 'static,
-{
-    Router::new()
-    // TODO: Add web app routes here
-    // For example:
-    // .route("/", get(index))
-    // .route("/app/*path", get(serve_static))
+use crate::AppState;
+use crate::limits::{RouteGroup, limit};
+use crate::models::ContentItemSummary;
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+#[cfg(feature = "activitypub")]
+pub mod activitypub;
+pub mod app;
+pub mod archive;
+pub mod assets;
+pub mod index;
+pub mod opds;
+pub mod read;
+pub mod save;
+pub mod search;
+pub mod share;
+pub mod theme;
+
+use theme::Theme;
+
+pub fn create_web_router<S: AppState>() -> Router<S> {
+    let router = Router::new()
+        .route("/", get(index::index::<S>))
+        .route("/manifest.webmanifest", get(app::manifest))
+        .route("/sw.js", get(app::service_worker))
+        .route("/read/{id}", get(read::read::<S>))
+        .route("/archive/{id}", get(archive::archive::<S>))
+        .route("/save", post(save::save::<S>))
+        .route(
+            "/search",
+            get(search::search::<S>).layer(limit(RouteGroup::Search)),
+        )
+        .route("/opds", get(opds::catalog::<S>))
+        .route("/assets/{file}", get(assets::serve_asset))
+        .route("/theme", post(theme::set_theme))
+        .route(
+            "/share/{token}",
+            get(share::view_share_link::<S>).post(share::unlock_share_link::<S>),
+        )
+        .route(
+            "/share/{token}/comments",
+            post(share::comment_on_share_link::<S>),
+        );
+    #[cfg(feature = "activitypub")]
+    let router = router
+        .route("/.well-known/webfinger", get(activitypub::webfinger::<S>))
+        .route("/ap/actor", get(activitypub::actor::<S>))
+        .route("/ap/outbox", get(activitypub::outbox::<S>))
+        .route("/ap/followers", get(activitypub::followers::<S>))
+        .route("/ap/notes/{id}", get(activitypub::note::<S>))
+        .route("/ap/inbox", post(activitypub::inbox::<S>));
+    router
+}
+
+/// Escapes text for safe inclusion in HTML element content and attribute values
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A list of items linking to their URLs, with pinned items marked and
+/// items with a stored body linking to the reader view
+pub fn render_items(items: &[ContentItemSummary]) -> String {
+    let mut list = "<ul class=\"items\">\n".to_string();
+    for item in items {
+        let title = item.title.as_deref().unwrap_or(&item.url);
+        list.push_str("<li>");
+        if item.pinned_position.is_some() {
+            list.push_str("<span class=\"pinned\">Pinned</span> ");
+        }
+        list.push_str(&format!(
+            "<a href=\"{}\" rel=\"noopener noreferrer\">{}</a>",
+            escape_html(&item.url),
+            escape_html(title)
+        ));
+        if item.body_hash.is_some() {
+            list.push_str(&format!(
+                " <a class=\"read\" href=\"/web/read/{}\">Read</a>",
+                item.id
+            ));
+        }
+        if let Some(author) = &item.author {
+            list.push_str(&format!(
+                " <span class=\"meta\">{}</span>",
+                escape_html(author)
+            ));
+        }
+        list.push_str("</li>\n");
+    }
+    list.push_str("</ul>");
+    list
+}
+
+/// Wraps page content in the shared HTML document shell, with a banner
+/// announcing any scheduled maintenance
+pub fn render_page<S: AppState>(state: &S, title: &str, content: &str) -> String {
+    render_document(state, Theme::default(), title, content, "", "")
+}
+
+/// [`render_page`] for pages browsing the collection, which also get the
+/// visitor's theme, the search palette and the app manifest. Share pages go
+/// without, as their visitors are other people and can't search.
+pub fn render_collection_page<S: AppState>(
+    state: &S,
+    theme: Theme,
+    title: &str,
+    content: &str,
+) -> String {
+    let scripts = ["palette.js", "app.js"]
+        .into_iter()
+        .map(|script| {
+            format!(
+                "<script src=\"{}\" defer></script>\n",
+                assets::asset_path(script)
+            )
+        })
+        .collect::<String>();
+    render_document(state, theme, title, content, &app::head_tags(), &scripts)
+}
+
+fn render_document<S: AppState>(
+    state: &S,
+    theme: Theme,
+    title: &str,
+    content: &str,
+    head: &str,
+    scripts: &str,
+) -> String {
+    let banner = state
+        .maintenance()
+        .current(chrono::Utc::now().naive_utc())
+        .map(|window| {
+            format!(
+                "<p class=\"banner\" role=\"status\">{}</p>\n",
+                escape_html(&window.notice())
+            )
+        })
+        .unwrap_or_default();
+    let mut stylesheets = format!(
+        "<link rel=\"stylesheet\" href=\"{}\">\n",
+        assets::asset_path("lectara.css")
+    );
+    if let Some(stylesheet) = theme.stylesheet() {
+        stylesheets.push_str(&format!(
+            "<link rel=\"stylesheet\" href=\"{}\">\n",
+            assets::asset_path(&stylesheet)
+        ));
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en" class="theme-{theme}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{title}</title>
+{stylesheets}{head}</head>
+<body>
+{banner}{content}
+{scripts}</body>
+</html>
+"#,
+        theme = theme.name(),
+        title = escape_html(title),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html_escapes_markup() {
+        assert_eq!(
+            escape_html(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#x27;Jerry&#x27;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_escape_html_leaves_plain_text() {
+        assert_eq!(escape_html("plain text"), "plain text");
+    }
 }
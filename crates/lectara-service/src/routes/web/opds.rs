@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use crate::errors::ApiError;
+use crate::export::opds;
+use crate::{AppState, repositories::ContentRepository};
+
+/// Entries per page of the catalog
+const PAGE_SIZE: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct OpdsQuery {
+    page: Option<u32>,
+}
+
+/// Saved PDFs and EPUBs as an OPDS catalog, newest first
+#[instrument(skip_all, fields(page = query.page))]
+pub async fn catalog<S: AppState>(
+    State(state): State<S>,
+    Query(query): Query<OpdsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    debug!("Serving OPDS catalog");
+
+    let page = query.page.unwrap_or(1).max(1);
+    // One more than a page tells whether there is a next one
+    let mut items = state
+        .content_repo()
+        .list_documents(PAGE_SIZE + 1, (page - 1).saturating_mul(PAGE_SIZE))
+        .await?;
+    let has_next = items.len() > PAGE_SIZE as usize;
+    items.truncate(PAGE_SIZE as usize);
+
+    info!(
+        document_count = items.len(),
+        page, "Successfully rendered OPDS catalog"
+    );
+
+    Ok((
+        [(header::CONTENT_TYPE, opds::ACQUISITION_TYPE)],
+        opds::render(&items, "/web/opds", page, has_next, Utc::now()),
+    ))
+}
@@ -0,0 +1,210 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use super::theme::Theme;
+use super::{assets, escape_html, render_collection_page};
+use crate::errors::ApiError;
+use crate::models::ContentItemSummary;
+use crate::routes::extract::deserialize_optional_datetime;
+use crate::validation;
+use crate::{
+    AppState,
+    repositories::{ContentFilter, ContentRepository},
+};
+
+/// Reading speed the estimated reading time assumes
+const WORDS_PER_MINUTE: usize = 230;
+
+/// The list filter the reader navigates within, as on the list endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct ReaderQuery {
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    since: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    until: Option<DateTime<Utc>>,
+    content_type: Option<String>,
+    source: Option<String>,
+}
+
+impl ReaderQuery {
+    fn filter(&self) -> Result<ContentFilter, ApiError> {
+        Ok(ContentFilter {
+            since: self.since.map(|since| since.naive_utc()),
+            until: self.until.map(|until| until.naive_utc()),
+            content_type: self.content_type.clone(),
+            source: self
+                .source
+                .as_deref()
+                .map(validation::parse_source)
+                .transpose()?,
+        })
+    }
+
+    /// The filter as a query string for links to the neighbouring items,
+    /// with relative times pinned so the sequence doesn't drift
+    fn query_string(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(since) = self.since {
+            query.append_pair("since", &since.to_rfc3339());
+        }
+        if let Some(until) = self.until {
+            query.append_pair("until", &until.to_rfc3339());
+        }
+        if let Some(content_type) = &self.content_type {
+            query.append_pair("content_type", content_type);
+        }
+        if let Some(source) = &self.source {
+            query.append_pair("source", source);
+        }
+        match query.finish() {
+            query if query.is_empty() => query,
+            query => format!("?{query}"),
+        }
+    }
+}
+
+/// Estimated minutes to read `words`, at least one
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE).max(1)
+}
+
+/// The stored text as paragraphs, split at blank lines
+fn render_paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>\n", escape_html(paragraph)))
+        .collect()
+}
+
+fn neighbour_link(item: &ContentItemSummary, rel: &str, label: &str, query: &str) -> String {
+    format!(
+        "<a rel=\"{rel}\" href=\"/web/read/{id}{query}\">{label}: {title}</a>",
+        id = item.id,
+        query = escape_html(query),
+        title = escape_html(item.title.as_deref().unwrap_or(&item.url))
+    )
+}
+
+/// A saved item's stored text in a reading layout, linking to the items
+/// saved before and after it within the list filter in the query
+#[instrument(skip_all, fields(id = %id))]
+pub async fn read<S: AppState>(
+    State(state): State<S>,
+    theme: Theme,
+    Path(id): Path<i32>,
+    Query(query): Query<ReaderQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Serving reader view");
+
+    let filter = query.filter()?;
+    let content_repo = state.content_repo();
+    let Some(item) = content_repo.find_by_id(id).await? else {
+        let content =
+            "<h1>Item not found</h1>\n<p><a href=\"/web\">Back to the reading list</a></p>";
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Html(render_collection_page(
+                &state,
+                theme,
+                "Item not found",
+                content,
+            )),
+        )
+            .into_response());
+    };
+    let adjacent = content_repo
+        .adjacent(item.id, item.created_at, &filter)
+        .await?;
+
+    let title = item.title.as_deref().unwrap_or(&item.url);
+    let mut content = format!(
+        "<article class=\"reader\">\n<h1>{}</h1>\n<p class=\"meta\">",
+        escape_html(title)
+    );
+    if let Some(author) = &item.author {
+        content.push_str(&format!("{} &middot; ", escape_html(author)));
+    }
+    content.push_str(&format!(
+        "<a href=\"{url}\" rel=\"noopener noreferrer\">{url}</a>",
+        url = escape_html(&item.url)
+    ));
+
+    match item.plain_text() {
+        Some(text) if !text.trim().is_empty() => {
+            content.push_str(&format!(
+                " &middot; {} min read</p>\n\
+                 <p class=\"reader-controls\">\
+                 <button type=\"button\" data-font-step=\"-1\" aria-label=\"Smaller text\">A&minus;</button> \
+                 <button type=\"button\" data-font-step=\"1\" aria-label=\"Larger text\">A+</button>\
+                 </p>\n<div class=\"reader-body\">\n{}</div>\n",
+                reading_minutes(item.word_count()),
+                render_paragraphs(text)
+            ));
+        }
+        _ => content.push_str("</p>\n<p>No text was saved for this item.</p>\n"),
+    }
+    content.push_str("</article>\n");
+
+    let query_string = query.query_string();
+    content.push_str("<nav class=\"reader-nav\">\n");
+    if let Some(newer) = &adjacent.newer {
+        content.push_str(&neighbour_link(newer, "prev", "Newer", &query_string));
+        content.push('\n');
+    }
+    if let Some(older) = &adjacent.older {
+        content.push_str(&neighbour_link(older, "next", "Older", &query_string));
+        content.push('\n');
+    }
+    content.push_str("</nav>\n");
+    content.push_str(&format!(
+        "<script src=\"{}\" defer></script>",
+        assets::asset_path("reader.js")
+    ));
+
+    info!(id = item.id, "Serving item in reader view");
+
+    Ok(Html(render_collection_page(&state, theme, title, &content)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_minutes() {
+        assert_eq!(reading_minutes(0), 1);
+        assert_eq!(reading_minutes(230), 1);
+        assert_eq!(reading_minutes(231), 2);
+        assert_eq!(reading_minutes(2300), 10);
+    }
+
+    #[test]
+    fn test_render_paragraphs() {
+        assert_eq!(
+            render_paragraphs("First <b>\n\n\n\nSecond\nline\n\n  "),
+            "<p>First &lt;b&gt;</p>\n<p>Second\nline</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_query_string_pins_filter() {
+        assert_eq!(ReaderQuery::default().query_string(), "");
+
+        let query = ReaderQuery {
+            since: DateTime::from_timestamp(1704153600, 0),
+            content_type: Some("article".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.query_string(),
+            "?since=2024-01-02T00%3A00%3A00%2B00%3A00&content_type=article"
+        );
+    }
+}
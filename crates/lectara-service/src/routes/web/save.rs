@@ -0,0 +1,136 @@
+use axum::{
+    Form,
+    extract::State,
+    http::{StatusCode, header},
+    response::{Html, IntoResponse, Response},
+};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+
+use super::theme::Theme;
+use super::{escape_html, render_collection_page};
+use crate::AppState;
+use crate::errors::ApiError;
+use crate::models::NewContentItem;
+use crate::read_only::Writable;
+use crate::routes::api::v1::{SaveOutcome, save_content};
+use crate::validation::NormalizedUrl;
+
+/// Source recorded for items saved through the web UI
+const WEB_SOURCE: &str = "web";
+
+/// What a share sheet sends: apps put the link in `url` or, often, in
+/// `text` along with other words
+#[derive(Debug, Deserialize)]
+pub struct SaveForm {
+    url: Option<String>,
+    title: Option<String>,
+    text: Option<String>,
+}
+
+impl SaveForm {
+    fn url(&self) -> Option<&str> {
+        self.url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .or_else(|| {
+                self.text
+                    .as_deref()?
+                    .split_whitespace()
+                    .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+            })
+    }
+
+    fn title(&self) -> Option<String> {
+        self.title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string)
+    }
+}
+
+fn error_page<S: AppState>(state: &S, theme: Theme, message: &str) -> Response {
+    let content = format!(
+        "<h1>Couldn't save</h1>\n<p class=\"error\">{}</p>\n<p><a href=\"/web\">Back to the reading list</a></p>",
+        escape_html(message)
+    );
+    (
+        StatusCode::BAD_REQUEST,
+        Html(render_collection_page(
+            state,
+            theme,
+            "Couldn't save",
+            &content,
+        )),
+    )
+        .into_response()
+}
+
+/// Saves a link shared from another app, then shows the reading list. A
+/// link that is already saved is left as it is, even if the shared title
+/// differs.
+#[instrument(skip_all)]
+pub async fn save<S: AppState>(
+    State(state): State<S>,
+    theme: Theme,
+    _: Writable,
+    Form(form): Form<SaveForm>,
+) -> Result<Response, ApiError> {
+    debug!("Processing web save request");
+
+    let Some(url) = form.url() else {
+        return Ok(error_page(&state, theme, "No link was shared"));
+    };
+    let new_content = NormalizedUrl::parse(url, state.validation())
+        .and_then(|url| {
+            NewContentItem::from_normalized(url, form.title(), None, None, state.validation())
+        })
+        .and_then(|new_content| new_content.with_source(WEB_SOURCE));
+    let new_content = match new_content {
+        Ok(new_content) => new_content,
+        Err(err) => return Ok(error_page(&state, theme, &err.to_string())),
+    };
+
+    let id = match save_content(&state, &new_content).await {
+        Ok(SaveOutcome::Created(id) | SaveOutcome::Existing(id)) => id,
+        Err(ApiError::DuplicateUrlDifferentMetadata(conflict)) => conflict.existing.id,
+        Err(err) => return Err(err),
+    };
+    info!(id, "Saved shared link");
+
+    Ok((StatusCode::SEE_OTHER, [(header::LOCATION, "/web")]).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_from_shared_text() {
+        let form = |url: Option<&str>, text: Option<&str>| SaveForm {
+            url: url.map(str::to_string),
+            title: None,
+            text: text.map(str::to_string),
+        };
+
+        assert_eq!(
+            form(
+                Some(" https://example.com/a "),
+                Some("https://example.com/b")
+            )
+            .url(),
+            Some("https://example.com/a")
+        );
+        assert_eq!(
+            form(
+                Some(""),
+                Some("Worth a read https://example.com/b via @app")
+            )
+            .url(),
+            Some("https://example.com/b")
+        );
+        assert_eq!(form(None, Some("no link here")).url(), None);
+    }
+}
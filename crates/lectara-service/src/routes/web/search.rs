@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use super::theme::Theme;
+use super::{escape_html, render_collection_page, render_items};
+use crate::errors::ApiError;
+use crate::search::{SearchLanguage, SearchQuery};
+use crate::{
+    AppState,
+    repositories::{ContentFilter, ContentRepository, SearchContentParams},
+};
+
+/// Number of results shown on the search page
+const RESULTS_SIZE: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchPageQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Full-text search results, best match first. Works without JavaScript;
+/// the palette links here when the query is submitted without a match.
+#[instrument(skip_all, fields(q = %query.q))]
+pub async fn search<S: AppState>(
+    State(state): State<S>,
+    theme: Theme,
+    Query(query): Query<SearchPageQuery>,
+) -> Result<Response, ApiError> {
+    debug!("Serving web search");
+
+    let q = query.q.trim();
+    let mut content = format!(
+        "<h1>Search</h1>\n\
+         <form action=\"/web/search\" role=\"search\">\n\
+         <input type=\"search\" name=\"q\" value=\"{}\" aria-label=\"Search saved items\" autofocus>\n\
+         <button type=\"submit\">Search</button>\n\
+         </form>\n",
+        escape_html(q)
+    );
+    if q.is_empty() {
+        content.push_str("<p class=\"meta\">Press Ctrl+K on any page to search as you type.</p>");
+        return Ok(Html(render_collection_page(&state, theme, "Search", &content)).into_response());
+    }
+
+    let title = format!("Search: {q}");
+    let search_query = match SearchQuery::parse(q) {
+        Ok(search_query) => search_query,
+        Err(err) => {
+            content.push_str(&format!(
+                "<p class=\"error\">{}</p>",
+                escape_html(&err.to_string())
+            ));
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                Html(render_collection_page(&state, theme, &title, &content)),
+            )
+                .into_response());
+        }
+    };
+
+    let params = SearchContentParams {
+        query: search_query,
+        language: SearchLanguage::default(),
+        limit: Some(RESULTS_SIZE),
+        offset: None,
+        filter: ContentFilter::default(),
+    };
+    let items = state.content_repo().search(&params).await?;
+    if items.is_empty() {
+        content.push_str("<p>No matches.</p>");
+    } else {
+        content.push_str(&render_items(&items));
+    }
+
+    Ok(Html(render_collection_page(&state, theme, &title, &content)).into_response())
+}
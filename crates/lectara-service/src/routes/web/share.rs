@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use axum::{
+    Form,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use tracing::{debug, info, instrument, warn};
+
+use super::{escape_html, render_page};
+use crate::comments::{self, MAX_AUTHOR_LENGTH, MAX_BODY_LENGTH};
+use crate::errors::ApiError;
+use crate::models::{Comment, NewComment, ShareLink};
+use crate::passphrases;
+use crate::read_only::Writable;
+use crate::{
+    AppState,
+    repositories::{CommentRepository, ContentRepository, ShareLinkRepository},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PassphraseForm {
+    passphrase: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentForm {
+    author: String,
+    body: String,
+    /// The comment this one replies to
+    parent_id: Option<i32>,
+    /// Needed again for a protected link, as nothing remembers the unlock
+    passphrase: Option<String>,
+}
+
+fn not_found_page<S: AppState>(state: &S) -> Response {
+    let content = "<h1>Link not found</h1>\n<p>This share link does not exist, has expired, or was revoked.</p>";
+    (
+        StatusCode::NOT_FOUND,
+        Html(render_page(state, "Link not found", content)),
+    )
+        .into_response()
+}
+
+/// Asks for the passphrase of a protected link, posting to the link itself
+fn passphrase_page<S: AppState>(
+    state: &S,
+    token: &str,
+    status: StatusCode,
+    error: Option<&str>,
+) -> Response {
+    let mut content = "<h1>Passphrase required</h1>\n".to_string();
+    if let Some(error) = error {
+        content.push_str(&format!("<p class=\"error\">{}</p>\n", escape_html(error)));
+    }
+    content.push_str(&format!(
+        "<form method=\"post\" action=\"/web/share/{}\">\n\
+         <label for=\"passphrase\">Passphrase</label>\n\
+         <input type=\"password\" id=\"passphrase\" name=\"passphrase\" required autofocus>\n\
+         <button type=\"submit\">View</button>\n\
+         </form>",
+        escape_html(token)
+    ));
+    (
+        status,
+        Html(render_page(state, "Passphrase required", &content)),
+    )
+        .into_response()
+}
+
+fn locked_page<S: AppState>(
+    state: &S,
+    locked_until: chrono::NaiveDateTime,
+    now: chrono::NaiveDateTime,
+) -> Response {
+    let retry_after = (locked_until - now).num_seconds().max(1);
+    let content = "<h1>Too many attempts</h1>\n<p>This link is locked after too many incorrect passphrases. Try again later.</p>";
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        Html(render_page(state, "Too many attempts", content)),
+    )
+        .into_response()
+}
+
+/// Looks up a link that can still be viewed
+async fn find_active_link<S: AppState>(
+    state: &S,
+    token: &str,
+) -> Result<Option<ShareLink>, ApiError> {
+    let Some(link) = state.share_link_repo().find_by_token(token).await? else {
+        debug!("Share link not found");
+        return Ok(None);
+    };
+
+    if !link.is_active(chrono::Utc::now().naive_utc()) {
+        debug!(
+            content_id = link.content_id,
+            "Share link expired or revoked"
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(link))
+}
+
+#[instrument(skip_all)]
+pub async fn view_share_link<S: AppState>(
+    State(state): State<S>,
+    Path(token): Path<String>,
+) -> Result<Response, ApiError> {
+    debug!("Processing view share link request");
+
+    let Some(link) = find_active_link(&state, &token).await? else {
+        return Ok(not_found_page(&state));
+    };
+
+    if link.passphrase_hash.is_some() {
+        debug!(
+            content_id = link.content_id,
+            "Share link needs a passphrase"
+        );
+        return Ok(passphrase_page(&state, &token, StatusCode::OK, None));
+    }
+
+    render_shared_item(&state, &link).await
+}
+
+#[instrument(skip_all)]
+pub async fn unlock_share_link<S: AppState>(
+    State(state): State<S>,
+    // Failed guesses can't be counted, and so limited, without writing
+    _: Writable,
+    Path(token): Path<String>,
+    Form(form): Form<PassphraseForm>,
+) -> Result<Response, ApiError> {
+    debug!("Processing unlock share link request");
+
+    let Some(link) = find_active_link(&state, &token).await? else {
+        return Ok(not_found_page(&state));
+    };
+
+    let Some(hash) = link.passphrase_hash.clone() else {
+        return render_shared_item(&state, &link).await;
+    };
+
+    if let Some(rejected) = check_passphrase(&state, &link, hash, form.passphrase).await? {
+        return Ok(rejected);
+    }
+
+    // The unlocked page must not be served to the next visitor from a cache
+    let mut response = render_shared_item(&state, &link).await?;
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_static("no-store"),
+    );
+    Ok(response)
+}
+
+/// Checks `passphrase` against the `hash` of a protected link, counting
+/// failures towards a lockout. Returns the page to answer with instead when
+/// it doesn't unlock the link.
+async fn check_passphrase<S: AppState>(
+    state: &S,
+    link: &ShareLink,
+    hash: String,
+    passphrase: String,
+) -> Result<Option<Response>, ApiError> {
+    let now = chrono::Utc::now().naive_utc();
+    if let Some(locked_until) = passphrases::locked_until(link, now) {
+        debug!(content_id = link.content_id, "Share link is locked");
+        return Ok(Some(locked_page(state, locked_until, now)));
+    }
+
+    // Verification recomputes the slow hash, so it stays off the async workers
+    let matches = tokio::task::spawn_blocking(move || passphrases::verify(&passphrase, &hash))
+        .await
+        .map_err(|_| ApiError::InternalError)?;
+
+    if !matches {
+        let link = state
+            .share_link_repo()
+            .record_failed_attempt(link.id, now, now - passphrases::LOCKOUT)
+            .await?;
+        warn!(
+            content_id = link.content_id,
+            failed_attempts = link.failed_attempts,
+            "Incorrect share link passphrase"
+        );
+        if let Some(locked_until) = passphrases::locked_until(&link, now) {
+            return Ok(Some(locked_page(state, locked_until, now)));
+        }
+        return Ok(Some(passphrase_page(
+            state,
+            &link.token,
+            StatusCode::UNAUTHORIZED,
+            Some("Incorrect passphrase."),
+        )));
+    }
+
+    if link.failed_attempts > 0 {
+        state
+            .share_link_repo()
+            .clear_failed_attempts(link.id)
+            .await?;
+    }
+    Ok(None)
+}
+
+#[instrument(skip_all, fields(reply = form.parent_id.is_some()))]
+pub async fn comment_on_share_link<S: AppState>(
+    State(state): State<S>,
+    _: Writable,
+    Path(token): Path<String>,
+    Form(form): Form<CommentForm>,
+) -> Result<Response, ApiError> {
+    debug!("Processing share link comment request");
+
+    let Some(link) = find_active_link(&state, &token).await? else {
+        return Ok(not_found_page(&state));
+    };
+
+    if let Some(hash) = link.passphrase_hash.clone() {
+        let Some(passphrase) = form.passphrase else {
+            return Ok(passphrase_page(
+                &state,
+                &token,
+                StatusCode::UNAUTHORIZED,
+                None,
+            ));
+        };
+        if let Some(rejected) = check_passphrase(&state, &link, hash, passphrase).await? {
+            return Ok(rejected);
+        }
+    }
+
+    let Some(item) = state.content_repo().find_by_id(link.content_id).await? else {
+        debug!(
+            content_id = link.content_id,
+            "Shared content item not found"
+        );
+        return Ok(not_found_page(&state));
+    };
+
+    let comment = NewComment {
+        content_id: item.id,
+        parent_id: form.parent_id,
+        author: form.author,
+        body: form.body,
+    };
+    let comment = match comments::post(&state, &item, comment).await {
+        Ok(comment) => comment,
+        Err(ApiError::BadRequest(message)) => {
+            let content = format!(
+                "<h1>Comment not posted</h1>\n<p class=\"error\">{}</p>\n\
+                 <p>Go back to change it and try again.</p>",
+                escape_html(&message)
+            );
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                Html(render_page(&state, "Comment not posted", &content)),
+            )
+                .into_response());
+        }
+        Err(err) => return Err(err),
+    };
+
+    if link.passphrase_hash.is_none() {
+        return Ok(
+            Redirect::to(&format!("/web/share/{token}#comment-{}", comment.id)).into_response(),
+        );
+    }
+
+    // Going back to the link would ask for the passphrase again
+    let mut response = render_shared_item(&state, &link).await?;
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_static("no-store"),
+    );
+    Ok(response)
+}
+
+async fn render_shared_item<S: AppState>(
+    state: &S,
+    link: &ShareLink,
+) -> Result<Response, ApiError> {
+    let Some(item) = state.content_repo().find_by_id(link.content_id).await? else {
+        debug!(
+            content_id = link.content_id,
+            "Shared content item not found"
+        );
+        return Ok(not_found_page(state));
+    };
+
+    let title = item.title.as_deref().unwrap_or(&item.url);
+
+    let mut content = format!("<h1>{}</h1>\n<p class=\"meta\">", escape_html(title));
+    if let Some(author) = &item.author {
+        content.push_str(&format!("{} &middot; ", escape_html(author)));
+    }
+    content.push_str(&format!(
+        "<a href=\"{url}\" rel=\"noopener noreferrer\">{url}</a></p>\n",
+        url = escape_html(&item.url)
+    ));
+    if let Some(body) = &item.body {
+        content.push_str(&format!(
+            "<div class=\"body\">{}</div>\n",
+            escape_html(body)
+        ));
+    }
+
+    let comments = state.comment_repo().list_for_content(item.id).await?;
+    content.push_str(&render_comments(
+        &link.token,
+        &comments,
+        link.passphrase_hash.is_some(),
+    ));
+
+    info!(content_id = item.id, "Serving shared content item");
+
+    Ok(Html(render_page(state, title, &content)).into_response())
+}
+
+/// The comments on a shared item as nested lists, each with a form to
+/// reply, followed by a form for a new comment
+fn render_comments(token: &str, comments: &[Comment], protected: bool) -> String {
+    let mut replies: HashMap<Option<i32>, Vec<&Comment>> = HashMap::new();
+    for comment in comments {
+        replies.entry(comment.parent_id).or_default().push(comment);
+    }
+
+    let mut html = "<section class=\"comments\">\n<h2>Comments</h2>\n<ul>\n".to_string();
+    // Walked with a stack rather than recursion, so deep threads can't
+    // exhaust the stack
+    let mut levels = vec![replies.remove(&None).unwrap_or_default().into_iter()];
+    while let Some(level) = levels.last_mut() {
+        let Some(comment) = level.next() else {
+            levels.pop();
+            html.push_str("</ul>\n");
+            if !levels.is_empty() {
+                html.push_str("</li>\n");
+            }
+            continue;
+        };
+
+        html.push_str(&format!(
+            "<li id=\"comment-{id}\">\n<p class=\"meta\"><strong>{author}</strong> &middot; {date}</p>\n\
+             <p class=\"comment-body\">{body}</p>\n\
+             <details><summary>Reply</summary>\n{form}</details>\n",
+            id = comment.id,
+            author = escape_html(&comment.author),
+            date = comment.created_at.format("%Y-%m-%d %H:%M"),
+            body = escape_html(&comment.body),
+            form = comment_form(token, Some(comment.id), protected),
+        ));
+        match replies.remove(&Some(comment.id)) {
+            Some(children) => {
+                html.push_str("<ul>\n");
+                levels.push(children.into_iter());
+            }
+            None => html.push_str("</li>\n"),
+        }
+    }
+
+    html.push_str(&comment_form(token, None, protected));
+    html.push_str("</section>\n");
+    html
+}
+
+fn comment_form(token: &str, parent_id: Option<i32>, protected: bool) -> String {
+    let mut form = format!(
+        "<form class=\"comment-form\" method=\"post\" action=\"/web/share/{}/comments\">\n",
+        escape_html(token)
+    );
+    if let Some(parent_id) = parent_id {
+        form.push_str(&format!(
+            "<input type=\"hidden\" name=\"parent_id\" value=\"{parent_id}\">\n"
+        ));
+    }
+    form.push_str(&format!(
+        "<label>Name <input name=\"author\" required maxlength=\"{MAX_AUTHOR_LENGTH}\"></label>\n\
+         <label>Comment <textarea name=\"body\" rows=\"3\" required maxlength=\"{MAX_BODY_LENGTH}\"></textarea></label>\n"
+    ));
+    if protected {
+        form.push_str(
+            "<label>Passphrase <input type=\"password\" name=\"passphrase\" required></label>\n",
+        );
+    }
+    form.push_str(&format!(
+        "<button type=\"submit\">{}</button>\n</form>\n",
+        if parent_id.is_some() {
+            "Reply"
+        } else {
+            "Comment"
+        }
+    ));
+    form
+}
@@ -0,0 +1,158 @@
+//! Colour themes for the web pages.
+//!
+//! Pages follow the system's light or dark setting unless the visitor
+//! picked a theme, which is kept in a cookie since the web UI has no
+//! accounts. Besides the built-in `system`, `light` and `dark`, each
+//! stylesheet in `assets/themes/` is a theme named after its file, compiled
+//! in by the build script: dropping in `sepia.css` adds a `sepia` theme.
+//! Pages carry the theme as a `theme-<name>` class on the root element for
+//! the theme's rules to hang off.
+
+use std::convert::Infallible;
+
+use axum::{
+    Form,
+    extract::FromRequestParts,
+    http::{StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use tracing::{debug, instrument};
+
+use super::escape_html;
+use crate::errors::ApiError;
+
+include!(concat!(env!("OUT_DIR"), "/themes.rs"));
+
+/// Cookie remembering the chosen theme
+pub const THEME_COOKIE: &str = "lectara_theme";
+
+/// Themes provided by the base stylesheet
+const BUILT_IN: &[&str] = &["system", "light", "dark"];
+
+/// How long the chosen theme is remembered
+const COOKIE_MAX_AGE_SECS: u32 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme(&'static str);
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::SYSTEM
+    }
+}
+
+impl Theme {
+    /// Follows the system's light or dark setting
+    pub const SYSTEM: Self = Self("system");
+
+    /// The built-in themes followed by the custom ones
+    pub fn all() -> impl Iterator<Item = Self> {
+        BUILT_IN
+            .iter()
+            .copied()
+            .chain(CUSTOM_THEMES.iter().map(|(name, _)| *name))
+            .map(Self)
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().find(|theme| theme.0 == name.trim())
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// Name of the asset holding a custom theme's stylesheet
+    pub fn stylesheet(&self) -> Option<String> {
+        (!BUILT_IN.contains(&self.0)).then(|| format!("theme-{}.css", self.0))
+    }
+}
+
+/// The theme picked by the visitor, or the default for a missing or
+/// unknown cookie
+impl<S: Send + Sync> FromRequestParts<S> for Theme {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let theme = parts
+            .headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == THEME_COOKIE)
+            .and_then(|(_, value)| Self::parse(value));
+        Ok(theme.unwrap_or_default())
+    }
+}
+
+/// A form posting the chosen theme to [`set_theme`]
+pub fn theme_picker(current: Theme) -> String {
+    let mut form = "<form method=\"post\" action=\"/web/theme\" class=\"theme-picker\">\n\
+                    <label for=\"theme\">Theme</label>\n\
+                    <select id=\"theme\" name=\"theme\">\n"
+        .to_string();
+    for theme in Theme::all() {
+        let selected = if theme == current { " selected" } else { "" };
+        form.push_str(&format!(
+            "<option value=\"{name}\"{selected}>{name}</option>\n",
+            name = escape_html(theme.name())
+        ));
+    }
+    form.push_str("</select>\n<button type=\"submit\">Apply</button>\n</form>");
+    form
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThemeForm {
+    theme: String,
+}
+
+/// Remembers the chosen theme and returns to the reading list
+#[instrument(skip_all, fields(theme = %form.theme))]
+pub async fn set_theme(Form(form): Form<ThemeForm>) -> Result<Response, ApiError> {
+    let theme = Theme::parse(&form.theme)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown theme '{}'", form.theme)))?;
+    debug!("Setting web theme");
+
+    let cookie = format!(
+        "{THEME_COOKIE}={}; Path=/web; Max-Age={COOKIE_MAX_AGE_SECS}; SameSite=Lax",
+        theme.name()
+    );
+    Ok((
+        StatusCode::SEE_OTHER,
+        [
+            (header::LOCATION, "/web".to_string()),
+            (header::SET_COOKIE, cookie),
+        ],
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!(Theme::parse("dark").map(|theme| theme.name()), Some("dark"));
+        assert_eq!(
+            Theme::parse(" sepia ").map(|theme| theme.name()),
+            Some("sepia")
+        );
+        assert_eq!(Theme::parse("Dark"), None);
+        assert_eq!(Theme::parse("../lectara"), None);
+    }
+
+    #[test]
+    fn test_only_custom_themes_have_stylesheets() {
+        assert_eq!(Theme::SYSTEM.stylesheet(), None);
+        assert_eq!(Theme::parse("dark").unwrap().stylesheet(), None);
+        assert_eq!(
+            Theme::parse("sepia").unwrap().stylesheet().as_deref(),
+            Some("theme-sepia.css")
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Rules applied to newly saved items.
+//!
+//! A rule matches items by domain (subdomains included), by a regular
+//! expression over the URL, or both. A matching rule can give the item a
+//! `content_type`, or keep its page from being fetched for enrichment.
+//! Rules run in order of position, so when several matching rules set a
+//! content type the last one wins.
+
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+use url::Url;
+
+use crate::errors::ApiError;
+use crate::models::{NewRule, Rule};
+use crate::validation::host_matches;
+
+/// Longest rule name accepted
+const MAX_NAME_LENGTH: usize = 200;
+
+/// Compiled size limit of a URL pattern, well above any reasonable one
+const MAX_PATTERN_SIZE: usize = 1 << 20;
+
+/// What the rules matching an item do to it
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RuleOutcome {
+    /// Ids of the matching rules, in the order they ran
+    pub matched: Vec<i32>,
+    pub content_type: Option<String>,
+    pub skip_enrichment: bool,
+}
+
+/// Runs `rules`, already in order, against an item saved as `url`
+pub fn evaluate(rules: &[Rule], url: &str) -> RuleOutcome {
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase));
+
+    let mut outcome = RuleOutcome::default();
+    for rule in rules {
+        if !matches(rule, host.as_deref(), url) {
+            continue;
+        }
+        outcome.matched.push(rule.id);
+        if let Some(content_type) = &rule.set_content_type {
+            outcome.content_type = Some(content_type.clone());
+        }
+        outcome.skip_enrichment |= rule.skip_enrichment;
+    }
+    outcome
+}
+
+fn matches(rule: &Rule, host: Option<&str>, url: &str) -> bool {
+    if let Some(domain) = &rule.domain
+        && !host.is_some_and(|host| host_matches(host, domain))
+    {
+        return false;
+    }
+    if let Some(pattern) = &rule.url_pattern {
+        match compile(pattern) {
+            Ok(regex) => return regex.is_match(url),
+            Err(err) => {
+                // Patterns are checked when saved, so this takes a regex
+                // crate that no longer accepts them
+                warn!(rule_id = rule.id, error = %err, "Skipping rule with invalid pattern");
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+}
+
+/// Normalizes a rule before it is stored, rejecting ones that match
+/// everything, do nothing or can't be evaluated
+pub fn validate(mut rule: NewRule) -> Result<NewRule, ApiError> {
+    let bad_request = |message: String| Err(ApiError::BadRequest(message));
+
+    rule.name = rule.name.trim().to_string();
+    if rule.name.is_empty() {
+        return bad_request("'name' must not be empty".to_string());
+    }
+    if rule.name.chars().count() > MAX_NAME_LENGTH {
+        return bad_request(format!(
+            "'name' must be at most {MAX_NAME_LENGTH} characters"
+        ));
+    }
+
+    rule.domain = non_empty(rule.domain).map(|domain| domain.to_ascii_lowercase());
+    if let Some(domain) = &rule.domain
+        && (domain.contains(['/', ':', ' ']) || domain.starts_with('.'))
+    {
+        return bad_request(format!("'domain' must be a host name, not '{domain}'"));
+    }
+
+    rule.url_pattern = rule.url_pattern.filter(|pattern| !pattern.is_empty());
+    if let Some(pattern) = &rule.url_pattern
+        && let Err(err) = compile(pattern)
+    {
+        return bad_request(format!("'url_pattern' is not a valid pattern: {err}"));
+    }
+
+    if rule.domain.is_none() && rule.url_pattern.is_none() {
+        return bad_request("A rule needs a 'domain' or a 'url_pattern'".to_string());
+    }
+
+    rule.set_content_type = non_empty(rule.set_content_type);
+    if rule.set_content_type.is_none() && !rule.skip_enrichment {
+        return bad_request(
+            "A rule needs 'set_content_type' or 'skip_enrichment' to do something".to_string(),
+        );
+    }
+
+    Ok(rule)
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn rule(id: i32, domain: Option<&str>, url_pattern: Option<&str>) -> Rule {
+        Rule {
+            id,
+            name: format!("rule {id}"),
+            position: id,
+            domain: domain.map(str::to_string),
+            url_pattern: url_pattern.map(str::to_string),
+            set_content_type: None,
+            skip_enrichment: false,
+            created_at: DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
+        }
+    }
+
+    fn new_rule() -> NewRule {
+        NewRule {
+            name: "Papers".to_string(),
+            position: 0,
+            domain: Some("arxiv.org".to_string()),
+            url_pattern: None,
+            set_content_type: Some("paper".to_string()),
+            skip_enrichment: false,
+        }
+    }
+
+    #[test]
+    fn test_domain_includes_subdomains() {
+        let rules = [rule(1, Some("arxiv.org"), None)];
+
+        assert_eq!(evaluate(&rules, "https://arxiv.org/abs/1").matched, [1]);
+        assert_eq!(
+            evaluate(&rules, "https://export.ARXIV.org/abs/1").matched,
+            [1]
+        );
+        assert!(
+            evaluate(&rules, "https://notarxiv.org/abs/1")
+                .matched
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_every_condition_must_match() {
+        let rules = [rule(1, Some("example.com"), Some(r"/jobs/\d+"))];
+
+        assert_eq!(evaluate(&rules, "https://example.com/jobs/12").matched, [1]);
+        assert!(
+            evaluate(&rules, "https://example.com/blog/12")
+                .matched
+                .is_empty()
+        );
+        assert!(
+            evaluate(&rules, "https://example.org/jobs/12")
+                .matched
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_later_rules_win() {
+        let mut first = rule(1, Some("example.com"), None);
+        first.set_content_type = Some("article".to_string());
+        first.skip_enrichment = true;
+        let mut second = rule(2, None, Some("/papers/"));
+        second.set_content_type = Some("paper".to_string());
+
+        let outcome = evaluate(&[first, second], "https://example.com/papers/1");
+        assert_eq!(
+            outcome,
+            RuleOutcome {
+                matched: vec![1, 2],
+                content_type: Some("paper".to_string()),
+                skip_enrichment: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_normalizes() {
+        let rule = validate(NewRule {
+            name: " Papers ".to_string(),
+            domain: Some(" ArXiv.org ".to_string()),
+            url_pattern: Some(String::new()),
+            ..new_rule()
+        })
+        .unwrap();
+
+        assert_eq!(rule.name, "Papers");
+        assert_eq!(rule.domain.as_deref(), Some("arxiv.org"));
+        assert_eq!(rule.url_pattern, None);
+    }
+
+    #[test]
+    fn test_validate_rejects_unusable_rules() {
+        let invalid = [
+            NewRule {
+                name: " ".to_string(),
+                ..new_rule()
+            },
+            NewRule {
+                domain: Some("https://arxiv.org".to_string()),
+                ..new_rule()
+            },
+            NewRule {
+                url_pattern: Some("(".to_string()),
+                ..new_rule()
+            },
+            NewRule {
+                domain: None,
+                ..new_rule()
+            },
+            NewRule {
+                set_content_type: Some(" ".to_string()),
+                ..new_rule()
+            },
+        ];
+
+        for rule in invalid {
+            assert!(matches!(validate(rule), Err(ApiError::BadRequest(_))));
+        }
+    }
+}
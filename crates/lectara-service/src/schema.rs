@@ -1,5 +1,69 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    archive_payloads (digest) {
+        digest -> Text,
+        payload -> Binary,
+        ref_count -> Integer,
+    }
+}
+
+diesel::table! {
+    archive_snapshots (id) {
+        id -> Integer,
+        content_id -> Integer,
+        record_id -> Text,
+        target_uri -> Text,
+        content_type -> Text,
+        http_headers -> Binary,
+        payload_digest -> Text,
+        payload_length -> Integer,
+        captured_at -> Timestamp,
+        mode -> Text,
+    }
+}
+
+diesel::table! {
+    body_blobs (hash) {
+        hash -> Text,
+        body -> Text,
+        ref_count -> Integer,
+        text -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    citations (content_id) {
+        content_id -> Integer,
+        source -> Text,
+        identifier -> Text,
+        title -> Nullable<Text>,
+        authors -> Text,
+        published_on -> Nullable<Date>,
+        container_title -> Nullable<Text>,
+        abstract_text -> Nullable<Text>,
+        fetched_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    comments (id) {
+        id -> Integer,
+        content_id -> Integer,
+        parent_id -> Nullable<Integer>,
+        author -> Text,
+        body -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    content_bodies (content_id) {
+        content_id -> Integer,
+        body -> Text,
+    }
+}
+
 diesel::table! {
     content_items (id) {
         id -> Integer,
@@ -7,6 +71,126 @@ diesel::table! {
         title -> Nullable<Text>,
         author -> Nullable<Text>,
         created_at -> Timestamp,
-        body -> Nullable<Text>
+        body_hash -> Nullable<Text>,
+        enclosure_url -> Nullable<Text>,
+        duration_seconds -> Nullable<Integer>,
+        enriched_fields -> Text,
+        content_type -> Nullable<Text>,
+        metadata -> Text,
+        summary -> Nullable<Text>,
+        pinned_position -> Nullable<Integer>,
+        read_at -> Nullable<Timestamp>,
+        archived_at -> Nullable<Timestamp>,
+        source -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        client_version -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    fetch_attempts (content_id, kind) {
+        content_id -> Integer,
+        kind -> Text,
+        status -> Text,
+        attempts -> Integer,
+        last_error -> Nullable<Text>,
+        last_attempt_at -> Timestamp,
+        next_attempt_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    followers (id) {
+        id -> Integer,
+        actor_id -> Text,
+        inbox_url -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    notifications (id) {
+        id -> Integer,
+        kind -> Text,
+        message -> Text,
+        content_id -> Nullable<Integer>,
+        read_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    page_snapshots (content_id) {
+        content_id -> Integer,
+        content_hash -> Text,
+        checked_at -> Timestamp,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    rules (id) {
+        id -> Integer,
+        name -> Text,
+        position -> Integer,
+        domain -> Nullable<Text>,
+        url_pattern -> Nullable<Text>,
+        set_content_type -> Nullable<Text>,
+        skip_enrichment -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    share_links (id) {
+        id -> Integer,
+        token -> Text,
+        content_id -> Integer,
+        expires_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        passphrase_hash -> Nullable<Text>,
+        failed_attempts -> Integer,
+        last_failed_at -> Nullable<Timestamp>,
     }
 }
+
+diesel::table! {
+    views (id) {
+        id -> Integer,
+        name -> Text,
+        content_type -> Nullable<Text>,
+        source -> Nullable<Text>,
+        since -> Nullable<Text>,
+        until -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(archive_snapshots -> archive_payloads (payload_digest));
+diesel::joinable!(archive_snapshots -> content_items (content_id));
+diesel::joinable!(citations -> content_items (content_id));
+diesel::joinable!(comments -> content_items (content_id));
+diesel::joinable!(content_bodies -> content_items (content_id));
+diesel::joinable!(content_items -> body_blobs (body_hash));
+diesel::joinable!(fetch_attempts -> content_items (content_id));
+diesel::joinable!(notifications -> content_items (content_id));
+diesel::joinable!(page_snapshots -> content_items (content_id));
+diesel::joinable!(share_links -> content_items (content_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    archive_payloads,
+    archive_snapshots,
+    body_blobs,
+    citations,
+    comments,
+    content_bodies,
+    content_items,
+    fetch_attempts,
+    followers,
+    notifications,
+    page_snapshots,
+    rules,
+    share_links,
+    views,
+);
@@ -0,0 +1,262 @@
+//! Parsing of user search queries into SQLite FTS5 match expressions.
+//!
+//! The accepted syntax is deliberately small:
+//!
+//! - `word` matches the word anywhere in the title, author or body
+//! - `"two words"` matches the words next to each other, in order
+//! - `word*` matches words starting with `word`
+//! - terms separated by spaces must all match; `AND` can be written
+//!   explicitly and `OR` matches either side. `AND` binds tighter than `OR`,
+//!   and both must be uppercase, so lowercase `and`/`or` are ordinary words
+//!
+//! Every term is quoted in the generated expression, so FTS5 syntax such as
+//! column filters, `NEAR` or parentheses can't be injected through a query.
+
+use std::fmt;
+
+use thiserror::Error;
+
+/// Longest accepted query, in characters
+pub const MAX_QUERY_LENGTH: usize = 256;
+
+/// Most terms (words, phrases or prefixes) accepted in one query
+pub const MAX_QUERY_TERMS: usize = 32;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SearchQueryError {
+    #[error("Search query is empty")]
+    Empty,
+
+    #[error("Search query is longer than {MAX_QUERY_LENGTH} characters")]
+    TooLong,
+
+    #[error("Search query has more than {MAX_QUERY_TERMS} terms")]
+    TooManyTerms,
+
+    #[error("Search query has an unterminated phrase")]
+    UnterminatedPhrase,
+
+    #[error("Prefix searches need at least one character before '*'")]
+    InvalidPrefix,
+
+    #[error("{0} must appear between terms")]
+    MisplacedOperator(Operator),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    And,
+    Or,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operator::And => "AND",
+            Operator::Or => "OR",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Words to match in order; a single word is a one-word phrase
+    Phrase {
+        text: String,
+        prefix: bool,
+    },
+    Operator(Operator),
+}
+
+/// A validated search query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    tokens: Vec<Token>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Result<Self, SearchQueryError> {
+        if query.chars().count() > MAX_QUERY_LENGTH {
+            return Err(SearchQueryError::TooLong);
+        }
+
+        let mut tokens = Vec::new();
+        let mut rest = query.trim_start();
+        while !rest.is_empty() {
+            let token = if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted
+                    .find('"')
+                    .ok_or(SearchQueryError::UnterminatedPhrase)?;
+                let text = quoted[..end].to_string();
+                rest = &quoted[end + 1..];
+                let prefix = rest.starts_with('*');
+                if prefix {
+                    rest = &rest[1..];
+                }
+                Token::Phrase { text, prefix }
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '"')
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                rest = &rest[end..];
+                match word {
+                    "AND" => Token::Operator(Operator::And),
+                    "OR" => Token::Operator(Operator::Or),
+                    _ => match word.strip_suffix('*') {
+                        Some(stem) if stem.is_empty() || stem.contains('*') => {
+                            return Err(SearchQueryError::InvalidPrefix);
+                        }
+                        Some(stem) => Token::Phrase {
+                            text: stem.to_string(),
+                            prefix: true,
+                        },
+                        None => Token::Phrase {
+                            text: word.to_string(),
+                            prefix: false,
+                        },
+                    },
+                }
+            };
+            tokens.push(token);
+            rest = rest.trim_start();
+        }
+
+        // Terms without any letters or digits index to nothing, so they
+        // can't be matched; dropping them keeps e.g. `C - D` searchable
+        tokens.retain(|token| match token {
+            Token::Phrase { text, .. } => text.chars().any(char::is_alphanumeric),
+            Token::Operator(_) => true,
+        });
+
+        let terms = tokens
+            .iter()
+            .filter(|token| matches!(token, Token::Phrase { .. }))
+            .count();
+        if terms == 0 {
+            return Err(SearchQueryError::Empty);
+        }
+        if terms > MAX_QUERY_TERMS {
+            return Err(SearchQueryError::TooManyTerms);
+        }
+
+        let mut previous_was_term = false;
+        for token in &tokens {
+            match token {
+                Token::Phrase { .. } => previous_was_term = true,
+                Token::Operator(operator) if !previous_was_term => {
+                    return Err(SearchQueryError::MisplacedOperator(*operator));
+                }
+                Token::Operator(_) => previous_was_term = false,
+            }
+        }
+        if let Some(Token::Operator(operator)) = tokens.last() {
+            return Err(SearchQueryError::MisplacedOperator(*operator));
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// The query as an FTS5 match expression
+    pub fn to_fts(&self) -> String {
+        self.tokens
+            .iter()
+            .map(|token| match token {
+                Token::Phrase { text, prefix } => {
+                    let quoted = format!("\"{}\"", text.replace('"', "\"\""));
+                    if *prefix { quoted + "*" } else { quoted }
+                }
+                Token::Operator(operator) => operator.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Which index a search runs against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchLanguage {
+    /// Porter-stemmed, so `running` also finds `runs`
+    #[default]
+    English,
+    /// Case- and diacritic-insensitive matching without stemming
+    None,
+}
+
+impl SearchLanguage {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "english" | "en" => Some(Self::English),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fts(query: &str) -> String {
+        SearchQuery::parse(query).unwrap().to_fts()
+    }
+
+    #[test]
+    fn test_terms_are_quoted() {
+        assert_eq!(fts("rust async"), r#""rust" "async""#);
+        assert_eq!(fts("title:rust NEAR(a b)"), r#""title:rust" "NEAR(a" "b)""#);
+    }
+
+    #[test]
+    fn test_phrases_prefixes_and_operators() {
+        assert_eq!(
+            fts(r#""type system" OR trait* AND generics"#),
+            r#""type system" OR "trait"* AND "generics""#
+        );
+        assert_eq!(fts(r#""hello wor"*"#), r#""hello wor"*"#);
+        assert_eq!(fts("cats and dogs"), r#""cats" "and" "dogs""#);
+    }
+
+    #[test]
+    fn test_punctuation_only_terms_are_dropped() {
+        assert_eq!(fts("C - D"), r#""C" "D""#);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert_eq!(SearchQuery::parse("  "), Err(SearchQueryError::Empty));
+        assert_eq!(SearchQuery::parse("- ..."), Err(SearchQueryError::Empty));
+        assert_eq!(
+            SearchQuery::parse(r#""open phrase"#),
+            Err(SearchQueryError::UnterminatedPhrase)
+        );
+        assert_eq!(
+            SearchQuery::parse("*"),
+            Err(SearchQueryError::InvalidPrefix)
+        );
+        assert_eq!(
+            SearchQuery::parse("a**"),
+            Err(SearchQueryError::InvalidPrefix)
+        );
+        assert_eq!(
+            SearchQuery::parse("OR rust"),
+            Err(SearchQueryError::MisplacedOperator(Operator::Or))
+        );
+        assert_eq!(
+            SearchQuery::parse("rust AND"),
+            Err(SearchQueryError::MisplacedOperator(Operator::And))
+        );
+        assert_eq!(
+            SearchQuery::parse("rust AND OR go"),
+            Err(SearchQueryError::MisplacedOperator(Operator::Or))
+        );
+        assert_eq!(
+            SearchQuery::parse(&"word ".repeat(33)),
+            Err(SearchQueryError::TooManyTerms)
+        );
+        assert_eq!(
+            SearchQuery::parse(&"a".repeat(257)),
+            Err(SearchQueryError::TooLong)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use chrono::{Duration, NaiveDateTime, Timelike};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::bodies;
+use crate::schema::content_items;
+use crate::validation::normalize_url;
+
+/// Items are inserted in batches of this size, each in its own transaction
+const BATCH_SIZE: usize = 500;
+
+/// Generated items are spread over this many days before the reference time
+const HISTORY_DAYS: i64 = 365;
+
+const DOMAINS: &[&str] = &[
+    "blog.rust-lang.org",
+    "news.ycombinator.com",
+    "arxiv.org",
+    "github.com",
+    "lwn.net",
+    "jvns.ca",
+    "danluu.com",
+    "simonwillison.net",
+    "www.theatlantic.com",
+    "www.quantamagazine.org",
+    "notes.substack.com",
+    "medium.com",
+];
+
+const WORDS: &[&str] = &[
+    "async",
+    "rust",
+    "database",
+    "memory",
+    "design",
+    "systems",
+    "notes",
+    "compiler",
+    "distributed",
+    "reading",
+    "history",
+    "networks",
+    "garden",
+    "city",
+    "writing",
+    "science",
+    "future",
+    "tools",
+    "language",
+    "performance",
+    "attention",
+    "economics",
+    "craft",
+    "software",
+    "learning",
+    "archive",
+    "protocol",
+    "latency",
+    "ownership",
+    "essay",
+    "review",
+    "lessons",
+    "small",
+    "web",
+    "quiet",
+    "theory",
+    "practice",
+    "machines",
+    "maps",
+    "time",
+];
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Grace", "Alan", "Barbara", "Ken", "Margaret", "Linus", "Radia", "Dennis", "Frances",
+    "Leslie", "Edsger", "Hedy", "Donald", "Sophie",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Lovelace", "Hopper", "Turing", "Liskov", "Thompson", "Hamilton", "Torvalds", "Perlman",
+    "Ritchie", "Allen", "Lamport", "Dijkstra", "Lamarr", "Knuth", "Wilson",
+];
+
+/// A generated content item, with a backdated creation time
+#[derive(Debug, Clone)]
+pub struct SeedContentItem {
+    pub url: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub body: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+fn words<R: Rng>(rng: &mut R, count: usize) -> Vec<&'static str> {
+    (0..count)
+        .map(|_| *WORDS.choose(rng).expect("word list is not empty"))
+        .collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn generate_body<R: Rng>(rng: &mut R) -> String {
+    let paragraphs = rng.random_range(1..=5);
+    (0..paragraphs)
+        .map(|_| {
+            let sentences = rng.random_range(2..=6);
+            (0..sentences)
+                .map(|_| {
+                    let length = rng.random_range(6..=16);
+                    let sentence = words(rng, length).join(" ");
+                    format!("{}.", capitalize(&sentence))
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Generates a plausible content item created within [`HISTORY_DAYS`] of `now`.
+///
+/// `index` and `run` are folded into the URL so items never collide, both
+/// within one seeding run and across repeated runs against the same database.
+pub fn generate_item<R: Rng>(
+    rng: &mut R,
+    index: usize,
+    run: u32,
+    now: NaiveDateTime,
+) -> SeedContentItem {
+    let domain = DOMAINS.choose(rng).expect("domain list is not empty");
+    let title_length = rng.random_range(3..=7);
+    let title_words = words(rng, title_length);
+    let slug = title_words.join("-");
+
+    let url = normalize_url(&format!("https://{domain}/{slug}-{run:08x}{index}"))
+        .expect("generated URLs are valid");
+
+    let title = rng.random_bool(0.9).then(|| {
+        title_words
+            .iter()
+            .map(|word| capitalize(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+
+    let author = rng.random_bool(0.7).then(|| {
+        format!(
+            "{} {}",
+            FIRST_NAMES.choose(rng).expect("name list is not empty"),
+            LAST_NAMES.choose(rng).expect("name list is not empty")
+        )
+    });
+
+    let body = rng.random_bool(0.6).then(|| generate_body(rng));
+
+    let age_seconds = rng.random_range(0..HISTORY_DAYS * 24 * 60 * 60);
+    let created_at = now - Duration::seconds(age_seconds);
+
+    SeedContentItem {
+        url,
+        title,
+        author,
+        body,
+        created_at,
+    }
+}
+
+fn insert_batch(
+    conn: &mut SqliteConnection,
+    batch: &[SeedContentItem],
+) -> Result<usize, diesel::result::Error> {
+    let mut inserted = 0;
+    for item in batch {
+        let body_hash = item
+            .body
+            .as_deref()
+            .map(|body| bodies::store_body(conn, body))
+            .transpose()?;
+
+        let id = diesel::insert_or_ignore_into(content_items::table)
+            .values((
+                content_items::url.eq(&item.url),
+                content_items::title.eq(&item.title),
+                content_items::author.eq(&item.author),
+                content_items::body_hash.eq(&body_hash),
+                content_items::created_at.eq(item.created_at),
+            ))
+            .returning(content_items::id)
+            .get_result::<i32>(conn)
+            .optional()?;
+
+        match (id, body_hash) {
+            (Some(_), _) => inserted += 1,
+            // The item was ignored, so give back the reference taken above
+            (None, Some(hash)) => bodies::release_body(conn, &hash)?,
+            (None, None) => {}
+        }
+    }
+    Ok(inserted)
+}
+
+/// Inserts `count` generated items, returning how many rows were written
+pub fn seed_content<R: Rng>(
+    conn: &mut SqliteConnection,
+    rng: &mut R,
+    count: usize,
+) -> Result<usize, diesel::result::Error> {
+    // Match the whole-second precision of CURRENT_TIMESTAMP defaults
+    let now = chrono::Utc::now()
+        .naive_utc()
+        .with_nanosecond(0)
+        .expect("zero nanoseconds is valid");
+    let run: u32 = rng.random();
+    let mut inserted = 0;
+
+    let mut index = 0;
+    while index < count {
+        let batch: Vec<SeedContentItem> = (index..count.min(index + BATCH_SIZE))
+            .map(|i| generate_item(rng, i, run, now))
+            .collect();
+        index += batch.len();
+
+        inserted += conn.transaction(|conn| insert_batch(conn, &batch))?;
+    }
+
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn now() -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc()
+    }
+
+    #[test]
+    fn test_generated_items_are_reproducible() {
+        let first = generate_item(&mut StdRng::seed_from_u64(7), 0, 1, now());
+        let second = generate_item(&mut StdRng::seed_from_u64(7), 0, 1, now());
+
+        assert_eq!(first.url, second.url);
+        assert_eq!(first.title, second.title);
+        assert_eq!(first.created_at, second.created_at);
+    }
+
+    #[test]
+    fn test_generated_items_are_valid() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for index in 0..200 {
+            let item = generate_item(&mut rng, index, 1, now());
+
+            assert_eq!(normalize_url(&item.url).unwrap(), item.url);
+            assert!(item.created_at <= now());
+            assert!(item.created_at > now() - Duration::days(HISTORY_DAYS));
+            if let Some(body) = item.body {
+                assert!(!body.trim().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_urls_are_unique() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let urls: std::collections::HashSet<String> = (0..1000)
+            .map(|index| generate_item(&mut rng, index, 1, now()).url)
+            .collect();
+
+        assert_eq!(urls.len(), 1000);
+    }
+}
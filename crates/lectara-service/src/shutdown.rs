@@ -4,12 +4,14 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::task::{Context, Poll};
 
-use http::{Request, Response, StatusCode};
+use http::{HeaderValue, Request, Response, StatusCode, header};
 use http_body::Body;
 use pin_project::pin_project;
 use tokio::sync::{Notify, futures::Notified};
 use tower::{Layer, Service};
 
+use crate::maintenance::{self, MaintenanceSchedule};
+
 /// Shared state for tracking shutdown status and in-flight requests
 #[derive(Clone)]
 pub struct ShutdownState {
@@ -63,11 +65,22 @@ impl ShutdownState {
 #[derive(Clone)]
 pub struct GracefulShutdownLayer {
     state: ShutdownState,
+    maintenance: Option<MaintenanceSchedule>,
 }
 
 impl GracefulShutdownLayer {
     pub fn new(state: ShutdownState) -> Self {
-        Self { state }
+        Self {
+            state,
+            maintenance: None,
+        }
+    }
+
+    /// Announces scheduled maintenance windows on every response and drains
+    /// the service while one is in progress
+    pub fn with_maintenance(mut self, schedule: MaintenanceSchedule) -> Self {
+        self.maintenance = Some(schedule);
+        self
     }
 }
 
@@ -78,6 +91,7 @@ impl<S> Layer<S> for GracefulShutdownLayer {
         GracefulShutdownService {
             inner,
             state: self.state.clone(),
+            maintenance: self.maintenance.clone(),
         }
     }
 }
@@ -87,6 +101,7 @@ impl<S> Layer<S> for GracefulShutdownLayer {
 pub struct GracefulShutdownService<S> {
     inner: S,
     state: ShutdownState,
+    maintenance: Option<MaintenanceSchedule>,
 }
 
 impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for GracefulShutdownService<S>
@@ -103,6 +118,13 @@ where
     }
 
     fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let now = chrono::Utc::now().naive_utc();
+        let window = self
+            .maintenance
+            .as_ref()
+            .and_then(|schedule| schedule.current(now));
+        let warning = window.as_ref().map(|window| window.warning_header());
+
         // Check if we're shutting down
         if self.state.is_shutting_down() {
             // Return 503 Service Unavailable
@@ -114,6 +136,24 @@ where
             GracefulShutdownFuture {
                 kind: FutureKind::Immediate(Some(Ok(response))),
                 state: self.state.clone(),
+                warning: None,
+            }
+        } else if let Some(window) = window
+            && window.is_active(now)
+            && !maintenance::is_drain_exempt(req.uri().path())
+        {
+            // Drained for maintenance: come back once the window ends
+            let retry_after = (window.ends_at - now).num_seconds().max(1);
+            let response = Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header(header::RETRY_AFTER, retry_after)
+                .body(ResBody::default())
+                .expect("building empty response should not fail");
+
+            GracefulShutdownFuture {
+                kind: FutureKind::Immediate(Some(Ok(response))),
+                state: self.state.clone(),
+                warning,
             }
         } else {
             // Increment in-flight counter
@@ -123,6 +163,7 @@ where
             GracefulShutdownFuture {
                 kind: FutureKind::Inner(self.inner.call(req)),
                 state: self.state.clone(),
+                warning,
             }
         }
     }
@@ -134,6 +175,8 @@ pub struct GracefulShutdownFuture<F, B, E> {
     #[pin]
     kind: FutureKind<F, B, E>,
     state: ShutdownState,
+    /// Added to the response while a maintenance window is scheduled
+    warning: Option<HeaderValue>,
 }
 
 #[pin_project(project = FutureKindProj)]
@@ -152,7 +195,7 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
 
-        match this.kind.project() {
+        let result = match this.kind.project() {
             FutureKindProj::Inner(fut) => {
                 let result = fut.poll(cx);
 
@@ -171,7 +214,16 @@ where
                 // SAFETY: We know this is Some because we only poll once
                 Poll::Ready(response.take().unwrap())
             }
-        }
+        };
+
+        result.map(|result| {
+            result.map(|mut response| {
+                if let Some(warning) = this.warning.take() {
+                    response.headers_mut().insert(header::WARNING, warning);
+                }
+                response
+            })
+        })
     }
 }
 
@@ -476,4 +528,73 @@ mod tests {
         // All done
         assert_eq!(state.in_flight_count(), 0);
     }
+
+    fn schedule_window(
+        starts_in: chrono::TimeDelta,
+        ends_in: chrono::TimeDelta,
+    ) -> MaintenanceSchedule {
+        let now = chrono::Utc::now().naive_utc();
+        let schedule = MaintenanceSchedule::default();
+        schedule.schedule(maintenance::MaintenanceWindow {
+            starts_at: now + starts_in,
+            ends_at: now + ends_in,
+            message: Some("Disk upgrade".to_string()),
+        });
+        schedule
+    }
+
+    #[tokio::test]
+    async fn test_announces_upcoming_maintenance() {
+        let state = ShutdownState::new();
+        let schedule = schedule_window(chrono::TimeDelta::hours(1), chrono::TimeDelta::hours(2));
+        let service = ServiceBuilder::new()
+            .layer(GracefulShutdownLayer::new(state.clone()).with_maintenance(schedule.clone()))
+            .service(EchoService::new());
+
+        let req = Request::builder().body(Empty::new()).unwrap();
+        let response = service.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let warning = response.headers()[header::WARNING].to_str().unwrap();
+        assert!(warning.starts_with("199 lectara \"Scheduled maintenance from "));
+        assert!(warning.ends_with(": Disk upgrade\""));
+
+        schedule.cancel();
+        let req = Request::builder().body(Empty::new()).unwrap();
+        let response = service.oneshot(req).await.unwrap();
+        assert!(!response.headers().contains_key(header::WARNING));
+    }
+
+    #[tokio::test]
+    async fn test_drains_during_maintenance() {
+        let state = ShutdownState::new();
+        let schedule = schedule_window(
+            -chrono::TimeDelta::minutes(1),
+            chrono::TimeDelta::minutes(30),
+        );
+        let service = ServiceBuilder::new()
+            .layer(GracefulShutdownLayer::new(state.clone()).with_maintenance(schedule))
+            .service(EchoService::new());
+
+        let req = Request::builder()
+            .uri("/api/v1/content")
+            .body(Empty::new())
+            .unwrap();
+        let response = service.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().contains_key(header::WARNING));
+        let retry_after: i64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after > 29 * 60 && retry_after <= 30 * 60);
+
+        // The window can still be managed, and liveness still reported
+        for uri in ["/api/v1/admin/maintenance", "/health"] {
+            let req = Request::builder().uri(uri).body(Empty::new()).unwrap();
+            let response = service.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(state.in_flight_count(), 0);
+    }
 }
@@ -0,0 +1,161 @@
+//! Readiness and watchdog notifications for systemd units with
+//! `Type=notify`, sent to the socket systemd names in `NOTIFY_SOCKET` (see
+//! sd_notify(3)). Without that variable, e.g. outside systemd, nothing is
+//! sent.
+//!
+//! The watchdog pings come from a task on the server's runtime, and each
+//! waits for every database to answer through its writer thread. A runtime
+//! too stuck to answer requests, a writer stuck on a write or a database
+//! that stopped answering all stop the pings, and systemd restarts the
+//! service once `WatchdogSec=` passes without one.
+
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use crate::database::Database;
+
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+
+/// Tells systemd the server is accepting connections
+pub fn notify_ready(status: &str) {
+    notify(&format!("READY=1\nSTATUS={status}"));
+}
+
+/// Tells systemd a graceful shutdown has started, so it doesn't count the
+/// drain against the watchdog
+pub fn notify_stopping() {
+    notify("STOPPING=1\nSTATUS=Draining requests");
+}
+
+/// Pings the watchdog at half the interval systemd expects, if it expects
+/// any, as long as `databases` answer within that interval
+pub fn spawn_watchdog(databases: Vec<Database>) {
+    let usec = std::env::var("WATCHDOG_USEC").ok();
+    let pid = std::env::var("WATCHDOG_PID").ok();
+    let Some(interval) = watchdog_interval(usec.as_deref(), pid.as_deref(), std::process::id())
+    else {
+        return;
+    };
+
+    debug!(
+        interval_ms = interval.as_millis(),
+        "Pinging the systemd watchdog"
+    );
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            match tokio::time::timeout(interval, ping_all(&databases)).await {
+                Ok(Ok(())) => notify("WATCHDOG=1"),
+                Ok(Err(err)) => {
+                    warn!(error = %err, "Database check failed, skipping the watchdog ping");
+                }
+                Err(_) => warn!("Database check timed out, skipping the watchdog ping"),
+            }
+        }
+    });
+}
+
+async fn ping_all(databases: &[Database]) -> Result<(), crate::errors::ApiError> {
+    for database in databases {
+        database.ping().await?;
+    }
+    Ok(())
+}
+
+/// How often to ping, given `WATCHDOG_USEC` and `WATCHDOG_PID`; `None` when
+/// the watchdog is off or meant for another process
+fn watchdog_interval(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid
+        && pid.trim().parse::<u32>().ok() != Some(own_pid)
+    {
+        return None;
+    }
+    let usec = usec?.trim().parse::<u64>().ok().filter(|&usec| usec > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+fn notify(state: &str) {
+    let Some(socket) = std::env::var_os(NOTIFY_SOCKET_ENV) else {
+        return;
+    };
+    let socket = socket.to_string_lossy();
+    if let Err(err) = send(&socket, state) {
+        warn!(socket = %socket, error = %err, "Failed to notify systemd");
+    }
+}
+
+/// Sends one datagram to `socket`, a path or, with a leading `@`, an
+/// abstract socket name
+#[cfg(unix)]
+fn send(socket: &str, state: &str) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+
+            let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &address)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract sockets only exist on Linux",
+            ));
+        }
+        None => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &str, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "systemd only runs on Unix",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_interval() {
+        assert_eq!(
+            watchdog_interval(Some("30000000"), None, 7),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval(Some("30000000"), Some("7"), 7),
+            Some(Duration::from_secs(15))
+        );
+        // Meant for another process, e.g. the shell that started this one
+        assert_eq!(watchdog_interval(Some("30000000"), Some("8"), 7), None);
+        assert_eq!(watchdog_interval(None, None, 7), None);
+        assert_eq!(watchdog_interval(Some("0"), None, 7), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_to_a_socket_path() {
+        use std::os::unix::net::UnixDatagram;
+
+        let path = std::env::temp_dir().join(format!("lectara-notify-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        send(path.to_str().unwrap(), "READY=1").unwrap();
+        let mut buffer = [0; 64];
+        let received = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..received], b"READY=1");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
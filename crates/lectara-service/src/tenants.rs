@@ -0,0 +1,352 @@
+//! Multi-tenant instances, where each tenant has its own database file.
+//!
+//! Tenants are listed in a TOML registry:
+//!
+//! ```toml
+//! [tenants.alice]
+//! database = "/var/lib/lectara/alice.db"
+//! hosts = ["alice.lectara.example"]
+//! api_key_hashes = ["<sha256 hex of the key>"]
+//! public = false
+//! share_activity = false
+//! ```
+//!
+//! A request belongs to the tenant whose API key it sends as
+//! `Authorization: Bearer <key>`, or else to the tenant owning its host.
+//! Keys are stored as SHA-256 digests so the registry holds no usable
+//! secrets, and a tenant with keys requires them as described in
+//! [`crate::auth`]. Requests matching no tenant only reach the health checks
+//! and, if the instance has keys of its own to guard it, the instance-wide
+//! admin API. Tenants sharing their activity see
+//! each other's recent saves, see [`crate::activity`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::response::{IntoResponse, Response};
+use http::header;
+use serde::Deserialize;
+use thiserror::Error;
+use tower::ServiceExt;
+use tracing::debug;
+
+use crate::auth::{AccessPolicy, api_key, hash_api_key, parse_key_hash};
+use crate::errors::ApiError;
+
+/// Environment variable naming the tenant registry; the instance serves a
+/// single database when unset
+pub const TENANTS_ENV: &str = "LECTARA_TENANTS";
+
+#[derive(Error, Debug)]
+pub enum TenantError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid tenant registry: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid tenant {tenant}: {reason}")]
+    InvalidTenant { tenant: String, reason: String },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    #[serde(default)]
+    tenants: BTreeMap<String, TenantEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantEntry {
+    database: PathBuf,
+    #[serde(default)]
+    hosts: Vec<String>,
+    #[serde(default)]
+    api_key_hashes: Vec<String>,
+    #[serde(default)]
+    public: bool,
+    #[serde(default)]
+    share_activity: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant {
+    pub name: String,
+    pub database: PathBuf,
+    pub api_key_hashes: Vec<String>,
+    /// Anyone reaching the tenant by host may read its items
+    pub public: bool,
+    /// The tenant's saves appear in the activity feed of the other tenants
+    /// sharing theirs, and it sees theirs
+    pub share_activity: bool,
+}
+
+impl Tenant {
+    pub fn access_policy(&self) -> AccessPolicy {
+        AccessPolicy::new(self.api_key_hashes.iter().cloned(), self.public)
+    }
+}
+
+/// Tenants and the hosts and API keys that identify them
+#[derive(Debug, Clone, Default)]
+pub struct TenantRegistry {
+    tenants: Vec<Tenant>,
+    by_host: HashMap<String, String>,
+    by_key_hash: HashMap<String, String>,
+}
+
+impl TenantRegistry {
+    /// Loads the registry named by [`TENANTS_ENV`], or `None` when it is
+    /// unset
+    pub fn from_env() -> Result<Option<Self>, TenantError> {
+        match std::env::var(TENANTS_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, TenantError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| TenantError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, TenantError> {
+        let file: RegistryFile = toml::from_str(contents)?;
+
+        let mut registry = Self::default();
+        let mut databases = HashSet::new();
+        for (name, entry) in file.tenants {
+            let invalid = |reason: &str| TenantError::InvalidTenant {
+                tenant: name.clone(),
+                reason: reason.to_string(),
+            };
+
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                return Err(invalid(
+                    "names may only contain lowercase letters, digits, '-' and '_'",
+                ));
+            }
+            if entry.database.as_os_str().is_empty() {
+                return Err(invalid("database must not be empty"));
+            }
+            // Sharing a file would defeat the isolation tenants exist for
+            if !databases.insert(entry.database.clone()) {
+                return Err(invalid("database is already used by another tenant"));
+            }
+            if entry.hosts.is_empty() && entry.api_key_hashes.is_empty() {
+                return Err(invalid("at least one host or API key hash is required"));
+            }
+
+            for host in &entry.hosts {
+                let host = normalize_host(host);
+                if registry.by_host.insert(host, name.clone()).is_some() {
+                    return Err(invalid("host is already used by another tenant"));
+                }
+            }
+            let mut api_key_hashes = Vec::new();
+            for key_hash in &entry.api_key_hashes {
+                let key_hash = parse_key_hash(key_hash)
+                    .ok_or_else(|| invalid("API key hashes must be SHA-256 hex digests"))?;
+                if registry
+                    .by_key_hash
+                    .insert(key_hash.clone(), name.clone())
+                    .is_some()
+                {
+                    return Err(invalid("API key is already used by another tenant"));
+                }
+                api_key_hashes.push(key_hash);
+            }
+
+            registry.tenants.push(Tenant {
+                name,
+                database: entry.database,
+                api_key_hashes,
+                public: entry.public,
+                share_activity: entry.share_activity,
+            });
+        }
+
+        Ok(registry)
+    }
+
+    pub fn tenants(&self) -> &[Tenant] {
+        &self.tenants
+    }
+
+    /// Name of the tenant a request belongs to, or `None` if it matches no
+    /// tenant. A tenant's API key sent to another tenant's host is
+    /// rejected; other keys are left for the matched router to check.
+    pub fn resolve(
+        &self,
+        host: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<Option<&str>, ApiError> {
+        let host_tenant = host.and_then(|host| self.by_host.get(&normalize_host(host)));
+        let key_tenant = api_key.and_then(|api_key| self.by_key_hash.get(&hash_api_key(api_key)));
+
+        match (host_tenant, key_tenant) {
+            (Some(host_tenant), Some(key_tenant)) if host_tenant != key_tenant => Err(
+                ApiError::Forbidden("API key belongs to a different tenant".to_string()),
+            ),
+            (host_tenant, key_tenant) => Ok(key_tenant.or(host_tenant).map(String::as_str)),
+        }
+    }
+}
+
+/// Lowercased host without port or trailing dot
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = match host.rsplit_once(':') {
+        // Leave IPv6 literals like `[::1]` alone
+        Some((name, port)) if !name.ends_with(':') && port.chars().all(|c| c.is_ascii_digit()) => {
+            name
+        }
+        _ => host,
+    };
+    host.trim_end_matches('.').to_lowercase()
+}
+
+fn request_host(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| request.uri().host())
+}
+
+#[derive(Clone)]
+struct TenantRouters {
+    registry: Arc<TenantRegistry>,
+    tenants: Arc<HashMap<String, Router>>,
+    instance: Router,
+}
+
+/// Router forwarding each request to the router of the tenant it belongs
+/// to, or to `instance` when it matches none. Every tenant in the registry
+/// needs an entry in `tenants`.
+pub fn create_multi_tenant_router(
+    registry: TenantRegistry,
+    tenants: HashMap<String, Router>,
+    instance: Router,
+) -> Router {
+    Router::new().fallback(dispatch).with_state(TenantRouters {
+        registry: Arc::new(registry),
+        tenants: Arc::new(tenants),
+        instance,
+    })
+}
+
+async fn dispatch(State(routers): State<TenantRouters>, request: Request) -> Response {
+    let api_key = api_key(request.headers());
+    let resolved = routers
+        .registry
+        .resolve(request_host(&request), api_key.as_deref());
+    let router = match resolved {
+        Ok(Some(tenant)) => {
+            debug!(tenant, "Routing request to tenant");
+            match routers.tenants.get(tenant) {
+                Some(router) => router.clone(),
+                None => return ApiError::InternalError.into_response(),
+            }
+        }
+        Ok(None) => routers.instance.clone(),
+        Err(err) => return err.into_response(),
+    };
+
+    match router.oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"
+        [tenants.alice]
+        database = "alice.db"
+        hosts = ["Alice.Example.com"]
+
+        [tenants.bob]
+        database = "bob.db"
+        api_key_hashes = ["9b94dc1a51a38769f135edf04033ad7f2f487b6c25929be7a861cfc1ab10cf98"]
+    "#;
+
+    #[test]
+    fn test_resolve_by_host_and_key() {
+        let registry = TenantRegistry::parse(REGISTRY).unwrap();
+
+        assert_eq!(registry.tenants().len(), 2);
+        assert!(registry.tenants()[1].access_policy().requires_keys());
+        assert_eq!(
+            registry
+                .resolve(Some("alice.example.com:3000"), None)
+                .unwrap(),
+            Some("alice")
+        );
+        assert_eq!(registry.resolve(Some("example.com"), None).unwrap(), None);
+        assert_eq!(
+            registry
+                .resolve(Some("example.com"), Some("bob-key"))
+                .unwrap(),
+            Some("bob")
+        );
+        // Unknown keys are for the matched router to reject
+        assert_eq!(registry.resolve(None, Some("guess")).unwrap(), None);
+        assert_eq!(
+            registry
+                .resolve(Some("alice.example.com"), Some("guess"))
+                .unwrap(),
+            Some("alice")
+        );
+        assert!(matches!(
+            registry.resolve(Some("alice.example.com"), Some("bob-key")),
+            Err(ApiError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_shared_or_unreachable_tenants() {
+        let shared_database = r#"
+            [tenants.alice]
+            database = "shared.db"
+            hosts = ["alice.example.com"]
+            [tenants.bob]
+            database = "shared.db"
+            hosts = ["bob.example.com"]
+        "#;
+        assert!(matches!(
+            TenantRegistry::parse(shared_database),
+            Err(TenantError::InvalidTenant { tenant, .. }) if tenant == "bob"
+        ));
+
+        let unreachable = "[tenants.alice]\ndatabase = \"alice.db\"\n";
+        assert!(TenantRegistry::parse(unreachable).is_err());
+
+        let bad_hash = "[tenants.alice]\ndatabase = \"alice.db\"\napi_key_hashes = [\"secret\"]\n";
+        assert!(TenantRegistry::parse(bad_hash).is_err());
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("Example.COM."), "example.com");
+        assert_eq!(normalize_host("example.com:8080"), "example.com");
+        assert_eq!(normalize_host("[::1]"), "[::1]");
+        assert_eq!(normalize_host("[::1]:3000"), "[::1]");
+    }
+}
@@ -1,7 +1,11 @@
+use crate::models::MetadataField;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 use thiserror::Error;
+use unicode_normalization::{UnicodeNormalization, is_nfc};
 use url::Url;
 
 #[derive(Error, Debug)]
@@ -14,14 +18,37 @@ pub enum ValidationError {
     MissingHost,
     #[error("Local addresses not allowed: {0}")]
     LocalAddress(String),
+    #[error("Host not allowed: {0}")]
+    DeniedHost(String),
     #[error("Unsupported URL scheme: {0}")]
     UnsupportedScheme(String),
+    #[error("Invalid metadata: {}", describe_violations(.0))]
+    InvalidMetadata(Vec<FieldViolation>),
+    #[error("Unknown source: {0}")]
+    InvalidSource(String),
+}
+
+/// A title, author or body rejected by [`check_metadata`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldViolation {
+    pub field: MetadataField,
+    pub reason: String,
+}
+
+fn describe_violations(violations: &[FieldViolation]) -> String {
+    violations
+        .iter()
+        .map(|violation| format!("{} {}", violation.field, violation.reason))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Scheme {
     Http,
     Https,
+    /// BitTorrent magnet link, identified by its `xt` parameter alone
+    Magnet,
 }
 
 impl fmt::Display for Scheme {
@@ -29,17 +56,449 @@ impl fmt::Display for Scheme {
         match self {
             Scheme::Http => write!(f, "http"),
             Scheme::Https => write!(f, "https"),
+            Scheme::Magnet => write!(f, "magnet"),
+        }
+    }
+}
+
+/// Non-HTTP schemes that deployments can opt into.
+///
+/// `doi:`, `arxiv:` and `ipfs://`/`ipns://` identifiers are rewritten to their
+/// canonical https URL before validation, so they deduplicate against links
+/// saved directly. Magnet links have no https form and keep their own scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraScheme {
+    Doi,
+    Arxiv,
+    Ipfs,
+    Magnet,
+}
+
+impl ExtraScheme {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "doi" => Some(ExtraScheme::Doi),
+            "arxiv" => Some(ExtraScheme::Arxiv),
+            "ipfs" => Some(ExtraScheme::Ipfs),
+            "magnet" => Some(ExtraScheme::Magnet),
+            _ => None,
+        }
+    }
+}
+
+/// Environment variable listing hosts that are always accepted, comma separated
+pub const ALLOWED_HOSTS_ENV: &str = "LECTARA_ALLOWED_HOSTS";
+
+/// Environment variable listing hosts that are always rejected, comma separated
+pub const DENIED_HOSTS_ENV: &str = "LECTARA_DENIED_HOSTS";
+
+/// Environment variable that, when `true` or `1`, accepts local addresses
+pub const ALLOW_LOCAL_URLS_ENV: &str = "LECTARA_ALLOW_LOCAL_URLS";
+
+/// Environment variable listing enabled [`ExtraScheme`]s, comma separated
+pub const EXTRA_SCHEMES_ENV: &str = "LECTARA_EXTRA_SCHEMES";
+
+/// Environment variable naming the gateway host `ipfs://` links resolve through
+pub const IPFS_GATEWAY_ENV: &str = "LECTARA_IPFS_GATEWAY";
+
+/// Environment variable listing the [`MetadataComparison`] rules, comma
+/// separated
+pub const METADATA_COMPARISON_ENV: &str = "LECTARA_METADATA_COMPARISON";
+
+/// Environment variables overriding the [`MetadataLimits`], in characters
+pub const MAX_TITLE_LENGTH_ENV: &str = "LECTARA_MAX_TITLE_LENGTH";
+pub const MAX_AUTHOR_LENGTH_ENV: &str = "LECTARA_MAX_AUTHOR_LENGTH";
+pub const MAX_BODY_LENGTH_ENV: &str = "LECTARA_MAX_BODY_LENGTH";
+
+/// Gateway used for `ipfs://` links when none is configured
+pub const DEFAULT_IPFS_GATEWAY: &str = "ipfs.io";
+
+/// Ways items are saved, besides `import:<name>` for importers
+pub const SOURCES: &[&str] = &[
+    "cli",
+    "api",
+    "extension",
+    "email",
+    "feed",
+    "web",
+    "zotero",
+    "sync",
+];
+
+/// Deployment-specific rules applied while validating URLs.
+///
+/// Host entries match the host itself and any subdomain of it. A denied host
+/// is rejected even if it is also allowed; an allowed host skips the
+/// local-address check, e.g. to save pages from `localhost` in development.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationContext {
+    pub allowed_hosts: Vec<String>,
+    pub denied_hosts: Vec<String>,
+    /// Developer mode: accept every local address, not just allowlisted ones.
+    /// Denied hosts are still rejected.
+    pub allow_local_urls: bool,
+    /// Non-HTTP schemes accepted in addition to http and https
+    pub extra_schemes: Vec<ExtraScheme>,
+    /// Gateway host for `ipfs://` links; [`DEFAULT_IPFS_GATEWAY`] when unset
+    pub ipfs_gateway: Option<String>,
+    /// How metadata is compared when a saved URL is submitted again
+    pub comparison: MetadataComparison,
+    pub limits: MetadataLimits,
+}
+
+impl ValidationContext {
+    /// Reads host lists from [`ALLOWED_HOSTS_ENV`] and [`DENIED_HOSTS_ENV`],
+    /// developer mode from [`ALLOW_LOCAL_URLS_ENV`], and extra schemes from
+    /// [`EXTRA_SCHEMES_ENV`] and [`IPFS_GATEWAY_ENV`], and the metadata
+    /// comparison from [`METADATA_COMPARISON_ENV`]. Unknown scheme and rule
+    /// names are ignored, as are limits that aren't positive numbers.
+    pub fn from_env() -> Self {
+        let hosts = |name| {
+            std::env::var(name)
+                .map(|value| parse_host_list(&value))
+                .unwrap_or_default()
+        };
+
+        Self {
+            allowed_hosts: hosts(ALLOWED_HOSTS_ENV),
+            denied_hosts: hosts(DENIED_HOSTS_ENV),
+            allow_local_urls: std::env::var(ALLOW_LOCAL_URLS_ENV)
+                .is_ok_and(|value| parse_flag(&value)),
+            extra_schemes: std::env::var(EXTRA_SCHEMES_ENV)
+                .map(|value| value.split(',').filter_map(ExtraScheme::parse).collect())
+                .unwrap_or_default(),
+            ipfs_gateway: std::env::var(IPFS_GATEWAY_ENV)
+                .ok()
+                .map(|gateway| gateway.trim().to_lowercase())
+                .filter(|gateway| !gateway.is_empty()),
+            comparison: std::env::var(METADATA_COMPARISON_ENV)
+                .map(|value| MetadataComparison::parse(&value))
+                .unwrap_or_default(),
+            limits: MetadataLimits::from_env(),
         }
     }
+
+    fn allows_scheme(&self, scheme: ExtraScheme) -> bool {
+        self.extra_schemes.contains(&scheme)
+    }
+
+    fn ipfs_gateway(&self) -> &str {
+        self.ipfs_gateway.as_deref().unwrap_or(DEFAULT_IPFS_GATEWAY)
+    }
+
+    fn is_allowed(&self, host: &str) -> bool {
+        self.allowed_hosts
+            .iter()
+            .any(|entry| host_matches(host, entry))
+    }
+
+    fn is_denied(&self, host: &str) -> bool {
+        self.denied_hosts
+            .iter()
+            .any(|entry| host_matches(host, entry))
+    }
+}
+
+/// How a resubmitted title, author or body is compared with the stored one
+/// to decide whether saving the URL again is idempotent. With every rule off,
+/// the default, any difference is a conflict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetadataComparison {
+    /// Ignore leading and trailing whitespace
+    pub trim: bool,
+    /// Treat each run of whitespace as a single space
+    pub collapse_whitespace: bool,
+    /// Compare titles and authors case-insensitively
+    pub ignore_case: bool,
+}
+
+impl MetadataComparison {
+    /// Every rule, for deployments where clients retype metadata by hand
+    pub const LENIENT: Self = Self {
+        trim: true,
+        collapse_whitespace: true,
+        ignore_case: true,
+    };
+
+    /// Parses a comma-separated list of `trim`, `collapse-whitespace` and
+    /// `ignore-case`
+    pub fn parse(value: &str) -> Self {
+        let mut comparison = Self::default();
+        for rule in value.split(',') {
+            match rule.trim().to_lowercase().as_str() {
+                "trim" => comparison.trim = true,
+                "collapse-whitespace" => comparison.collapse_whitespace = true,
+                "ignore-case" => comparison.ignore_case = true,
+                _ => {}
+            }
+        }
+        comparison
+    }
+
+    /// Whether two values of a field are the same under these rules; case is
+    /// only ignored when `case_insensitive_field` is set
+    pub fn matches(
+        &self,
+        submitted: Option<&str>,
+        stored: Option<&str>,
+        case_insensitive_field: bool,
+    ) -> bool {
+        match (submitted, stored) {
+            (Some(submitted), Some(stored)) => {
+                self.normalize(submitted, case_insensitive_field)
+                    == self.normalize(stored, case_insensitive_field)
+            }
+            (submitted, stored) => submitted == stored,
+        }
+    }
+
+    fn normalize<'a>(&self, value: &'a str, case_insensitive_field: bool) -> Cow<'a, str> {
+        let mut value = Cow::Borrowed(value);
+        if self.collapse_whitespace {
+            let mut collapsed = String::with_capacity(value.len());
+            let mut in_whitespace = false;
+            for c in value.chars() {
+                if !c.is_whitespace() {
+                    collapsed.push(c);
+                } else if !in_whitespace {
+                    collapsed.push(' ');
+                }
+                in_whitespace = c.is_whitespace();
+            }
+            value = Cow::Owned(collapsed);
+        }
+        if self.trim && value.trim().len() != value.len() {
+            value = Cow::Owned(value.trim().to_string());
+        }
+        if self.ignore_case && case_insensitive_field {
+            value = Cow::Owned(value.to_lowercase());
+        }
+        value
+    }
+}
+
+/// Longest title, author and body accepted, in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataLimits {
+    pub title: usize,
+    pub author: usize,
+    pub body: usize,
+}
+
+impl Default for MetadataLimits {
+    fn default() -> Self {
+        Self {
+            title: 1_000,
+            author: 500,
+            body: 1_000_000,
+        }
+    }
+}
+
+impl MetadataLimits {
+    fn from_env() -> Self {
+        let limit = |name, default| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .filter(|limit| *limit > 0)
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+
+        Self {
+            title: limit(MAX_TITLE_LENGTH_ENV, defaults.title),
+            author: limit(MAX_AUTHOR_LENGTH_ENV, defaults.author),
+            body: limit(MAX_BODY_LENGTH_ENV, defaults.body),
+        }
+    }
+}
+
+/// NFC-normalizes `value` and strips control characters. Line breaks and
+/// tabs are kept in multi-line text and become spaces otherwise.
+pub fn clean_text(value: &str, multiline: bool) -> String {
+    let value = if is_nfc(value) {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(value.nfc().collect())
+    };
+
+    value
+        .chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' if multiline => Some(c),
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// Cleans the title, author and body in place with [`clean_text`] and checks
+/// them against `limits`, listing every field that is too long
+pub fn check_metadata(
+    title: &mut Option<String>,
+    author: &mut Option<String>,
+    body: &mut Option<String>,
+    limits: &MetadataLimits,
+) -> Result<(), ValidationError> {
+    let fields = [
+        (MetadataField::Title, title, false, limits.title),
+        (MetadataField::Author, author, false, limits.author),
+        (MetadataField::Body, body, true, limits.body),
+    ];
+
+    let mut violations = Vec::new();
+    for (field, value, multiline, limit) in fields {
+        if let Some(value) = value {
+            *value = clean_text(value, multiline);
+            if value.chars().count() > limit {
+                violations.push(FieldViolation {
+                    field,
+                    reason: format!("must be at most {limit} characters"),
+                });
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidMetadata(violations))
+    }
+}
+
+/// Longest user agent or client version kept for an item
+const MAX_CLIENT_INFO_LENGTH: usize = 256;
+
+/// Cleans a client-reported user agent or version with [`clean_text`],
+/// cutting it to [`MAX_CLIENT_INFO_LENGTH`] characters; `None` when blank
+pub fn clean_client_info(value: &str) -> Option<String> {
+    let value: String = clean_text(value.trim(), false)
+        .chars()
+        .take(MAX_CLIENT_INFO_LENGTH)
+        .collect();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Lowercases a source recorded for a saved item, which must be one of
+/// [`SOURCES`] or `import:<name>` with a name of up to 32 letters, digits,
+/// `-` and `_`
+pub fn parse_source(value: &str) -> Result<String, ValidationError> {
+    let source = value.trim().to_lowercase();
+    let valid = match source.strip_prefix("import:") {
+        Some(name) => {
+            (1..=32).contains(&name.len())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        None => SOURCES.contains(&source.as_str()),
+    };
+    if valid {
+        Ok(source)
+    } else {
+        Err(ValidationError::InvalidSource(value.to_string()))
+    }
+}
+
+fn parse_host_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+pub(crate) fn host_matches(host: &str, entry: &str) -> bool {
+    host == entry
+        || host
+            .strip_suffix(entry)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Rewrites an enabled identifier scheme to its canonical https URL; other
+/// input is returned unchanged
+fn rewrite_identifier(
+    url_str: &str,
+    context: &ValidationContext,
+) -> Result<String, ValidationError> {
+    let Some((scheme, rest)) = url_str.split_once(':') else {
+        return Ok(url_str.to_string());
+    };
+
+    let (extra, rewritten) = match scheme.to_lowercase().as_str() {
+        "doi" => (
+            ExtraScheme::Doi,
+            format!("https://doi.org/{}", rest.trim_start_matches('/')),
+        ),
+        "arxiv" => (
+            ExtraScheme::Arxiv,
+            format!("https://arxiv.org/abs/{}", rest.trim_start_matches('/')),
+        ),
+        kind @ ("ipfs" | "ipns") => (
+            ExtraScheme::Ipfs,
+            format!(
+                "https://{}/{kind}/{}",
+                context.ipfs_gateway(),
+                rest.trim_start_matches('/')
+            ),
+        ),
+        _ => return Ok(url_str.to_string()),
+    };
+
+    if !context.allows_scheme(extra) {
+        return Err(ValidationError::UnsupportedScheme(scheme.to_lowercase()));
+    }
+    if rest.trim_start_matches('/').is_empty() {
+        return Err(ValidationError::MalformedUrl(url_str.to_string()));
+    }
+
+    Ok(rewritten)
+}
+
+/// Reduces a magnet link to its exact-topic parameter, the part that
+/// identifies the content; trackers and display names vary between copies
+fn validate_magnet(url: &Url) -> Result<ValidatedUrl, ValidationError> {
+    let topic = url
+        .query_pairs()
+        .find(|(key, _)| key == "xt")
+        .map(|(_, value)| value.to_lowercase())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| ValidationError::MalformedUrl(url.to_string()))?;
+
+    Ok(ValidatedUrl {
+        scheme: Scheme::Magnet,
+        host: String::new(),
+        port: None,
+        path: String::new(),
+        query: Some(BTreeMap::from([("xt".to_string(), topic)])),
+    })
+}
+
+fn is_local_host(host: &str) -> bool {
+    host == "localhost"
+        || host.starts_with("127.")
+        || host.starts_with("192.168.")
+        || host.starts_with("10.")
 }
 
 /// A URL that has been validated for internet content access
-/// Guarantees: non-empty host, HTTP/HTTPS scheme, no local addresses
+/// Guarantees: non-empty host, HTTP/HTTPS scheme, no local addresses unless
+/// explicitly allowed; magnet links (when enabled) are the one exception and
+/// carry only their `xt` query parameter
 #[derive(Debug, Clone, PartialEq)]
 pub struct ValidatedUrl {
     pub scheme: Scheme,
 
-    /// guaranteed non-empty and non-local
+    /// guaranteed non-empty and non-local (empty for magnet links)
     pub host: String,
 
     /// only non-default ports
@@ -54,7 +513,11 @@ pub struct ValidatedUrl {
 
 impl fmt::Display for ValidatedUrl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}://{}", self.scheme, self.host)?;
+        if self.scheme == Scheme::Magnet {
+            write!(f, "magnet:")?;
+        } else {
+            write!(f, "{}://{}", self.scheme, self.host)?;
+        }
 
         if let Some(port) = self.port {
             write!(f, ":{port}")?;
@@ -62,22 +525,22 @@ impl fmt::Display for ValidatedUrl {
 
         write!(f, "{}", self.path)?;
 
-        if let Some(ref query_params) = self.query {
-            if !query_params.is_empty() {
-                write!(f, "?")?;
-                let query_string = query_params
-                    .iter()
-                    .map(|(k, v)| {
-                        if v.is_empty() {
-                            k.clone()
-                        } else {
-                            format!("{k}={v}")
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("&");
-                write!(f, "{query_string}")?;
-            }
+        if let Some(ref query_params) = self.query
+            && !query_params.is_empty()
+        {
+            write!(f, "?")?;
+            let query_string = query_params
+                .iter()
+                .map(|(k, v)| {
+                    if v.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{k}={v}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            write!(f, "{query_string}")?;
         }
 
         Ok(())
@@ -88,10 +551,19 @@ impl TryFrom<Url> for ValidatedUrl {
     type Error = ValidationError;
 
     fn try_from(url: Url) -> Result<Self, Self::Error> {
+        ValidatedUrl::from_url(url, &ValidationContext::default())
+    }
+}
+
+impl ValidatedUrl {
+    pub fn from_url(url: Url, context: &ValidationContext) -> Result<Self, ValidationError> {
         // Parse and validate scheme
         let scheme = match url.scheme() {
             "http" => Scheme::Http,
             "https" => Scheme::Https,
+            "magnet" if context.allows_scheme(ExtraScheme::Magnet) => {
+                return validate_magnet(&url);
+            }
             scheme => return Err(ValidationError::UnsupportedScheme(scheme.to_string())),
         };
 
@@ -103,13 +575,13 @@ impl TryFrom<Url> for ValidatedUrl {
             return Err(ValidationError::MissingHost);
         }
 
-        // Normalize host to lowercase and check for local addresses
+        // Normalize host to lowercase, then apply the host lists ahead of the
+        // local address check so an allowlist entry can override it
         let host = host.to_lowercase();
-        if host == "localhost"
-            || host.starts_with("127.")
-            || host.starts_with("192.168.")
-            || host.starts_with("10.")
-        {
+        if context.is_denied(&host) {
+            return Err(ValidationError::DeniedHost(host));
+        }
+        if !context.allow_local_urls && !context.is_allowed(&host) && is_local_host(&host) {
             return Err(ValidationError::LocalAddress(host));
         }
 
@@ -117,7 +589,7 @@ impl TryFrom<Url> for ValidatedUrl {
         let port = url.port().filter(|&p| {
             let default_port = match scheme {
                 Scheme::Http => 80,
-                Scheme::Https => 443,
+                Scheme::Https | Scheme::Magnet => 443,
             };
             p != default_port
         });
@@ -159,24 +631,153 @@ impl TryFrom<Url> for ValidatedUrl {
 }
 
 pub fn validate_url(url_str: &str) -> Result<ValidatedUrl, ValidationError> {
+    validate_url_with(url_str, &ValidationContext::default())
+}
+
+pub fn validate_url_with(
+    url_str: &str,
+    context: &ValidationContext,
+) -> Result<ValidatedUrl, ValidationError> {
     if url_str.is_empty() {
         return Err(ValidationError::EmptyUrl);
     }
 
+    let rewritten = rewrite_identifier(url_str, context)?;
     let url =
-        Url::parse(url_str).map_err(|_| ValidationError::MalformedUrl(url_str.to_string()))?;
-    ValidatedUrl::try_from(url)
+        Url::parse(&rewritten).map_err(|_| ValidationError::MalformedUrl(url_str.to_string()))?;
+    ValidatedUrl::from_url(url, context)
 }
 
 pub fn normalize_url(url_str: &str) -> Result<String, ValidationError> {
-    let validated_url = validate_url(url_str)?;
+    normalize_url_with(url_str, &ValidationContext::default())
+}
+
+pub fn normalize_url_with(
+    url_str: &str,
+    context: &ValidationContext,
+) -> Result<String, ValidationError> {
+    let validated_url = validate_url_with(url_str, context)?;
     Ok(validated_url.to_string())
 }
 
+/// A URL that passed [`normalize_url_with`], in its normalized form, so it
+/// needn't be validated again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedUrl(String);
+
+impl NormalizedUrl {
+    pub fn parse(url_str: &str, context: &ValidationContext) -> Result<Self, ValidationError> {
+        normalize_url_with(url_str, context).map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for NormalizedUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_clean_text() {
+        // "e" followed by a combining acute accent composes to "é"
+        assert_eq!(clean_text("Cafe\u{301}", false), "Café");
+        assert_eq!(clean_text("Jane\u{0}\u{1b}[0m Doe", false), "Jane[0m Doe");
+        assert_eq!(clean_text("Two\nlines", false), "Two lines");
+        assert_eq!(clean_text("Two\nlines\u{7}", true), "Two\nlines");
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(parse_source(" CLI ").unwrap(), "cli");
+        assert_eq!(parse_source("import:pocket").unwrap(), "import:pocket");
+        assert!(parse_source("import:").is_err());
+        assert!(parse_source("import:my feed").is_err());
+        assert!(parse_source("zapier").is_err());
+    }
+
+    #[test]
+    fn test_clean_client_info() {
+        assert_eq!(
+            clean_client_info(" Lectara-Extension/1.4.2\u{0} ").as_deref(),
+            Some("Lectara-Extension/1.4.2")
+        );
+        assert_eq!(clean_client_info("  "), None);
+        assert_eq!(
+            clean_client_info(&"x".repeat(1000)).map(|value| value.len()),
+            Some(MAX_CLIENT_INFO_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_check_metadata_lists_every_long_field() {
+        let limits = MetadataLimits {
+            title: 5,
+            author: 5,
+            body: 5,
+        };
+        let result = check_metadata(
+            &mut Some("Too long".to_string()),
+            &mut Some("Ann".to_string()),
+            &mut Some("Longer body".to_string()),
+            &limits,
+        );
+        let Err(ValidationError::InvalidMetadata(violations)) = result else {
+            panic!("expected invalid metadata, got {result:?}");
+        };
+        let fields: Vec<_> = violations.iter().map(|v| v.field).collect();
+        assert_eq!(fields, [MetadataField::Title, MetadataField::Body]);
+
+        // Limits count characters, not bytes
+        assert!(
+            check_metadata(
+                &mut Some("ééééé".to_string()),
+                &mut None,
+                &mut None,
+                &limits
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parse_metadata_comparison() {
+        assert_eq!(MetadataComparison::parse(""), MetadataComparison::default());
+        assert_eq!(
+            MetadataComparison::parse("Trim, collapse-whitespace,ignore-case,unknown"),
+            MetadataComparison::LENIENT
+        );
+    }
+
+    #[test]
+    fn test_metadata_comparison_rules() {
+        let strict = MetadataComparison::default();
+        assert!(strict.matches(Some("Jane Doe"), Some("Jane Doe"), true));
+        assert!(!strict.matches(Some("Jane Doe"), Some("jane doe "), true));
+        assert!(!strict.matches(None, Some(""), true));
+
+        let lenient = MetadataComparison::LENIENT;
+        assert!(lenient.matches(Some("Jane Doe"), Some(" jane\t doe "), true));
+        // Bodies keep their case
+        assert!(!lenient.matches(Some("Body"), Some("body"), false));
+        assert!(!lenient.matches(None, Some(""), true));
+
+        let trim_only = MetadataComparison::parse("trim");
+        assert!(trim_only.matches(Some("Jane Doe"), Some("Jane Doe "), true));
+        assert!(!trim_only.matches(Some("Jane Doe"), Some("Jane  Doe"), true));
+    }
+
     // Valid URL tests
     #[test]
     fn test_validate_https_url() {
@@ -454,4 +1055,194 @@ mod tests {
         assert_eq!(validated.query, Some(expected_params));
         assert_eq!(validated.to_string(), "https://example.com/test?a=1&b=2");
     }
+
+    // Host allow/deny list tests
+    fn context(allowed: &[&str], denied: &[&str]) -> ValidationContext {
+        ValidationContext {
+            allowed_hosts: allowed.iter().map(|host| host.to_string()).collect(),
+            denied_hosts: denied.iter().map(|host| host.to_string()).collect(),
+            ..ValidationContext::default()
+        }
+    }
+
+    #[test]
+    fn test_denied_host_returns_denied_error() {
+        assert!(matches!(
+            validate_url_with(
+                "https://internal.corp/wiki",
+                &context(&[], &["internal.corp"])
+            ),
+            Err(ValidationError::DeniedHost(_))
+        ));
+    }
+
+    #[test]
+    fn test_denied_host_matches_subdomains_only() {
+        let context = context(&[], &["internal.corp"]);
+
+        assert!(matches!(
+            validate_url_with("https://wiki.internal.corp/", &context),
+            Err(ValidationError::DeniedHost(_))
+        ));
+        assert!(validate_url_with("https://notinternal.corp/", &context).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_host_overrides_local_address_check() {
+        let context = context(&["localhost"], &[]);
+
+        assert_eq!(
+            normalize_url_with("http://localhost:8000/article", &context).unwrap(),
+            "http://localhost:8000/article"
+        );
+        assert!(matches!(
+            validate_url_with("http://127.0.0.1/", &context),
+            Err(ValidationError::LocalAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_deny_takes_precedence_over_allow() {
+        assert!(matches!(
+            validate_url_with(
+                "https://docs.internal.corp/",
+                &context(&["docs.internal.corp"], &["internal.corp"])
+            ),
+            Err(ValidationError::DeniedHost(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_host_list_normalizes_entries() {
+        assert_eq!(
+            parse_host_list(" Internal.Corp, .example.org,,localhost "),
+            vec!["internal.corp", "example.org", "localhost"]
+        );
+    }
+
+    #[test]
+    fn test_allow_local_urls_accepts_private_addresses() {
+        let context = ValidationContext {
+            allow_local_urls: true,
+            ..ValidationContext::default()
+        };
+
+        assert!(validate_url_with("http://localhost:4000/post", &context).is_ok());
+        assert!(validate_url_with("http://192.168.1.20/notes", &context).is_ok());
+        assert!(validate_url_with("http://10.0.0.1/internal", &context).is_ok());
+    }
+
+    #[test]
+    fn test_allow_local_urls_still_applies_deny_list() {
+        let context = ValidationContext {
+            denied_hosts: vec!["localhost".to_string()],
+            allow_local_urls: true,
+            ..ValidationContext::default()
+        };
+
+        assert!(matches!(
+            validate_url_with("http://localhost/", &context),
+            Err(ValidationError::DeniedHost(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        assert!(parse_flag("true"));
+        assert!(parse_flag(" 1 "));
+        assert!(parse_flag("YES"));
+        assert!(!parse_flag("false"));
+        assert!(!parse_flag(""));
+    }
+
+    // Extra scheme tests
+    fn with_schemes(schemes: &[ExtraScheme]) -> ValidationContext {
+        ValidationContext {
+            extra_schemes: schemes.to_vec(),
+            ..ValidationContext::default()
+        }
+    }
+
+    #[test]
+    fn test_extra_schemes_rejected_by_default() {
+        for url in [
+            "doi:10.1000/182",
+            "arxiv:2101.00001",
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a",
+        ] {
+            assert!(
+                matches!(
+                    validate_url(url),
+                    Err(ValidationError::UnsupportedScheme(_))
+                ),
+                "{url} should be unsupported"
+            );
+        }
+    }
+
+    #[test]
+    fn test_doi_maps_to_doi_org() {
+        assert_eq!(
+            normalize_url_with("doi:10.1000/182", &with_schemes(&[ExtraScheme::Doi])).unwrap(),
+            "https://doi.org/10.1000/182"
+        );
+    }
+
+    #[test]
+    fn test_arxiv_maps_to_abstract_page() {
+        assert_eq!(
+            normalize_url_with("arXiv:2101.00001v2", &with_schemes(&[ExtraScheme::Arxiv])).unwrap(),
+            "https://arxiv.org/abs/2101.00001v2"
+        );
+    }
+
+    #[test]
+    fn test_ipfs_uses_configured_gateway() {
+        let mut context = with_schemes(&[ExtraScheme::Ipfs]);
+        let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+        assert_eq!(
+            normalize_url_with(&format!("ipfs://{cid}/wiki/"), &context).unwrap(),
+            format!("https://ipfs.io/ipfs/{cid}/wiki")
+        );
+
+        context.ipfs_gateway = Some("dweb.link".to_string());
+        assert_eq!(
+            normalize_url_with("ipns://example.eth", &context).unwrap(),
+            "https://dweb.link/ipns/example.eth"
+        );
+    }
+
+    #[test]
+    fn test_identifier_without_value_is_malformed() {
+        assert!(matches!(
+            validate_url_with("doi:", &with_schemes(&[ExtraScheme::Doi])),
+            Err(ValidationError::MalformedUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_magnet_keeps_only_exact_topic() {
+        let context = with_schemes(&[ExtraScheme::Magnet]);
+
+        assert_eq!(
+            normalize_url_with(
+                "magnet:?dn=Some+Dataset&xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A&tr=udp://tracker.example:80",
+                &context
+            )
+            .unwrap(),
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a"
+        );
+        assert!(matches!(
+            validate_url_with("magnet:?dn=missing-topic", &context),
+            Err(ValidationError::MalformedUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_extra_scheme_parse() {
+        assert_eq!(ExtraScheme::parse(" DOI "), Some(ExtraScheme::Doi));
+        assert_eq!(ExtraScheme::parse("gopher"), None);
+    }
 }
@@ -0,0 +1,123 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use lectara_service::auth::{AccessPolicy, hash_api_key};
+use lectara_service::{DefaultAppState, routes};
+use serde_json::{Value, json};
+
+use crate::common::establish_test_connection;
+
+fn create_server(public_reads: bool) -> TestServer {
+    let policy = AccessPolicy::new([hash_api_key("owner-key")], public_reads);
+    let state = DefaultAppState::new(Arc::new(Mutex::new(establish_test_connection())));
+    TestServer::new(policy.protect(routes::create_router()).with_state(state)).unwrap()
+}
+
+fn owner_key() -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static("authorization"),
+        HeaderValue::from_static("Bearer owner-key"),
+    )
+}
+
+#[tokio::test]
+async fn test_keys_required_for_everything_but_health() -> Result<()> {
+    let server = create_server(false);
+    let (authorization, key) = owner_key();
+
+    server
+        .get("/api/v1/content")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/private" }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server.get("/health").await.assert_status_ok();
+    server
+        .get("/web")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    server
+        .get("/api/v1/content")
+        .add_header(authorization, key)
+        .await
+        .assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_public_reads_leave_writes_behind_keys() -> Result<()> {
+    let server = create_server(true);
+    let (authorization, key) = owner_key();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/anonymous" }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    let created: Value = server
+        .post("/api/v1/content")
+        .add_header(authorization.clone(), key.clone())
+        .json(&json!({ "url": "https://example.com/reading", "title": "Reading" }))
+        .await
+        .json();
+    let id = created["id"].as_i64().unwrap();
+
+    let listed: Value = server.get("/api/v1/content").await.json();
+    assert_eq!(listed["items"][0]["url"], "https://example.com/reading");
+    server
+        .get(&format!("/api/v1/content/{id}"))
+        .await
+        .assert_status_ok();
+    server.get("/api/v1/content/count").await.assert_status_ok();
+    server
+        .get("/api/v1/search")
+        .add_query_param("q", "reading")
+        .await
+        .assert_status_ok();
+    server.get("/web").await.assert_status_ok();
+
+    // Share tokens and the admin API stay private
+    server
+        .get(&format!("/api/v1/content/{id}/share"))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .get("/api/v1/admin/read-only")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .put("/api/v1/admin/read-only")
+        .json(&json!({ "enabled": true }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keys_accepted_as_basic_auth_password() -> Result<()> {
+    let server = create_server(false);
+
+    // printf %s reader:owner-key | base64
+    server
+        .get("/web/opds")
+        .add_header(
+            HeaderName::from_static("authorization"),
+            HeaderValue::from_static("Basic cmVhZGVyOm93bmVyLWtleQ=="),
+        )
+        .await
+        .assert_status_ok();
+    server
+        .get("/web/opds")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use axum_test::TestServer;
+use lectara_service::metrics::{MetricsLayer, RequestMetrics};
+use lectara_service::{DefaultAppState, routes};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+use crate::common::establish_test_connection;
+
+#[tokio::test]
+async fn test_health_report_groups_requests_by_route() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let metrics = RequestMetrics::default();
+    let state = DefaultAppState::new(db).with_metrics(metrics.clone());
+    let app = routes::create_router()
+        .layer(MetricsLayer::new(metrics))
+        .with_state(state);
+    let server = TestServer::new(app)?;
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/measured" }))
+        .await
+        .assert_status_ok();
+    for id in [1, 2, 3] {
+        server.get(&format!("/api/v1/content/{id}")).await;
+    }
+    server.get("/no-such-page").await;
+
+    let report: Value = server.get("/api/v1/admin/health-report").await.json();
+    assert_eq!(report["status"], "ok");
+    assert_eq!(report["window_seconds"], 300);
+
+    let endpoints = report["endpoints"].as_array().unwrap();
+    let names: Vec<&str> = endpoints
+        .iter()
+        .map(|endpoint| endpoint["endpoint"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "GET /api/v1/content/{id}",
+            "GET unmatched",
+            "POST /api/v1/content"
+        ]
+    );
+
+    let by_id = &endpoints[0];
+    assert_eq!(by_id["requests"], 3);
+    // Items 2 and 3 don't exist
+    assert!((by_id["client_error_rate"].as_f64().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(by_id["error_rate"], 0.0);
+    assert!(by_id["p95_ms"].as_f64().unwrap() >= by_id["p50_ms"].as_f64().unwrap());
+    assert_eq!(by_id["alerts"], json!([]));
+
+    let groups: Vec<&str> = report["concurrency"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|group| group["group"].as_str().unwrap())
+        .collect();
+    assert_eq!(groups, ["search", "export", "archive"]);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use chrono::{SecondsFormat, TimeDelta, Utc};
+use serde_json::{Value, json};
+
+fn from_now(delta: TimeDelta) -> String {
+    (Utc::now() + delta).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[tokio::test]
+async fn test_schedule_and_cancel_maintenance() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let status: Value = server.get("/api/v1/admin/maintenance").await.json();
+    assert_eq!(status, json!({ "window": null, "active": false }));
+
+    let response = server
+        .put("/api/v1/admin/maintenance")
+        .json(&json!({
+            "starts_at": from_now(TimeDelta::hours(1)),
+            "ends_at": from_now(TimeDelta::hours(2)),
+            "message": "Moving to a bigger disk"
+        }))
+        .await;
+    response.assert_status_ok();
+    let status: Value = response.json();
+    assert_eq!(status["active"], false);
+    assert_eq!(status["window"]["message"], "Moving to a bigger disk");
+
+    // Shared pages announce the upcoming window
+    let id = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/shared" }))
+        .await
+        .json::<Value>()["id"]
+        .as_u64()
+        .unwrap();
+    let token = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({}))
+        .await
+        .json::<Value>()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let html = server.get(&format!("/web/share/{token}")).await.text();
+    assert!(html.contains("<p class=\"banner\" role=\"status\">Scheduled maintenance from "));
+    assert!(html.contains("UTC: Moving to a bigger disk</p>"));
+
+    server
+        .delete("/api/v1/admin/maintenance")
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let status: Value = server.get("/api/v1/admin/maintenance").await.json();
+    assert_eq!(status["window"], Value::Null);
+    let html = server.get(&format!("/web/share/{token}")).await.text();
+    assert!(!html.contains("class=\"banner\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schedule_maintenance_validation() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let cases = [
+        json!({ "starts_at": "tomorrow", "ends_at": from_now(TimeDelta::hours(2)) }),
+        json!({
+            "starts_at": from_now(TimeDelta::hours(2)),
+            "ends_at": from_now(TimeDelta::hours(1))
+        }),
+        json!({
+            "starts_at": from_now(-TimeDelta::hours(2)),
+            "ends_at": from_now(-TimeDelta::hours(1))
+        }),
+        json!({
+            "starts_at": from_now(TimeDelta::hours(1)),
+            "ends_at": from_now(TimeDelta::hours(2)),
+            "message": "x".repeat(201)
+        }),
+    ];
+    for payload in cases {
+        server
+            .put("/api/v1/admin/maintenance")
+            .json(&payload)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(())
+}
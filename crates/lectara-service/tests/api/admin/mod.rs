@@ -0,0 +1,4 @@
+pub mod health_report;
+pub mod maintenance;
+pub mod read_only;
+pub mod reload;
@@ -0,0 +1,79 @@
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+use anyhow::Result;
+use axum::http::StatusCode;
+use lectara_service::read_only::ReadOnlyMode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_read_only_mode_rejects_writes() -> Result<()> {
+    let (server, _db) = create_test_server();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/before" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .put("/api/v1/admin/read-only")
+        .json(&json!({ "enabled": true, "reason": "Restoring last night's backup" }))
+        .await;
+    response.assert_status_ok();
+    let status: Value = response.json();
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["reason"], "Restoring last night's backup");
+    assert!(status["since"].is_string());
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/during" }))
+        .await;
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "Service is read-only: Restoring last night's backup"
+    );
+    server
+        .post("/api/v1/content/1/share")
+        .json(&json!({}))
+        .await
+        .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+    // Reads keep working
+    server.get("/api/v1/content/1").await.assert_status_ok();
+    let count: Value = server.get("/api/v1/content/count").await.json();
+    assert_eq!(count["total"], 1);
+
+    let response = server
+        .put("/api/v1/admin/read-only")
+        .json(&json!({ "enabled": false }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["enabled"], false);
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/after" }))
+        .await
+        .assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_only_mode_set_at_startup() -> Result<()> {
+    let read_only = ReadOnlyMode::default();
+    read_only.enable(None);
+    let (server, _db) = create_test_server_with_state(|state| state.with_read_only(read_only));
+
+    let status: Value = server.get("/api/v1/admin/read-only").await.json();
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["reason"], "The service is in read-only mode");
+
+    server
+        .post("/api/v1/content/import/rss")
+        .text("<rss></rss>")
+        .await
+        .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use lectara_service::reload::Reloader;
+use lectara_service::{DefaultAppState, routes};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::common::establish_test_connection;
+
+#[tokio::test]
+async fn test_reload_applies_the_settings_file() -> Result<()> {
+    let settings =
+        std::env::temp_dir().join(format!("lectara-admin-reload-{}.toml", std::process::id()));
+    std::fs::write(&settings, "RUST_LOG = \"info\"\n")?;
+
+    let reloader = Reloader::new(Some(settings.clone()), HashMap::new());
+    let mut log_filter = reloader.log_filter();
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let state = DefaultAppState::new(db).with_reloader(reloader);
+    let server = TestServer::new(routes::create_router().with_state(state))?;
+
+    let response = server.post("/api/v1/admin/reload").await;
+    response.assert_status_ok();
+    let reloaded: Value = response.json();
+    assert_eq!(reloaded["log_filter"], "info");
+    assert_eq!(reloaded["credential_sites"], 0);
+    assert_eq!(*log_filter.borrow_and_update(), "info");
+
+    // A broken file is reported, and the filter in use stays
+    std::fs::write(&settings, "RUST_LOG = \"info,lectara=loud\"\n")?;
+    let response = server.post("/api/v1/admin/reload").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("RUST_LOG"));
+    assert!(!log_filter.has_changed()?);
+
+    std::fs::remove_file(&settings)?;
+    Ok(())
+}
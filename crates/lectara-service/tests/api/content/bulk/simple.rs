@@ -0,0 +1,206 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils::{
+    count_content_items, get_content_item_by_id, update_content_item_timestamp,
+};
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer, url: &str, body: Option<&str>) -> i64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "body": body }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_dry_run_counts_without_changing_anything() -> Result<()> {
+    let (server, db) = create_test_server();
+    let first = add_item(&server, "https://example.com/first", None).await;
+    let second = add_item(&server, "https://example.com/second", None).await;
+
+    let response = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "delete", "ids": [first, second, 999], "dry_run": true }))
+        .await;
+    response.assert_status_ok();
+    let result: Value = response.json();
+    assert_eq!(result["action"], "delete");
+    assert_eq!(result["dry_run"], true);
+    assert_eq!(result["affected"], 2);
+
+    let mut conn = db.lock().unwrap();
+    assert_eq!(count_content_items(&mut conn), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mark_read_and_archive_by_ids() -> Result<()> {
+    let (server, db) = create_test_server();
+    let first = add_item(&server, "https://example.com/first", None).await;
+    let second = add_item(&server, "https://example.com/second", None).await;
+
+    let result: Value = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "mark-read", "ids": [first] }))
+        .await
+        .json();
+    assert_eq!(result["affected"], 1);
+    let result: Value = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "archive", "ids": [first, second] }))
+        .await
+        .json();
+    assert_eq!(result["affected"], 2);
+
+    let read_at = {
+        let mut conn = db.lock().unwrap();
+        let first = get_content_item_by_id(&mut conn, first as i32).unwrap();
+        let second = get_content_item_by_id(&mut conn, second as i32).unwrap();
+        assert!(first.read_at.is_some() && first.archived_at.is_some());
+        assert!(second.read_at.is_none() && second.archived_at.is_some());
+        first.read_at
+    };
+
+    // Marking again keeps the time it was first read
+    server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "mark-read", "ids": [first] }))
+        .await
+        .assert_status_ok();
+    let item: Value = server.get(&format!("/api/v1/content/{first}")).await.json();
+    assert!(item["archived_at"].is_string());
+    let mut conn = db.lock().unwrap();
+    assert_eq!(
+        get_content_item_by_id(&mut conn, first as i32)
+            .unwrap()
+            .read_at,
+        read_at
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_by_domain_and_date() -> Result<()> {
+    let (server, db) = create_test_server();
+    let old = add_item(&server, "https://example.com/old", Some("Shared body")).await;
+    let recent = add_item(&server, "https://blog.example.com/recent", None).await;
+    let other = add_item(&server, "https://notexample.com/old", Some("Shared body")).await;
+    {
+        let mut conn = db.lock().unwrap();
+        let saved = NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        update_content_item_timestamp(&mut conn, old as i32, saved);
+        update_content_item_timestamp(&mut conn, other as i32, saved);
+    }
+    server
+        .post(&format!("/api/v1/content/{old}/pin"))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/v1/content/{recent}/pin"))
+        .await
+        .assert_status_ok();
+    for id in [old, other] {
+        server
+            .post(&format!("/api/v1/content/{id}/comments"))
+            .json(&json!({ "author": "Ada", "body": "Worth reading" }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let preview: Value = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({
+            "action": "delete",
+            "filter": { "domain": "Example.com" },
+            "dry_run": true,
+        }))
+        .await
+        .json();
+    assert_eq!(preview["affected"], 2);
+
+    let result: Value = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({
+            "action": "delete",
+            "filter": { "domain": "example.com", "until": "2025-06-01T00:00:00Z" },
+        }))
+        .await
+        .json();
+    assert_eq!(result["affected"], 1);
+
+    server
+        .get(&format!("/api/v1/content/{old}"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    // The body is still referenced by the other item
+    let kept: Value = server.get(&format!("/api/v1/content/{other}")).await.json();
+    assert_eq!(kept["body"], "Shared body");
+
+    // The remaining pin moved up to the freed position
+    let pinned: Value = server.get("/api/v1/content/pins").await.json();
+    assert_eq!(pinned["items"][0]["id"], recent);
+    assert_eq!(pinned["items"][0]["pinned_position"], 0);
+    server
+        .post(&format!("/api/v1/content/{other}/pin"))
+        .await
+        .assert_status_ok();
+
+    // Only the notification about the kept item is left
+    let notifications: Value = server.get("/api/v1/notifications").await.json();
+    let items = notifications["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["content_id"], other);
+
+    let count: Value = server.get("/api/v1/content/count").await.json();
+    assert_eq!(count["total"], 2);
+    let mut conn = db.lock().unwrap();
+    assert_eq!(count_content_items(&mut conn), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_ambiguous_or_unsupported_selections() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/item", None).await;
+
+    for request in [
+        json!({ "action": "delete" }),
+        json!({ "action": "delete", "ids": [id], "filter": { "domain": "example.com" } }),
+        json!({ "action": "delete", "filter": {} }),
+        json!({ "action": "archive", "filter": { "tag": "rust" } }),
+        json!({ "action": "archive", "filter": { "until": "yesterday" } }),
+    ] {
+        server
+            .post("/api/v1/content:bulk")
+            .json(&request)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+    server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "shred", "ids": [id] }))
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "archive", "filter": { "tag": "rust" } }))
+        .await;
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "Filtering by tag is not supported"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing::get,
+};
+use axum_test::TestServer;
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_validation;
+
+/// Serves `/page` with the HTML in `page`, and 404 at `/gone`
+async fn spawn_site(page: Arc<Mutex<String>>) -> String {
+    let app = Router::new()
+        .route(
+            "/page",
+            get(|State(page): State<Arc<Mutex<String>>>| async move {
+                let html = page.lock().unwrap().clone();
+                ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response()
+            }),
+        )
+        .route("/gone", get(|| async { StatusCode::NOT_FOUND }))
+        .with_state(page);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn create_server() -> TestServer {
+    let (server, _db) = create_test_server_with_validation(ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    });
+    server
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "body": "Saved notes" }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn check_update(server: &TestServer, id: u64) -> Value {
+    let response = server
+        .post(&format!("/api/v1/content/{id}/check-update"))
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_check_update_detects_changes() -> Result<()> {
+    let page = Arc::new(Mutex::new(
+        "<p>First version</p><script>nonce = 1</script>".to_string(),
+    ));
+    let site = spawn_site(page.clone()).await;
+    let server = create_server();
+    let id = add_content(&server, &format!("{site}/page")).await;
+
+    let first = check_update(&server, id).await;
+    assert_eq!(first["status"], "first_check");
+    assert!(first["previous_hash"].is_null());
+    assert_eq!(first["content_hash"].as_str().unwrap().len(), 64);
+
+    // Only markup and scripts differ
+    *page.lock().unwrap() =
+        "<div><p>First   version</p><script>nonce = 2</script></div>".to_string();
+    let unchanged = check_update(&server, id).await;
+    assert_eq!(unchanged["status"], "unchanged");
+    assert_eq!(unchanged["content_hash"], first["content_hash"]);
+    assert_eq!(unchanged["changed_at"], first["changed_at"]);
+
+    *page.lock().unwrap() = "<p>Second version</p>".to_string();
+    let changed = check_update(&server, id).await;
+    assert_eq!(changed["status"], "changed");
+    assert_eq!(changed["previous_hash"], first["content_hash"]);
+    assert_ne!(changed["content_hash"], first["content_hash"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_update_reports_dead_links() -> Result<()> {
+    let site = spawn_site(Arc::new(Mutex::new(String::new()))).await;
+    let server = create_server();
+    let id = add_content(&server, &format!("{site}/gone")).await;
+
+    let check = check_update(&server, id).await;
+
+    assert_eq!(check["status"], "unreachable");
+    assert_eq!(check["http_status"], 404);
+    assert!(check["content_hash"].is_null());
+    assert!(check["error"].is_string());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_update_missing_item() -> Result<()> {
+    let server = create_server();
+
+    server
+        .post("/api/v1/content/999/check-update")
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
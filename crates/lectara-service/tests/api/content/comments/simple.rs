@@ -0,0 +1,146 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "title": "Shared Article" }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn comment(server: &TestServer, id: u64, payload: Value) -> Value {
+    let response = server
+        .post(&format!("/api/v1/content/{id}/comments"))
+        .json(&payload)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_comments_are_threaded() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let first = comment(
+        &server,
+        id,
+        json!({ "author": " Ada ", "body": "Worth reading" }),
+    )
+    .await;
+    assert_eq!(first["author"], "Ada");
+    assert_eq!(first["content_id"].as_u64().unwrap(), id);
+    assert!(first["parent_id"].is_null());
+
+    let reply = comment(
+        &server,
+        id,
+        json!({ "author": "Grace", "body": "Agreed", "parent_id": first["id"] }),
+    )
+    .await;
+    assert_eq!(reply["parent_id"], first["id"]);
+
+    let response = server.get(&format!("/api/v1/content/{id}/comments")).await;
+    response.assert_status_ok();
+    let comments: Vec<Value> = response.json();
+    assert_eq!(comments, [first, reply]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_comments() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+    let other_id = add_item(&server, "https://example.com/other").await;
+    let other = comment(
+        &server,
+        other_id,
+        json!({ "author": "Ada", "body": "Elsewhere" }),
+    )
+    .await;
+
+    for payload in [
+        json!({ "author": "", "body": "Worth reading" }),
+        json!({ "author": "Ada", "body": "  " }),
+        // Replies stay on the item of the comment they answer
+        json!({ "author": "Ada", "body": "Agreed", "parent_id": other["id"] }),
+    ] {
+        server
+            .post(&format!("/api/v1/content/{id}/comments"))
+            .json(&payload)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    server
+        .post("/api/v1/content/999/comments")
+        .json(&json!({ "author": "Ada", "body": "Worth reading" }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_comment_leaves_a_notification() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+    comment(
+        &server,
+        id,
+        json!({ "author": "Ada", "body": "Worth reading" }),
+    )
+    .await;
+
+    let notifications: Value = server.get("/api/v1/notifications").await.json();
+    let notification = &notifications["items"][0];
+    assert_eq!(notification["kind"], "comment-added");
+    assert_eq!(notification["message"], "Ada commented on Shared Article");
+    assert_eq!(notification["content_id"].as_u64().unwrap(), id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_comment_removes_replies() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+    let first = comment(&server, id, json!({ "author": "Ada", "body": "First" })).await;
+    let reply = comment(
+        &server,
+        id,
+        json!({ "author": "Grace", "body": "Reply", "parent_id": first["id"] }),
+    )
+    .await;
+    comment(
+        &server,
+        id,
+        json!({ "author": "Ada", "body": "Nested", "parent_id": reply["id"] }),
+    )
+    .await;
+    let second = comment(&server, id, json!({ "author": "Grace", "body": "Second" })).await;
+
+    let path = format!("/api/v1/content/{id}/comments/{}", first["id"]);
+    server
+        .delete(&path)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .delete(&path)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let comments: Vec<Value> = server
+        .get(&format!("/api/v1/content/{id}/comments"))
+        .await
+        .json();
+    assert_eq!(comments, [second]);
+
+    Ok(())
+}
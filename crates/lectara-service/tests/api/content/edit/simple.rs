@@ -0,0 +1,95 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/scraped",
+            "title": "Scraped Titel | Example Blog",
+            "author": "admin",
+            "body": "The original body",
+        }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_patch_replaces_given_fields() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server).await;
+
+    let response = server
+        .patch(&format!("/api/v1/content/{id}"))
+        .json(&json!({ "title": "Scraped Title", "author": "" }))
+        .await;
+    response.assert_status_ok();
+    let item: Value = response.json();
+    assert_eq!(item["title"], "Scraped Title");
+    assert!(item["author"].is_null());
+    assert_eq!(item["body"], "The original body");
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["title"], "Scraped Title");
+    assert!(item["author"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_patch_replaces_the_searched_body() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server).await;
+
+    server
+        .patch(&format!("/api/v1/content/{id}"))
+        .json(&json!({ "body": "A corrected body about lighthouses" }))
+        .await
+        .assert_status_ok();
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["body"], "A corrected body about lighthouses");
+
+    let found: Value = server
+        .get("/api/v1/search")
+        .add_query_param("q", "lighthouses")
+        .await
+        .json();
+    assert_eq!(found["items"][0]["id"].as_u64(), Some(id));
+    let found: Value = server
+        .get("/api/v1/search")
+        .add_query_param("q", "original")
+        .await
+        .json();
+    assert!(found["items"].as_array().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_patch_rejects_empty_and_unknown() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server).await;
+
+    server
+        .patch(&format!("/api/v1/content/{id}"))
+        .json(&json!({}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .patch(&format!("/api/v1/content/{id}"))
+        .json(&json!({ "title": "x".repeat(10_000) }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .patch("/api/v1/content/9999")
+        .json(&json!({ "title": "Missing" }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
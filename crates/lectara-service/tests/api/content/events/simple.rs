@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::common::establish_test_connection;
+use anyhow::Result;
+use axum::http::StatusCode;
+use lectara_service::{DefaultAppState, routes};
+use serde_json::json;
+
+/// Reads the stream until an event of `kind` containing `text` arrives,
+/// returning it
+async fn next_event(stream: &mut reqwest::Response, kind: &str, text: &str) -> Result<String> {
+    let mut received = String::new();
+    loop {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+            .await??
+            .expect("the stream stays open");
+        received.push_str(std::str::from_utf8(&chunk)?);
+        if let Some(event) = received
+            .split("\n\n")
+            .find(|event| event.contains(&format!("event: {kind}\n")) && event.contains(text))
+        {
+            return Ok(event.to_string());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_stream_sends_saved_and_updated_items() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let app = routes::create_router().with_state(DefaultAppState::new(db));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = reqwest::Client::new();
+    let mut stream = client
+        .get(format!("http://{address}/api/v1/content/events"))
+        .send()
+        .await?;
+    assert_eq!(stream.status(), StatusCode::OK);
+    assert_eq!(
+        stream.headers()["content-type"].to_str()?,
+        "text/event-stream"
+    );
+
+    let saved: serde_json::Value = client
+        .post(format!("http://{address}/api/v1/content"))
+        .json(&json!({
+            "url": "https://example.com/watched",
+            "title": "Watched",
+            "body": "Some text",
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let id = saved["id"].as_i64().unwrap();
+
+    let event = next_event(
+        &mut stream,
+        "saved",
+        "\"url\":\"https://example.com/watched\"",
+    )
+    .await?;
+    assert!(event.contains(&format!("id: {id}\n")), "{event}");
+
+    client
+        .patch(format!("http://{address}/api/v1/content/{id}"))
+        .json(&json!({ "title": "Renamed" }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    // Enrichment may have updated the item before
+    next_event(&mut stream, "updated", "\"title\":\"Renamed\"").await?;
+
+    Ok(())
+}
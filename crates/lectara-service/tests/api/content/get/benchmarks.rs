@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use lectara_service::repositories::{
+    ContentFilter, ContentRepository, ListContentParams, SqliteContentRepository,
+};
+
+use crate::common::establish_test_connection;
+
+const ROWS: usize = 100_000;
+const PAGES: u32 = 20;
+const PAGE_SIZE: u32 = 50;
+
+fn seed_rows(conn: &mut SqliteConnection) {
+    diesel::sql_query(format!(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {ROWS})
+         INSERT INTO content_items (url, title, author, created_at)
+         SELECT 'https://example.com/' || i, 'Title ' || i, 'Author',
+                datetime(1600000000 + i * 60, 'unixepoch')
+         FROM n"
+    ))
+    .execute(conn)
+    .expect("Failed to seed rows");
+}
+
+fn page_params(page: u32, filter: &ContentFilter, include_total: bool) -> ListContentParams {
+    ListContentParams {
+        limit: Some(PAGE_SIZE),
+        offset: Some(page * PAGE_SIZE),
+        filter: filter.clone(),
+        include_total,
+    }
+}
+
+/// Pages through a filtered listing the way clients do, comparing the
+/// cached total against recounting every page with a second query.
+#[tokio::test]
+#[ignore = "benchmark over 100k rows; run with --ignored --nocapture"]
+async fn bench_list_total_on_100k_rows() {
+    let mut conn = establish_test_connection();
+    seed_rows(&mut conn);
+    let db = Arc::new(Mutex::new(conn));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let filter = ContentFilter {
+        since: Some(
+            NaiveDate::from_ymd_opt(2020, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        ),
+        until: None,
+        content_type: None,
+        source: None,
+    };
+
+    // Two statements per page: items, then an uncached COUNT
+    let mut recount_elapsed = Duration::ZERO;
+    for page in 0..PAGES {
+        let start = Instant::now();
+        let result = repo.list(&page_params(page, &filter, false)).await.unwrap();
+        let total: i64 = {
+            use lectara_service::schema::content_items;
+            let mut conn = db.lock().unwrap();
+            content_items::table
+                .filter(content_items::created_at.ge(filter.since.unwrap()))
+                .count()
+                .get_result(&mut *conn)
+                .unwrap()
+        };
+        recount_elapsed += start.elapsed();
+        assert_eq!(result.items.len(), PAGE_SIZE as usize);
+        assert_eq!(total as usize, ROWS);
+    }
+
+    // Total computed once for the filter, then served from the cache
+    let mut cached_elapsed = Duration::ZERO;
+    for page in 0..PAGES {
+        let start = Instant::now();
+        let result = repo.list(&page_params(page, &filter, true)).await.unwrap();
+        cached_elapsed += start.elapsed();
+        assert_eq!(result.items.len(), PAGE_SIZE as usize);
+        assert_eq!(result.total, Some(ROWS as u64));
+    }
+
+    println!(
+        "{PAGES} pages over {ROWS} rows: recount {:?}/page, cached total {:?}/page",
+        recount_elapsed / PAGES,
+        cached_elapsed / PAGES
+    );
+    assert!(cached_elapsed < recount_elapsed);
+}
@@ -0,0 +1,91 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_list_revalidates_until_a_write() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/content").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "private, no-cache");
+    let etag = response.header("etag");
+
+    for path in ["/api/v1/content", "/api/v1/content?limit=10"] {
+        let response = server.get(path).await;
+        let response = server
+            .get(path)
+            .add_header("if-none-match", response.header("etag"))
+            .await;
+        response.assert_status(StatusCode::NOT_MODIFIED);
+        assert!(response.text().is_empty());
+    }
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/new" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/content")
+        .add_header("if-none-match", etag.clone())
+        .await;
+    response.assert_status_ok();
+    assert_ne!(response.header("etag"), etag);
+    assert_eq!(response.json::<serde_json::Value>()["total"], 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_count_and_pins_revalidate() -> Result<()> {
+    let (server, _db) = create_test_server();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/rust", "title": "Rust" }))
+        .await
+        .assert_status_ok();
+
+    for path in [
+        "/api/v1/search?q=rust",
+        "/api/v1/content/count",
+        "/api/v1/content/pins",
+    ] {
+        let etag = server.get(path).await.header("etag");
+        server
+            .get(path)
+            .add_header("if-none-match", etag)
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_relative_times_are_not_revalidated() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    // The window moves with the clock, so it may drop items without a write
+    let etag = server.get("/api/v1/content?since=7d").await.header("etag");
+    server
+        .get("/api/v1/content?since=7d")
+        .add_header("if-none-match", etag)
+        .await
+        .assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_errors_are_not_tagged() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/content?since=not_a_date").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.maybe_header("etag").is_none());
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub mod benchmarks;
+pub mod etag;
 pub mod properties;
 pub mod simple;
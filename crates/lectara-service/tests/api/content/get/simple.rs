@@ -44,6 +44,8 @@ async fn test_invalid_query_parameters() -> Result<()> {
     // Invalid datetime
     let response = server.get("/api/v1/content?since=not_a_date").await;
     response.assert_status(StatusCode::BAD_REQUEST);
+    let error: Value = response.json();
+    assert!(error["error"].as_str().unwrap().contains("since"));
 
     Ok(())
 }
@@ -121,5 +123,237 @@ async fn test_date_range_filtering() -> Result<()> {
     let returned_items = json_response["items"].as_array().unwrap();
     assert_eq!(returned_items.len(), 1); // Only Item 2
 
+    // Dates stand for midnight UTC, and unix epoch seconds work too
+    let response = server
+        .get("/api/v1/content?since=2024-01-02&until=1704326400")
+        .await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    let returned_items = json_response["items"].as_array().unwrap();
+    assert_eq!(returned_items.len(), 2); // Items 2 and 3
+
+    // As do times relative to now
+    let response = server.get("/api/v1/content?since=7d").await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    assert_eq!(json_response["total"], 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_count_content_with_filters() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let items = vec![
+        ("2024-01-01T10:00:00Z", "https://example.com/item1"),
+        ("2024-01-02T10:00:00Z", "https://example.com/item2"),
+        ("2024-01-03T10:00:00Z", "https://example.com/item3"),
+    ];
+
+    for (timestamp, url) in &items {
+        let response = server
+            .post("/api/v1/content")
+            .json(&json!({ "url": url }))
+            .await;
+        response.assert_status_ok();
+        let item_id = response.json::<Value>()["id"].as_u64().unwrap() as i32;
+
+        let mut conn = db.lock().unwrap();
+        let dt = DateTime::parse_from_rfc3339(timestamp).unwrap().naive_utc();
+        test_utils::update_content_item_timestamp(&mut conn, item_id, dt);
+    }
+
+    let response = server.get("/api/v1/content/count").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "total": 3 }));
+
+    let response = server
+        .get("/api/v1/content/count?since=2024-01-02T00:00:00Z")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "total": 2 }));
+
+    let response = server
+        .get("/api/v1/content/count?until=2023-12-31T00:00:00Z")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "total": 0 }));
+
+    let response = server.get("/api/v1/content/count?since=not_a_date").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_count_content_exists_only() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/content/count?exists=true").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "exists": false }));
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/item" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/api/v1/content/count?exists=true").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "exists": true }));
+
+    let response = server
+        .get("/api/v1/content/count?exists=true&since=2999-01-01T00:00:00Z")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "exists": false }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_content_without_total() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/item" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/api/v1/content?include_total=false").await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    assert_eq!(json_response["items"].as_array().unwrap().len(), 1);
+    assert!(json_response.get("total").is_none());
+
+    let response = server.get("/api/v1/content?include_total=true").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["total"].as_u64().unwrap(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_total_reflects_new_items() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/first" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/api/v1/content").await;
+    assert_eq!(response.json::<Value>()["total"].as_u64().unwrap(), 1);
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/second" }))
+        .await
+        .assert_status_ok();
+
+    // Cached totals must not survive a write
+    let response = server.get("/api/v1/content").await;
+    assert_eq!(response.json::<Value>()["total"].as_u64().unwrap(), 2);
+
+    let response = server.get("/api/v1/content/count").await;
+    assert_eq!(response.json::<Value>()["total"].as_u64().unwrap(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_snapshot_pins_pages() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    for (index, timestamp) in ["2024-01-01T10:00:00Z", "2024-01-02T10:00:00Z"]
+        .iter()
+        .enumerate()
+    {
+        let response = server
+            .post("/api/v1/content")
+            .json(&json!({ "url": format!("https://example.com/old{index}") }))
+            .await;
+        let item_id = response.json::<Value>()["id"].as_u64().unwrap() as i32;
+        let mut conn = db.lock().unwrap();
+        let dt = DateTime::parse_from_rfc3339(timestamp).unwrap().naive_utc();
+        test_utils::update_content_item_timestamp(&mut conn, item_id, dt);
+    }
+
+    let response = server.get("/api/v1/content?limit=1").await;
+    response.assert_status_ok();
+    let first_page: Value = response.json();
+    assert_eq!(first_page["items"][0]["url"], "https://example.com/old1");
+    let snapshot_at = first_page["snapshot_at"].as_str().unwrap().to_string();
+    assert!(DateTime::parse_from_rfc3339(&snapshot_at).is_ok());
+
+    // Saved mid-session, after the snapshot
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/new" }))
+        .await;
+    let item_id = response.json::<Value>()["id"].as_u64().unwrap() as i32;
+    {
+        let mut conn = db.lock().unwrap();
+        let dt = DateTime::parse_from_rfc3339("2999-01-01T00:00:00Z")
+            .unwrap()
+            .naive_utc();
+        test_utils::update_content_item_timestamp(&mut conn, item_id, dt);
+    }
+
+    let response = server
+        .get("/api/v1/content")
+        .add_query_param("limit", 1)
+        .add_query_param("offset", 1)
+        .add_query_param("snapshot_at", &snapshot_at)
+        .await;
+    response.assert_status_ok();
+    let second_page: Value = response.json();
+    assert_eq!(second_page["items"][0]["url"], "https://example.com/old0");
+    assert_eq!(second_page["total"].as_u64().unwrap(), 2);
+    assert_eq!(
+        DateTime::parse_from_rfc3339(second_page["snapshot_at"].as_str().unwrap()).unwrap(),
+        DateTime::parse_from_rfc3339(&snapshot_at).unwrap()
+    );
+
+    // An earlier until still applies within the snapshot
+    let response = server
+        .get("/api/v1/content")
+        .add_query_param("until", "2024-01-01T12:00:00Z")
+        .add_query_param("snapshot_at", &snapshot_at)
+        .await;
+    assert_eq!(response.json::<Value>()["total"].as_u64().unwrap(), 1);
+
+    let response = server.get("/api/v1/content?snapshot_at=soon").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_content_word_count() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/words",
+            "body": "<p>Three <em>short</em> words</p><style>p { margin: 0 }</style>"
+        }))
+        .await;
+    let id = response.json::<Value>()["id"].as_i64().unwrap();
+
+    let response = server.get(&format!("/api/v1/content/{id}")).await;
+    response.assert_status_ok();
+    let json_response: Value = response.json();
+    assert_eq!(json_response["word_count"], 3);
+    assert!(json_response.get("text").is_none());
+
     Ok(())
 }
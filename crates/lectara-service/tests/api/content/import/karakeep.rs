@@ -0,0 +1,81 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_import_karakeep_export() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let export = json!({ "bookmarks": [
+        {
+            "createdAt": 1704153600,
+            "title": "Post",
+            "tags": ["rust"],
+            "content": { "type": "link", "url": "https://blog.example.com/post" },
+            "note": "Read later",
+            "archived": true
+        },
+        {
+            "createdAt": 1704153700,
+            "title": null,
+            "tags": [],
+            "content": { "type": "link", "url": "https://example.com/other" },
+            "note": null
+        },
+        {
+            "createdAt": 1704153800,
+            "title": null,
+            "tags": [],
+            "content": { "type": "text", "text": "A thought" },
+            "note": null
+        }
+    ]});
+
+    let response: Value = server
+        .post("/api/v1/content/import/karakeep")
+        .text(export.to_string())
+        .await
+        .json();
+    let statuses: Vec<&str> = response["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["created", "created", "invalid"]);
+    assert_eq!(
+        response["items"][2]["error"],
+        "Only link bookmarks can be imported"
+    );
+
+    {
+        let mut conn = db.lock().unwrap();
+        let post = test_utils::get_content_item_by_url(&mut conn, "https://blog.example.com/post")
+            .unwrap();
+        assert_eq!(post.title.as_deref(), Some("Post"));
+        assert_eq!(post.created_at.and_utc().timestamp(), 1704153600);
+        assert!(post.archived_at.is_some());
+        assert_eq!(post.source.as_deref(), Some("import:karakeep"));
+
+        let other =
+            test_utils::get_content_item_by_url(&mut conn, "https://example.com/other").unwrap();
+        assert!(other.archived_at.is_none());
+        assert_eq!(test_utils::count_content_items(&mut conn), 2);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_rejects_other_json() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .post("/api/v1/content/import/karakeep")
+        .text(r#"{"items": []}"#)
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
@@ -0,0 +1,2 @@
+pub mod karakeep;
+pub mod rss;
@@ -0,0 +1,267 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Stub Cast</title>
+    <item>
+      <title>Episode 1</title>
+      <link>https://podcast.example.com/episodes/1</link>
+      <itunes:author>Ada Lovelace</itunes:author>
+      <enclosure url="https://cdn.example.com/ep1.mp3" type="audio/mpeg"/>
+      <itunes:duration>12:05</itunes:duration>
+    </item>
+    <item>
+      <title>Episode 2</title>
+      <link>https://podcast.example.com/episodes/2</link>
+    </item>
+    <item>
+      <title>Local only</title>
+      <link>http://localhost/episodes/3</link>
+    </item>
+  </channel>
+</rss>"#;
+
+#[tokio::test]
+async fn test_import_rss_feed() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    // Saved earlier with a different title, so the feed item conflicts
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://podcast.example.com/episodes/2", "title": "Old" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/api/v1/content/import/rss").text(FEED).await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    let items = json_response["items"].as_array().unwrap();
+    let statuses: Vec<&str> = items
+        .iter()
+        .map(|item| item["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["created", "conflict", "invalid"]);
+    assert!(items[0]["id"].is_number());
+    assert!(items[2]["error"].is_string());
+
+    {
+        let mut conn = db.lock().unwrap();
+        let episode = test_utils::get_content_item_by_url(
+            &mut conn,
+            "https://podcast.example.com/episodes/1",
+        )
+        .unwrap();
+        assert_eq!(episode.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(
+            episode.enclosure_url.as_deref(),
+            Some("https://cdn.example.com/ep1.mp3")
+        );
+        assert_eq!(episode.duration_seconds, Some(725));
+        assert_eq!(episode.source.as_deref(), Some("import:rss"));
+        assert_eq!(test_utils::count_content_items(&mut conn), 2);
+    }
+
+    // Importing the same feed again is idempotent for the created item
+    let again: Value = server
+        .post("/api/v1/content/import/rss")
+        .text(FEED)
+        .await
+        .json();
+    assert_eq!(again["items"][0]["status"], "existing");
+    assert_eq!(again["items"][0]["id"], items[0]["id"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_rejects_malformed_feed() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content/import/rss")
+        .text("<rss><channel><item></channel></rss>")
+        .await;
+    response.assert_status_bad_request();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_rss_dry_run() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://podcast.example.com/episodes/2", "title": "Old" }))
+        .await
+        .assert_status_ok();
+
+    // Repeats Episode 1 once as it is and once retitled
+    let feed = FEED.replace(
+        "  </channel>",
+        r#"    <item>
+      <title>Episode 1</title>
+      <link>https://podcast.example.com/episodes/1</link>
+      <itunes:author>Ada Lovelace</itunes:author>
+      <enclosure url="https://cdn.example.com/ep1.mp3" type="audio/mpeg"/>
+      <itunes:duration>12:05</itunes:duration>
+    </item>
+    <item>
+      <title>Episode One</title>
+      <link>https://podcast.example.com/episodes/1</link>
+    </item>
+  </channel>"#,
+    );
+
+    let preview: Value = server
+        .post("/api/v1/content/import/rss")
+        .add_query_param("dry_run", "true")
+        .text(&feed)
+        .await
+        .json();
+    assert_eq!(preview["dry_run"], true);
+    let statuses: Vec<&str> = preview["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        statuses,
+        vec!["created", "conflict", "invalid", "existing", "conflict"]
+    );
+    assert!(preview["items"][0]["id"].is_null());
+    assert!(preview["items"][1]["error"].is_string());
+    {
+        let mut conn = db.lock().unwrap();
+        assert_eq!(test_utils::count_content_items(&mut conn), 1);
+    }
+
+    // The preview matches what the import then does
+    let imported: Value = server
+        .post("/api/v1/content/import/rss")
+        .text(&feed)
+        .await
+        .json();
+    assert_eq!(imported["dry_run"], false);
+    for (previewed, imported) in preview["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(imported["items"].as_array().unwrap())
+    {
+        assert_eq!(previewed["status"], imported["status"]);
+    }
+
+    // Previewing again matches the saved items by id
+    let again: Value = server
+        .post("/api/v1/content/import/rss")
+        .add_query_param("dry_run", "true")
+        .text(&feed)
+        .await
+        .json();
+    assert_eq!(again["items"][0]["status"], "existing");
+    assert_eq!(again["items"][0]["id"], imported["items"][0]["id"]);
+
+    server
+        .post("/api/v1/content/import/rss")
+        .add_query_param("dry_run", "true")
+        .add_query_param("background", "true")
+        .text(&feed)
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_rss_feed_in_background() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://podcast.example.com/episodes/2", "title": "Old" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/v1/content/import/rss?background=true")
+        .text(FEED)
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let started: Value = response.json();
+    let status_url = started["status_url"].as_str().unwrap().to_string();
+    assert_eq!(status_url, format!("/api/v1/jobs/{}", started["job_id"]));
+
+    let mut job = Value::Null;
+    for _ in 0..100 {
+        job = server.get(&status_url).await.json();
+        if job["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(job["status"], "finished");
+    assert_eq!(job["kind"], "import:rss");
+    assert_eq!(job["processed"], 3);
+    assert_eq!(job["total"], 3);
+    assert_eq!(
+        job["outcomes"],
+        json!({ "created": 1, "conflict": 1, "invalid": 1 })
+    );
+    assert_eq!(job["error_count"], 2);
+    assert_eq!(job["errors"][1]["item"], "http://localhost/episodes/3");
+    assert!(job["finished_at"].is_string());
+
+    {
+        let mut conn = db.lock().unwrap();
+        assert_eq!(test_utils::count_content_items(&mut conn), 2);
+    }
+
+    // Cancelling a job that ended leaves it as it was
+    let response = server.post(&format!("{status_url}/cancel")).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["status"], "finished");
+
+    // Left just after the job ends
+    let mut message = Value::Null;
+    for _ in 0..100 {
+        let notifications: Value = server.get("/api/v1/notifications").await.json();
+        message = notifications["items"][0]["message"].clone();
+        if !message.is_null() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(
+        message
+            .as_str()
+            .unwrap()
+            .contains("finished after 3 of 3 items")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_job() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .get("/api/v1/jobs/42")
+        .await
+        .assert_status_not_found();
+    server
+        .post("/api/v1/jobs/42/cancel")
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+async fn add_item(server: &axum_test::TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_lookup_by_ids() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let first = add_item(&server, "https://example.com/first").await;
+    let second = add_item(&server, "https://example.com/second").await;
+
+    let response = server
+        .post("/api/v1/content/lookup")
+        .json(&json!({ "ids": [second, 999, first] }))
+        .await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    let items = json_response["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["id"].as_u64().unwrap(), first);
+    assert_eq!(items[1]["id"].as_u64().unwrap(), second);
+    assert!(items[0].get("body").is_none());
+    assert_eq!(json_response["missing_ids"], json!([999]));
+    assert_eq!(json_response["urls"], json!([]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lookup_by_urls_normalizes_input() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/article").await;
+
+    let response = server
+        .post("/api/v1/content/lookup")
+        .json(&json!({
+            "urls": [
+                "https://EXAMPLE.com/article/#comments",
+                "https://example.com/unsaved",
+                "mailto:someone@example.com"
+            ]
+        }))
+        .await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    assert_eq!(json_response["items"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        json_response["urls"],
+        json!([
+            { "url": "https://EXAMPLE.com/article/#comments", "id": id },
+            { "url": "https://example.com/unsaved", "id": null },
+            { "url": "mailto:someone@example.com", "id": null }
+        ])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lookup_deduplicates_items() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/article").await;
+
+    let response = server
+        .post("/api/v1/content/lookup")
+        .json(&json!({ "ids": [id], "urls": ["https://example.com/article"] }))
+        .await;
+    response.assert_status_ok();
+
+    let json_response: Value = response.json();
+    assert_eq!(json_response["items"].as_array().unwrap().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lookup_rejects_oversized_batches() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let ids: Vec<u64> = (1..=101).collect();
+    let response = server
+        .post("/api/v1/content/lookup")
+        .json(&json!({ "ids": ids }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -1,2 +1,15 @@
+pub mod bulk;
+pub mod check_update;
+pub mod comments;
+pub mod edit;
+pub mod events;
 pub mod get;
+pub mod import;
+pub mod lookup;
+pub mod pins;
 pub mod post;
+pub mod send;
+pub mod share;
+pub mod snapshots;
+pub mod source;
+pub mod suggested_tags;
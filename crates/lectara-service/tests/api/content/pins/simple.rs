@@ -0,0 +1,131 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use lectara_service::routes::api::v1::MAX_PINNED_ITEMS;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer, url: &str) -> i64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap()
+}
+
+fn ids(response: &Value) -> Vec<i64> {
+    response["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pinned_items_lead_the_list_in_order() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let first = add_item(&server, "https://example.com/first").await;
+    let second = add_item(&server, "https://example.com/second").await;
+    let third = add_item(&server, "https://example.com/third").await;
+
+    server
+        .post(&format!("/api/v1/content/{first}/pin"))
+        .await
+        .assert_status_ok();
+    let pinned: Value = server
+        .post(&format!("/api/v1/content/{second}/pin"))
+        .await
+        .json();
+    assert_eq!(ids(&pinned), [first, second]);
+
+    // Pinning again leaves the order alone
+    let pinned: Value = server
+        .post(&format!("/api/v1/content/{first}/pin"))
+        .await
+        .json();
+    assert_eq!(ids(&pinned), [first, second]);
+
+    let listed: Value = server.get("/api/v1/content").await.json();
+    assert_eq!(ids(&listed), [first, second, third]);
+    assert_eq!(listed["items"][0]["pinned_position"], 0);
+    assert_eq!(listed["items"][2]["pinned_position"], Value::Null);
+
+    let reordered: Value = server
+        .put("/api/v1/content/pins")
+        .json(&json!({ "ids": [second, first] }))
+        .await
+        .json();
+    assert_eq!(ids(&reordered), [second, first]);
+    let listed: Value = server.get("/api/v1/content").await.json();
+    assert_eq!(ids(&listed), [second, first, third]);
+
+    let pinned: Value = server
+        .delete(&format!("/api/v1/content/{second}/pin"))
+        .await
+        .json();
+    assert_eq!(ids(&pinned), [first]);
+    let item: Value = server.get(&format!("/api/v1/content/{first}")).await.json();
+    assert_eq!(item["pinned_position"], 0);
+    let listed: Value = server.get("/api/v1/content/pins").await.json();
+    assert_eq!(ids(&listed), [first]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reorder_requires_every_pinned_item_once() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let first = add_item(&server, "https://example.com/first").await;
+    let second = add_item(&server, "https://example.com/second").await;
+    for id in [first, second] {
+        server
+            .post(&format!("/api/v1/content/{id}/pin"))
+            .await
+            .assert_status_ok();
+    }
+
+    for ids in [
+        json!([first]),
+        json!([first, first]),
+        json!([first, second, 99]),
+    ] {
+        server
+            .put("/api/v1/content/pins")
+            .json(&json!({ "ids": ids }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pin_limit_and_missing_items() -> Result<()> {
+    let (server, _db) = create_test_server();
+    for n in 0..MAX_PINNED_ITEMS {
+        let id = add_item(&server, &format!("https://example.com/{n}")).await;
+        server
+            .post(&format!("/api/v1/content/{id}/pin"))
+            .await
+            .assert_status_ok();
+    }
+
+    let extra = add_item(&server, "https://example.com/extra").await;
+    server
+        .post(&format!("/api/v1/content/{extra}/pin"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    server
+        .post("/api/v1/content/999/pin")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .delete("/api/v1/content/999/pin")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
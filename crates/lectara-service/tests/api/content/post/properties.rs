@@ -1,6 +1,7 @@
-use crate::common::server_utils::create_test_server;
+use crate::common::server_utils::{create_test_server, create_test_server_with_validation};
 use axum::http::StatusCode;
 use lectara_service::models::NewContentItem;
+use lectara_service::validation::{MetadataComparison, ValidationContext, clean_text};
 use proptest::prelude::*;
 use serde_json::{Value, json};
 
@@ -55,10 +56,39 @@ prop_compose! {
             title: title.filter(|s| !s.trim().is_empty()),
             author: author.filter(|s| !s.trim().is_empty()),
             body: body.filter(|s| !s.trim().is_empty()),
+            enclosure_url: None,
+            duration_seconds: None,
+            source: None,
+            user_agent: None,
+            client_version: None,
+            created_at: None,
+            comparison: MetadataComparison::default(),
         }
     }
 }
 
+// Generate a name and the same name retyped with other case and spacing
+prop_compose! {
+    fn arb_retyped_name()(
+        words in prop::collection::vec("[a-zA-Z0-9]{1,8}", 1..4),
+        upper in prop::bool::ANY,
+        leading in " {0,2}",
+        gaps in prop::collection::vec("( {1,3}|\t)", 3),
+        trailing in " {0,2}",
+    ) -> (String, String) {
+        let retyped = words
+            .iter()
+            .map(|word| if upper { word.to_uppercase() } else { word.to_lowercase() })
+            .zip(gaps.iter().map(String::as_str).chain(std::iter::repeat("")))
+            .map(|(word, gap)| format!("{word}{gap}"))
+            .collect::<String>();
+        (
+            words.join(" "),
+            format!("{leading}{}{trailing}", retyped.trim_end()),
+        )
+    }
+}
+
 #[cfg(test)]
 mod post_properties {
     use super::*;
@@ -213,5 +243,66 @@ mod post_properties {
                 Ok(())
             }).expect("Async proptest should not fail")
         }
+
+        #[test]
+        fn lenient_comparison_property(
+            url in arb_normalizable_url(),
+            (title, retyped_title) in arb_retyped_name(),
+            (author, retyped_author) in arb_retyped_name(),
+        ) {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let (server, _db) = create_test_server_with_validation(ValidationContext {
+                    comparison: MetadataComparison::LENIENT,
+                    ..Default::default()
+                });
+
+                let payload1 = json!({ "url": url, "title": title, "author": author });
+                let payload2 = json!({ "url": url, "title": retyped_title, "author": retyped_author });
+
+                let response1 = server.post("/api/v1/content").json(&payload1).await;
+                if response1.status_code() == StatusCode::OK {
+                    let response2 = server.post("/api/v1/content").json(&payload2).await;
+                    prop_assert_eq!(
+                        response2.status_code(),
+                        StatusCode::OK,
+                        "Retyped metadata should match under the lenient comparison"
+                    );
+
+                    let body1: Value = response1.json();
+                    let body2: Value = response2.json();
+                    prop_assert_eq!(&body1["id"], &body2["id"]);
+                }
+                Ok(())
+            }).expect("Async proptest should not fail")
+        }
+
+        #[test]
+        fn strict_comparison_property(
+            url in arb_normalizable_url(),
+            (title, retyped_title) in arb_retyped_name(),
+        ) {
+            // Cleaning turns tabs in titles into spaces before comparing
+            prop_assume!(clean_text(&title, false) != clean_text(&retyped_title, false));
+
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let (server, _db) = create_test_server();
+
+                let payload1 = json!({ "url": url, "title": title });
+                let payload2 = json!({ "url": url, "title": retyped_title });
+
+                let response1 = server.post("/api/v1/content").json(&payload1).await;
+                if response1.status_code() == StatusCode::OK {
+                    let response2 = server.post("/api/v1/content").json(&payload2).await;
+                    prop_assert_eq!(
+                        response2.status_code(),
+                        StatusCode::CONFLICT,
+                        "Retyped metadata should conflict under the default comparison"
+                    );
+                }
+                Ok(())
+            }).expect("Async proptest should not fail")
+        }
     }
 }
@@ -1,7 +1,8 @@
-use crate::common::server_utils::create_test_server;
+use crate::common::server_utils::{create_test_server, create_test_server_with_validation};
 use crate::common::test_utils;
 use anyhow::Result;
 use axum::http::StatusCode;
+use lectara_service::validation::{ExtraScheme, MetadataLimits, ValidationContext};
 use serde_json::{Value, json};
 
 #[tokio::test]
@@ -237,6 +238,101 @@ async fn test_duplicate_url_handling() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_conflict_includes_existing_item_and_diff() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/conflict",
+            "title": "Stored Title",
+            "author": "Same Author",
+            "body": "Stored body"
+        }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].clone();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/conflict",
+            "title": "New Title",
+            "author": "Same Author"
+        }))
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+
+    let conflict: Value = response.json();
+    assert_eq!(
+        conflict["error"],
+        "URL already exists with different metadata"
+    );
+    assert_eq!(conflict["existing"]["id"], id);
+    assert_eq!(conflict["existing"]["title"], "Stored Title");
+    assert_eq!(conflict["existing"]["body"], "Stored body");
+    assert_eq!(
+        conflict["differences"],
+        json!([
+            { "field": "title", "stored": "Stored Title", "submitted": "New Title" },
+            { "field": "body", "stored": "Stored body", "submitted": null },
+        ])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_metadata_is_cleaned_and_length_checked() -> Result<()> {
+    let (server, db) = create_test_server_with_validation(ValidationContext {
+        limits: MetadataLimits {
+            title: 10,
+            author: 10,
+            body: 100,
+        },
+        ..Default::default()
+    });
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/cleaned",
+            "title": "Cafe\u{301}\u{0}\nMenu",
+            "body": "Line one\nLine two\u{7}"
+        }))
+        .await;
+    response.assert_status_ok();
+    {
+        let mut conn = db.lock().unwrap();
+        let item = test_utils::get_content_item_by_url(&mut conn, "https://example.com/cleaned")
+            .expect("Content item should exist in database");
+        assert_eq!(item.title.as_deref(), Some("Café Menu"));
+        assert_eq!(item.body.as_deref(), Some("Line one\nLine two"));
+    }
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://example.com/too-long",
+            "title": "A title that is too long",
+            "author": "An author who is too long"
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let error: Value = response.json();
+    assert_eq!(
+        error["fields"],
+        json!([
+            { "field": "title", "reason": "must be at most 10 characters" },
+            { "field": "author", "reason": "must be at most 10 characters" },
+        ])
+    );
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 1);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_true_idempotent_behavior() -> Result<()> {
     let (server, db) = create_test_server();
@@ -341,6 +437,8 @@ async fn test_invalid_url_rejection() -> Result<()> {
 
         // Should reject with 400 Bad Request
         response.assert_status(StatusCode::BAD_REQUEST);
+        let error: Value = response.json();
+        assert!(error["error"].is_string(), "{description}: {error}");
     }
     Ok(())
 }
@@ -416,3 +514,175 @@ async fn test_url_with_query_parameters() -> Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn test_host_lists_apply_to_new_content() -> Result<()> {
+    let (server, _db) = create_test_server_with_validation(ValidationContext {
+        allowed_hosts: vec!["localhost".to_string()],
+        denied_hosts: vec!["internal.corp".to_string()],
+        ..ValidationContext::default()
+    });
+
+    let allowed = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "http://localhost:8000/draft" }))
+        .await;
+    allowed.assert_status_ok();
+
+    let denied = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://wiki.internal.corp/page" }))
+        .await;
+    denied.assert_status(StatusCode::BAD_REQUEST);
+    let json: Value = denied.json();
+    assert_eq!(json["error"], "Host not allowed: wiki.internal.corp");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_local_urls_rejected_unless_enabled() -> Result<()> {
+    let payload = json!({ "url": "http://127.0.0.1:8080/article" });
+
+    let (server, _db) = create_test_server();
+    let response = server.post("/api/v1/content").json(&payload).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let (server, _db) = create_test_server_with_validation(ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    });
+    let response = server.post("/api/v1/content").json(&payload).await;
+    response.assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_doi_identifier_deduplicates_with_https_url() -> Result<()> {
+    let (server, _db) = create_test_server_with_validation(ValidationContext {
+        extra_schemes: vec![ExtraScheme::Doi],
+        ..ValidationContext::default()
+    });
+
+    let by_identifier = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "doi:10.1000/182" }))
+        .await;
+    by_identifier.assert_status_ok();
+
+    let by_url = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://doi.org/10.1000/182" }))
+        .await;
+    by_url.assert_status_ok();
+
+    let first: Value = by_identifier.json();
+    let second: Value = by_url.json();
+    assert_eq!(first["id"], second["id"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_podcast_episode() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let payload = json!({
+        "url": "https://podcast.example.com/episodes/1",
+        "title": "Episode 1",
+        "enclosure_url": "https://cdn.example.com/ep1.mp3",
+        "duration_seconds": 3723
+    });
+    server
+        .post("/api/v1/content")
+        .json(&payload)
+        .await
+        .assert_status_ok();
+
+    {
+        let mut conn = db.lock().unwrap();
+        let item = test_utils::get_content_item_by_url(
+            &mut conn,
+            "https://podcast.example.com/episodes/1",
+        )
+        .unwrap();
+        assert_eq!(
+            item.enclosure_url.as_deref(),
+            Some("https://cdn.example.com/ep1.mp3")
+        );
+        assert_eq!(item.duration_seconds, Some(3723));
+    }
+
+    // Re-saving without the enclosure changes the stored metadata
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://podcast.example.com/episodes/1",
+            "title": "Episode 1"
+        }))
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_podcast_episode_validation() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let local_enclosure = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://podcast.example.com/episodes/1",
+            "enclosure_url": "http://localhost/ep1.mp3"
+        }))
+        .await;
+    local_enclosure.assert_status_bad_request();
+
+    let duration_only = server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://podcast.example.com/episodes/1",
+            "duration_seconds": 60
+        }))
+        .await;
+    duration_only.assert_status_bad_request();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_info_is_recorded() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .add_header("User-Agent", "Lectara-Extension/1.4.2 (Firefox)")
+        .add_header("X-Lectara-Client-Version", " 1.4.2 ")
+        .json(&json!({ "url": "https://example.com/clipped" }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_i64().unwrap();
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["user_agent"], "Lectara-Extension/1.4.2 (Firefox)");
+    assert_eq!(item["client_version"], "1.4.2");
+
+    // Saving again from another client keeps the first client's details
+    server
+        .post("/api/v1/content")
+        .add_header("User-Agent", "curl/8.0")
+        .json(&json!({ "url": "https://example.com/clipped" }))
+        .await
+        .assert_status_ok();
+    let mut conn = db.lock().unwrap();
+    let stored =
+        test_utils::get_content_item_by_url(&mut conn, "https://example.com/clipped").unwrap();
+    assert_eq!(
+        stored.user_agent.as_deref(),
+        Some("Lectara-Extension/1.4.2 (Firefox)")
+    );
+
+    Ok(())
+}
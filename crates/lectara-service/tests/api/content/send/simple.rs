@@ -0,0 +1,114 @@
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use lectara_service::delivery::DeviceMailer;
+use lettre::{AsyncSmtpTransport, Tokio1Executor};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+async fn add_item(server: &TestServer, body: Option<&str>) -> i64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/engines", "title": "Engines", "body": body }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap()
+}
+
+/// A mailer whose SMTP server refuses connections
+fn unreachable_mailer() -> DeviceMailer {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+        .port(port)
+        .build();
+    DeviceMailer::new(
+        transport,
+        "lectara@example.com".parse().unwrap(),
+        "reader@kindle.com".parse().unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_send_without_mailer_is_rejected() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, Some("Some text.")).await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/send?target=kindle"))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_checks_target_and_item() -> Result<()> {
+    let (server, _db) =
+        create_test_server_with_state(|state| state.with_device_mailer(Some(unreachable_mailer())));
+    let id = add_item(&server, None).await;
+
+    server
+        .post(&format!("/api/v1/content/{id}/send?target=kobo"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .post("/api/v1/content/999/send?target=kindle")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Nothing to put in the book
+    let response = server
+        .post(&format!("/api/v1/content/{id}/send?target=kindle"))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_send_is_reported_on_the_job() -> Result<()> {
+    let (server, _db) =
+        create_test_server_with_state(|state| state.with_device_mailer(Some(unreachable_mailer())));
+    let id = add_item(&server, Some("First paragraph.\n\nSecond paragraph.")).await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/send?target=kindle"))
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let status_url = response.json::<Value>()["status_url"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let mut job = Value::Null;
+    for _ in 0..100 {
+        job = server.get(&status_url).await.json();
+        if job["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(job["status"], "finished");
+    assert_eq!(job["kind"], "send:kindle");
+    assert_eq!(job["outcomes"], json!({ "failed": 1 }));
+    assert_eq!(job["errors"][0]["item"], "https://example.com/engines");
+
+    let mut notification = Value::Null;
+    for _ in 0..100 {
+        let notifications: Value = server.get("/api/v1/notifications").await.json();
+        notification = notifications["items"][0].clone();
+        if !notification.is_null() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(notification["kind"], "send-failed");
+    assert_eq!(notification["content_id"], id);
+
+    Ok(())
+}
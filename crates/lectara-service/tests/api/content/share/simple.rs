@@ -0,0 +1,160 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+async fn add_item(server: &axum_test::TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "title": "Shared Article" }))
+        .await;
+    response.assert_status_ok();
+    let json_response: Value = response.json();
+    json_response["id"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_create_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+
+    let json_response: Value = response.json();
+    let token = json_response["token"].as_str().unwrap();
+    assert_eq!(token.len(), 32);
+    assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_eq!(json_response["content_id"].as_u64().unwrap(), id);
+    assert_eq!(
+        json_response["share_url"].as_str().unwrap(),
+        format!("/web/share/{token}")
+    );
+    assert!(json_response["expires_at"].is_null());
+    assert!(json_response["revoked_at"].is_null());
+    assert_eq!(json_response["protected"], false);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_protected_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({ "passphrase": "short" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({ "passphrase": "long enough" }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json_response: Value = response.json();
+    assert_eq!(json_response["protected"], true);
+    assert!(json_response.get("passphrase_hash").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_share_tokens_are_unique() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let first: Value = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({}))
+        .await
+        .json();
+    let second: Value = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({}))
+        .await
+        .json();
+
+    assert_ne!(first["token"], second["token"]);
+
+    let response = server.get(&format!("/api/v1/content/{id}/share")).await;
+    response.assert_status_ok();
+    let links: Value = response.json();
+    assert_eq!(links.as_array().unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_share_link_for_missing_item() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content/999/share")
+        .json(&json!({}))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_share_link_invalid_expiry() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({ "expires_at": "not_a_date" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({ "expires_at": "2000-01-01T00:00:00Z" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_revoke_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(&server, "https://example.com/shared").await;
+
+    let created: Value = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&json!({}))
+        .await
+        .json();
+    let token = created["token"].as_str().unwrap();
+
+    let response = server
+        .delete(&format!("/api/v1/content/{id}/share/{token}"))
+        .await;
+    response.assert_status(StatusCode::NO_CONTENT);
+
+    let links: Value = server
+        .get(&format!("/api/v1/content/{id}/share"))
+        .await
+        .json();
+    assert!(links[0]["revoked_at"].is_string());
+
+    // Unknown tokens and tokens belonging to other items are not found
+    let response = server
+        .delete(&format!("/api/v1/content/{id}/share/unknown"))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    let response = server
+        .delete(&format!("/api/v1/content/999/share/{token}"))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
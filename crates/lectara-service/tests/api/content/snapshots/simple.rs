@@ -0,0 +1,221 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::{
+    Router,
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing::get,
+};
+use axum_test::TestServer;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use lectara_service::schema::{archive_payloads, archive_snapshots};
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_validation;
+
+const PAGE: &str = "<html><body><p>Archived</p></body></html>";
+
+/// Serves `PAGE` at `/page`, and 404 at `/gone`
+async fn spawn_site() -> String {
+    let app = Router::new()
+        .route(
+            "/page",
+            get(|| async {
+                (
+                    [
+                        (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                        (header::ETAG, "\"v1\""),
+                    ],
+                    PAGE,
+                )
+                    .into_response()
+            }),
+        )
+        .route("/gone", get(|| async { StatusCode::NOT_FOUND }));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn create_server() -> (TestServer, Arc<Mutex<SqliteConnection>>) {
+    create_test_server_with_validation(ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    })
+}
+
+async fn add_content(server: &TestServer, url: &str) -> i64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap()
+}
+
+async fn capture(server: &TestServer, id: i64) -> Value {
+    let response = server
+        .post(&format!("/api/v1/content/{id}/snapshots"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+fn payload_refs(db: &Mutex<SqliteConnection>) -> Vec<i32> {
+    let mut conn = db.lock().unwrap();
+    archive_payloads::table
+        .select(archive_payloads::ref_count)
+        .load(&mut *conn)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_capture_and_download_warc() -> Result<()> {
+    let site = spawn_site().await;
+    let (server, _db) = create_server();
+    let id = add_content(&server, &format!("{site}/page")).await;
+
+    let snapshot = capture(&server, id).await;
+    assert_eq!(snapshot["content_id"], id);
+    assert_eq!(snapshot["url"], format!("{site}/page"));
+    assert_eq!(snapshot["content_type"], "text/html");
+    assert_eq!(snapshot["size"], PAGE.len());
+    assert_eq!(snapshot["digest"].as_str().unwrap().len(), 64);
+
+    let warc_url = snapshot["warc_url"].as_str().unwrap();
+    let response = server.get(warc_url).await;
+    response.assert_status_ok();
+    assert_eq!(response.header(header::CONTENT_TYPE), "application/warc");
+    assert_eq!(
+        response.header(header::CONTENT_DISPOSITION),
+        format!(
+            "attachment; filename=\"lectara-snapshot-{}.warc\"",
+            snapshot["id"]
+        )
+    );
+
+    let warc = response.text();
+    assert!(warc.starts_with("WARC/1.1\r\nWARC-Type: warcinfo\r\n"));
+    assert!(warc.contains("WARC-Type: response\r\n"));
+    assert!(warc.contains(&format!("WARC-Target-URI: {site}/page\r\n")));
+    assert!(warc.contains(&format!(
+        "WARC-Payload-Digest: sha256:{}\r\n",
+        snapshot["digest"].as_str().unwrap()
+    )));
+    assert!(warc.contains("HTTP/1.1 200 OK\r\n"));
+    assert!(warc.contains("etag: \"v1\"\r\n"));
+    assert!(warc.ends_with(&format!(
+        "Content-Length: {}\r\n\r\n{PAGE}\r\n\r\n",
+        PAGE.len()
+    )));
+
+    let fetched: Value = server
+        .get(&format!("/api/v1/snapshots/{}", snapshot["id"]))
+        .await
+        .json();
+    assert_eq!(fetched, snapshot);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unchanged_pages_share_their_payload() -> Result<()> {
+    let site = spawn_site().await;
+    let (server, db) = create_server();
+    let id = add_content(&server, &format!("{site}/page")).await;
+
+    let first = capture(&server, id).await;
+    let second = capture(&server, id).await;
+    assert_eq!(first["digest"], second["digest"]);
+    assert_eq!(payload_refs(&db), [2]);
+
+    let listed: Value = server
+        .get(&format!("/api/v1/content/{id}/snapshots"))
+        .await
+        .json();
+    let ids: Vec<&Value> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| &s["id"])
+        .collect();
+    assert_eq!(ids, [&second["id"], &first["id"]]);
+
+    server
+        .delete(&format!("/api/v1/snapshots/{}", first["id"]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(payload_refs(&db), [1]);
+    server
+        .delete(&format!("/api/v1/snapshots/{}", first["id"]))
+        .await
+        .assert_status_not_found();
+
+    server
+        .delete(&format!("/api/v1/snapshots/{}", second["id"]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert!(payload_refs(&db).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deleting_item_removes_its_snapshots() -> Result<()> {
+    let site = spawn_site().await;
+    let (server, db) = create_server();
+    let id = add_content(&server, &format!("{site}/page")).await;
+    capture(&server, id).await;
+
+    server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "delete", "ids": [id] }))
+        .await
+        .assert_status_ok();
+
+    let mut conn = db.lock().unwrap();
+    let snapshots: i64 = archive_snapshots::table.count().get_result(&mut *conn)?;
+    assert_eq!(snapshots, 0);
+    let payloads: i64 = archive_payloads::table.count().get_result(&mut *conn)?;
+    assert_eq!(payloads, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_capture_errors() -> Result<()> {
+    let site = spawn_site().await;
+    let (server, _db) = create_server();
+    let id = add_content(&server, &format!("{site}/gone")).await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/snapshots"))
+        .await;
+    response.assert_status(StatusCode::BAD_GATEWAY);
+    assert!(
+        response.json::<Value>()["error"]
+            .as_str()
+            .unwrap()
+            .contains("404")
+    );
+
+    server
+        .post("/api/v1/content/999/snapshots")
+        .await
+        .assert_status_not_found();
+    server
+        .get("/api/v1/content/999/snapshots")
+        .await
+        .assert_status_not_found();
+    server
+        .get("/api/v1/snapshots/999/warc")
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
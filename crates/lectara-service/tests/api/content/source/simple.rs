@@ -0,0 +1,92 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils::get_content_item_by_url;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_source_is_recorded_from_field_or_header() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/plain" }))
+        .await
+        .assert_status_ok();
+    server
+        .post("/api/v1/content")
+        .add_header("X-Lectara-Source", "Extension")
+        .json(&json!({ "url": "https://example.com/clipped" }))
+        .await
+        .assert_status_ok();
+    // The field wins over the header
+    server
+        .post("/api/v1/content")
+        .add_header("X-Lectara-Source", "extension")
+        .json(&json!({ "url": "https://example.com/imported", "source": "import:pocket" }))
+        .await
+        .assert_status_ok();
+
+    let mut conn = db.lock().unwrap();
+    let source = |conn: &mut _, url| get_content_item_by_url(conn, url).unwrap().source;
+    assert_eq!(
+        source(&mut conn, "https://example.com/plain").as_deref(),
+        Some("api")
+    );
+    assert_eq!(
+        source(&mut conn, "https://example.com/clipped").as_deref(),
+        Some("extension")
+    );
+    assert_eq!(
+        source(&mut conn, "https://example.com/imported").as_deref(),
+        Some("import:pocket")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_source_is_rejected() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/a", "source": "zapier" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let response = server.get("/api/v1/content?source=import:").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_and_count_filter_by_source() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    for (url, source) in [
+        ("https://example.com/a", "cli"),
+        ("https://example.com/b", "feed"),
+        ("https://example.com/c", "cli"),
+    ] {
+        server
+            .post("/api/v1/content")
+            .json(&json!({ "url": url, "source": source }))
+            .await
+            .assert_status_ok();
+    }
+
+    let response = server.get("/api/v1/content?source=cli").await;
+    response.assert_status_ok();
+    let json_response: Value = response.json();
+    let items = json_response["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|item| item["source"] == "cli"));
+    assert_eq!(json_response["total"], 2);
+
+    let count: Value = server.get("/api/v1/content/count?source=feed").await.json();
+    assert_eq!(count["total"], 1);
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer, item: Value) -> u64 {
+    let response = server.post("/api/v1/content").json(&item).await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn suggested_tags(server: &TestServer, id: u64) -> Vec<String> {
+    let response = server
+        .get(&format!("/api/v1/content/{id}/suggested-tags"))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["id"], id);
+    body["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|suggestion| suggestion["tag"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_suggestions_favour_distinctive_words() -> Result<()> {
+    let (server, _db) = create_test_server();
+    for (index, topic) in ["gardening", "cooking", "travel"].iter().enumerate() {
+        add_item(
+            &server,
+            json!({
+                "url": format!("https://example.com/weekly/{index}"),
+                "body": format!("This weekly newsletter covers {topic}. Newsletter readers enjoy it."),
+            }),
+        )
+        .await;
+    }
+    let id = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/weekly/rust",
+            "body": "This weekly newsletter covers Rust. The borrow checker and Rust \
+                     lifetimes explained; readers enjoy Rust.",
+        }),
+    )
+    .await;
+
+    let tags = suggested_tags(&server, id).await;
+    assert_eq!(tags[0], "rust");
+    assert!(tags.contains(&"borrow".to_string()));
+    // Words every item shares rank below the distinctive ones
+    let position = |tag: &str| tags.iter().position(|t| t == tag).unwrap();
+    assert!(position("newsletter") > position("lifetimes"));
+    // Stop words are never suggested
+    assert!(!tags.contains(&"the".to_string()));
+    assert!(!tags.contains(&"this".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggestions_use_title_without_body() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(
+        &server,
+        json!({ "url": "https://example.com/talk", "title": "Compilers and Café Culture" }),
+    )
+    .await;
+
+    let mut tags = suggested_tags(&server, id).await;
+    tags.sort();
+    assert_eq!(tags, ["cafe", "compilers", "culture"]);
+
+    let untitled = add_item(&server, json!({ "url": "https://example.com/empty" })).await;
+    assert!(suggested_tags(&server, untitled).await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggestions_respect_limit() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let id = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/words",
+            "body": "alpha bravo charlie delta echo foxtrot golf hotel india juliet kilo lima",
+        }),
+    )
+    .await;
+
+    assert_eq!(suggested_tags(&server, id).await.len(), 10);
+    let response = server
+        .get(&format!("/api/v1/content/{id}/suggested-tags"))
+        .add_query_param("limit", 3)
+        .await;
+    assert_eq!(
+        response.json::<Value>()["suggestions"]
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    server
+        .get(&format!("/api/v1/content/{id}/suggested-tags"))
+        .add_query_param("limit", 0)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .get("/api/v1/content/999/suggested-tags")
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
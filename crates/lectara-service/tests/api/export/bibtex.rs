@@ -0,0 +1,107 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils::update_content_item_timestamp;
+use anyhow::Result;
+use diesel::SqliteConnection;
+use lectara_service::models::NewCitation;
+use lectara_service::repositories::{CitationRepository, SqliteCitationRepository};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+async fn add_item(server: &axum_test::TestServer, url: &str) -> i32 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap() as i32
+}
+
+async fn add_citation(db: &Arc<Mutex<SqliteConnection>>, content_id: i32, identifier: &str) {
+    SqliteCitationRepository::new(db.clone())
+        .upsert(&NewCitation {
+            content_id,
+            source: "crossref".to_string(),
+            identifier: identifier.to_string(),
+            title: Some("Stub Paper".to_string()),
+            authors: r#"["Ada Lovelace"]"#.to_string(),
+            published_on: chrono::NaiveDate::from_ymd_opt(1843, 9, 1),
+            container_title: Some("Journal of Stubs".to_string()),
+            abstract_text: None,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_export_bibtex() -> Result<()> {
+    let (server, db) = create_test_server();
+    let paper = add_item(&server, "https://doi.org/10.1000/182").await;
+    add_item(&server, "https://example.com/not-a-paper").await;
+    add_citation(&db, paper, "10.1000/182").await;
+
+    let response = server.get("/api/v1/export/bibtex").await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.header("content-type"),
+        "application/x-bibtex; charset=utf-8"
+    );
+
+    let body = response.text();
+    assert_eq!(body.matches('@').count(), 1);
+    assert!(body.starts_with("@article{lovelace1843stub,\n"));
+    assert!(body.contains("  doi = {10.1000/182},\n"));
+    assert!(body.contains("  url = {https://doi.org/10.1000/182},\n"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_bibtex_empty() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/export/bibtex").await;
+    response.assert_status_ok();
+    assert_eq!(response.text(), "");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_bibtex_since_filter() -> Result<()> {
+    let (server, db) = create_test_server();
+    let old = add_item(&server, "https://doi.org/10.1000/1").await;
+    let new = add_item(&server, "https://doi.org/10.1000/2").await;
+    add_citation(&db, old, "10.1000/1").await;
+    add_citation(&db, new, "10.1000/2").await;
+
+    let old_timestamp = chrono::NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    update_content_item_timestamp(&mut db.lock().unwrap(), old, old_timestamp);
+
+    let response = server
+        .get("/api/v1/export/bibtex")
+        .add_query_param("since", "2024-01-01T00:00:00Z")
+        .await;
+    response.assert_status_ok();
+
+    let body = response.text();
+    assert!(body.contains("doi = {10.1000/2}"));
+    assert!(!body.contains("doi = {10.1000/1}"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_bibtex_rejects_tag_filter() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .get("/api/v1/export/bibtex")
+        .add_query_param("tag", "papers")
+        .await;
+    response.assert_status_bad_request();
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_karakeep_export_round_trips() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    for (url, title) in [
+        ("https://example.com/first", Some("First")),
+        ("https://example.com/second", None),
+    ] {
+        server
+            .post("/api/v1/content")
+            .json(&json!({ "url": url, "title": title }))
+            .await
+            .assert_status_ok();
+    }
+    let second: Value = server
+        .post("/api/v1/content/lookup")
+        .json(&json!({ "urls": ["https://example.com/second"] }))
+        .await
+        .json();
+    let second_id = second["items"][0]["id"].as_i64().unwrap();
+    server
+        .post("/api/v1/content:bulk")
+        .json(&json!({ "action": "archive", "ids": [second_id] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/api/v1/export/karakeep").await;
+    response.assert_status_ok();
+    let export: Value = response.json();
+    let bookmarks = export["bookmarks"].as_array().unwrap();
+    assert_eq!(bookmarks.len(), 2);
+    assert_eq!(bookmarks[0]["title"], "First");
+    assert_eq!(
+        bookmarks[0]["content"],
+        json!({ "type": "link", "url": "https://example.com/first" })
+    );
+    assert_eq!(bookmarks[0]["tags"], json!([]));
+    assert_eq!(bookmarks[0]["archived"], false);
+    assert_eq!(bookmarks[1]["archived"], true);
+    assert!(bookmarks[0]["createdAt"].is_i64());
+
+    // Importing the export elsewhere recreates the same bookmarks
+    let (other, _other_db) = create_test_server();
+    other
+        .post("/api/v1/content/import/karakeep")
+        .text(export.to_string())
+        .await
+        .assert_status_ok();
+    let reexported: Value = other.get("/api/v1/export/karakeep").await.json();
+    assert_eq!(reexported, export);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_karakeep_export_rejects_tag_filter() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .get("/api/v1/export/karakeep")
+        .add_query_param("tag", "rust")
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
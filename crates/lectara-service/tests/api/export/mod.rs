@@ -0,0 +1,3 @@
+pub mod bibtex;
+pub mod karakeep;
+pub mod podcast;
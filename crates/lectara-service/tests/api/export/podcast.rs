@@ -0,0 +1,42 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_podcast_feed_lists_episodes() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .post("/api/v1/content")
+        .json(&json!({
+            "url": "https://podcast.example.com/episodes/1",
+            "title": "Episode 1",
+            "enclosure_url": "https://cdn.example.com/ep1.m4a",
+            "duration_seconds": 60
+        }))
+        .await
+        .assert_status_ok();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/article", "title": "Article" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/api/v1/export/podcast").await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.header("content-type"),
+        "application/rss+xml; charset=utf-8"
+    );
+
+    let feed = response.text();
+    assert_eq!(feed.matches("<item>").count(), 1);
+    assert!(feed.contains("<title>Episode 1</title>"));
+    assert!(feed.contains(
+        "<enclosure url=\"https://cdn.example.com/ep1.m4a\" length=\"0\" type=\"audio/mp4\"/>"
+    ));
+    assert!(feed.contains("<itunes:duration>60</itunes:duration>"));
+    assert!(!feed.contains("Article"));
+
+    Ok(())
+}
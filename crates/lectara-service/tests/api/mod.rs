@@ -1 +1,11 @@
+pub mod access;
+pub mod admin;
 pub mod content;
+pub mod export;
+pub mod notifications;
+pub mod rules;
+pub mod search;
+pub mod sync;
+pub mod versioning;
+pub mod views;
+pub mod zotero;
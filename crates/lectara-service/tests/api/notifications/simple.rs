@@ -0,0 +1,147 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::common::{establish_test_connection, server_utils::create_test_server};
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use lectara_service::{DefaultAppState, routes};
+use serde_json::{Value, json};
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <item><link>https://example.com/one</link></item>
+    <item><link>https://example.com/two</link></item>
+    <item><link>http://localhost/three</link></item>
+  </channel>
+</rss>"#;
+
+async fn import_feed(server: &TestServer) {
+    server
+        .post("/api/v1/content/import/rss")
+        .text(FEED)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_import_leaves_a_notification() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/notifications").await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>(),
+        json!({ "items": [], "unread": 0 })
+    );
+
+    import_feed(&server).await;
+
+    let response = server.get("/api/v1/notifications").await;
+    response.assert_status_ok();
+    let json_response: Value = response.json();
+    assert_eq!(json_response["unread"], 1);
+    let notification = &json_response["items"][0];
+    assert_eq!(notification["kind"], "import-finished");
+    assert_eq!(
+        notification["message"],
+        "RSS import finished: 2 created, 0 already saved, 0 conflicting, 1 invalid"
+    );
+    assert!(notification["read_at"].is_null());
+    assert!(notification["content_id"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mark_notifications_read() -> Result<()> {
+    let (server, _db) = create_test_server();
+    for _ in 0..3 {
+        import_feed(&server).await;
+    }
+
+    let listed: Value = server.get("/api/v1/notifications").await.json();
+    let newest = listed["items"][0]["id"].as_i64().unwrap();
+
+    let response = server
+        .post("/api/v1/notifications/read")
+        .json(&json!({ "ids": [newest, 999] }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>(), json!({ "marked": 1 }));
+
+    let unread: Value = server.get("/api/v1/notifications?unread=true").await.json();
+    assert_eq!(unread["unread"], 2);
+    let ids: Vec<i64> = unread["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_i64().unwrap())
+        .collect();
+    assert!(!ids.contains(&newest));
+
+    // Without ids, everything left is marked
+    let response = server
+        .post("/api/v1/notifications/read")
+        .json(&json!({}))
+        .await;
+    assert_eq!(response.json::<Value>(), json!({ "marked": 2 }));
+
+    let listed: Value = server.get("/api/v1/notifications?limit=2").await.json();
+    assert_eq!(listed["unread"], 0);
+    assert_eq!(listed["items"].as_array().unwrap().len(), 2);
+    assert!(listed["items"][0]["read_at"].is_string());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_notifications_rejects_bad_limit() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    for query in ["limit=0", "limit=201", "unread=maybe"] {
+        server
+            .get(&format!("/api/v1/notifications?{query}"))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_sends_new_notifications() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let app = routes::create_router().with_state(DefaultAppState::new(db));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = reqwest::Client::new();
+    let mut stream = client
+        .get(format!("http://{address}/api/v1/notifications/stream"))
+        .send()
+        .await?;
+    assert_eq!(stream.status(), StatusCode::OK);
+    assert_eq!(
+        stream.headers()["content-type"].to_str()?,
+        "text/event-stream"
+    );
+
+    client
+        .post(format!("http://{address}/api/v1/content/import/rss"))
+        .body(FEED)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+        .await??
+        .unwrap();
+    let event = String::from_utf8(chunk.to_vec())?;
+    assert!(event.contains("event: notification\n"), "{event}");
+    assert!(event.contains("\"kind\":\"import-finished\""), "{event}");
+
+    Ok(())
+}
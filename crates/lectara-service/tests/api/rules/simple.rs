@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use lectara_service::DefaultAppState;
+use lectara_service::enrichment::EnrichmentError;
+use lectara_service::enrichment::pipeline::{Enricher, EnricherRegistry, EnrichmentInput};
+use lectara_service::models::{ContentItem, MetadataPatch};
+use serde_json::{Value, json};
+
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+
+/// Titles every item "Enriched"
+struct Titles;
+
+#[async_trait]
+impl Enricher<DefaultAppState> for Titles {
+    fn name(&self) -> &'static str {
+        "titles"
+    }
+
+    fn applies(&self, _state: &DefaultAppState, _item: &ContentItem) -> bool {
+        true
+    }
+
+    async fn enrich(
+        &self,
+        _state: &DefaultAppState,
+        _input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        Ok(Some(MetadataPatch {
+            title: Some("Enriched".to_string()),
+            ..MetadataPatch::default()
+        }))
+    }
+}
+
+async fn create_rule(server: &TestServer, rule: Value) -> Value {
+    let response = server.post("/api/v1/rules").json(&rule).await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn get_content(server: &TestServer, id: u64) -> Value {
+    server.get(&format!("/api/v1/content/{id}")).await.json()
+}
+
+#[tokio::test]
+async fn test_rule_crud() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let first = create_rule(
+        &server,
+        json!({ "name": "Papers", "domain": "ArXiv.org", "set_content_type": "paper" }),
+    )
+    .await;
+    assert_eq!(first["name"], "Papers");
+    assert_eq!(first["domain"], "arxiv.org");
+    assert_eq!(first["position"], 0);
+    assert_eq!(first["skip_enrichment"], false);
+    let second = create_rule(
+        &server,
+        json!({ "name": "Jobs", "url_pattern": "/jobs/", "skip_enrichment": true }),
+    )
+    .await;
+    assert_eq!(second["position"], 1);
+
+    let response = server
+        .put(&format!("/api/v1/rules/{}", second["id"]))
+        .json(&json!({ "name": "Job ads", "position": -1, "url_pattern": "/jobs/", "skip_enrichment": true }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["name"], "Job ads");
+
+    let listed: Value = server.get("/api/v1/rules").await.json();
+    let names: Vec<&Value> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| &rule["name"])
+        .collect();
+    assert_eq!(names, ["Job ads", "Papers"]);
+
+    server
+        .delete(&format!("/api/v1/rules/{}", first["id"]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .get(&format!("/api/v1/rules/{}", first["id"]))
+        .await
+        .assert_status_not_found();
+    server
+        .delete(&format!("/api/v1/rules/{}", first["id"]))
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_rules_are_rejected() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    for rule in [
+        json!({ "name": "Everything", "set_content_type": "paper" }),
+        json!({ "name": "Nothing", "domain": "arxiv.org" }),
+        json!({ "name": "Broken", "url_pattern": "(", "skip_enrichment": true }),
+    ] {
+        let response = server.post("/api/v1/rules").json(&rule).await;
+        response.assert_status_bad_request();
+    }
+    server
+        .put("/api/v1/rules/999")
+        .json(&json!({ "name": "Missing", "domain": "arxiv.org", "skip_enrichment": true }))
+        .await
+        .assert_status_not_found();
+
+    let listed: Value = server.get("/api/v1/rules").await.json();
+    assert_eq!(listed, json!([]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rules_apply_when_saving() -> Result<()> {
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_enrichers(EnricherRegistry::new().with(Titles))
+    });
+    create_rule(
+        &server,
+        json!({ "name": "Papers", "domain": "arxiv.org", "set_content_type": "paper" }),
+    )
+    .await;
+    create_rule(
+        &server,
+        json!({ "name": "Unfetched", "url_pattern": r"/pdf/\d+", "skip_enrichment": true }),
+    )
+    .await;
+
+    let skipped = add_content(&server, "https://export.arxiv.org/pdf/2401").await;
+    let enriched = add_content(&server, "https://arxiv.org/abs/2401").await;
+    let unmatched = add_content(&server, "https://example.com/pdf/2401").await;
+
+    let mut item = get_content(&server, enriched).await;
+    for _ in 0..100 {
+        if !item["title"].is_null() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        item = get_content(&server, enriched).await;
+    }
+    assert_eq!(item["title"], "Enriched");
+    assert_eq!(item["content_type"], "paper");
+
+    let item = get_content(&server, skipped).await;
+    assert_eq!(item["content_type"], "paper");
+    assert!(item["title"].is_null());
+
+    let item = get_content(&server, unmatched).await;
+    assert!(item["content_type"].is_null());
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+async fn add_item(server: &TestServer, item: Value) -> u64 {
+    let response = server.post("/api/v1/content").json(&item).await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+/// Ids of the items found for `query`, in result order
+async fn search(server: &TestServer, query: &[(&str, &str)]) -> Vec<u64> {
+    let response = server.get("/api/v1/search").add_query_params(query).await;
+    response.assert_status_ok();
+    response.json::<Value>()["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_u64().unwrap())
+        .collect()
+}
+
+async fn setup() -> (TestServer, u64, u64, u64) {
+    let (server, _db) = create_test_server();
+    let marathon = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/marathon",
+            "title": "Running my first marathon",
+            "body": "Training plans and the long runs that got me there.",
+        }),
+    )
+    .await;
+    let types = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/types",
+            "title": "Notes on Rust",
+            "author": "Ferris",
+            "body": "A tour of the type system and its generics.",
+        }),
+    )
+    .await;
+    let cafe = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/cafe",
+            "title": "Le café du coin",
+            "body": "Un article sur le système des cafés parisiens.",
+        }),
+    )
+    .await;
+    (server, marathon, types, cafe)
+}
+
+#[tokio::test]
+async fn test_search_stems_english_words() -> Result<()> {
+    let (server, marathon, _, _) = setup().await;
+
+    assert_eq!(search(&server, &[("q", "run")]).await, [marathon]);
+    assert_eq!(search(&server, &[("q", "trained")]).await, [marathon]);
+    // Without stemming only the exact word matches
+    assert!(
+        search(&server, &[("q", "run"), ("language", "none")])
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        search(&server, &[("q", "runs"), ("language", "none")]).await,
+        [marathon]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_phrases_and_prefixes() -> Result<()> {
+    let (server, marathon, types, _) = setup().await;
+
+    assert_eq!(search(&server, &[("q", "\"type system\"")]).await, [types]);
+    assert!(
+        search(&server, &[("q", "\"system type\"")])
+            .await
+            .is_empty()
+    );
+    assert_eq!(search(&server, &[("q", "mara*")]).await, [marathon]);
+    assert_eq!(search(&server, &[("q", "ferris")]).await, [types]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_operators() -> Result<()> {
+    let (server, marathon, types, _) = setup().await;
+
+    let mut either = search(&server, &[("q", "marathon OR generics")]).await;
+    either.sort();
+    assert_eq!(either, [marathon, types]);
+    assert!(
+        search(&server, &[("q", "marathon AND generics")])
+            .await
+            .is_empty()
+    );
+    assert!(
+        search(&server, &[("q", "marathon generics")])
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        search(&server, &[("q", "marathon generics OR training")]).await,
+        [marathon]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_folds_diacritics() -> Result<()> {
+    let (server, _, _, cafe) = setup().await;
+
+    assert_eq!(
+        search(&server, &[("q", "cafe"), ("language", "none")]).await,
+        [cafe]
+    );
+    assert_eq!(
+        search(&server, &[("q", "systeme"), ("language", "none")]).await,
+        [cafe]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_ranks_title_matches_first() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let in_body = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/body",
+            "title": "Weekly links",
+            "body": "Includes a piece on sourdough among many other things.",
+        }),
+    )
+    .await;
+    let in_title = add_item(
+        &server,
+        json!({
+            "url": "https://example.com/title",
+            "title": "Sourdough basics",
+        }),
+    )
+    .await;
+
+    assert_eq!(
+        search(&server, &[("q", "sourdough")]).await,
+        [in_title, in_body]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_rejects_invalid_queries() -> Result<()> {
+    let (server, _, _, _) = setup().await;
+
+    for (query, message) in [
+        ("", "Search query is empty"),
+        ("\"open", "Search query has an unterminated phrase"),
+        ("rust OR", "OR must appear between terms"),
+        (
+            "*",
+            "Prefix searches need at least one character before '*'",
+        ),
+    ] {
+        let response = server
+            .get("/api/v1/search")
+            .add_query_param("q", query)
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert!(
+            response.text().contains(message),
+            "{query}: {}",
+            response.text()
+        );
+    }
+
+    server
+        .get("/api/v1/search")
+        .add_query_params([("q", "rust"), ("language", "klingon")])
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use diesel::SqliteConnection;
+use lectara_service::DefaultAppState;
+use lectara_service::federation::SyncTarget;
+use serde_json::{Value, json};
+
+use crate::common::establish_test_connection;
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+use crate::common::test_utils::get_content_item_by_url;
+
+/// Serves another lectara instance on a local port
+async fn spawn_remote() -> (String, Arc<Mutex<SqliteConnection>>) {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let app = lectara_service::create_app(DefaultAppState::new(db.clone()));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{address}"), db)
+}
+
+async fn create_server() -> (TestServer, Arc<Mutex<SqliteConnection>>) {
+    let (remote, remote_db) = spawn_remote().await;
+    let target = SyncTarget::new(&remote, None, "public").unwrap();
+    let (server, _db) = create_test_server_with_state(|state| state.with_sync_target(Some(target)));
+    (server, remote_db)
+}
+
+async fn create_view(server: &TestServer) {
+    server
+        .post("/api/v1/views")
+        .json(&json!({ "name": "public", "source": "cli" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+async fn save(server: &TestServer, url: &str, source: &str) {
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "title": "Local Title", "source": source }))
+        .await
+        .assert_status_ok();
+}
+
+async fn wait_for_job(server: &TestServer, response: Value) -> Value {
+    let status_url = response["status_url"].as_str().unwrap();
+    for _ in 0..50 {
+        let job: Value = server.get(status_url).await.json();
+        if job["status"] != "running" {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Sync job did not finish");
+}
+
+#[tokio::test]
+async fn test_new_saves_selected_by_the_view_are_pushed() -> Result<()> {
+    let (server, remote_db) = create_server().await;
+    create_view(&server).await;
+
+    save(&server, "https://example.com/public", "cli").await;
+
+    let mut pushed = None;
+    for _ in 0..50 {
+        pushed =
+            get_content_item_by_url(&mut remote_db.lock().unwrap(), "https://example.com/public");
+        if pushed.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let pushed = pushed.expect("Item was not pushed");
+    assert_eq!(pushed.title.as_deref(), Some("Local Title"));
+    assert_eq!(pushed.source.as_deref(), Some("sync"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sync_pushes_the_selection_without_overwriting() -> Result<()> {
+    let (server, remote_db) = create_server().await;
+    // Saved before the view exists, so nothing is pushed yet
+    save(&server, "https://example.com/public", "cli").await;
+    save(&server, "https://example.com/edited", "cli").await;
+    save(&server, "https://example.com/private", "api").await;
+    create_view(&server).await;
+
+    let remote = TestServer::new(lectara_service::create_app(DefaultAppState::new(
+        remote_db.clone(),
+    )))?;
+    remote
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/edited", "title": "Remote Title" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/api/v1/sync").await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let job = wait_for_job(&server, response.json()).await;
+    assert_eq!(job["kind"], "sync");
+    assert_eq!(job["status"], "finished");
+    assert_eq!(job["outcomes"], json!({ "saved": 1, "conflicting": 1 }));
+
+    {
+        let mut conn = remote_db.lock().unwrap();
+        assert!(get_content_item_by_url(&mut conn, "https://example.com/public").is_some());
+        assert!(get_content_item_by_url(&mut conn, "https://example.com/private").is_none());
+        let edited = get_content_item_by_url(&mut conn, "https://example.com/edited").unwrap();
+        assert_eq!(edited.title.as_deref(), Some("Remote Title"));
+    }
+
+    // Pushing again changes nothing
+    let response = server.post("/api/v1/sync").await;
+    let job = wait_for_job(&server, response.json()).await;
+    assert_eq!(job["outcomes"], json!({ "saved": 1, "conflicting": 1 }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sync_needs_a_target_and_view() -> Result<()> {
+    let (server, _db) = create_test_server();
+    server
+        .post("/api/v1/sync")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let (server, _remote_db) = create_server().await;
+    let response = server.post("/api/v1/sync").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("No view named 'public'"));
+
+    Ok(())
+}
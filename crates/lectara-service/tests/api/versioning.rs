@@ -0,0 +1,43 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server;
+
+#[tokio::test]
+async fn test_v1_routes_name_their_version() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/content").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("api-version"), "1");
+    assert!(response.maybe_header("deprecation").is_none());
+
+    let response = server
+        .get("/api/v1/content")
+        .add_header("Accept-Version", "v1")
+        .await;
+    response.assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsupported_accept_version_is_refused() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .add_header("Accept-Version", "2")
+        .json(&json!({ "url": "https://example.com/v2-client" }))
+        .await;
+    response.assert_status(StatusCode::NOT_ACCEPTABLE);
+    let error: Value = response.json();
+    assert!(error["error"].as_str().unwrap().contains("supports v1"));
+
+    // The request never reached the handler
+    let mut conn = db.lock().unwrap();
+    assert_eq!(crate::common::test_utils::count_content_items(&mut conn), 0);
+
+    Ok(())
+}
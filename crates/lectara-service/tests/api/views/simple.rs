@@ -0,0 +1,131 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server;
+
+async fn add_content(server: &TestServer, url: &str, source: &str) {
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "source": source }))
+        .await
+        .assert_status_ok();
+}
+
+fn urls(list: &Value) -> Vec<&str> {
+    list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["url"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_view_crud() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/views")
+        .json(&json!({ "name": "work-reading", "source": "CLI", "since": "7d" }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let view: Value = response.json();
+    assert_eq!(view["name"], "work-reading");
+    assert_eq!(view["source"], "cli");
+    assert_eq!(view["since"], "7d");
+    assert!(view["content_type"].is_null());
+
+    server
+        .post("/api/v1/views")
+        .json(&json!({ "name": "work-reading" }))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let response = server
+        .put("/api/v1/views/work-reading")
+        .json(&json!({ "content_type": "paper" }))
+        .await;
+    response.assert_status_ok();
+    let updated: Value = response.json();
+    assert_eq!(updated["content_type"], "paper");
+    assert!(updated["source"].is_null());
+
+    let listed: Value = server.get("/api/v1/views").await.json();
+    assert_eq!(listed, json!([updated]));
+
+    server
+        .delete("/api/v1/views/work-reading")
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .get("/api/v1/views/work-reading")
+        .await
+        .assert_status_not_found();
+    server
+        .put("/api/v1/views/work-reading")
+        .json(&json!({}))
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_views_are_rejected() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    for view in [
+        json!({ "name": "Work Reading" }),
+        json!({ "name": "" }),
+        json!({ "name": "recent", "since": "last week" }),
+        json!({ "name": "mobile", "source": "phone" }),
+    ] {
+        server
+            .post("/api/v1/views")
+            .json(&view)
+            .await
+            .assert_status_bad_request();
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_with_view() -> Result<()> {
+    let (server, _db) = create_test_server();
+    add_content(&server, "https://example.com/cli", "cli").await;
+    add_content(&server, "https://example.com/web", "web").await;
+    server
+        .post("/api/v1/views")
+        .json(&json!({ "name": "from-cli", "source": "cli", "since": "1d" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let listed: Value = server
+        .get("/api/v1/content")
+        .add_query_param("view", "from-cli")
+        .await
+        .json();
+    assert_eq!(urls(&listed), ["https://example.com/cli"]);
+    assert_eq!(listed["total"], 1);
+
+    // Filters in the query take precedence over the view's
+    let listed: Value = server
+        .get("/api/v1/content")
+        .add_query_param("view", "from-cli")
+        .add_query_param("source", "web")
+        .await
+        .json();
+    assert_eq!(urls(&listed), ["https://example.com/web"]);
+
+    let response = server
+        .get("/api/v1/content")
+        .add_query_param("view", "missing")
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["error"], "No view named 'missing'");
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::common::server_utils::create_test_server;
+use crate::common::test_utils;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_ping() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server.post("/api/v1/zotero/ping").await.assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_items_keeps_citations() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/zotero/saveItems")
+        .json(&json!({
+            "sessionID": "abc",
+            "uri": "https://journal.example.com/articles/42",
+            "items": [
+                {
+                    "itemType": "journalArticle",
+                    "title": "On Engines",
+                    "creators": [
+                        { "firstName": "Ada", "lastName": "Lovelace", "creatorType": "author" },
+                        { "firstName": "Charles", "lastName": "Babbage", "creatorType": "editor" }
+                    ],
+                    "date": "1843-10",
+                    "url": "https://journal.example.com/articles/42",
+                    "DOI": "10.1000/engines",
+                    "publicationTitle": "Scientific Memoirs",
+                    "abstractNote": "Notes on the analytical engine.",
+                    "attachments": [
+                        {
+                            "title": "Full Text PDF",
+                            "url": "https://journal.example.com/articles/42.pdf",
+                            "mimeType": "application/pdf"
+                        },
+                        { "title": "Snapshot", "mimeType": "text/html" }
+                    ]
+                },
+                { "itemType": "note", "title": "No link" }
+            ]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+
+    let json_response: Value = response.json();
+    assert_eq!(json_response["items"][0]["status"], "created");
+    assert_eq!(json_response["items"][1]["status"], "invalid");
+    let id = json_response["items"][0]["id"].as_i64().unwrap();
+
+    {
+        let mut conn = db.lock().unwrap();
+        let item = test_utils::get_content_item_by_url(
+            &mut conn,
+            "https://journal.example.com/articles/42",
+        )
+        .unwrap();
+        assert_eq!(item.title.as_deref(), Some("On Engines"));
+        assert_eq!(item.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(item.source.as_deref(), Some("zotero"));
+    }
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(
+        item["metadata"]["attachments"],
+        json!([{
+            "title": "Full Text PDF",
+            "url": "https://journal.example.com/articles/42.pdf",
+            "mimeType": "application/pdf"
+        }])
+    );
+    assert_eq!(item["citation"]["source"], "zotero");
+    assert_eq!(item["citation"]["identifier"], "10.1000/engines");
+
+    let bibtex = server.get("/api/v1/export/bibtex").await.text();
+    assert!(bibtex.contains("title = {{On Engines}}"));
+    assert!(bibtex.contains("year = {1843}"));
+    assert!(bibtex.contains("journal = {Scientific Memoirs}"));
+
+    // Saving the item again keeps it, refreshing the citation
+    let again: Value = server
+        .post("/api/v1/zotero/saveItems")
+        .json(&json!({ "items": [{
+            "title": "On Engines (2nd ed.)",
+            "url": "https://journal.example.com/articles/42"
+        }]}))
+        .await
+        .json();
+    assert_eq!(again["items"][0]["status"], "existing");
+    assert_eq!(again["items"][0]["id"], id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_items_limit() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let items: Vec<Value> = (0..101)
+        .map(|i| json!({ "url": format!("https://example.com/{i}") }))
+        .collect();
+    server
+        .post("/api/v1/zotero/saveItems")
+        .json(&json!({ "items": items }))
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use diesel::{Connection, sqlite::SqliteConnection};
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
-
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+use diesel_migrations::MigrationHarness;
+use lectara_service::migrations::MIGRATIONS;
 
 pub fn establish_test_connection() -> SqliteConnection {
     let mut connection =
@@ -17,14 +16,26 @@ pub fn establish_test_connection() -> SqliteConnection {
 pub mod server_utils {
     use super::*;
     use axum_test::TestServer;
-    use lectara_service::{DefaultAppState, routes};
+    use lectara_service::{DefaultAppState, routes, validation::ValidationContext};
     use std::sync::{Arc, Mutex};
 
     pub fn create_test_server() -> (TestServer, Arc<Mutex<SqliteConnection>>) {
+        create_test_server_with_validation(ValidationContext::default())
+    }
+
+    pub fn create_test_server_with_validation(
+        validation: ValidationContext,
+    ) -> (TestServer, Arc<Mutex<SqliteConnection>>) {
+        create_test_server_with_state(|state| state.with_validation(validation))
+    }
+
+    pub fn create_test_server_with_state(
+        configure: impl FnOnce(DefaultAppState) -> DefaultAppState,
+    ) -> (TestServer, Arc<Mutex<SqliteConnection>>) {
         let connection = establish_test_connection();
         let db = Arc::new(Mutex::new(connection));
 
-        let state = DefaultAppState::new(db.clone());
+        let state = configure(DefaultAppState::new(db.clone()));
         let app = routes::create_router().with_state(state);
 
         let server = TestServer::new(app).unwrap();
@@ -35,8 +46,8 @@ pub mod server_utils {
 pub mod test_utils {
     use diesel::SqliteConnection;
     use diesel::prelude::*;
-    use lectara_service::models::ContentItem;
-    use lectara_service::schema::content_items;
+    use lectara_service::models::{ContentItem, ContentItemSummary};
+    use lectara_service::schema::{body_blobs, content_items};
 
     pub fn count_content_items(conn: &mut SqliteConnection) -> i64 {
         content_items::table
@@ -45,27 +56,40 @@ pub mod test_utils {
             .expect("Failed to count content items")
     }
 
+    fn with_bodies(rows: Vec<(ContentItemSummary, Option<String>)>) -> Vec<ContentItem> {
+        rows.into_iter()
+            .map(|(summary, body)| ContentItem::from_parts(summary, body))
+            .collect()
+    }
+
     pub fn get_all_content_items(conn: &mut SqliteConnection) -> Vec<ContentItem> {
-        content_items::table
-            .load::<ContentItem>(conn)
-            .expect("Failed to load content items")
+        let rows = content_items::table
+            .left_join(body_blobs::table)
+            .select((ContentItemSummary::as_select(), body_blobs::body.nullable()))
+            .load(conn)
+            .expect("Failed to load content items");
+        with_bodies(rows)
     }
 
     pub fn get_content_item_by_url(conn: &mut SqliteConnection, url: &str) -> Option<ContentItem> {
-        content_items::table
+        let rows = content_items::table
+            .left_join(body_blobs::table)
             .filter(content_items::url.eq(url))
-            .first::<ContentItem>(conn)
-            .optional()
-            .expect("Failed to query content item by URL")
+            .select((ContentItemSummary::as_select(), body_blobs::body.nullable()))
+            .load(conn)
+            .expect("Failed to query content item by URL");
+        with_bodies(rows).into_iter().next()
     }
 
     #[allow(dead_code)]
     pub fn get_content_item_by_id(conn: &mut SqliteConnection, id: i32) -> Option<ContentItem> {
-        content_items::table
-            .find(id)
-            .first::<ContentItem>(conn)
-            .optional()
-            .expect("Failed to query content item by ID")
+        let rows = content_items::table
+            .left_join(body_blobs::table)
+            .filter(content_items::id.eq(id))
+            .select((ContentItemSummary::as_select(), body_blobs::body.nullable()))
+            .load(conn)
+            .expect("Failed to query content item by ID");
+        with_bodies(rows).into_iter().next()
     }
 
     pub fn update_content_item_timestamp(
@@ -80,3 +104,36 @@ pub mod test_utils {
             .expect("Failed to update timestamp");
     }
 }
+
+pub mod scratch {
+    use std::path::{Path, PathBuf};
+
+    /// A temporary directory for one test's files, removed when dropped
+    pub struct ScratchDir(PathBuf);
+
+    impl ScratchDir {
+        pub fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("lectara-test-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
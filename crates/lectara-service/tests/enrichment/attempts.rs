@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, http::StatusCode, http::header, response::IntoResponse, routing::get};
+use axum_test::TestServer;
+use chrono::{TimeDelta, Utc};
+use lectara_service::enrichment::attempts;
+use lectara_service::enrichment::fetch::Fetcher;
+use lectara_service::enrichment::pdf::PdfExtractor;
+use lectara_service::models::NewFetchAttempt;
+use lectara_service::repositories::FetchAttemptRepository;
+use lectara_service::validation::ValidationContext;
+use lectara_service::{AppState, DefaultAppState};
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_state;
+
+/// Serves `/flaky.pdf`, which fails until `available` is set and then turns
+/// out to be an ordinary page
+async fn spawn_site(available: Arc<AtomicBool>) -> String {
+    let app = Router::new()
+        .route(
+            "/flaky.pdf",
+            get(move || async move {
+                if available.load(Ordering::SeqCst) {
+                    ([(header::CONTENT_TYPE, "text/html")], "<p>Moved</p>").into_response()
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE.into_response()
+                }
+            }),
+        )
+        .route(
+            "/article",
+            get(|| async { ([(header::CONTENT_TYPE, "text/html")], "<p>Hello</p>") }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn configure(state: DefaultAppState) -> DefaultAppState {
+    let validation = ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    };
+    state
+        .with_validation(validation.clone())
+        .with_pdf_extractor(Some(PdfExtractor::new(Fetcher::new(validation))))
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+/// Polls the fetch status until the PDF check has recorded `attempts` runs
+async fn wait_for_attempt(server: &TestServer, id: u64, attempts: u64) -> Value {
+    for _ in 0..100 {
+        let status: Value = server
+            .get(&format!("/api/v1/content/{id}/fetch-status"))
+            .await
+            .json();
+        if let Some(attempt) = status.as_array().and_then(|list| list.first())
+            && attempt["attempts"] == attempts
+        {
+            return attempt.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("fetch attempt {attempts} for item {id} was never recorded");
+}
+
+#[tokio::test]
+async fn test_failed_fetch_is_recorded_and_retried_manually() -> Result<()> {
+    let available = Arc::new(AtomicBool::new(false));
+    let site = spawn_site(available.clone()).await;
+    let (server, _db) = create_test_server_with_state(configure);
+
+    let id = add_content(&server, &format!("{site}/flaky.pdf")).await;
+
+    let attempt = wait_for_attempt(&server, id, 1).await;
+    assert_eq!(attempt["kind"], "pdf");
+    assert_eq!(attempt["status"], "failed");
+    assert!(
+        attempt["last_error"]
+            .as_str()
+            .unwrap()
+            .contains("503 Service Unavailable")
+    );
+    assert!(attempt["next_attempt_at"].is_string());
+
+    available.store(true, Ordering::SeqCst);
+    let response = server
+        .post(&format!("/api/v1/content/{id}/fetch-status/retry"))
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(response.json::<Value>(), json!({ "retrying": ["pdf"] }));
+
+    let attempt = wait_for_attempt(&server, id, 2).await;
+    assert_eq!(attempt["status"], "succeeded");
+    assert!(attempt["last_error"].is_null());
+    assert!(attempt["next_attempt_at"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_due_retries_are_run() -> Result<()> {
+    let available = Arc::new(AtomicBool::new(false));
+    let site = spawn_site(available.clone()).await;
+    let (server, db) = create_test_server_with_state(configure);
+
+    let id = add_content(&server, &format!("{site}/flaky.pdf")).await;
+    wait_for_attempt(&server, id, 1).await;
+
+    let state = configure(DefaultAppState::new(db));
+    // Nothing is due until the backoff has passed
+    assert_eq!(
+        attempts::retry_due(&state, Utc::now().naive_utc()).await?,
+        0
+    );
+
+    let later = Utc::now().naive_utc() + TimeDelta::minutes(2);
+    assert_eq!(attempts::retry_due(&state, later).await?, 1);
+
+    let attempt = wait_for_attempt(&server, id, 2).await;
+    assert_eq!(attempt["status"], "failed");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_abandoned_fetch_leaves_a_notification() -> Result<()> {
+    let available = Arc::new(AtomicBool::new(false));
+    let site = spawn_site(available.clone()).await;
+    let (server, db) = create_test_server_with_state(configure);
+
+    let id = add_content(&server, &format!("{site}/flaky.pdf")).await;
+    wait_for_attempt(&server, id, 1).await;
+
+    // One failure short of giving up
+    let state = configure(DefaultAppState::new(db));
+    let now = Utc::now().naive_utc();
+    state
+        .fetch_attempt_repo()
+        .record(&NewFetchAttempt {
+            content_id: id as i32,
+            kind: "pdf".to_string(),
+            status: attempts::STATUS_FAILED.to_string(),
+            attempts: attempts::MAX_ATTEMPTS - 1,
+            last_error: None,
+            last_attempt_at: now,
+            next_attempt_at: Some(now),
+        })
+        .await?;
+    assert_eq!(attempts::retry_due(&state, now).await?, 1);
+
+    let attempt = wait_for_attempt(&server, id, attempts::MAX_ATTEMPTS as u64).await;
+    assert_eq!(attempt["status"], "abandoned");
+
+    let notifications: Value = server.get("/api/v1/notifications").await.json();
+    let notification = &notifications["items"][0];
+    assert_eq!(notification["kind"], "fetch-abandoned");
+    assert_eq!(notification["content_id"], id);
+    assert!(
+        notification["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Gave up fetching pdf for ")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_successful_fetches_need_no_retry() -> Result<()> {
+    let site = spawn_site(Arc::new(AtomicBool::new(true))).await;
+    let (server, _db) = create_test_server_with_state(configure);
+
+    let id = add_content(&server, &format!("{site}/article")).await;
+    let attempt = wait_for_attempt(&server, id, 1).await;
+    assert_eq!(attempt["status"], "succeeded");
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/fetch-status/retry"))
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(response.json::<Value>(), json!({ "retrying": [] }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_status_of_missing_item() -> Result<()> {
+    let (server, _db) = create_test_server_with_state(configure);
+
+    server
+        .get("/api/v1/content/999/fetch-status")
+        .await
+        .assert_status_not_found();
+    server
+        .post("/api/v1/content/999/fetch-status/retry")
+        .await
+        .assert_status_not_found();
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, extract::Path, http::StatusCode, response::IntoResponse, routing::get};
+use axum_test::TestServer;
+use lectara_service::enrichment::citations::CitationResolver;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+
+const CROSSREF_WORK: &str = r#"{
+    "status": "ok",
+    "message": {
+        "title": ["Stub Paper"],
+        "author": [{"given": "Ada", "family": "Lovelace"}],
+        "container-title": ["Journal of Stubs"],
+        "issued": {"date-parts": [[1843, 9, 1]]}
+    }
+}"#;
+
+const ARXIV_FEED: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <published>2017-06-12T17:57:34Z</published>
+    <title>Stub Preprint</title>
+    <summary>An abstract.</summary>
+    <author><name>Grace Hopper</name></author>
+  </entry>
+</feed>"#;
+
+/// Serves canned Crossref and arXiv responses on a local port
+async fn spawn_upstream() -> String {
+    let app = Router::new()
+        .route(
+            "/works/{*doi}",
+            get(|Path(doi): Path<String>| async move {
+                if doi == "10.1000/182" {
+                    CROSSREF_WORK.into_response()
+                } else {
+                    StatusCode::NOT_FOUND.into_response()
+                }
+            }),
+        )
+        .route("/query", get(|| async { ARXIV_FEED }));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn add_and_wait_for_citation(server: &TestServer, url: &str) -> Value {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    for _ in 0..50 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["citation"].is_null() {
+            return item;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("citation for {url} was never stored");
+}
+
+#[tokio::test]
+async fn test_doi_item_gets_crossref_citation() -> Result<()> {
+    let upstream = spawn_upstream().await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_citation_resolver(Some(CitationResolver::with_endpoints(&upstream, &upstream)))
+    });
+
+    let item = add_and_wait_for_citation(&server, "https://doi.org/10.1000/182").await;
+    let citation = &item["citation"];
+
+    assert_eq!(citation["source"], "crossref");
+    assert_eq!(citation["identifier"], "10.1000/182");
+    assert_eq!(citation["title"], "Stub Paper");
+    assert_eq!(citation["authors"], json!(["Ada Lovelace"]));
+    assert_eq!(citation["published_on"], "1843-09-01");
+    assert_eq!(citation["container_title"], "Journal of Stubs");
+    // The item's own metadata is left as submitted
+    assert!(item["title"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_arxiv_item_gets_arxiv_citation() -> Result<()> {
+    let upstream = spawn_upstream().await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_citation_resolver(Some(CitationResolver::with_endpoints(&upstream, &upstream)))
+    });
+
+    let item = add_and_wait_for_citation(&server, "https://arxiv.org/abs/1706.03762").await;
+    let citation = &item["citation"];
+
+    assert_eq!(citation["source"], "arxiv");
+    assert_eq!(citation["title"], "Stub Preprint");
+    assert_eq!(citation["abstract"], "An abstract.");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_citation_without_resolver() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://doi.org/10.1000/182" }))
+        .await;
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert!(item.get("citation").is_none());
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use axum::{
+    Router,
+    extract::Path,
+    http::{HeaderMap, header},
+    response::Redirect,
+    routing::get,
+};
+use lectara_service::enrichment::credentials::SiteCredentials;
+use lectara_service::enrichment::fetch::Fetcher;
+use lectara_service::validation::ValidationContext;
+
+/// Serves an article whose full text needs a subscriber cookie, and echoes
+/// the cookie it received at `/cookie`
+async fn spawn_site() -> u16 {
+    let app = Router::new()
+        .route(
+            "/article",
+            get(|headers: HeaderMap| async move {
+                let subscribed = headers
+                    .get(header::COOKIE)
+                    .is_some_and(|cookie| cookie == "session=subscriber");
+                if subscribed {
+                    "The full article"
+                } else {
+                    "A teaser paragraph"
+                }
+            }),
+        )
+        .route(
+            "/cookie",
+            get(|headers: HeaderMap| async move {
+                headers
+                    .get(header::COOKIE)
+                    .and_then(|cookie| cookie.to_str().ok())
+                    .unwrap_or("none")
+                    .to_string()
+            }),
+        )
+        .route(
+            "/to/{host}",
+            get(|Path(host): Path<String>, headers: HeaderMap| async move {
+                let port = headers[header::HOST]
+                    .to_str()
+                    .unwrap()
+                    .rsplit(':')
+                    .next()
+                    .unwrap()
+                    .to_string();
+                Redirect::temporary(&format!("http://{host}:{port}/cookie"))
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    port
+}
+
+fn fetcher() -> Fetcher {
+    let validation = ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    };
+    let credentials = SiteCredentials::parse(
+        r#"
+        [sites."127.0.0.1"]
+        cookies = { session = "subscriber" }
+        "#,
+    )
+    .unwrap();
+    Fetcher::new(validation).with_credentials(credentials)
+}
+
+async fn fetch_text(fetcher: &Fetcher, url: &str) -> Result<String> {
+    let document = fetcher.fetch(url, |_| true).await?.unwrap();
+    Ok(String::from_utf8(document.bytes)?)
+}
+
+#[tokio::test]
+async fn test_configured_cookies_unlock_full_text() -> Result<()> {
+    let port = spawn_site().await;
+    let url = format!("http://127.0.0.1:{port}/article");
+
+    assert_eq!(fetch_text(&fetcher(), &url).await?, "The full article");
+    let anonymous = Fetcher::new(ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    });
+    assert_eq!(fetch_text(&anonymous, &url).await?, "A teaser paragraph");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_credentials_follow_redirects_only_to_their_host() -> Result<()> {
+    let port = spawn_site().await;
+    let fetcher = fetcher();
+
+    let same_host = format!("http://127.0.0.1:{port}/to/127.0.0.1");
+    assert_eq!(
+        fetch_text(&fetcher, &same_host).await?,
+        "session=subscriber"
+    );
+
+    // localhost is the same server under a host with no credentials
+    let other_host = format!("http://127.0.0.1:{port}/to/localhost");
+    let document = fetcher.fetch(&other_host, |_| true).await?.unwrap();
+    assert_eq!(document.url, format!("http://localhost:{port}/cookie"));
+    assert_eq!(String::from_utf8(document.bytes)?, "none");
+
+    Ok(())
+}
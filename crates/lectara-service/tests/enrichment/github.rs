@@ -0,0 +1,216 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, http::HeaderMap, http::StatusCode, response::IntoResponse, routing::get};
+use axum_test::TestServer;
+use lectara_service::enrichment::github::GithubResolver;
+use serde_json::{Value, json};
+use tokio::sync::Notify;
+
+use crate::common::server_utils::create_test_server_with_state;
+
+const REPOSITORY: &str = r#"{
+    "full_name": "ada/engine",
+    "description": "An analytical engine",
+    "stargazers_count": 42,
+    "language": "Rust",
+    "owner": {"login": "ada"}
+}"#;
+
+const RELEASE: &str = r#"{"tag_name": "v1.0.0", "published_at": "1843-09-01T00:00:00Z"}"#;
+
+/// Serves a canned repository on a local port; only requests carrying the
+/// test token see it
+async fn spawn_api() -> String {
+    let authorized = |headers: &HeaderMap| {
+        headers
+            .get("authorization")
+            .is_some_and(|value| value == "Bearer test-token")
+    };
+    let app = Router::new()
+        .route(
+            "/repos/ada/engine",
+            get(move |headers: HeaderMap| async move {
+                if authorized(&headers) {
+                    REPOSITORY.into_response()
+                } else {
+                    StatusCode::UNAUTHORIZED.into_response()
+                }
+            }),
+        )
+        .route(
+            "/repos/ada/engine/releases/latest",
+            get(|| async { RELEASE }),
+        )
+        .route(
+            "/repos/ada/unreleased",
+            get(|| async { REPOSITORY.replace("ada/engine", "ada/unreleased") }),
+        )
+        .route(
+            "/repos/ada/unreleased/releases/latest",
+            get(|| async { StatusCode::NOT_FOUND }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn create_server() -> TestServer {
+    let api = spawn_api().await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_github_resolver(Some(GithubResolver::with_endpoint(
+            &api,
+            Some("test-token"),
+        )))
+    });
+    server
+}
+
+async fn add_and_wait_for_enrichment(server: &TestServer, url: &str) -> Value {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    for _ in 0..50 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["content_type"].is_null() {
+            return item;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("repository metadata for {url} was never stored");
+}
+
+#[tokio::test]
+async fn test_github_repository_is_enriched() -> Result<()> {
+    let server = create_server().await;
+
+    let item = add_and_wait_for_enrichment(&server, "https://github.com/ada/engine").await;
+
+    assert_eq!(item["content_type"], "repository");
+    assert_eq!(item["title"], "ada/engine");
+    assert_eq!(item["author"], "ada");
+    assert_eq!(
+        item["metadata"],
+        json!({
+            "description": "An analytical engine",
+            "stars": 42,
+            "language": "Rust",
+            "latest_release": {"tag": "v1.0.0", "published_at": "1843-09-01T00:00:00Z"},
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_repository_without_releases() -> Result<()> {
+    let server = create_server().await;
+
+    let item = add_and_wait_for_enrichment(&server, "https://github.com/ada/unreleased").await;
+
+    assert_eq!(item["title"], "ada/unreleased");
+    assert!(item["metadata"]["latest_release"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_filters_by_content_type() -> Result<()> {
+    let server = create_server().await;
+
+    add_and_wait_for_enrichment(&server, "https://github.com/ada/engine").await;
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/article" }))
+        .await
+        .assert_status_ok();
+
+    let list: Value = server
+        .get("/api/v1/content")
+        .add_query_param("content_type", "repository")
+        .await
+        .json();
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["items"][0]["url"], "https://github.com/ada/engine");
+    assert_eq!(list["items"][0]["content_type"], "repository");
+
+    let count: Value = server
+        .get("/api/v1/content/count")
+        .add_query_param("content_type", "repository")
+        .await
+        .json();
+    assert_eq!(count["total"], 1);
+
+    Ok(())
+}
+
+/// Serves a repository only once `gate` is notified, holding its
+/// enrichment back until then
+async fn spawn_gated_api(gate: Arc<Notify>) -> String {
+    let app = Router::new()
+        .route(
+            "/repos/ada/gated",
+            get(move || async move {
+                gate.notified().await;
+                REPOSITORY.replace("ada/engine", "ada/gated")
+            }),
+        )
+        .route(
+            "/repos/ada/gated/releases/latest",
+            get(|| async { StatusCode::NOT_FOUND }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+#[tokio::test]
+async fn test_totals_follow_enrichment() -> Result<()> {
+    let gate = Arc::new(Notify::new());
+    let api = spawn_gated_api(gate.clone()).await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_github_resolver(Some(GithubResolver::with_endpoint(&api, None)))
+    });
+    let total = async |path: &str| -> Value {
+        server
+            .get(path)
+            .add_query_param("content_type", "repository")
+            .await
+            .json::<Value>()["total"]
+            .clone()
+    };
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://github.com/ada/gated" }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    // Counted, and cached, while the item has no content type yet
+    assert_eq!(total("/api/v1/content").await, 0);
+    assert_eq!(total("/api/v1/content/count").await, 0);
+
+    gate.notify_one();
+    for _ in 0..50 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["content_type"].is_null() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(total("/api/v1/content").await, 1);
+    assert_eq!(total("/api/v1/content/count").await, 1);
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum_test::TestServer;
+use lectara_service::enrichment::hooks::{CommandHook, HookStage, Hooks};
+use lectara_service::enrichment::pipeline::EnricherRegistry;
+use serde_json::{Value, json};
+
+use crate::common::scratch::ScratchDir;
+use crate::common::server_utils::create_test_server_with_state;
+
+/// Writes an executable shell script into `dir`
+fn script(dir: &ScratchDir, name: &str, body: &str) -> PathBuf {
+    let path = dir.write(name, &format!("#!/bin/sh\n{body}\n"));
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn wait_for_attempt(server: &TestServer, id: u64) -> Value {
+    for _ in 0..200 {
+        let status: Value = server
+            .get(&format!("/api/v1/content/{id}/fetch-status"))
+            .await
+            .json();
+        if let Some(attempt) = status.as_array().unwrap().first() {
+            return attempt.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("hook never ran for item {id}");
+}
+
+async fn wait_for_file(path: &Path) -> String {
+    for _ in 0..200 {
+        if let Ok(contents) = std::fs::read_to_string(path)
+            && !contents.is_empty()
+        {
+            return contents;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{} was never written", path.display());
+}
+
+#[tokio::test]
+async fn test_enrich_hook_patches_item() -> Result<()> {
+    let dir = ScratchDir::new("hooks-enrich");
+    let tagger = script(
+        &dir,
+        "tagger",
+        r#"cat > "$HOME/input.json"
+printf '{"title": "Tagged", "metadata": {"tags": ["%s"], "leaked": "%s"}}' "$TAGGER_TAG" "$CARGO_MANIFEST_DIR""#,
+    );
+    let hook = CommandHook::new("tagger", tagger, HookStage::Enrich)
+        .with_env("TAGGER_TAG", "rust")
+        .with_working_dir(dir.path());
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_enrichers(EnricherRegistry::new().with(hook))
+    });
+
+    let id = add_content(&server, "https://example.com/hooked").await;
+    let attempt = wait_for_attempt(&server, id).await;
+    assert_eq!(attempt["kind"], "tagger");
+    assert_eq!(attempt["status"], "succeeded");
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["title"], "Tagged");
+    assert_eq!(item["metadata"]["tags"], json!(["rust"]));
+    // The server's own environment is not passed on
+    assert_eq!(item["metadata"]["leaked"], "");
+
+    let input: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("input.json"))?)?;
+    assert_eq!(input["stage"], "enrich");
+    assert_eq!(input["item"]["url"], "https://example.com/hooked");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failing_hooks_are_recorded() -> Result<()> {
+    let dir = ScratchDir::new("hooks-failing");
+    let slow = script(&dir, "slow", "sleep 5");
+    let broken = script(&dir, "broken", "echo 'model missing' >&2\nexit 3");
+    let garbled = script(&dir, "garbled", "echo 'not json'");
+
+    let cases = [
+        (
+            CommandHook::new("slow", slow, HookStage::Enrich).with_timeout(Duration::from_secs(1)),
+            "Hook slow failed: timed out after 1s",
+        ),
+        (
+            CommandHook::new("broken", broken, HookStage::Enrich),
+            "Hook broken failed: exit status: 3: model missing",
+        ),
+        (
+            CommandHook::new("garbled", garbled, HookStage::Enrich),
+            "Hook garbled failed: invalid output: expected ident at line 1 column 2",
+        ),
+    ];
+
+    for (hook, error) in cases {
+        let (server, _db) = create_test_server_with_state(|state| {
+            state.with_enrichers(EnricherRegistry::new().with(hook))
+        });
+
+        let id = add_content(&server, "https://example.com/failing").await;
+        let attempt = wait_for_attempt(&server, id).await;
+        assert_eq!(attempt["status"], "failed");
+        assert_eq!(attempt["last_error"], error);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_post_save_hook_sees_enriched_item() -> Result<()> {
+    let dir = ScratchDir::new("hooks-post-save");
+    let titler = script(&dir, "titler", r#"echo '{"title": "From hook"}'"#);
+    let notify = script(&dir, "notify", r#"cat > "$HOME/notified.json""#);
+
+    let hooks = Hooks::parse(&format!(
+        r#"
+        [[hooks]]
+        name = "notify"
+        command = "{notify}"
+        stage = "post_save"
+        working_dir = "{dir}"
+
+        [[hooks]]
+        name = "titler"
+        command = "{titler}"
+        "#,
+        notify = notify.display(),
+        titler = titler.display(),
+        dir = dir.path().display(),
+    ))?;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_enrichers(EnricherRegistry::new().with_hooks(hooks))
+    });
+
+    let id = add_content(&server, "https://example.com/notify").await;
+    let notified: Value =
+        serde_json::from_str(&wait_for_file(&dir.path().join("notified.json")).await)?;
+    assert_eq!(notified["stage"], "post_save");
+    assert_eq!(notified["item"]["id"], id);
+    assert_eq!(notified["item"]["title"], "From hook");
+
+    // Only the enrich hook is a pipeline step
+    let status: Value = server
+        .get(&format!("/api/v1/content/{id}/fetch-status"))
+        .await
+        .json();
+    assert_eq!(status.as_array().unwrap().len(), 1);
+    assert_eq!(status[0]["kind"], "titler");
+
+    Ok(())
+}
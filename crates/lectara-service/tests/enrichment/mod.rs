@@ -0,0 +1,12 @@
+pub mod attempts;
+pub mod citations;
+pub mod fetch;
+pub mod github;
+pub mod hooks;
+pub mod pdf;
+pub mod pipeline;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugins;
+pub mod site_rules;
+pub mod summary;
+pub mod threads;
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, http::header, response::Redirect, routing::get};
+use axum_test::TestServer;
+use lectara_service::enrichment::fetch::Fetcher;
+use lectara_service::enrichment::pdf::PdfExtractor;
+use lectara_service::validation::ValidationContext;
+use pdf_extract::content::{Content, Operation};
+use pdf_extract::{Document, Object, Stream, dictionary};
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_state;
+
+/// A PDF with one page per entry in `pages`, each showing that text
+pub fn build_pdf(pages: &[&str]) -> Vec<u8> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+
+    let kids: Vec<Object> = pages
+        .iter()
+        .map(|text| {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![100.into(), 600.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            })
+            .into()
+        })
+        .collect();
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).unwrap();
+    bytes
+}
+
+/// Serves a PDF, an HTML page and a redirect to the PDF on a local port
+async fn spawn_site() -> String {
+    let pdf = build_pdf(&["Attention is all you need", "Appendix"]);
+    let app = Router::new()
+        .route(
+            "/paper.pdf",
+            get(move || async move { ([(header::CONTENT_TYPE, "application/pdf")], pdf) }),
+        )
+        .route(
+            "/article",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                    "<html><body>Not a PDF</body></html>",
+                )
+            }),
+        )
+        .route(
+            "/download",
+            get(|| async { Redirect::temporary("/paper.pdf") }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn local_validation() -> ValidationContext {
+    ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    }
+}
+
+fn create_server() -> TestServer {
+    let (server, _db) = create_test_server_with_state(|state| {
+        state
+            .with_validation(local_validation())
+            .with_pdf_extractor(Some(PdfExtractor::new(Fetcher::new(local_validation()))))
+    });
+    server
+}
+
+async fn add_content(server: &TestServer, body: Value) -> u64 {
+    let response = server.post("/api/v1/content").json(&body).await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn wait_for_pdf(server: &TestServer, id: u64) -> Value {
+    for _ in 0..100 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["content_type"].is_null() {
+            return item;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("PDF text for item {id} was never stored");
+}
+
+#[tokio::test]
+async fn test_pdf_text_is_extracted() -> Result<()> {
+    let site = spawn_site().await;
+    let server = create_server();
+
+    let id = add_content(&server, json!({ "url": format!("{site}/paper.pdf") })).await;
+    let item = wait_for_pdf(&server, id).await;
+
+    assert_eq!(item["content_type"], "pdf");
+    assert_eq!(item["metadata"], json!({ "page_count": 2 }));
+    let body = item["body"].as_str().unwrap();
+    assert!(body.contains("Attention is all you need"), "{body}");
+    assert!(body.contains("Appendix"), "{body}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pdf_behind_redirect_is_extracted() -> Result<()> {
+    let site = spawn_site().await;
+    let server = create_server();
+
+    let id = add_content(&server, json!({ "url": format!("{site}/download") })).await;
+    let item = wait_for_pdf(&server, id).await;
+
+    assert_eq!(item["metadata"]["page_count"], 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_html_pages_are_left_alone() -> Result<()> {
+    let site = spawn_site().await;
+    let extractor = PdfExtractor::new(Fetcher::new(local_validation()));
+
+    assert_eq!(extractor.extract(&format!("{site}/article")).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_redirects_to_local_addresses_are_refused() -> Result<()> {
+    let site = spawn_site().await;
+    // Default rules reject the local stub, so following the redirect fails
+    let extractor = PdfExtractor::new(Fetcher::new(ValidationContext::default()));
+
+    assert!(
+        extractor
+            .extract(&format!("{site}/download"))
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oversized_documents_are_abandoned() -> Result<()> {
+    let site = spawn_site().await;
+    let extractor = PdfExtractor::new(Fetcher::with_max_bytes(local_validation(), 64));
+
+    assert!(
+        extractor
+            .extract(&format!("{site}/paper.pdf"))
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_submitted_body_is_not_replaced() -> Result<()> {
+    let site = spawn_site().await;
+    let server = create_server();
+
+    let id = add_content(
+        &server,
+        json!({ "url": format!("{site}/paper.pdf"), "body": "My own notes" }),
+    )
+    .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["body"], "My own notes");
+    assert!(item["content_type"].is_null());
+
+    Ok(())
+}
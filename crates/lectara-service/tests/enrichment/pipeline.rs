@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Router, http::header, routing::get};
+use axum_test::TestServer;
+use lectara_service::DefaultAppState;
+use lectara_service::enrichment::EnrichmentError;
+use lectara_service::enrichment::fetch::Fetcher;
+use lectara_service::enrichment::pdf::PdfExtractor;
+use lectara_service::enrichment::pipeline::{Enricher, EnricherRegistry, EnrichmentInput};
+use lectara_service::enrichment::summary::Summarizer;
+use lectara_service::models::{ContentItem, MetadataPatch};
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use super::pdf::build_pdf;
+use crate::common::server_utils::create_test_server_with_state;
+
+async fn spawn_site() -> String {
+    let pdf = build_pdf(&["Attention is all you need", "Appendix"]);
+    let app = Router::new().route(
+        "/paper.pdf",
+        get(move || async move { ([(header::CONTENT_TYPE, "application/pdf")], pdf) }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+struct Panics;
+
+#[async_trait]
+impl Enricher<DefaultAppState> for Panics {
+    fn name(&self) -> &'static str {
+        "panics"
+    }
+
+    fn applies(&self, _state: &DefaultAppState, _item: &ContentItem) -> bool {
+        true
+    }
+
+    async fn enrich(
+        &self,
+        _state: &DefaultAppState,
+        _input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        panic!("enricher bug");
+    }
+}
+
+struct Fails;
+
+#[async_trait]
+impl Enricher<DefaultAppState> for Fails {
+    fn name(&self) -> &'static str {
+        "fails"
+    }
+
+    fn applies(&self, _state: &DefaultAppState, _item: &ContentItem) -> bool {
+        true
+    }
+
+    async fn enrich(
+        &self,
+        _state: &DefaultAppState,
+        _input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        Err(EnrichmentError::InvalidResponse(
+            "upstream down".to_string(),
+        ))
+    }
+}
+
+/// Titles items after their URL's last path segment
+struct Titles;
+
+#[async_trait]
+impl Enricher<DefaultAppState> for Titles {
+    fn name(&self) -> &'static str {
+        "titles"
+    }
+
+    fn applies(&self, _state: &DefaultAppState, item: &ContentItem) -> bool {
+        item.title.is_none()
+    }
+
+    async fn enrich(
+        &self,
+        _state: &DefaultAppState,
+        input: &EnrichmentInput,
+    ) -> Result<Option<MetadataPatch>, EnrichmentError> {
+        let title = input.item().url.rsplit('/').next().map(str::to_string);
+        Ok(Some(MetadataPatch {
+            title,
+            ..MetadataPatch::default()
+        }))
+    }
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+/// Polls the fetch status until `count` enrichers have recorded a run
+async fn wait_for_attempts(server: &TestServer, id: u64, count: usize) -> Vec<Value> {
+    for _ in 0..100 {
+        let status: Value = server
+            .get(&format!("/api/v1/content/{id}/fetch-status"))
+            .await
+            .json();
+        let attempts = status.as_array().unwrap();
+        if attempts.len() == count {
+            return attempts.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{count} enrichers never finished for item {id}");
+}
+
+#[tokio::test]
+async fn test_later_steps_see_earlier_results() -> Result<()> {
+    let site = spawn_site().await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        let validation = ValidationContext {
+            allow_local_urls: true,
+            ..ValidationContext::default()
+        };
+        state
+            .with_validation(validation.clone())
+            .with_pdf_extractor(Some(PdfExtractor::new(Fetcher::new(validation))))
+            .with_summarizer(Some(Summarizer::Extractive))
+    });
+
+    let id = add_content(&server, &format!("{site}/paper.pdf")).await;
+    let attempts = wait_for_attempts(&server, id, 2).await;
+    assert_eq!(attempts[0]["kind"], "pdf");
+    assert_eq!(attempts[1]["kind"], "summary");
+    assert!(
+        attempts
+            .iter()
+            .all(|attempt| attempt["status"] == "succeeded")
+    );
+
+    // The summary was made from the body the PDF step extracted
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["summary"], "Attention is all you need");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failing_steps_do_not_stop_the_pipeline() -> Result<()> {
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_enrichers(
+            EnricherRegistry::new()
+                .with(Panics)
+                .with(Fails)
+                .with(Titles),
+        )
+    });
+
+    let id = add_content(&server, "https://example.com/notes/enrichers").await;
+    let attempts = wait_for_attempts(&server, id, 3).await;
+
+    let status = |kind: &str| {
+        attempts
+            .iter()
+            .find(|attempt| attempt["kind"] == kind)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(status("panics")["status"], "failed");
+    assert_eq!(status("panics")["last_error"], "Enricher panicked");
+    assert_eq!(
+        status("fails")["last_error"],
+        "Unexpected response: upstream down"
+    );
+    assert_eq!(status("titles")["status"], "succeeded");
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["title"], "enrichers");
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/fetch-status/retry"))
+        .await;
+    assert_eq!(
+        response.json::<Value>(),
+        json!({ "retrying": ["fails", "panics"] })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum_test::TestServer;
+use lectara_service::enrichment::pipeline::EnricherRegistry;
+use lectara_service::enrichment::plugins::{WasmProcessor, load_dir, plugin_engine};
+use serde_json::{Value, json};
+
+use crate::common::scratch::ScratchDir;
+use crate::common::server_utils::create_test_server_with_state;
+
+/// Titles every item "From wasm". The input is written after the patch
+/// at offset 1024 and ignored.
+const TITLER: &str = r#"(module
+    (memory (export "memory") 1)
+    (data (i32.const 0) "{\"title\": \"From wasm\"}")
+    (func (export "lectara_api_version") (result i32) i32.const 1)
+    (func (export "lectara_alloc") (param i32) (result i32) i32.const 1024)
+    (func (export "lectara_process") (param i32 i32) (result i64) i64.const 22))"#;
+
+/// Never returns
+const SPINNER: &str = r#"(module
+    (memory (export "memory") 1)
+    (func (export "lectara_api_version") (result i32) i32.const 1)
+    (func (export "lectara_alloc") (param i32) (result i32) i32.const 1024)
+    (func (export "lectara_process") (param i32 i32) (result i64)
+        (loop $forever (br $forever))
+        i64.const 0))"#;
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn wait_for_attempts(server: &TestServer, id: u64, count: usize) -> Vec<Value> {
+    for _ in 0..500 {
+        let status: Value = server
+            .get(&format!("/api/v1/content/{id}/fetch-status"))
+            .await
+            .json();
+        let attempts = status.as_array().unwrap();
+        if attempts.len() == count {
+            return attempts.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{count} plugins never finished for item {id}");
+}
+
+#[tokio::test]
+async fn test_plugins_patch_items_and_runaways_fail() -> Result<()> {
+    let engine = plugin_engine();
+    let titler = WasmProcessor::new(&engine, "titler", TITLER.as_bytes())?;
+    let spinner = WasmProcessor::new(&engine, "spinner", SPINNER.as_bytes())?;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_enrichers(EnricherRegistry::new().with(spinner).with(titler))
+    });
+
+    let id = add_content(&server, "https://example.com/plugged").await;
+    let attempts = wait_for_attempts(&server, id, 2).await;
+    let status = |kind: &str| {
+        attempts
+            .iter()
+            .find(|attempt| attempt["kind"] == kind)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(status("titler")["status"], "succeeded");
+    assert_eq!(status("spinner")["status"], "failed");
+    assert!(
+        status("spinner")["last_error"]
+            .as_str()
+            .unwrap()
+            .contains("all fuel consumed")
+    );
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert_eq!(item["title"], "From wasm");
+
+    Ok(())
+}
+
+#[test]
+fn test_load_dir_orders_by_file_name() -> Result<()> {
+    let dir = ScratchDir::new("plugins");
+    dir.write("b-titler.wat", TITLER);
+    dir.write("a-spinner.wat", SPINNER);
+    dir.write("README.md", "not a plugin");
+
+    let names: Vec<String> = load_dir(dir.path())?
+        .iter()
+        .map(|processor| processor.name().to_string())
+        .collect();
+    assert_eq!(names, ["a-spinner", "b-titler"]);
+    Ok(())
+}
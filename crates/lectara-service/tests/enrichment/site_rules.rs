@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, response::Html, routing::get};
+use axum_test::TestServer;
+use lectara_service::enrichment::site_rules::SiteRules;
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use crate::common::scratch::ScratchDir;
+use crate::common::server_utils::create_test_server_with_state;
+
+const PAGE: &str = r#"<html><head><title>Stubborn | Site</title></head><body>
+    <div id="nav">Home About</div>
+    <div class="post">
+      <h2 class="post-title">Extracting stubborn pages</h2>
+      <span class="meta">by <b>Ada Lovelace</b></span>
+      <div class="post-body">
+        <p>Some sites hide their articles in unusual markup.</p>
+        <div class="share">Share this</div>
+        <p>Rules point at the right elements.</p>
+      </div>
+    </div>
+</body></html>"#;
+
+async fn spawn_site() -> String {
+    let app = Router::new().route("/post", get(|| async { Html(PAGE) }));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn add_content(server: &TestServer, url: &str) -> u64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn wait_for_enrichment(server: &TestServer, id: u64) -> Value {
+    for _ in 0..100 {
+        let status: Value = server
+            .get(&format!("/api/v1/content/{id}/fetch-status"))
+            .await
+            .json();
+        if let Some(attempt) = status.as_array().unwrap().first() {
+            assert_eq!(attempt["kind"], "site");
+            assert_eq!(attempt["status"], "succeeded");
+            return server.get(&format!("/api/v1/content/{id}")).await.json();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("site rules never ran for item {id}");
+}
+
+#[tokio::test]
+async fn test_site_rules_extract_and_reload() -> Result<()> {
+    let site = spawn_site().await;
+    let dir = ScratchDir::new("site-rules");
+    dir.write(
+        "example.toml",
+        r#"
+        hosts = ["127.0.0.1"]
+        title = "//div[@class='post']/h2"
+        author = ".meta b"
+        body = "div.post-body"
+        strip = [".share"]
+        "#,
+    );
+    let rules = SiteRules::load(dir.path())?.with_reload_check_interval(Duration::ZERO);
+    let (server, _db) = create_test_server_with_state(|state| {
+        state
+            .with_validation(ValidationContext {
+                allow_local_urls: true,
+                ..ValidationContext::default()
+            })
+            .with_site_rules(Some(rules))
+    });
+
+    let id = add_content(&server, &format!("{site}/post")).await;
+    let item = wait_for_enrichment(&server, id).await;
+    assert_eq!(item["title"], "Extracting stubborn pages");
+    assert_eq!(item["author"], "Ada Lovelace");
+    assert_eq!(
+        item["body"],
+        "Some sites hide their articles in unusual markup.\n\n\
+         Rules point at the right elements."
+    );
+
+    // Edited rules apply to the next item without a restart
+    dir.write(
+        "example.toml",
+        r#"
+        hosts = ["127.0.0.1"]
+        title = "title"
+        "#,
+    );
+    let id = add_content(&server, &format!("{site}/post?edited")).await;
+    let item = wait_for_enrichment(&server, id).await;
+    assert_eq!(item["title"], "Stubborn | Site");
+    assert_eq!(item["author"], Value::Null);
+    assert_eq!(item["body"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sites_without_rules_are_skipped() -> Result<()> {
+    let dir = ScratchDir::new("site-rules-other");
+    dir.write("example.org.toml", r#"title = "h1""#);
+    let rules = SiteRules::load(dir.path())?;
+    assert!(rules.for_host("blog.example.org").is_some());
+    assert!(rules.for_host("example.com").is_none());
+
+    let (server, _db) = create_test_server_with_state(|state| state.with_site_rules(Some(rules)));
+    let id = add_content(&server, "https://example.com/elsewhere").await;
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let status: Value = server
+        .get(&format!("/api/v1/content/{id}/fetch-status"))
+        .await
+        .json();
+    assert_eq!(status, json!([]));
+
+    Ok(())
+}
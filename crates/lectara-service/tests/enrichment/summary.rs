@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{
+    Json, Router, http::HeaderMap, http::StatusCode, response::IntoResponse, routing::post,
+};
+use axum_test::TestServer;
+use lectara_service::enrichment::summary::{LlmSummarizer, Summarizer};
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_state;
+
+const ARTICLE: &str = "\
+    Sourdough bread needs a healthy starter before anything else.\n\
+    My neighbour's cat watched the whole time from the windowsill.\n\
+    Feed the starter flour and water until the starter doubles reliably.\n\
+    The weather was unusually warm for October that week.\n\
+    Bread made with a strong starter rises well and tastes of sourdough.\n\
+    Anyway, that is enough about my kitchen for one day.";
+
+/// A chat completions endpoint that answers with a canned summary, and
+/// checks the request carries the model, the article and the API key
+async fn spawn_llm() -> String {
+    let app = Router::new().route(
+        "/v1/chat/completions",
+        post(
+            |headers: HeaderMap, Json(request): Json<Value>| async move {
+                let authorized = headers
+                    .get("authorization")
+                    .is_some_and(|value| value == "Bearer test-key");
+                let article = request["messages"][1]["content"].as_str().unwrap_or("");
+                if !authorized || request["model"] != "tiny" || !article.starts_with("Bread\n\n") {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+                Json(json!({
+                    "choices": [{ "message": { "role": "assistant", "content": " A summary.\n" } }]
+                }))
+                .into_response()
+            },
+        ),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}/v1")
+}
+
+async fn add_content(server: &TestServer, item: Value) -> u64 {
+    let response = server.post("/api/v1/content").json(&item).await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_u64().unwrap()
+}
+
+async fn wait_for_summary(server: &TestServer, id: u64) -> Value {
+    for _ in 0..50 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["summary"].is_null() {
+            return item["summary"].clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("item {id} was never summarized");
+}
+
+#[tokio::test]
+async fn test_extractive_summary_is_listed() -> Result<()> {
+    let (server, _db) =
+        create_test_server_with_state(|state| state.with_summarizer(Some(Summarizer::Extractive)));
+
+    let id = add_content(
+        &server,
+        json!({ "url": "https://example.com/bread", "body": ARTICLE }),
+    )
+    .await;
+
+    let expected = "Sourdough bread needs a healthy starter before anything else. \
+                    Feed the starter flour and water until the starter doubles reliably. \
+                    Bread made with a strong starter rises well and tastes of sourdough.";
+    assert_eq!(wait_for_summary(&server, id).await, expected);
+
+    let list: Value = server.get("/api/v1/content").await.json();
+    assert_eq!(list["items"][0]["summary"], expected);
+
+    // The summary isn't client metadata, so saving again still matches
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/bread", "body": ARTICLE }))
+        .await
+        .assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_llm_summary() -> Result<()> {
+    let llm = spawn_llm().await;
+    let (server, _db) = create_test_server_with_state(|state| {
+        state.with_summarizer(Some(Summarizer::Llm(LlmSummarizer::new(
+            &llm,
+            "tiny",
+            Some("test-key"),
+        ))))
+    });
+
+    let id = add_content(
+        &server,
+        json!({ "url": "https://example.com/bread", "title": "Bread", "body": ARTICLE }),
+    )
+    .await;
+
+    assert_eq!(wait_for_summary(&server, id).await, "A summary.");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bodiless_items_are_not_summarized() -> Result<()> {
+    let (server, _db) =
+        create_test_server_with_state(|state| state.with_summarizer(Some(Summarizer::Extractive)));
+
+    let id = add_content(&server, json!({ "url": "https://example.com/empty" })).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+    assert!(item["summary"].is_null());
+    let status: Value = server
+        .get(&format!("/api/v1/content/{id}/fetch-status"))
+        .await
+        .json();
+    assert_eq!(status, json!([]));
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{Router, http::StatusCode, routing::get};
+use axum_test::TestServer;
+use lectara_service::enrichment::threads::ThreadResolver;
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_state;
+
+const STATUS: &str = r#"{
+    "id": "2",
+    "in_reply_to_id": "1",
+    "content": "<p>Second &amp; last</p>",
+    "account": {"id": "7", "acct": "ada", "display_name": "Ada Lovelace"}
+}"#;
+
+const CONTEXT: &str = r#"{
+    "ancestors": [{
+        "id": "1",
+        "in_reply_to_id": null,
+        "content": "<p>First post</p>",
+        "account": {"id": "7", "acct": "ada", "display_name": "Ada Lovelace"}
+    }],
+    "descendants": []
+}"#;
+
+/// Serves a canned two-post Mastodon thread on a local port
+async fn spawn_instance() -> String {
+    let app = Router::new()
+        .route("/api/v1/statuses/2", get(|| async { STATUS }))
+        .route("/api/v1/statuses/2/context", get(|| async { CONTEXT }))
+        .route(
+            "/api/v1/statuses/{id}",
+            get(|| async { StatusCode::NOT_FOUND }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn create_server() -> TestServer {
+    // The stub instance listens on a loopback address
+    let (server, _db) = create_test_server_with_state(|state| {
+        state
+            .with_validation(ValidationContext {
+                allow_local_urls: true,
+                ..ValidationContext::default()
+            })
+            .with_thread_resolver(Some(ThreadResolver::default()))
+    });
+    server
+}
+
+async fn add_and_wait_for_body(server: &TestServer, url: &str) -> Value {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    for _ in 0..50 {
+        let item: Value = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["body"].is_null() {
+            return item;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("thread for {url} was never stored");
+}
+
+#[tokio::test]
+async fn test_mastodon_thread_is_unrolled_into_body() -> Result<()> {
+    let instance = spawn_instance().await;
+    let server = create_server();
+    let url = format!("{instance}/@ada/2");
+
+    let item = add_and_wait_for_body(&server, &url).await;
+
+    assert_eq!(item["body"], "First post\n\nSecond & last");
+    assert_eq!(item["author"], "Ada Lovelace");
+
+    // Saving the bare URL again still matches the enriched item
+    let again = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    again.assert_status_ok();
+    assert_eq!(again.json::<Value>()["id"], item["id"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_submitted_metadata_is_kept() -> Result<()> {
+    let instance = spawn_instance().await;
+    let server = create_server();
+
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": format!("{instance}/@ada/2"), "author": "Someone" }))
+        .await;
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    let mut item = Value::Null;
+    for _ in 0..50 {
+        item = server.get(&format!("/api/v1/content/{id}")).await.json();
+        if !item["body"].is_null() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(item["body"], "First post\n\nSecond & last");
+    assert_eq!(item["author"], "Someone");
+
+    Ok(())
+}
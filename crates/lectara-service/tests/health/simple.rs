@@ -0,0 +1,80 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use diesel_migrations::MigrationHarness;
+use lectara_service::migrations::MIGRATIONS;
+use serde_json::Value;
+
+use crate::common::server_utils::create_test_server;
+
+#[tokio::test]
+async fn test_health_ok() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/health").await;
+
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_eq!(json["status"], "ok");
+    assert_eq!(json["build"]["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["build"]["git_sha"].is_string());
+    assert!(json["build"]["built_at"].is_string());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ready_reports_applied_migrations() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/ready").await;
+
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_eq!(json["status"], "ready");
+    let applied = json["migrations"]["applied"].as_array().unwrap();
+    assert_eq!(
+        applied[0].as_str(),
+        Some("2025-07-01-000407_create_content_items")
+    );
+    assert!(json["migrations"]["pending"].as_array().unwrap().is_empty());
+
+    // The newest applied migration is the one this binary expects
+    let newest = applied.last().unwrap().as_str().unwrap();
+    let schema_version = json["schema_version"].as_str().unwrap();
+    assert!(
+        newest.replace('-', "").starts_with(schema_version),
+        "{newest} vs {schema_version}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ready_unavailable_with_pending_migrations() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    db.lock()
+        .unwrap()
+        .revert_last_migration(MIGRATIONS)
+        .expect("Failed to revert migration");
+
+    let response = server.get("/ready").await;
+
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    let json: Value = response.json();
+    assert_eq!(json["status"], "pending_migrations");
+    assert_eq!(json["migrations"]["pending"].as_array().unwrap().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_version_reports_api_and_build() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/api/v1/version").await;
+
+    response.assert_status_ok();
+    let json: Value = response.json();
+    assert_eq!(json["api_version"], 1);
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["git_sha"].is_string());
+    Ok(())
+}
@@ -1,2 +1,8 @@
 mod api;
 mod common;
+mod enrichment;
+mod health;
+mod migrations;
+mod repositories;
+mod tenants;
+mod web;
@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use diesel::migration::MigrationSource;
+use diesel::sqlite::Sqlite;
+use diesel_migrations::MigrationHarness;
+use lectara_service::bodies;
+use lectara_service::migrations::{MIGRATIONS, migration_status};
+use lectara_service::models::NewContentItem;
+use lectara_service::repositories::{ContentRepository, SqliteContentRepository};
+
+use crate::common::establish_test_connection;
+
+#[test]
+fn test_every_migration_reverts_and_reapplies() {
+    let mut conn = establish_test_connection();
+    let total = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .unwrap()
+        .len();
+
+    // Step down one migration at a time so a failing down script is named
+    for remaining in (0..total).rev() {
+        let version = conn
+            .revert_last_migration(MIGRATIONS)
+            .unwrap_or_else(|err| panic!("Failed to revert migration: {err}"));
+        let status = migration_status(&mut conn).unwrap();
+        assert_eq!(
+            status.applied.len(),
+            remaining,
+            "migration {version} did not revert cleanly"
+        );
+    }
+
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to reapply migrations");
+    assert!(migration_status(&mut conn).unwrap().pending.is_empty());
+}
+
+#[tokio::test]
+async fn test_bodies_survive_down_and_up() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let created = repo
+        .create(&NewContentItem::new(
+            "https://example.com/kept".to_string(),
+            None,
+            None,
+            Some("Body through a round trip".to_string()),
+        )?)
+        .await?;
+
+    {
+        let mut conn = db.lock().unwrap();
+        // Back to the inline body column, then forward again
+        while migration_status(&mut conn)
+            .unwrap()
+            .applied
+            .iter()
+            .any(|name| name.starts_with("2025-07-16-200000"))
+        {
+            conn.revert_last_migration(MIGRATIONS)
+                .expect("Failed to revert migration");
+        }
+        conn.run_pending_migrations(MIGRATIONS)
+            .expect("Failed to reapply migrations");
+        bodies::migrate_legacy_bodies(&mut conn)?;
+    }
+
+    let found = repo.find_by_id(created.id).await?.unwrap();
+    assert_eq!(found.body.as_deref(), Some("Body through a round trip"));
+    assert_eq!(found.body_hash, created.body_hash);
+    Ok(())
+}
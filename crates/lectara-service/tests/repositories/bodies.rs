@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use diesel::prelude::*;
+use lectara_service::bodies;
+use lectara_service::models::NewContentItem;
+use lectara_service::repositories::{ContentRepository, SqliteContentRepository};
+use lectara_service::schema::{body_blobs, content_items};
+
+use crate::common::establish_test_connection;
+
+fn new_item(url: &str, body: &str) -> NewContentItem {
+    NewContentItem::new(url.to_string(), None, None, Some(body.to_string())).unwrap()
+}
+
+fn ref_count(conn: &mut SqliteConnection, hash: &str) -> Option<i32> {
+    body_blobs::table
+        .find(hash)
+        .select(body_blobs::ref_count)
+        .first(conn)
+        .optional()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_identical_bodies_are_stored_once() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let first = repo
+        .create(&new_item("https://example.com/original", "Syndicated text"))
+        .await?;
+    let second = repo
+        .create(&new_item(
+            "https://mirror.example.org/copy",
+            "Syndicated text",
+        ))
+        .await?;
+
+    let hash = first.body_hash.clone().unwrap();
+    assert_eq!(second.body_hash.as_deref(), Some(hash.as_str()));
+    assert_eq!(hash, bodies::body_hash("Syndicated text"));
+
+    let mut conn = db.lock().unwrap();
+    let blobs: i64 = body_blobs::table.count().get_result(&mut *conn)?;
+    assert_eq!(blobs, 1);
+    assert_eq!(ref_count(&mut conn, &hash), Some(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bodies_read_back_through_hash() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let created = repo
+        .create(&new_item("https://example.com/read-back", "Body text"))
+        .await?;
+    let found = repo.find_by_id(created.id).await?.unwrap();
+
+    assert_eq!(found.body.as_deref(), Some("Body text"));
+    assert_eq!(found.body_hash, created.body_hash);
+    Ok(())
+}
+
+#[test]
+fn test_release_deletes_unreferenced_blob() -> Result<()> {
+    let mut conn = establish_test_connection();
+
+    let hash = bodies::store_body(&mut conn, "Shared")?;
+    bodies::store_body(&mut conn, "Shared")?;
+
+    bodies::release_body(&mut conn, &hash)?;
+    assert_eq!(ref_count(&mut conn, &hash), Some(1));
+
+    bodies::release_body(&mut conn, &hash)?;
+    assert_eq!(ref_count(&mut conn, &hash), None);
+    Ok(())
+}
+
+#[test]
+fn test_legacy_bodies_are_migrated() -> Result<()> {
+    let mut conn = establish_test_connection();
+
+    diesel::sql_query(
+        "INSERT INTO content_items (id, url) VALUES
+            (1, 'https://example.com/a'), (2, 'https://example.com/b')",
+    )
+    .execute(&mut conn)?;
+    diesel::sql_query(
+        "INSERT INTO content_bodies (content_id, body) VALUES (1, 'Same'), (2, 'Same')",
+    )
+    .execute(&mut conn)?;
+
+    assert_eq!(bodies::migrate_legacy_bodies(&mut conn)?, 2);
+    assert_eq!(bodies::migrate_legacy_bodies(&mut conn)?, 0);
+
+    let hash = bodies::body_hash("Same");
+    assert_eq!(ref_count(&mut conn, &hash), Some(2));
+
+    let hashes: Vec<Option<String>> = content_items::table
+        .select(content_items::body_hash)
+        .load(&mut conn)?;
+    assert_eq!(hashes, vec![Some(hash.clone()), Some(hash)]);
+    Ok(())
+}
+
+fn search_hits(conn: &mut SqliteConnection, term: &str) -> i64 {
+    diesel::select(diesel::dsl::sql::<diesel::sql_types::BigInt>(&format!(
+        "(SELECT COUNT(*) FROM content_search WHERE content_search MATCH '{term}')"
+    )))
+    .get_result(conn)
+    .unwrap()
+}
+
+const HTML_BODY: &str = r#"<article class="entry"><h1>Heading</h1><p>First paragraph</p><script>tracker()</script></article>"#;
+
+#[tokio::test]
+async fn test_html_bodies_store_plain_text() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let created = repo
+        .create(&new_item("https://example.com/html", HTML_BODY))
+        .await?;
+    let found = repo.find_by_id(created.id).await?.unwrap();
+
+    assert_eq!(found.body.as_deref(), Some(HTML_BODY));
+    assert_eq!(found.plain_text(), Some("Heading\n\nFirst paragraph"));
+    assert_eq!(found.word_count(), 3);
+
+    let mut conn = db.lock().unwrap();
+    assert_eq!(search_hits(&mut conn, "paragraph"), 1);
+    assert_eq!(search_hits(&mut conn, "entry"), 0);
+    assert_eq!(search_hits(&mut conn, "tracker"), 0);
+    Ok(())
+}
+
+#[test]
+fn test_plain_text_is_backfilled() -> Result<()> {
+    let mut conn = establish_test_connection();
+
+    diesel::sql_query("INSERT INTO content_items (id, url) VALUES (1, 'https://example.com/a')")
+        .execute(&mut conn)?;
+    let hash = bodies::store_body(&mut conn, HTML_BODY)?;
+    diesel::update(body_blobs::table.find(&hash))
+        .set(body_blobs::text.eq(None::<String>))
+        .execute(&mut conn)?;
+    diesel::update(content_items::table.find(1))
+        .set(content_items::body_hash.eq(&hash))
+        .execute(&mut conn)?;
+    assert_eq!(search_hits(&mut conn, "entry"), 1);
+
+    assert_eq!(bodies::fill_plain_text(&mut conn)?, 1);
+    assert_eq!(bodies::fill_plain_text(&mut conn)?, 0);
+    assert_eq!(search_hits(&mut conn, "paragraph"), 1);
+    assert_eq!(search_hits(&mut conn, "entry"), 0);
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use lectara_service::database::Database;
+use lectara_service::errors::ApiError;
+use lectara_service::models::{MetadataEdit, MetadataField, MetadataPatch, NewContentItem};
+use lectara_service::repositories::{
+    BulkAction, BulkSelection, ContentFilter, ContentRepository, QueryCache,
+    SqliteContentRepository,
+};
+
+use crate::common::{establish_test_connection, test_utils};
+
+fn new_item(title: &str) -> NewContentItem {
+    NewContentItem::new(
+        "https://example.com/raced".to_string(),
+        Some(title.to_string()),
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+// The add-content handler looks a URL up before inserting it, so these
+// tests call `create` directly to reproduce an insert losing that race.
+
+#[tokio::test]
+async fn test_create_duplicate_identical_returns_existing() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let first = repo.create(&new_item("Same Title")).await?;
+    let second = repo.create(&new_item("Same Title")).await?;
+
+    assert_eq!(first.id, second.id);
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_duplicate_different_metadata_conflicts() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    repo.create(&new_item("First Title")).await?;
+    let result = repo.create(&new_item("Second Title")).await;
+
+    assert!(matches!(
+        result,
+        Err(ApiError::DuplicateUrlDifferentMetadata(_))
+    ));
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 1);
+
+    Ok(())
+}
+
+fn item_at(url: &str, title: &str) -> NewContentItem {
+    NewContentItem::new(url.to_string(), Some(title.to_string()), None, None).unwrap()
+}
+
+#[tokio::test]
+async fn test_create_many_inserts_all_items() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let items = repo
+        .create_many(&[
+            item_at("https://example.com/one", "One"),
+            item_at("https://example.com/two", "Two"),
+            item_at("https://example.com/one", "One"),
+        ])
+        .await?;
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].id, items[2].id);
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_many_conflict_rolls_back_batch() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    repo.create(&item_at("https://example.com/existing", "Original"))
+        .await?;
+    let result = repo
+        .create_many(&[
+            item_at("https://example.com/new", "New"),
+            item_at("https://example.com/existing", "Changed"),
+        ])
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ApiError::DuplicateUrlDifferentMetadata(_))
+    ));
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_by_urls_returns_matches_only() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let saved = repo
+        .create_many(&[
+            item_at("https://example.com/a", "A"),
+            item_at("https://example.com/b", "B"),
+        ])
+        .await?;
+
+    let found = repo
+        .find_by_urls(&[
+            "https://example.com/b".to_string(),
+            "https://example.com/missing".to_string(),
+            "https://example.com/a".to_string(),
+        ])
+        .await?;
+
+    let ids: Vec<i32> = found.iter().map(|item| item.id).collect();
+    assert_eq!(ids, vec![saved[0].id, saved[1].id]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_apply_enrichment_fills_only_missing_fields() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db.clone());
+
+    let created = repo.create(&new_item("Kept Title")).await?;
+    let patch = MetadataPatch {
+        title: Some("Found Title".to_string()),
+        author: Some("Found Author".to_string()),
+        body: Some("Found body".to_string()),
+        ..MetadataPatch::default()
+    };
+    let enriched = repo.apply_enrichment(created.id, &patch).await?.unwrap();
+
+    assert_eq!(enriched.title.as_deref(), Some("Kept Title"));
+    assert_eq!(enriched.author.as_deref(), Some("Found Author"));
+    assert_eq!(enriched.body.as_deref(), Some("Found body"));
+    assert_eq!(
+        enriched.enriched_fields(),
+        vec![MetadataField::Author, MetadataField::Body]
+    );
+
+    // Resubmitting without the enriched fields is still identical, but a
+    // different explicit value is not
+    assert!(
+        new_item("Kept Title")
+            .metadata_differences(&enriched)
+            .is_empty()
+    );
+    let mut with_author = new_item("Kept Title");
+    with_author.author = Some("Other Author".to_string());
+    assert_eq!(
+        with_author.metadata_differences(&enriched),
+        vec![MetadataField::Author]
+    );
+
+    assert!(repo.apply_enrichment(9999, &patch).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_lookups_follow_writes() -> Result<()> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let repo = SqliteContentRepository::new(db).with_cache(QueryCache::new(1024 * 1024));
+    let url = "https://example.com/raced";
+
+    // A cached miss doesn't hide the item once it is saved
+    assert!(repo.find_by_url(url).await?.is_none());
+    let created = repo.create(&new_item("Cached")).await?;
+    assert_eq!(
+        repo.find_by_url(url).await?.map(|item| item.id),
+        Some(created.id)
+    );
+
+    assert!(repo.find_by_id(created.id).await?.unwrap().author.is_none());
+    let edit = MetadataEdit {
+        author: Some("Edited Author".to_string()),
+        ..MetadataEdit::default()
+    };
+    repo.edit(created.id, &edit).await?;
+    let found = repo.find_by_id(created.id).await?.unwrap();
+    assert_eq!(found.author.as_deref(), Some("Edited Author"));
+    let found = repo.find_by_url(url).await?.unwrap();
+    assert_eq!(found.author.as_deref(), Some("Edited Author"));
+
+    repo.bulk_update(
+        BulkAction::Delete,
+        &BulkSelection::Ids(vec![created.id]),
+        false,
+    )
+    .await?;
+    assert!(repo.find_by_id(created.id).await?.is_none());
+    assert!(repo.find_by_url(url).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_totals_follow_every_write() -> Result<()> {
+    let db = Database::new(Arc::new(Mutex::new(establish_test_connection())));
+    let repo = SqliteContentRepository::new(db.clone());
+    let repositories = ContentFilter {
+        content_type: Some("repository".to_string()),
+        ..ContentFilter::default()
+    };
+
+    let created = repo.create(&new_item("Untyped")).await?;
+    assert_eq!(repo.count(&repositories).await?, 0);
+
+    // Enrichment changes which filters the item matches without a create
+    // or delete
+    let patch = MetadataPatch {
+        content_type: Some("repository".to_string()),
+        ..MetadataPatch::default()
+    };
+    repo.apply_enrichment(created.id, &patch).await?;
+    assert_eq!(repo.count(&repositories).await?, 1);
+
+    // So does a write through another repository on the same database
+    let other = SqliteContentRepository::new(db);
+    other
+        .bulk_update(
+            BulkAction::Delete,
+            &BulkSelection::Ids(vec![created.id]),
+            false,
+        )
+        .await?;
+    assert_eq!(repo.count(&repositories).await?, 0);
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, TimeDelta};
+use lectara_service::models::{NewContentItem, NewFetchAttempt};
+use lectara_service::repositories::{
+    ContentRepository, FetchAttemptRepository, SqliteContentRepository,
+    SqliteFetchAttemptRepository,
+};
+
+use crate::common::establish_test_connection;
+
+fn at(hour: i64) -> NaiveDateTime {
+    DateTime::UNIX_EPOCH.naive_utc() + TimeDelta::hours(hour)
+}
+
+fn failed(content_id: i32, kind: &str, next_attempt_at: Option<NaiveDateTime>) -> NewFetchAttempt {
+    NewFetchAttempt {
+        content_id,
+        kind: kind.to_string(),
+        status: "failed".to_string(),
+        attempts: 1,
+        last_error: Some("Request failed".to_string()),
+        last_attempt_at: at(0),
+        next_attempt_at,
+    }
+}
+
+async fn setup() -> Result<(SqliteFetchAttemptRepository, i32)> {
+    let db = Arc::new(Mutex::new(establish_test_connection()));
+    let item = SqliteContentRepository::new(db.clone())
+        .create(&NewContentItem::new(
+            "https://example.com/paper.pdf".to_string(),
+            None,
+            None,
+            None,
+        )?)
+        .await?;
+    Ok((SqliteFetchAttemptRepository::new(db), item.id))
+}
+
+#[tokio::test]
+async fn test_record_replaces_previous_attempt_of_same_kind() -> Result<()> {
+    let (repo, id) = setup().await?;
+
+    repo.record(&failed(id, "pdf", Some(at(1)))).await?;
+    repo.record(&failed(id, "citation", Some(at(1)))).await?;
+    let stored = repo
+        .record(&NewFetchAttempt {
+            status: "succeeded".to_string(),
+            attempts: 2,
+            last_error: None,
+            next_attempt_at: None,
+            ..failed(id, "pdf", None)
+        })
+        .await?;
+
+    assert_eq!(stored.attempts, 2);
+    assert_eq!(stored.last_error, None);
+    let kinds: Vec<_> = repo
+        .list_for_content(id)
+        .await?
+        .into_iter()
+        .map(|attempt| (attempt.kind, attempt.status))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("citation".to_string(), "failed".to_string()),
+            ("pdf".to_string(), "succeeded".to_string()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_due_returns_only_due_retries() -> Result<()> {
+    let (repo, id) = setup().await?;
+
+    repo.record(&failed(id, "pdf", Some(at(3)))).await?;
+    repo.record(&failed(id, "citation", Some(at(1)))).await?;
+    repo.record(&failed(id, "thread", None)).await?;
+
+    let due: Vec<_> = repo
+        .list_due(at(3), 10)
+        .await?
+        .into_iter()
+        .map(|attempt| attempt.kind)
+        .collect();
+    assert_eq!(due, ["citation", "pdf"]);
+    assert_eq!(repo.list_due(at(2), 10).await?.len(), 1);
+    assert!(repo.list_due(at(0), 10).await?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::MigrationHarness;
+use lectara_service::errors::ApiError;
+use lectara_service::migrations::MIGRATIONS;
+use lectara_service::models::NewContentItem;
+use lectara_service::repositories::{ContentRepository, SqliteContentRepository};
+
+use crate::common::scratch::ScratchDir;
+
+fn item(url: &str) -> Result<NewContentItem> {
+    Ok(NewContentItem::new(url.to_string(), None, None, None)?)
+}
+
+/// A migrated database file with one item, and a second connection to it
+/// standing in for another process
+async fn open(dir: &ScratchDir) -> Result<(SqliteContentRepository, SqliteConnection)> {
+    let url = dir.path().join("lectara.db").display().to_string();
+    let mut connection = SqliteConnection::establish(&url)?;
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    let repo = SqliteContentRepository::new(Arc::new(Mutex::new(connection)));
+    // The first write also loads the search index, which reports a lock as
+    // a failure to set the index up rather than as a lock
+    repo.create(&item("https://example.com/first")?).await?;
+    Ok((repo, SqliteConnection::establish(&url)?))
+}
+
+#[tokio::test]
+async fn test_write_waits_out_a_brief_lock() -> Result<()> {
+    let dir = ScratchDir::new("locked-brief");
+    let (repo, mut other) = open(&dir).await?;
+
+    diesel::sql_query("BEGIN EXCLUSIVE").execute(&mut other)?;
+    let release = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        diesel::sql_query("COMMIT").execute(&mut other).unwrap();
+    });
+
+    let created = repo.create(&item("https://example.com/patient")?).await?;
+    assert_eq!(created.url, "https://example.com/patient");
+    release.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_gives_up_on_a_held_lock() -> Result<()> {
+    let dir = ScratchDir::new("locked-held");
+    let (repo, mut other) = open(&dir).await?;
+
+    diesel::sql_query("BEGIN EXCLUSIVE").execute(&mut other)?;
+    let result = repo.create(&item("https://example.com/blocked")?).await;
+    assert!(matches!(result, Err(ApiError::Busy)), "{result:?}");
+
+    diesel::sql_query("COMMIT").execute(&mut other)?;
+    assert!(
+        repo.find_by_url("https://example.com/blocked")
+            .await?
+            .is_none()
+    );
+
+    Ok(())
+}
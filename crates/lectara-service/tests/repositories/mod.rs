@@ -0,0 +1,6 @@
+pub mod bodies;
+pub mod content;
+pub mod fetch_attempts;
+pub mod locked;
+pub mod read_pool;
+pub mod reconnect;
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::MigrationHarness;
+use lectara_service::database::{Database, ReadPool};
+use lectara_service::migrations::MIGRATIONS;
+use lectara_service::models::NewContentItem;
+use lectara_service::repositories::{ContentRepository, SqliteContentRepository};
+
+use crate::common::scratch::ScratchDir;
+
+/// A migrated database file in `dir`, read through `readers` connections
+fn database_in(dir: &ScratchDir, readers: usize) -> Result<Database> {
+    let url = dir.path().join("lectara.db").display().to_string();
+    let mut connection = SqliteConnection::establish(&url)?;
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    let pool = ReadPool::open(&url, readers).map_err(anyhow::Error::msg)?;
+    Ok(Database::new(Arc::new(Mutex::new(connection))).with_readers(pool))
+}
+
+#[tokio::test]
+async fn test_reads_see_committed_writes() -> Result<()> {
+    let dir = ScratchDir::new("read-pool-writes");
+    let repo = SqliteContentRepository::new(database_in(&dir, 2)?);
+
+    let saves: Vec<_> = (0..10)
+        .map(|index| {
+            let repo = repo.clone();
+            tokio::spawn(async move {
+                let item =
+                    NewContentItem::new(format!("https://example.com/{index}"), None, None, None)?;
+                repo.create(&item).await.map_err(anyhow::Error::from)
+            })
+        })
+        .collect();
+    for save in saves {
+        let saved = save.await??;
+        assert!(repo.find_by_id(saved.id).await?.is_some());
+    }
+    assert!(repo.find_by_url("https://example.com/9").await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_connections_are_read_only() -> Result<()> {
+    let dir = ScratchDir::new("read-pool-read-only");
+    let database = database_in(&dir, 1)?;
+
+    let write = diesel::sql_query("DELETE FROM content_items").execute(&mut *database.read());
+    assert!(write.is_err());
+    Ok(())
+}
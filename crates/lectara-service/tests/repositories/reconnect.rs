@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::MigrationHarness;
+use lectara_service::connection::{ConnectionHealth, ConnectionMonitor};
+use lectara_service::migrations::MIGRATIONS;
+use lectara_service::models::NewContentItem;
+use lectara_service::repositories::{ContentRepository, SqliteContentRepository};
+
+use crate::common::scratch::ScratchDir;
+
+fn item(url: &str) -> Result<NewContentItem> {
+    Ok(NewContentItem::new(url.to_string(), None, None, None)?)
+}
+
+/// A migrated database file holding one item saved with `url`
+async fn database_with(path: &Path, url: &str) -> Result<SqliteContentRepository> {
+    let mut connection = SqliteConnection::establish(&path.display().to_string())?;
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    let repo = SqliteContentRepository::new(Arc::new(Mutex::new(connection)));
+    repo.create(&item(url)?).await?;
+    Ok(repo)
+}
+
+/// The connection to the database in `dir` and a monitor of it
+fn connection_for(dir: &ScratchDir) -> Result<(Arc<Mutex<SqliteConnection>>, ConnectionMonitor)> {
+    let url = dir.path().join("lectara.db").display().to_string();
+    let db = Arc::new(Mutex::new(SqliteConnection::establish(&url)?));
+    let monitor = ConnectionMonitor::new(db.clone(), url);
+    Ok((db, monitor))
+}
+
+/// A repository on the database in `dir` and a monitor of its connection
+fn monitor_for(dir: &ScratchDir) -> Result<(SqliteContentRepository, ConnectionMonitor)> {
+    let (db, monitor) = connection_for(dir)?;
+    Ok((SqliteContentRepository::new(db), monitor))
+}
+
+#[tokio::test]
+async fn test_reconnects_when_file_is_replaced() -> Result<()> {
+    let dir = ScratchDir::new("reconnect-replaced");
+    let live = dir.path().join("lectara.db");
+    drop(database_with(&live, "https://example.com/before").await?);
+    let (repo, mut monitor) = monitor_for(&dir)?;
+    assert_eq!(monitor.check(), ConnectionHealth::Healthy);
+
+    let restored = dir.path().join("restored.db");
+    drop(database_with(&restored, "https://example.com/restored").await?);
+    std::fs::rename(&restored, &live)?;
+
+    // The open connection still reads the old, now unlinked, file
+    assert!(
+        repo.find_by_url("https://example.com/before")
+            .await?
+            .is_some()
+    );
+
+    assert_eq!(monitor.check(), ConnectionHealth::Reconnected);
+    assert!(
+        repo.find_by_url("https://example.com/before")
+            .await?
+            .is_none()
+    );
+    assert!(
+        repo.find_by_url("https://example.com/restored")
+            .await?
+            .is_some()
+    );
+    assert_eq!(monitor.check(), ConnectionHealth::Healthy);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconnect_migrates_older_backup() -> Result<()> {
+    let dir = ScratchDir::new("reconnect-unmigrated");
+    let live = dir.path().join("lectara.db");
+    drop(database_with(&live, "https://example.com/before").await?);
+    let (repo, mut monitor) = monitor_for(&dir)?;
+
+    // A backup taken before any migrations ran
+    let restored = dir.path().join("restored.db");
+    drop(SqliteConnection::establish(
+        &restored.display().to_string(),
+    )?);
+    std::fs::rename(&restored, &live)?;
+
+    assert_eq!(monitor.check(), ConnectionHealth::Reconnected);
+    let created = repo.create(&item("https://example.com/after")?).await?;
+    assert_eq!(created.url, "https://example.com/after");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keeps_connection_while_file_is_missing() -> Result<()> {
+    let dir = ScratchDir::new("reconnect-missing");
+    let live = dir.path().join("lectara.db");
+    drop(database_with(&live, "https://example.com/before").await?);
+    let (repo, mut monitor) = monitor_for(&dir)?;
+
+    std::fs::remove_file(&live)?;
+    assert!(matches!(monitor.check(), ConnectionHealth::Unavailable(_)));
+    assert!(!live.exists(), "reconnecting created an empty database");
+    assert!(
+        repo.find_by_url("https://example.com/before")
+            .await?
+            .is_some()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_checks_survive_a_panicked_write() -> Result<()> {
+    let dir = ScratchDir::new("reconnect-poisoned");
+    drop(database_with(&dir.path().join("lectara.db"), "https://example.com/before").await?);
+    let (db, mut monitor) = connection_for(&dir)?;
+
+    let holder = db.clone();
+    let panicked = std::thread::spawn(move || {
+        let _connection = holder.lock().unwrap();
+        panic!("write panicked");
+    })
+    .join();
+    assert!(panicked.is_err() && db.is_poisoned());
+
+    assert_eq!(monitor.check(), ConnectionHealth::Healthy);
+
+    Ok(())
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use lectara_service::activity::ActivityFeed;
+use lectara_service::auth::{AccessPolicy, hash_api_key};
+use lectara_service::routes::{create_instance_router, create_tenant_router};
+use lectara_service::tenants::{TenantRegistry, create_multi_tenant_router};
+use lectara_service::{AppState, DefaultAppState};
+use serde_json::{Value, json};
+
+use crate::common::establish_test_connection;
+
+fn header(name: &'static str, value: &str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static(name),
+        HeaderValue::from_str(value).unwrap(),
+    )
+}
+
+fn bearer(key: &str) -> (HeaderName, HeaderValue) {
+    header("authorization", &format!("Bearer {key}"))
+}
+
+/// Alice is reached through her host, Bob through his with his API key;
+/// each gets their own in-memory database. `instance_keys` guard the admin
+/// API.
+fn create_multi_tenant_server(instance_keys: &[&str]) -> TestServer {
+    let registry = TenantRegistry::parse(&format!(
+        r#"
+        [tenants.alice]
+        database = "alice.db"
+        hosts = ["alice.lectara.test"]
+
+        [tenants.bob]
+        database = "bob.db"
+        hosts = ["bob.lectara.test"]
+        api_key_hashes = ["{}"]
+        "#,
+        hash_api_key("bob-key")
+    ))
+    .unwrap();
+
+    let state = || DefaultAppState::new(Arc::new(Mutex::new(establish_test_connection())));
+    let routers = registry
+        .tenants()
+        .iter()
+        .map(|tenant| {
+            (
+                tenant.name.clone(),
+                tenant
+                    .access_policy()
+                    .protect(create_tenant_router())
+                    .with_state(state()),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let access = AccessPolicy::new(instance_keys.iter().map(|key| hash_api_key(key)), false);
+    let instance = create_instance_router(access).with_state(state());
+
+    TestServer::new(create_multi_tenant_router(registry, routers, instance)).unwrap()
+}
+
+#[tokio::test]
+async fn test_tenants_see_only_their_own_items() -> Result<()> {
+    let server = create_multi_tenant_server(&["admin-key"]);
+    let (host, alice) = header("host", "alice.lectara.test");
+    let (authorization, bob) = bearer("bob-key");
+
+    server
+        .post("/api/v1/content")
+        .add_header(host.clone(), alice.clone())
+        .json(&json!({ "url": "https://example.com/alice" }))
+        .await
+        .assert_status_ok();
+    server
+        .post("/api/v1/content")
+        .add_header(authorization.clone(), bob.clone())
+        .json(&json!({ "url": "https://example.com/bob" }))
+        .await
+        .assert_status_ok();
+
+    let alice_items: Value = server
+        .get("/api/v1/content")
+        .add_header(host, alice)
+        .await
+        .json();
+    assert_eq!(alice_items["items"].as_array().unwrap().len(), 1);
+    assert_eq!(alice_items["items"][0]["url"], "https://example.com/alice");
+
+    let bob_items: Value = server
+        .get("/api/v1/content")
+        .add_header(authorization, bob)
+        .await
+        .json();
+    assert_eq!(bob_items["items"].as_array().unwrap().len(), 1);
+    assert_eq!(bob_items["items"][0]["url"], "https://example.com/bob");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_unknown_and_mismatched_keys() -> Result<()> {
+    let server = create_multi_tenant_server(&["admin-key"]);
+
+    let (authorization, guess) = bearer("guess");
+    let (host, bob_host) = header("host", "bob.lectara.test");
+    let response = server
+        .get("/api/v1/content")
+        .add_header(authorization, guess)
+        .add_header(host.clone(), bob_host.clone())
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header("www-authenticate"), "Bearer");
+
+    // Bob's host alone doesn't grant access to his items
+    server
+        .get("/api/v1/content")
+        .add_header(host, bob_host)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let (authorization, bob) = bearer("bob-key");
+    let (host, alice) = header("host", "alice.lectara.test");
+    server
+        .get("/api/v1/content")
+        .add_header(authorization, bob)
+        .add_header(host, alice)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unmatched_requests_reach_only_instance_routes() -> Result<()> {
+    let server = create_multi_tenant_server(&["admin-key"]);
+    let (authorization, admin) = bearer("admin-key");
+
+    server
+        .get("/api/v1/content")
+        .add_header(authorization.clone(), admin.clone())
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server.get("/health").await.assert_status_ok();
+    server
+        .get("/api/v1/admin/read-only")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .get("/api/v1/admin/read-only")
+        .add_header(authorization, admin)
+        .await
+        .assert_status_ok();
+
+    // Tenants can't reach the instance-wide admin API
+    let (host, alice) = header("host", "alice.lectara.test");
+    server
+        .get("/api/v1/admin/read-only")
+        .add_header(host, alice)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    let (authorization, bob) = bearer("bob-key");
+    server
+        .get("/api/v1/admin/read-only")
+        .add_header(authorization, bob)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_admin_api_is_off_without_instance_keys() -> Result<()> {
+    let server = create_multi_tenant_server(&[]);
+
+    // Tenant keys don't guard it, so anonymous requests would reach it
+    server
+        .get("/api/v1/admin/read-only")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .put("/api/v1/admin/read-only")
+        .json(&json!({ "reason": "Restoring a backup" }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    let (authorization, bob) = bearer("bob-key");
+    server
+        .get("/api/v1/admin/read-only")
+        .add_header(authorization, bob)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server.get("/health").await.assert_status_ok();
+
+    Ok(())
+}
+
+/// Alice and Bob share their activity, Carol doesn't; each is reached
+/// through their host
+fn create_activity_server() -> TestServer {
+    let registry = TenantRegistry::parse(
+        r#"
+        [tenants.alice]
+        database = "alice.db"
+        hosts = ["alice.lectara.test"]
+        share_activity = true
+
+        [tenants.bob]
+        database = "bob.db"
+        hosts = ["bob.lectara.test"]
+        share_activity = true
+
+        [tenants.carol]
+        database = "carol.db"
+        hosts = ["carol.lectara.test"]
+        "#,
+    )
+    .unwrap();
+
+    let states: Vec<_> = registry
+        .tenants()
+        .iter()
+        .map(|tenant| {
+            let db = Arc::new(Mutex::new(establish_test_connection()));
+            (tenant, DefaultAppState::new(db))
+        })
+        .collect();
+    let activity = ActivityFeed::new(
+        states
+            .iter()
+            .filter(|(tenant, _)| tenant.share_activity)
+            .map(|(tenant, state)| (tenant.name.clone(), state.content_repo()))
+            .collect(),
+    );
+    let routers = states
+        .into_iter()
+        .map(|(tenant, state)| {
+            let state = state.with_activity(
+                tenant
+                    .share_activity
+                    .then(|| activity.viewed_by(&tenant.name)),
+            );
+            (
+                tenant.name.clone(),
+                create_tenant_router().with_state(state),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let instance = create_instance_router(AccessPolicy::default()).with_state(
+        DefaultAppState::new(Arc::new(Mutex::new(establish_test_connection()))),
+    );
+
+    TestServer::new(create_multi_tenant_router(registry, routers, instance)).unwrap()
+}
+
+#[tokio::test]
+async fn test_activity_feed_shows_other_sharing_tenants() -> Result<()> {
+    let server = create_activity_server();
+    let (host, alice) = header("host", "alice.lectara.test");
+    let (_, bob) = header("host", "bob.lectara.test");
+    let (_, carol) = header("host", "carol.lectara.test");
+
+    for (tenant, url) in [
+        (&alice, "https://example.com/alice"),
+        (&bob, "https://example.com/bob"),
+        (&carol, "https://example.com/carol"),
+    ] {
+        server
+            .post("/api/v1/content")
+            .add_header(host.clone(), tenant.clone())
+            .json(&json!({ "url": url, "title": "Shared?" }))
+            .await
+            .assert_status_ok();
+    }
+
+    let response = server
+        .get("/api/v1/activity")
+        .add_header(host.clone(), alice)
+        .await;
+    response.assert_status_ok();
+    let feed: Value = response.json();
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["tenant"], "bob");
+    assert_eq!(items[0]["url"], "https://example.com/bob");
+    assert_eq!(items[0]["title"], "Shared?");
+
+    let feed: Value = server
+        .get("/api/v1/activity")
+        .add_header(host.clone(), bob.clone())
+        .await
+        .json();
+    assert_eq!(feed["items"][0]["tenant"], "alice");
+
+    server
+        .get("/api/v1/activity")
+        .add_header(host.clone(), carol)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/api/v1/activity")
+        .add_header(host, bob)
+        .add_query_param("limit", 0)
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
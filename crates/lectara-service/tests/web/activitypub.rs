@@ -0,0 +1,341 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use axum_test::TestServer;
+use chrono::Utc;
+use lectara_service::activitypub::{ActivityPub, signatures};
+use lectara_service::validation::ValidationContext;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::{create_test_server, create_test_server_with_state};
+
+const BASE_URL: &str = "https://reading.example.com";
+const INBOX_PATH: &str = "/web/ap/inbox";
+
+/// A fediverse account on a local port, collecting what is delivered to it
+struct RemoteActor {
+    id: String,
+    key: PKey<Private>,
+    delivered: Arc<Mutex<Vec<Value>>>,
+}
+
+impl RemoteActor {
+    async fn spawn() -> Self {
+        Self::spawn_claiming(None).await
+    }
+
+    /// An account whose actor document says it is `claimed` instead, still
+    /// with its own key
+    async fn spawn_claiming(claimed: Option<&str>) -> Self {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let id = format!("{base}/users/ada");
+        let document_id = claimed.unwrap_or(&id);
+        let document = json!({
+            "id": document_id,
+            "type": "Person",
+            "inbox": format!("{id}/inbox"),
+            "endpoints": { "sharedInbox": format!("{base}/inbox") },
+            "publicKey": {
+                "id": format!("{id}#main-key"),
+                "owner": document_id,
+                "publicKeyPem": String::from_utf8(key.public_key_to_pem().unwrap()).unwrap(),
+            },
+        });
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new()
+            .route("/users/ada", get(move || async move { Json(document) }))
+            .route(
+                "/inbox",
+                post(
+                    |State(delivered): State<Arc<Mutex<Vec<Value>>>>,
+                     Json(activity): Json<Value>| async move {
+                        delivered.lock().unwrap().push(activity);
+                        StatusCode::ACCEPTED
+                    },
+                ),
+            )
+            .with_state(delivered.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Self { id, key, delivered }
+    }
+
+    /// Posts an activity to the instance's inbox, signed with the actor's key
+    async fn post(&self, server: &TestServer, activity: &Value) -> axum_test::TestResponse {
+        let body = serde_json::to_vec(activity).unwrap();
+        let date = signatures::http_date(Utc::now());
+        let digest = signatures::digest(&body);
+        let names = ["(request-target)", "host", "date", "digest"];
+        let owned = names.map(str::to_string);
+        let signing_string =
+            signatures::signing_string("POST", INBOX_PATH, &owned, |name| match name {
+                "host" => Some("reading.example.com"),
+                "date" => Some(date.as_str()),
+                "digest" => Some(digest.as_str()),
+                _ => None,
+            })
+            .unwrap();
+        let key_id = format!("{}#main-key", self.id);
+        let signature = signatures::sign(&self.key, &key_id, &names, &signing_string).unwrap();
+
+        server
+            .post(INBOX_PATH)
+            .add_header("host", "reading.example.com")
+            .add_header("date", date)
+            .add_header("digest", digest)
+            .add_header("signature", signature)
+            .add_header("content-type", "application/activity+json")
+            .bytes(body.into())
+            .await
+    }
+
+    /// Waits for an activity of `kind` to be delivered
+    async fn delivered(&self, kind: &str) -> Value {
+        for _ in 0..50 {
+            let found = self
+                .delivered
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|activity| activity["type"] == kind)
+                .cloned();
+            if let Some(activity) = found {
+                return activity;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("No {kind} was delivered");
+    }
+}
+
+fn create_server(view: Option<&str>) -> TestServer {
+    let key = Rsa::generate(2048).unwrap().private_key_to_pem().unwrap();
+    let activitypub = ActivityPub::new(BASE_URL, "reading", &key, view).unwrap();
+    let validation = ValidationContext {
+        allow_local_urls: true,
+        ..ValidationContext::default()
+    };
+    let (server, _db) = create_test_server_with_state(|state| {
+        state
+            .with_validation(validation)
+            .with_activitypub(Some(activitypub))
+    });
+    server
+}
+
+async fn save(server: &TestServer, url: &str, source: &str) {
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url, "title": "A Title", "source": source }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_webfinger_and_actor() -> Result<()> {
+    let server = create_server(None);
+
+    let response = server
+        .get("/web/.well-known/webfinger")
+        .add_query_param("resource", "acct:reading@reading.example.com")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/jrd+json");
+    let document: Value = serde_json::from_str(&response.text())?;
+    assert_eq!(
+        document["links"][0]["href"],
+        "https://reading.example.com/web/ap/actor"
+    );
+
+    server
+        .get("/web/.well-known/webfinger")
+        .add_query_param("resource", "acct:someone@reading.example.com")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let response = server.get("/web/ap/actor").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/activity+json");
+    let actor: Value = serde_json::from_str(&response.text())?;
+    assert_eq!(actor["preferredUsername"], "reading");
+    assert_eq!(actor["inbox"], "https://reading.example.com/web/ap/inbox");
+    assert!(
+        actor["publicKey"]["publicKeyPem"]
+            .as_str()
+            .unwrap()
+            .starts_with("-----BEGIN PUBLIC KEY-----")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_outbox_and_notes_follow_the_view() -> Result<()> {
+    let server = create_server(Some("public"));
+    server
+        .post("/api/v1/views")
+        .json(&json!({ "name": "public", "source": "cli" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    save(&server, "https://example.com/public", "cli").await;
+    save(&server, "https://example.com/private", "api").await;
+
+    let outbox: Value = serde_json::from_str(&server.get("/web/ap/outbox").await.text())?;
+    assert_eq!(outbox["totalItems"], 1);
+    let create = &outbox["orderedItems"][0];
+    assert_eq!(create["type"], "Create");
+    assert_eq!(create["object"]["type"], "Note");
+    assert_eq!(create["object"]["url"], "https://example.com/public");
+    assert!(
+        create["object"]["content"]
+            .as_str()
+            .unwrap()
+            .contains("<a href=\"https://example.com/public\">A Title</a>")
+    );
+
+    let note_id = create["object"]["id"].as_str().unwrap();
+    let path = note_id.strip_prefix(BASE_URL).unwrap();
+    server.get(path).await.assert_status_ok();
+
+    let private_id = path.rsplit('/').next().unwrap().parse::<i32>()? + 1;
+    server
+        .get(&format!("/web/ap/notes/{private_id}"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_followers_get_new_saves() -> Result<()> {
+    let server = create_server(None);
+    let remote = RemoteActor::spawn().await;
+    let follow = json!({
+        "@context": "https://www.w3.org/ns/activitystreams",
+        "id": format!("{}#follows/1", remote.id),
+        "type": "Follow",
+        "actor": remote.id,
+        "object": "https://reading.example.com/web/ap/actor",
+    });
+
+    remote
+        .post(&server, &follow)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    let accept = remote.delivered("Accept").await;
+    assert_eq!(accept["object"]["id"], follow["id"]);
+
+    let followers: Value = serde_json::from_str(&server.get("/web/ap/followers").await.text())?;
+    assert_eq!(followers["totalItems"], 1);
+
+    save(&server, "https://example.com/new", "cli").await;
+    let create = remote.delivered("Create").await;
+    assert_eq!(create["object"]["url"], "https://example.com/new");
+
+    let undo = json!({
+        "type": "Undo",
+        "actor": remote.id,
+        "object": follow,
+    });
+    remote
+        .post(&server, &undo)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    let followers: Value = serde_json::from_str(&server.get("/web/ap/followers").await.text())?;
+    assert_eq!(followers["totalItems"], 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inbox_rejects_unsigned_and_forged_activities() -> Result<()> {
+    let server = create_server(None);
+    let remote = RemoteActor::spawn().await;
+
+    server
+        .post(INBOX_PATH)
+        .json(&json!({ "type": "Follow", "actor": remote.id }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // Signed by the remote actor but claiming to be someone else
+    remote
+        .post(
+            &server,
+            &json!({
+                "type": "Follow",
+                "actor": "https://social.example/users/eve",
+                "object": "https://reading.example.com/web/ap/actor",
+            }),
+        )
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let followers: Value = serde_json::from_str(&server.get("/web/ap/followers").await.text())?;
+    assert_eq!(followers["totalItems"], 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inbox_rejects_actors_served_from_another_url() -> Result<()> {
+    let server = create_server(None);
+    let remote = RemoteActor::spawn().await;
+    let follow = json!({
+        "id": format!("{}#follows/1", remote.id),
+        "type": "Follow",
+        "actor": remote.id,
+        "object": "https://reading.example.com/web/ap/actor",
+    });
+    remote
+        .post(&server, &follow)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+
+    // Another server passing off the follower's id with its own key
+    let impostor = RemoteActor::spawn_claiming(Some(&remote.id)).await;
+    impostor
+        .post(
+            &server,
+            &json!({ "type": "Undo", "actor": remote.id, "object": follow }),
+        )
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    impostor
+        .post(
+            &server,
+            &json!({
+                "type": "Follow",
+                "actor": remote.id,
+                "object": "https://reading.example.com/web/ap/actor",
+            }),
+        )
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let followers: Value = serde_json::from_str(&server.get("/web/ap/followers").await.text())?;
+    assert_eq!(followers["totalItems"], 1);
+    save(&server, "https://example.com/new", "cli").await;
+    remote.delivered("Create").await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_endpoints_are_missing_without_an_actor() {
+    let (server, _db) = create_test_server();
+    server
+        .get("/web/ap/actor")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
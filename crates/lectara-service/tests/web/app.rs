@@ -0,0 +1,92 @@
+use crate::common::{server_utils::create_test_server, test_utils};
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_manifest_declares_share_target() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/manifest.webmanifest").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/manifest+json");
+    let manifest: Value = response.json();
+    assert_eq!(manifest["start_url"], "/web");
+    assert_eq!(manifest["share_target"]["action"], "/web/save");
+    assert_eq!(manifest["share_target"]["method"], "POST");
+
+    let html = server.get("/web").await.text();
+    assert!(html.contains("<link rel=\"manifest\" href=\"/web/manifest.webmanifest\">"));
+    assert!(html.contains("<script src=\"/web/assets/app."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_worker_precaches_assets() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/sw.js").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-cache");
+    let script = response.text();
+    assert!(script.contains("\"/web/assets/lectara."));
+    assert!(!script.contains("__ASSETS__") && !script.contains("__VERSION__"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shared_links_are_saved() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let response = server
+        .post("/web/save")
+        .form(&[
+            ("title", "Shared article"),
+            ("text", "Have a look https://example.com/shared"),
+        ])
+        .await;
+    response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(response.header("location"), "/web");
+
+    let item = {
+        let mut conn = db.lock().unwrap();
+        test_utils::get_content_item_by_url(&mut conn, "https://example.com/shared").unwrap()
+    };
+    assert_eq!(item.title.as_deref(), Some("Shared article"));
+    assert_eq!(item.source.as_deref(), Some("web"));
+
+    // Sharing it again under another title keeps the saved item
+    server
+        .post("/web/save")
+        .form(&[("url", "https://example.com/shared"), ("title", "Other")])
+        .await
+        .assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sharing_without_valid_link_shows_error() -> Result<()> {
+    let (server, db) = create_test_server();
+
+    let response = server
+        .post("/web/save")
+        .form(&[("text", "no link in here")])
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("No link was shared"));
+
+    let response = server
+        .post("/web/save")
+        .form(&[("url", "ftp://example.com/file")])
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("class=\"error\""));
+
+    assert_eq!(test_utils::count_content_items(&mut db.lock().unwrap()), 0);
+
+    Ok(())
+}
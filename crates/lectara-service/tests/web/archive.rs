@@ -0,0 +1,139 @@
+use anyhow::Result;
+use axum::{Router, http::header, routing::get};
+use axum_test::TestServer;
+use lectara_service::validation::ValidationContext;
+use serde_json::{Value, json};
+
+use crate::common::server_utils::create_test_server_with_validation;
+
+const PAGE: &str = r#"<html><head><link rel="stylesheet" href="/site.css"></head>
+<body><img src="/logo.gif" alt="Logo"><img src="https://192.0.2.1/tracker.gif"></body></html>"#;
+
+/// Serves `PAGE` at `/page` with the stylesheet and image it references,
+/// and plain text at `/notes.txt`
+async fn spawn_site() -> String {
+    let app = Router::new()
+        .route(
+            "/page",
+            get(|| async { ([(header::CONTENT_TYPE, "text/html")], PAGE) }),
+        )
+        .route(
+            "/site.css",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "text/css")],
+                    "body { background: url(bg.gif) }",
+                )
+            }),
+        )
+        .route(
+            "/logo.gif",
+            get(|| async { ([(header::CONTENT_TYPE, "image/gif")], "GIF") }),
+        )
+        .route(
+            "/bg.gif",
+            get(|| async { ([(header::CONTENT_TYPE, "image/gif")], "BG") }),
+        )
+        .route(
+            "/notes.txt",
+            get(|| async { ([(header::CONTENT_TYPE, "text/plain")], "Notes") }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn create_server() -> TestServer {
+    let (server, _db) = create_test_server_with_validation(ValidationContext {
+        allow_local_urls: true,
+        denied_hosts: vec!["192.0.2.1".to_string()],
+        ..ValidationContext::default()
+    });
+    server
+}
+
+async fn add_content(server: &TestServer, url: &str) -> i64 {
+    let response = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": url }))
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_single_file_archive_inlines_resources() -> Result<()> {
+    let site = spawn_site().await;
+    let server = create_server();
+    let id = add_content(&server, &format!("{site}/page")).await;
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/snapshots?mode=single-file"))
+        .await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let snapshot: Value = response.json();
+    assert_eq!(snapshot["mode"], "single-file");
+    let view_url = snapshot["view_url"].as_str().unwrap();
+    assert_eq!(view_url, format!("/web/archive/{}", snapshot["id"]));
+
+    let response = server.get(view_url).await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        "text/html; charset=utf-8"
+    );
+    assert!(
+        response
+            .header(header::CONTENT_SECURITY_POLICY)
+            .to_str()?
+            .starts_with("sandbox allow-scripts")
+    );
+
+    let html = response.text();
+    assert!(
+        html.contains("<style>body { background: url(\"data:image/gif;base64,Qkc=\") }</style>")
+    );
+    assert!(html.contains("<img src=\"data:image/gif;base64,R0lG\" alt=\"Logo\">"));
+    // Denied hosts are never fetched
+    assert!(html.contains("<img src=\"https://192.0.2.1/tracker.gif\">"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_only_single_file_html_snapshots_are_viewable() -> Result<()> {
+    let site = spawn_site().await;
+    let server = create_server();
+    let page = add_content(&server, &format!("{site}/page")).await;
+    let notes = add_content(&server, &format!("{site}/notes.txt")).await;
+
+    let snapshot: Value = server
+        .post(&format!("/api/v1/content/{page}/snapshots"))
+        .await
+        .json();
+    assert_eq!(snapshot["mode"], "warc");
+    assert!(snapshot.get("view_url").is_none());
+    server
+        .get(&format!("/web/archive/{}", snapshot["id"]))
+        .await
+        .assert_status_not_found();
+    server
+        .get("/web/archive/999")
+        .await
+        .assert_status_not_found();
+
+    server
+        .post(&format!(
+            "/api/v1/content/{notes}/snapshots?mode=single-file"
+        ))
+        .await
+        .assert_status_bad_request();
+    server
+        .post(&format!("/api/v1/content/{page}/snapshots?mode=monolith"))
+        .await
+        .assert_status_bad_request();
+
+    Ok(())
+}
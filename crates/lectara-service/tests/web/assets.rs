@@ -0,0 +1,60 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+
+/// The `href` of the stylesheet linked from `html`
+fn stylesheet_path(html: &str) -> &str {
+    let start = html.find("<link rel=\"stylesheet\" href=\"").unwrap() + 29;
+    let end = start + html[start..].find('"').unwrap();
+    &html[start..end]
+}
+
+#[tokio::test]
+async fn test_linked_assets_are_cached_forever() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let html = server.get("/web").await.text();
+    let path = stylesheet_path(&html);
+    assert!(path.starts_with("/web/assets/lectara.") && path.ends_with(".css"));
+
+    let response = server.get(path).await;
+    response.assert_status_ok();
+    assert!(
+        response
+            .header("content-type")
+            .to_str()?
+            .starts_with("text/css")
+    );
+    assert!(
+        response
+            .header("cache-control")
+            .to_str()?
+            .contains("immutable")
+    );
+    assert!(response.text().contains(".banner"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_plain_asset_names_revalidate() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/assets/palette.js").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-cache");
+    assert!(response.text().contains("/api/v1/search"));
+
+    let etag = response.header("etag");
+    let response = server
+        .get("/web/assets/palette.js")
+        .add_header("if-none-match", etag)
+        .await;
+    response.assert_status(StatusCode::NOT_MODIFIED);
+
+    server
+        .get("/web/assets/missing.js")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
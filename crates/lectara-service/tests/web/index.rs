@@ -0,0 +1,35 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_index_lists_pinned_items_first() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let pinned = server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/pinned", "title": "Pinned <essay>" }))
+        .await
+        .json::<Value>()["id"]
+        .as_i64()
+        .unwrap();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/recent", "title": "Recent", "author": "Ada" }))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/v1/content/{pinned}/pin"))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/web").await;
+    response.assert_status_ok();
+    let html = response.text();
+    let pinned_at = html.find("Pinned &lt;essay&gt;").unwrap();
+    let recent_at = html.find("Recent").unwrap();
+    assert!(pinned_at < recent_at);
+    assert!(html.contains("<span class=\"pinned\">Pinned</span>"));
+    assert!(html.contains("<span class=\"meta\">Ada</span>"));
+
+    Ok(())
+}
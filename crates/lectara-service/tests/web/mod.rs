@@ -0,0 +1,11 @@
+#[cfg(feature = "activitypub")]
+pub mod activitypub;
+pub mod app;
+pub mod archive;
+pub mod assets;
+pub mod index;
+pub mod opds;
+pub mod read;
+pub mod search;
+pub mod share;
+pub mod theme;
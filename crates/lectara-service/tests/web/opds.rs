@@ -0,0 +1,66 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_opds_catalog_lists_documents() -> Result<()> {
+    let (server, _db) = create_test_server();
+    for (url, title) in [
+        ("https://example.com/papers/engines.pdf", "Engines"),
+        ("https://example.com/books/novel.epub", "Novel"),
+        ("https://example.com/article", "Article"),
+    ] {
+        server
+            .post("/api/v1/content")
+            .json(&json!({ "url": url, "title": title }))
+            .await
+            .assert_status_ok();
+    }
+
+    let response = server.get("/web/opds").await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.header("content-type"),
+        "application/atom+xml;profile=opds-catalog;kind=acquisition"
+    );
+
+    let feed = response.text();
+    assert_eq!(feed.matches("<entry>").count(), 2);
+    assert!(feed.contains(
+        "<link rel=\"http://opds-spec.org/acquisition\" href=\"https://example.com/papers/engines.pdf\" type=\"application/pdf\"/>"
+    ));
+    assert!(feed.contains(
+        "<link rel=\"http://opds-spec.org/acquisition\" href=\"https://example.com/books/novel.epub\" type=\"application/epub+zip\"/>"
+    ));
+    assert!(!feed.contains("Article"));
+    assert!(!feed.contains("rel=\"next\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_opds_catalog_pages() -> Result<()> {
+    let (server, _db) = create_test_server();
+    for i in 0..51 {
+        server
+            .post("/api/v1/content")
+            .json(&json!({ "url": format!("https://example.com/papers/{i}.pdf") }))
+            .await
+            .assert_status_ok();
+    }
+
+    let first = server.get("/web/opds").await.text();
+    assert_eq!(first.matches("<entry>").count(), 50);
+    assert!(first.contains("<link rel=\"next\" href=\"/web/opds?page=2\""));
+
+    let second = server
+        .get("/web/opds")
+        .add_query_param("page", 2)
+        .await
+        .text();
+    assert_eq!(second.matches("<entry>").count(), 1);
+    assert!(!second.contains("rel=\"next\""));
+    assert!(second.contains("<link rel=\"previous\" href=\"/web/opds\""));
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::common::{server_utils::create_test_server, test_utils};
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::DateTime;
+use diesel::SqliteConnection;
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+/// Saves an item at `timestamp`, returning its id
+async fn save_at(
+    server: &TestServer,
+    db: &Arc<Mutex<SqliteConnection>>,
+    timestamp: &str,
+    payload: Value,
+) -> i64 {
+    let id = server
+        .post("/api/v1/content")
+        .json(&payload)
+        .await
+        .json::<Value>()["id"]
+        .as_i64()
+        .unwrap();
+    let created_at = DateTime::parse_from_rfc3339(timestamp).unwrap().naive_utc();
+    test_utils::update_content_item_timestamp(&mut db.lock().unwrap(), id as i32, created_at);
+    id
+}
+
+#[tokio::test]
+async fn test_reader_renders_body_with_navigation() -> Result<()> {
+    let (server, db) = create_test_server();
+    let oldest = save_at(
+        &server,
+        &db,
+        "2024-01-01T10:00:00Z",
+        json!({ "url": "https://example.com/oldest", "title": "Oldest", "body": "Old text" }),
+    )
+    .await;
+    let middle = save_at(
+        &server,
+        &db,
+        "2024-01-02T10:00:00Z",
+        json!({
+            "url": "https://example.com/middle",
+            "title": "Middle <part>",
+            "author": "Ada",
+            "body": "<p>First paragraph.</p><p>Second <b>one</b>.</p><script>alert(1)</script>"
+        }),
+    )
+    .await;
+    let newest = save_at(
+        &server,
+        &db,
+        "2024-01-03T10:00:00Z",
+        json!({ "url": "https://example.com/newest", "title": "Newest" }),
+    )
+    .await;
+
+    let response = server.get(&format!("/web/read/{middle}")).await;
+    response.assert_status_ok();
+    let html = response.text();
+    assert!(html.contains("<h1>Middle &lt;part&gt;</h1>"));
+    assert!(html.contains("Ada &middot; "));
+    assert!(html.contains("1 min read"));
+    assert!(html.contains("<p>First paragraph.</p>"));
+    assert!(html.contains("<p>Second one.</p>"));
+    assert!(!html.contains("alert(1)"));
+    assert!(html.contains(&format!(
+        "<a rel=\"prev\" href=\"/web/read/{newest}\">Newer: Newest</a>"
+    )));
+    assert!(html.contains(&format!(
+        "<a rel=\"next\" href=\"/web/read/{oldest}\">Older: Oldest</a>"
+    )));
+
+    // Items without a body say so, and the ends of the list link one way
+    let html = server.get(&format!("/web/read/{newest}")).await.text();
+    assert!(html.contains("No text was saved for this item."));
+    assert!(!html.contains("rel=\"prev\""));
+    assert!(html.contains(&format!("href=\"/web/read/{middle}\"")));
+
+    // The index links items with a body to the reader
+    let html = server.get("/web").await.text();
+    assert!(html.contains(&format!("href=\"/web/read/{middle}\">Read</a>")));
+    assert!(!html.contains(&format!("href=\"/web/read/{newest}\">Read</a>")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_navigates_within_filter() -> Result<()> {
+    let (server, db) = create_test_server();
+    let oldest = save_at(
+        &server,
+        &db,
+        "2024-01-01T10:00:00Z",
+        json!({ "url": "https://example.com/a", "title": "A", "source": "feed" }),
+    )
+    .await;
+    save_at(
+        &server,
+        &db,
+        "2024-01-02T10:00:00Z",
+        json!({ "url": "https://example.com/b", "title": "B", "source": "email" }),
+    )
+    .await;
+    let newest = save_at(
+        &server,
+        &db,
+        "2024-01-03T10:00:00Z",
+        json!({ "url": "https://example.com/c", "title": "C", "source": "feed" }),
+    )
+    .await;
+
+    let html = server
+        .get(&format!("/web/read/{newest}?source=feed"))
+        .await
+        .text();
+    assert!(html.contains(&format!(
+        "<a rel=\"next\" href=\"/web/read/{oldest}?source=feed\">Older: A</a>"
+    )));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_missing_item() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/read/999").await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert!(response.text().contains("Item not found"));
+
+    Ok(())
+}
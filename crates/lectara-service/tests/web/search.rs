@@ -0,0 +1,65 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_search_page_lists_matches() -> Result<()> {
+    let (server, _db) = create_test_server();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/ferris", "title": "Ferris <the crab>" }))
+        .await
+        .assert_status_ok();
+    server
+        .post("/api/v1/content")
+        .json(&json!({ "url": "https://example.com/gopher", "title": "Gopher" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/web/search?q=ferris").await;
+    response.assert_status_ok();
+    let html = response.text();
+    assert!(html.contains("Ferris &lt;the crab&gt;"));
+    assert!(!html.contains("Gopher"));
+    assert!(html.contains("value=\"ferris\""));
+
+    let html = server.get("/web/search?q=unicorn").await.text();
+    assert!(html.contains("No matches."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_page_without_query_shows_form() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/search").await;
+    response.assert_status_ok();
+    let html = response.text();
+    assert!(html.contains("<form action=\"/web/search\""));
+    assert!(!html.contains("No matches."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_page_rejects_malformed_query() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/search?q=%22unterminated").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("class=\"error\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pages_include_search_palette() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let html = server.get("/web").await.text();
+    assert!(html.contains("<script src=\"/web/assets/palette."));
+
+    Ok(())
+}
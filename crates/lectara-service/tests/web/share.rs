@@ -0,0 +1,290 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+async fn create_share_link(server: &axum_test::TestServer, payload: Value) -> String {
+    create_share_link_with(server, payload, json!({})).await
+}
+
+async fn create_share_link_with(
+    server: &axum_test::TestServer,
+    payload: Value,
+    share: Value,
+) -> String {
+    let response = server.post("/api/v1/content").json(&payload).await;
+    response.assert_status_ok();
+    let id = response.json::<Value>()["id"].as_u64().unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/content/{id}/share"))
+        .json(&share)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_view_share_link_renders_item() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let token = create_share_link(
+        &server,
+        json!({
+            "url": "https://example.com/shared",
+            "title": "Shared <Article>",
+            "author": "Test Author",
+            "body": "Body with <script>alert('xss')</script>"
+        }),
+    )
+    .await;
+
+    let response = server.get(&format!("/web/share/{token}")).await;
+    response.assert_status_ok();
+    assert!(
+        response
+            .header("content-type")
+            .to_str()?
+            .starts_with("text/html")
+    );
+
+    let html = response.text();
+    assert!(html.contains("Shared &lt;Article&gt;"));
+    assert!(html.contains("Test Author"));
+    assert!(html.contains("https://example.com/shared"));
+    assert!(html.contains("&lt;script&gt;"));
+    assert!(!html.contains("<script>"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_unknown_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.get("/web/share/does-not-exist").await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_revoked_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let token = create_share_link(&server, json!({ "url": "https://example.com/shared" })).await;
+
+    let response = server.get(&format!("/web/share/{token}")).await;
+    response.assert_status_ok();
+
+    server
+        .delete(&format!("/api/v1/content/1/share/{token}"))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let response = server.get(&format!("/web/share/{token}")).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_expired_share_link() -> Result<()> {
+    let (server, db) = create_test_server();
+    let token = create_share_link(&server, json!({ "url": "https://example.com/shared" })).await;
+
+    {
+        use diesel::prelude::*;
+        let mut conn = db.lock().unwrap();
+        diesel::sql_query("UPDATE share_links SET expires_at = '2000-01-01 00:00:00'")
+            .execute(&mut *conn)
+            .expect("Failed to expire share link");
+    }
+
+    let response = server.get(&format!("/web/share/{token}")).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_protected_share_link_needs_passphrase() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let token = create_share_link_with(
+        &server,
+        json!({ "url": "https://example.com/secret", "title": "Secret Article" }),
+        json!({ "passphrase": "open sesame" }),
+    )
+    .await;
+    let path = format!("/web/share/{token}");
+
+    let response = server.get(&path).await;
+    response.assert_status_ok();
+    let html = response.text();
+    assert!(html.contains(&format!("<form method=\"post\" action=\"{path}\">")));
+    assert!(!html.contains("Secret Article"));
+
+    let response = server
+        .post(&path)
+        .form(&[("passphrase", "open sesame!")])
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(response.text().contains("Incorrect passphrase."));
+    assert!(!response.text().contains("Secret Article"));
+
+    let response = server
+        .post(&path)
+        .form(&[("passphrase", "open sesame")])
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-store");
+    assert!(response.text().contains("Secret Article"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_protected_share_link_locks_after_failed_attempts() -> Result<()> {
+    let (server, db) = create_test_server();
+    let token = create_share_link_with(
+        &server,
+        json!({ "url": "https://example.com/secret" }),
+        json!({ "passphrase": "open sesame" }),
+    )
+    .await;
+    let path = format!("/web/share/{token}");
+
+    for _ in 0..4 {
+        server
+            .post(&path)
+            .form(&[("passphrase", "wrong guess")])
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+    let response = server
+        .post(&path)
+        .form(&[("passphrase", "wrong guess")])
+        .await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+    // Even the right passphrase is refused until the lockout ends
+    let response = server
+        .post(&path)
+        .form(&[("passphrase", "open sesame")])
+        .await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: i64 = response.header("retry-after").to_str()?.parse()?;
+    assert!(retry_after > 0 && retry_after <= 15 * 60);
+
+    {
+        use diesel::prelude::*;
+        let mut conn = db.lock().unwrap();
+        diesel::sql_query("UPDATE share_links SET last_failed_at = '2000-01-01 00:00:00'")
+            .execute(&mut *conn)
+            .expect("Failed to age failed attempts");
+    }
+
+    // An old failure starts a new count rather than adding to the lockout
+    server
+        .post(&path)
+        .form(&[("passphrase", "wrong guess")])
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post(&path)
+        .form(&[("passphrase", "open sesame")])
+        .await
+        .assert_status_ok();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_visitors_comment_on_share_link() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let token = create_share_link(
+        &server,
+        json!({ "url": "https://example.com/shared", "title": "Shared Article" }),
+    )
+    .await;
+    let path = format!("/web/share/{token}/comments");
+
+    let response = server
+        .post(&path)
+        .form(&[("author", "Ada"), ("body", "Worth <reading>")])
+        .await;
+    response.assert_status(StatusCode::SEE_OTHER);
+    let location = response.header("location").to_str()?.to_string();
+    assert!(location.starts_with(&format!("/web/share/{token}#comment-")));
+    let comment_id = location.rsplit('-').next().unwrap().to_string();
+
+    let response = server
+        .post(&path)
+        .form(&[
+            ("author", "Grace"),
+            ("body", "Agreed"),
+            ("parent_id", &comment_id),
+        ])
+        .await;
+    response.assert_status(StatusCode::SEE_OTHER);
+
+    let html = server.get(&format!("/web/share/{token}")).await.text();
+    assert!(html.contains("Worth &lt;reading&gt;"));
+    // The reply is nested in the list under the comment it answers
+    let comment = html.find(&format!("id=\"comment-{comment_id}\"")).unwrap();
+    let reply = html.find("Agreed").unwrap();
+    assert!(html[comment..reply].contains("<ul>"));
+
+    let response = server
+        .post(&path)
+        .form(&[("author", "Ada"), ("body", " ")])
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_comments_on_protected_share_link_need_passphrase() -> Result<()> {
+    let (server, _db) = create_test_server();
+    let token = create_share_link_with(
+        &server,
+        json!({ "url": "https://example.com/secret", "title": "Secret Article" }),
+        json!({ "passphrase": "open sesame" }),
+    )
+    .await;
+    let path = format!("/web/share/{token}/comments");
+
+    server
+        .post(&path)
+        .form(&[("author", "Ada"), ("body", "Worth reading")])
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post(&path)
+        .form(&[
+            ("author", "Ada"),
+            ("body", "Worth reading"),
+            ("passphrase", "wrong guess"),
+        ])
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post(&path)
+        .form(&[
+            ("author", "Ada"),
+            ("body", "Worth reading"),
+            ("passphrase", "open sesame"),
+        ])
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-store");
+    let html = response.text();
+    assert!(html.contains("Secret Article"));
+    assert!(html.contains("Worth reading"));
+    assert!(html.contains("name=\"passphrase\""));
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::common::server_utils::create_test_server;
+use anyhow::Result;
+use axum::http::StatusCode;
+
+#[tokio::test]
+async fn test_pages_follow_system_theme_by_default() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let html = server.get("/web").await.text();
+    assert!(html.contains("<html lang=\"en\" class=\"theme-system\">"));
+    assert!(html.contains("<option value=\"system\" selected>"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chosen_theme_is_remembered() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let response = server.post("/web/theme").form(&[("theme", "dark")]).await;
+    response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(response.header("location"), "/web");
+    let cookie = response.header("set-cookie");
+    assert!(cookie.to_str()?.starts_with("lectara_theme=dark;"));
+
+    let html = server
+        .get("/web/search")
+        .add_header("cookie", "other=1; lectara_theme=dark")
+        .await
+        .text();
+    assert!(html.contains("class=\"theme-dark\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_theme_links_its_stylesheet() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    let html = server
+        .get("/web")
+        .add_header("cookie", "lectara_theme=sepia")
+        .await
+        .text();
+    assert!(html.contains("class=\"theme-sepia\""));
+    assert!(html.contains("href=\"/web/assets/theme-sepia."));
+
+    let css = server.get("/web/assets/theme-sepia.css").await;
+    css.assert_status_ok();
+    assert!(css.text().contains(":root.theme-sepia"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_themes_are_rejected_or_ignored() -> Result<()> {
+    let (server, _db) = create_test_server();
+
+    server
+        .post("/web/theme")
+        .form(&[("theme", "neon")])
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let html = server
+        .get("/web")
+        .add_header("cookie", "lectara_theme=neon")
+        .await
+        .text();
+    assert!(html.contains("class=\"theme-system\""));
+
+    Ok(())
+}
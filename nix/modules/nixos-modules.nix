@@ -17,8 +17,7 @@
           };
 
           port = lib.mkOption {
-            # TODO: come back here when we make ports configurable
-            description = "Port for the lectara service to listen on. WARNING: currently doesn't do anything";
+            description = "Port for the lectara service to listen on";
             default = 3000;
             type = lib.types.port;
           };
@@ -80,11 +79,17 @@
 
           environment = {
             DATABASE_URL = "sqlite://${cfg.baseDir}/data/lectara.db";
+            LECTARA_BIND = "0.0.0.0:${toString cfg.port}";
             RUST_LOG = "info";
           };
 
           serviceConfig = {
+            Type = "notify";
             ExecStart = "${cfg.package}/bin/lectara-service";
+            # `systemctl reload lectara` re-reads the fetch credentials
+            ExecReload = "${pkgs.coreutils}/bin/kill -HUP $MAINPID";
+            # Restarts the service when it stops answering
+            WatchdogSec = 30;
             Restart = "on-failure";
             RestartSec = 5;
 
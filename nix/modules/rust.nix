@@ -17,6 +17,7 @@
         fileset = lib.fileset.unions [
           (craneLib.fileset.commonCargoSources projectRoot)
           (projectRoot + /crates/lectara-service/migrations)
+          (projectRoot + /crates/lectara-service/src/routes/web/assets)
         ];
       };
 
@@ -54,6 +55,8 @@
         // {
           pname = "lectara-service";
           cargoExtraArgs = "--package lectara-service";
+          # The source has no .git, so build.rs can't ask git for the commit
+          LECTARA_GIT_SHA = inputs.self.rev or inputs.self.dirtyRev or "unknown";
           src = lib.fileset.toSource {
             root = projectRoot;
             fileset = lib.fileset.unions [
@@ -61,6 +64,7 @@
               (projectRoot + /Cargo.lock)
               (craneLib.fileset.commonCargoSources (projectRoot + /crates/lectara-service))
               (projectRoot + /crates/lectara-service/migrations)
+              (projectRoot + /crates/lectara-service/src/routes/web/assets)
             ];
           };
         }
@@ -82,6 +86,21 @@
           passthru.exePath = "/bin/lectara";
         }
       );
+
+      # The CLI as the release assets `lectara self-update` downloads: the
+      # binary named for its platform and its SHA-256 checksum
+      lectara-cli-release =
+        let
+          platform = pkgs.stdenv.hostPlatform;
+          os = if platform.isDarwin then "macos" else "linux";
+          asset = "lectara-${platform.parsed.cpu.name}-${os}";
+        in
+        pkgs.runCommand "lectara-cli-release" { } ''
+          mkdir -p $out
+          cp ${lectara-cli}/bin/lectara $out/${asset}
+          cd $out
+          sha256sum ${asset} > ${asset}.sha256
+        '';
     in
     {
       checks = {
@@ -108,7 +127,7 @@
       };
 
       packages = {
-        inherit lectara-service lectara-cli;
+        inherit lectara-service lectara-cli lectara-cli-release;
       };
 
       apps = {